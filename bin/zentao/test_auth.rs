@@ -11,6 +11,11 @@ fn convert_auth_config(config_auth: &mcp_any_rest::config::config::AuthConfig) -
     let mode = match config_auth.mode {
         mcp_any_rest::config::config::AuthMode::Direct => StrategyAuthMode::Direct,
         mcp_any_rest::config::config::AuthMode::Login => StrategyAuthMode::Login,
+        mcp_any_rest::config::config::AuthMode::Jwt => StrategyAuthMode::Jwt,
+        mcp_any_rest::config::config::AuthMode::AwsSigV4 => StrategyAuthMode::AwsSigV4,
+        mcp_any_rest::config::config::AuthMode::Hmac => StrategyAuthMode::Hmac,
+        mcp_any_rest::config::config::AuthMode::Cookie => StrategyAuthMode::Cookie,
+        mcp_any_rest::config::config::AuthMode::Custom => StrategyAuthMode::Custom,
     };
     
     let direct_config = config_auth.direct_config.as_ref().map(|config| {
@@ -20,6 +25,9 @@ fn convert_auth_config(config_auth: &mcp_any_rest::config::config::AuthConfig) -
             mcp_any_rest::config::config::DirectAuthType::Basic => StrategyDirectAuthType::Basic,
             mcp_any_rest::config::config::DirectAuthType::Token => StrategyDirectAuthType::Token,
             mcp_any_rest::config::config::DirectAuthType::CustomHeaders => StrategyDirectAuthType::CustomHeaders,
+            mcp_any_rest::config::config::DirectAuthType::Digest => StrategyDirectAuthType::Digest,
+            mcp_any_rest::config::config::DirectAuthType::Ntlm => StrategyDirectAuthType::Ntlm,
+            mcp_any_rest::config::config::DirectAuthType::Negotiate => StrategyDirectAuthType::Negotiate,
         };
         
         StrategyDirectAuthConfig {
@@ -29,6 +37,7 @@ fn convert_auth_config(config_auth: &mcp_any_rest::config::config::AuthConfig) -
             username: config.username.clone(),
             password: config.password.clone(),
             custom_headers: config.custom_headers.clone(),
+            key_pool: config.key_pool.clone(),
         }
     });
     
@@ -114,6 +123,8 @@ fn convert_auth_config(config_auth: &mcp_any_rest::config::config::AuthConfig) -
             token_extraction,
             refresh_url: config.refresh_url.clone(),
             refresh_method,
+            oauth2: config.oauth2.clone(),
+            refresh_token: config.refresh_token.clone(),
         }
     });
     
@@ -121,9 +132,15 @@ fn convert_auth_config(config_auth: &mcp_any_rest::config::config::AuthConfig) -
         mode,
         direct_config,
         login_config,
+        jwt_config: config_auth.jwt_config.clone(),
+        aws_sigv4_config: config_auth.aws_sigv4_config.clone(),
+        hmac_config: config_auth.hmac_config.clone(),
+        cookie_config: config_auth.cookie_config.clone(),
+        custom_config: config_auth.custom_config.clone(),
         token_expiry: config_auth.token_expiry,
         refresh_buffer: config_auth.refresh_buffer,
         max_retry_attempts: config_auth.max_retry_attempts,
+        reauth_markers: config_auth.reauth_markers.clone(),
     }
 }
 
@@ -5,12 +5,15 @@ use std::io::{self, Read};
 use std::path::PathBuf;
 
 use clap::{Args, Parser, Subcommand};
+use mcp_any_rest::config::format::{convert_config_file, ConfigFormat};
 use mcp_any_rest::config::zml_loader::ZmlModuleLoader;
 use mcp_any_rest::config::preset_loader::PresetLoader;
+use mcp_any_rest::config::secrets::{SecretKey, SecretStore, SECRETS_FILE_NAME, SECRETS_KEY_ENV};
 use mcp_any_rest::config::module::{AccessLevel as ConfigAccessLevel, MethodConfig, ModuleConfig, RateLimitConfig, ResourceConfig, ResourceType as ConfigResourceType};
 use mcp_any_rest::zml::ast::{AccessLevel as AstAccessLevel, RateLimit as AstRateLimit};
+use mcp_any_rest::config::migrate::CURRENT_CONFIG_VERSION;
 use mcp_any_rest::config::preset_loader::PresetConfig;
-use mcp_any_rest::zml::{process_zml, process_zml_file};
+use mcp_any_rest::zml::{ParseOptions, ZMLProcessor};
 
 /// Get executable directory path
 fn get_executable_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
@@ -77,6 +80,10 @@ enum Commands {
     Compile(CompileArgs),
     /// Generate preset JSON from ZML directory
     Preset(PresetArgs),
+    /// Convert a configuration or preset file between JSON, YAML and TOML
+    ConvertConfig(ConvertConfigArgs),
+    /// Manage the encrypted secrets store referenced as secret://<name>
+    Secret(SecretArgs),
 }
 
 #[derive(Args, Debug)]
@@ -98,6 +105,10 @@ struct CompileArgs {
     /// Pretty print JSON output
     #[arg(short = 'p', long = "pretty")]
     pretty: bool,
+
+    /// Reject unrecognized constructs instead of ignoring them
+    #[arg(long = "strict")]
+    strict: bool,
 }
 
 #[derive(Args, Debug)]
@@ -116,6 +127,40 @@ struct PresetArgs {
     out: Option<String>,
 }
 
+#[derive(Args, Debug)]
+struct ConvertConfigArgs {
+    /// Input configuration file (format detected by extension)
+    #[arg(short = 'i', long = "input", value_name = "FILE")]
+    input: PathBuf,
+    /// Output configuration file (format detected by extension)
+    #[arg(short = 'o', long = "output", value_name = "FILE")]
+    output: Option<PathBuf>,
+    /// Target format (json, yaml, toml); used when --output is omitted
+    #[arg(short = 't', long = "to", value_name = "FORMAT")]
+    to: Option<ConfigFormat>,
+}
+
+#[derive(Args, Debug)]
+struct SecretArgs {
+    /// Configuration directory path (default: config/)
+    #[arg(short = 'c', long = "config-dir", value_name = "DIR", global = true)]
+    config_dir: Option<String>,
+    #[command(subcommand)]
+    command: SecretCommands,
+}
+
+#[derive(Subcommand, Debug)]
+enum SecretCommands {
+    /// Generate a new store key to export as MCP_SECRETS_KEY
+    GenKey,
+    /// Encrypt and store a secret; the value is read from STDIN when omitted
+    Set { name: String, value: Option<String> },
+    /// List stored secret names
+    List,
+    /// Remove a stored secret
+    Remove { name: String },
+}
+
 fn main() {
     let cli = Cli::parse();
 
@@ -123,6 +168,8 @@ fn main() {
         Commands::List(args) => list_modules(args),
         Commands::Compile(args) => compile_zml(args),
         Commands::Preset(args) => generate_preset(args),
+        Commands::ConvertConfig(args) => convert_config(args),
+        Commands::Secret(args) => manage_secrets(args),
     }
 }
 
@@ -248,11 +295,13 @@ fn generate_preset(args: PresetArgs) {
             }
 
             let preset = PresetConfig {
+                config_version: CURRENT_CONFIG_VERSION,
                 name: "完整功能".to_string(),
                 description: "启用所有模块和功能的完整配置".to_string(),
                 default_access_level: Some(ConfigAccessLevel::Internal),
                 default_rate_limit: Some(RateLimitConfig { requests_per_minute: 60, requests_per_hour: 1000, burst_capacity: 10 }),
                 modules: modules_cfg,
+                enable: None,
             };
 
             let mut loader = PresetLoader::new(&out_dir);
@@ -275,8 +324,9 @@ fn generate_preset(args: PresetArgs) {
 
 fn compile_zml(args: CompileArgs) {
     // Compile ZML to JSON
+    let mut processor = ZMLProcessor::with_options(ParseOptions { strict: args.strict });
     let result = if let Some(ref input) = args.input {
-        process_zml_file(input)
+        processor.process_file(input)
     } else {
         let mut buf = String::new();
         if let Err(e) = io::stdin().read_to_string(&mut buf) {
@@ -287,7 +337,7 @@ fn compile_zml(args: CompileArgs) {
             eprintln!("Error: ZML source is empty");
             std::process::exit(1);
         }
-        process_zml(&buf)
+        processor.process(&buf)
     };
 
     match result {
@@ -303,4 +353,98 @@ fn compile_zml(args: CompileArgs) {
             std::process::exit(1);
         }
     }
-}
\ No newline at end of file
+}
+
+fn convert_config(args: ConvertConfigArgs) {
+    let output = match (args.output, args.to) {
+        (Some(output), _) => output,
+        (None, Some(format)) => args.input.with_extension(format.extension()),
+        (None, None) => {
+            eprintln!("Error: either --output or --to must be specified");
+            std::process::exit(1);
+        }
+    };
+
+    if output == args.input {
+        eprintln!("Error: output file must differ from input file");
+        std::process::exit(1);
+    }
+
+    match convert_config_file(&args.input, &output) {
+        Ok(format) => {
+            println!("Converted {} to {} ({})", args.input.display(), output.display(), format.name());
+        }
+        Err(e) => {
+            eprintln!("Error: {:#}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn manage_secrets(args: SecretArgs) {
+    if let SecretCommands::GenKey = args.command {
+        println!("{}", SecretKey::generate().to_base64());
+        return;
+    }
+
+    let config_dir = match determine_config_dir(args.config_dir) {
+        Ok(dir) => dir,
+        Err(e) => {
+            eprintln!("Failed to determine config directory: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let key = match SecretKey::from_env() {
+        Ok(Some(key)) => key,
+        Ok(None) => {
+            eprintln!("Error: {} is not set; generate one with `zml secret gen-key`", SECRETS_KEY_ENV);
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("Error: {:#}", e);
+            std::process::exit(1);
+        }
+    };
+    let store = match SecretStore::open(config_dir.join(SECRETS_FILE_NAME), &key) {
+        Ok(store) => store,
+        Err(e) => {
+            eprintln!("Error: {:#}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let result = match args.command {
+        SecretCommands::GenKey => Ok(()),
+        SecretCommands::Set { name, value } => {
+            let value = value.unwrap_or_else(|| {
+                let mut buf = String::new();
+                if let Err(e) = io::stdin().read_to_string(&mut buf) {
+                    eprintln!("Error: failed to read from STDIN: {}", e);
+                    std::process::exit(1);
+                }
+                buf.trim_end_matches(['\r', '\n']).to_string()
+            });
+            store.set(&name, &value).and_then(|_| store.save()).map(|_| {
+                println!("Stored secret '{}'; reference it as secret://{}", name, name);
+            })
+        }
+        SecretCommands::List => {
+            for name in store.names() {
+                println!("{}", name);
+            }
+            Ok(())
+        }
+        SecretCommands::Remove { name } => {
+            if store.remove(&name) {
+                store.save().map(|_| println!("Removed secret '{}'", name))
+            } else {
+                Err(anyhow::anyhow!("Secret '{}' not found", name))
+            }
+        }
+    };
+
+    if let Err(e) = result {
+        eprintln!("Error: {:#}", e);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,153 @@
+//! Authentication of the web API for MCP-ANY-REST
+//!
+//! With `server.admin_users` or `server.admin_token` configured, `/config/*` requests must send
+//! `Authorization: Bearer <token>` or the HTTP basic credentials of a user. Viewers may read the
+//! configuration, its history and diagnostics; changing it takes the editor role. The admin token
+//! authenticates as the `admin` editor. Without any credential the web API stays open, so the web
+//! server refuses to start on a `server.bind_address` other than a loopback address.
+
+use axum::http::{header, HeaderMap};
+use base64::Engine;
+use log::warn;
+use std::net::IpAddr;
+use subtle::ConstantTimeEq;
+
+use crate::config::config::{AdminRole, ServerConfig};
+use crate::config::credentials::resolve_credential_ref;
+
+/// User the admin token authenticates as
+pub const ADMIN_TOKEN_USER: &str = "admin";
+
+/// Authenticated user of the web API
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdminIdentity {
+    pub name: String,
+    pub role: AdminRole,
+}
+
+/// Whether web API requests must authenticate: users or the admin token are configured
+pub fn is_enabled(server: &ServerConfig) -> bool {
+    !server.admin_users.is_empty() || server.admin_token.as_deref().is_some_and(|token| !token.is_empty())
+}
+
+/// Whether the web server only accepts connections from this machine
+pub fn is_loopback(bind_address: &str) -> bool {
+    bind_address == "localhost" || bind_address.parse::<IpAddr>().is_ok_and(|address| address.is_loopback())
+}
+
+/// User sending a request with `headers`; fails with the reason when the credentials are missing
+/// or match no user
+pub fn authenticate(server: &ServerConfig, headers: &HeaderMap) -> Result<AdminIdentity, String> {
+    let authorization = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .ok_or("Authentication required: send a bearer token or basic credentials")?;
+
+    if let Some(token) = authorization.strip_prefix("Bearer ") {
+        let token = token.trim();
+        if matches(&server.admin_token, token) {
+            return Ok(AdminIdentity { name: ADMIN_TOKEN_USER.to_string(), role: AdminRole::Editor });
+        }
+        return server
+            .admin_users
+            .iter()
+            .find(|user| matches(&user.token, token))
+            .map(|user| AdminIdentity { name: user.name.clone(), role: user.role })
+            .ok_or_else(|| "Invalid bearer token".to_string());
+    }
+
+    if let Some(encoded) = authorization.strip_prefix("Basic ") {
+        let decoded = base64::engine::general_purpose::STANDARD
+            .decode(encoded.trim())
+            .ok()
+            .and_then(|decoded| String::from_utf8(decoded).ok())
+            .ok_or("Malformed basic credentials")?;
+        let (name, password) = decoded.split_once(':').ok_or("Malformed basic credentials")?;
+        return server
+            .admin_users
+            .iter()
+            .find(|user| user.name == name && matches(&user.password, password))
+            .map(|user| AdminIdentity { name: user.name.clone(), role: user.role })
+            .ok_or_else(|| "Invalid user name or password".to_string());
+    }
+
+    Err("Unsupported authorization scheme; use Bearer or Basic".to_string())
+}
+
+/// Whether `given` is the configured credential, resolving credential references and comparing
+/// in constant time; unset and empty credentials match nothing
+pub fn matches(configured: &Option<String>, given: &str) -> bool {
+    let Some(configured) = configured else {
+        return false;
+    };
+    match resolve_credential_ref(configured) {
+        Ok(value) => !value.is_empty() && bool::from(value.as_bytes().ct_eq(given.as_bytes())),
+        Err(e) => {
+            warn!("Failed to resolve a web API credential: {}", e);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::config::AdminUser;
+    use axum::http::HeaderValue;
+
+    fn headers(authorization: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_str(authorization).unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_users_authenticate_with_tokens_or_basic_credentials() {
+        let mut server = ServerConfig::default();
+        assert!(!is_enabled(&server));
+        server.admin_token = Some("root-token".to_string());
+        assert!(is_enabled(&server));
+
+        server.admin_users = vec![
+            AdminUser { name: "ops".to_string(), token: Some("ops-token".to_string()), password: None, role: AdminRole::Editor },
+            AdminUser { name: "auditor".to_string(), token: None, password: Some("s3cret".to_string()), role: AdminRole::Viewer },
+        ];
+        assert!(is_enabled(&server));
+
+        let admin = authenticate(&server, &headers("Bearer root-token")).unwrap();
+        assert_eq!((admin.name.as_str(), admin.role), (ADMIN_TOKEN_USER, AdminRole::Editor));
+        assert_eq!(authenticate(&server, &headers("Bearer ops-token")).unwrap().name, "ops");
+        // "auditor:s3cret"
+        let auditor = authenticate(&server, &headers("Basic YXVkaXRvcjpzM2NyZXQ=")).unwrap();
+        assert_eq!((auditor.name.as_str(), auditor.role), ("auditor", AdminRole::Viewer));
+
+        assert!(authenticate(&server, &HeaderMap::new()).is_err());
+        assert!(authenticate(&server, &headers("Bearer wrong")).is_err());
+        // "auditor:wrong"
+        assert!(authenticate(&server, &headers("Basic YXVkaXRvcjp3cm9uZw==")).is_err());
+        // "ops:" has no password to match
+        assert!(authenticate(&server, &headers("Basic b3BzOg==")).is_err());
+        assert!(authenticate(&server, &headers("Digest username=\"ops\"")).is_err());
+    }
+
+    #[test]
+    fn test_admin_token_references_are_resolved() {
+        std::env::set_var("MCP_ADMIN_AUTH_TEST_TOKEN", "root-token");
+        let configured = Some("env://MCP_ADMIN_AUTH_TEST_TOKEN".to_string());
+        assert!(matches(&configured, "root-token"));
+        assert!(!matches(&configured, "env://MCP_ADMIN_AUTH_TEST_TOKEN"));
+        assert!(!matches(&configured, "root-toke"));
+        assert!(!matches(&Some(String::new()), ""));
+        assert!(!matches(&None, ""));
+    }
+
+    #[test]
+    fn test_loopback_bind_addresses() {
+        for address in ["127.0.0.1", "::1", "localhost"] {
+            assert!(is_loopback(address), "{}", address);
+        }
+        for address in ["0.0.0.0", "::", "192.168.1.10", "example.com"] {
+            assert!(!is_loopback(address), "{}", address);
+        }
+    }
+}
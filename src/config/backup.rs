@@ -0,0 +1,299 @@
+//! Configuration file backups for MCP-ANY-REST
+//! Before a configuration, module or preset file is overwritten, the existing file is copied to a
+//! timestamped `.bak` under `<config dir>/backups/`, keeping the newest backups of each file.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Directory of the backups inside the configuration directory
+pub const BACKUPS_DIR_NAME: &str = "backups";
+
+/// Default number of backups kept per file
+pub const DEFAULT_BACKUP_RETENTION: usize = 10;
+
+const BACKUP_EXTENSION: &str = ".bak";
+
+/// A backup on disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupEntry {
+    /// Backup path relative to the backups directory, used to restore it
+    pub name: String,
+    /// Backed up file, relative to the configuration directory
+    pub file: String,
+    pub timestamp: DateTime<Utc>,
+    pub size: u64,
+}
+
+/// Timestamped backups of the files of a configuration directory
+pub struct ConfigBackups {
+    root: PathBuf,
+    dir: PathBuf,
+    retention: AtomicUsize,
+}
+
+impl ConfigBackups {
+    /// Backups of the files below `config_dir`, stored in `config_dir/backups`;
+    /// a retention of 0 disables backups
+    pub fn new(config_dir: impl AsRef<Path>, retention: usize) -> Self {
+        let root = config_dir.as_ref().to_path_buf();
+        Self {
+            dir: root.join(BACKUPS_DIR_NAME),
+            root,
+            retention: AtomicUsize::new(retention),
+        }
+    }
+
+    /// Backups with the default retention for a file loaded on its own, stored in `backups/`
+    /// of the directory holding it
+    pub fn beside(path: impl AsRef<Path>) -> Self {
+        let dir = path.as_ref().parent().unwrap_or_else(|| Path::new("."));
+        Self::new(dir, DEFAULT_BACKUP_RETENTION)
+    }
+
+    /// Directory holding the backups
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Change the number of backups kept per file
+    pub fn set_retention(&self, retention: usize) {
+        self.retention.store(retention, Ordering::Relaxed);
+    }
+
+    /// Copy a file to a new backup before it is overwritten or deleted. Returns the backup path,
+    /// or `None` when backups are disabled, the file does not exist yet or lies outside the
+    /// configuration directory
+    pub fn backup(&self, path: impl AsRef<Path>) -> Result<Option<PathBuf>> {
+        let path = path.as_ref();
+        let retention = self.retention.load(Ordering::Relaxed);
+        if retention == 0 || !path.is_file() {
+            return Ok(None);
+        }
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return Ok(None);
+        };
+        let Some(file_name) = relative.file_name().map(|name| name.to_string_lossy().into_owned()) else {
+            return Ok(None);
+        };
+
+        let backup_dir = self.dir.join(relative.parent().unwrap_or_else(|| Path::new("")));
+        fs::create_dir_all(&backup_dir)
+            .with_context(|| format!("Failed to create backup directory: {:?}", backup_dir))?;
+
+        // A counter separates backups within the same millisecond
+        let timestamp = Utc::now().format("%Y%m%dT%H%M%S%3fZ").to_string();
+        let mut backup_path = backup_dir.join(format!("{}.{}{}", file_name, timestamp, BACKUP_EXTENSION));
+        let mut counter = 1;
+        while backup_path.exists() {
+            backup_path = backup_dir.join(format!("{}.{}-{}{}", file_name, timestamp, counter, BACKUP_EXTENSION));
+            counter += 1;
+        }
+        fs::copy(path, &backup_path)
+            .with_context(|| format!("Failed to back up {:?} to {:?}", path, backup_path))?;
+
+        self.prune(&backup_dir, &file_name, retention);
+        Ok(Some(backup_path))
+    }
+
+    /// Remove the oldest backups of a file beyond the retention limit
+    fn prune(&self, backup_dir: &Path, file_name: &str, retention: usize) {
+        let mut backups: Vec<(BackupOrder, PathBuf)> = fs::read_dir(backup_dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                    .filter_map(|path| {
+                        let name = path.file_name()?.to_string_lossy().into_owned();
+                        (backup_source_name(&name)? == file_name).then_some((backup_order(&name)?, path))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        backups.sort();
+
+        let excess = backups.len().saturating_sub(retention);
+        for (_, path) in backups.into_iter().take(excess) {
+            if let Err(e) = fs::remove_file(&path) {
+                warn!("Failed to remove old backup {:?}: {}", path, e);
+            }
+        }
+    }
+
+    /// All backups, newest first
+    pub fn list(&self) -> Result<Vec<BackupEntry>> {
+        let mut entries = Vec::new();
+        if self.dir.exists() {
+            self.collect(&self.dir, &mut entries)?;
+        }
+        entries.sort_by(|a, b| {
+            b.timestamp
+                .cmp(&a.timestamp)
+                .then_with(|| backup_order(&b.name).cmp(&backup_order(&a.name)))
+                .then_with(|| b.name.cmp(&a.name))
+        });
+        Ok(entries)
+    }
+
+    fn collect(&self, dir: &Path, entries: &mut Vec<BackupEntry>) -> Result<()> {
+        for entry in fs::read_dir(dir).with_context(|| format!("Failed to read backup directory: {:?}", dir))? {
+            let path = entry?.path();
+            if path.is_dir() {
+                self.collect(&path, entries)?;
+                continue;
+            }
+
+            let Ok(relative) = path.strip_prefix(&self.dir) else {
+                continue;
+            };
+            let Some(source) = relative
+                .file_name()
+                .and_then(|name| backup_source_name(&name.to_string_lossy()))
+            else {
+                continue;
+            };
+            let metadata = fs::metadata(&path)?;
+            let file = relative.with_file_name(source);
+            entries.push(BackupEntry {
+                name: path_label(relative),
+                file: path_label(&file),
+                timestamp: metadata.modified().map(DateTime::<Utc>::from).unwrap_or_else(|_| Utc::now()),
+                size: metadata.len(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Copy a backup over the file it was taken from; the current file is backed up first.
+    /// Returns the restored file
+    pub fn restore(&self, name: &str) -> Result<PathBuf> {
+        let relative = Path::new(name);
+        if relative.components().any(|c| !matches!(c, Component::Normal(_))) {
+            bail!("Invalid backup name: {}", name);
+        }
+        let Some(source) = relative
+            .file_name()
+            .and_then(|file_name| backup_source_name(&file_name.to_string_lossy()))
+        else {
+            bail!("Invalid backup name: {}", name);
+        };
+
+        let backup_path = self.dir.join(relative);
+        if !backup_path.is_file() {
+            bail!("Backup not found: {}", name);
+        }
+
+        let target = self.root.join(relative.with_file_name(source));
+        self.backup(&target)?;
+        fs::copy(&backup_path, &target)
+            .with_context(|| format!("Failed to restore {:?} from backup {}", target, name))?;
+        Ok(target)
+    }
+}
+
+/// Name of the backed up file for a backup file name (`modules.json.20250101T120000000Z.bak` -> `modules.json`)
+fn backup_source_name(backup_name: &str) -> Option<String> {
+    let (source, timestamp) = backup_name.strip_suffix(BACKUP_EXTENSION)?.rsplit_once('.')?;
+    if source.is_empty() || !(timestamp.ends_with('Z') || timestamp.contains("Z-")) {
+        return None;
+    }
+    Some(source.to_string())
+}
+
+/// Timestamp and counter of a backup, in the order the backups were taken
+type BackupOrder = (String, u32);
+
+/// Order of a backup file name (`modules.json.20250101T120000000Z-2.bak` -> (`20250101T120000000Z`, 2)).
+/// Timestamps have a fixed width and sort as text; counters are compared as numbers, so `-10` follows `-9`
+fn backup_order(backup_name: &str) -> Option<BackupOrder> {
+    let (_, stamp) = backup_name.strip_suffix(BACKUP_EXTENSION)?.rsplit_once('.')?;
+    match stamp.split_once("Z-") {
+        Some((timestamp, counter)) => Some((format!("{}Z", timestamp), counter.parse().ok()?)),
+        None => Some((stamp.to_string(), 0)),
+    }
+}
+
+/// Path with `/` separators, for names shown in the web API
+fn path_label(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_backup_is_pruned_to_retention() {
+        let temp_dir = tempdir().unwrap();
+        let presets = temp_dir.path().join("presets");
+        fs::create_dir_all(&presets).unwrap();
+        let preset = presets.join("dev.json");
+        let backups = ConfigBackups::new(temp_dir.path(), 2);
+
+        assert!(backups.backup(&preset).unwrap().is_none());
+        for version in 0..3 {
+            fs::write(&preset, format!("{{\"version\": {}}}", version)).unwrap();
+            assert!(backups.backup(&preset).unwrap().is_some());
+        }
+
+        let entries = backups.list().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(entries.iter().all(|entry| entry.file == "presets/dev.json"));
+        assert!(entries[0].name.starts_with("presets/dev.json."));
+
+        backups.set_retention(0);
+        assert!(backups.backup(&preset).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_prune_orders_backups_by_timestamp_and_counter() {
+        let temp_dir = tempdir().unwrap();
+        let config = temp_dir.path().join("config.json");
+        fs::write(&config, "{}").unwrap();
+        let backups = ConfigBackups::new(temp_dir.path(), 3);
+
+        fs::create_dir_all(backups.dir()).unwrap();
+        let stamp = "20250101T000000000Z";
+        fs::write(backups.dir().join(format!("config.json.{}.bak", stamp)), "{}").unwrap();
+        for counter in 1..=10 {
+            fs::write(backups.dir().join(format!("config.json.{}-{}.bak", stamp, counter)), "{}").unwrap();
+        }
+
+        let newest = backups.backup(&config).unwrap().unwrap();
+        let mut kept: Vec<String> = fs::read_dir(backups.dir())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        kept.sort();
+        let mut expected = vec![
+            format!("config.json.{}-9.bak", stamp),
+            format!("config.json.{}-10.bak", stamp),
+            newest.file_name().unwrap().to_string_lossy().into_owned(),
+        ];
+        expected.sort();
+        assert_eq!(kept, expected);
+    }
+
+    #[test]
+    fn test_restore_backs_up_current_file() {
+        let temp_dir = tempdir().unwrap();
+        let config = temp_dir.path().join("config.json");
+        let backups = ConfigBackups::new(temp_dir.path(), 5);
+
+        fs::write(&config, "old").unwrap();
+        let backup = backups.backup(&config).unwrap().unwrap();
+        fs::write(&config, "new").unwrap();
+
+        let name = path_label(backup.strip_prefix(backups.dir()).unwrap());
+        assert_eq!(backups.restore(&name).unwrap(), config);
+        assert_eq!(fs::read_to_string(&config).unwrap(), "old");
+        assert_eq!(backups.list().unwrap().len(), 2);
+
+        assert!(backups.restore("../config.json").is_err());
+        assert!(backups.restore("config.json.missing.bak").is_err());
+    }
+}
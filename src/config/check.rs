@@ -0,0 +1,469 @@
+//! Configuration checks for MCP-ANY-REST
+//! This module loads the configuration, module, preset, client and ZML files of a configuration
+//! directory without modifying them and cross-checks their references, producing a report for CI gates.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use crate::config::clients::ClientStore;
+use crate::config::config::Config;
+use crate::config::format::{read_config_file, ConfigFormat};
+use crate::config::migrate::ConfigKind;
+use crate::config::module::{GlobalModuleConfig, ModuleConfig};
+use crate::config::preset_loader::{PresetConfig, PresetIndex};
+use crate::config::profile::read_layered_config_file_readonly;
+use crate::config::validator::{ConfigValidator, ValidationResult};
+use crate::zml::ast::Module;
+use crate::zml::parser::ZMLParserWrapper;
+
+/// Severity of a check issue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckLevel {
+    Error,
+    Warning,
+}
+
+/// Single problem found in a configuration directory
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckIssue {
+    pub level: CheckLevel,
+    /// File the problem was found in, relative to the configuration directory
+    pub file: String,
+    /// Dotted path of the offending element (empty for file-level problems)
+    pub path: String,
+    pub message: String,
+}
+
+/// Result of checking a configuration directory
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CheckReport {
+    /// Number of ZML modules found
+    pub zml_modules: usize,
+    /// Number of presets checked
+    pub presets: usize,
+    pub issues: Vec<CheckIssue>,
+}
+
+impl CheckReport {
+    /// Number of errors
+    pub fn error_count(&self) -> usize {
+        self.issues.iter().filter(|i| i.level == CheckLevel::Error).count()
+    }
+
+    /// Number of warnings
+    pub fn warning_count(&self) -> usize {
+        self.issues.iter().filter(|i| i.level == CheckLevel::Warning).count()
+    }
+
+    /// Whether no errors were found
+    pub fn is_ok(&self) -> bool {
+        self.error_count() == 0
+    }
+
+    fn push(&mut self, level: CheckLevel, file: &str, path: &str, message: impl Into<String>) {
+        self.issues.push(CheckIssue {
+            level,
+            file: file.to_string(),
+            path: path.to_string(),
+            message: message.into(),
+        });
+    }
+
+    fn error(&mut self, file: &str, path: &str, message: impl Into<String>) {
+        self.push(CheckLevel::Error, file, path, message);
+    }
+
+    fn warning(&mut self, file: &str, path: &str, message: impl Into<String>) {
+        self.push(CheckLevel::Warning, file, path, message);
+    }
+
+    /// Add the errors and warnings of a validator result
+    fn extend_validation(&mut self, file: &str, result: &ValidationResult) {
+        for error in &result.errors {
+            self.error(file, &error.path, error.message.clone());
+        }
+        for warning in &result.warnings {
+            self.warning(file, &warning.path, warning.message.clone());
+        }
+    }
+}
+
+impl fmt::Display for CheckReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for issue in &self.issues {
+            let level = match issue.level {
+                CheckLevel::Error => "error",
+                CheckLevel::Warning => "warning",
+            };
+            if issue.path.is_empty() {
+                writeln!(f, "{}: {}: {}", level, issue.file, issue.message)?;
+            } else {
+                writeln!(f, "{}: {}: {}: {}", level, issue.file, issue.path, issue.message)?;
+            }
+        }
+        write!(
+            f,
+            "Checked {} ZML modules and {} presets: {} errors, {} warnings",
+            self.zml_modules,
+            self.presets,
+            self.error_count(),
+            self.warning_count()
+        )
+    }
+}
+
+/// Check every configuration file of a directory and the references between them
+pub fn check_config_dir(config_dir: impl AsRef<Path>, profile: Option<&str>) -> CheckReport {
+    let config_dir = config_dir.as_ref();
+    let validator = ConfigValidator::new();
+    let mut report = CheckReport::default();
+
+    // Main configuration
+    let config_path = ConfigFormat::resolve_path(config_dir, "config", ConfigFormat::Json);
+    let config_file = file_label(config_dir, &config_path);
+    let config = if config_path.exists() {
+        match read_layered_config_file_readonly::<Config>(&config_path, ConfigFormat::Json, ConfigKind::Main, profile) {
+            Ok(config) => {
+                report.extend_validation(&config_file, &validator.validate_config(&config));
+                Some(config)
+            }
+            Err(e) => {
+                report.error(&config_file, "", format!("{:#}", e));
+                None
+            }
+        }
+    } else {
+        report.warning(&config_file, "", "File not found, built-in defaults are used");
+        Some(Config::default())
+    };
+
+    // Module configuration
+    let modules_path = ConfigFormat::resolve_path(config_dir, "modules", ConfigFormat::Json);
+    let modules_file = file_label(config_dir, &modules_path);
+    let module_config = if modules_path.exists() {
+        match read_layered_config_file_readonly::<GlobalModuleConfig>(
+            &modules_path,
+            ConfigFormat::Json,
+            ConfigKind::Modules,
+            profile,
+        ) {
+            Ok(module_config) => {
+                report.extend_validation(&modules_file, &validator.validate_global_module_config(&module_config));
+                Some(module_config)
+            }
+            Err(e) => {
+                report.error(&modules_file, "", format!("{:#}", e));
+                None
+            }
+        }
+    } else {
+        report.warning(&modules_file, "", "File not found, no modules are enabled");
+        None
+    };
+
+    // ZML sources
+    let zml_modules = load_zml_modules(config_dir, &mut report);
+    report.zml_modules = zml_modules.len();
+
+    if let Some(module_config) = &module_config {
+        check_module_references(&modules_file, "modules", &module_config.modules, &zml_modules, &mut report);
+    }
+
+    if let Some(config) = &config {
+        for (module_name, module) in &zml_modules {
+            if let Some(backend) = &module.backend {
+                if !config.backends.contains_key(backend) {
+                    report.error(
+                        &format!("zml/{}", module_name),
+                        "backend",
+                        format!("Module '{}' references unknown backend '{}'", module_name, backend),
+                    );
+                }
+            }
+        }
+    }
+
+    check_presets(config_dir, &zml_modules, &validator, &mut report);
+
+    // Client overrides must apply cleanly and should only mention known modules
+    if let Some(clients_path) = ConfigFormat::find_existing(config_dir, "clients") {
+        let clients_file = file_label(config_dir, &clients_path);
+        match ClientStore::open_in_dir(config_dir) {
+            Ok(store) => {
+                let mut base = config.clone().unwrap_or_default();
+                if let Some(module_config) = &module_config {
+                    base.module_config = module_config.clone();
+                }
+                let mut clients: Vec<_> = store.list().into_iter().collect();
+                clients.sort_by(|a, b| a.0.cmp(&b.0));
+                for (client, client_override) in clients {
+                    let path = format!("clients.{}", client);
+                    if let Err(e) = client_override.apply(&base) {
+                        report.error(&clients_file, &path, format!("{:#}", e));
+                    }
+                    for module_name in client_override.modules.keys() {
+                        if !zml_modules.contains_key(module_name) {
+                            report.warning(
+                                &clients_file,
+                                &format!("{}.modules.{}", path, module_name),
+                                format!("Module '{}' is not defined in ZML", module_name),
+                            );
+                        }
+                    }
+                }
+            }
+            Err(e) => report.error(&clients_file, "", format!("{:#}", e)),
+        }
+    }
+
+    report
+}
+
+/// Parse every `.zml` file, reporting files that fail to parse
+fn load_zml_modules(config_dir: &Path, report: &mut CheckReport) -> HashMap<String, Module> {
+    let zml_dir = config_dir.join("zml");
+    let mut modules = HashMap::new();
+
+    let entries = match fs::read_dir(&zml_dir) {
+        Ok(entries) => entries,
+        Err(_) => {
+            report.warning("zml", "", "ZML directory not found, no modules are available");
+            return modules;
+        }
+    };
+
+    let mut paths: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().map(|e| e == "zml").unwrap_or(false))
+        .collect();
+    paths.sort();
+
+    let mut parser = ZMLParserWrapper::new();
+    for path in paths {
+        let file = file_label(config_dir, &path);
+        let source = match fs::read_to_string(&path) {
+            Ok(source) => source,
+            Err(e) => {
+                report.error(&file, "", format!("Failed to read ZML file: {}", e));
+                continue;
+            }
+        };
+        match parser.parse_with_file(&source, &path.display().to_string()) {
+            Ok(module) => {
+                if modules.contains_key(&module.name) {
+                    report.error(&file, "", format!("Module '{}' is defined more than once", module.name));
+                }
+                modules.insert(module.name.clone(), module);
+            }
+            Err(e) => report.error(&file, "", e.to_string()),
+        }
+    }
+
+    modules
+}
+
+/// Check that configured modules, methods and resources exist in the ZML sources
+fn check_module_references(
+    file: &str,
+    prefix: &str,
+    modules: &HashMap<String, ModuleConfig>,
+    zml_modules: &HashMap<String, Module>,
+    report: &mut CheckReport,
+) {
+    let mut module_names: Vec<&String> = modules.keys().collect();
+    module_names.sort();
+
+    for module_name in module_names {
+        let module_path = format!("{}.{}", prefix, module_name);
+        let zml_module = match zml_modules.get(module_name) {
+            Some(zml_module) => zml_module,
+            None => {
+                report.error(file, &module_path, format!("Module '{}' is not defined in ZML", module_name));
+                continue;
+            }
+        };
+        let module_config = &modules[module_name];
+
+        if let Some(methods) = &module_config.methods {
+            let mut method_names: Vec<&String> = methods.keys().collect();
+            method_names.sort();
+            for method_name in method_names {
+                if !zml_module.methods.contains_key(method_name) {
+                    report.error(
+                        file,
+                        &format!("{}.methods.{}", module_path, method_name),
+                        format!("Method '{}' is not defined in ZML module '{}'", method_name, module_name),
+                    );
+                }
+            }
+        }
+
+        if let Some(resources) = &module_config.resources {
+            let mut resource_names: Vec<&String> = resources.keys().collect();
+            resource_names.sort();
+            for resource_name in resource_names {
+                let known = zml_module
+                    .resources
+                    .values()
+                    .any(|r| &r.uri == resource_name || &r.name == resource_name);
+                if !known {
+                    report.error(
+                        file,
+                        &format!("{}.resources.{}", module_path, resource_name),
+                        format!("Resource '{}' is not defined in ZML module '{}'", resource_name, module_name),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Check the preset index and every preset it lists
+fn check_presets(
+    config_dir: &Path,
+    zml_modules: &HashMap<String, Module>,
+    validator: &ConfigValidator,
+    report: &mut CheckReport,
+) {
+    let presets_dir = config_dir.join("presets");
+    let index_path = match ConfigFormat::find_existing(&presets_dir, "index") {
+        Some(index_path) => index_path,
+        None => return,
+    };
+    let index_file = file_label(config_dir, &index_path);
+    let index: PresetIndex = match read_config_file(&index_path, ConfigFormat::Json) {
+        Ok(index) => index,
+        Err(e) => {
+            report.error(&index_file, "", format!("{:#}", e));
+            return;
+        }
+    };
+
+    if let Some(default_preset) = &index.default_preset {
+        if !index.presets.iter().any(|p| &p.id == default_preset) {
+            report.error(
+                &index_file,
+                "default_preset",
+                format!("Default preset '{}' is not listed in the index", default_preset),
+            );
+        }
+    }
+
+    for preset in &index.presets {
+        report.presets += 1;
+        let preset_path = match ConfigFormat::find_existing(&presets_dir, &preset.file) {
+            Some(preset_path) => preset_path,
+            None => {
+                report.error(
+                    &index_file,
+                    &format!("presets.{}", preset.id),
+                    format!("Preset file not found: {}.{{yaml,yml,toml,json}}", preset.file),
+                );
+                continue;
+            }
+        };
+        let preset_file = file_label(config_dir, &preset_path);
+        match read_layered_config_file_readonly::<PresetConfig>(&preset_path, ConfigFormat::Json, ConfigKind::Preset, None)
+        {
+            Ok(preset_config) => {
+                let module_config = GlobalModuleConfig {
+                    modules: preset_config.modules.clone(),
+                    enable: preset_config.enable.clone(),
+                    ..GlobalModuleConfig::default()
+                };
+                report.extend_validation(&preset_file, &validator.validate_global_module_config(&module_config));
+                check_module_references(&preset_file, "modules", &preset_config.modules, zml_modules, report);
+            }
+            Err(e) => report.error(&preset_file, "", format!("{:#}", e)),
+        }
+    }
+}
+
+/// Path of a file relative to the configuration directory, for messages
+fn file_label(config_dir: &Path, path: &Path) -> String {
+    path.strip_prefix(config_dir)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const USER_ZML: &str = r#"
+module user {
+    method get_user {
+        http_method: GET
+        uri: "/users/current"
+        response: any
+    }
+}
+"#;
+
+    #[test]
+    fn test_check_reports_cross_reference_errors() {
+        let temp_dir = tempdir().unwrap();
+        let dir = temp_dir.path();
+        Config::default().save_to_file(dir.join("config.json")).unwrap();
+        fs::create_dir(dir.join("zml")).unwrap();
+        fs::write(dir.join("zml/user.zml"), USER_ZML).unwrap();
+        fs::write(
+            dir.join("modules.json"),
+            r#"{
+                "default_access_level": "Internal",
+                "default_rate_limit": null,
+                "modules": {
+                    "user": { "enabled": true, "methods": { "get_user": { "enabled": true }, "drop_user": { "enabled": true } } },
+                    "project": { "enabled": true }
+                }
+            }"#,
+        )
+        .unwrap();
+        fs::create_dir(dir.join("presets")).unwrap();
+        fs::write(
+            dir.join("presets/index.json"),
+            r#"{ "presets": [{ "id": "basic", "name": "Basic", "description": "", "file": "basic", "enabled": true, "priority": 1 }], "default_preset": "full" }"#,
+        )
+        .unwrap();
+
+        let report = check_config_dir(dir, None);
+        let errors: Vec<(&str, &str)> = report
+            .issues
+            .iter()
+            .filter(|i| i.level == CheckLevel::Error)
+            .map(|i| (i.file.as_str(), i.path.as_str()))
+            .collect();
+
+        assert_eq!(report.zml_modules, 1);
+        assert!(errors.contains(&("modules.json", "modules.user.methods.drop_user")));
+        assert!(errors.contains(&("modules.json", "modules.project")));
+        assert!(errors.contains(&("presets/index.json", "default_preset")));
+        assert!(errors.contains(&("presets/index.json", "presets.basic")));
+        assert!(!errors.iter().any(|(_, path)| *path == "modules.user.methods.get_user"));
+        assert!(!report.is_ok());
+    }
+
+    #[test]
+    fn test_check_valid_directory() {
+        let temp_dir = tempdir().unwrap();
+        let dir = temp_dir.path();
+        Config::default().save_to_file(dir.join("config.json")).unwrap();
+        fs::create_dir(dir.join("zml")).unwrap();
+        fs::write(dir.join("zml/user.zml"), USER_ZML).unwrap();
+        fs::write(
+            dir.join("modules.json"),
+            r#"{ "default_access_level": "Internal", "default_rate_limit": null, "modules": { "user": { "enabled": true } } }"#,
+        )
+        .unwrap();
+
+        let report = check_config_dir(dir, None);
+        assert!(report.is_ok(), "{}", report);
+        assert!(report.to_string().ends_with("0 errors, 0 warnings"));
+    }
+}
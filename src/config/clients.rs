@@ -0,0 +1,218 @@
+//! Per-client configuration overrides for MCP-ANY-REST
+//! This module keeps module/tool overrides scoped to a client identity (the MCP `clientInfo` name
+//! or an HTTP session id), so agents connected to the same server can see different tool sets.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use crate::config::config::Config;
+use crate::config::format::{read_config_file, write_config_file, ConfigFormat};
+use crate::config::module::ModuleConfig;
+use crate::config::profile::deep_merge;
+
+/// File stem of the client overrides inside the configuration directory
+pub const CLIENTS_CONFIG_STEM: &str = "clients";
+
+/// Identity of a connected MCP client
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientIdentity {
+    /// `clientInfo.name` sent during MCP initialization
+    pub name: Option<String>,
+    /// HTTP session id (`Mcp-Session-Id`) of streamable HTTP connections
+    pub session: Option<String>,
+}
+
+/// Overrides applied on top of the module configuration for one client
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClientOverride {
+    /// Free-form description shown in the web API
+    #[serde(default)]
+    pub description: Option<String>,
+
+    /// Partial module configurations deep-merged over `modules`
+    /// (e.g. `{"project": {"enabled": false}}` or `{"user": {"methods": {"delete_user": {"enabled": false}}}}`)
+    #[serde(default)]
+    pub modules: Map<String, Value>,
+}
+
+impl ClientOverride {
+    /// Configuration as seen by the client: the module configuration with this override merged in
+    pub fn apply(&self, config: &Config) -> Result<Config> {
+        let mut modules = serde_json::to_value(&config.module_config.modules)?;
+        deep_merge(&mut modules, Value::Object(self.modules.clone()));
+
+        let mut config = config.clone();
+        config.module_config.modules = serde_json::from_value::<HashMap<String, ModuleConfig>>(modules)
+            .context("Client override does not produce a valid module configuration")?;
+        Ok(config)
+    }
+}
+
+/// Client overrides file (`clients.json` / `clients.yaml` / `clients.toml`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ClientOverridesFile {
+    #[serde(default)]
+    clients: HashMap<String, ClientOverride>,
+}
+
+/// Persistent store of per-client overrides, keyed by client name or session id
+pub struct ClientStore {
+    path: PathBuf,
+    clients: RwLock<HashMap<String, ClientOverride>>,
+}
+
+impl ClientStore {
+    /// Open the client overrides of a configuration directory
+    pub fn open_in_dir(config_dir: impl AsRef<Path>) -> Result<Self> {
+        let path = ConfigFormat::resolve_path(config_dir, CLIENTS_CONFIG_STEM, ConfigFormat::Json);
+        let clients = if path.exists() {
+            let file: ClientOverridesFile = read_config_file(&path, ConfigFormat::Json)?;
+            file.clients
+        } else {
+            HashMap::new()
+        };
+
+        Ok(Self {
+            path,
+            clients: RwLock::new(clients),
+        })
+    }
+
+    /// File holding the overrides
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// All overrides by key
+    pub fn list(&self) -> HashMap<String, ClientOverride> {
+        self.clients.read().unwrap().clone()
+    }
+
+    /// Override stored under a key
+    pub fn get(&self, key: &str) -> Option<ClientOverride> {
+        self.clients.read().unwrap().get(key).cloned()
+    }
+
+    /// Override of a connected client: the session id wins over the client name
+    pub fn resolve(&self, identity: &ClientIdentity) -> Option<(String, ClientOverride)> {
+        let clients = self.clients.read().unwrap();
+        [identity.session.as_deref(), identity.name.as_deref()]
+            .into_iter()
+            .flatten()
+            .find_map(|key| clients.get(key).map(|o| (key.to_string(), o.clone())))
+    }
+
+    /// Store an override after checking that it applies cleanly to `config`
+    pub fn set(&self, key: &str, client_override: ClientOverride, config: &Config) -> Result<()> {
+        if key.trim().is_empty() {
+            bail!("Client key cannot be empty");
+        }
+        client_override.apply(config)?;
+
+        let mut clients = self.clients.write().unwrap();
+        let mut updated = clients.clone();
+        updated.insert(key.to_string(), client_override);
+        self.persist(&updated)?;
+        *clients = updated;
+        Ok(())
+    }
+
+    /// Remove an override; returns whether it existed
+    pub fn remove(&self, key: &str) -> Result<bool> {
+        let mut clients = self.clients.write().unwrap();
+        if !clients.contains_key(key) {
+            return Ok(false);
+        }
+        let mut updated = clients.clone();
+        updated.remove(key);
+        self.persist(&updated)?;
+        *clients = updated;
+        Ok(true)
+    }
+
+    fn persist(&self, clients: &HashMap<String, ClientOverride>) -> Result<()> {
+        let file = ClientOverridesFile {
+            clients: clients.clone(),
+        };
+        write_config_file(&self.path, &file, ConfigFormat::Json)
+            .with_context(|| format!("Failed to save client overrides: {:?}", self.path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    fn config_with_modules() -> Config {
+        let mut config = Config::default();
+        for name in ["user", "project"] {
+            config
+                .module_config
+                .modules
+                .insert(name.to_string(), ModuleConfig::default());
+        }
+        config
+    }
+
+    fn client_override(modules: Value) -> ClientOverride {
+        ClientOverride {
+            description: None,
+            modules: modules.as_object().unwrap().clone(),
+        }
+    }
+
+    #[test]
+    fn test_override_applies_to_modules_and_methods() {
+        let config = config_with_modules();
+        let client = client_override(json!({
+            "project": { "enabled": false },
+            "user": { "methods": { "delete_user": { "enabled": false } } }
+        }));
+
+        let effective = client.apply(&config).unwrap();
+        assert!(!effective.is_module_enabled("project"));
+        assert!(effective.is_module_enabled("user"));
+        assert!(!effective.is_method_enabled("user", "delete_user"));
+        assert!(config.is_module_enabled("project"));
+
+        let invalid = client_override(json!({ "user": { "enabled": "yes" } }));
+        assert!(invalid.apply(&config).is_err());
+    }
+
+    #[test]
+    fn test_store_resolves_session_before_name_and_persists() {
+        let temp_dir = tempdir().unwrap();
+        let config = config_with_modules();
+        let store = ClientStore::open_in_dir(temp_dir.path()).unwrap();
+
+        store
+            .set("cursor", client_override(json!({ "project": { "enabled": false } })), &config)
+            .unwrap();
+        store
+            .set("session-1", client_override(json!({ "user": { "enabled": false } })), &config)
+            .unwrap();
+
+        let identity = ClientIdentity {
+            name: Some("cursor".to_string()),
+            session: Some("session-1".to_string()),
+        };
+        assert_eq!(store.resolve(&identity).unwrap().0, "session-1");
+        let by_name = ClientIdentity {
+            name: Some("cursor".to_string()),
+            session: None,
+        };
+        assert_eq!(store.resolve(&by_name).unwrap().0, "cursor");
+        assert!(store.resolve(&ClientIdentity::default()).is_none());
+
+        let reopened = ClientStore::open_in_dir(temp_dir.path()).unwrap();
+        assert_eq!(reopened.list().len(), 2);
+        assert!(reopened.remove("session-1").unwrap());
+        assert!(!reopened.remove("session-1").unwrap());
+    }
+}
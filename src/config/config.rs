@@ -6,14 +6,22 @@
 //! 2. Login-based Authentication - Login information is configured first, then authentication is obtained after login
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::fs;
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
-use crate::config::module::GlobalModuleConfig;
+use crate::config::backup::{ConfigBackups, DEFAULT_BACKUP_RETENTION};
+use crate::config::credentials::{deserialize_credential, deserialize_credential_list};
+use crate::config::format::{read_migrated_config_file, write_config_file, ConfigFormat};
+use crate::config::migrate::{current_config_version, ConfigKind, CURRENT_CONFIG_VERSION};
+use crate::config::module::{GlobalModuleConfig, RetryConfig};
+use crate::config::store::StoreConfig;
 
 /// Main configuration structure for MCP-ANY-REST
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
+    /// Configuration schema version
+    #[serde(default = "current_config_version")]
+    pub config_version: u32,
+
     /// Server configuration
     pub server: ServerConfig,
     
@@ -22,9 +30,54 @@ pub struct Config {
     
     /// Authentication configuration
     pub auth: AuthConfig,
+
+    /// TLS settings of the default API (client certificate, CA bundle, SNI override)
+    #[serde(default, skip_serializing_if = "TlsConfig::is_default")]
+    pub tls: TlsConfig,
+
+    /// Outgoing proxy of the API clients; when unset the `HTTP_PROXY`/`HTTPS_PROXY`/`NO_PROXY`
+    /// environment variables apply
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<ProxyConfig>,
+
+    /// Additional named API backends; ZML modules bind to one with `backend: "<name>"`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub backends: HashMap<String, BackendConfig>,
     
     /// Module configuration
     pub module_config: GlobalModuleConfig,
+
+    /// Lock the configuration: the web API rejects changes and nothing is written to disk
+    /// (for deployments where configuration is managed externally, e.g. via GitOps)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub lock: bool,
+
+    /// Variables substituted into `{{name}}` placeholders of base URLs, endpoint URIs, headers
+    /// and preset values (e.g. `{"tenant": "acme"}`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub variables: BTreeMap<String, String>,
+
+    /// Number of timestamped backups kept per configuration file in `backups/` (0 disables backups)
+    #[serde(default = "default_backup_retention", skip_serializing_if = "is_default_backup_retention")]
+    pub backup_retention: usize,
+
+    /// Database holding modules, presets and history instead of the flat files, shared by
+    /// several instances (requires the `sql` feature)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub store: Option<StoreConfig>,
+
+    /// Forward the identity of the MCP client to the API in an impersonation header
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub impersonation: Option<ImpersonationConfig>,
+
+    /// Receive callbacks of the API on `POST /webhooks/:module/:event` and expose them as resources
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhooks: Option<WebhookConfig>,
+
+    /// Require bearer tokens on the MCP HTTP endpoints (`/mcp`, `/sse`), each limited to some
+    /// modules and tools; stdio, unix and tcp connections are not affected
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mcp_auth: Option<McpAuthConfig>,
 }
 
 /// Server configuration
@@ -35,6 +88,122 @@ pub struct ServerConfig {
     
     /// Log level
     pub log_level: String,
+
+    /// Address the web server listens on; `0.0.0.0` or `::` exposes it to the network
+    #[serde(default = "default_bind_address", skip_serializing_if = "is_default_bind_address")]
+    pub bind_address: String,
+
+    /// Serve the web API and MCP endpoints over HTTPS
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub https: Option<HttpsConfig>,
+
+    /// Token that unlocks unmasked credentials (`?reveal=true`) in the web API when sent as
+    /// `Authorization: Bearer <token>`; revealing is disabled without it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub admin_token: Option<String>,
+
+    /// Users of the web API; when any are set, or `admin_token` is, `/config/*` requests must
+    /// authenticate as one of them or with `admin_token`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub admin_users: Vec<AdminUser>,
+
+    /// Path prefix of the web UI, the web API and the MCP endpoints, e.g. `/mcp-any-rest` when a
+    /// reverse proxy forwards that path unchanged; `--base-path` overrides it
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub base_path: String,
+
+    /// Cross-origin requests allowed from browsers, e.g. a dashboard embedding the web UI
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cors: Option<CorsConfig>,
+
+    /// Addresses or CIDR ranges of reverse proxies whose `X-Forwarded-For` and
+    /// `X-Forwarded-Prefix` headers are trusted
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trusted_proxies: Vec<String>,
+
+    /// Serve MCP on a network address without authenticating clients: the HTTP endpoints without
+    /// `mcp_auth` and the tcp transport; both refuse to start otherwise
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub allow_unauthenticated_mcp: bool,
+}
+
+fn default_bind_address() -> String {
+    "127.0.0.1".to_string()
+}
+
+fn is_default_bind_address(address: &String) -> bool {
+    *address == default_bind_address()
+}
+
+/// Certificate of the web server: `cert` and `key`, or a self-signed certificate generated at startup
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HttpsConfig {
+    /// PEM encoded certificate (chain), as a file path or inline PEM
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cert: Option<String>,
+
+    /// PEM encoded private key of `cert`, as a file path, inline PEM or `secret://` reference
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+
+    /// Generate a self-signed certificate for `localhost`, the bind address and `self_signed_names`
+    /// instead of `cert` and `key`
+    #[serde(default)]
+    pub self_signed: bool,
+
+    /// Additional host names and addresses of the self-signed certificate
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub self_signed_names: Vec<String>,
+}
+
+/// Cross-origin resource sharing of the web server
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CorsConfig {
+    /// Origins allowed to call the web server, e.g. `https://dashboard.example.com`; `*` allows any
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_origins: Vec<String>,
+
+    /// Request headers allowed in addition to `Authorization`, `Content-Type`, `If-Match`,
+    /// `Mcp-Session-Id` and `X-Config-Actor`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_headers: Vec<String>,
+
+    /// Allow requests with cookies and HTTP authentication; cannot be combined with `*`
+    #[serde(default)]
+    pub allow_credentials: bool,
+
+    /// Seconds browsers may cache the answer of a preflight request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_age_secs: Option<u64>,
+}
+
+/// User of the web API, authenticating with a bearer token or HTTP basic authentication
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AdminUser {
+    /// User name, recorded as the author of configuration changes
+    pub name: String,
+
+    /// Token sent as `Authorization: Bearer <token>`, or a credential reference (`env://`, `secret://`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+
+    /// Password of basic authentication with `name`, or a credential reference
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+
+    #[serde(default)]
+    pub role: AdminRole,
+}
+
+/// What a web API user may do
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdminRole {
+    /// Read configuration, history and diagnostics
+    Viewer,
+    /// Read and change configuration
+    #[default]
+    Editor,
 }
 
 /// API configuration
@@ -45,6 +214,441 @@ pub struct ApiConfig {
     
     /// Request timeout in seconds
     pub timeout: u64,
+
+    /// Retries after transient failures; modules and methods may override it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryConfig>,
+
+    /// Connection pool and concurrency limits of the API clients, applied at startup
+    #[serde(default, skip_serializing_if = "ConnectionConfig::is_default")]
+    pub connections: ConnectionConfig,
+
+    /// Largest binary response (PDF, image, ...) returned to MCP clients, in bytes (10 MiB when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_binary_response_bytes: Option<usize>,
+
+    /// Directory `file` parameters may name local files in; without it only base64, data: URLs
+    /// and MCP resources are uploaded and no server file is read
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upload_root: Option<String>,
+
+    /// Size limit of JSON and text tool results and the noisy fields dropped from them
+    #[serde(default, skip_serializing_if = "ResponseLimitsConfig::is_default")]
+    pub response_limits: ResponseLimitsConfig,
+
+    /// Return the requests tool calls would send instead of sending them
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub dry_run: bool,
+
+    /// Items per progress notification when array and NDJSON results are streamed to clients
+    /// calling a tool with `stream` and a progress token; results are returned whole when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream_chunk_items: Option<usize>,
+
+    /// Response decompression and request body compression; backends may override it
+    #[serde(default, skip_serializing_if = "CompressionConfig::is_default")]
+    pub compression: CompressionConfig,
+}
+
+/// Compression of API responses and request bodies
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompressionConfig {
+    /// Accept gzip, deflate and brotli responses and decompress them
+    #[serde(default = "default_decompress")]
+    pub decompress: bool,
+
+    /// Encoding request bodies are compressed with; they are sent uncompressed when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request: Option<ContentEncoding>,
+
+    /// Smallest request body compressed, in bytes
+    #[serde(default = "default_min_request_bytes")]
+    pub min_request_bytes: usize,
+}
+
+impl CompressionConfig {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            decompress: default_decompress(),
+            request: None,
+            min_request_bytes: default_min_request_bytes(),
+        }
+    }
+}
+
+fn default_decompress() -> bool {
+    true
+}
+
+fn default_min_request_bytes() -> usize {
+    1024
+}
+
+/// `Content-Encoding` of compressed request bodies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentEncoding {
+    Gzip,
+    Deflate,
+    #[serde(rename = "br")]
+    Brotli,
+}
+
+impl ContentEncoding {
+    /// Value of the `Content-Encoding` header
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContentEncoding::Gzip => "gzip",
+            ContentEncoding::Deflate => "deflate",
+            ContentEncoding::Brotli => "br",
+        }
+    }
+}
+
+/// Connection pool and limits on requests in flight
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConnectionConfig {
+    /// Idle connections kept open per host; unlimited when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pool_max_idle_per_host: Option<usize>,
+
+    /// Seconds an idle connection is kept open (90 when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pool_idle_timeout_secs: Option<u64>,
+
+    /// Seconds allowed for establishing a connection, TLS handshake included; bounded by the
+    /// request timeout alone when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connect_timeout_secs: Option<u64>,
+
+    /// API requests in flight at once over all hosts; further tool calls wait for a slot
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent: Option<usize>,
+
+    /// API requests in flight at once to a single host
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent_per_host: Option<usize>,
+}
+
+impl ConnectionConfig {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Truncation of large JSON and text tool results
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResponseLimitsConfig {
+    /// Largest result returned, in bytes of JSON or text; larger results are truncated. Unlimited
+    /// when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_bytes: Option<usize>,
+
+    /// Items first kept of each array of a truncated result (100 when unset); halved until the
+    /// result fits
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_array_items: Option<usize>,
+
+    /// Field names removed from every JSON result, at any depth (e.g. `_links`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub omit_fields: Vec<String>,
+}
+
+impl ResponseLimitsConfig {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Identity of the MCP client sent with API requests, e.g. as `X-On-Behalf-Of`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImpersonationConfig {
+    /// Header added to API requests
+    #[serde(default = "default_impersonation_header")]
+    pub header: String,
+
+    /// Header of streamable HTTP requests naming the user (e.g. `X-User`); the client's
+    /// `clientInfo.name` is used when unset or missing from the request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identity_header: Option<String>,
+
+    /// Value sent for an identity (e.g. agent name -> API user); other identities are sent as they are
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub mapping: HashMap<String, String>,
+
+    /// Send the header only for identities listed in `mapping`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub mapped_only: bool,
+}
+
+impl ImpersonationConfig {
+    /// Value of `header` for a client, from its `identity_header` value or else its `clientInfo.name`;
+    /// `None` when the client has no identity or it is not mapped under `mapped_only`
+    pub fn value_for(&self, header_value: Option<&str>, client_name: Option<&str>) -> Option<String> {
+        let identity = header_value
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .or_else(|| client_name.map(str::trim).filter(|name| !name.is_empty()))?;
+        match self.mapping.get(identity) {
+            Some(value) => Some(value.clone()),
+            None if self.mapped_only => None,
+            None => Some(identity.to_string()),
+        }
+    }
+}
+
+impl Default for ImpersonationConfig {
+    fn default() -> Self {
+        Self {
+            header: default_impersonation_header(),
+            identity_header: None,
+            mapping: HashMap::new(),
+            mapped_only: false,
+        }
+    }
+}
+
+fn default_impersonation_header() -> String {
+    "X-On-Behalf-Of".to_string()
+}
+
+/// Callbacks of the API received by the web server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    /// HMAC-SHA256 key, or a `secret://` reference, signing the callbacks; unsigned callbacks
+    /// are accepted when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+
+    /// Header carrying the `sha256=<hex>` signature of the body
+    #[serde(default = "default_webhook_signature_header")]
+    pub signature_header: String,
+
+    /// Events kept per module and event name, oldest dropped first
+    #[serde(default = "default_webhook_max_events")]
+    pub max_events: usize,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            secret: None,
+            signature_header: default_webhook_signature_header(),
+            max_events: default_webhook_max_events(),
+        }
+    }
+}
+
+/// Tokens accepted by the MCP HTTP endpoints
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct McpAuthConfig {
+    /// Static tokens
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tokens: Vec<McpToken>,
+
+    /// OAuth 2.0 token introspection (RFC 7662) of tokens that are not static
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub introspection: Option<TokenIntrospectionConfig>,
+}
+
+/// Static token of the MCP HTTP endpoints
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct McpToken {
+    /// Name of the token holder, shown in logs
+    pub name: String,
+
+    /// Token sent as `Authorization: Bearer <token>`, or a credential reference (`env://`, `secret://`)
+    pub token: String,
+
+    /// Modules whose tools, prompts and resources the token may use
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modules: Vec<String>,
+
+    /// Further tools the token may call, as `<module>/<method>`; with neither `modules` nor
+    /// `tools` every module is allowed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<String>,
+}
+
+/// Introspection endpoint validating OAuth access tokens
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenIntrospectionConfig {
+    /// URL of the introspection endpoint
+    pub url: String,
+
+    /// Client credentials sent with HTTP basic authentication
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+
+    /// Client secret, or a credential reference
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<String>,
+
+    /// Modules allowed to every active token, in addition to its `module:<name>` scopes
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modules: Vec<String>,
+
+    /// Tools allowed to every active token, in addition to its `tool:<module>/<method>` scopes
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<String>,
+
+    /// Seconds an active token is trusted before it is introspected again
+    #[serde(default = "default_introspection_cache_secs")]
+    pub cache_secs: u64,
+}
+
+fn default_introspection_cache_secs() -> u64 {
+    60
+}
+
+fn default_webhook_signature_header() -> String {
+    "X-Hub-Signature-256".to_string()
+}
+
+fn default_webhook_max_events() -> usize {
+    100
+}
+
+/// Named API backend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackendConfig {
+    /// Base URL for API requests to this backend
+    pub base_url: String,
+
+    /// Request timeout in seconds (defaults to `api.timeout`)
+    #[serde(default)]
+    pub timeout: Option<u64>,
+
+    /// Authentication configuration (defaults to the main `auth` configuration)
+    #[serde(default)]
+    pub auth: Option<AuthConfig>,
+
+    /// Retries after transient failures (defaults to `api.retry`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryConfig>,
+
+    /// TLS settings
+    #[serde(default)]
+    pub tls: TlsConfig,
+
+    /// Outgoing proxy (defaults to the main `proxy`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<ProxyConfig>,
+
+    /// Compression (defaults to `api.compression`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compression: Option<CompressionConfig>,
+}
+
+/// Outgoing HTTP or SOCKS proxy of the API clients
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProxyConfig {
+    /// Proxy URL (`http://`, `https://`, `socks5://` or `socks5h://`); empty connects directly,
+    /// ignoring the proxy environment variables
+    pub url: String,
+
+    /// User name for proxy authentication
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+
+    /// Password for proxy authentication, or a `secret://` reference
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+
+    /// Hosts, domains (`.example.com`) and IP ranges (`10.0.0.0/8`) reached without the proxy
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub no_proxy: Vec<String>,
+}
+
+/// TLS settings for an API backend
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TlsConfig {
+    /// Accept invalid or self-signed server certificates (disables verification; opt-in only)
+    #[serde(default)]
+    pub accept_invalid_certs: bool,
+
+    /// Additional PEM encoded CA certificates, as a file path or inline PEM bundle
+    #[serde(default)]
+    pub ca_cert: Option<String>,
+
+    /// PEM encoded client certificate (chain) for mutual TLS, as a file path or inline PEM
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_cert: Option<String>,
+
+    /// PKCS#8 PEM encoded private key of `client_cert`, as a file path, inline PEM or `secret://` reference
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_key: Option<String>,
+
+    /// Host name sent in SNI and verified against the server certificate instead of the base URL
+    /// host; requests are addressed to this name and connect to the base URL host
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_name: Option<String>,
+
+    /// SHA-256 fingerprints (hex, `:` separators allowed) of the accepted server certificates;
+    /// when set, a server certificate is accepted only if it matches one of them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pinned_fingerprints: Vec<String>,
+}
+
+impl TlsConfig {
+    /// Whether all settings are the defaults
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Decoded `pinned_fingerprints`
+    pub fn pins(&self) -> anyhow::Result<Vec<[u8; 32]>> {
+        self.pinned_fingerprints
+            .iter()
+            .map(|fingerprint| {
+                parse_fingerprint(fingerprint)
+                    .ok_or_else(|| anyhow::anyhow!("Invalid SHA-256 certificate fingerprint: {}", fingerprint))
+            })
+            .collect()
+    }
+
+    /// URL requests to `base_url` are sent to: the base URL with its host replaced by `server_name`
+    pub fn request_url(&self, base_url: &str) -> String {
+        let Some(server_name) = &self.server_name else {
+            return base_url.to_string();
+        };
+        let Ok(mut url) = url::Url::parse(base_url) else {
+            return base_url.to_string();
+        };
+        if url.set_host(Some(server_name)).is_err() {
+            return base_url.to_string();
+        }
+        // `Url` always adds a trailing slash to an empty path
+        let url = url.to_string();
+        if base_url.ends_with('/') { url } else { url.trim_end_matches('/').to_string() }
+    }
+}
+
+fn default_backup_retention() -> usize {
+    DEFAULT_BACKUP_RETENTION
+}
+
+fn is_default_backup_retention(retention: &usize) -> bool {
+    *retention == DEFAULT_BACKUP_RETENTION
+}
+
+/// Parse a SHA-256 fingerprint written as 64 hex digits, optionally separated by `:`
+fn parse_fingerprint(value: &str) -> Option<[u8; 32]> {
+    let digits: Vec<char> = value.trim().chars().filter(|c| *c != ':').collect();
+    if digits.len() != 64 {
+        return None;
+    }
+    let mut fingerprint = [0u8; 32];
+    for (byte, pair) in fingerprint.iter_mut().zip(digits.chunks(2)) {
+        *byte = u8::from_str_radix(&pair.iter().collect::<String>(), 16).ok()?;
+    }
+    Some(fingerprint)
 }
 
 /// Authentication configuration
@@ -59,6 +663,26 @@ pub struct AuthConfig {
     /// Login authentication configuration (used when mode is "login")
     pub login_config: Option<LoginAuthConfig>,
     
+    /// JWT authentication configuration (used when mode is "jwt")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jwt_config: Option<JwtAuthConfig>,
+    
+    /// AWS SigV4 signing configuration (used when mode is "aws_sigv4")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aws_sigv4_config: Option<AwsSigV4Config>,
+    
+    /// HMAC signing configuration (used when mode is "hmac")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hmac_config: Option<HmacAuthConfig>,
+    
+    /// Cookie session configuration (used when mode is "cookie")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cookie_config: Option<CookieSessionConfig>,
+    
+    /// Registered custom strategy and its settings (used when mode is "custom")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_config: Option<CustomAuthConfig>,
+    
     /// Token expiry time in seconds
     pub token_expiry: u64,
     
@@ -67,6 +691,11 @@ pub struct AuthConfig {
     
     /// Maximum retry attempts for authentication
     pub max_retry_attempts: u32,
+    
+    /// Response body texts that mark a rejected token even when the status is not 401 or 403,
+    /// e.g. `"token expired"` in a 200 response envelope
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reauth_markers: Vec<String>,
 }
 
 /// Authentication mode
@@ -78,6 +707,22 @@ pub enum AuthMode {
     
     /// Login-based authentication - login information is configured first, then authentication is obtained after login
     Login,
+    
+    /// JWT authentication - tokens are signed locally with a private key
+    Jwt,
+    
+    /// AWS Signature Version 4 - every request is signed with AWS credentials
+    #[serde(rename = "aws_sigv4")]
+    AwsSigV4,
+    
+    /// HMAC signing - every request carries an HMAC over its method, path, timestamp and body
+    Hmac,
+    
+    /// Cookie session - a login form sets session cookies that are sent with every request
+    Cookie,
+    
+    /// Custom - a strategy registered by an application embedding mcp-any-rest
+    Custom,
 }
 
 /// Direct authentication configuration
@@ -86,20 +731,58 @@ pub struct DirectAuthConfig {
     /// Type of direct authentication
     pub auth_type: DirectAuthType,
     
-    /// Authentication token (for token-based authentication)
+    /// Authentication token (for token-based authentication); a plain value, a `secret://`
+    /// reference or a credential source such as `{ "source": "env", "name": "API_TOKEN" }`
+    #[serde(default, deserialize_with = "deserialize_credential")]
     pub token: Option<String>,
     
     /// API key name (for API key authentication)
     pub api_key_name: Option<String>,
     
-    /// Username (for basic authentication)
+    /// Username (for basic authentication); accepts a credential source like `token`
+    #[serde(default, deserialize_with = "deserialize_credential")]
     pub username: Option<String>,
     
-    /// Password (for basic authentication)
+    /// Password (for basic authentication); accepts a credential source like `token`
+    #[serde(default, deserialize_with = "deserialize_credential")]
     pub password: Option<String>,
     
     /// Custom headers (for custom headers authentication)
     pub custom_headers: Option<HashMap<String, String>>,
+    
+    /// API keys used in turn instead of `token` (for bearer, token and API key authentication)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_pool: Option<ApiKeyPoolConfig>,
+}
+
+/// Pool of API keys spreading requests over several keys
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ApiKeyPoolConfig {
+    /// Keys; plain values, `secret://` references or credential sources like `token`
+    #[serde(deserialize_with = "deserialize_credential_list")]
+    pub api_keys: Vec<String>,
+    
+    /// How the key of the next request is chosen
+    #[serde(default)]
+    pub rotation: KeyRotation,
+    
+    /// Seconds a key answered with 429 is left out when the response has no `Retry-After`
+    #[serde(default = "default_key_cooldown_secs")]
+    pub cooldown_secs: u64,
+}
+
+fn default_key_cooldown_secs() -> u64 {
+    60
+}
+
+/// Rotation policy of an API key pool
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyRotation {
+    /// Use the keys in turn
+    RoundRobin,
+    /// Stay on one key until the API throttles it with 429
+    Failover,
 }
 
 /// Direct authentication type
@@ -120,6 +803,15 @@ pub enum DirectAuthType {
     
     /// Custom headers authentication
     CustomHeaders,
+    
+    /// HTTP Digest authentication (RFC 7616) with `username` and `password`
+    Digest,
+    
+    /// Windows integrated authentication with the `NTLM` scheme (`ntlm` feature)
+    Ntlm,
+    
+    /// Windows integrated authentication with the `Negotiate` scheme (`ntlm` feature)
+    Negotiate,
 }
 
 /// Login authentication configuration
@@ -151,6 +843,331 @@ pub struct LoginAuthConfig {
     
     /// HTTP method for token refresh request (optional)
     pub refresh_method: Option<HttpMethod>,
+
+    /// OAuth2 grant settings (optional); when set, `url` is the token endpoint and the body is built per RFC 6749
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oauth2: Option<OAuth2Config>,
+
+    /// Where the refresh token is found in login responses and sent to `refresh_url` (optional;
+    /// defaults apply when only `refresh_url` is set)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<RefreshTokenConfig>,
+}
+
+/// Refresh token handling for `refresh_url`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RefreshTokenConfig {
+    /// Where the refresh token is read from in login and refresh responses
+    #[serde(default = "default_refresh_token_source")]
+    pub source_location: TokenLocation,
+
+    /// Key of the refresh token in the response; JSON bodies accept dot paths and JSONPath
+    #[serde(default = "default_refresh_token_key")]
+    pub source_key: String,
+
+    /// Where the refresh token is placed in the refresh request
+    #[serde(default = "default_refresh_token_target")]
+    pub target_location: TokenTargetLocation,
+
+    /// Key of the refresh token in the refresh request
+    #[serde(default = "default_refresh_token_key")]
+    pub target_key: String,
+
+    /// Text put before the refresh token, e.g. `Bearer ` for an `Authorization` header
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+}
+
+impl Default for RefreshTokenConfig {
+    fn default() -> Self {
+        Self {
+            source_location: default_refresh_token_source(),
+            source_key: default_refresh_token_key(),
+            target_location: default_refresh_token_target(),
+            target_key: default_refresh_token_key(),
+            prefix: None,
+        }
+    }
+}
+
+fn default_refresh_token_source() -> TokenLocation {
+    TokenLocation::Body
+}
+
+fn default_refresh_token_key() -> String {
+    "refresh_token".to_string()
+}
+
+fn default_refresh_token_target() -> TokenTargetLocation {
+    TokenTargetLocation::Body
+}
+
+/// OAuth2 token request configuration
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct OAuth2Config {
+    /// Grant used to obtain an access token
+    #[serde(default)]
+    pub grant_type: OAuth2GrantType,
+
+    /// Client identifier
+    pub client_id: String,
+
+    /// Client secret (optional for public clients)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<String>,
+
+    /// Resource owner username (for the password grant)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+
+    /// Resource owner password (for the password grant)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+
+    /// Refresh token (for the refresh_token grant). With the authorization_code grant, a `secret://`
+    /// reference here is where the refresh token obtained interactively is persisted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+
+    /// Authorization endpoint (for the authorization_code grant)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authorization_url: Option<String>,
+
+    /// Redirect URI registered with the provider; a local listener on it receives the authorization code
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redirect_uri: Option<String>,
+
+    /// Requested scopes
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scopes: Vec<String>,
+
+    /// Requested audience (`audience` parameter), for providers issuing tokens per API
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audience: Option<String>,
+
+    /// How the client authenticates to the token endpoint
+    #[serde(default)]
+    pub client_auth: OAuth2ClientAuth,
+}
+
+/// OAuth2 grant type
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OAuth2GrantType {
+    ClientCredentials,
+    Password,
+    RefreshToken,
+    /// Interactive consent in a browser, for APIs without non-interactive grants
+    AuthorizationCode,
+}
+
+impl OAuth2GrantType {
+    /// Value of the `grant_type` parameter
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ClientCredentials => "client_credentials",
+            Self::Password => "password",
+            Self::RefreshToken => "refresh_token",
+            Self::AuthorizationCode => "authorization_code",
+        }
+    }
+}
+
+/// OAuth2 client authentication method
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OAuth2ClientAuth {
+    /// HTTP Basic authentication with the client id and secret
+    Basic,
+
+    /// `client_id` and `client_secret` in the request body
+    Body,
+}
+
+/// JWT authentication configuration
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct JwtAuthConfig {
+    /// Signing algorithm
+    #[serde(default)]
+    pub algorithm: JwtAlgorithm,
+    
+    /// PEM encoded private key (or a `secret://` reference to it)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub private_key: Option<String>,
+    
+    /// Path of a PEM encoded private key file (used when `private_key` is not set)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub private_key_file: Option<String>,
+    
+    /// Key id placed in the `kid` header
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<String>,
+    
+    /// Issuer (`iss` claim), e.g. a GitHub App id or a service account email
+    pub issuer: String,
+    
+    /// Subject (`sub` claim)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    
+    /// Audience (`aud` claim)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audience: Option<String>,
+    
+    /// Token lifetime in seconds
+    #[serde(default = "default_jwt_ttl")]
+    pub ttl: u64,
+    
+    /// Additional claims, e.g. `scope` for Google service accounts
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub claims: serde_json::Map<String, serde_json::Value>,
+    
+    /// Header carrying the token
+    #[serde(default = "default_jwt_header")]
+    pub header_name: String,
+    
+    /// Token format in the header
+    #[serde(default)]
+    pub format: TokenFormat,
+}
+
+/// AWS Signature Version 4 configuration
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AwsSigV4Config {
+    /// Access key id (defaults to `AWS_ACCESS_KEY_ID`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_key_id: Option<String>,
+    
+    /// Secret access key (defaults to `AWS_SECRET_ACCESS_KEY`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret_access_key: Option<String>,
+    
+    /// Session token of temporary credentials (defaults to `AWS_SESSION_TOKEN`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_token: Option<String>,
+    
+    /// AWS region, e.g. `us-east-1`
+    pub region: String,
+    
+    /// Service name in the credential scope
+    #[serde(default = "default_sigv4_service")]
+    pub service: String,
+}
+
+fn default_sigv4_service() -> String {
+    "execute-api".to_string()
+}
+
+/// HMAC request signing configuration
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HmacAuthConfig {
+    /// Signing secret (or a `secret://` reference to it)
+    pub secret: String,
+    
+    /// How `secret` is encoded; raw bytes of the string when not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret_encoding: Option<HmacEncoding>,
+    
+    /// API key sent alongside the signature
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<String>,
+    
+    /// Digest algorithm
+    #[serde(default)]
+    pub algorithm: HmacAlgorithm,
+    
+    /// Canonical string template; `{method}`, `{path}`, `{query}`, `{path_and_query}`, `{timestamp}`,
+    /// `{body}` and `{key_id}` are replaced with the request's values
+    #[serde(default = "default_hmac_template")]
+    pub template: String,
+    
+    /// Signature encoding
+    #[serde(default)]
+    pub encoding: HmacEncoding,
+    
+    /// Timestamp format
+    #[serde(default)]
+    pub timestamp_format: HmacTimestampFormat,
+    
+    /// Header carrying the signature
+    #[serde(default = "default_hmac_signature_header")]
+    pub signature_header: String,
+    
+    /// Prefix of the signature header value, e.g. `HMAC-SHA256 `
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature_prefix: Option<String>,
+    
+    /// Header carrying the timestamp (not sent when null)
+    #[serde(default = "default_hmac_timestamp_header")]
+    pub timestamp_header: Option<String>,
+    
+    /// Header carrying `key_id`
+    #[serde(default = "default_hmac_key_id_header")]
+    pub key_id_header: String,
+}
+
+/// HMAC digest algorithm
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HmacAlgorithm {
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+/// Binary-to-text encoding of HMAC secrets and signatures
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HmacEncoding {
+    Hex,
+    Base64,
+}
+
+/// Timestamp format used in HMAC signatures
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HmacTimestampFormat {
+    /// Unix time in seconds
+    Seconds,
+    /// Unix time in milliseconds
+    Milliseconds,
+    /// RFC 3339 / ISO 8601
+    Iso8601,
+}
+
+fn default_hmac_template() -> String {
+    "{timestamp}{method}{path_and_query}{body}".to_string()
+}
+
+fn default_hmac_signature_header() -> String {
+    "X-Signature".to_string()
+}
+
+fn default_hmac_timestamp_header() -> Option<String> {
+    Some("X-Timestamp".to_string())
+}
+
+fn default_hmac_key_id_header() -> String {
+    "X-Api-Key".to_string()
+}
+
+/// JWT signing algorithm
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum JwtAlgorithm {
+    RS256,
+    RS384,
+    RS512,
+    ES256,
+    ES384,
+    EdDSA,
+}
+
+fn default_jwt_ttl() -> u64 {
+    600
+}
+
+fn default_jwt_header() -> String {
+    "Authorization".to_string()
 }
 
 /// Login authentication type
@@ -193,6 +1210,49 @@ pub enum ResponseFormat {
     Text,
 }
 
+/// Cookie session authentication configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CookieSessionConfig {
+    /// URL the login form is submitted to
+    pub login_url: String,
+    
+    /// HTTP method of the login request
+    #[serde(default = "default_cookie_login_method")]
+    pub method: HttpMethod,
+    
+    /// Headers for the login request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub headers: Option<HashMap<String, String>>,
+    
+    /// Login form fields; values may be `secret://` references
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<LoginRequestBody>,
+    
+    /// Cookie that must be set for a login to succeed (any cookie when not set)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_cookie: Option<String>,
+    
+    /// Path of the login page; requests redirected there have lost their session
+    /// (defaults to the path of `login_url`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub login_page: Option<String>,
+}
+
+fn default_cookie_login_method() -> HttpMethod {
+    HttpMethod::Post
+}
+
+/// Custom authentication configuration
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CustomAuthConfig {
+    /// Name the strategy was registered under with `AuthServiceFactory::register_auth_strategy`
+    pub strategy: String,
+    
+    /// Settings passed to the strategy factory as they are
+    #[serde(default)]
+    pub settings: serde_json::Value,
+}
+
 /// Login request body
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LoginRequestBody {
@@ -217,7 +1277,8 @@ pub struct TokenExtractionItem {
     /// Token location in the response
     pub source_location: TokenLocation,
     
-    /// Token key in the response
+    /// Token key in the response; JSON bodies also accept dot paths and JSONPath
+    /// (`data.auth.access_token`, `$.items[0].token`)
     pub source_key: String,
     
     /// Token format
@@ -270,10 +1331,21 @@ pub enum TokenFormat {
 impl Default for Config {
     fn default() -> Self {
         Self {
+            config_version: CURRENT_CONFIG_VERSION,
             server: ServerConfig::default(),
             api: ApiConfig::default(),
             auth: AuthConfig::default(),
+            tls: TlsConfig::default(),
+            proxy: None,
+            backends: HashMap::new(),
             module_config: GlobalModuleConfig::default(),
+            lock: false,
+            variables: BTreeMap::new(),
+            backup_retention: DEFAULT_BACKUP_RETENTION,
+            store: None,
+            impersonation: None,
+            webhooks: None,
+            mcp_auth: None,
         }
     }
 }
@@ -283,6 +1355,14 @@ impl Default for ServerConfig {
         Self {
             port: 8082,
             log_level: "info".to_string(),
+            bind_address: default_bind_address(),
+            https: None,
+            admin_token: None,
+            admin_users: Vec::new(),
+            base_path: String::new(),
+            cors: None,
+            trusted_proxies: Vec::new(),
+            allow_unauthenticated_mcp: false,
         }
     }
 }
@@ -292,6 +1372,27 @@ impl Default for ApiConfig {
         Self {
             base_url: "https://api.example.com".to_string(),
             timeout: 30,
+            retry: None,
+            connections: ConnectionConfig::default(),
+            max_binary_response_bytes: None,
+            upload_root: None,
+            response_limits: ResponseLimitsConfig::default(),
+            dry_run: false,
+            stream_chunk_items: None,
+            compression: CompressionConfig::default(),
+        }
+    }
+}
+
+impl Default for TlsConfig {
+    fn default() -> Self {
+        Self {
+            accept_invalid_certs: false,
+            ca_cert: None,
+            client_cert: None,
+            client_key: None,
+            server_name: None,
+            pinned_fingerprints: Vec::new(),
         }
     }
 }
@@ -302,9 +1403,15 @@ impl Default for AuthConfig {
             mode: AuthMode::Direct,
             direct_config: Some(DirectAuthConfig::default()),
             login_config: None,
+            jwt_config: None,
+            aws_sigv4_config: None,
+            hmac_config: None,
+            cookie_config: None,
+            custom_config: None,
             token_expiry: 3600,
             refresh_buffer: 300,
             max_retry_attempts: 3,
+            reauth_markers: Vec::new(),
         }
     }
 }
@@ -318,6 +1425,7 @@ impl Default for DirectAuthConfig {
             username: None,
             password: None,
             custom_headers: None,
+            key_pool: None,
         }
     }
 }
@@ -334,6 +1442,8 @@ impl Default for LoginAuthConfig {
             token_extraction: TokenExtraction::default(),
             refresh_url: None,
             refresh_method: None,
+            oauth2: None,
+            refresh_token: None,
         }
     }
 }
@@ -356,6 +1466,84 @@ impl Default for LoginAuthType {
     }
 }
 
+impl Default for HmacAlgorithm {
+    fn default() -> Self {
+        Self::Sha256
+    }
+}
+
+impl Default for HmacEncoding {
+    fn default() -> Self {
+        Self::Hex
+    }
+}
+
+impl Default for HmacTimestampFormat {
+    fn default() -> Self {
+        Self::Seconds
+    }
+}
+
+impl Default for HmacAuthConfig {
+    fn default() -> Self {
+        Self {
+            secret: String::new(),
+            secret_encoding: None,
+            key_id: None,
+            algorithm: HmacAlgorithm::default(),
+            template: default_hmac_template(),
+            encoding: HmacEncoding::default(),
+            timestamp_format: HmacTimestampFormat::default(),
+            signature_header: default_hmac_signature_header(),
+            signature_prefix: None,
+            timestamp_header: default_hmac_timestamp_header(),
+            key_id_header: default_hmac_key_id_header(),
+        }
+    }
+}
+
+impl Default for JwtAlgorithm {
+    fn default() -> Self {
+        Self::RS256
+    }
+}
+
+impl Default for JwtAuthConfig {
+    fn default() -> Self {
+        Self {
+            algorithm: JwtAlgorithm::default(),
+            private_key: None,
+            private_key_file: None,
+            key_id: None,
+            issuer: String::new(),
+            subject: None,
+            audience: None,
+            ttl: default_jwt_ttl(),
+            claims: serde_json::Map::new(),
+            header_name: default_jwt_header(),
+            format: TokenFormat::default(),
+        }
+    }
+}
+
+impl Default for OAuth2GrantType {
+    fn default() -> Self {
+        Self::ClientCredentials
+    }
+}
+
+impl Default for OAuth2ClientAuth {
+    fn default() -> Self {
+        Self::Basic
+    }
+}
+
+impl Default for KeyRotation {
+    fn default() -> Self {
+        Self::RoundRobin
+    }
+}
+
 impl Default for BodyFormat {
     fn default() -> Self {
         Self::Json
@@ -409,17 +1597,16 @@ impl Config {
         Self::default()
     }
     
-    /// Load configuration from a file
+    /// Load configuration from a file (JSON, YAML or TOML, detected by extension),
+    /// migrating it to the current schema version if needed
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
-        let content = fs::read_to_string(path)?;
-        let config: Config = serde_json::from_str(&content)?;
-        Ok(config)
+        let backups = ConfigBackups::beside(&path);
+        Ok(read_migrated_config_file(path, ConfigFormat::Json, ConfigKind::Main, &backups)?)
     }
     
-    /// Save configuration to a file
+    /// Save configuration to a file (JSON, YAML or TOML, detected by extension)
     pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn std::error::Error>> {
-        let content = serde_json::to_string_pretty(self)?;
-        fs::write(path, content)?;
+        write_config_file(path, self, ConfigFormat::Json)?;
         Ok(())
     }
     
@@ -439,6 +1626,7 @@ impl Config {
             username,
             password,
             custom_headers,
+            key_pool: None,
         };
         
         Self {
@@ -446,6 +1634,11 @@ impl Config {
                 mode: AuthMode::Direct,
                 direct_config: Some(direct_config),
                 login_config: None,
+                jwt_config: None,
+                aws_sigv4_config: None,
+                hmac_config: None,
+                cookie_config: None,
+                custom_config: None,
                 ..Default::default()
             },
             ..Default::default()
@@ -474,6 +1667,8 @@ impl Config {
             token_extraction,
             refresh_url,
             refresh_method,
+            oauth2: None,
+            refresh_token: None,
         };
         
         Self {
@@ -481,6 +1676,11 @@ impl Config {
                 mode: AuthMode::Login,
                 direct_config: None,
                 login_config: Some(login_config),
+                jwt_config: None,
+                aws_sigv4_config: None,
+                hmac_config: None,
+                cookie_config: None,
+                custom_config: None,
                 ..Default::default()
             },
             ..Default::default()
@@ -854,6 +2054,52 @@ impl Config {
         self
     }
     
+    /// Add a named API backend
+    pub fn with_backend(mut self, name: impl Into<String>, backend: BackendConfig) -> Self {
+        self.backends.insert(name.into(), backend);
+        self
+    }
+
+    /// Base URL of a backend; `None` selects the default `api.base_url`
+    pub fn backend_base_url(&self, backend: Option<&str>) -> Option<&str> {
+        match backend {
+            None => Some(&self.api.base_url),
+            Some(name) => self.backends.get(name).map(|b| b.base_url.as_str()),
+        }
+    }
+    
+    /// Retry settings of a backend (`None` selects the default one), falling back to `api.retry`
+    pub fn backend_retry(&self, backend: Option<&str>) -> Option<&RetryConfig> {
+        backend
+            .and_then(|name| self.backends.get(name))
+            .and_then(|b| b.retry.as_ref())
+            .or(self.api.retry.as_ref())
+    }
+    
+    /// TLS settings of a backend; `None` selects the default `tls`
+    pub fn backend_tls(&self, backend: Option<&str>) -> Option<&TlsConfig> {
+        match backend {
+            None => Some(&self.tls),
+            Some(name) => self.backends.get(name).map(|b| &b.tls),
+        }
+    }
+    
+    /// Proxy settings of a backend (`None` selects the default one), falling back to `proxy`
+    pub fn backend_proxy(&self, backend: Option<&str>) -> Option<&ProxyConfig> {
+        backend
+            .and_then(|name| self.backends.get(name))
+            .and_then(|b| b.proxy.as_ref())
+            .or(self.proxy.as_ref())
+    }
+
+    /// Compression settings of a backend, falling back to `api.compression`
+    pub fn backend_compression(&self, backend: Option<&str>) -> &CompressionConfig {
+        backend
+            .and_then(|name| self.backends.get(name))
+            .and_then(|b| b.compression.as_ref())
+            .unwrap_or(&self.api.compression)
+    }
+    
     /// Set token expiry time
     pub fn with_token_expiry(mut self, token_expiry: u64) -> Self {
         self.auth.token_expiry = token_expiry;
@@ -1060,6 +2306,61 @@ mod tests {
         assert_eq!(config.auth.max_retry_attempts, 5);
     }
 
+    #[test]
+    fn test_backends() {
+        let config = Config::default().with_backend(
+            "billing",
+            BackendConfig {
+                base_url: "https://billing.example.com".to_string(),
+                timeout: None,
+                auth: Some(AuthConfig::default()),
+                retry: None,
+                tls: TlsConfig::default(),
+                proxy: Some(ProxyConfig { url: String::new(), ..ProxyConfig::default() }),
+                compression: Some(CompressionConfig {
+                    request: Some(ContentEncoding::Brotli),
+                    ..CompressionConfig::default()
+                }),
+            },
+        );
+
+        assert_eq!(config.backend_base_url(None), Some("https://api.example.com"));
+        assert!(config.backend_proxy(None).is_none());
+        assert_eq!(config.backend_proxy(Some("billing")).unwrap().url, "");
+        assert_eq!(config.backend_compression(None).request, None);
+        assert_eq!(config.backend_compression(Some("billing")).request, Some(ContentEncoding::Brotli));
+        assert_eq!(config.backend_base_url(Some("billing")), Some("https://billing.example.com"));
+        assert_eq!(config.backend_base_url(Some("missing")), None);
+
+        let serialized = serde_json::to_string(&config).unwrap();
+        let deserialized: Config = serde_json::from_str(&serialized).unwrap();
+        assert!(!deserialized.backends["billing"].tls.accept_invalid_certs);
+
+        // Certificate verification stays on unless a backend opts out
+        let minimal: BackendConfig = serde_json::from_str(r#"{ "base_url": "https://crm" }"#).unwrap();
+        assert!(minimal.auth.is_none());
+        assert!(minimal.tls.ca_cert.is_none());
+        assert!(!minimal.tls.accept_invalid_certs);
+    }
+
+    #[test]
+    fn test_impersonation_identity() {
+        let impersonation: ImpersonationConfig = serde_json::from_str(
+            r#"{ "identity_header": "X-User", "mapping": { "claude-desktop": "svc-agent" } }"#,
+        )
+        .unwrap();
+        assert_eq!(impersonation.header, "X-On-Behalf-Of");
+
+        assert_eq!(impersonation.value_for(Some("alice"), Some("claude-desktop")).as_deref(), Some("alice"));
+        assert_eq!(impersonation.value_for(Some(" "), Some("claude-desktop")).as_deref(), Some("svc-agent"));
+        assert_eq!(impersonation.value_for(None, Some("cursor")).as_deref(), Some("cursor"));
+        assert_eq!(impersonation.value_for(None, None), None);
+
+        let mapped_only = ImpersonationConfig { mapped_only: true, ..impersonation };
+        assert_eq!(mapped_only.value_for(None, Some("cursor")), None);
+        assert_eq!(mapped_only.value_for(None, Some("claude-desktop")).as_deref(), Some("svc-agent"));
+    }
+
     #[test]
     fn test_serialization_deserialization() {
         let config = Config::with_bearer_auth("test-token".to_string())
@@ -0,0 +1,304 @@
+//! Credential sources for MCP-ANY-REST
+//! Credentials can be read from the environment, a file, the OS keyring or a command instead of
+//! sitting in config.json. The configuration describes them as objects such as
+//! `{ "source": "env", "name": "API_TOKEN" }` or `{ "exec": "vault kv get -field=token secret/api" }`,
+//! which are kept as references (`env://API_TOKEN`, `file://<path>`, `keyring://<service>/<account>`,
+//! `exec://<command>`) and only resolved when the authentication service needs the value.
+
+use super::secrets::{is_secret_ref, resolve_secret_ref};
+use anyhow::{bail, Context, Result};
+use log::debug;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::process::Command;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+/// Reference to an environment variable
+pub const ENV_SCHEME: &str = "env://";
+
+/// Reference to a file holding the credential
+pub const FILE_SCHEME: &str = "file://";
+
+/// Reference to an entry of the OS keyring
+pub const KEYRING_SCHEME: &str = "keyring://";
+
+/// Reference to a command printing the credential
+pub const EXEC_SCHEME: &str = "exec://";
+
+/// How long values read from the keyring or a command are reused before running it again
+pub const CREDENTIAL_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// Keyring and command values by reference, with when they were read
+static RESOLVED: RwLock<BTreeMap<String, (Instant, String)>> = RwLock::new(BTreeMap::new());
+
+/// Where a credential comes from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "source", rename_all = "lowercase")]
+pub enum CredentialSource {
+    /// Environment variable
+    Env { name: String },
+    /// File whose content is the credential; a trailing newline is ignored
+    File { path: String },
+    /// OS keyring entry (macOS Keychain through `security`, Secret Service through `secret-tool`)
+    Keyring {
+        service: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        account: Option<String>,
+    },
+    /// Shell command printing the credential, e.g. `vault kv get -field=token secret/api`
+    Exec {
+        #[serde(alias = "exec")]
+        command: String,
+    },
+}
+
+/// A credential as written in the configuration
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CredentialValue {
+    Plain(String),
+    Source(CredentialSource),
+    Exec { exec: String },
+}
+
+impl CredentialValue {
+    fn into_reference(self) -> String {
+        match self {
+            CredentialValue::Plain(value) => value,
+            CredentialValue::Source(source) => source.to_reference(),
+            CredentialValue::Exec { exec } => CredentialSource::Exec { command: exec }.to_reference(),
+        }
+    }
+}
+
+impl CredentialSource {
+    /// Reference kept in the configuration in place of the credential
+    pub fn to_reference(&self) -> String {
+        match self {
+            CredentialSource::Env { name } => format!("{}{}", ENV_SCHEME, name),
+            CredentialSource::File { path } => format!("{}{}", FILE_SCHEME, path),
+            CredentialSource::Keyring { service, account: Some(account) } => {
+                format!("{}{}/{}", KEYRING_SCHEME, service, account)
+            }
+            CredentialSource::Keyring { service, account: None } => format!("{}{}", KEYRING_SCHEME, service),
+            CredentialSource::Exec { command } => format!("{}{}", EXEC_SCHEME, command),
+        }
+    }
+
+    /// Parse a reference; `None` for other values
+    pub fn from_reference(value: &str) -> Option<Self> {
+        if let Some(name) = value.strip_prefix(ENV_SCHEME) {
+            return Some(CredentialSource::Env { name: name.to_string() });
+        }
+        if let Some(path) = value.strip_prefix(FILE_SCHEME) {
+            return Some(CredentialSource::File { path: path.to_string() });
+        }
+        if let Some(entry) = value.strip_prefix(KEYRING_SCHEME) {
+            let (service, account) = match entry.rsplit_once('/') {
+                Some((service, account)) => (service, Some(account.to_string())),
+                None => (entry, None),
+            };
+            return Some(CredentialSource::Keyring { service: service.to_string(), account });
+        }
+        value
+            .strip_prefix(EXEC_SCHEME)
+            .map(|command| CredentialSource::Exec { command: command.to_string() })
+    }
+
+    /// Read the credential
+    pub fn resolve(&self) -> Result<String> {
+        match self {
+            CredentialSource::Env { name } => {
+                std::env::var(name).with_context(|| format!("Environment variable {} is not set", name))
+            }
+            CredentialSource::File { path } => fs::read_to_string(path)
+                .map(|content| content.trim_end_matches(['\r', '\n']).to_string())
+                .with_context(|| format!("Failed to read credential file {}", path)),
+            CredentialSource::Keyring { .. } | CredentialSource::Exec { .. } => {
+                let reference = self.to_reference();
+                if let Some((read_at, value)) = RESOLVED.read().unwrap().get(&reference) {
+                    if read_at.elapsed() < CREDENTIAL_CACHE_TTL {
+                        return Ok(value.clone());
+                    }
+                }
+                let value = self.read_uncached()?;
+                RESOLVED.write().unwrap().insert(reference, (Instant::now(), value.clone()));
+                Ok(value)
+            }
+        }
+    }
+
+    fn read_uncached(&self) -> Result<String> {
+        match self {
+            CredentialSource::Keyring { service, account } => keyring_command(service, account.as_deref())
+                .and_then(|command| run(command, &format!("keyring entry {}", service))),
+            CredentialSource::Exec { command } => {
+                debug!("Running credential command: {}", command);
+                run(shell_command(command), &format!("credential command `{}`", command))
+            }
+            _ => self.resolve(),
+        }
+    }
+}
+
+/// Deserialize a credential given as a string or as a credential source object, keeping
+/// sources as references
+pub fn deserialize_credential<'de, D>(deserializer: D) -> std::result::Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Option::<CredentialValue>::deserialize(deserializer)?.map(CredentialValue::into_reference))
+}
+
+/// Deserialize a list of credentials like `deserialize_credential`
+pub fn deserialize_credential_list<'de, D>(deserializer: D) -> std::result::Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Vec::<CredentialValue>::deserialize(deserializer)?.into_iter().map(CredentialValue::into_reference).collect())
+}
+
+/// Whether a configuration value references a credential kept elsewhere
+pub fn is_credential_ref(value: &str) -> bool {
+    is_secret_ref(value) || CredentialSource::from_reference(value).is_some()
+}
+
+/// Resolve a configuration value: `secret://` references and credential sources are read,
+/// other values are returned as-is
+pub fn resolve_credential_ref(value: &str) -> Result<String> {
+    match CredentialSource::from_reference(value) {
+        Some(source) => source.resolve(),
+        None => resolve_secret_ref(value),
+    }
+}
+
+/// Forget keyring and command values so the next use reads them again, e.g. after the API
+/// rejected a credential
+pub fn forget_resolved_credentials() {
+    RESOLVED.write().unwrap().clear();
+}
+
+#[cfg(target_os = "macos")]
+fn keyring_command(service: &str, account: Option<&str>) -> Result<Command> {
+    let mut command = Command::new("security");
+    command.args(["find-generic-password", "-w", "-s", service]);
+    if let Some(account) = account {
+        command.args(["-a", account]);
+    }
+    Ok(command)
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn keyring_command(service: &str, account: Option<&str>) -> Result<Command> {
+    let mut command = Command::new("secret-tool");
+    command.args(["lookup", "service", service]);
+    if let Some(account) = account {
+        command.args(["account", account]);
+    }
+    Ok(command)
+}
+
+#[cfg(not(unix))]
+fn keyring_command(service: &str, _account: Option<&str>) -> Result<Command> {
+    bail!("Keyring credentials are not supported on this platform (keyring entry {})", service)
+}
+
+fn shell_command(command: &str) -> Command {
+    #[cfg(windows)]
+    {
+        let mut shell = Command::new("cmd");
+        shell.args(["/C", command]);
+        shell
+    }
+    #[cfg(not(windows))]
+    {
+        let mut shell = Command::new("sh");
+        shell.args(["-c", command]);
+        shell
+    }
+}
+
+/// Run a command and return its output without the trailing newline
+fn run(mut command: Command, what: &str) -> Result<String> {
+    let output = command.output().with_context(|| format!("Failed to run {}", what))?;
+    if !output.status.success() {
+        bail!(
+            "Reading {} failed with {}: {}",
+            what,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let value = String::from_utf8(output.stdout).with_context(|| format!("Output of {} is not valid UTF-8", what))?;
+    let value = value.trim_end_matches(['\r', '\n']).to_string();
+    if value.is_empty() {
+        bail!("Reading {} returned nothing", what);
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[derive(Deserialize)]
+    struct Credentials {
+        #[serde(default, deserialize_with = "deserialize_credential")]
+        token: Option<String>,
+    }
+
+    fn token(json: &str) -> Option<String> {
+        serde_json::from_str::<Credentials>(json).unwrap().token
+    }
+
+    #[test]
+    fn test_credential_sources_become_references() {
+        assert_eq!(token(r#"{"token": "plain"}"#).as_deref(), Some("plain"));
+        assert_eq!(token(r#"{}"#), None);
+        assert_eq!(token(r#"{"token": {"source": "env", "name": "API_TOKEN"}}"#).as_deref(), Some("env://API_TOKEN"));
+        assert_eq!(token(r#"{"token": {"source": "file", "path": "/run/secrets/api"}}"#).as_deref(), Some("file:///run/secrets/api"));
+        assert_eq!(
+            token(r#"{"token": {"source": "keyring", "service": "zentao", "account": "admin"}}"#).as_deref(),
+            Some("keyring://zentao/admin")
+        );
+        assert_eq!(token(r#"{"token": {"exec": "vault kv get -field=token secret/api"}}"#).as_deref(), Some("exec://vault kv get -field=token secret/api"));
+        assert_eq!(token(r#"{"token": {"source": "exec", "command": "pass api"}}"#).as_deref(), Some("exec://pass api"));
+
+        assert_eq!(
+            CredentialSource::from_reference("keyring://zentao/admin"),
+            Some(CredentialSource::Keyring { service: "zentao".to_string(), account: Some("admin".to_string()) })
+        );
+        assert!(is_credential_ref("env://API_TOKEN"));
+        assert!(!is_credential_ref("plain"));
+    }
+
+    #[test]
+    fn test_resolve_env_and_file_credentials() {
+        std::env::set_var("MCP_CREDENTIALS_TEST_TOKEN", "from-env");
+        assert_eq!(resolve_credential_ref("env://MCP_CREDENTIALS_TEST_TOKEN").unwrap(), "from-env");
+        assert!(resolve_credential_ref("env://MCP_CREDENTIALS_TEST_MISSING").is_err());
+
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("token");
+        fs::write(&path, "from-file\n").unwrap();
+        let reference = CredentialSource::File { path: path.display().to_string() }.to_reference();
+        assert_eq!(resolve_credential_ref(&reference).unwrap(), "from-file");
+        assert_eq!(resolve_credential_ref("plain").unwrap(), "plain");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_exec_credentials_are_cached_until_forgotten() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("count");
+        let reference = format!("exec://echo x >> {0}; wc -l < {0} | tr -d ' '", path.display());
+        assert_eq!(resolve_credential_ref(&reference).unwrap(), "1");
+        assert_eq!(resolve_credential_ref(&reference).unwrap(), "1");
+        forget_resolved_credentials();
+        assert_eq!(resolve_credential_ref(&reference).unwrap(), "2");
+        assert!(resolve_credential_ref("exec://exit 3").is_err());
+    }
+}
@@ -0,0 +1,197 @@
+//! Module dependencies for MCP-ANY-REST
+//! A module can declare `depends_on: ["auth", "core"]`. Modules are activated in dependency order,
+//! and a module whose dependencies are disabled is not activated.
+
+use anyhow::{bail, Result};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::config::module::GlobalModuleConfig;
+
+/// Module in the dependency graph
+#[derive(Debug, Clone, Serialize)]
+pub struct DependencyNode {
+    pub name: String,
+    /// Enabled in the module configuration
+    pub enabled: bool,
+    /// Enabled and all dependencies active
+    pub active: bool,
+    pub depends_on: Vec<String>,
+    /// Dependencies that are disabled or unknown
+    pub unmet: Vec<String>,
+    /// Modules depending on this one, directly or transitively
+    pub dependents: Vec<String>,
+}
+
+/// Dependency graph of the configured modules
+#[derive(Debug, Clone, Serialize)]
+pub struct DependencyGraph {
+    pub modules: Vec<DependencyNode>,
+    /// Activation order of the active modules
+    pub activation_order: Vec<String>,
+    /// Modules forming a dependency cycle
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub cycle: Vec<String>,
+}
+
+impl DependencyGraph {
+    /// Build the graph of a module configuration
+    pub fn build(config: &GlobalModuleConfig) -> Self {
+        let mut names: Vec<&String> = config.modules.keys().collect();
+        names.sort();
+
+        let modules = names
+            .iter()
+            .map(|name| {
+                let module_config = &config.modules[*name];
+                DependencyNode {
+                    name: name.to_string(),
+                    enabled: module_config.enabled,
+                    active: config.is_module_active(name),
+                    depends_on: module_config.depends_on.clone(),
+                    unmet: config.unmet_dependencies(name),
+                    dependents: config.dependents(name),
+                }
+            })
+            .collect();
+
+        let active: Vec<String> = names
+            .iter()
+            .filter(|name| config.is_module_active(name))
+            .map(|name| name.to_string())
+            .collect();
+        // Modules in a cycle are never active, so the active modules can always be ordered
+        let activation_order = activation_order(config, &active).unwrap_or(active);
+
+        Self {
+            modules,
+            activation_order,
+            cycle: find_cycle(config),
+        }
+    }
+}
+
+/// Order modules so every module comes after its dependencies; dependencies outside `names` are
+/// ignored and independent modules keep alphabetical order. Fails on a dependency cycle
+pub fn activation_order(config: &GlobalModuleConfig, names: &[String]) -> Result<Vec<String>> {
+    let included: BTreeSet<&str> = names.iter().map(String::as_str).collect();
+    let mut pending: BTreeMap<&str, BTreeSet<&str>> = included
+        .iter()
+        .map(|name| {
+            let dependencies = config
+                .get_module_config(name)
+                .map(|module_config| {
+                    module_config
+                        .depends_on
+                        .iter()
+                        .map(String::as_str)
+                        .filter(|dependency| included.contains(dependency) && dependency != name)
+                        .collect()
+                })
+                .unwrap_or_default();
+            (*name, dependencies)
+        })
+        .collect();
+
+    let mut order = Vec::with_capacity(pending.len());
+    while !pending.is_empty() {
+        let ready: Vec<&str> = pending
+            .iter()
+            .filter(|(_, dependencies)| dependencies.is_empty())
+            .map(|(name, _)| *name)
+            .collect();
+        if ready.is_empty() {
+            let remaining: Vec<&str> = pending.keys().copied().collect();
+            bail!("Dependency cycle between modules: {}", remaining.join(", "));
+        }
+        for name in ready {
+            pending.remove(name);
+            for dependencies in pending.values_mut() {
+                dependencies.remove(name);
+            }
+            order.push(name.to_string());
+        }
+    }
+    Ok(order)
+}
+
+/// Modules of the first dependency cycle found, in dependency order
+pub fn find_cycle(config: &GlobalModuleConfig) -> Vec<String> {
+    fn visit(config: &GlobalModuleConfig, name: &str, path: &mut Vec<String>, done: &mut BTreeSet<String>) -> Option<Vec<String>> {
+        if let Some(start) = path.iter().position(|visited| visited == name) {
+            return Some(path[start..].to_vec());
+        }
+        if done.contains(name) {
+            return None;
+        }
+        path.push(name.to_string());
+        if let Some(module_config) = config.get_module_config(name) {
+            for dependency in &module_config.depends_on {
+                if let Some(cycle) = visit(config, dependency, path, done) {
+                    return Some(cycle);
+                }
+            }
+        }
+        path.pop();
+        done.insert(name.to_string());
+        None
+    }
+
+    let mut names: Vec<&String> = config.modules.keys().collect();
+    names.sort();
+    let mut done = BTreeSet::new();
+    for name in names {
+        if let Some(cycle) = visit(config, name, &mut Vec::new(), &mut done) {
+            return cycle;
+        }
+    }
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::module::ModuleConfig;
+
+    fn module(depends_on: &[&str]) -> ModuleConfig {
+        ModuleConfig {
+            depends_on: depends_on.iter().map(|name| name.to_string()).collect(),
+            ..ModuleConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_activation_order_follows_dependencies() {
+        let mut config = GlobalModuleConfig::default();
+        config.modules.insert("auth".to_string(), module(&[]));
+        config.modules.insert("core".to_string(), module(&["auth"]));
+        config.modules.insert("bug".to_string(), module(&["core", "auth"]));
+        config.modules.insert("user".to_string(), module(&[]));
+
+        let names: Vec<String> = ["bug", "core", "user", "auth"].iter().map(|n| n.to_string()).collect();
+        assert_eq!(activation_order(&config, &names).unwrap(), vec!["auth", "user", "core", "bug"]);
+
+        config.modules.get_mut("auth").unwrap().enabled = false;
+        assert!(!config.is_module_active("bug"));
+        assert_eq!(config.unmet_dependencies("bug"), vec!["auth"]);
+        assert_eq!(config.dependents("auth"), vec!["bug", "core"]);
+
+        let graph = DependencyGraph::build(&config);
+        assert_eq!(graph.activation_order, vec!["user"]);
+        assert!(graph.cycle.is_empty());
+    }
+
+    #[test]
+    fn test_cycles_are_reported() {
+        let mut config = GlobalModuleConfig::default();
+        config.modules.insert("a".to_string(), module(&["b"]));
+        config.modules.insert("b".to_string(), module(&["a"]));
+        config.modules.insert("c".to_string(), module(&[]));
+
+        let names: Vec<String> = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        assert!(activation_order(&config, &names).is_err());
+        assert!(!config.is_module_active("a"));
+        assert_eq!(find_cycle(&config), vec!["a", "b"]);
+        assert_eq!(DependencyGraph::build(&config).activation_order, vec!["c"]);
+    }
+}
@@ -0,0 +1,262 @@
+//! Configuration health diagnostics for MCP-ANY-REST
+//! The doctor combines the offline checks of the configuration directory with live probes of the
+//! API backends and their authentication, and explains why tools might not show up.
+
+use serde::Serialize;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::config::check::{check_config_dir, CheckLevel};
+use crate::config::config::{AuthConfig, Config, TlsConfig};
+use crate::config::variables::render;
+use crate::config::zml_loader::ZmlModuleLoader;
+use crate::services::auth_service::UnifiedAuthService;
+use crate::services::composer_service::backend_registry::build_backend_client;
+use crate::services::composer_service::service_composer::auth_strategy_config;
+
+/// Seconds to wait for a backend to answer the reachability probe
+const PROBE_TIMEOUT_SECS: u64 = 5;
+
+/// Area a finding belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DoctorCategory {
+    /// Problems in the configuration, module, preset, client or ZML files
+    Config,
+    /// A backend base URL cannot be reached
+    Connectivity,
+    /// Authentication (e.g. login) fails
+    Auth,
+    /// Reasons no tools are exposed
+    Tools,
+}
+
+/// Single diagnostic finding
+#[derive(Debug, Clone, Serialize)]
+pub struct DoctorFinding {
+    pub level: CheckLevel,
+    pub category: DoctorCategory,
+    /// File, backend or module the finding is about
+    pub target: String,
+    pub message: String,
+    /// Suggested fix
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+/// Result of a diagnostics run
+#[derive(Debug, Clone, Serialize)]
+pub struct DoctorReport {
+    /// Whether no errors were found
+    pub healthy: bool,
+    pub findings: Vec<DoctorFinding>,
+}
+
+impl DoctorReport {
+    fn push(
+        &mut self,
+        level: CheckLevel,
+        category: DoctorCategory,
+        target: impl Into<String>,
+        message: impl Into<String>,
+        hint: Option<&str>,
+    ) {
+        self.findings.push(DoctorFinding {
+            level,
+            category,
+            target: target.into(),
+            message: message.into(),
+            hint: hint.map(str::to_string),
+        });
+    }
+}
+
+/// Diagnose a configuration directory and the running configuration `config`
+pub async fn diagnose(config_dir: &Path, profile: Option<&str>, config: &Config) -> DoctorReport {
+    let mut report = DoctorReport {
+        healthy: true,
+        findings: Vec::new(),
+    };
+
+    // Offline checks: invalid files, references to missing modules or methods, zero rate limits, ...
+    for issue in check_config_dir(config_dir, profile).issues {
+        let target = if issue.path.is_empty() {
+            issue.file
+        } else {
+            format!("{}: {}", issue.file, issue.path)
+        };
+        report.push(issue.level, DoctorCategory::Config, target, issue.message, None);
+    }
+
+    check_tools(config_dir, config, &mut report);
+
+    // Live probes of every backend; undefined variables are reported by the offline checks
+    let base_url = |url: &str| render(url, &config.variables).unwrap_or_else(|_| url.to_string());
+    let mut backends = vec![(
+        "api".to_string(),
+        base_url(&config.api.base_url),
+        config.auth.clone(),
+        config.tls.clone(),
+    )];
+    let mut names: Vec<&String> = config.backends.keys().collect();
+    names.sort();
+    for name in names {
+        let backend = &config.backends[name];
+        backends.push((
+            format!("backends.{}", name),
+            base_url(&backend.base_url),
+            backend.auth.clone().unwrap_or_else(|| config.auth.clone()),
+            backend.tls.clone(),
+        ));
+    }
+    for (target, base_url, auth, tls) in backends {
+        probe_backend(&target, &base_url, auth, &tls, &mut report).await;
+    }
+
+    report.healthy = !report.findings.iter().any(|f| f.level == CheckLevel::Error);
+    report
+}
+
+/// Explain why enabled modules expose no tools
+fn check_tools(config_dir: &Path, config: &Config, report: &mut DoctorReport) {
+    let zml_dir = config_dir.join("zml");
+    let Ok(loader) = ZmlModuleLoader::from_dir(&zml_dir) else {
+        // Parse errors are reported by the offline checks
+        return;
+    };
+
+    let module_config = &config.module_config;
+    let mut tool_count = 0;
+    for name in loader.get_all_module_names() {
+        let Some(module) = loader.get_module(&name) else {
+            continue;
+        };
+        if !module_config.is_module_enabled(&name) {
+            if module_config.get_module_config(&name).is_none() {
+                report.push(
+                    CheckLevel::Warning,
+                    DoctorCategory::Tools,
+                    &name,
+                    "ZML module is not listed in the module configuration and is disabled",
+                    Some("Add the module to modules.json or apply a preset that enables it"),
+                );
+            }
+            continue;
+        }
+
+        let enabled = module
+            .methods
+            .keys()
+            .filter(|method| module_config.is_method_enabled(&name, method))
+            .count();
+        if enabled == 0 && !module.methods.is_empty() {
+            report.push(
+                CheckLevel::Warning,
+                DoctorCategory::Tools,
+                &name,
+                "Module is enabled but all of its methods are disabled",
+                Some("Check the method settings and the `enable` tag filter"),
+            );
+        }
+        tool_count += enabled;
+    }
+
+    if tool_count == 0 {
+        report.push(
+            CheckLevel::Error,
+            DoctorCategory::Tools,
+            zml_dir.display().to_string(),
+            "No tools are exposed: no enabled module has an enabled ZML method",
+            Some("Enable a module that has a ZML file in the zml directory"),
+        );
+    }
+}
+
+/// Check that a backend answers and that its authentication succeeds
+async fn probe_backend(
+    target: &str,
+    base_url: &str,
+    auth: AuthConfig,
+    tls: &TlsConfig,
+    report: &mut DoctorReport,
+) {
+    let client = match build_backend_client(tls, PROBE_TIMEOUT_SECS, base_url) {
+        Ok(client) => client,
+        Err(e) => {
+            report.push(CheckLevel::Error, DoctorCategory::Connectivity, target, format!("{:#}", e), None);
+            return;
+        }
+    };
+
+    match client.get(tls.request_url(base_url)).timeout(Duration::from_secs(PROBE_TIMEOUT_SECS)).send().await {
+        Ok(response) if response.status().is_server_error() => report.push(
+            CheckLevel::Warning,
+            DoctorCategory::Connectivity,
+            target,
+            format!("{} answered with {}", base_url, response.status()),
+            None,
+        ),
+        Ok(_) => {}
+        Err(e) => {
+            report.push(
+                CheckLevel::Error,
+                DoctorCategory::Connectivity,
+                target,
+                format!("{} is unreachable: {}", base_url, e),
+                Some("Check base_url, DNS, proxies and TLS settings"),
+            );
+            // Login would fail the same way
+            return;
+        }
+    }
+
+    let auth_service = match UnifiedAuthService::with_client(auth_strategy_config(auth), client) {
+        Ok(auth_service) => auth_service,
+        Err(e) => {
+            report.push(CheckLevel::Error, DoctorCategory::Auth, target, e.to_string(), None);
+            return;
+        }
+    };
+    if let Err(e) = auth_service.get_valid_auth_headers().await {
+        report.push(
+            CheckLevel::Error,
+            DoctorCategory::Auth,
+            target,
+            format!("Authentication failed: {}", e),
+            Some("Check the credentials and login settings in the auth section"),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::init::{init_config_dir, InitAuth, InitOptions};
+    use crate::config::module::GlobalModuleConfig;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_doctor_reports_unreachable_backend_and_missing_tools() {
+        let temp_dir = tempdir().unwrap();
+        let options = InitOptions {
+            base_url: "http://127.0.0.1:9".to_string(),
+            auth: InitAuth::None,
+            force: false,
+        };
+        init_config_dir(temp_dir.path(), &options).unwrap();
+        let mut config = Config::from_file(temp_dir.path().join("config.json")).unwrap();
+        config.module_config = GlobalModuleConfig::default();
+
+        let report = diagnose(temp_dir.path(), None, &config).await;
+        assert!(!report.healthy);
+        let categories: Vec<DoctorCategory> = report
+            .findings
+            .iter()
+            .filter(|f| f.level == CheckLevel::Error)
+            .map(|f| f.category)
+            .collect();
+        assert!(categories.contains(&DoctorCategory::Connectivity));
+        assert!(categories.contains(&DoctorCategory::Tools));
+        assert!(!categories.contains(&DoctorCategory::Config));
+    }
+}
@@ -4,18 +4,39 @@
 use anyhow::{Context, Result};
 use log::{info, warn};
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, VecDeque};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, RwLock};
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 use tokio::sync::broadcast;
 
+use crate::config::backup::{BackupEntry, ConfigBackups};
+use crate::config::clients::{ClientIdentity, ClientStore};
 use crate::config::config::Config;
+use crate::config::format::{
+    read_config_file, read_migrated_config_file, read_migrated_config_file_readonly, ConfigFormat,
+};
+use crate::config::history::{
+    current_actor, diff_json, ConfigDiffEntry, ConfigHistory, HistoryEntry, DEFAULT_HISTORY_LIMIT, SYSTEM_ACTOR,
+};
+use crate::config::migrate::ConfigKind;
 use crate::config::loader::ConfigLoader;
 use crate::config::module::GlobalModuleConfig;
 use crate::config::module::ModuleConfig;
-use crate::config::preset_loader::PresetLoader;
+use crate::config::preset_loader::{PresetConfig as StoredPresetConfig, PresetLoader};
+use crate::config::profile::{
+    active_profile, profile_path, read_layered_config_file, read_layered_config_file_readonly,
+    write_layered_config_file,
+};
+use crate::config::redact::restore_credentials;
+use crate::config::remote::{RemoteSync, REMOTE_CHANGE_SOURCE};
+use crate::config::store::{open_store, ConfigStore, STORE_CHANGE_SOURCE};
+use crate::config::validator::ConfigValidator;
+use crate::config::variables::render_serde;
+use crate::config::zml_loader::ZmlModuleLoader;
 
 /// Configuration preset definition
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,73 +77,487 @@ pub struct DynamicConfigManager {
     preset_config_path: PathBuf,
     /// Configuration change notifier
     change_sender: broadcast::Sender<ConfigChangeEvent>,
-    /// Last modification time
-    last_modified: Arc<RwLock<SystemTime>>,
+    /// Modification times of the watched files as of the last load or write
+    file_stamps: Arc<RwLock<FileStamps>>,
     /// Configuration change history (last 100 changes)
     change_history: Arc<RwLock<VecDeque<ConfigChangeEvent>>>,
+    /// Persistent revision history with snapshots
+    history: Arc<ConfigHistory>,
+    /// Active configuration profile (overlay files `<name>.<profile>.<ext>`)
+    profile: Option<String>,
+    /// Per-client module/tool overrides
+    clients: Arc<ClientStore>,
+    /// Timestamped backups taken before configuration files are overwritten
+    backups: Arc<ConfigBackups>,
+    /// Database holding modules, presets and history instead of the files, if configured
+    store: Option<Arc<dyn ConfigStore>>,
+    /// Store version as of the last load or write
+    store_version: AtomicU64,
+    /// Reject changes and skip all file writes (in addition to `lock` in the configuration)
+    read_only: bool,
 }
 
-/// Configuration change event
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ConfigChangeEvent {
+/// Changes a preset would make relative to the current module configuration
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PresetPreview {
     pub preset: String,
-    pub timestamp: SystemTime,
-    pub changes: Vec<String>,
+    pub modules_enabled: Vec<String>,
+    pub modules_disabled: Vec<String>,
+    /// Methods as `module.method`
+    pub methods_enabled: Vec<String>,
+    pub methods_disabled: Vec<String>,
+    /// Changed module settings
+    pub settings: Vec<ConfigDiffEntry>,
+    /// Whether the resulting module configuration passes validation
+    pub valid: bool,
+}
+
+/// Configuration change event, one per changed part of the configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ConfigChangeEvent {
+    /// Server, API or backend settings changed
+    ServerConfig { diff: Vec<ConfigDiffEntry> },
+    /// Authentication settings changed
+    Auth { diff: Vec<ConfigDiffEntry> },
+    /// Global module defaults changed; they apply to every module
+    ModuleDefaults { diff: Vec<ConfigDiffEntry> },
+    /// Settings of a module changed, or the module was added or removed
+    Module { name: String, diff: Vec<ConfigDiffEntry> },
+    /// A preset was applied; the modules it changed get their own `Module` events
+    Preset { id: String },
+    /// Preset definitions changed on disk
+    PresetFiles { files: Vec<String> },
+    /// ZML sources changed on disk
+    Zml { files: Vec<String> },
+}
+
+/// Events describing the differences between two configurations
+pub fn config_change_events(old: &Config, new: &Config) -> Vec<ConfigChangeEvent> {
+    let mut events = Vec::new();
+
+    let server = |config: &Config| {
+        let mut value = serde_json::to_value(config).unwrap_or_default();
+        if let Some(object) = value.as_object_mut() {
+            object.remove("auth");
+            object.remove("module_config");
+        }
+        value
+    };
+    let diff = diff_json("", &server(old), &server(new));
+    if !diff.is_empty() {
+        events.push(ConfigChangeEvent::ServerConfig { diff });
+    }
+
+    let diff = diff_json(
+        "auth",
+        &serde_json::to_value(&old.auth).unwrap_or_default(),
+        &serde_json::to_value(&new.auth).unwrap_or_default(),
+    );
+    if !diff.is_empty() {
+        events.push(ConfigChangeEvent::Auth { diff });
+    }
+
+    events.extend(module_change_events(&old.module_config, &new.module_config));
+    events
+}
+
+/// Events describing the differences between two module configurations;
+/// module diffs are relative to the module (e.g. `methods.delete_user.enabled`)
+pub fn module_change_events(old: &GlobalModuleConfig, new: &GlobalModuleConfig) -> Vec<ConfigChangeEvent> {
+    let mut events = Vec::new();
+
+    let defaults = |config: &GlobalModuleConfig| {
+        let mut value = serde_json::to_value(config).unwrap_or_default();
+        if let Some(object) = value.as_object_mut() {
+            object.remove("modules");
+        }
+        value
+    };
+    let diff = diff_json("", &defaults(old), &defaults(new));
+    if !diff.is_empty() {
+        events.push(ConfigChangeEvent::ModuleDefaults { diff });
+    }
+
+    let mut names: Vec<&String> = old.modules.keys().chain(new.modules.keys()).collect();
+    names.sort();
+    names.dedup();
+    for name in names {
+        // A missing module diffs as an empty object, so additions and removals list every field
+        let module = |config: &GlobalModuleConfig| {
+            config
+                .modules
+                .get(name)
+                .and_then(|module| serde_json::to_value(module).ok())
+                .unwrap_or_else(|| Value::Object(Default::default()))
+        };
+        let diff = diff_json("", &module(old), &module(new));
+        if !diff.is_empty() {
+            events.push(ConfigChangeEvent::Module {
+                name: name.clone(),
+                diff,
+            });
+        }
+    }
+
+    events
+}
+
+/// Modification times of the watched files, grouped by what they configure
+#[derive(Debug, Clone, Default, PartialEq)]
+struct FileStamps {
+    main: Vec<(PathBuf, SystemTime)>,
+    modules: Vec<(PathBuf, SystemTime)>,
+    presets: Vec<(PathBuf, SystemTime)>,
+    zml: Vec<(PathBuf, SystemTime)>,
+}
+
+/// Modification times of the existing files among `paths`
+fn file_stamps(paths: impl IntoIterator<Item = PathBuf>) -> Vec<(PathBuf, SystemTime)> {
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let modified = fs::metadata(&path).and_then(|metadata| metadata.modified()).ok()?;
+            Some((path, modified))
+        })
+        .collect()
+}
+
+/// Modification times of the files in a directory, skipping hidden and temporary files
+fn dir_stamps(dir: &Path, extension: Option<&str>) -> Vec<(PathBuf, SystemTime)> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.is_file())
+                .filter(|path| {
+                    !path
+                        .file_name()
+                        .map(|name| name.to_string_lossy().starts_with('.'))
+                        .unwrap_or(true)
+                })
+                .filter(|path| match extension {
+                    Some(extension) => path.extension().map(|e| e == extension).unwrap_or(false),
+                    None => true,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    paths.sort();
+    file_stamps(paths)
+}
+
+/// Names of the files added, removed or modified between two scans
+fn changed_files(old: &[(PathBuf, SystemTime)], new: &[(PathBuf, SystemTime)]) -> Vec<String> {
+    let mut files: Vec<String> = old
+        .iter()
+        .filter(|stamp| !new.contains(stamp))
+        .chain(new.iter().filter(|stamp| !old.contains(stamp)))
+        .filter_map(|(path, _)| path.file_name().map(|name| name.to_string_lossy().into_owned()))
+        .collect();
+    files.sort();
+    files.dedup();
+    files
 }
 
 impl DynamicConfigManager {
-    /// Create a new dynamic configuration manager
+    /// Create a new dynamic configuration manager using the profile selected by `MCP_PROFILE`
     pub fn new(
         config_path: PathBuf,
         module_config_path: PathBuf,
         preset_config_path: PathBuf,
+    ) -> Result<Self> {
+        Self::with_profile(config_path, module_config_path, preset_config_path, active_profile(None))
+    }
+
+    /// Create a new dynamic configuration manager with an explicit profile
+    pub fn with_profile(
+        config_path: PathBuf,
+        module_config_path: PathBuf,
+        preset_config_path: PathBuf,
+        profile: Option<String>,
+    ) -> Result<Self> {
+        Self::with_options(config_path, module_config_path, preset_config_path, profile, false)
+    }
+
+    /// Create a new dynamic configuration manager with an explicit profile;
+    /// `read_only` rejects all changes and file writes regardless of `lock` in the configuration
+    pub fn with_options(
+        config_path: PathBuf,
+        module_config_path: PathBuf,
+        preset_config_path: PathBuf,
+        profile: Option<String>,
+        read_only: bool,
     ) -> Result<Self> {
         let (change_sender, _) = broadcast::channel(100);
 
-        // Load initial configurations
-        let mut config = Self::load_config(&config_path)?;
-        config.module_config = Self::load_module_config(&module_config_path)?;
+        if let Some(profile) = &profile {
+            info!("Using configuration profile: {}", profile);
+        }
+
+        // Load initial configurations; schema upgrades are only written back when the configuration is writable
+        let config_dir = config_path.parent().unwrap_or_else(|| std::path::Path::new("."));
+        let mut config = Self::load_config(&config_path, profile.as_deref(), None)?;
+        let backups = Arc::new(ConfigBackups::new(config_dir, config.backup_retention));
+        let writable = !read_only && !config.lock;
+        let persist = writable.then_some(&backups);
+        if writable {
+            config = Self::load_config(&config_path, profile.as_deref(), persist)?;
+        } else {
+            info!("Configuration is read-only; changes through the web API are rejected");
+        }
+        config.module_config = Self::load_module_config(&module_config_path, profile.as_deref(), persist)?;
+
+        // Modules, presets and history live in the database when a store is configured
+        let store = match &config.store {
+            Some(store_config) => {
+                info!("Using configuration store: {}", store_config.describe());
+                Some(open_store(store_config)?)
+            }
+            None => None,
+        };
+        let mut store_version = 0;
+        if let Some(store) = &store {
+            match store.load_module_config()? {
+                Some(module_config) => config.module_config = module_config,
+                // Seed an empty store from the module configuration file
+                None if writable => store.save_module_config(&config.module_config)?,
+                None => {}
+            }
+            store_version = store.version()?;
+        }
 
-        let last_modified = Arc::new(RwLock::new(SystemTime::now()));
         let change_history = Arc::new(RwLock::new(VecDeque::new()));
 
-        Ok(Self {
+        // Open persistent history next to the configuration file
+        let history_dir = config_path
+            .parent()
+            .map(|dir| dir.join("history"))
+            .unwrap_or_else(|| PathBuf::from("history"));
+        let history = Arc::new(ConfigHistory::open(&history_dir, DEFAULT_HISTORY_LIMIT)?);
+        if let (Some(store), true) = (&store, writable) {
+            let baseline = match store.history() {
+                Ok(entries) if entries.is_empty() => store
+                    .record_history(
+                        &config,
+                        SYSTEM_ACTOR,
+                        "initial",
+                        vec!["Initial configuration".to_string()],
+                        DEFAULT_HISTORY_LIMIT,
+                    )
+                    .map(|_| ()),
+                Ok(_) => Ok(()),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = baseline {
+                warn!("Failed to record initial configuration history: {}", e);
+            }
+        } else if writable {
+            if let Err(e) = history.ensure_baseline(&config) {
+                warn!("Failed to record initial configuration history: {}", e);
+            }
+        }
+
+        let clients = Arc::new(ClientStore::open_in_dir(config_dir)?);
+
+        let manager = Self {
             config: Arc::new(RwLock::new(config)),
             config_path,
             module_config_path,
             preset_config_path,
             change_sender,
-            last_modified,
+            file_stamps: Arc::new(RwLock::new(FileStamps::default())),
             change_history,
-        })
+            history,
+            profile,
+            clients,
+            backups,
+            store,
+            store_version: AtomicU64::new(store_version),
+            read_only,
+        };
+        manager.refresh_file_stamps();
+        Ok(manager)
     }
 
-    /// Load configuration from file
-    fn load_config(config_path: &PathBuf) -> Result<Config> {
+    /// Load configuration from file, merging the profile overlay if any.
+    /// With `backups` older schema versions are upgraded on disk, keeping the original as a backup.
+    fn load_config(
+        config_path: &PathBuf,
+        profile: Option<&str>,
+        backups: Option<&Arc<ConfigBackups>>,
+    ) -> Result<Config> {
         if !config_path.exists() {
             // Create default config if file doesn't exist
             Ok(Config::new())
+        } else if let Some(backups) = backups {
+            // Load existing config (JSON, YAML or TOML), upgrading older schema versions
+            read_layered_config_file(config_path, ConfigFormat::Json, ConfigKind::Main, profile, backups)
+        } else {
+            read_layered_config_file_readonly(config_path, ConfigFormat::Json, ConfigKind::Main, profile)
+        }
+    }
+
+    /// Load module configuration from file, merging the profile overlay if any.
+    /// With `backups` older schema versions are upgraded on disk, keeping the original as a backup.
+    fn load_module_config(
+        module_config_path: &PathBuf,
+        profile: Option<&str>,
+        backups: Option<&Arc<ConfigBackups>>,
+    ) -> Result<GlobalModuleConfig> {
+        if let Some(backups) = backups {
+            let loader = ConfigLoader::new(module_config_path)
+                .with_profile(profile.map(str::to_string))
+                .with_backups(backups.clone());
+            loader.load_config()
+        } else if module_config_path.exists() {
+            read_layered_config_file_readonly(module_config_path, ConfigFormat::Json, ConfigKind::Modules, profile)
         } else {
-            // Load existing config
-            let config_content = std::fs::read_to_string(config_path)?;
-            Ok(serde_json::from_str(&config_content)?)
+            Ok(GlobalModuleConfig::default())
+        }
+    }
+
+    /// Whether changes are rejected, either by `--read-only-config` or by `lock` in the configuration
+    pub fn is_read_only(&self) -> bool {
+        self.read_only || self.config.read().unwrap().lock
+    }
+
+    /// Fail when the configuration is read-only
+    fn ensure_writable(&self) -> Result<()> {
+        if self.is_read_only() {
+            anyhow::bail!("Configuration is read-only");
+        }
+        Ok(())
+    }
+
+    /// Get the active configuration profile
+    pub fn profile(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+
+    /// Directory holding the ZML sources, next to the configuration file
+    pub fn zml_dir(&self) -> PathBuf {
+        self.config_path
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .join("zml")
+    }
+
+    /// Configuration file and its profile overlay
+    fn layered_paths(&self, base: &Path) -> Vec<PathBuf> {
+        let mut paths = vec![base.to_path_buf()];
+        if let Some(profile) = &self.profile {
+            paths.push(profile_path(base, profile));
+        }
+        paths
+    }
+
+    /// Current modification times of the configuration, module configuration, presets and ZML sources
+    fn scan_files(&self) -> FileStamps {
+        FileStamps {
+            main: file_stamps(self.layered_paths(&self.config_path)),
+            modules: file_stamps(self.layered_paths(&self.module_config_path)),
+            presets: dir_stamps(&self.preset_config_path, None),
+            zml: dir_stamps(&self.zml_dir(), Some("zml")),
+        }
+    }
+
+    /// Remember the current file state so our own writes are not picked up as external changes
+    fn refresh_file_stamps(&self) {
+        *self.file_stamps.write().unwrap() = self.scan_files();
+    }
+
+    /// Module configuration loader bound to the active profile
+    fn module_loader(&self) -> ConfigLoader {
+        ConfigLoader::new(&self.module_config_path)
+            .with_profile(self.profile.clone())
+            .with_backups(self.backups.clone())
+    }
+
+    /// File written for a configuration file: the profile overlay if a profile is active
+    fn layered_target(&self, path: &Path) -> PathBuf {
+        match &self.profile {
+            Some(profile) => profile_path(path, profile),
+            None => path.to_path_buf(),
+        }
+    }
+
+    /// Back up a file before it is overwritten; failures are logged and do not block the write
+    fn backup_file(&self, path: &Path, config: &Config) {
+        self.backups.set_retention(config.backup_retention);
+        if let Err(e) = self.backups.backup(path) {
+            warn!("Failed to back up {:?}: {}", path, e);
+        }
+    }
+
+    /// Save the main configuration without module settings (to the profile overlay if active)
+    fn save_main_config(&self, config: &Config) -> Result<()> {
+        self.backup_file(&self.layered_target(&self.config_path), config);
+
+        // Keep in-memory configuration intact, but write a sanitized copy to disk
+        let mut config_to_save = config.clone();
+        config_to_save.module_config.modules.clear();
+        write_layered_config_file(
+            &self.config_path,
+            &config_to_save,
+            ConfigFormat::Json,
+            ConfigKind::Main,
+            self.profile.as_deref(),
+        )
+        .context("Failed to save config")
+    }
+
+    /// Save the module configuration (to the profile overlay if active)
+    fn save_module_config(&self, config: &Config) -> Result<()> {
+        if let Some(store) = &self.store {
+            store.save_module_config(&config.module_config)?;
+            // Our own write is not a change to reload
+            self.store_version.store(store.version()?, Ordering::SeqCst);
+            return Ok(());
+        }
+        self.backup_file(&self.layered_target(&self.module_config_path), config);
+        self.module_loader().save_config(&config.module_config)
+    }
+
+    /// Preset loader for the preset directory that backs up preset files before writing them
+    /// and leaves them untouched while the configuration is read-only
+    pub fn preset_loader(&self) -> PresetLoader {
+        PresetLoader::new(&self.preset_config_path)
+            .with_backups(self.backups.clone())
+            .with_read_only(self.is_read_only())
+    }
+
+    /// Preset from the configuration store, if one is configured and holds the preset
+    fn load_stored_preset(&self, preset_id: &str) -> Result<Option<StoredPresetConfig>> {
+        match &self.store {
+            Some(store) => store.load_preset(preset_id),
+            None => Ok(None),
         }
     }
 
-    /// Load module configuration from file
-    fn load_module_config(module_config_path: &PathBuf) -> Result<GlobalModuleConfig> {
-        let loader = ConfigLoader::new(module_config_path);
-        loader.load_config()
+    /// Save a preset to the configuration store, or to the preset directory without one
+    pub fn save_preset(&self, preset_id: &str, preset: &StoredPresetConfig) -> Result<()> {
+        match &self.store {
+            Some(store) => store.save_preset(preset_id, preset),
+            None => self.preset_loader().save_preset(preset_id, preset),
+        }
+    }
+
+    /// Delete a preset from the configuration store and the preset directory
+    pub fn delete_preset(&self, preset_id: &str) -> Result<()> {
+        if let Some(store) = &self.store {
+            let deleted = store.delete_preset(preset_id)?;
+            if deleted || ConfigFormat::find_existing(&self.preset_config_path, preset_id).is_none() {
+                return Ok(());
+            }
+        }
+        self.preset_loader().delete_preset(preset_id)
     }
 
     /// Load preset configuration index
     pub fn load_preset_index(&self) -> Result<PresetIndex> {
-        let json_path = self.preset_config_path.join("index.json");
-
-        if json_path.exists() {
-            let content = std::fs::read_to_string(&json_path)?;
-            let index: PresetIndex = serde_json::from_str(&content)?;
-            Ok(index)
+        if let Some(index_path) = ConfigFormat::find_existing(&self.preset_config_path, "index") {
+            read_config_file(&index_path, ConfigFormat::Json)
         } else {
             Ok(PresetIndex {
                 presets: Vec::new(),
@@ -132,6 +567,19 @@ impl DynamicConfigManager {
 
     /// Load specific preset configuration
     pub fn load_preset_config(&self, preset_id: &str) -> Result<PresetConfig> {
+        if let Some(preset) = self.load_stored_preset(preset_id)? {
+            return Ok(PresetConfig {
+                name: preset.name,
+                description: preset.description,
+                modules: preset.modules,
+                default_access_level: preset
+                    .default_access_level
+                    .and_then(|level| serde_json::to_value(level).ok())
+                    .and_then(|level| level.as_str().map(str::to_string)),
+                default_rate_limit: preset.default_rate_limit.map(|limit| limit.requests_per_minute),
+            });
+        }
+
         let index = self.load_preset_index()?;
 
         let preset_info = index
@@ -140,14 +588,13 @@ impl DynamicConfigManager {
             .find(|p| p.id == preset_id)
             .ok_or_else(|| anyhow::anyhow!("Preset not found: {}", preset_id))?;
 
-        // Try YAML first, then JSON for backward compatibility
-        let preset_path = self.preset_config_path.join(&preset_info.file);
-        let json_path = preset_path.with_extension("json");
-
-        if json_path.exists() {
-            let content = std::fs::read_to_string(&json_path)?;
-            let preset: PresetConfig = serde_json::from_str(&content)?;
-            Ok(preset)
+        // Try YAML first, then TOML, then JSON for backward compatibility
+        if let Some(preset_path) = ConfigFormat::find_existing(&self.preset_config_path, &preset_info.file) {
+            if self.is_read_only() {
+                read_migrated_config_file_readonly(&preset_path, ConfigFormat::Json, ConfigKind::Preset)
+            } else {
+                read_migrated_config_file(&preset_path, ConfigFormat::Json, ConfigKind::Preset, &self.backups)
+            }
         } else {
             Err(anyhow::anyhow!(
                 "Preset file not found: {}",
@@ -158,8 +605,23 @@ impl DynamicConfigManager {
 
     /// Get available preset configurations
     pub fn get_available_presets(&self) -> Result<Vec<PresetInfo>> {
-        let index = self.load_preset_index()?;
-        Ok(index.presets)
+        let mut presets = self.load_preset_index()?.presets;
+        if let Some(store) = &self.store {
+            for (id, preset) in store.list_presets()? {
+                if presets.iter().any(|p| p.id == id) {
+                    continue;
+                }
+                presets.push(PresetInfo {
+                    file: id.clone(),
+                    id,
+                    name: preset.name,
+                    description: preset.description,
+                    enabled: true,
+                    priority: presets.len() as u32 + 1,
+                });
+            }
+        }
+        Ok(presets)
     }
 
     /// Get current configuration
@@ -167,26 +629,45 @@ impl DynamicConfigManager {
         self.config.read().unwrap().clone()
     }
 
+    /// Get the configuration as seen by a client, with its override (if any) applied
+    pub fn get_config_for_client(&self, identity: &ClientIdentity) -> Config {
+        let config = self.get_config();
+        match self.clients.resolve(identity) {
+            Some((key, client_override)) => client_override.apply(&config).unwrap_or_else(|e| {
+                warn!("Ignoring invalid override for client '{}': {:#}", key, e);
+                config
+            }),
+            None => config,
+        }
+    }
+
+    /// Per-client override store
+    pub fn clients(&self) -> &ClientStore {
+        &self.clients
+    }
+
     /// Update configuration
     pub fn update_config(&self, new_config: Config) -> Result<()> {
+        self.ensure_writable()?;
+
+        // Reject invalid configuration before touching memory or disk
+        ConfigValidator::new().validate_config(&new_config).ensure_valid()?;
+
         let mut config = self.config.write().unwrap();
+        let events = config_change_events(&config, &new_config);
         *config = new_config;
 
         // Save to file without module_config.modules to avoid duplicating module settings in config.json
-        // Keep in-memory configuration intact, but write a sanitized copy to disk.
-        let mut config_to_save = config.clone();
-        config_to_save.module_config.modules.clear();
-        config_to_save
-            .save_to_file(&self.config_path)
-            .map_err(|e| anyhow::anyhow!("Failed to save config: {}", e))?;
+        self.save_main_config(&config)?;
 
-        // Update modification time
-        *self.last_modified.write().unwrap() = SystemTime::now();
+        self.refresh_file_stamps();
 
         // Notify configuration change
         self.notify_change(
             "custom".to_string(),
             vec!["Main configuration updated".to_string()],
+            events,
+            &config,
         )?;
 
         Ok(())
@@ -194,20 +675,27 @@ impl DynamicConfigManager {
 
     /// Update module configuration
     pub fn update_module_config(&self, new_module_config: GlobalModuleConfig) -> Result<()> {
+        self.ensure_writable()?;
+
+        ConfigValidator::new()
+            .validate_global_module_config(&new_module_config)
+            .ensure_valid()?;
+
         let mut config = self.config.write().unwrap();
+        let events = module_change_events(&config.module_config, &new_module_config);
         config.module_config = new_module_config;
 
         // Save to file
-        let loader = ConfigLoader::new(&self.module_config_path);
-        loader.save_config(&config.module_config)?;
+        self.save_module_config(&config)?;
 
-        // Update modification time
-        *self.last_modified.write().unwrap() = SystemTime::now();
+        self.refresh_file_stamps();
 
         // Notify configuration change
         self.notify_change(
             "custom".to_string(),
             vec!["Module configuration updated".to_string()],
+            events,
+            &config,
         )?;
 
         Ok(())
@@ -216,31 +704,54 @@ impl DynamicConfigManager {
     /// Apply configuration preset
     pub fn apply_preset(&self, preset: String) -> Result<()> {
         info!("Applying configuration preset: {}", preset);
+        self.ensure_writable()?;
 
         let mut changes = Vec::new();
         self.apply_preset_from_file(&preset, &mut changes)?;
 
-        // Notify configuration change
-        self.notify_change(preset, changes)?;
+        // Notify configuration change; the module changes were broadcast by the module update
+        let config = self.get_config();
+        let events = vec![ConfigChangeEvent::Preset { id: preset.clone() }];
+        self.notify_change(preset, changes, events, &config)?;
 
         Ok(())
     }
 
     /// Apply configuration preset from file
     fn apply_preset_from_file(&self, preset_id: &str, changes: &mut Vec<String>) -> Result<()> {
-        // Create a new PresetLoader instance
-        let mut preset_loader = PresetLoader::new(&self.preset_config_path);
+        let module_config = self.preset_module_config(preset_id, changes)?;
 
-        // Load preset index first
-        preset_loader.load_preset_index()?;
+        // Update the module configuration
+        self.update_module_config(module_config)?;
+        changes.push(format!(
+            "Completely replaced configuration with preset: {}",
+            preset_id
+        ));
 
-        // Load the specific preset
-        preset_loader.load_preset(preset_id)?;
+        Ok(())
+    }
+
+    /// Module configuration that applying a preset would produce
+    fn preset_module_config(&self, preset_id: &str, changes: &mut Vec<String>) -> Result<GlobalModuleConfig> {
+        let preset = match self.load_stored_preset(preset_id)? {
+            Some(preset) => preset,
+            None => {
+                // Create a new PresetLoader instance
+                let mut preset_loader = self.preset_loader();
 
-        // Get the preset configuration
-        let preset = preset_loader
-            .get_preset(preset_id)
-            .with_context(|| format!("Preset not loaded: {}", preset_id))?;
+                // Load preset index first
+                preset_loader.load_preset_index()?;
+
+                // Load the specific preset
+                preset_loader.load_preset(preset_id)?;
+
+                // Get the preset configuration
+                preset_loader
+                    .get_preset(preset_id)
+                    .cloned()
+                    .with_context(|| format!("Preset not loaded: {}", preset_id))?
+            }
+        };
 
         // Create a completely new module configuration based on the preset
         let mut module_config = GlobalModuleConfig::default();
@@ -261,43 +772,111 @@ impl DynamicConfigManager {
             changes.push("Updated default rate limit configuration".to_string());
         }
 
-        // Completely replace modules with preset modules
+        // Completely replace modules with preset modules, resolving `{{name}}` variables now
+        let variables = self.get_config().variables;
         for (module_name, preset_module) in &preset.modules {
-            module_config
-                .modules
-                .insert(module_name.clone(), preset_module.clone().into());
+            let preset_module: ModuleConfig = render_serde(preset_module, &variables)
+                .with_context(|| format!("Failed to resolve variables of preset module '{}'", module_name))?;
+            module_config.modules.insert(module_name.clone(), preset_module);
             changes.push(format!("Added module: {}", module_name));
         }
 
-        // Update the module configuration
-        self.update_module_config(module_config)?;
-        changes.push(format!(
-            "Completely replaced configuration with preset: {}",
-            preset_id
-        ));
+        // Enable methods by tag
+        if let Some(enable) = &preset.enable {
+            module_config.enable = Some(enable.clone());
+            changes.push(format!("Set tag filter: {}", enable));
+        }
 
-        Ok(())
+        Ok(module_config)
     }
 
-    /// Notify configuration change
-    fn notify_change(&self, preset: String, changes: Vec<String>) -> Result<()> {
-        let event = ConfigChangeEvent {
-            preset,
-            timestamp: SystemTime::now(),
-            changes,
+    /// What applying a preset would change, without applying it
+    pub fn preview_preset(&self, preset_id: &str) -> Result<PresetPreview> {
+        let mut changes = Vec::new();
+        let new_module_config = self.preset_module_config(preset_id, &mut changes)?;
+        let current = self.get_config().module_config;
+
+        // Methods are known from the ZML sources and the method settings of both configurations
+        let zml_loader = ZmlModuleLoader::from_dir(self.zml_dir()).unwrap_or_default();
+        let mut methods: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        for name in zml_loader.get_all_module_names() {
+            if let Some(module) = zml_loader.get_module(&name) {
+                methods.entry(name.clone()).or_default().extend(module.methods.keys().cloned());
+            }
+        }
+        for module_config in [&current, &new_module_config] {
+            for (name, module) in &module_config.modules {
+                let entry = methods.entry(name.clone()).or_default();
+                entry.extend(module.methods.iter().flat_map(|methods| methods.keys().cloned()));
+            }
+        }
+
+        let mut preview = PresetPreview {
+            preset: preset_id.to_string(),
+            ..PresetPreview::default()
         };
+        for (module_name, method_names) in &methods {
+            match (current.is_module_active(module_name), new_module_config.is_module_active(module_name)) {
+                (false, true) => preview.modules_enabled.push(module_name.clone()),
+                (true, false) => preview.modules_disabled.push(module_name.clone()),
+                _ => {}
+            }
+            for method_name in method_names {
+                let tool = format!("{}.{}", module_name, method_name);
+                let was = current.is_module_active(module_name) && current.is_method_enabled(module_name, method_name);
+                let will = new_module_config.is_module_active(module_name)
+                    && new_module_config.is_method_enabled(module_name, method_name);
+                match (was, will) {
+                    (false, true) => preview.methods_enabled.push(tool),
+                    (true, false) => preview.methods_disabled.push(tool),
+                    _ => {}
+                }
+            }
+        }
+        preview.settings = diff_json(
+            "",
+            &serde_json::to_value(&current).unwrap_or_default(),
+            &serde_json::to_value(&new_module_config).unwrap_or_default(),
+        );
+        preview.valid = ConfigValidator::new()
+            .validate_global_module_config(&new_module_config)
+            .is_valid;
 
-        // Add to change history
-        if let Ok(mut history) = self.change_history.write() {
-            history.push_back(event.clone());
-            // Keep only last 100 changes
-            if history.len() > 100 {
-                history.pop_front();
+        Ok(preview)
+    }
+
+    /// Record a change in the persistent history and broadcast its events
+    fn notify_change(
+        &self,
+        source: String,
+        changes: Vec<String>,
+        events: Vec<ConfigChangeEvent>,
+        config: &Config,
+    ) -> Result<()> {
+        // Nothing is written while the configuration is read-only
+        if !self.read_only && !config.lock && source != STORE_CHANGE_SOURCE {
+            let recorded = match &self.store {
+                Some(store) => store.record_history(config, &current_actor(), &source, changes, DEFAULT_HISTORY_LIMIT),
+                None => self.history.record(config, &current_actor(), &source, changes),
+            };
+            if let Err(e) = recorded {
+                warn!("Failed to record configuration history: {}", e);
             }
         }
 
-        if let Err(e) = self.change_sender.send(event) {
-            warn!("Failed to send configuration change notification: {}", e);
+        for event in events {
+            // Add to change history
+            if let Ok(mut history) = self.change_history.write() {
+                history.push_back(event.clone());
+                // Keep only last 100 changes
+                if history.len() > 100 {
+                    history.pop_front();
+                }
+            }
+
+            if let Err(e) = self.change_sender.send(event) {
+                warn!("Failed to send configuration change notification: {}", e);
+            }
         }
 
         Ok(())
@@ -308,15 +887,9 @@ impl DynamicConfigManager {
         self.change_sender.subscribe()
     }
 
-    /// Check if configuration has been modified
+    /// Check if the configuration, module configuration, presets or ZML sources changed on disk
     pub fn is_modified(&self) -> bool {
-        if let Ok(metadata) = fs::metadata(&self.config_path) {
-            if let Ok(modified_time) = metadata.modified() {
-                let last_modified = *self.last_modified.read().unwrap();
-                return modified_time > last_modified;
-            }
-        }
-        false
+        self.scan_files() != *self.file_stamps.read().unwrap()
     }
 
     /// Get recent configuration changes
@@ -327,37 +900,535 @@ impl DynamicConfigManager {
         }
     }
 
-    /// Reload configuration if modified
+    /// Reload whatever changed on disk and broadcast one event per changed part
     pub fn reload_if_modified(&self) -> Result<bool> {
-        if self.is_modified() {
-            info!("Configuration file modified, reloading...");
+        self.reload_changed("custom", "Configuration reloaded from file".to_string(), false)
+    }
 
-            let new_config = Self::load_config(&self.config_path)?;
-            let new_module_config = Self::load_module_config(&self.module_config_path)?;
+    /// Reload configuration and module configuration from disk, along with changed presets and ZML sources
+    pub fn reload(&self, source: &str, change: String) -> Result<()> {
+        self.reload_changed(source, change, true).map(|_| ())
+    }
 
-            {
-                let mut config = self.config.write().unwrap();
-                *config = new_config;
-            }
+    /// Reload the files changed since they were last loaded (the configuration and module
+    /// configuration unconditionally with `force`); returns whether anything was reloaded
+    fn reload_changed(&self, source: &str, change: String, force: bool) -> Result<bool> {
+        let stamps = self.scan_files();
+        let previous = self.file_stamps.read().unwrap().clone();
+        let store_changed = self.store_changed()?;
+        if stamps == previous && !force && !store_changed {
+            return Ok(false);
+        }
+        // Changes written by another instance are already in the shared history
+        let (source, change) = if store_changed && stamps == previous && !force {
+            (STORE_CHANGE_SOURCE, "Configuration reloaded from the configuration store".to_string())
+        } else {
+            (source, change)
+        };
+        // Record the new state first so an invalid edit is reported once, not on every poll
+        *self.file_stamps.write().unwrap() = stamps.clone();
+
+        let mut changes = Vec::new();
+        let mut events = Vec::new();
+
+        if force || store_changed || stamps.main != previous.main || stamps.modules != previous.modules {
+            info!("Reloading configuration from file...");
+            let persist = (!self.is_read_only()).then_some(&self.backups);
+            let mut new_config = Self::load_config(&self.config_path, self.profile(), persist)?;
+            new_config.module_config = match self.store.as_ref().map(|store| store.load_module_config()) {
+                Some(Ok(Some(module_config))) => module_config,
+                Some(Err(e)) => return Err(e),
+                _ => Self::load_module_config(&self.module_config_path, self.profile(), persist)?,
+            };
+            ConfigValidator::new().validate_config(&new_config).ensure_valid()?;
 
             let mut config = self.config.write().unwrap();
-            config.module_config = new_module_config;
+            events.extend(config_change_events(&config, &new_config));
+            *config = new_config;
+            changes.push(change);
+        }
 
-            *self.last_modified.write().unwrap() = SystemTime::now();
+        if stamps.presets != previous.presets {
+            let files = changed_files(&previous.presets, &stamps.presets);
+            info!("Preset files modified: {}", files.join(", "));
+            changes.push(format!("Preset files changed: {}", files.join(", ")));
+            events.push(ConfigChangeEvent::PresetFiles { files });
+        }
 
-            self.notify_change(
-                "custom".to_string(),
-                vec!["Configuration reloaded from file".to_string()],
-            )?;
+        if stamps.zml != previous.zml {
+            let files = changed_files(&previous.zml, &stamps.zml);
+            info!("ZML sources modified: {}", files.join(", "));
+            changes.push(format!("ZML sources changed: {}", files.join(", ")));
+            events.push(ConfigChangeEvent::Zml { files });
+        }
 
-            Ok(true)
-        } else {
-            Ok(false)
+        let config = self.get_config();
+        self.notify_change(source.to_string(), changes, events, &config)?;
+        Ok(true)
+    }
+
+    /// Whether another instance wrote to the configuration store since the last check
+    fn store_changed(&self) -> Result<bool> {
+        let Some(store) = &self.store else {
+            return Ok(false);
+        };
+        let version = store.version()?;
+        Ok(self.store_version.swap(version, Ordering::SeqCst) != version)
+    }
+
+    /// Poll the configuration directory and reload changed files.
+    /// Running instances pick up edits to the configuration, modules.json, presets and ZML sources
+    /// without a restart.
+    pub fn start_file_watch(self: &Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let manager = self.clone();
+        info!("Watching configuration files every {:?}", interval);
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                if let Err(e) = manager.reload_if_modified() {
+                    warn!("Failed to reload modified configuration files: {:#}", e);
+                }
+            }
+        })
+    }
+
+    /// Periodically sync a remote configuration source and reload when it changed.
+    /// The initial sync is expected to have run before the manager was created.
+    pub fn start_remote_refresh(self: &Arc<Self>, remote: Arc<RemoteSync>) -> tokio::task::JoinHandle<()> {
+        let manager = self.clone();
+        info!(
+            "Refreshing configuration from {} every {:?}",
+            remote.source().describe(),
+            remote.refresh_interval()
+        );
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(remote.refresh_interval());
+            // The first tick completes immediately
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                match remote.sync().await {
+                    Ok(true) => {
+                        info!("Remote configuration changed, reloading...");
+                        let change = format!("Configuration reloaded from {}", remote.source().describe());
+                        if let Err(e) = manager.reload(REMOTE_CHANGE_SOURCE, change) {
+                            warn!("Failed to reload remote configuration: {:#}", e);
+                        }
+                    }
+                    Ok(false) => {}
+                    Err(e) => warn!("Failed to refresh remote configuration: {:#}", e),
+                }
+            }
+        })
+    }
+
+    /// Get persistent configuration history, newest first
+    pub fn get_history(&self) -> Vec<HistoryEntry> {
+        match &self.store {
+            Some(store) => store.history().unwrap_or_else(|e| {
+                warn!("Failed to read configuration history from the configuration store: {}", e);
+                Vec::new()
+            }),
+            None => self.history.entries(),
         }
     }
 
+    /// Restore configuration and module configuration from a history revision
+    pub fn rollback(&self, revision: u64) -> Result<()> {
+        info!("Rolling back configuration to revision {}", revision);
+        self.ensure_writable()?;
+        let snapshot = match &self.store {
+            Some(store) => store.snapshot(revision)?,
+            None => self.history.snapshot(revision)?,
+        };
+
+        let mut config = self.config.write().unwrap();
+        // Snapshots only hold masked credentials; keep the stored ones
+        let mut restored = serde_json::to_value(&snapshot)?;
+        restore_credentials(&mut restored, &serde_json::to_value(&*config)?);
+        let snapshot: Config = serde_json::from_value(restored)
+            .with_context(|| format!("Invalid configuration snapshot for revision {}", revision))?;
+        let events = config_change_events(&config, &snapshot);
+        *config = snapshot;
+
+        self.save_main_config(&config)?;
+        self.save_module_config(&config)?;
+
+        self.refresh_file_stamps();
+
+        self.notify_change(
+            "rollback".to_string(),
+            vec![format!("Rolled back to revision {}", revision)],
+            events,
+            &config,
+        )?;
+
+        Ok(())
+    }
+
+    /// Backups of the configuration files, newest first
+    pub fn list_backups(&self) -> Result<Vec<BackupEntry>> {
+        self.backups.list()
+    }
+
+    /// Restore a configuration file from a backup and reload the configuration
+    pub fn restore_backup(&self, name: &str) -> Result<()> {
+        info!("Restoring configuration backup {}", name);
+        self.ensure_writable()?;
+        let restored = self.backups.restore(name)?;
+        self.reload("backup", format!("Restored {:?} from backup {}", restored, name))
+    }
+
     /// Get configuration file paths
     pub fn get_config_paths(&self) -> (PathBuf, PathBuf, PathBuf) {
         (self.config_path.clone(), self.module_config_path.clone(), self.preset_config_path.clone())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::config::{DirectAuthConfig, DirectAuthType};
+    use crate::config::format::write_config_file;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_change_events_carry_diffs() {
+        let mut old = Config::default();
+        old.module_config.modules.insert("user".to_string(), ModuleConfig::default());
+        old.module_config.modules.insert("project".to_string(), ModuleConfig::default());
+
+        let mut new = old.clone();
+        new.server.port = old.server.port + 1;
+        new.module_config.modules.get_mut("project").unwrap().enabled = false;
+        new.module_config.modules.insert("task".to_string(), ModuleConfig::default());
+
+        let events = config_change_events(&old, &new);
+        assert_eq!(events.len(), 3);
+        match &events[0] {
+            ConfigChangeEvent::ServerConfig { diff } => assert_eq!(diff[0].path, "server.port"),
+            other => panic!("unexpected event: {:?}", other),
+        }
+        match &events[1] {
+            ConfigChangeEvent::Module { name, diff } => {
+                assert_eq!(name, "project");
+                assert_eq!(diff.len(), 1);
+                assert_eq!(diff[0].path, "enabled");
+                assert_eq!(diff[0].new, Some(Value::Bool(false)));
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+        assert!(matches!(&events[2], ConfigChangeEvent::Module { name, .. } if name == "task"));
+
+        let mut new = old.clone();
+        new.module_config.default_timeout_ms = Some(1_000);
+        let events = config_change_events(&old, &new);
+        assert!(matches!(events.as_slice(), [ConfigChangeEvent::ModuleDefaults { .. }]));
+        assert!(config_change_events(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn test_auth_change_event_hides_credentials_and_serializes_tagged() {
+        let old = Config::default();
+        let mut new = old.clone();
+        new.auth.direct_config = Some(DirectAuthConfig {
+            auth_type: DirectAuthType::Bearer,
+            token: Some("plain-secret-token".to_string()),
+            api_key_name: None,
+            username: None,
+            password: None,
+            custom_headers: None,
+            key_pool: None,
+        });
+
+        let events = config_change_events(&old, &new);
+        let [ConfigChangeEvent::Auth { diff }] = events.as_slice() else {
+            panic!("unexpected events: {:?}", events);
+        };
+        assert!(diff.iter().all(|entry| entry.path.starts_with("auth.direct_config")));
+
+        let json = serde_json::to_value(&events[0]).unwrap();
+        assert_eq!(json["type"], "auth");
+        assert!(!json.to_string().contains("plain-secret-token"));
+    }
+
+    #[test]
+    fn test_read_only_manager_rejects_changes_without_writing() {
+        let temp_dir = tempdir().unwrap();
+        let dir = temp_dir.path();
+        let manager = DynamicConfigManager::with_options(
+            dir.join("config.json"),
+            dir.join("modules.json"),
+            dir.join("presets"),
+            None,
+            true,
+        )
+        .unwrap();
+
+        assert!(manager.is_read_only());
+        assert!(manager.update_config(Config::default()).is_err());
+        assert!(manager.update_module_config(GlobalModuleConfig::default()).is_err());
+        assert!(!dir.join("config.json").exists());
+        assert!(manager.get_history().is_empty());
+
+        let mut locked = Config::default();
+        locked.lock = true;
+        write_config_file(dir.join("config.json"), &locked, ConfigFormat::Json).unwrap();
+        let manager = DynamicConfigManager::with_profile(
+            dir.join("config.json"),
+            dir.join("modules.json"),
+            dir.join("presets"),
+            None,
+        )
+        .unwrap();
+        assert!(manager.is_read_only());
+        assert!(manager.update_config(Config::default()).is_err());
+    }
+
+    #[test]
+    fn test_module_updates_are_backed_up_and_restorable() {
+        let temp_dir = tempdir().unwrap();
+        let dir = temp_dir.path();
+        let manager = DynamicConfigManager::with_profile(
+            dir.join("config.json"),
+            dir.join("modules.json"),
+            dir.join("presets"),
+            None,
+        )
+        .unwrap();
+
+        let mut module_config = GlobalModuleConfig::default();
+        module_config.modules.insert("user".to_string(), ModuleConfig::default());
+        manager.update_module_config(module_config.clone()).unwrap();
+        module_config.modules.get_mut("user").unwrap().enabled = false;
+        manager.update_module_config(module_config).unwrap();
+        assert!(!manager.get_config().is_module_enabled("user"));
+
+        let backup = manager
+            .list_backups()
+            .unwrap()
+            .into_iter()
+            .find(|backup| backup.file == "modules.json")
+            .unwrap();
+        manager.restore_backup(&backup.name).unwrap();
+        assert!(manager.get_config().is_module_enabled("user"));
+    }
+
+    #[test]
+    fn test_saves_keep_environment_placeholders_in_files() {
+        std::env::set_var("MCP_TEST_SAVED_TOKEN", "saved-secret");
+        let temp_dir = tempdir().unwrap();
+        let dir = temp_dir.path();
+        let config = Config::with_bearer_auth("${MCP_TEST_SAVED_TOKEN}".to_string());
+        write_config_file(dir.join("config.json"), &config, ConfigFormat::Json).unwrap();
+        let manager = DynamicConfigManager::with_profile(
+            dir.join("config.json"),
+            dir.join("modules.json"),
+            dir.join("presets"),
+            None,
+        )
+        .unwrap();
+
+        let mut config = manager.get_config();
+        let token = config.auth.direct_config.as_ref().unwrap().token.clone();
+        assert_eq!(token.as_deref(), Some("saved-secret"));
+
+        config.server.port += 1;
+        manager.update_config(config).unwrap();
+        let content = fs::read_to_string(dir.join("config.json")).unwrap();
+        assert!(content.contains("${MCP_TEST_SAVED_TOKEN}"));
+        assert!(!content.contains("saved-secret"));
+    }
+
+    #[test]
+    fn test_rollback_keeps_credentials_out_of_history() {
+        let temp_dir = tempdir().unwrap();
+        let dir = temp_dir.path();
+        let config = Config::with_bearer_auth("stored-token-5678".to_string());
+        write_config_file(dir.join("config.json"), &config, ConfigFormat::Json).unwrap();
+        let manager = DynamicConfigManager::with_profile(
+            dir.join("config.json"),
+            dir.join("modules.json"),
+            dir.join("presets"),
+            None,
+        )
+        .unwrap();
+
+        let mut config = manager.get_config();
+        let baseline = manager.get_history()[0].revision;
+        config.server.port += 1;
+        manager.update_config(config).unwrap();
+        manager.rollback(baseline).unwrap();
+
+        let config = manager.get_config();
+        assert_eq!(config.server.port, Config::default().server.port);
+        let token = config.auth.direct_config.as_ref().unwrap().token.clone();
+        assert_eq!(token.as_deref(), Some("stored-token-5678"));
+        for entry in fs::read_dir(dir.join("history")).unwrap() {
+            let content = fs::read_to_string(entry.unwrap().path()).unwrap();
+            assert!(!content.contains("stored-token-5678"));
+        }
+    }
+
+    #[test]
+    fn test_preview_preset_lists_changes_without_applying() {
+        let temp_dir = tempdir().unwrap();
+        let dir = temp_dir.path();
+        let manager = DynamicConfigManager::with_profile(
+            dir.join("config.json"),
+            dir.join("modules.json"),
+            dir.join("presets"),
+            None,
+        )
+        .unwrap();
+
+        let mut user = ModuleConfig::default();
+        user.add_method("list_users".to_string(), crate::config::module::MethodConfig::default());
+        let mut module_config = GlobalModuleConfig::default();
+        module_config.modules.insert("user".to_string(), user);
+        manager.update_module_config(module_config).unwrap();
+
+        let preset = StoredPresetConfig {
+            config_version: crate::config::migrate::CURRENT_CONFIG_VERSION,
+            name: "Bugs".to_string(),
+            description: "Bug tracking only".to_string(),
+            default_access_level: None,
+            default_rate_limit: None,
+            modules: HashMap::from([("bug".to_string(), ModuleConfig::default())]),
+            enable: None,
+        };
+        manager.save_preset("bugs", &preset).unwrap();
+
+        let preview = manager.preview_preset("bugs").unwrap();
+        assert_eq!(preview.modules_enabled, vec!["bug"]);
+        assert_eq!(preview.modules_disabled, vec!["user"]);
+        assert_eq!(preview.methods_disabled, vec!["user.list_users"]);
+        assert!(preview.settings.iter().any(|d| d.path == "modules.user"));
+        assert!(preview.valid);
+        assert!(manager.get_config().is_module_enabled("user"));
+    }
+
+    #[test]
+    fn test_read_only_manager_does_not_rewrite_old_presets() {
+        let temp_dir = tempdir().unwrap();
+        let dir = temp_dir.path();
+        let presets = dir.join("presets");
+        fs::create_dir_all(&presets).unwrap();
+        let index = serde_json::json!({
+            "presets": [{
+                "id": "legacy",
+                "name": "Legacy",
+                "description": "Written before config_version existed",
+                "file": "legacy",
+                "enabled": true,
+                "priority": 1
+            }]
+        });
+        fs::write(presets.join("index.json"), index.to_string()).unwrap();
+        let legacy = serde_json::json!({
+            "name": "Legacy",
+            "description": "Written before config_version existed",
+            "default_access_level": "internal",
+            "modules": { "bug": { "enabled": true } }
+        })
+        .to_string();
+        fs::write(presets.join("legacy.json"), &legacy).unwrap();
+
+        let manager = DynamicConfigManager::with_options(
+            dir.join("config.json"),
+            dir.join("modules.json"),
+            presets.clone(),
+            None,
+            true,
+        )
+        .unwrap();
+        let preview = manager.preview_preset("legacy").unwrap();
+        assert_eq!(preview.modules_enabled, vec!["bug"]);
+        let preset = manager.load_preset_config("legacy").unwrap();
+        assert_eq!(preset.default_access_level.as_deref(), Some("Internal"));
+
+        assert_eq!(fs::read_to_string(presets.join("legacy.json")).unwrap(), legacy);
+        let mut files: Vec<_> = fs::read_dir(&presets)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        files.sort();
+        assert_eq!(files, vec!["index.json", "legacy.json"]);
+        assert!(!dir.join("backups").exists());
+        assert!(!dir.join("config.json").exists());
+    }
+
+    #[cfg(feature = "sql")]
+    #[test]
+    fn test_instances_share_configuration_store() {
+        let temp_dir = tempdir().unwrap();
+        let mut config = Config::new();
+        config.store = Some(crate::config::store::StoreConfig {
+            url: format!("sqlite://{}", temp_dir.path().join("config.db").display()),
+        });
+        let open = |name: &str| {
+            let dir = temp_dir.path().join(name);
+            fs::create_dir_all(&dir).unwrap();
+            write_config_file(&dir.join("config.json"), &config, ConfigFormat::Json).unwrap();
+            DynamicConfigManager::with_profile(
+                dir.join("config.json"),
+                dir.join("modules.json"),
+                dir.join("presets"),
+                None,
+            )
+            .unwrap()
+        };
+        let first = open("first");
+        let second = open("second");
+
+        let mut module_config = GlobalModuleConfig::default();
+        module_config.modules.insert("user".to_string(), ModuleConfig::default());
+        first.update_module_config(module_config).unwrap();
+        assert!(!first.reload_if_modified().unwrap());
+
+        assert!(second.reload_if_modified().unwrap());
+        assert!(second.get_config().is_module_enabled("user"));
+        // The reload of the second instance is not recorded again
+        assert_eq!(second.get_history().len(), first.get_history().len());
+    }
+
+    #[test]
+    fn test_reload_if_modified_broadcasts_changed_parts() {
+        let temp_dir = tempdir().unwrap();
+        let dir = temp_dir.path();
+        let manager = DynamicConfigManager::with_profile(
+            dir.join("config.json"),
+            dir.join("modules.json"),
+            dir.join("presets"),
+            None,
+        )
+        .unwrap();
+        let mut changes = manager.subscribe();
+        assert!(!manager.reload_if_modified().unwrap());
+
+        let mut module_config = GlobalModuleConfig::default();
+        module_config.modules.insert("user".to_string(), ModuleConfig::default());
+        write_config_file(dir.join("modules.json"), &module_config, ConfigFormat::Json).unwrap();
+        fs::create_dir_all(dir.join("zml")).unwrap();
+        fs::write(dir.join("zml").join("user.zml"), "module user {}").unwrap();
+
+        assert!(manager.is_modified());
+        assert!(manager.reload_if_modified().unwrap());
+        assert!(manager.get_config().is_module_enabled("user"));
+        assert!(matches!(
+            changes.try_recv().unwrap(),
+            ConfigChangeEvent::Module { name, .. } if name == "user"
+        ));
+        assert_eq!(
+            changes.try_recv().unwrap(),
+            ConfigChangeEvent::Zml {
+                files: vec!["user.zml".to_string()]
+            }
+        );
+        assert!(changes.try_recv().is_err());
+    }
+}
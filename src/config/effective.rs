@@ -0,0 +1,296 @@
+//! Effective (resolved) module settings for MCP-ANY-REST
+//! This module cascades access level, rate limit, timeout, retry, cache, header, token scope,
+//! transform script, mock and response validation settings from the global module configuration down to modules and
+//! methods, so each method sees one resolved value.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::config::config::Config;
+use crate::config::module::{
+    AccessLevel, MockResponse, ModuleConfig, RateLimitConfig, ResponseValidation, RetryConfig, TransformConfig,
+};
+use crate::config::module_env::ModuleEnv;
+use crate::config::variables::render;
+use log::warn;
+
+/// Resolved settings of a single method
+#[derive(Debug, Clone, Serialize)]
+pub struct EffectiveMethodSettings {
+    pub enabled: bool,
+    pub access_level: AccessLevel,
+    pub rate_limit: Option<RateLimitConfig>,
+    /// Request timeout in milliseconds; `None` keeps the HTTP client timeout (`api.timeout` or the backend timeout)
+    pub timeout_ms: Option<u64>,
+    pub headers: BTreeMap<String, String>,
+    /// OAuth2 scopes of the method's token; empty uses the configured token
+    pub scopes: Vec<String>,
+    pub audience: Option<String>,
+    /// Configured retries; `None` leaves them to the ZML declaration and the backend's `retry`
+    pub retry: Option<RetryConfig>,
+    /// Configured response cache TTL in milliseconds; `None` leaves it to the ZML declaration
+    pub cache_ttl_ms: Option<u64>,
+    /// Transformation scripts, each from the method or else the module
+    #[serde(skip_serializing_if = "TransformConfig::is_empty")]
+    pub transform: TransformConfig,
+    /// Whether calls are answered with `mocks` instead of calling the API
+    pub mock: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub mocks: Vec<MockResponse>,
+    /// What a response not matching its ZML declaration does to the call
+    pub validation: ResponseValidation,
+}
+
+/// Resolved settings of a module and its known methods
+#[derive(Debug, Clone, Serialize)]
+pub struct EffectiveModuleSettings {
+    pub enabled: bool,
+    pub access_level: AccessLevel,
+    pub rate_limit: Option<RateLimitConfig>,
+    pub timeout_ms: Option<u64>,
+    pub headers: BTreeMap<String, String>,
+    pub scopes: Vec<String>,
+    pub audience: Option<String>,
+    pub retry: Option<RetryConfig>,
+    pub cache_ttl_ms: Option<u64>,
+    #[serde(skip_serializing_if = "TransformConfig::is_empty")]
+    pub transform: TransformConfig,
+    pub mock: bool,
+    pub validation: ResponseValidation,
+    pub methods: BTreeMap<String, EffectiveMethodSettings>,
+}
+
+/// Resolve the settings of a module: module values override the global defaults
+pub fn resolve_module(config: &Config, module_name: &str) -> EffectiveModuleSettings {
+    let global = &config.module_config;
+    let module = global.get_module_config(module_name);
+
+    let mut headers: BTreeMap<String, String> = global.default_headers.clone().into_iter().collect();
+    if let Some(module) = module {
+        headers.extend(module.headers.clone());
+    }
+    headers.extend(env_headers(module));
+    let headers = render_headers(config, headers);
+
+    EffectiveModuleSettings {
+        enabled: global.is_module_enabled(module_name),
+        access_level: module
+            .and_then(|m| m.access_level.clone())
+            .unwrap_or_else(|| global.default_access_level.clone()),
+        rate_limit: module
+            .and_then(|m| m.rate_limit.clone())
+            .or_else(|| global.default_rate_limit.clone()),
+        timeout_ms: module.and_then(|m| m.timeout_ms).or(global.default_timeout_ms),
+        headers,
+        scopes: module.map(|m| m.scopes.clone()).unwrap_or_default(),
+        audience: module.and_then(|m| m.audience.clone()),
+        retry: module.and_then(|m| m.retry.clone()),
+        cache_ttl_ms: module.and_then(|m| m.cache_ttl_ms),
+        transform: module.map(|m| m.transform.clone()).unwrap_or_default(),
+        mock: module.and_then(|m| m.mock).unwrap_or(global.mock),
+        validation: module.and_then(|m| m.validation).unwrap_or_default(),
+        methods: BTreeMap::new(),
+    }
+}
+
+/// Headers set through the module's `env_prefix` variables
+fn env_headers(module: Option<&ModuleConfig>) -> BTreeMap<String, String> {
+    module
+        .and_then(ModuleEnv::for_module)
+        .map(|env| env.headers)
+        .unwrap_or_default()
+}
+
+/// Substitute the configuration variables into header values; a header referencing an undefined
+/// variable keeps its raw value (the validator reports it)
+fn render_headers(config: &Config, headers: BTreeMap<String, String>) -> BTreeMap<String, String> {
+    headers
+        .into_iter()
+        .map(|(name, value)| match render(&value, &config.variables) {
+            Ok(rendered) => (name, rendered),
+            Err(e) => {
+                warn!("Header '{}': {}", name, e);
+                (name, value)
+            }
+        })
+        .collect()
+}
+
+/// Resolve the settings of a method. Precedence for each value:
+/// method configuration, module configuration, then the global defaults.
+/// The timeout declared in ZML (`declared_timeout_ms`) sits between the module and the global default.
+pub fn resolve_method(
+    config: &Config,
+    module_name: &str,
+    method_name: &str,
+    declared_timeout_ms: Option<u64>,
+) -> EffectiveMethodSettings {
+    let global = &config.module_config;
+    let module = global.get_module_config(module_name);
+    let method = global.get_method_config(module_name, method_name);
+    let module_settings = resolve_module(config, module_name);
+
+    let mut headers = module_settings.headers;
+    if let Some(method) = method {
+        headers.extend(method.headers.clone());
+    }
+    // Headers from the module's environment variables win over every configured header
+    headers.extend(env_headers(module));
+    let headers = render_headers(config, headers);
+
+    EffectiveMethodSettings {
+        enabled: global.is_method_enabled(module_name, method_name),
+        access_level: method
+            .and_then(|m| m.access_level.clone())
+            .unwrap_or(module_settings.access_level),
+        rate_limit: method
+            .and_then(|m| m.rate_limit.clone())
+            .or(module_settings.rate_limit),
+        timeout_ms: method
+            .and_then(|m| m.timeout_ms)
+            .or_else(|| module.and_then(|m| m.timeout_ms))
+            .or(declared_timeout_ms)
+            .or(global.default_timeout_ms),
+        headers,
+        // Scopes are a set: the method's replace the module's rather than adding to them
+        scopes: method
+            .map(|m| m.scopes.clone())
+            .filter(|scopes| !scopes.is_empty())
+            .unwrap_or(module_settings.scopes),
+        audience: method.and_then(|m| m.audience.clone()).or(module_settings.audience),
+        retry: method.and_then(|m| m.retry.clone()).or(module_settings.retry),
+        cache_ttl_ms: method.and_then(|m| m.cache_ttl_ms).or(module_settings.cache_ttl_ms),
+        transform: method
+            .map(|m| m.transform.or(&module_settings.transform))
+            .unwrap_or(module_settings.transform),
+        mock: module_settings.mock,
+        mocks: method.map(|m| m.mocks.clone()).unwrap_or_default(),
+        validation: method.and_then(|m| m.validation).unwrap_or(module_settings.validation),
+    }
+}
+
+/// Resolve every configured module, including the configured methods and the `known_methods`
+/// of each module (e.g. the methods declared in ZML with their declared timeouts)
+pub fn resolve_config(
+    config: &Config,
+    known_methods: &HashMap<String, HashMap<String, Option<u64>>>,
+) -> BTreeMap<String, EffectiveModuleSettings> {
+    let mut module_names: Vec<&String> = config.module_config.modules.keys().collect();
+    module_names.extend(known_methods.keys());
+    module_names.sort();
+    module_names.dedup();
+
+    module_names
+        .into_iter()
+        .map(|module_name| {
+            let mut methods: BTreeMap<&str, Option<u64>> = BTreeMap::new();
+            if let Some(configured) = config
+                .module_config
+                .get_module_config(module_name)
+                .and_then(|m| m.methods.as_ref())
+            {
+                methods.extend(configured.keys().map(|name| (name.as_str(), None)));
+            }
+            if let Some(declared) = known_methods.get(module_name) {
+                methods.extend(declared.iter().map(|(name, timeout)| (name.as_str(), *timeout)));
+            }
+
+            let mut settings = resolve_module(config, module_name);
+            settings.methods = methods
+                .into_iter()
+                .map(|(method_name, declared_timeout_ms)| {
+                    (
+                        method_name.to_string(),
+                        resolve_method(config, module_name, method_name, declared_timeout_ms),
+                    )
+                })
+                .collect();
+            (module_name.clone(), settings)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::module::{MethodConfig, ModuleConfig};
+
+    #[test]
+    fn test_settings_cascade_from_global_to_method() {
+        let mut config = Config::default();
+        config.module_config.default_timeout_ms = Some(10_000);
+        config
+            .module_config
+            .default_headers
+            .insert("X-Tenant".to_string(), "acme".to_string());
+
+        let mut module = ModuleConfig::default();
+        module.access_level = Some(AccessLevel::Public);
+        module.headers.insert("X-Module".to_string(), "user".to_string());
+        module.scopes = vec!["users:read".to_string()];
+        module.audience = Some("https://users.example.com".to_string());
+        module.transform.pre_request = Some("params.tenant = \"acme\";".to_string());
+        module.transform.post_response = Some("response.remove(\"token\");".to_string());
+        let mut method = MethodConfig::default();
+        method.access_level = None;
+        method.timeout_ms = Some(2_000);
+        method.headers.insert("X-Tenant".to_string(), "globex".to_string());
+        method.scopes = vec!["users:admin".to_string()];
+        method.retry = Some(RetryConfig { attempts: 5, ..RetryConfig::default() });
+        method.transform.post_response = Some("response = response.data;".to_string());
+        method.mocks.push(MockResponse { when: BTreeMap::new(), response: serde_json::json!({ "deleted": true }) });
+        method.validation = Some(ResponseValidation::Off);
+        module.mock = Some(true);
+        module.validation = Some(ResponseValidation::Strict);
+        module.add_method("delete_user".to_string(), method);
+        config.module_config.modules.insert("user".to_string(), module);
+
+        let list = resolve_method(&config, "user", "list_users", Some(5_000));
+        assert!(matches!(list.access_level, AccessLevel::Public));
+        assert_eq!(list.timeout_ms, Some(5_000));
+        assert_eq!(list.headers["X-Tenant"], "acme");
+        assert_eq!(list.headers["X-Module"], "user");
+        assert_eq!(list.rate_limit.unwrap().requests_per_minute, 60);
+        assert_eq!(list.scopes, vec!["users:read"]);
+        assert_eq!(list.retry, None);
+        assert!(list.mock && list.mocks.is_empty());
+        assert_eq!(list.validation, ResponseValidation::Strict);
+
+        let delete = resolve_method(&config, "user", "delete_user", Some(5_000));
+        assert!(matches!(delete.access_level, AccessLevel::Public));
+        assert_eq!(delete.timeout_ms, Some(2_000));
+        assert_eq!(delete.headers["X-Tenant"], "globex");
+        assert_eq!(delete.scopes, vec!["users:admin"]);
+        assert_eq!(delete.audience.as_deref(), Some("https://users.example.com"));
+        assert_eq!(delete.retry.unwrap().attempts, 5);
+        assert_eq!(delete.transform.pre_request, list.transform.pre_request);
+        assert_eq!(delete.transform.post_response.as_deref(), Some("response = response.data;"));
+        assert_eq!(delete.mocks[0].response["deleted"], true);
+        assert_eq!(delete.validation, ResponseValidation::Off);
+
+        let unconfigured = resolve_method(&config, "project", "list_projects", None);
+        assert!(!unconfigured.enabled);
+        assert!(matches!(unconfigured.access_level, AccessLevel::Internal));
+        assert_eq!(unconfigured.timeout_ms, Some(10_000));
+        assert!(unconfigured.scopes.is_empty());
+        assert!(!unconfigured.mock);
+        assert_eq!(unconfigured.validation, ResponseValidation::Warn);
+    }
+
+    #[test]
+    fn test_resolve_config_includes_known_methods() {
+        let mut config = Config::default();
+        config
+            .module_config
+            .modules
+            .insert("user".to_string(), ModuleConfig::default());
+        let known = HashMap::from([(
+            "user".to_string(),
+            HashMap::from([("get_user".to_string(), Some(1_500))]),
+        )]);
+
+        let effective = resolve_config(&config, &known);
+        assert_eq!(effective["user"].methods["get_user"].timeout_ms, Some(1_500));
+        assert!(effective["user"].enabled);
+    }
+}
@@ -0,0 +1,196 @@
+//! Environment variable interpolation for MCP-ANY-REST configuration
+//! This module resolves `${VAR}` and `${VAR:-default}` placeholders in configuration strings,
+//! so credentials and environment specific values can stay out of configuration files
+
+use anyhow::{bail, Result};
+use serde_json::Value;
+
+/// Resolve placeholders in a string using the process environment
+pub fn interpolate_str(input: &str) -> Result<String> {
+    interpolate_str_with(input, |name| std::env::var(name).ok())
+}
+
+/// Resolve placeholders in a string using a custom variable lookup.
+///
+/// `${VAR}` fails when `VAR` is unset, `${VAR:-default}` falls back to `default`
+/// when `VAR` is unset or empty, and `$${` produces a literal `${`.
+pub fn interpolate_str_with<F>(input: &str, lookup: F) -> Result<String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(pos) = rest.find('$') {
+        output.push_str(&rest[..pos]);
+        let tail = &rest[pos..];
+
+        if tail.starts_with("$${") {
+            output.push_str("${");
+            rest = &tail[3..];
+        } else if let Some(body) = tail.strip_prefix("${") {
+            let end = match body.find('}') {
+                Some(end) => end,
+                None => bail!("Unterminated placeholder in configuration value: {}", input),
+            };
+            output.push_str(&resolve_placeholder(&body[..end], &lookup)?);
+            rest = &body[end + 1..];
+        } else {
+            output.push('$');
+            rest = &tail[1..];
+        }
+    }
+
+    output.push_str(rest);
+    Ok(output)
+}
+
+/// Resolve placeholders in every string of a JSON value, recursively
+pub fn interpolate_value(value: &mut Value) -> Result<()> {
+    interpolate_value_with(value, &|name: &str| std::env::var(name).ok())
+}
+
+/// Resolve placeholders in every string of a JSON value using a custom variable lookup
+pub fn interpolate_value_with<F>(value: &mut Value, lookup: &F) -> Result<()>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    match value {
+        Value::String(s) if s.contains('$') => {
+            *s = interpolate_str_with(s, lookup)?;
+        }
+        Value::Array(items) => {
+            for item in items {
+                interpolate_value_with(item, lookup)?;
+            }
+        }
+        Value::Object(map) => {
+            for item in map.values_mut() {
+                interpolate_value_with(item, lookup)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Put the placeholders of `raw` back into `value` wherever `value` still holds what they
+/// resolve to, so writing a resolved configuration keeps `${VAR}` in the file instead of the
+/// secret. Returns whether any placeholder was restored.
+pub fn restore_placeholders(value: &mut Value, raw: &Value) -> bool {
+    restore_placeholders_with(value, raw, &|name: &str| std::env::var(name).ok())
+}
+
+/// Restore placeholders like `restore_placeholders` using a custom variable lookup
+pub fn restore_placeholders_with<F>(value: &mut Value, raw: &Value, lookup: &F) -> bool
+where
+    F: Fn(&str) -> Option<String>,
+{
+    match (value, raw) {
+        (Value::String(s), Value::String(template)) if template.contains('$') => {
+            let unchanged = interpolate_str_with(template, lookup).is_ok_and(|resolved| resolved == *s);
+            if unchanged && *s != *template {
+                *s = template.clone();
+            }
+            unchanged
+        }
+        (Value::Array(items), Value::Array(raw_items)) => items
+            .iter_mut()
+            .zip(raw_items)
+            .fold(false, |restored, (item, raw_item)| {
+                restore_placeholders_with(item, raw_item, lookup) | restored
+            }),
+        (Value::Object(map), Value::Object(raw_map)) => {
+            map.iter_mut().fold(false, |restored, (key, item)| match raw_map.get(key) {
+                Some(raw_item) => restore_placeholders_with(item, raw_item, lookup) | restored,
+                None => restored,
+            })
+        }
+        _ => false,
+    }
+}
+
+/// Resolve a single placeholder body (`VAR` or `VAR:-default`)
+fn resolve_placeholder<F>(body: &str, lookup: &F) -> Result<String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let (name, default) = match body.split_once(":-") {
+        Some((name, default)) => (name, Some(default)),
+        None => (body, None),
+    };
+
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        bail!("Invalid environment variable name in placeholder: ${{{}}}", body);
+    }
+
+    match (lookup(name), default) {
+        (Some(value), Some(default)) if value.is_empty() => Ok(default.to_string()),
+        (Some(value), _) => Ok(value),
+        (None, Some(default)) => Ok(default.to_string()),
+        (None, None) => bail!("Environment variable '{}' is not set", name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "API_HOST" => Some("api.example.com".to_string()),
+            "API_TOKEN" => Some("secret-token".to_string()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_interpolate_placeholders() {
+        assert_eq!(
+            interpolate_str_with("https://${API_HOST}/v1", lookup).unwrap(),
+            "https://api.example.com/v1"
+        );
+        assert_eq!(interpolate_str_with("${MISSING:-fallback}", lookup).unwrap(), "fallback");
+        assert_eq!(interpolate_str_with("${EMPTY:-fallback}", lookup).unwrap(), "fallback");
+        assert_eq!(interpolate_str_with("cost: $5 $${API_HOST}", lookup).unwrap(), "cost: $5 ${API_HOST}");
+    }
+
+    #[test]
+    fn test_interpolate_errors() {
+        assert!(interpolate_str_with("${MISSING}", lookup).is_err());
+        assert!(interpolate_str_with("${API_HOST", lookup).is_err());
+        assert!(interpolate_str_with("${BAD NAME}", lookup).is_err());
+    }
+
+    #[test]
+    fn test_interpolate_nested_value() {
+        let mut value = json!({
+            "api": { "base_url": "https://${API_HOST}" },
+            "headers": [{ "Authorization": "Bearer ${API_TOKEN}" }],
+            "timeout": 30
+        });
+        interpolate_value_with(&mut value, &lookup).unwrap();
+        assert_eq!(value["api"]["base_url"], "https://api.example.com");
+        assert_eq!(value["headers"][0]["Authorization"], "Bearer secret-token");
+        assert_eq!(value["timeout"], 30);
+    }
+
+    #[test]
+    fn test_restore_placeholders_keeps_unchanged_references() {
+        let raw = json!({
+            "api": { "base_url": "https://${API_HOST}" },
+            "headers": [{ "Authorization": "Bearer ${API_TOKEN}" }],
+            "note": "cost $${API_HOST}"
+        });
+        let mut value = raw.clone();
+        interpolate_value_with(&mut value, &lookup).unwrap();
+        value["api"]["base_url"] = json!("https://changed.example.com");
+
+        assert!(restore_placeholders_with(&mut value, &raw, &lookup));
+        assert_eq!(value["api"]["base_url"], "https://changed.example.com");
+        assert_eq!(value["headers"][0]["Authorization"], "Bearer ${API_TOKEN}");
+        assert_eq!(value["note"], "cost $${API_HOST}");
+    }
+}
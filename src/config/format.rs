@@ -0,0 +1,322 @@
+//! Configuration file formats for MCP-ANY-REST
+//! This module detects configuration formats by file extension and converts between JSON, YAML and TOML
+
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use crate::config::backup::ConfigBackups;
+use crate::config::env::interpolate_value;
+use crate::config::migrate::{migrate_and_persist, migrate_value, ConfigKind};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Supported configuration file formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl ConfigFormat {
+    /// Extensions probed when looking up a configuration file by stem, in priority order
+    pub const SEARCH_EXTENSIONS: [&'static str; 4] = ["yaml", "yml", "toml", "json"];
+
+    /// Detect format from a file extension (without the leading dot)
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext.to_ascii_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "yaml" | "yml" => Some(Self::Yaml),
+            "toml" => Some(Self::Toml),
+            _ => None,
+        }
+    }
+
+    /// Detect format from a file path
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        path.as_ref()
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(Self::from_extension)
+    }
+
+    /// Canonical file extension for this format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Yaml => "yaml",
+            Self::Toml => "toml",
+        }
+    }
+
+    /// Human readable format name
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Json => "JSON",
+            Self::Yaml => "YAML",
+            Self::Toml => "TOML",
+        }
+    }
+
+    /// Parse content in this format
+    pub fn parse<T: DeserializeOwned>(&self, content: &str) -> Result<T> {
+        let value = match self {
+            Self::Json => serde_json::from_str(content)?,
+            Self::Yaml => serde_yaml::from_str(content)?,
+            Self::Toml => toml::from_str(content)?,
+        };
+        Ok(value)
+    }
+
+    /// Parse content in this format, resolving `${VAR}` placeholders in string values
+    pub fn parse_interpolated<T: DeserializeOwned>(&self, content: &str) -> Result<T> {
+        let mut value: Value = self.parse(content)?;
+        interpolate_value(&mut value)?;
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// Serialize a value in this format
+    pub fn serialize<T: Serialize>(&self, value: &T) -> Result<String> {
+        let content = match self {
+            Self::Json => serde_json::to_string_pretty(value)?,
+            Self::Yaml => serde_yaml::to_string(value)?,
+            Self::Toml => {
+                // TOML has no null, so drop empty values before serializing
+                let mut value = serde_json::to_value(value)?;
+                strip_nulls(&mut value);
+                toml::to_string_pretty(&value)?
+            }
+        };
+        Ok(content)
+    }
+
+    /// Find an existing configuration file named `<stem>.<ext>` in a directory.
+    /// Falls back to `<stem>.<default_ext>` when no file exists.
+    pub fn resolve_path(dir: impl AsRef<Path>, stem: &str, default: ConfigFormat) -> PathBuf {
+        Self::find_existing(&dir, stem)
+            .unwrap_or_else(|| dir.as_ref().join(format!("{}.{}", stem, default.extension())))
+    }
+
+    /// Find an existing configuration file named `<stem>.<ext>` in a directory
+    pub fn find_existing(dir: impl AsRef<Path>, stem: &str) -> Option<PathBuf> {
+        Self::SEARCH_EXTENSIONS
+            .iter()
+            .map(|ext| dir.as_ref().join(format!("{}.{}", stem, ext)))
+            .find(|path| path.exists())
+    }
+}
+
+impl std::fmt::Display for ConfigFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.extension())
+    }
+}
+
+impl std::str::FromStr for ConfigFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::from_extension(s).ok_or_else(|| anyhow::anyhow!("Unsupported configuration format: {}", s))
+    }
+}
+
+/// Read and parse a configuration file, detecting the format by extension
+/// and resolving `${VAR}` placeholders from the environment
+pub fn read_config_file<T: DeserializeOwned>(path: impl AsRef<Path>, default: ConfigFormat) -> Result<T> {
+    let path = path.as_ref();
+    let format = ConfigFormat::from_path(path).unwrap_or(default);
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read configuration file: {:?}", path))?;
+    format
+        .parse_interpolated(&content)
+        .with_context(|| format!("Failed to parse {} configuration file: {:?}", format.name(), path))
+}
+
+/// Read a configuration file like `read_config_file`, first upgrading it to the current
+/// schema version and writing the migrated document back when it was outdated;
+/// the original file is kept in `backups`
+pub fn read_migrated_config_file<T: DeserializeOwned>(
+    path: impl AsRef<Path>,
+    default: ConfigFormat,
+    kind: ConfigKind,
+    backups: &ConfigBackups,
+) -> Result<T> {
+    read_migrated(path.as_ref(), default, kind, Some(backups))
+}
+
+/// Read a configuration file like `read_migrated_config_file` without writing anything back;
+/// older schema versions are only upgraded in memory
+pub fn read_migrated_config_file_readonly<T: DeserializeOwned>(
+    path: impl AsRef<Path>,
+    default: ConfigFormat,
+    kind: ConfigKind,
+) -> Result<T> {
+    read_migrated(path.as_ref(), default, kind, None)
+}
+
+fn read_migrated<T: DeserializeOwned>(
+    path: &Path,
+    default: ConfigFormat,
+    kind: ConfigKind,
+    backups: Option<&ConfigBackups>,
+) -> Result<T> {
+    let format = ConfigFormat::from_path(path).unwrap_or(default);
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read configuration file: {:?}", path))?;
+    let mut value: Value = format
+        .parse(&content)
+        .with_context(|| format!("Failed to parse {} configuration file: {:?}", format.name(), path))?;
+
+    if let Some(backups) = backups {
+        migrate_and_persist(path, format, kind, &mut value, backups)?;
+    } else {
+        migrate_value(kind, &mut value)
+            .with_context(|| format!("Failed to migrate configuration file: {:?}", path))?;
+    }
+    interpolate_value(&mut value)
+        .with_context(|| format!("Failed to resolve environment variables in: {:?}", path))?;
+    serde_json::from_value(value)
+        .with_context(|| format!("Invalid configuration structure: {:?}", path))
+}
+
+/// Serialize and write a configuration file, detecting the format by extension.
+/// The content is written to a temporary file next to the target, parsed back and only then
+/// renamed over the original, so a failed write never leaves a truncated configuration behind.
+pub fn write_config_file<T: Serialize>(path: impl AsRef<Path>, value: &T, default: ConfigFormat) -> Result<()> {
+    let path = path.as_ref();
+    let format = ConfigFormat::from_path(path).unwrap_or(default);
+    let content = format
+        .serialize(value)
+        .with_context(|| format!("Failed to serialize configuration to {}", format.name()))?;
+
+    let temp_path = temp_path_for(path);
+    let result = fs::write(&temp_path, &content)
+        .with_context(|| format!("Failed to write configuration file: {:?}", temp_path))
+        .and_then(|_| {
+            let written = fs::read_to_string(&temp_path)
+                .with_context(|| format!("Failed to read back configuration file: {:?}", temp_path))?;
+            format
+                .parse::<Value>(&written)
+                .with_context(|| format!("Written {} configuration does not parse: {:?}", format.name(), path))?;
+            fs::rename(&temp_path, path)
+                .with_context(|| format!("Failed to replace configuration file: {:?}", path))
+        });
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// Temporary sibling of a configuration file used for atomic writes
+fn temp_path_for(path: &Path) -> PathBuf {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.tmp", file_name))
+}
+
+/// Convert a configuration file between formats based on the input and output extensions
+pub fn convert_config_file(input: impl AsRef<Path>, output: impl AsRef<Path>) -> Result<ConfigFormat> {
+    let input = input.as_ref();
+    let output = output.as_ref();
+    let format = ConfigFormat::from_path(input)
+        .with_context(|| format!("Cannot detect configuration format of {:?}", input))?;
+    let target = ConfigFormat::from_path(output)
+        .with_context(|| format!("Cannot detect configuration format of {:?}", output))?;
+
+    // Keep placeholders intact so converted files stay free of resolved secrets
+    let content = fs::read_to_string(input)
+        .with_context(|| format!("Failed to read configuration file: {:?}", input))?;
+    let value: Value = format
+        .parse(&content)
+        .with_context(|| format!("Failed to parse {} configuration file: {:?}", format.name(), input))?;
+    write_config_file(output, &value, target)?;
+    Ok(target)
+}
+
+/// Remove null values from objects and arrays recursively
+fn strip_nulls(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.retain(|_, v| !v.is_null());
+            map.values_mut().for_each(strip_nulls);
+        }
+        Value::Array(items) => {
+            items.retain(|v| !v.is_null());
+            items.iter_mut().for_each(strip_nulls);
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::config::Config;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_format_detection() {
+        assert_eq!(ConfigFormat::from_path("config.json"), Some(ConfigFormat::Json));
+        assert_eq!(ConfigFormat::from_path("config.YML"), Some(ConfigFormat::Yaml));
+        assert_eq!(ConfigFormat::from_path("config.toml"), Some(ConfigFormat::Toml));
+        assert_eq!(ConfigFormat::from_path("config.ini"), None);
+        assert!("xml".parse::<ConfigFormat>().is_err());
+    }
+
+    #[test]
+    fn test_config_round_trip_all_formats() {
+        let temp_dir = tempdir().unwrap();
+        let config = Config::default();
+
+        for format in [ConfigFormat::Json, ConfigFormat::Yaml, ConfigFormat::Toml] {
+            let path = temp_dir.path().join(format!("config.{}", format.extension()));
+            config.save_to_file(&path).unwrap();
+            let loaded = Config::from_file(&path).unwrap();
+            assert_eq!(loaded.server.port, config.server.port, "round trip failed for {}", format);
+        }
+    }
+
+    #[test]
+    fn test_convert_config_file() {
+        let temp_dir = tempdir().unwrap();
+        let json_path = temp_dir.path().join("config.json");
+        let toml_path = temp_dir.path().join("config.toml");
+        Config::default().save_to_file(&json_path).unwrap();
+
+        let format = convert_config_file(&json_path, &toml_path).unwrap();
+        assert_eq!(format, ConfigFormat::Toml);
+        assert!(Config::from_file(&toml_path).is_ok());
+    }
+
+    #[test]
+    fn test_write_config_file_replaces_atomically() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("config.yaml");
+        fs::write(&path, "previous: true").unwrap();
+
+        write_config_file(&path, &Config::default(), ConfigFormat::Json).unwrap();
+        assert!(Config::from_file(&path).is_ok());
+        assert!(!temp_path_for(&path).exists());
+
+        // A target that cannot be replaced keeps nothing half-written around
+        let dir_target = temp_dir.path().join("modules.json");
+        fs::create_dir(&dir_target).unwrap();
+        assert!(write_config_file(&dir_target, &Config::default(), ConfigFormat::Json).is_err());
+        assert!(!temp_path_for(&dir_target).exists());
+    }
+
+    #[test]
+    fn test_resolve_path_prefers_existing_file() {
+        let temp_dir = tempdir().unwrap();
+        let default = ConfigFormat::resolve_path(temp_dir.path(), "config", ConfigFormat::Json);
+        assert_eq!(default, temp_dir.path().join("config.json"));
+
+        fs::write(temp_dir.path().join("config.toml"), "").unwrap();
+        let resolved = ConfigFormat::resolve_path(temp_dir.path(), "config", ConfigFormat::Json);
+        assert_eq!(resolved, temp_dir.path().join("config.toml"));
+    }
+}
@@ -0,0 +1,321 @@
+//! Persistent configuration change history for MCP-ANY-REST
+//! This module records every configuration change as a numbered revision with who/when/what
+//! metadata and a full snapshot on disk, so earlier revisions can be inspected and restored.
+//! Snapshots hold credentials only in masked form; a rollback keeps the current credentials.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::config::config::Config;
+use crate::config::redact::{redact_at_path, redact_value};
+use crate::config::token_cache::write_private;
+
+/// Default number of revisions kept on disk
+pub const DEFAULT_HISTORY_LIMIT: usize = 50;
+
+/// Actor recorded when no caller identity is available
+pub const SYSTEM_ACTOR: &str = "system";
+
+const INDEX_FILE_NAME: &str = "index.json";
+
+tokio::task_local! {
+    static CURRENT_ACTOR: String;
+}
+
+/// Run a future with `actor` recorded as the author of any configuration change it makes
+pub async fn with_actor<F: Future>(actor: String, future: F) -> F::Output {
+    CURRENT_ACTOR.scope(actor, future).await
+}
+
+/// Actor of the configuration change being made by the current task
+pub fn current_actor() -> String {
+    CURRENT_ACTOR
+        .try_with(|actor| actor.clone())
+        .unwrap_or_else(|_| SYSTEM_ACTOR.to_string())
+}
+
+/// Single changed value between two revisions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfigDiffEntry {
+    /// Dotted path of the changed value
+    pub path: String,
+    pub old: Option<Value>,
+    pub new: Option<Value>,
+}
+
+/// Metadata of a recorded revision
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub revision: u64,
+    pub timestamp: DateTime<Utc>,
+    pub actor: String,
+    /// Change source (preset id, "custom", "rollback", ...)
+    pub source: String,
+    pub changes: Vec<String>,
+    pub diff: Vec<ConfigDiffEntry>,
+}
+
+/// Revision index stored next to the snapshots
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct HistoryIndex {
+    next_revision: u64,
+    entries: Vec<HistoryEntry>,
+}
+
+/// On-disk configuration history with bounded snapshot retention
+pub struct ConfigHistory {
+    dir: PathBuf,
+    limit: usize,
+    index: Mutex<HistoryIndex>,
+}
+
+impl ConfigHistory {
+    /// Open (or create) a history directory
+    pub fn open(dir: impl AsRef<Path>, limit: usize) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        let index_path = dir.join(INDEX_FILE_NAME);
+        let index = if index_path.exists() {
+            let content = fs::read_to_string(&index_path)
+                .with_context(|| format!("Failed to read history index: {:?}", index_path))?;
+            serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse history index: {:?}", index_path))?
+        } else {
+            HistoryIndex::default()
+        };
+
+        Ok(Self {
+            dir,
+            limit: limit.max(1),
+            index: Mutex::new(index),
+        })
+    }
+
+    /// Directory holding the index and snapshots
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Recorded revisions, newest first
+    pub fn entries(&self) -> Vec<HistoryEntry> {
+        self.index.lock().unwrap().entries.iter().rev().cloned().collect()
+    }
+
+    /// Latest recorded revision number
+    pub fn latest_revision(&self) -> Option<u64> {
+        self.index.lock().unwrap().entries.last().map(|e| e.revision)
+    }
+
+    /// Record the initial configuration if the history is empty
+    pub fn ensure_baseline(&self, config: &Config) -> Result<()> {
+        if self.latest_revision().is_none() {
+            self.record(
+                config,
+                SYSTEM_ACTOR,
+                "initial",
+                vec!["Initial configuration".to_string()],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Record a new revision and prune revisions beyond the retention limit
+    pub fn record(&self, config: &Config, actor: &str, source: &str, changes: Vec<String>) -> Result<HistoryEntry> {
+        let mut index = self.index.lock().unwrap();
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create history directory: {:?}", self.dir))?;
+
+        let snapshot = snapshot_value(config)?;
+        let previous = match index.entries.last() {
+            Some(entry) => self.read_snapshot_value(entry.revision).ok(),
+            None => None,
+        };
+        let mut diff = Vec::new();
+        if let Some(previous) = &previous {
+            diff_values("", previous, &snapshot, &mut diff);
+        }
+
+        let revision = index.next_revision.max(index.entries.last().map_or(0, |e| e.revision + 1));
+        let entry = HistoryEntry {
+            revision,
+            timestamp: Utc::now(),
+            actor: actor.to_string(),
+            source: source.to_string(),
+            changes,
+            diff,
+        };
+
+        let snapshot_path = self.snapshot_path(revision);
+        write_private(&snapshot_path, &serde_json::to_string_pretty(&snapshot)?)
+            .with_context(|| format!("Failed to write history snapshot: {:?}", snapshot_path))?;
+
+        index.entries.push(entry.clone());
+        index.next_revision = revision + 1;
+        while index.entries.len() > self.limit {
+            let removed = index.entries.remove(0);
+            if let Err(e) = fs::remove_file(self.snapshot_path(removed.revision)) {
+                warn!("Failed to remove pruned history snapshot {}: {}", removed.revision, e);
+            }
+        }
+
+        let index_path = self.dir.join(INDEX_FILE_NAME);
+        write_private(&index_path, &serde_json::to_string_pretty(&*index)?)
+            .with_context(|| format!("Failed to write history index: {:?}", index_path))?;
+
+        Ok(entry)
+    }
+
+    /// Load the configuration snapshot of a revision; its credentials are masked
+    pub fn snapshot(&self, revision: u64) -> Result<Config> {
+        if !self.index.lock().unwrap().entries.iter().any(|e| e.revision == revision) {
+            anyhow::bail!("Revision {} not found in configuration history", revision);
+        }
+        let value = self.read_snapshot_value(revision)?;
+        serde_json::from_value(value)
+            .with_context(|| format!("Invalid configuration snapshot for revision {}", revision))
+    }
+
+    fn read_snapshot_value(&self, revision: u64) -> Result<Value> {
+        let path = self.snapshot_path(revision);
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read history snapshot: {:?}", path))?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    fn snapshot_path(&self, revision: u64) -> PathBuf {
+        self.dir.join(format!("rev-{:06}.json", revision))
+    }
+}
+
+/// Snapshot of a configuration as stored in the history, with its credentials masked
+pub fn snapshot_value(config: &Config) -> Result<Value> {
+    let mut snapshot = serde_json::to_value(config)?;
+    redact_value(&mut snapshot);
+    Ok(snapshot)
+}
+
+/// Value-level differences between two JSON documents, with paths prefixed by `path`
+/// and credential values redacted
+pub fn diff_json(path: &str, old: &Value, new: &Value) -> Vec<ConfigDiffEntry> {
+    let mut diff = Vec::new();
+    diff_values(path, old, new, &mut diff);
+    diff
+}
+
+/// Collect value-level differences between two JSON documents
+fn diff_values(path: &str, old: &Value, new: &Value, out: &mut Vec<ConfigDiffEntry>) {
+    match (old, new) {
+        (Value::Object(old_map), Value::Object(new_map)) => {
+            for (key, old_value) in old_map {
+                let child = join_path(path, key);
+                match new_map.get(key) {
+                    Some(new_value) => diff_values(&child, old_value, new_value, out),
+                    None => out.push(diff_entry(child, Some(old_value), None)),
+                }
+            }
+            for (key, new_value) in new_map {
+                if !old_map.contains_key(key) {
+                    out.push(diff_entry(join_path(path, key), None, Some(new_value)));
+                }
+            }
+        }
+        _ if old != new => out.push(diff_entry(path.to_string(), Some(old), Some(new))),
+        _ => {}
+    }
+}
+
+fn join_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+/// Build a diff entry, masking credential values that are not references
+fn diff_entry(path: String, old: Option<&Value>, new: Option<&Value>) -> ConfigDiffEntry {
+    let redact = |value: Option<&Value>| {
+        value.map(|value| {
+            let mut value = value.clone();
+            redact_at_path(&path, &mut value);
+            value
+        })
+    };
+    ConfigDiffEntry {
+        old: redact(old),
+        new: redact(new),
+        path,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_record_diff_and_snapshot() {
+        let temp_dir = tempdir().unwrap();
+        let history = ConfigHistory::open(temp_dir.path(), DEFAULT_HISTORY_LIMIT).unwrap();
+
+        let mut config = Config::with_bearer_auth("old-token-1111".to_string());
+        history.ensure_baseline(&config).unwrap();
+
+        config.server.port = 9090;
+        config.auth.direct_config.as_mut().unwrap().token = Some("new-token-2222".to_string());
+        let entry = history.record(&config, "alice", "custom", vec!["Updated".to_string()]).unwrap();
+
+        assert_eq!(entry.revision, 1);
+        assert_eq!(entry.actor, "alice");
+        let port = entry.diff.iter().find(|d| d.path == "server.port").unwrap();
+        assert_eq!(port.new, Some(Value::from(9090)));
+        let token = entry.diff.iter().find(|d| d.path == "auth.direct_config.token").unwrap();
+        assert_eq!(token.new, Some(Value::from("****2222")));
+
+        let snapshot_path = temp_dir.path().join("rev-000001.json");
+        let content = fs::read_to_string(&snapshot_path).unwrap();
+        assert!(!content.contains("new-token-2222"));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&snapshot_path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        assert_eq!(history.snapshot(0).unwrap().server.port, Config::default().server.port);
+        assert!(history.snapshot(42).is_err());
+
+        let reopened = ConfigHistory::open(temp_dir.path(), DEFAULT_HISTORY_LIMIT).unwrap();
+        assert_eq!(reopened.entries()[0].revision, 1);
+    }
+
+    #[test]
+    fn test_history_is_pruned_to_limit() {
+        let temp_dir = tempdir().unwrap();
+        let history = ConfigHistory::open(temp_dir.path(), 2).unwrap();
+        let mut config = Config::default();
+
+        for port in 1..=4 {
+            config.server.port = port;
+            history.record(&config, SYSTEM_ACTOR, "custom", Vec::new()).unwrap();
+        }
+
+        let revisions: Vec<u64> = history.entries().iter().map(|e| e.revision).collect();
+        assert_eq!(revisions, vec![3, 2]);
+        assert!(history.snapshot(0).is_err());
+        assert!(!temp_dir.path().join("rev-000000.json").exists());
+    }
+
+    #[tokio::test]
+    async fn test_current_actor_scope() {
+        assert_eq!(current_actor(), SYSTEM_ACTOR);
+        let actor = with_actor("bob".to_string(), async { current_actor() }).await;
+        assert_eq!(actor, "bob");
+    }
+}
@@ -0,0 +1,85 @@
+//! HTTPS for the built-in web server of MCP-ANY-REST
+//!
+//! With `server.https` set, the web API and the MCP HTTP and SSE endpoints are served over TLS,
+//! with the configured certificate and key or a self-signed certificate generated at startup.
+//! Together with `server.bind_address` this lets the HTTP transport be reached securely on a LAN.
+
+use anyhow::{Context, Result};
+use axum_server::tls_rustls::RustlsConfig;
+use log::info;
+use std::net::IpAddr;
+
+use crate::config::config::HttpsConfig;
+use crate::services::composer_service::backend_registry::read_pem;
+
+/// TLS configuration of the web server; `bind_address` is added to self-signed certificates.
+/// The certificate chain and key are checked here, so a bad one is reported with its setting
+pub async fn server_tls_config(https: &HttpsConfig, bind_address: &str) -> Result<RustlsConfig> {
+    let (cert, key) = match (&https.cert, &https.key) {
+        (Some(cert), Some(key)) => (
+            read_pem(cert).context("Failed to read server.https.cert")?,
+            read_pem(key).context("Failed to read server.https.key")?,
+        ),
+        (None, None) if https.self_signed => self_signed_certificate(https, bind_address)?,
+        (None, None) => anyhow::bail!("server.https needs cert and key, or self_signed"),
+        _ => anyhow::bail!("server.https.cert and server.https.key must be set together"),
+    };
+
+    let chain = rustls_pemfile::certs(&mut cert.as_bytes()).context("Invalid PEM server certificate")?;
+    if chain.is_empty() {
+        anyhow::bail!("server.https.cert holds no certificate");
+    }
+    let has_key = rustls_pemfile::read_all(&mut key.as_bytes())
+        .context("Invalid PEM server key")?
+        .into_iter()
+        .any(|item| {
+            matches!(
+                item,
+                rustls_pemfile::Item::PKCS8Key(_) | rustls_pemfile::Item::RSAKey(_) | rustls_pemfile::Item::ECKey(_)
+            )
+        });
+    if !has_key {
+        anyhow::bail!("server.https.key holds no private key");
+    }
+
+    // Offers HTTP/2 and HTTP/1.1 via ALPN
+    RustlsConfig::from_pem(cert.into_bytes(), key.into_bytes())
+        .await
+        .context("Invalid server certificate or key")
+}
+
+/// PEM certificate and key valid for `localhost`, the bind address and `self_signed_names`
+fn self_signed_certificate(https: &HttpsConfig, bind_address: &str) -> Result<(String, String)> {
+    let mut names = vec!["localhost".to_string(), "127.0.0.1".to_string()];
+    // Wildcard addresses are no names clients connect to
+    if !bind_address.parse::<IpAddr>().is_ok_and(|address| address.is_unspecified()) {
+        names.push(bind_address.to_string());
+    }
+    names.extend(https.self_signed_names.iter().cloned());
+    names.sort();
+    names.dedup();
+
+    let certificate = rcgen::generate_simple_self_signed(names.clone()).context("Failed to generate a self-signed certificate")?;
+    info!("Generated a self-signed certificate for {}", names.join(", "));
+    Ok((certificate.serialize_pem()?, certificate.serialize_private_key_pem()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_self_signed_and_configured_certificates_are_loaded() {
+        let https = HttpsConfig { self_signed: true, self_signed_names: vec!["mcp.lan".to_string()], ..HttpsConfig::default() };
+        assert!(server_tls_config(&https, "0.0.0.0").await.is_ok());
+
+        // A generated certificate configured inline works like one read from files
+        let (cert, key) = self_signed_certificate(&https, "192.168.1.20").unwrap();
+        let configured = HttpsConfig { cert: Some(cert), key: Some(key), ..HttpsConfig::default() };
+        assert!(server_tls_config(&configured, "192.168.1.20").await.is_ok());
+
+        assert!(server_tls_config(&HttpsConfig::default(), "127.0.0.1").await.is_err());
+        let key_only = HttpsConfig { key: configured.key.clone(), ..HttpsConfig::default() };
+        assert!(server_tls_config(&key_only, "127.0.0.1").await.is_err());
+    }
+}
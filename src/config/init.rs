@@ -0,0 +1,193 @@
+//! First-run configuration scaffolding for MCP-ANY-REST
+//! `mcp-any-rest init` writes a runnable configuration directory: `config.json` with the API base
+//! URL and authentication, `modules.json` enabling an example module, a `full` preset and
+//! `zml/example.zml` to copy from.
+
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::config::Config;
+use crate::config::format::{write_config_file, ConfigFormat};
+use crate::config::module::{GlobalModuleConfig, ModuleConfig};
+use crate::config::preset_loader::{PresetConfig, PresetIndex, PresetInfo};
+
+/// Name of the module defined by the generated ZML file
+pub const EXAMPLE_MODULE: &str = "example";
+
+const EXAMPLE_ZML: &str = r#"// Example module generated by `mcp-any-rest init`
+// Each method becomes an MCP tool; replace them with the endpoints of your API.
+
+module example {
+    version: "1.0.0"
+    description: "Example module. Replace the methods with the endpoints of your API."
+    enabled: true
+    access_level: public
+
+    method get_item {
+        description: "Get an item by ID"
+        http_method: GET
+        uri: "items/{id}"
+        access_level: public
+        rate_limit: 60/60
+
+        params {
+            id: integer         // Item ID
+        }
+
+        response: object{}
+    }
+
+    method list_items {
+        description: "List items"
+        http_method: GET
+        uri: "items"
+        access_level: public
+        rate_limit: 60/60
+
+        params {
+            page: integer?      // Page number
+            limit: integer?     // Page size
+        }
+
+        response: object{}
+    }
+}
+"#;
+
+/// Authentication written to the generated configuration
+#[derive(Debug, Clone, PartialEq)]
+pub enum InitAuth {
+    /// No credentials
+    None,
+    /// `Authorization: Bearer <token>`
+    Bearer { token: String },
+    /// API key sent in the `name` header
+    ApiKey { name: String, token: String },
+    /// HTTP basic authentication
+    Basic { username: String, password: String },
+}
+
+/// Answers of the `init` subcommand
+#[derive(Debug, Clone)]
+pub struct InitOptions {
+    pub base_url: String,
+    pub auth: InitAuth,
+    /// Overwrite existing files
+    pub force: bool,
+}
+
+impl InitOptions {
+    /// Main configuration for the answers
+    pub fn config(&self) -> Config {
+        let config = match &self.auth {
+            InitAuth::None => Config::with_custom_headers_auth(HashMap::new()),
+            InitAuth::Bearer { token } => Config::with_bearer_auth(token.clone()),
+            InitAuth::ApiKey { name, token } => Config::with_api_key_auth(name.clone(), token.clone()),
+            InitAuth::Basic { username, password } => Config::with_basic_auth(username.clone(), password.clone()),
+        };
+        config.with_base_url(self.base_url.trim_end_matches('/').to_string())
+    }
+}
+
+/// Write a skeleton configuration directory; returns the files written.
+/// Fails without writing anything when a file exists and `force` is not set
+pub fn init_config_dir(config_dir: impl AsRef<Path>, options: &InitOptions) -> Result<Vec<PathBuf>> {
+    let config_dir = config_dir.as_ref();
+    let config_path = config_dir.join("config.json");
+    let modules_path = config_dir.join("modules.json");
+    let presets_dir = config_dir.join("presets");
+    let preset_index_path = presets_dir.join("index.json");
+    let preset_path = presets_dir.join("full.json");
+    let zml_path = config_dir.join("zml").join("example.zml");
+
+    let files = vec![
+        config_path.clone(),
+        modules_path.clone(),
+        preset_index_path.clone(),
+        preset_path.clone(),
+        zml_path.clone(),
+    ];
+    if !options.force {
+        let existing: Vec<String> = files
+            .iter()
+            .filter(|path| path.exists())
+            .map(|path| path.display().to_string())
+            .collect();
+        if !existing.is_empty() {
+            bail!("Refusing to overwrite existing files (use --force): {}", existing.join(", "));
+        }
+    }
+
+    fs::create_dir_all(&presets_dir)
+        .with_context(|| format!("Failed to create preset directory: {:?}", presets_dir))?;
+    fs::create_dir_all(config_dir.join("zml"))
+        .with_context(|| format!("Failed to create ZML directory: {:?}", config_dir.join("zml")))?;
+
+    write_config_file(&config_path, &options.config(), ConfigFormat::Json)?;
+
+    let mut example = ModuleConfig::new();
+    example.description = Some("Example module from zml/example.zml".to_string());
+    let mut module_config = GlobalModuleConfig::default();
+    module_config.modules.insert(EXAMPLE_MODULE.to_string(), example);
+    write_config_file(&modules_path, &module_config, ConfigFormat::Json)?;
+
+    let preset = PresetConfig {
+        config_version: module_config.config_version,
+        name: "Full".to_string(),
+        description: "Enable all modules".to_string(),
+        default_access_level: Some(module_config.default_access_level.clone()),
+        default_rate_limit: module_config.default_rate_limit.clone(),
+        modules: module_config.modules.clone(),
+        enable: None,
+    };
+    write_config_file(&preset_path, &preset, ConfigFormat::Json)?;
+    let index = PresetIndex {
+        presets: vec![PresetInfo {
+            id: "full".to_string(),
+            name: preset.name.clone(),
+            description: preset.description.clone(),
+            file: "full".to_string(),
+            enabled: true,
+            priority: 1,
+        }],
+        default_preset: Some("full".to_string()),
+    };
+    write_config_file(&preset_index_path, &index, ConfigFormat::Json)?;
+
+    fs::write(&zml_path, EXAMPLE_ZML).with_context(|| format!("Failed to write {:?}", zml_path))?;
+
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::check::check_config_dir;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_init_writes_valid_config_dir() {
+        let temp_dir = tempdir().unwrap();
+        let options = InitOptions {
+            base_url: "https://api.example.com/".to_string(),
+            auth: InitAuth::Bearer {
+                token: "secret".to_string(),
+            },
+            force: false,
+        };
+
+        let files = init_config_dir(temp_dir.path(), &options).unwrap();
+        assert!(files.iter().all(|file| file.exists()));
+
+        let report = check_config_dir(temp_dir.path(), None);
+        assert!(report.is_ok(), "{}", report);
+        let config = Config::from_file(temp_dir.path().join("config.json")).unwrap();
+        assert_eq!(config.api.base_url, "https://api.example.com");
+
+        assert!(init_config_dir(temp_dir.path(), &options).is_err());
+        let forced = InitOptions { force: true, ..options };
+        assert!(init_config_dir(temp_dir.path(), &forced).is_ok());
+    }
+}
@@ -1,15 +1,20 @@
 //! Configuration loader for MCP-ANY-REST
 //! This module provides functionality to load and parse module configuration files with preset support
 
+use crate::config::backup::ConfigBackups;
+use crate::config::env::interpolate_value;
+use crate::config::format::ConfigFormat;
+use crate::config::migrate::{migrate_and_persist, ConfigKind};
 use crate::config::module::GlobalModuleConfig;
 use crate::config::preset_loader::{PresetLoader};
+use crate::config::profile::{deep_merge, read_overlay, write_layered_config_file};
 use crate::config::validator::ConfigValidator;
 use anyhow::{Context, Result};
 use log::{debug, error, info, warn};
-use serde_json;
-use serde_yaml;
+use serde_json::{self, Value};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 /// Configuration loader
 pub struct ConfigLoader {
@@ -17,6 +22,10 @@ pub struct ConfigLoader {
     config_path: PathBuf,
     /// Preset loader for preset configurations
     preset_loader: PresetLoader,
+    /// Active profile whose overlay is merged over the configuration file
+    profile: Option<String>,
+    /// Backups taken before a migrated configuration file is written back
+    backups: Option<Arc<ConfigBackups>>,
 }
 
 impl ConfigLoader {
@@ -25,6 +34,8 @@ impl ConfigLoader {
         Self {
             config_path: config_path.as_ref().to_path_buf(),
             preset_loader: PresetLoader::default(),
+            profile: None,
+            backups: None,
         }
     }
 
@@ -33,9 +44,29 @@ impl ConfigLoader {
         Self {
             config_path: config_path.as_ref().to_path_buf(),
             preset_loader: PresetLoader::new(preset_path),
+            profile: None,
+            backups: None,
         }
     }
 
+    /// Merge the overlay of a profile (`modules.<profile>.json`) over the configuration file
+    pub fn with_profile(mut self, profile: Option<String>) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Back up the configuration file before a migrated version is written back;
+    /// without it the backups go to `backups/` next to the file
+    pub fn with_backups(mut self, backups: Arc<ConfigBackups>) -> Self {
+        self.backups = Some(backups);
+        self
+    }
+
+    /// Get the active profile
+    pub fn profile(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+
     /// Load configuration from file with optional preset application
     pub fn load_config(&self) -> Result<GlobalModuleConfig> {
         self.load_config_with_preset(None)
@@ -61,8 +92,9 @@ impl ConfigLoader {
         let config_content = fs::read_to_string(&self.config_path)
             .with_context(|| format!("Failed to read configuration file: {:?}", self.config_path))?;
 
-        // Determine file format based on extension
-        let mut config: GlobalModuleConfig = if self.config_path.extension().map_or(false, |ext| ext == "json") {
+        // Determine file format based on extension (YAML by default)
+        let format = ConfigFormat::from_path(&self.config_path).unwrap_or(ConfigFormat::Yaml);
+        let mut raw: Value = if format == ConfigFormat::Json {
             // Parse JSON configuration
             match serde_json::from_str(&config_content) {
                 Ok(config) => config,
@@ -94,15 +126,16 @@ impl ConfigLoader {
                 }
             }
         } else {
-            // Parse YAML configuration (default)
-            match serde_yaml::from_str(&config_content) {
+            // Parse YAML or TOML configuration
+            match format.parse(&config_content) {
                 Ok(config) => config,
                 Err(e) => {
-                    error!("YAML parsing error: {}", e);
-                    error!("YAML content preview (first 500 chars): {}", &config_content.chars().take(500).collect::<String>());
+                    error!("{} parsing error: {}", format.name(), e);
+                    error!("{} content preview (first 500 chars): {}", format.name(), &config_content.chars().take(500).collect::<String>());
                     
                     return Err(anyhow::anyhow!(
-                        "Failed to parse YAML configuration file: {}. Error details: {}",
+                        "Failed to parse {} configuration file: {}. Error details: {}",
+                        format.name(),
                         self.config_path.display(),
                         e
                     ));
@@ -110,6 +143,23 @@ impl ConfigLoader {
             }
         };
 
+        // Upgrade older schema versions, then resolve ${VAR} placeholders before deserializing
+        let backups = self
+            .backups
+            .clone()
+            .unwrap_or_else(|| Arc::new(ConfigBackups::beside(&self.config_path)));
+        migrate_and_persist(&self.config_path, format, ConfigKind::Modules, &mut raw, &backups)?;
+        if let Some(profile) = &self.profile {
+            if let Some(overlay) = read_overlay(&self.config_path, ConfigKind::Modules, profile)? {
+                info!("Applying profile overlay: {}", profile);
+                deep_merge(&mut raw, overlay);
+            }
+        }
+        interpolate_value(&mut raw)
+            .with_context(|| format!("Failed to resolve environment variables in: {:?}", self.config_path))?;
+        let mut config: GlobalModuleConfig = serde_json::from_value(raw)
+            .with_context(|| format!("Invalid module configuration structure: {:?}", self.config_path))?;
+
         // Apply preset if specified
         if let Some(preset_id) = preset_id {
             self.apply_preset(preset_id, &mut config)?;
@@ -229,20 +279,14 @@ impl ConfigLoader {
                 .with_context(|| format!("Failed to create parent directories: {:?}", parent))?;
         }
 
-        // Determine file format based on extension
-        let content = if self.config_path.extension().map_or(false, |ext| ext == "json") {
-            // Serialize configuration to JSON
-            serde_json::to_string_pretty(config)
-                .with_context(|| "Failed to serialize configuration to JSON")?
-        } else {
-            // Serialize configuration to YAML (default)
-            serde_yaml::to_string(config)
-                .with_context(|| "Failed to serialize configuration to YAML")?
-        };
-
-        // Write to file
-        fs::write(&self.config_path, content)
-            .with_context(|| format!("Failed to write configuration file: {:?}", self.config_path))?;
+        // Write to file (YAML by default); with a profile only the overlay is written
+        write_layered_config_file(
+            &self.config_path,
+            config,
+            ConfigFormat::Yaml,
+            ConfigKind::Modules,
+            self.profile.as_deref(),
+        )?;
 
         info!("Successfully saved module configuration");
         Ok(())
@@ -416,6 +460,84 @@ mod tests {
         assert!(!loader2.config_exists());
     }
 
+    #[test]
+    fn test_load_toml_config() {
+        let temp_dir = tempdir().unwrap();
+        let config_path = temp_dir.path().join("modules.toml");
+        fs::write(
+            &config_path,
+            "default_access_level = \"Public\"\n\n[modules.user]\nenabled = true\n",
+        )
+        .unwrap();
+
+        let loader = ConfigLoader::new(&config_path);
+        let config = loader.load_config().unwrap();
+        assert!(config.modules.contains_key("user"));
+
+        loader.save_config(&config).unwrap();
+        let reloaded = loader.load_config().unwrap();
+        assert_eq!(reloaded.modules.len(), config.modules.len());
+    }
+
+    #[test]
+    fn test_load_config_resolves_env_placeholders() {
+        let temp_dir = tempdir().unwrap();
+        let config_path = temp_dir.path().join("modules.json");
+        std::env::set_var("MCP_TEST_MODULE_DESCRIPTION", "From environment");
+        fs::write(
+            &config_path,
+            r#"{
+                "default_access_level": "Internal",
+                "default_rate_limit": null,
+                "modules": {
+                    "user": { "enabled": true, "description": "${MCP_TEST_MODULE_DESCRIPTION}" },
+                    "task": { "enabled": true, "description": "${MCP_TEST_UNSET_VARIABLE:-Default}" }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let config = ConfigLoader::new(&config_path).load_config().unwrap();
+        assert_eq!(config.modules["user"].description.as_deref(), Some("From environment"));
+        assert_eq!(config.modules["task"].description.as_deref(), Some("Default"));
+    }
+
+    #[test]
+    fn test_load_config_with_profile_overlay() {
+        let temp_dir = tempdir().unwrap();
+        let config_path = temp_dir.path().join("modules.json");
+        fs::write(
+            &config_path,
+            r#"{
+                "default_access_level": "Internal",
+                "default_rate_limit": null,
+                "modules": {
+                    "user": { "enabled": true, "description": "Users" },
+                    "task": { "enabled": true }
+                }
+            }"#,
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("modules.prod.json"),
+            r#"{ "modules": { "task": { "enabled": false } } }"#,
+        )
+        .unwrap();
+
+        let base = ConfigLoader::new(&config_path).load_config().unwrap();
+        assert!(base.modules["task"].enabled);
+
+        let loader = ConfigLoader::new(&config_path).with_profile(Some("prod".to_string()));
+        let config = loader.load_config().unwrap();
+        assert!(!config.modules["task"].enabled);
+        assert_eq!(config.modules["user"].description.as_deref(), Some("Users"));
+
+        // Saving with a profile leaves the base file untouched
+        loader.save_config(&config).unwrap();
+        assert!(ConfigLoader::new(&config_path).load_config().unwrap().modules["task"].enabled);
+        assert!(!loader.load_config().unwrap().modules["task"].enabled);
+    }
+
     #[test]
     fn test_apply_preset_to_default() {
         let loader = ConfigLoader::new("config/modules.json");
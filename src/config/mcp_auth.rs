@@ -0,0 +1,300 @@
+//! Authentication of the MCP HTTP endpoints for MCP-ANY-REST
+//!
+//! With `mcp_auth` configured, requests to `/mcp` and the SSE endpoints must send
+//! `Authorization: Bearer <token>` with a static token or an OAuth access token accepted by the
+//! introspection endpoint (RFC 7662). Each token is granted some modules and tools; the service
+//! registry hides and refuses the others. Active introspected tokens are cached for `cache_secs`,
+//! inactive ones for a few seconds, so repeated attempts with them do not reach the endpoint.
+//! Introspection requests go through the configured `proxy` and use the CA, client certificate
+//! and pinning settings of `tls`.
+
+use axum::http::{header, HeaderMap};
+use log::{debug, warn};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use subtle::ConstantTimeEq;
+
+use crate::config::config::{Config, McpAuthConfig, TlsConfig, TokenIntrospectionConfig};
+use crate::config::credentials::resolve_credential_ref;
+use crate::services::composer_service::backend_registry::{backend_client_builder, configure_proxy};
+
+/// Introspected tokens kept at most; the ones expiring first are dropped beyond it
+const MAX_INTROSPECTED: usize = 1000;
+
+/// Time an inactive token is remembered
+const INACTIVE_CACHE_SECS: u64 = 10;
+
+/// Grants of introspected tokens by SHA-256 of the token, `None` for inactive tokens, with their
+/// expiry
+static INTROSPECTED: Mutex<BTreeMap<String, (Option<McpGrant>, Instant)>> = Mutex::new(BTreeMap::new());
+
+/// Modules and tools a token may use
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct McpGrant {
+    /// Token holder, shown in logs
+    pub name: String,
+    pub modules: Vec<String>,
+    /// Tools as `<module>/<method>`
+    pub tools: Vec<String>,
+}
+
+impl McpGrant {
+    /// Whether neither modules nor tools are listed, so everything is allowed
+    pub fn is_unrestricted(&self) -> bool {
+        self.modules.is_empty() && self.tools.is_empty()
+    }
+
+    /// Whether the whole module is granted, with its prompts and resources
+    pub fn grants_module(&self, module: &str) -> bool {
+        self.is_unrestricted() || self.modules.iter().any(|allowed| allowed == module)
+    }
+
+    /// Whether some of the module's tools may be called: the whole module or one of its tools is
+    /// granted. Tool grants do not extend to the module's prompts and resources.
+    pub fn allows_module(&self, module: &str) -> bool {
+        self.grants_module(module)
+            || self.tools.iter().any(|tool| tool.split_once('/').is_some_and(|(allowed, _)| allowed == module))
+    }
+
+    /// Whether the tool of `method` in `module` may be called
+    pub fn allows_tool(&self, module: &str, method: &str) -> bool {
+        self.grants_module(module) || self.tools.iter().any(|tool| tool.split_once('/') == Some((module, method)))
+    }
+}
+
+/// Grant of the bearer token in `headers`; fails with the reason when it is missing or not
+/// accepted. `settings` is the configuration the introspection client takes its proxy and TLS
+/// settings from.
+pub async fn authenticate(config: &McpAuthConfig, settings: &Config, headers: &HeaderMap) -> Result<McpGrant, String> {
+    let token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+        .ok_or("Authentication required: send Authorization: Bearer <token>")?;
+
+    for configured in &config.tokens {
+        match resolve_credential_ref(&configured.token) {
+            // Compared in constant time, so response times do not reveal how much of a token matched
+            Ok(value) if !value.is_empty() && bool::from(value.as_bytes().ct_eq(token.as_bytes())) => {
+                return Ok(McpGrant {
+                    name: configured.name.clone(),
+                    modules: configured.modules.clone(),
+                    tools: configured.tools.clone(),
+                });
+            }
+            Ok(_) => {}
+            Err(e) => warn!("Failed to resolve the MCP token of '{}': {}", configured.name, e),
+        }
+    }
+
+    match &config.introspection {
+        Some(introspection) => introspect(introspection, settings, token).await,
+        None => Err("Invalid token".to_string()),
+    }
+}
+
+/// Grant of an OAuth access token, from the cache or the introspection endpoint
+async fn introspect(config: &TokenIntrospectionConfig, settings: &Config, token: &str) -> Result<McpGrant, String> {
+    let key = format!("{:x}", Sha256::digest(token.as_bytes()));
+    {
+        let mut introspected = INTROSPECTED.lock().unwrap();
+        let now = Instant::now();
+        introspected.retain(|_, (_, expires_at)| *expires_at > now);
+        if let Some((grant, _)) = introspected.get(&key) {
+            return grant.clone().ok_or_else(|| "Invalid or expired token".to_string());
+        }
+    }
+
+    let mut request = introspection_client(config, settings)
+        .map_err(|e| format!("Failed to build the token introspection client: {:#}", e))?
+        .post(&config.url)
+        .form(&[("token", token), ("token_type_hint", "access_token")]);
+    if let Some(client_id) = &config.client_id {
+        let client_secret = config
+            .client_secret
+            .as_deref()
+            .map(resolve_credential_ref)
+            .transpose()
+            .map_err(|e| format!("Failed to resolve the introspection client secret: {}", e))?;
+        request = request.basic_auth(client_id, client_secret);
+    }
+    let response: Value = request
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Token introspection failed: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid token introspection response: {}", e))?;
+    if response.get("active").and_then(Value::as_bool) != Some(true) {
+        remember(key, None, Duration::from_secs(INACTIVE_CACHE_SECS));
+        return Err("Invalid or expired token".to_string());
+    }
+
+    let mut grant = McpGrant {
+        name: ["username", "sub", "client_id"]
+            .iter()
+            .find_map(|field| response.get(*field).and_then(Value::as_str))
+            .unwrap_or("oauth")
+            .to_string(),
+        modules: config.modules.clone(),
+        tools: config.tools.clone(),
+    };
+    for scope in response.get("scope").and_then(Value::as_str).unwrap_or_default().split_whitespace() {
+        if let Some(module) = scope.strip_prefix("module:") {
+            grant.modules.push(module.to_string());
+        } else if let Some(tool) = scope.strip_prefix("tool:") {
+            grant.tools.push(tool.to_string());
+        }
+    }
+    debug!("Introspected the MCP token of '{}'", grant.name);
+    remember(key, Some(grant.clone()), Duration::from_secs(config.cache_secs));
+    Ok(grant)
+}
+
+/// HTTP client of the introspection endpoint, with the proxy and TLS settings of the API. The
+/// `tls.server_name` override only applies to the API host, so it is left out.
+fn introspection_client(config: &TokenIntrospectionConfig, settings: &Config) -> anyhow::Result<reqwest::Client> {
+    let tls = TlsConfig { server_name: None, ..settings.tls.clone() };
+    let builder = backend_client_builder(&tls, settings.api.timeout, &config.url)?;
+    Ok(configure_proxy(builder, settings.proxy.as_ref())?.build()?)
+}
+
+/// Cache the result of an introspection for `ttl`
+fn remember(key: String, grant: Option<McpGrant>, ttl: Duration) {
+    insert_bounded(&mut INTROSPECTED.lock().unwrap(), key, grant, Instant::now() + ttl);
+}
+
+/// Insert into a cache of introspections, dropping the entries expiring first when it is full
+fn insert_bounded(
+    introspected: &mut BTreeMap<String, (Option<McpGrant>, Instant)>,
+    key: String,
+    grant: Option<McpGrant>,
+    expires_at: Instant,
+) {
+    while introspected.len() >= MAX_INTROSPECTED && !introspected.contains_key(&key) {
+        let Some(first) = introspected
+            .iter()
+            .min_by_key(|(_, (_, expires_at))| *expires_at)
+            .map(|(key, _)| key.clone())
+        else {
+            break;
+        };
+        introspected.remove(&first);
+    }
+    introspected.insert(key, (grant, expires_at));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::config::{McpToken, ProxyConfig};
+    use axum::http::HeaderValue;
+
+    fn bearer(token: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {}", token)).unwrap());
+        headers
+    }
+
+    #[tokio::test]
+    async fn test_static_and_introspected_tokens_are_granted_modules_and_tools() {
+        let mut server = mockito::Server::new_async().await;
+        let introspection = server
+            .mock("POST", "/introspect")
+            .match_body(mockito::Matcher::UrlEncoded("token".into(), "oauth-token".into()))
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"active":true,"sub":"ci-bot","scope":"openid module:bugs tool:projects/list_projects"}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let revoked = server
+            .mock("POST", "/introspect")
+            .match_body(mockito::Matcher::UrlEncoded("token".into(), "revoked".into()))
+            .with_body(r#"{"active":false}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let config = McpAuthConfig {
+            tokens: vec![McpToken {
+                name: "dashboard".to_string(),
+                token: "static-token".to_string(),
+                modules: Vec::new(),
+                tools: vec!["projects/get_project".to_string()],
+            }],
+            introspection: Some(TokenIntrospectionConfig {
+                url: format!("{}/introspect", server.url()),
+                client_id: Some("mcp".to_string()),
+                client_secret: Some("secret".to_string()),
+                modules: Vec::new(),
+                tools: Vec::new(),
+                cache_secs: 60,
+            }),
+        };
+
+        let dashboard = authenticate(&config, &Config::default(), &bearer("static-token")).await.unwrap();
+        assert!(dashboard.allows_module("projects") && !dashboard.allows_module("bugs"));
+        // A tool grant does not open the module's prompts and resources
+        assert!(!dashboard.grants_module("projects"));
+        assert!(dashboard.allows_tool("projects", "get_project"));
+        assert!(!dashboard.allows_tool("projects", "delete_project"));
+
+        // The second use of the token is served from the cache
+        for _ in 0..2 {
+            let bot = authenticate(&config, &Config::default(), &bearer("oauth-token")).await.unwrap();
+            assert_eq!(bot.name, "ci-bot");
+            assert!(bot.allows_tool("bugs", "create_bug") && bot.grants_module("bugs"));
+            assert!(bot.allows_tool("projects", "list_projects") && !bot.allows_tool("projects", "get_project"));
+        }
+        introspection.assert_async().await;
+
+        // Inactive tokens are remembered too
+        for _ in 0..2 {
+            assert!(authenticate(&config, &Config::default(), &bearer("revoked")).await.is_err());
+        }
+        revoked.assert_async().await;
+        assert!(authenticate(&config, &Config::default(), &bearer("static-tokem")).await.is_err());
+        assert!(authenticate(&config, &Config::default(), &HeaderMap::new()).await.is_err());
+        let unrestricted = McpGrant { name: "all".to_string(), modules: Vec::new(), tools: Vec::new() };
+        assert!(unrestricted.allows_tool("anything", "at_all"));
+    }
+
+    #[tokio::test]
+    async fn test_introspection_uses_the_configured_proxy() {
+        let config = McpAuthConfig {
+            tokens: Vec::new(),
+            introspection: Some(TokenIntrospectionConfig {
+                url: "http://auth.example.com/introspect".to_string(),
+                client_id: None,
+                client_secret: None,
+                modules: Vec::new(),
+                tools: Vec::new(),
+                cache_secs: 60,
+            }),
+        };
+        let mut settings = Config::default();
+        settings.proxy = Some(ProxyConfig { url: "not a url".to_string(), ..ProxyConfig::default() });
+
+        let error = authenticate(&config, &settings, &bearer("proxied-token")).await.unwrap_err();
+        assert!(error.contains("introspection client"), "{}", error);
+    }
+
+    #[test]
+    fn test_introspection_cache_is_bounded() {
+        let mut introspected = BTreeMap::new();
+        let now = Instant::now();
+        for index in 0..MAX_INTROSPECTED + 10 {
+            let expires_at = now + Duration::from_secs(index as u64);
+            insert_bounded(&mut introspected, format!("bounded-{}", index), None, expires_at);
+        }
+        assert_eq!(introspected.len(), MAX_INTROSPECTED);
+        // The entries expiring first were dropped
+        assert!(!introspected.contains_key("bounded-0"));
+        assert!(introspected.contains_key(&format!("bounded-{}", MAX_INTROSPECTED + 9)));
+    }
+}
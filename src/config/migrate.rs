@@ -0,0 +1,304 @@
+//! Configuration schema migrations for MCP-ANY-REST
+//! This module upgrades configuration, module and preset files written by older versions
+//! to the current schema. Migrations run on the raw document before environment
+//! interpolation, so migrated files written back to disk keep their `${VAR}` placeholders.
+
+use anyhow::{bail, Context, Result};
+use log::{info, warn};
+use serde_json::{Map, Value};
+use std::path::Path;
+
+use crate::config::backup::ConfigBackups;
+use crate::config::format::{write_config_file, ConfigFormat};
+
+/// Current configuration schema version
+pub const CURRENT_CONFIG_VERSION: u32 = 1;
+
+/// Key holding the schema version in configuration files
+pub const VERSION_KEY: &str = "config_version";
+
+/// Serde default for `config_version` fields
+pub fn current_config_version() -> u32 {
+    CURRENT_CONFIG_VERSION
+}
+
+/// Kind of configuration document being migrated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigKind {
+    /// Main server configuration (`config.json`)
+    Main,
+    /// Module configuration (`modules.json`)
+    Modules,
+    /// Preset configuration (`presets/<id>.json`)
+    Preset,
+}
+
+/// Result of migrating a configuration document
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MigrationReport {
+    pub from_version: u32,
+    pub to_version: u32,
+    pub changes: Vec<String>,
+}
+
+impl MigrationReport {
+    /// Whether the document was upgraded
+    pub fn is_migrated(&self) -> bool {
+        self.from_version != self.to_version
+    }
+}
+
+/// A single schema upgrade from `from` to `from + 1`
+struct Migration {
+    from: u32,
+    apply: fn(ConfigKind, &mut Map<String, Value>, &mut Vec<String>),
+}
+
+/// Registered migrations, in version order
+const MIGRATIONS: &[Migration] = &[Migration { from: 0, apply: migrate_v0_to_v1 }];
+
+/// Upgrade a configuration document in place to the current schema version
+pub fn migrate_value(kind: ConfigKind, value: &mut Value) -> Result<MigrationReport> {
+    let root = value
+        .as_object_mut()
+        .context("Configuration root must be an object")?;
+
+    let from_version = match root.get(VERSION_KEY) {
+        None => 0,
+        Some(version) => version
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .with_context(|| format!("Invalid {}: {}", VERSION_KEY, version))?,
+    };
+    if from_version > CURRENT_CONFIG_VERSION {
+        bail!(
+            "Configuration version {} is newer than the supported version {}",
+            from_version,
+            CURRENT_CONFIG_VERSION
+        );
+    }
+
+    let mut changes = Vec::new();
+    for migration in MIGRATIONS.iter().filter(|m| m.from >= from_version) {
+        (migration.apply)(kind, root, &mut changes);
+    }
+    if from_version != CURRENT_CONFIG_VERSION {
+        root.insert(VERSION_KEY.to_string(), Value::from(CURRENT_CONFIG_VERSION));
+    }
+
+    Ok(MigrationReport {
+        from_version,
+        to_version: CURRENT_CONFIG_VERSION,
+        changes,
+    })
+}
+
+/// Migrate a document loaded from `path` and write the upgraded document back.
+/// The original file is kept in `backups` first; write failures are logged, not fatal.
+pub fn migrate_and_persist(
+    path: &Path,
+    format: ConfigFormat,
+    kind: ConfigKind,
+    value: &mut Value,
+    backups: &ConfigBackups,
+) -> Result<MigrationReport> {
+    let report = migrate_value(kind, value)
+        .with_context(|| format!("Failed to migrate configuration file: {:?}", path))?;
+    if !report.is_migrated() {
+        return Ok(report);
+    }
+
+    info!(
+        "Migrated {:?} from config_version {} to {}",
+        path, report.from_version, report.to_version
+    );
+    for change in &report.changes {
+        info!("  {}", change);
+    }
+
+    let persisted = backups
+        .backup(path)
+        .and_then(|_| write_config_file(path, value, format));
+    if let Err(e) = persisted {
+        warn!("Migrated configuration was not written back: {:#}", e);
+    }
+
+    Ok(report)
+}
+
+/// v0 -> v1: rename legacy auth/server fields and normalize access level casing
+fn migrate_v0_to_v1(kind: ConfigKind, root: &mut Map<String, Value>, changes: &mut Vec<String>) {
+    match kind {
+        ConfigKind::Main => {
+            move_field(root, "server_port", &["server", "port"], changes);
+            move_field(root, "log_level", &["server", "log_level"], changes);
+            move_field(root, "token_expiry_time", &["auth", "token_expiry"], changes);
+            if let Some(auth) = root.get_mut("auth").and_then(Value::as_object_mut) {
+                rename_field(auth, "auth.", "token_expiry_time", "token_expiry", changes);
+                rename_field(auth, "auth.", "token_refresh_buffer", "refresh_buffer", changes);
+            }
+            if let Some(module_config) = root.get_mut("module_config").and_then(Value::as_object_mut) {
+                normalize_access_levels(module_config, "module_config.", changes);
+            }
+        }
+        ConfigKind::Modules | ConfigKind::Preset => normalize_access_levels(root, "", changes),
+    }
+}
+
+/// Rename a key within an object; an existing value under the new name wins
+fn rename_field(
+    obj: &mut Map<String, Value>,
+    prefix: &str,
+    old: &str,
+    new: &str,
+    changes: &mut Vec<String>,
+) {
+    if let Some(value) = obj.remove(old) {
+        if obj.contains_key(new) {
+            changes.push(format!("Dropped {}{} (superseded by {}{})", prefix, old, prefix, new));
+        } else {
+            obj.insert(new.to_string(), value);
+            changes.push(format!("Renamed {}{} to {}{}", prefix, old, prefix, new));
+        }
+    }
+}
+
+/// Move a top-level key into a nested object path
+fn move_field(root: &mut Map<String, Value>, old: &str, target: &[&str], changes: &mut Vec<String>) {
+    let value = match root.remove(old) {
+        Some(value) => value,
+        None => return,
+    };
+    let (last, parents) = target.split_last().expect("target path must not be empty");
+
+    let mut obj = &mut *root;
+    for key in parents {
+        let entry = obj
+            .entry(key.to_string())
+            .or_insert_with(|| Value::Object(Map::new()));
+        if !entry.is_object() {
+            *entry = Value::Object(Map::new());
+        }
+        obj = entry.as_object_mut().unwrap();
+    }
+
+    let target_name = target.join(".");
+    if obj.contains_key(*last) {
+        changes.push(format!("Dropped {} (superseded by {})", old, target_name));
+    } else {
+        obj.insert(last.to_string(), value);
+        changes.push(format!("Moved {} to {}", old, target_name));
+    }
+}
+
+/// Normalize lowercase access levels (`internal`) to the serialized enum form (`Internal`)
+fn normalize_access_levels(obj: &mut Map<String, Value>, prefix: &str, changes: &mut Vec<String>) {
+    normalize_access_level(obj, prefix, "default_access_level", changes);
+
+    let modules = match obj.get_mut("modules").and_then(Value::as_object_mut) {
+        Some(modules) => modules,
+        None => return,
+    };
+    for (module_name, module) in modules.iter_mut() {
+        let module = match module.as_object_mut() {
+            Some(module) => module,
+            None => continue,
+        };
+        for section in ["methods", "resources"] {
+            if let Some(items) = module.get_mut(section).and_then(Value::as_object_mut) {
+                for (item_name, item) in items.iter_mut() {
+                    if let Some(item) = item.as_object_mut() {
+                        let item_prefix = format!("{}modules.{}.{}.{}.", prefix, module_name, section, item_name);
+                        normalize_access_level(item, &item_prefix, "access_level", changes);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn normalize_access_level(obj: &mut Map<String, Value>, prefix: &str, key: &str, changes: &mut Vec<String>) {
+    if let Some(Value::String(level)) = obj.get_mut(key) {
+        let normalized = match level.to_lowercase().as_str() {
+            "public" => "Public",
+            "internal" => "Internal",
+            "private" => "Private",
+            _ => return,
+        };
+        if *level != normalized {
+            changes.push(format!("Normalized {}{} '{}' to '{}'", prefix, key, level, normalized));
+            *level = normalized.to_string();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::backup::DEFAULT_BACKUP_RETENTION;
+    use crate::config::config::Config;
+    use crate::config::format::read_migrated_config_file;
+    use serde_json::json;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_migrate_legacy_main_config() {
+        let mut value = json!({
+            "server_port": 9000,
+            "server": { "log_level": "debug" },
+            "auth": { "token_expiry_time": 7200, "token_refresh_buffer": 60 },
+            "module_config": { "default_access_level": "public" }
+        });
+
+        let report = migrate_value(ConfigKind::Main, &mut value).unwrap();
+        assert!(report.is_migrated());
+        assert_eq!(report.from_version, 0);
+        assert_eq!(value["config_version"], CURRENT_CONFIG_VERSION);
+        assert_eq!(value["server"]["port"], 9000);
+        assert_eq!(value["auth"]["token_expiry"], 7200);
+        assert_eq!(value["auth"]["refresh_buffer"], 60);
+        assert!(value["auth"].get("token_expiry_time").is_none());
+        assert_eq!(value["module_config"]["default_access_level"], "Public");
+        assert_eq!(report.changes.len(), 4);
+    }
+
+    #[test]
+    fn test_current_version_is_untouched() {
+        let mut value = json!({ "config_version": CURRENT_CONFIG_VERSION, "default_access_level": "internal" });
+        let report = migrate_value(ConfigKind::Modules, &mut value).unwrap();
+        assert!(!report.is_migrated());
+        assert_eq!(value["default_access_level"], "internal");
+
+        let mut newer = json!({ "config_version": CURRENT_CONFIG_VERSION + 1 });
+        assert!(migrate_value(ConfigKind::Modules, &mut newer).is_err());
+    }
+
+    #[test]
+    fn test_migrated_file_is_written_back_with_backup() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("config.json");
+        let mut legacy = serde_json::to_value(Config::default()).unwrap();
+        let auth = legacy["auth"].as_object_mut().unwrap();
+        let expiry = auth.remove("token_expiry").unwrap();
+        auth.insert("token_expiry_time".to_string(), expiry);
+        legacy.as_object_mut().unwrap().remove(VERSION_KEY);
+        fs::write(&path, serde_json::to_string(&legacy).unwrap()).unwrap();
+
+        let backups = ConfigBackups::new(temp_dir.path(), DEFAULT_BACKUP_RETENTION);
+        let config: Config =
+            read_migrated_config_file(&path, ConfigFormat::Json, ConfigKind::Main, &backups).unwrap();
+        assert_eq!(config.config_version, CURRENT_CONFIG_VERSION);
+        assert_eq!(config.auth.token_expiry, 3600);
+
+        let written: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(written["config_version"], CURRENT_CONFIG_VERSION);
+        let entries = backups.list().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].file, "config.json");
+        let original: Value =
+            serde_json::from_str(&fs::read_to_string(backups.dir().join(&entries[0].name)).unwrap()).unwrap();
+        assert_eq!(original, legacy);
+        assert!(!temp_dir.path().join("config.json.v0.bak").exists());
+    }
+}
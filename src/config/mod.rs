@@ -1,9 +1,34 @@
 
+pub mod admin_auth;
+pub mod backup;
+pub mod clients;
+pub mod check;
 pub mod config;
+pub mod credentials;
+pub mod dependencies;
+pub mod doctor;
 pub mod dynamic;
+pub mod effective;
+pub mod env;
+pub mod format;
+pub mod history;
+pub mod https;
+pub mod init;
 pub mod loader;
+pub mod mcp_auth;
+pub mod migrate;
 pub mod module;
+pub mod module_env;
 pub mod preset_loader;
+pub mod profile;
+pub mod redact;
+pub mod remote;
+pub mod reverse_proxy;
+pub mod secrets;
+pub mod store;
+pub mod tag_filter;
+pub mod token_cache;
 pub mod web;
 pub mod validator;
+pub mod variables;
 pub mod zml_loader;
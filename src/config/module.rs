@@ -1,8 +1,13 @@
 //! Module configuration for ZenTao MCP Server
 //! This module provides dynamic configuration for modules, methods and resources visibility
 
+use log::warn;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+use crate::config::migrate::{current_config_version, CURRENT_CONFIG_VERSION};
+use crate::config::tag_filter::TagFilter;
 
 /// Module visibility configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,6 +20,52 @@ pub struct ModuleConfig {
     pub methods: Option<HashMap<String, MethodConfig>>,
     /// Resources configuration
     pub resources: Option<HashMap<String, ResourceConfig>>,
+    /// Access level of the module's methods (overrides `default_access_level`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_level: Option<AccessLevel>,
+    /// Rate limiting of the module's methods (overrides `default_rate_limit`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimitConfig>,
+    /// Request timeout in milliseconds for the module's methods
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    /// HTTP headers added to the module's requests
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub headers: HashMap<String, String>,
+    /// OAuth2 scopes the module's methods need; their requests use a token issued for these scopes
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scopes: Vec<String>,
+    /// Audience of the token used by the module's methods
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audience: Option<String>,
+    /// Retries of the module's methods after transient failures (overrides the backend's `retry`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryConfig>,
+    /// Time in milliseconds GET responses of the module's methods are served from the response cache
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_ttl_ms: Option<u64>,
+    /// Prefix of environment variables overriding the module's base URL, timeout, credentials
+    /// and headers (e.g. `JIRA_` reads `JIRA_BASE_URL`, `JIRA_TOKEN`, `JIRA_HEADER_<NAME>`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env_prefix: Option<String>,
+    /// Tags of the module (e.g. `["read"]`), inherited by its methods
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Modules that must be enabled for this module to be enabled; they are activated first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+    /// Scripts transforming the parameters and responses of the module's methods
+    #[serde(default, skip_serializing_if = "TransformConfig::is_empty")]
+    pub transform: TransformConfig,
+    /// Whether the module's methods answer with their mock responses (overrides the global `mock`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mock: Option<bool>,
+    /// What a response not matching its ZML declaration does to the module's tool calls
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validation: Option<ResponseValidation>,
+    /// Methods polled for changes, for APIs without webhooks
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub watch: Vec<WatchConfig>,
 }
 
 /// Method visibility configuration
@@ -28,6 +79,97 @@ pub struct MethodConfig {
     pub access_level: Option<AccessLevel>,
     /// Rate limiting configuration
     pub rate_limit: Option<RateLimitConfig>,
+    /// Request timeout in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    /// HTTP headers added to the method's requests
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub headers: HashMap<String, String>,
+    /// OAuth2 scopes the method needs (replaces the module's scopes)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scopes: Vec<String>,
+    /// Audience of the token used by the method (overrides the module's audience)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audience: Option<String>,
+    /// Retries of the method after transient failures (overrides the module's and the ZML `retry`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryConfig>,
+    /// Time in milliseconds the method's GET responses are served from the response cache
+    /// (overrides the module's and the ZML `cache_ttl`; 0 disables caching)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_ttl_ms: Option<u64>,
+    /// Tags of the method (e.g. `["write", "admin"]`), added to the module's tags
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Scripts transforming the method's parameters and responses (each replaces the module's)
+    #[serde(default, skip_serializing_if = "TransformConfig::is_empty")]
+    pub transform: TransformConfig,
+    /// Static responses served in mock mode instead of calling the API
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mocks: Vec<MockResponse>,
+    /// Headers callers may add to the method's requests through the `_headers` parameter
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_headers: Vec<String>,
+    /// What a response not matching its ZML declaration does to the call (overrides the module's)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validation: Option<ResponseValidation>,
+}
+
+/// Static response of a method in mock mode
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MockResponse {
+    /// Parameter values the call must have; strings may use `*` wildcards. Empty matches every call
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub when: BTreeMap<String, serde_json::Value>,
+    /// JSON returned as the tool result
+    pub response: serde_json::Value,
+}
+
+/// Method of a module polled for changed entities
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WatchConfig {
+    /// Method called on every poll; it should only read
+    pub method: String,
+    /// Seconds between polls
+    #[serde(default = "default_watch_interval_secs")]
+    pub interval_secs: u64,
+    /// Parameters of every call
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: BTreeMap<String, serde_json::Value>,
+    /// Field identifying the entities of an array result (e.g. `id`); results are compared as a
+    /// whole when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+}
+
+fn default_watch_interval_secs() -> u64 {
+    60
+}
+
+/// Rhai scripts run around a method's API request
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TransformConfig {
+    /// Script run before the request; it may change the `params` map sent to the API
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_request: Option<String>,
+    /// Script run on the decoded JSON response; it may change `response`, and reads the call's
+    /// parameters from `params`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_response: Option<String>,
+}
+
+impl TransformConfig {
+    pub fn is_empty(&self) -> bool {
+        self.pre_request.is_none() && self.post_response.is_none()
+    }
+
+    /// Scripts of `self`, each falling back to the one of `other`
+    pub fn or(&self, other: &TransformConfig) -> TransformConfig {
+        TransformConfig {
+            pre_request: self.pre_request.clone().or_else(|| other.pre_request.clone()),
+            post_response: self.post_response.clone().or_else(|| other.post_response.clone()),
+        }
+    }
 }
 
 /// Resource visibility configuration
@@ -90,31 +232,166 @@ pub struct RateLimitConfig {
     pub burst_capacity: u32,
 }
 
+/// What happens to a tool call over its rate limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RateLimitAction {
+    /// Fail the call with a "rate limited" error telling when to retry
+    Reject,
+    /// Wait for the limit to allow the call (up to a minute, then reject it)
+    Queue,
+}
+
+impl Default for RateLimitAction {
+    fn default() -> Self {
+        RateLimitAction::Reject
+    }
+}
+
+/// What happens to a tool call whose response does not match its declared type
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResponseValidation {
+    /// Fail the call with the first mismatch
+    Strict,
+    /// Return the result with the mismatch attached as a warning
+    #[default]
+    Warn,
+    /// Do not check the response
+    Off,
+}
+
+/// Retries of requests that failed transiently: connection errors, 429 and 5xx responses.
+/// Delays grow exponentially from `base_delay_ms` up to `max_delay_ms`; a `Retry-After` header
+/// replaces the computed delay. Connection errors and timeouts of POST and PATCH requests are
+/// only retried with `retry_non_idempotent`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetryConfig {
+    /// Total number of attempts, including the first request
+    #[serde(default = "default_retry_attempts")]
+    pub attempts: u32,
+    /// Delay before the first retry in milliseconds
+    #[serde(default = "default_retry_base_delay_ms")]
+    pub base_delay_ms: u64,
+    /// Longest delay between two attempts in milliseconds
+    #[serde(default = "default_retry_max_delay_ms")]
+    pub max_delay_ms: u64,
+    /// Wait a random part (50% to 100%) of each delay, so clients do not retry in lockstep
+    #[serde(default = "default_true")]
+    pub jitter: bool,
+    /// Give up once retrying would take the request past this many milliseconds in total;
+    /// 60 seconds when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_elapsed_ms: Option<u64>,
+    /// Also retry connection errors and timeouts of POST and PATCH requests, which the API may
+    /// have applied already
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub retry_non_idempotent: bool,
+    /// Status codes that trigger a retry; empty retries 429 and any 5xx response
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub on: Vec<u16>,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            attempts: default_retry_attempts(),
+            base_delay_ms: default_retry_base_delay_ms(),
+            max_delay_ms: default_retry_max_delay_ms(),
+            jitter: true,
+            max_elapsed_ms: None,
+            retry_non_idempotent: false,
+            on: Vec::new(),
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Delay before each retry, without jitter
+    pub fn delays(&self) -> Vec<Duration> {
+        (1..self.attempts)
+            .map(|retry| {
+                let delay = self.base_delay_ms.saturating_mul(1u64 << (retry - 1).min(16));
+                Duration::from_millis(delay.min(self.max_delay_ms))
+            })
+            .collect()
+    }
+}
+
+fn default_retry_attempts() -> u32 {
+    3
+}
+
+fn default_retry_base_delay_ms() -> u64 {
+    500
+}
+
+fn default_retry_max_delay_ms() -> u64 {
+    30_000
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn is_default_rate_limit_action(action: &RateLimitAction) -> bool {
+    *action == RateLimitAction::default()
+}
+
 /// Global module configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GlobalModuleConfig {
+    /// Configuration schema version
+    #[serde(default = "current_config_version")]
+    pub config_version: u32,
     /// Default access level for new modules
     pub default_access_level: AccessLevel,
     /// Default rate limiting configuration
     pub default_rate_limit: Option<RateLimitConfig>,
+    /// Whether calls over a rate limit are rejected or queued
+    #[serde(default, skip_serializing_if = "is_default_rate_limit_action")]
+    pub rate_limit_action: RateLimitAction,
+    /// Default request timeout in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_timeout_ms: Option<u64>,
+    /// Default HTTP headers added to every API request
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub default_headers: HashMap<String, String>,
+    /// Pages fetched at most by `fetch_all` for methods whose ZML declares no `max_pages` (10 when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fetch_all_max_pages: Option<u32>,
+    /// Answer tool calls with the methods' mock responses instead of calling the API
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub mock: bool,
     /// Module-specific configurations
     #[serde(
         default,
         skip_serializing_if = "HashMap::is_empty"
     )]
     pub modules: HashMap<String, ModuleConfig>,
+    /// Tag filter expression (e.g. `tag == "read"`); when set, only methods whose tags match
+    /// are enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enable: Option<String>,
 }
 
 impl Default for GlobalModuleConfig {
     fn default() -> Self {
         Self {
+            config_version: CURRENT_CONFIG_VERSION,
             default_access_level: AccessLevel::Internal,
             default_rate_limit: Some(RateLimitConfig {
                 requests_per_minute: 60,
                 requests_per_hour: 1000,
                 burst_capacity: 10,
             }),
+            rate_limit_action: RateLimitAction::default(),
+            default_timeout_ms: None,
+            default_headers: HashMap::new(),
+            fetch_all_max_pages: None,
+            mock: false,
             modules: HashMap::new(),
+            enable: None,
         }
     }
 }
@@ -126,6 +403,21 @@ impl Default for ModuleConfig {
             description: None,
             methods: None,
             resources: None,
+            access_level: None,
+            rate_limit: None,
+            timeout_ms: None,
+            headers: HashMap::new(),
+            scopes: Vec::new(),
+            audience: None,
+            retry: None,
+            cache_ttl_ms: None,
+            env_prefix: None,
+            tags: Vec::new(),
+            depends_on: Vec::new(),
+            transform: TransformConfig::default(),
+            mock: None,
+            validation: None,
+            watch: Vec::new(),
         }
     }
 }
@@ -137,6 +429,17 @@ impl Default for MethodConfig {
             description: None,
             access_level: Some(AccessLevel::Internal),
             rate_limit: None,
+            timeout_ms: None,
+            headers: HashMap::new(),
+            scopes: Vec::new(),
+            audience: None,
+            retry: None,
+            cache_ttl_ms: None,
+            tags: Vec::new(),
+            transform: TransformConfig::default(),
+            mocks: Vec::new(),
+            allowed_headers: Vec::new(),
+            validation: None,
         }
     }
 }
@@ -162,6 +465,55 @@ impl GlobalModuleConfig {
             .unwrap_or(false) // No configuration means disabled
     }
 
+    /// Dependencies of a module that are not enabled (including unknown modules)
+    pub fn unmet_dependencies(&self, module_name: &str) -> Vec<String> {
+        self.modules
+            .get(module_name)
+            .map(|module_config| {
+                module_config
+                    .depends_on
+                    .iter()
+                    .filter(|dependency| !self.is_module_enabled(dependency))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Check if a module is enabled and all of its dependencies are active, transitively.
+    /// Modules in a dependency cycle are never active
+    pub fn is_module_active(&self, module_name: &str) -> bool {
+        fn active(config: &GlobalModuleConfig, module_name: &str, visiting: &mut Vec<String>) -> bool {
+            if !config.is_module_enabled(module_name) || visiting.iter().any(|name| name == module_name) {
+                return false;
+            }
+            visiting.push(module_name.to_string());
+            let dependencies_active = config.modules[module_name]
+                .depends_on
+                .iter()
+                .all(|dependency| active(config, dependency, visiting));
+            visiting.pop();
+            dependencies_active
+        }
+        active(self, module_name, &mut Vec::new())
+    }
+
+    /// Modules depending on a module, directly or transitively
+    pub fn dependents(&self, module_name: &str) -> Vec<String> {
+        let mut dependents: Vec<String> = Vec::new();
+        let mut pending = vec![module_name.to_string()];
+        while let Some(name) = pending.pop() {
+            for (dependent, module_config) in &self.modules {
+                if module_config.depends_on.contains(&name) && !dependents.contains(dependent) && dependent != module_name {
+                    dependents.push(dependent.clone());
+                    pending.push(dependent.clone());
+                }
+            }
+        }
+        dependents.sort();
+        dependents
+    }
+
     /// Check if a method is enabled
     /// Rule: If module is enabled but method not configured, method is enabled
     ///       If module is disabled, method is disabled
@@ -173,12 +525,40 @@ impl GlobalModuleConfig {
         }
         
         // If module is enabled but method not configured, method is enabled
-        self.modules
+        let enabled = self.modules
             .get(module_name)
             .and_then(|module_config| module_config.methods.as_ref())
             .and_then(|methods| methods.get(method_name))
             .map(|method_config| method_config.enabled)
-            .unwrap_or(true) // Module enabled but method not configured means method enabled
+            .unwrap_or(true); // Module enabled but method not configured means method enabled
+
+        enabled && self.matches_enable_filter(module_name, method_name)
+    }
+
+    /// Tags of a method: the module's tags followed by the method's own tags
+    pub fn method_tags(&self, module_name: &str, method_name: &str) -> Vec<String> {
+        let mut tags = self
+            .get_module_config(module_name)
+            .map(|module_config| module_config.tags.clone())
+            .unwrap_or_default();
+        if let Some(method_config) = self.get_method_config(module_name, method_name) {
+            tags.extend(method_config.tags.iter().cloned());
+        }
+        tags
+    }
+
+    /// Check a method's tags against the `enable` filter; an invalid filter matches nothing
+    pub fn matches_enable_filter(&self, module_name: &str, method_name: &str) -> bool {
+        let Some(expression) = &self.enable else {
+            return true;
+        };
+        match TagFilter::parse(expression) {
+            Ok(filter) => filter.matches(&self.method_tags(module_name, method_name)),
+            Err(e) => {
+                warn!("Invalid enable filter, disabling {}::{}: {}", module_name, method_name, e);
+                false
+            }
+        }
     }
 
     /// Check if a resource is enabled
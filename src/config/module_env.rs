@@ -0,0 +1,188 @@
+//! Per-module environment overrides for MCP-ANY-REST
+//! A module configuration may declare `env_prefix` (e.g. `"JIRA_"`); the module's base URL, timeout,
+//! credentials and headers are then taken from `<PREFIX>*` environment variables, so container
+//! deployments can point a module at another API without editing configuration files.
+
+use log::warn;
+use std::collections::BTreeMap;
+
+use crate::config::config::{AuthConfig, AuthMode, DirectAuthConfig, DirectAuthType};
+use crate::config::module::ModuleConfig;
+
+/// Variables after the module prefix that define a header (`<PREFIX>HEADER_X_API_VERSION`)
+const HEADER_VAR_PREFIX: &str = "HEADER_";
+
+/// Settings of a module read from its environment variables
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModuleEnv {
+    /// `<PREFIX>BASE_URL`
+    pub base_url: Option<String>,
+    /// `<PREFIX>TIMEOUT`, in seconds
+    pub timeout: Option<u64>,
+    /// `<PREFIX>TOKEN`
+    pub token: Option<String>,
+    /// `<PREFIX>AUTH_TYPE` (`bearer`, `token`, `api_key`, `basic`, `digest`, `ntlm` or `negotiate`); defaults to `bearer` with a token
+    /// and to `basic` with a username and password
+    pub auth_type: Option<DirectAuthType>,
+    /// `<PREFIX>API_KEY_NAME`
+    pub api_key_name: Option<String>,
+    /// `<PREFIX>USERNAME`
+    pub username: Option<String>,
+    /// `<PREFIX>PASSWORD`
+    pub password: Option<String>,
+    /// `<PREFIX>HEADER_<NAME>`, with `_` in the name replaced by `-`
+    pub headers: BTreeMap<String, String>,
+}
+
+impl ModuleEnv {
+    /// Read the variables of a module from the process environment; `None` without `env_prefix`
+    pub fn for_module(module: &ModuleConfig) -> Option<Self> {
+        let prefix = module.env_prefix.as_deref()?;
+        Some(Self::from_vars(prefix, std::env::vars()))
+    }
+
+    /// Collect the variables starting with `prefix`; empty values are ignored
+    pub fn from_vars(prefix: &str, vars: impl IntoIterator<Item = (String, String)>) -> Self {
+        let mut env = Self::default();
+
+        for (name, value) in vars {
+            let Some(key) = name.strip_prefix(prefix) else {
+                continue;
+            };
+            if value.is_empty() {
+                continue;
+            }
+
+            match key {
+                "BASE_URL" => env.base_url = Some(value.trim_end_matches('/').to_string()),
+                "TIMEOUT" => match value.parse() {
+                    Ok(timeout) => env.timeout = Some(timeout),
+                    Err(_) => warn!("Ignoring {}: '{}' is not a number of seconds", name, value),
+                },
+                "TOKEN" => env.token = Some(value),
+                "AUTH_TYPE" => match parse_auth_type(&value) {
+                    Some(auth_type) => env.auth_type = Some(auth_type),
+                    None => warn!("Ignoring {}: unknown authentication type '{}'", name, value),
+                },
+                "API_KEY_NAME" => env.api_key_name = Some(value),
+                "USERNAME" => env.username = Some(value),
+                "PASSWORD" => env.password = Some(value),
+                _ => {
+                    if let Some(header) = key.strip_prefix(HEADER_VAR_PREFIX).filter(|h| !h.is_empty()) {
+                        env.headers.insert(header.replace('_', "-"), value);
+                    }
+                }
+            }
+        }
+
+        env
+    }
+
+    /// Whether credentials are set
+    pub fn has_credentials(&self) -> bool {
+        self.token.is_some() || self.username.is_some() || self.password.is_some()
+    }
+
+    /// Whether the module needs its own HTTP client and authentication (timeout or credentials set)
+    pub fn needs_own_client(&self) -> bool {
+        self.timeout.is_some() || self.has_credentials()
+    }
+
+    /// Authentication of the module: direct authentication from the variables when credentials
+    /// are set, `base` otherwise
+    pub fn auth(&self, base: &AuthConfig) -> AuthConfig {
+        if !self.has_credentials() {
+            return base.clone();
+        }
+
+        let auth_type = self.auth_type.clone().unwrap_or(if self.token.is_some() {
+            DirectAuthType::Bearer
+        } else {
+            DirectAuthType::Basic
+        });
+
+        AuthConfig {
+            mode: AuthMode::Direct,
+            direct_config: Some(DirectAuthConfig {
+                auth_type,
+                token: self.token.clone(),
+                api_key_name: self.api_key_name.clone(),
+                username: self.username.clone(),
+                password: self.password.clone(),
+                custom_headers: None,
+                key_pool: None,
+            }),
+            login_config: None,
+            jwt_config: None,
+            aws_sigv4_config: None,
+            hmac_config: None,
+            cookie_config: None,
+            custom_config: None,
+            ..base.clone()
+        }
+    }
+}
+
+/// Parse an authentication type, accepting `api_key`, `api-key` and `apikey`
+fn parse_auth_type(value: &str) -> Option<DirectAuthType> {
+    match value.to_lowercase().replace(['_', '-'], "").as_str() {
+        "bearer" => Some(DirectAuthType::Bearer),
+        "token" => Some(DirectAuthType::Token),
+        "apikey" => Some(DirectAuthType::ApiKey),
+        "basic" => Some(DirectAuthType::Basic),
+        "digest" => Some(DirectAuthType::Digest),
+        "ntlm" => Some(DirectAuthType::Ntlm),
+        "negotiate" => Some(DirectAuthType::Negotiate),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_reads_prefixed_variables() {
+        let env = ModuleEnv::from_vars(
+            "JIRA_",
+            vars(&[
+                ("JIRA_BASE_URL", "https://jira.example.com/"),
+                ("JIRA_TIMEOUT", "15"),
+                ("JIRA_TOKEN", "jira-token"),
+                ("JIRA_HEADER_X_API_VERSION", "2"),
+                ("JIRA_USERNAME", ""),
+                ("GITHUB_TOKEN", "github-token"),
+            ]),
+        );
+
+        assert_eq!(env.base_url.as_deref(), Some("https://jira.example.com"));
+        assert_eq!(env.timeout, Some(15));
+        assert_eq!(env.token.as_deref(), Some("jira-token"));
+        assert_eq!(env.username, None);
+        assert_eq!(env.headers["X-API-VERSION"], "2");
+        assert!(env.needs_own_client());
+    }
+
+    #[test]
+    fn test_auth_from_variables() {
+        let base = AuthConfig::default();
+        assert_eq!(ModuleEnv::default().auth(&base).mode, base.mode);
+
+        let token = ModuleEnv::from_vars("JIRA_", vars(&[("JIRA_TOKEN", "t"), ("JIRA_AUTH_TYPE", "api_key")]));
+        let auth = token.auth(&base);
+        assert_eq!(auth.mode, AuthMode::Direct);
+        let direct = auth.direct_config.unwrap();
+        assert_eq!(direct.auth_type, DirectAuthType::ApiKey);
+        assert_eq!(direct.token.as_deref(), Some("t"));
+
+        let basic = ModuleEnv::from_vars("JIRA_", vars(&[("JIRA_USERNAME", "u"), ("JIRA_PASSWORD", "p")]));
+        assert_eq!(basic.auth(&base).direct_config.unwrap().auth_type, DirectAuthType::Basic);
+    }
+}
@@ -1,6 +1,11 @@
 //! Preset loader for MCP-ANY-REST
 //! This module provides enhanced preset loading functionality with better error handling and validation
 
+use super::backup::ConfigBackups;
+use super::format::{
+    read_config_file, read_migrated_config_file, read_migrated_config_file_readonly, write_config_file, ConfigFormat,
+};
+use super::migrate::{current_config_version, ConfigKind};
 use super::module::{GlobalModuleConfig, ModuleConfig, AccessLevel, RateLimitConfig};
 use anyhow::{Context, Result};
 use log::{debug, info, warn};
@@ -8,11 +13,15 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::clone::Clone;
 
 /// Preset configuration definition
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PresetConfig {
+    /// Configuration schema version
+    #[serde(default = "current_config_version")]
+    pub config_version: u32,
     /// Preset name
     pub name: String,
     /// Preset description
@@ -23,6 +32,9 @@ pub struct PresetConfig {
     pub default_rate_limit: Option<RateLimitConfig>,
     /// Module configurations - directly reuse existing module configs
     pub modules: HashMap<String, ModuleConfig>,
+    /// Tag filter enabling only the methods whose tags match (e.g. `tag == "read"`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enable: Option<String>,
 }
 
 /// Preset information for index
@@ -62,6 +74,10 @@ pub struct PresetLoader {
     preset_index: Option<PresetIndex>,
     /// Loaded preset configurations
     loaded_presets: HashMap<String, PresetConfig>,
+    /// Backups taken before preset files are overwritten or deleted
+    backups: Option<Arc<ConfigBackups>>,
+    /// Whether older preset files are only upgraded in memory instead of written back
+    read_only: bool,
 }
 
 impl PresetLoader {
@@ -71,6 +87,29 @@ impl PresetLoader {
             preset_path: preset_path.as_ref().to_path_buf(),
             preset_index: None,
             loaded_presets: HashMap::new(),
+            backups: None,
+            read_only: false,
+        }
+    }
+
+    /// Back up preset files before they are overwritten or deleted
+    pub fn with_backups(mut self, backups: Arc<ConfigBackups>) -> Self {
+        self.backups = Some(backups);
+        self
+    }
+
+    /// Never write preset files back when migrating them to the current schema version
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Back up a preset file if backups are configured; failures are logged
+    fn backup_file(&self, path: &Path) {
+        if let Some(backups) = &self.backups {
+            if let Err(e) = backups.backup(path) {
+                warn!("Failed to back up {:?}: {}", path, e);
+            }
         }
     }
 
@@ -78,31 +117,13 @@ impl PresetLoader {
     pub fn load_preset_index(&mut self) -> Result<&PresetIndex> {
         info!("Loading preset index from: {:?}", self.preset_path);
 
-        // Try to load index from YAML first
-        let yaml_path = self.preset_path.join("index.yaml");
-        if yaml_path.exists() {
-            let content = fs::read_to_string(&yaml_path)
-                .with_context(|| format!("Failed to read preset index YAML: {:?}", yaml_path))?;
-            
-            let index: PresetIndex = serde_yaml::from_str(&content)
-                .with_context(|| format!("Failed to parse preset index YAML: {:?}", yaml_path))?;
-            
-            self.preset_index = Some(index);
-            info!("Successfully loaded preset index from YAML");
-            return Ok(self.preset_index.as_ref().unwrap());
-        }
-
-        // Fall back to JSON
-        let json_path = self.preset_path.join("index.json");
-        if json_path.exists() {
-            let content = fs::read_to_string(&json_path)
-                .with_context(|| format!("Failed to read preset index JSON: {:?}", json_path))?;
-            
-            let index: PresetIndex = serde_json::from_str(&content)
-                .with_context(|| format!("Failed to parse preset index JSON: {:?}", json_path))?;
+        // Try YAML first, then TOML, then JSON
+        if let Some(index_path) = ConfigFormat::find_existing(&self.preset_path, "index") {
+            let index: PresetIndex = read_config_file(&index_path, ConfigFormat::Json)
+                .with_context(|| format!("Failed to load preset index: {:?}", index_path))?;
             
             self.preset_index = Some(index);
-            info!("Successfully loaded preset index from JSON");
+            info!("Successfully loaded preset index from {:?}", index_path);
             return Ok(self.preset_index.as_ref().unwrap());
         }
 
@@ -141,38 +162,27 @@ impl PresetLoader {
 
         info!("Loading preset configuration: {} ({})", preset_info.name, preset_id);
 
-        // Try to load from YAML first
-        let yaml_path = self.preset_path.join(format!("{}.yaml", preset_info.file));
-        if yaml_path.exists() {
-            let content = fs::read_to_string(&yaml_path)
-                .with_context(|| format!("Failed to read preset YAML: {:?}", yaml_path))?;
-            
-            let preset: PresetConfig = serde_yaml::from_str(&content)
-                .with_context(|| format!("Failed to parse preset YAML: {:?}", yaml_path))?;
-            
-            self.loaded_presets.insert(preset_id.to_string(), preset);
-            info!("Successfully loaded preset from YAML: {}", preset_id);
-            return Ok(());
-        }
-
-        // Fall back to JSON
-        let json_path = self.preset_path.join(format!("{}.json", preset_info.file));
-        if json_path.exists() {
-            let content = fs::read_to_string(&json_path)
-                .with_context(|| format!("Failed to read preset JSON: {:?}", json_path))?;
-            
-            let preset: PresetConfig = serde_json::from_str(&content)
-                .with_context(|| format!("Failed to parse preset JSON: {:?}", json_path))?;
+        // Try YAML first, then TOML, then JSON
+        if let Some(preset_path) = ConfigFormat::find_existing(&self.preset_path, &preset_info.file) {
+            let preset: PresetConfig = if self.read_only {
+                read_migrated_config_file_readonly(&preset_path, ConfigFormat::Json, ConfigKind::Preset)
+            } else {
+                let backups = self
+                    .backups
+                    .clone()
+                    .unwrap_or_else(|| Arc::new(ConfigBackups::beside(&preset_path)));
+                read_migrated_config_file(&preset_path, ConfigFormat::Json, ConfigKind::Preset, &backups)
+            }
+            .with_context(|| format!("Failed to load preset: {:?}", preset_path))?;
             
             self.loaded_presets.insert(preset_id.to_string(), preset);
-            info!("Successfully loaded preset from JSON: {}", preset_id);
+            info!("Successfully loaded preset from {:?}: {}", preset_path, preset_id);
             return Ok(());
         }
 
         Err(anyhow::anyhow!(
-            "Preset file not found for {}: {}.yaml or {}.json",
+            "Preset file not found for {}: {}.{{yaml,yml,toml,json}}",
             preset_id,
-            preset_info.file,
             preset_info.file
         ))
     }
@@ -220,6 +230,9 @@ impl PresetLoader {
                 if let Some(description) = &preset_module.description {
                     existing_module.description = Some(description.clone());
                 }
+                if !preset_module.tags.is_empty() {
+                    existing_module.tags = preset_module.tags.clone();
+                }
                 
                 // Update methods
                 if let Some(preset_methods) = &preset_module.methods {
@@ -241,6 +254,9 @@ impl PresetLoader {
                                 if let Some(rate_limit) = &preset_method.rate_limit {
                                     existing_method.rate_limit = Some(rate_limit.clone());
                                 }
+                                if !preset_method.tags.is_empty() {
+                                    existing_method.tags = preset_method.tags.clone();
+                                }
                             } else {
                                 // Create new method configuration
                                 existing_methods.insert(method_name.clone(), preset_method.clone());
@@ -285,6 +301,12 @@ impl PresetLoader {
             }
         }
 
+        // Apply tag filter
+        if let Some(enable) = &preset.enable {
+            global_config.enable = Some(enable.clone());
+            changes.push(format!("Set tag filter: {}", enable));
+        }
+
         changes.push(format!("Applied preset: {}", preset.name));
         info!("Successfully applied preset: {} with {} changes", preset_id, changes.len());
         
@@ -391,13 +413,11 @@ impl PresetLoader {
             index.presets.push(preset_info);
         }
 
-        // Save preset configuration as JSON
-        let json_path = self.preset_path.join(format!("{}.json", preset_id));
-        let json_content = serde_json::to_string_pretty(preset_config)
-            .with_context(|| format!("Failed to serialize preset to JSON: {}", preset_id))?;
-        
-        fs::write(&json_path, json_content)
-            .with_context(|| format!("Failed to write preset JSON file: {:?}", json_path))?;
+        // Save preset configuration in its existing format, JSON for new presets
+        let preset_path = ConfigFormat::resolve_path(&self.preset_path, preset_id, ConfigFormat::Json);
+        self.backup_file(&preset_path);
+        write_config_file(&preset_path, preset_config, ConfigFormat::Json)
+            .with_context(|| format!("Failed to save preset: {}", preset_id))?;
 
         // Save preset index
         self.save_preset_index()?;
@@ -413,12 +433,10 @@ impl PresetLoader {
     pub fn save_preset_index(&self) -> Result<()> {
         if let Some(index) = &self.preset_index {
 
-            let json_path = self.preset_path.join("index.json");
-            let json_content = serde_json::to_string_pretty(index)
-                .with_context(|| "Failed to serialize preset index to JSON")?;
-            
-            fs::write(&json_path, json_content)
-                .with_context(|| format!("Failed to write preset index JSON file: {:?}", json_path))?;
+            let index_path = ConfigFormat::resolve_path(&self.preset_path, "index", ConfigFormat::Json);
+            self.backup_file(&index_path);
+            write_config_file(&index_path, index, ConfigFormat::Json)
+                .with_context(|| "Failed to save preset index")?;
 
             info!("Successfully saved preset index");
         }
@@ -437,10 +455,10 @@ impl PresetLoader {
         
         // Remove preset from index
         index.presets.retain(|p| p.id != preset_id);
-        let json_path = self.preset_path.join(format!("{}.json", preset_id));
-        if json_path.exists() {
-            fs::remove_file(&json_path)
-                .with_context(|| format!("Failed to delete preset JSON file: {:?}", json_path))?;
+        if let Some(preset_path) = ConfigFormat::find_existing(&self.preset_path, preset_id) {
+            self.backup_file(&preset_path);
+            fs::remove_file(&preset_path)
+                .with_context(|| format!("Failed to delete preset file: {:?}", preset_path))?;
         }
 
         // Remove from loaded presets
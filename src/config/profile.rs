@@ -0,0 +1,295 @@
+//! Configuration profiles for MCP-ANY-REST
+//! This module layers profile overlays (`config.dev.json`, `modules.prod.yaml`, ...) on top of
+//! a shared base file, so environments only override the values that differ (base_url, auth, ...).
+
+use anyhow::{bail, Context, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::backup::ConfigBackups;
+use crate::config::env::{interpolate_value, restore_placeholders};
+use crate::config::format::{write_config_file, ConfigFormat};
+use crate::config::migrate::{migrate_and_persist, migrate_value, ConfigKind};
+
+/// Environment variable selecting the active profile
+pub const PROFILE_ENV: &str = "MCP_PROFILE";
+
+/// Resolve the active profile: an explicit (command line) value wins over `MCP_PROFILE`.
+/// Empty values mean no profile.
+pub fn active_profile(explicit: Option<&str>) -> Option<String> {
+    explicit
+        .map(str::to_string)
+        .or_else(|| std::env::var(PROFILE_ENV).ok())
+        .map(|profile| profile.trim().to_string())
+        .filter(|profile| !profile.is_empty())
+}
+
+/// Check that a profile name is usable as part of a file name
+pub fn validate_profile(profile: &str) -> Result<()> {
+    if profile.is_empty()
+        || !profile
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        bail!("Invalid profile name: '{}'", profile);
+    }
+    Ok(())
+}
+
+/// Overlay file for a base configuration file: `<dir>/<stem>.<profile>.<ext>`.
+/// An existing overlay in any supported format is preferred; otherwise the base extension is used.
+pub fn profile_path(base: &Path, profile: &str) -> PathBuf {
+    let dir = base.parent().unwrap_or_else(|| Path::new(""));
+    let stem = base
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let overlay_stem = format!("{}.{}", stem, profile);
+    let default = ConfigFormat::from_path(base).unwrap_or(ConfigFormat::Json);
+    ConfigFormat::resolve_path(dir, &overlay_stem, default)
+}
+
+/// Deep-merge `overlay` into `base`: objects are merged key by key,
+/// any other value (including arrays and null) replaces the base value
+pub fn deep_merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base_map), Value::Object(overlay_map)) => {
+            for (key, value) in overlay_map {
+                match base_map.get_mut(&key) {
+                    Some(existing) => deep_merge(existing, value),
+                    None => {
+                        base_map.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Minimal overlay that turns `base` into `target` when deep-merged.
+/// Returns `None` when both values are equal; removed keys are expressed as null.
+pub fn overlay_diff(base: &Value, target: &Value) -> Option<Value> {
+    match (base, target) {
+        (Value::Object(base_map), Value::Object(target_map)) => {
+            let mut diff = Map::new();
+            for (key, value) in target_map {
+                match base_map.get(key) {
+                    Some(existing) => {
+                        if let Some(child) = overlay_diff(existing, value) {
+                            diff.insert(key.clone(), child);
+                        }
+                    }
+                    None => {
+                        diff.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+            for key in base_map.keys() {
+                if !target_map.contains_key(key) {
+                    diff.insert(key.clone(), Value::Null);
+                }
+            }
+            (!diff.is_empty()).then_some(Value::Object(diff))
+        }
+        _ if base == target => None,
+        _ => Some(target.clone()),
+    }
+}
+
+/// Read the profile overlay of a base file, upgraded to the current schema in memory.
+/// Returns `None` when no overlay file exists.
+pub fn read_overlay(base: &Path, kind: ConfigKind, profile: &str) -> Result<Option<Value>> {
+    validate_profile(profile)?;
+    let path = profile_path(base, profile);
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let mut overlay = read_raw(&path, ConfigFormat::Json)?;
+    migrate_value(kind, &mut overlay)
+        .with_context(|| format!("Failed to migrate profile overlay: {:?}", path))?;
+    Ok(Some(overlay))
+}
+
+/// Read a configuration file merged with its profile overlay, then resolve `${VAR}` placeholders.
+/// The base file is upgraded and written back like `read_migrated_config_file`.
+pub fn read_layered_config_file<T: DeserializeOwned>(
+    path: impl AsRef<Path>,
+    default: ConfigFormat,
+    kind: ConfigKind,
+    profile: Option<&str>,
+    backups: &ConfigBackups,
+) -> Result<T> {
+    read_layered(path.as_ref(), default, kind, profile, Some(backups))
+}
+
+/// Read a configuration file like `read_layered_config_file` without writing anything back;
+/// older schema versions are only upgraded in memory
+pub fn read_layered_config_file_readonly<T: DeserializeOwned>(
+    path: impl AsRef<Path>,
+    default: ConfigFormat,
+    kind: ConfigKind,
+    profile: Option<&str>,
+) -> Result<T> {
+    read_layered(path.as_ref(), default, kind, profile, None)
+}
+
+fn read_layered<T: DeserializeOwned>(
+    path: &Path,
+    default: ConfigFormat,
+    kind: ConfigKind,
+    profile: Option<&str>,
+    backups: Option<&ConfigBackups>,
+) -> Result<T> {
+    let format = ConfigFormat::from_path(path).unwrap_or(default);
+    let mut value = read_raw(path, default)?;
+    if let Some(backups) = backups {
+        migrate_and_persist(path, format, kind, &mut value, backups)?;
+    } else {
+        migrate_value(kind, &mut value)
+            .with_context(|| format!("Failed to migrate configuration file: {:?}", path))?;
+    }
+
+    if let Some(profile) = profile {
+        if let Some(overlay) = read_overlay(path, kind, profile)? {
+            deep_merge(&mut value, overlay);
+        }
+    }
+
+    interpolate_value(&mut value)
+        .with_context(|| format!("Failed to resolve environment variables in: {:?}", path))?;
+    serde_json::from_value(value)
+        .with_context(|| format!("Invalid configuration structure: {:?}", path))
+}
+
+/// Write a configuration value. Without a profile the base file is overwritten;
+/// with a profile only the values that differ from the base file go to the overlay.
+/// Values that still equal what a `${VAR}` placeholder of the file being replaced resolves to
+/// are written as that placeholder again, so saving never stores resolved secrets.
+pub fn write_layered_config_file<T: Serialize>(
+    path: impl AsRef<Path>,
+    value: &T,
+    default: ConfigFormat,
+    kind: ConfigKind,
+    profile: Option<&str>,
+) -> Result<()> {
+    let path = path.as_ref();
+    let profile = match profile {
+        Some(profile) => profile,
+        None => {
+            let mut target = serde_json::to_value(value)?;
+            if restore_file_placeholders(path, default, kind, &mut target)? {
+                return write_config_file(path, &target, default);
+            }
+            return write_config_file(path, value, default);
+        }
+    };
+    validate_profile(profile)?;
+
+    // Compare against the resolved base so unchanged placeholders are not copied into the overlay
+    let base = if path.exists() {
+        let mut base = read_raw(path, default)?;
+        migrate_value(kind, &mut base)?;
+        interpolate_value(&mut base)?;
+        base
+    } else {
+        Value::Object(Map::new())
+    };
+    let target = serde_json::to_value(value)?;
+    let mut overlay = overlay_diff(&base, &target).unwrap_or_else(|| Value::Object(Map::new()));
+    let overlay_path = profile_path(path, profile);
+    restore_file_placeholders(&overlay_path, default, kind, &mut overlay)?;
+    write_config_file(overlay_path, &overlay, default)
+}
+
+/// Restore the placeholders of an existing configuration file into the value that replaces it
+fn restore_file_placeholders(path: &Path, default: ConfigFormat, kind: ConfigKind, value: &mut Value) -> Result<bool> {
+    if !path.exists() {
+        return Ok(false);
+    }
+    let mut raw = read_raw(path, default)?;
+    migrate_value(kind, &mut raw)?;
+    Ok(restore_placeholders(value, &raw))
+}
+
+/// Parse a configuration file into a raw value without migration or interpolation
+fn read_raw(path: &Path, default: ConfigFormat) -> Result<Value> {
+    let format = ConfigFormat::from_path(path).unwrap_or(default);
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read configuration file: {:?}", path))?;
+    format
+        .parse(&content)
+        .with_context(|| format!("Failed to parse {} configuration file: {:?}", format.name(), path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::backup::DEFAULT_BACKUP_RETENTION;
+    use crate::config::config::Config;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_deep_merge_and_diff() {
+        let mut base = json!({
+            "api": { "base_url": "https://base", "timeout": 30 },
+            "auth": { "mode": "Direct", "direct_config": { "token": "a" } },
+            "tags": ["x", "y"]
+        });
+        let overlay = json!({
+            "api": { "base_url": "https://dev" },
+            "auth": { "direct_config": { "token": "b" } },
+            "tags": ["z"]
+        });
+
+        let original = base.clone();
+        deep_merge(&mut base, overlay.clone());
+        assert_eq!(base["api"]["base_url"], "https://dev");
+        assert_eq!(base["api"]["timeout"], 30);
+        assert_eq!(base["auth"]["mode"], "Direct");
+        assert_eq!(base["auth"]["direct_config"]["token"], "b");
+        assert_eq!(base["tags"], json!(["z"]));
+
+        assert_eq!(overlay_diff(&original, &base), Some(overlay));
+        assert_eq!(overlay_diff(&base, &base), None);
+    }
+
+    #[test]
+    fn test_profile_path_and_selection() {
+        let base = Path::new("config/config.yaml");
+        assert_eq!(profile_path(base, "dev"), PathBuf::from("config/config.dev.yaml"));
+        assert_eq!(active_profile(Some("prod")).as_deref(), Some("prod"));
+        assert_eq!(active_profile(Some("  ")), None);
+        assert!(validate_profile("../prod").is_err());
+    }
+
+    #[test]
+    fn test_layered_config_read_and_write() {
+        let temp_dir = tempdir().unwrap();
+        let base_path = temp_dir.path().join("config.json");
+        let overlay_path = temp_dir.path().join("config.dev.json");
+        Config::default().save_to_file(&base_path).unwrap();
+        fs::write(&overlay_path, r#"{ "server": { "port": 9100 } }"#).unwrap();
+
+        let backups = ConfigBackups::new(temp_dir.path(), DEFAULT_BACKUP_RETENTION);
+        let mut config: Config =
+            read_layered_config_file(&base_path, ConfigFormat::Json, ConfigKind::Main, Some("dev"), &backups).unwrap();
+        assert_eq!(config.server.port, 9100);
+        assert_eq!(config.server.log_level, Config::default().server.log_level);
+
+        let base: Config =
+            read_layered_config_file(&base_path, ConfigFormat::Json, ConfigKind::Main, None, &backups).unwrap();
+        assert_eq!(base.server.port, Config::default().server.port);
+
+        config.server.log_level = "debug".to_string();
+        write_layered_config_file(&base_path, &config, ConfigFormat::Json, ConfigKind::Main, Some("dev")).unwrap();
+        let overlay: Value = serde_json::from_str(&fs::read_to_string(&overlay_path).unwrap()).unwrap();
+        assert_eq!(overlay, json!({ "server": { "port": 9100, "log_level": "debug" } }));
+        assert_eq!(Config::from_file(&base_path).unwrap().server.port, Config::default().server.port);
+    }
+}
@@ -0,0 +1,333 @@
+//! Reverse proxy and browser embedding support of the web server of MCP-ANY-REST
+//!
+//! `server.base_path` (or `--base-path`) serves every route under a path prefix, so a proxy can
+//! forward e.g. `/mcp-any-rest/` unchanged. Requests from `server.trusted_proxies` may name the
+//! client in `X-Forwarded-For`, a prefix the proxy strips in `X-Forwarded-Prefix` and the public
+//! scheme and host in `X-Forwarded-Proto` and `X-Forwarded-Host`; these headers are ignored from
+//! anyone else. `server.cors` lets browsers on other origins call the
+//! web API and `/mcp`.
+
+use anyhow::{Context, Result};
+use axum::http::{header, HeaderMap, HeaderName, HeaderValue};
+use std::net::IpAddr;
+
+use crate::config::config::CorsConfig;
+
+/// Request headers browsers may always send cross-origin
+const DEFAULT_ALLOWED_HEADERS: &str = "Authorization, Content-Type, If-Match, Mcp-Session-Id, X-Config-Actor";
+
+/// Response headers readable by cross-origin scripts
+const EXPOSED_HEADERS: &str = "ETag, Mcp-Session-Id, WWW-Authenticate";
+
+/// Client of a request as reported by a trusted reverse proxy
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ForwardedRequest {
+    /// Address of the client, or of the proxy when it sent none
+    pub client: Option<IpAddr>,
+    /// Path of the web server as seen by the client, without trailing `/`: the prefix removed
+    /// by the proxy followed by the base path
+    pub base_path: String,
+    /// Scheme the client used, when a trusted proxy reported it
+    pub scheme: Option<String>,
+    /// Host the client used: the one reported by a trusted proxy, otherwise the `Host` header
+    pub host: Option<String>,
+}
+
+impl ForwardedRequest {
+    /// URL of the web server as seen by the client, e.g. `https://tools.example.com/mcp-any-rest`;
+    /// `scheme` is used when no proxy reported one. None without a known host.
+    pub fn base_url(&self, scheme: &str) -> Option<String> {
+        let host = self.host.as_deref()?;
+        Some(format!("{}://{}{}", self.scheme.as_deref().unwrap_or(scheme), host, self.base_path))
+    }
+}
+
+/// `base_path` without trailing `/`, empty for the root; fails on anything but a plain path
+pub fn normalize_base_path(base_path: &str) -> Result<String> {
+    let trimmed = base_path.trim_end_matches('/');
+    if trimmed.is_empty() {
+        return Ok(String::new());
+    }
+    if !trimmed.starts_with('/') || !is_plain_path(trimmed) {
+        anyhow::bail!("Base path '{}' must be a path like /mcp-any-rest", base_path);
+    }
+    Ok(trimmed.to_string())
+}
+
+/// Whether a path only holds characters that are safe in URLs and HTML
+fn is_plain_path(path: &str) -> bool {
+    path.chars().all(|c| c.is_ascii_alphanumeric() || "/-._~".contains(c)) && !path.contains("//")
+}
+
+/// Parse a trusted proxy entry: an address, or a CIDR range like `10.0.0.0/8`
+pub fn parse_proxy_range(range: &str) -> Result<(IpAddr, u8)> {
+    let (address, prefix_len) = match range.split_once('/') {
+        Some((address, prefix_len)) => (address, Some(prefix_len)),
+        None => (range, None),
+    };
+    let address: IpAddr = address
+        .trim()
+        .parse()
+        .with_context(|| format!("Invalid proxy address '{}'", range))?;
+    let max_len = if address.is_ipv4() { 32 } else { 128 };
+    let prefix_len = match prefix_len {
+        Some(prefix_len) => prefix_len
+            .trim()
+            .parse::<u8>()
+            .ok()
+            .filter(|len| *len <= max_len)
+            .with_context(|| format!("Invalid prefix length in proxy range '{}'", range))?,
+        None => max_len,
+    };
+    Ok((address, prefix_len))
+}
+
+/// Whether `address` is one of the trusted proxies; invalid entries match nothing
+pub fn is_trusted_proxy(trusted_proxies: &[String], address: IpAddr) -> bool {
+    let address = canonical(address);
+    trusted_proxies.iter().filter_map(|range| parse_proxy_range(range).ok()).any(|(network, prefix_len)| {
+        match (network, address) {
+            (IpAddr::V4(network), IpAddr::V4(address)) => {
+                let mask = u32::MAX.checked_shl(32 - prefix_len as u32).unwrap_or(0);
+                u32::from(network) & mask == u32::from(address) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(address)) => {
+                let mask = u128::MAX.checked_shl(128 - prefix_len as u32).unwrap_or(0);
+                u128::from(network) & mask == u128::from(address) & mask
+            }
+            _ => false,
+        }
+    })
+}
+
+/// Whether a host header value is a plain host name or address with an optional port
+fn is_plain_host(host: &str) -> bool {
+    !host.is_empty() && host.chars().all(|c| c.is_ascii_alphanumeric() || "-.:[]".contains(c))
+}
+
+/// First entry of a forwarded header, as proxies in a chain append their own
+fn first_forwarded(headers: &HeaderMap, name: &str) -> Option<String> {
+    let value = headers.get(name)?.to_str().ok()?;
+    Some(value.split(',').next()?.trim().to_ascii_lowercase())
+}
+
+/// IPv4 addresses mapped into IPv6, as dual-stack listeners report them, as plain IPv4
+fn canonical(address: IpAddr) -> IpAddr {
+    match address {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(address),
+        v4 => v4,
+    }
+}
+
+/// Client and public path of a request from `peer` to the server at `base_path`; forwarded
+/// headers only count when `peer` is a trusted proxy. The client is the last `X-Forwarded-For`
+/// entry that is not itself a trusted proxy, as earlier entries can be made up by the client;
+/// an invalid entry ends the search, since nothing before it was added by a trusted proxy.
+pub fn forwarded_request(
+    trusted_proxies: &[String],
+    peer: Option<IpAddr>,
+    headers: &HeaderMap,
+    base_path: &str,
+) -> ForwardedRequest {
+    let host = headers
+        .get(header::HOST)
+        .and_then(|value| value.to_str().ok())
+        .filter(|host| is_plain_host(host))
+        .map(str::to_string);
+    let trusted = peer.is_some_and(|peer| is_trusted_proxy(trusted_proxies, peer));
+    if !trusted {
+        return ForwardedRequest { client: peer, base_path: base_path.to_string(), scheme: None, host };
+    }
+    let forwarded_for: Vec<Option<IpAddr>> = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .flat_map(|value| match value.to_str() {
+            Ok(value) => value.split(',').map(|entry| entry.trim().parse().ok()).collect(),
+            Err(_) => vec![None],
+        })
+        .collect();
+    let mut client = peer;
+    for entry in forwarded_for.into_iter().rev() {
+        let Some(address) = entry else {
+            break;
+        };
+        client = Some(address);
+        if !is_trusted_proxy(trusted_proxies, address) {
+            break;
+        }
+    }
+    // A prefix that is not a plain path is dropped rather than echoed into the web UI
+    let prefix = headers
+        .get("x-forwarded-prefix")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|prefix| normalize_base_path(prefix).ok())
+        .unwrap_or_default();
+    let scheme = first_forwarded(headers, "x-forwarded-proto").filter(|scheme| scheme == "http" || scheme == "https");
+    let host = first_forwarded(headers, "x-forwarded-host").filter(|host| is_plain_host(host)).or(host);
+    ForwardedRequest { client, base_path: format!("{}{}", prefix, base_path), scheme, host }
+}
+
+/// Whether a cross-origin request from `origin` is allowed
+pub fn is_origin_allowed(cors: &CorsConfig, origin: &str) -> bool {
+    cors.allowed_origins
+        .iter()
+        .any(|allowed| allowed == "*" || allowed.trim_end_matches('/').eq_ignore_ascii_case(origin))
+}
+
+/// Whether a WebSocket may be opened from the page in `Origin`: pages of the web server itself,
+/// at the host the client used (see `ForwardedRequest`), and of the origins of `cors`. Clients
+/// sending no origin are no browsers and may connect.
+pub fn is_websocket_origin_allowed(cors: Option<&CorsConfig>, headers: &HeaderMap, host: Option<&str>) -> bool {
+    let Some(origin) = headers.get(header::ORIGIN).and_then(|value| value.to_str().ok()) else {
+        return true;
+    };
+    let origin_host = origin.split_once("://").map_or(origin, |(_, host)| host);
+    let same_origin = host.is_some_and(|host| host.eq_ignore_ascii_case(origin_host));
+    same_origin || cors.is_some_and(|cors| is_origin_allowed(cors, origin))
+}
+
+/// CORS headers answering a request from an allowed `origin`; `preflight` adds the allowed
+/// methods, headers and max age
+pub fn cors_headers(cors: &CorsConfig, origin: &HeaderValue, preflight: bool) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin.clone());
+    headers.insert(header::VARY, HeaderValue::from_static("Origin"));
+    if cors.allow_credentials {
+        headers.insert(header::ACCESS_CONTROL_ALLOW_CREDENTIALS, HeaderValue::from_static("true"));
+    }
+    if !preflight {
+        headers.insert(header::ACCESS_CONTROL_EXPOSE_HEADERS, HeaderValue::from_static(EXPOSED_HEADERS));
+        return headers;
+    }
+    headers.insert(
+        header::ACCESS_CONTROL_ALLOW_METHODS,
+        HeaderValue::from_static("GET, POST, PUT, PATCH, DELETE, OPTIONS"),
+    );
+    let allowed_headers = std::iter::once(DEFAULT_ALLOWED_HEADERS.to_string())
+        .chain(cors.allowed_headers.iter().filter(|name| HeaderName::try_from(name.as_str()).is_ok()).cloned())
+        .collect::<Vec<_>>()
+        .join(", ");
+    if let Ok(value) = HeaderValue::from_str(&allowed_headers) {
+        headers.insert(header::ACCESS_CONTROL_ALLOW_HEADERS, value);
+    }
+    if let Some(max_age) = cors.max_age_secs {
+        headers.insert(header::ACCESS_CONTROL_MAX_AGE, HeaderValue::from(max_age));
+    }
+    headers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base_path_and_trusted_proxies() {
+        assert_eq!(normalize_base_path("").unwrap(), "");
+        assert_eq!(normalize_base_path("/").unwrap(), "");
+        assert_eq!(normalize_base_path("/mcp-any-rest/").unwrap(), "/mcp-any-rest");
+        assert!(normalize_base_path("mcp").is_err());
+        assert!(normalize_base_path("/a\"><script>").is_err());
+
+        let trusted = vec!["10.0.0.0/8".to_string(), "::1".to_string()];
+        assert!(is_trusted_proxy(&trusted, "10.1.2.3".parse().unwrap()));
+        assert!(is_trusted_proxy(&trusted, "::ffff:10.1.2.3".parse().unwrap()));
+        assert!(is_trusted_proxy(&trusted, "::1".parse().unwrap()));
+        assert!(!is_trusted_proxy(&trusted, "192.168.1.1".parse().unwrap()));
+        assert!(parse_proxy_range("10.0.0.0/33").is_err());
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", HeaderValue::from_static("1.2.3.4, 203.0.113.7, 10.0.0.2"));
+        headers.insert("x-forwarded-prefix", HeaderValue::from_static("/tools/"));
+        let forwarded = forwarded_request(&trusted, Some("10.0.0.1".parse().unwrap()), &headers, "/mcp");
+        assert_eq!(forwarded.client, Some("203.0.113.7".parse().unwrap()));
+        assert_eq!(forwarded.base_path, "/tools/mcp");
+
+        // Anyone else cannot pretend to be a proxy
+        let direct = forwarded_request(&trusted, Some("192.168.1.1".parse().unwrap()), &headers, "/mcp");
+        assert_eq!(
+            direct,
+            ForwardedRequest {
+                client: Some("192.168.1.1".parse().unwrap()),
+                base_path: "/mcp".to_string(),
+                scheme: None,
+                host: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_forwarded_for_stops_at_an_invalid_entry() {
+        let trusted = vec!["10.0.0.0/8".to_string()];
+        let proxy = Some("10.0.0.1".parse().unwrap());
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", HeaderValue::from_static("203.0.113.7, unknown, 10.0.0.3, 10.0.0.2"));
+        let forwarded = forwarded_request(&trusted, proxy, &headers, "");
+        assert_eq!(forwarded.client, Some("10.0.0.3".parse().unwrap()));
+
+        headers.insert("x-forwarded-for", HeaderValue::from_static("203.0.113.7, garbage"));
+        assert_eq!(forwarded_request(&trusted, proxy, &headers, "").client, proxy);
+    }
+
+    #[test]
+    fn test_public_url_from_trusted_proxies() {
+        let trusted = vec!["10.0.0.0/8".to_string()];
+        let mut headers = HeaderMap::new();
+        headers.insert(header::HOST, HeaderValue::from_static("10.0.0.5:8082"));
+        headers.insert("x-forwarded-proto", HeaderValue::from_static("https, http"));
+        headers.insert("x-forwarded-host", HeaderValue::from_static("Tools.example.com"));
+        headers.insert("x-forwarded-prefix", HeaderValue::from_static("/tools"));
+        let forwarded = forwarded_request(&trusted, Some("10.0.0.1".parse().unwrap()), &headers, "/mcp-any-rest");
+        assert_eq!(forwarded.base_url("http").as_deref(), Some("https://tools.example.com/tools/mcp-any-rest"));
+
+        // Other clients get the URL they connected to
+        let direct = forwarded_request(&trusted, Some("192.168.1.1".parse().unwrap()), &headers, "/mcp-any-rest");
+        assert_eq!(direct.base_url("http").as_deref(), Some("http://10.0.0.5:8082/mcp-any-rest"));
+
+        // Made-up schemes and hosts are ignored
+        headers.insert("x-forwarded-proto", HeaderValue::from_static("javascript"));
+        headers.insert("x-forwarded-host", HeaderValue::from_static("evil.example.com/path"));
+        let forwarded = forwarded_request(&trusted, Some("10.0.0.1".parse().unwrap()), &headers, "");
+        assert_eq!(forwarded.base_url("https").as_deref(), Some("https://10.0.0.5:8082/tools"));
+    }
+
+    #[test]
+    fn test_cors_headers() {
+        let cors = CorsConfig {
+            allowed_origins: vec!["https://dashboard.example.com/".to_string()],
+            allowed_headers: vec!["X-Tenant".to_string()],
+            allow_credentials: true,
+            max_age_secs: Some(600),
+        };
+        assert!(is_origin_allowed(&cors, "https://dashboard.example.com"));
+        assert!(!is_origin_allowed(&cors, "https://evil.example.com"));
+
+        let origin = HeaderValue::from_static("https://dashboard.example.com");
+        let preflight = cors_headers(&cors, &origin, true);
+        assert_eq!(preflight[header::ACCESS_CONTROL_ALLOW_ORIGIN], origin);
+        assert_eq!(preflight[header::ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+        assert!(preflight[header::ACCESS_CONTROL_ALLOW_HEADERS].to_str().unwrap().ends_with("X-Config-Actor, X-Tenant"));
+        assert_eq!(preflight[header::ACCESS_CONTROL_MAX_AGE], "600");
+        let response = cors_headers(&cors, &origin, false);
+        assert!(response.get(header::ACCESS_CONTROL_ALLOW_METHODS).is_none());
+        assert_eq!(response[header::ACCESS_CONTROL_EXPOSE_HEADERS], EXPOSED_HEADERS);
+    }
+
+    #[test]
+    fn test_websocket_origin() {
+        let mut headers = HeaderMap::new();
+        let host = Some("127.0.0.1:8082");
+        assert!(is_websocket_origin_allowed(None, &headers, host));
+        headers.insert(header::ORIGIN, HeaderValue::from_static("http://127.0.0.1:8082"));
+        assert!(is_websocket_origin_allowed(None, &headers, host));
+        // Behind a proxy, the page is served from the forwarded host
+        assert!(!is_websocket_origin_allowed(None, &headers, Some("tools.example.com")));
+
+        headers.insert(header::ORIGIN, HeaderValue::from_static("https://dashboard.example.com"));
+        assert!(!is_websocket_origin_allowed(None, &headers, host));
+        let cors = CorsConfig {
+            allowed_origins: vec!["https://dashboard.example.com".to_string()],
+            ..Default::default()
+        };
+        assert!(is_websocket_origin_allowed(Some(&cors), &headers, host));
+    }
+}
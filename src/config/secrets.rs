@@ -0,0 +1,341 @@
+//! Encrypted secrets store for MCP-ANY-REST
+//! This module keeps tokens and passwords encrypted at rest (AES-256-GCM) in `secrets.json`.
+//! Configuration values reference them as `secret://<name>`; references are only decrypted
+//! when the authentication service builds request headers or login requests.
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use anyhow::{bail, Context, Result};
+use base64::Engine;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+/// URI scheme used to reference a stored secret from configuration values
+pub const SECRET_SCHEME: &str = "secret://";
+
+/// Default secrets file name inside the configuration directory
+pub const SECRETS_FILE_NAME: &str = "secrets.json";
+
+/// Environment variable holding the base64 encoded 256-bit store key
+pub const SECRETS_KEY_ENV: &str = "MCP_SECRETS_KEY";
+
+/// Environment variable pointing at a file that contains the base64 encoded store key
+pub const SECRETS_KEY_FILE_ENV: &str = "MCP_SECRETS_KEY_FILE";
+
+const NONCE_LEN: usize = 12;
+const STORE_VERSION: u32 = 1;
+
+/// Process-wide store used to resolve `secret://` references
+static GLOBAL_STORE: RwLock<Option<Arc<SecretStore>>> = RwLock::new(None);
+
+/// 256-bit key used to encrypt the secrets store
+#[derive(Clone)]
+pub struct SecretKey([u8; 32]);
+
+impl SecretKey {
+    /// Generate a new random key
+    pub fn generate() -> Self {
+        let key = Aes256Gcm::generate_key(OsRng);
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(&key);
+        Self(bytes)
+    }
+
+    /// Decode a key from base64
+    pub fn from_base64(encoded: &str) -> Result<Self> {
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(encoded.trim())
+            .context("Secrets key is not valid base64")?;
+        let key: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("Secrets key must be 32 bytes"))?;
+        Ok(Self(key))
+    }
+
+    /// Encode the key as base64
+    pub fn to_base64(&self) -> String {
+        base64::engine::general_purpose::STANDARD.encode(self.0)
+    }
+
+    /// AES-256-GCM cipher using this key
+    pub(crate) fn cipher(&self) -> Aes256Gcm {
+        Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&self.0))
+    }
+
+    /// Load the key from `MCP_SECRETS_KEY` or the file named by `MCP_SECRETS_KEY_FILE`
+    pub fn from_env() -> Result<Option<Self>> {
+        if let Ok(encoded) = std::env::var(SECRETS_KEY_ENV) {
+            return Self::from_base64(&encoded).map(Some);
+        }
+        if let Ok(path) = std::env::var(SECRETS_KEY_FILE_ENV) {
+            let encoded = fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read secrets key file: {}", path))?;
+            return Self::from_base64(&encoded).map(Some);
+        }
+        Ok(None)
+    }
+}
+
+impl std::fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SecretKey(..)")
+    }
+}
+
+/// On-disk representation of the secrets store
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SecretsFile {
+    version: u32,
+    /// Secret name -> base64(nonce || ciphertext)
+    secrets: BTreeMap<String, String>,
+}
+
+/// Encrypted secrets store backed by a JSON file
+pub struct SecretStore {
+    path: PathBuf,
+    cipher: Aes256Gcm,
+    secrets: RwLock<BTreeMap<String, String>>,
+}
+
+impl SecretStore {
+    /// Open a store file with the given key; a missing file yields an empty store
+    pub fn open(path: impl AsRef<Path>, key: &SecretKey) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = if path.exists() {
+            let content = fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read secrets file: {:?}", path))?;
+            serde_json::from_str::<SecretsFile>(&content)
+                .with_context(|| format!("Failed to parse secrets file: {:?}", path))?
+        } else {
+            SecretsFile::default()
+        };
+
+        if file.version > STORE_VERSION {
+            bail!("Unsupported secrets file version {} in {:?}", file.version, path);
+        }
+
+        Ok(Self {
+            path,
+            cipher: key.cipher(),
+            secrets: RwLock::new(file.secrets),
+        })
+    }
+
+    /// Open `secrets.json` in a configuration directory using the key from the environment.
+    /// Returns `None` when the directory has no secrets file.
+    pub fn open_in_dir(config_dir: impl AsRef<Path>) -> Result<Option<Self>> {
+        let path = config_dir.as_ref().join(SECRETS_FILE_NAME);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let key = SecretKey::from_env()?.with_context(|| {
+            format!(
+                "Secrets file {:?} exists but neither {} nor {} is set",
+                path, SECRETS_KEY_ENV, SECRETS_KEY_FILE_ENV
+            )
+        })?;
+        Self::open(&path, &key).map(Some)
+    }
+
+    /// Path of the backing file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Names of all stored secrets
+    pub fn names(&self) -> Vec<String> {
+        self.secrets.read().unwrap().keys().cloned().collect()
+    }
+
+    /// Encrypt and store a secret (in memory; call `save` to persist)
+    pub fn set(&self, name: &str, value: &str) -> Result<()> {
+        validate_name(name)?;
+        let encoded = seal(&self.cipher, name, value)
+            .with_context(|| format!("Failed to encrypt secret '{}'", name))?;
+        self.secrets.write().unwrap().insert(name.to_string(), encoded);
+        Ok(())
+    }
+
+    /// Decrypt a secret by name
+    pub fn get(&self, name: &str) -> Result<Option<String>> {
+        let encoded = match self.secrets.read().unwrap().get(name) {
+            Some(encoded) => encoded.clone(),
+            None => return Ok(None),
+        };
+
+        unseal(&self.cipher, name, &encoded)
+            .map(Some)
+            .with_context(|| format!("Failed to decrypt secret '{}'", name))
+    }
+
+    /// Remove a secret, returning whether it existed
+    pub fn remove(&self, name: &str) -> bool {
+        self.secrets.write().unwrap().remove(name).is_some()
+    }
+
+    /// Persist the store to its backing file
+    pub fn save(&self) -> Result<()> {
+        let file = SecretsFile {
+            version: STORE_VERSION,
+            secrets: self.secrets.read().unwrap().clone(),
+        };
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {:?}", parent))?;
+        }
+        let content = serde_json::to_string_pretty(&file)?;
+        fs::write(&self.path, content)
+            .with_context(|| format!("Failed to write secrets file: {:?}", self.path))?;
+        Ok(())
+    }
+
+    /// Resolve a configuration value: `secret://name` is decrypted, other values are returned as-is
+    pub fn resolve(&self, value: &str) -> Result<String> {
+        match value.strip_prefix(SECRET_SCHEME) {
+            Some(name) => self
+                .get(name)?
+                .with_context(|| format!("Secret '{}' not found in {:?}", name, self.path)),
+            None => Ok(value.to_string()),
+        }
+    }
+}
+
+/// Check whether a configuration value references a stored secret
+pub fn is_secret_ref(value: &str) -> bool {
+    value.starts_with(SECRET_SCHEME)
+}
+
+/// Install the process-wide store used by `resolve_secret_ref`
+pub fn install_global_store(store: Option<Arc<SecretStore>>) {
+    if let Some(store) = &store {
+        info!("Using secrets store: {:?} ({} secrets)", store.path(), store.names().len());
+    }
+    *GLOBAL_STORE.write().unwrap() = store;
+}
+
+/// Resolve a configuration value against the process-wide store.
+/// Plain values are returned unchanged; `secret://` references require an installed store.
+pub fn resolve_secret_ref(value: &str) -> Result<String> {
+    if !is_secret_ref(value) {
+        return Ok(value.to_string());
+    }
+    match GLOBAL_STORE.read().unwrap().as_ref() {
+        Some(store) => store.resolve(value),
+        None => bail!("Cannot resolve '{}': no secrets store is configured", value),
+    }
+}
+
+/// Like `resolve_secret_ref`, but a reference to a secret that has not been stored yet is `None`
+pub fn find_secret_ref(value: &str) -> Result<Option<String>> {
+    let Some(name) = value.strip_prefix(SECRET_SCHEME) else {
+        return Ok(Some(value.to_string()));
+    };
+    match GLOBAL_STORE.read().unwrap().as_ref() {
+        Some(store) => store.get(name),
+        None => bail!("Cannot resolve '{}': no secrets store is configured", value),
+    }
+}
+
+/// Encrypt `secret` under the `secret://` reference `value` in the process-wide store and persist it
+pub fn store_secret_ref(value: &str, secret: &str) -> Result<()> {
+    let name = value
+        .strip_prefix(SECRET_SCHEME)
+        .with_context(|| format!("'{}' is not a secret reference", value))?;
+    match GLOBAL_STORE.read().unwrap().as_ref() {
+        Some(store) => {
+            store.set(name, secret)?;
+            store.save()
+        }
+        None => bail!("Cannot store '{}': no secrets store is configured", value),
+    }
+}
+
+/// Encrypt `value` bound to `aad` and encode it as base64(nonce || ciphertext)
+pub(crate) fn seal(cipher: &Aes256Gcm, aad: &str, value: &str) -> Result<String> {
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, Payload { msg: value.as_bytes(), aad: aad.as_bytes() })
+        .map_err(|_| anyhow::anyhow!("Encryption failed"))?;
+
+    let mut sealed = nonce.to_vec();
+    sealed.extend_from_slice(&ciphertext);
+    Ok(base64::engine::general_purpose::STANDARD.encode(sealed))
+}
+
+/// Decrypt a value produced by `seal` with the same `aad`
+pub(crate) fn unseal(cipher: &Aes256Gcm, aad: &str, encoded: &str) -> Result<String> {
+    let sealed = base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .context("Value is not valid base64")?;
+    if sealed.len() <= NONCE_LEN {
+        bail!("Value is truncated");
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad: aad.as_bytes() })
+        .map_err(|_| anyhow::anyhow!("Wrong key or corrupted data"))?;
+
+    String::from_utf8(plaintext).context("Value is not valid UTF-8")
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '/'))
+    {
+        bail!("Invalid secret name: '{}'", name);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_secret_round_trip() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join(SECRETS_FILE_NAME);
+        let key = SecretKey::generate();
+
+        let store = SecretStore::open(&path, &key).unwrap();
+        store.set("api_token", "s3cr3t").unwrap();
+        store.save().unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(!content.contains("s3cr3t"), "secrets must be encrypted at rest");
+
+        let reopened = SecretStore::open(&path, &SecretKey::from_base64(&key.to_base64()).unwrap()).unwrap();
+        assert_eq!(reopened.get("api_token").unwrap().as_deref(), Some("s3cr3t"));
+        assert_eq!(reopened.resolve("secret://api_token").unwrap(), "s3cr3t");
+        assert_eq!(reopened.resolve("plain").unwrap(), "plain");
+        assert!(reopened.resolve("secret://missing").is_err());
+    }
+
+    #[test]
+    fn test_wrong_key_fails_to_decrypt() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join(SECRETS_FILE_NAME);
+
+        let store = SecretStore::open(&path, &SecretKey::generate()).unwrap();
+        store.set("password", "hunter2").unwrap();
+        store.save().unwrap();
+
+        let other = SecretStore::open(&path, &SecretKey::generate()).unwrap();
+        assert!(other.get("password").is_err());
+    }
+
+    #[test]
+    fn test_invalid_names_and_keys() {
+        let store = SecretStore::open("unused.json", &SecretKey::generate()).unwrap();
+        assert!(store.set("", "x").is_err());
+        assert!(store.set("bad name", "x").is_err());
+        assert!(SecretKey::from_base64("c2hvcnQ=").is_err());
+    }
+}
@@ -0,0 +1,336 @@
+//! SQL configuration store for MCP-ANY-REST
+//! Keeps the module configuration, presets and history snapshots as JSON documents in SQLite or
+//! PostgreSQL. A version counter bumped by every write lets other instances notice changes.
+
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use serde::de::DeserializeOwned;
+use sqlx::any::{install_default_drivers, AnyPoolOptions};
+use sqlx::{AnyPool, Row};
+use std::future::Future;
+use tokio::runtime::Runtime;
+
+use super::ConfigStore;
+use crate::config::config::Config;
+use crate::config::history::{diff_json, snapshot_value, HistoryEntry};
+use crate::config::module::GlobalModuleConfig;
+use crate::config::preset_loader::PresetConfig;
+
+/// Document name of the module configuration
+const MODULES_DOCUMENT: &str = "modules";
+
+const SCHEMA: &[&str] = &[
+    "CREATE TABLE IF NOT EXISTS mcp_documents (name VARCHAR(255) PRIMARY KEY, body TEXT NOT NULL)",
+    "CREATE TABLE IF NOT EXISTS mcp_presets (id VARCHAR(255) PRIMARY KEY, body TEXT NOT NULL)",
+    "CREATE TABLE IF NOT EXISTS mcp_history (revision BIGINT PRIMARY KEY, entry TEXT NOT NULL, snapshot TEXT NOT NULL)",
+    "CREATE TABLE IF NOT EXISTS mcp_store_version (id INTEGER PRIMARY KEY, version BIGINT NOT NULL)",
+    "INSERT INTO mcp_store_version (id, version) VALUES (1, 0) ON CONFLICT (id) DO NOTHING",
+];
+
+const BUMP_VERSION: &str = "UPDATE mcp_store_version SET version = version + 1 WHERE id = 1";
+
+/// Configuration store backed by SQLite or PostgreSQL
+pub struct SqlConfigStore {
+    pool: AnyPool,
+    /// Runtime driving the connection pool; the manager calls the store from synchronous code
+    runtime: Option<Runtime>,
+}
+
+impl SqlConfigStore {
+    /// Connect to `url` (`sqlite://...` or `postgres://...`) and create the tables if missing
+    pub fn connect(url: &str) -> Result<Self> {
+        install_default_drivers();
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("config-store")
+            .enable_all()
+            .build()
+            .context("Failed to start configuration store runtime")?;
+
+        // Every connection to an in-memory SQLite database opens a new, empty database
+        let max_connections = if url.contains(":memory:") { 1 } else { 5 };
+        let url = connect_url(url);
+        let pool = run(&runtime, async {
+            let pool = AnyPoolOptions::new().max_connections(max_connections).connect(&url).await?;
+            for statement in SCHEMA {
+                sqlx::query(statement).execute(&pool).await?;
+            }
+            Ok::<_, sqlx::Error>(pool)
+        })
+        .context("Failed to open configuration store")?;
+
+        Ok(Self {
+            pool,
+            runtime: Some(runtime),
+        })
+    }
+
+    fn block_on<F>(&self, future: F) -> F::Output
+    where
+        F: Future + Send,
+        F::Output: Send,
+    {
+        run(self.runtime.as_ref().expect("configuration store runtime"), future)
+    }
+
+    async fn fetch_document<T: DeserializeOwned>(&self, query: &str, key: &str) -> Result<Option<T>> {
+        let row = sqlx::query(query).bind(key).fetch_optional(&self.pool).await?;
+        match row {
+            Some(row) => {
+                let body: String = row.try_get(0)?;
+                Ok(Some(serde_json::from_str(&body)?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Run an upsert or delete and bump the version in one transaction; returns the affected rows
+    async fn write(&self, query: &str, key: &str, body: Option<String>) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        let mut statement = sqlx::query(query).bind(key);
+        if let Some(body) = body {
+            statement = statement.bind(body);
+        }
+        let affected = statement.execute(&mut *tx).await?.rows_affected();
+        sqlx::query(BUMP_VERSION).execute(&mut *tx).await?;
+        tx.commit().await?;
+        Ok(affected)
+    }
+}
+
+impl Drop for SqlConfigStore {
+    fn drop(&mut self) {
+        // Dropping a runtime blocks, which panics inside another runtime
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+/// Run a future on the store runtime, from a helper thread when called inside another runtime
+fn run<F>(runtime: &Runtime, future: F) -> F::Output
+where
+    F: Future + Send,
+    F::Output: Send,
+{
+    if tokio::runtime::Handle::try_current().is_ok() {
+        std::thread::scope(|scope| {
+            scope
+                .spawn(|| runtime.block_on(future))
+                .join()
+                .expect("configuration store thread panicked")
+        })
+    } else {
+        runtime.block_on(future)
+    }
+}
+
+/// Let SQLite create the database file on first use
+fn connect_url(url: &str) -> String {
+    if url.starts_with("sqlite:") && !url.contains(":memory:") && !url.contains("mode=") {
+        let separator = if url.contains('?') { '&' } else { '?' };
+        format!("{}{}mode=rwc", url, separator)
+    } else {
+        url.to_string()
+    }
+}
+
+impl ConfigStore for SqlConfigStore {
+    fn load_module_config(&self) -> Result<Option<GlobalModuleConfig>> {
+        self.block_on(self.fetch_document("SELECT body FROM mcp_documents WHERE name = $1", MODULES_DOCUMENT))
+    }
+
+    fn save_module_config(&self, module_config: &GlobalModuleConfig) -> Result<()> {
+        let body = serde_json::to_string(module_config)?;
+        self.block_on(self.write(
+            "INSERT INTO mcp_documents (name, body) VALUES ($1, $2) \
+             ON CONFLICT (name) DO UPDATE SET body = excluded.body",
+            MODULES_DOCUMENT,
+            Some(body),
+        ))
+        .context("Failed to save module configuration to the configuration store")?;
+        Ok(())
+    }
+
+    fn list_presets(&self) -> Result<Vec<(String, PresetConfig)>> {
+        self.block_on(async {
+            let rows = sqlx::query("SELECT id, body FROM mcp_presets ORDER BY id")
+                .fetch_all(&self.pool)
+                .await?;
+            let mut presets = Vec::new();
+            for row in rows {
+                let id: String = row.try_get(0)?;
+                let body: String = row.try_get(1)?;
+                let preset = serde_json::from_str(&body)
+                    .with_context(|| format!("Invalid preset '{}' in the configuration store", id))?;
+                presets.push((id, preset));
+            }
+            Ok::<_, anyhow::Error>(presets)
+        })
+    }
+
+    fn load_preset(&self, preset_id: &str) -> Result<Option<PresetConfig>> {
+        self.block_on(self.fetch_document("SELECT body FROM mcp_presets WHERE id = $1", preset_id))
+    }
+
+    fn save_preset(&self, preset_id: &str, preset: &PresetConfig) -> Result<()> {
+        let body = serde_json::to_string(preset)?;
+        self.block_on(self.write(
+            "INSERT INTO mcp_presets (id, body) VALUES ($1, $2) \
+             ON CONFLICT (id) DO UPDATE SET body = excluded.body",
+            preset_id,
+            Some(body),
+        ))
+        .with_context(|| format!("Failed to save preset '{}' to the configuration store", preset_id))?;
+        Ok(())
+    }
+
+    fn delete_preset(&self, preset_id: &str) -> Result<bool> {
+        let affected = self
+            .block_on(self.write("DELETE FROM mcp_presets WHERE id = $1", preset_id, None))
+            .with_context(|| format!("Failed to delete preset '{}' from the configuration store", preset_id))?;
+        Ok(affected > 0)
+    }
+
+    fn record_history(
+        &self,
+        config: &Config,
+        actor: &str,
+        source: &str,
+        changes: Vec<String>,
+        limit: usize,
+    ) -> Result<HistoryEntry> {
+        let snapshot = snapshot_value(config)?;
+        self.block_on(async {
+            let mut tx = self.pool.begin().await?;
+            let previous = sqlx::query("SELECT revision, snapshot FROM mcp_history ORDER BY revision DESC LIMIT 1")
+                .fetch_optional(&mut *tx)
+                .await?;
+
+            let (revision, diff) = match previous {
+                Some(row) => {
+                    let revision: i64 = row.try_get(0)?;
+                    let previous: String = row.try_get(1)?;
+                    let diff = match serde_json::from_str(&previous) {
+                        Ok(previous) => diff_json("", &previous, &snapshot),
+                        Err(_) => Vec::new(),
+                    };
+                    (revision as u64 + 1, diff)
+                }
+                None => (0, Vec::new()),
+            };
+            let entry = HistoryEntry {
+                revision,
+                timestamp: Utc::now(),
+                actor: actor.to_string(),
+                source: source.to_string(),
+                changes,
+                diff,
+            };
+
+            sqlx::query("INSERT INTO mcp_history (revision, entry, snapshot) VALUES ($1, $2, $3)")
+                .bind(revision as i64)
+                .bind(serde_json::to_string(&entry)?)
+                .bind(serde_json::to_string(&snapshot)?)
+                .execute(&mut *tx)
+                .await?;
+            let oldest_kept = revision as i64 + 1 - limit.max(1) as i64;
+            sqlx::query("DELETE FROM mcp_history WHERE revision < $1")
+                .bind(oldest_kept)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+            Ok::<_, anyhow::Error>(entry)
+        })
+    }
+
+    fn history(&self) -> Result<Vec<HistoryEntry>> {
+        self.block_on(async {
+            let rows = sqlx::query("SELECT entry FROM mcp_history ORDER BY revision DESC")
+                .fetch_all(&self.pool)
+                .await?;
+            rows.iter()
+                .map(|row| -> Result<HistoryEntry> { Ok(serde_json::from_str(&row.try_get::<String, _>(0)?)?) })
+                .collect::<Result<Vec<_>>>()
+        })
+    }
+
+    fn snapshot(&self, revision: u64) -> Result<Config> {
+        let row = self.block_on(
+            sqlx::query("SELECT snapshot FROM mcp_history WHERE revision = $1")
+                .bind(revision as i64)
+                .fetch_optional(&self.pool),
+        )?;
+        let Some(row) = row else {
+            bail!("Revision {} not found in configuration history", revision);
+        };
+        let snapshot: String = row.try_get(0)?;
+        serde_json::from_str(&snapshot)
+            .with_context(|| format!("Invalid configuration snapshot for revision {}", revision))
+    }
+
+    fn version(&self) -> Result<u64> {
+        let row = self.block_on(
+            sqlx::query("SELECT version FROM mcp_store_version WHERE id = 1").fetch_one(&self.pool),
+        )?;
+        Ok(row.try_get::<i64, _>(0)? as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::module::ModuleConfig;
+    use std::collections::HashMap;
+    use tempfile::tempdir;
+
+    fn open(dir: &std::path::Path) -> SqlConfigStore {
+        SqlConfigStore::connect(&format!("sqlite://{}", dir.join("config.db").display())).unwrap()
+    }
+
+    #[test]
+    fn test_modules_and_presets_are_shared() {
+        let temp_dir = tempdir().unwrap();
+        let first = open(temp_dir.path());
+        let second = open(temp_dir.path());
+        assert!(first.load_module_config().unwrap().is_none());
+
+        let version = second.version().unwrap();
+        let mut module_config = GlobalModuleConfig::default();
+        module_config.modules.insert("user".to_string(), ModuleConfig::new());
+        first.save_module_config(&module_config).unwrap();
+        assert!(second.version().unwrap() > version);
+        assert!(second.load_module_config().unwrap().unwrap().modules.contains_key("user"));
+
+        let preset = PresetConfig {
+            config_version: module_config.config_version,
+            name: "Dev".to_string(),
+            description: "Development".to_string(),
+            default_access_level: None,
+            default_rate_limit: None,
+            modules: HashMap::new(),
+            enable: Some(r#"tag == "read""#.to_string()),
+        };
+        first.save_preset("dev", &preset).unwrap();
+        assert_eq!(second.list_presets().unwrap().len(), 1);
+        assert_eq!(second.load_preset("dev").unwrap().unwrap().enable, preset.enable);
+        assert!(second.delete_preset("dev").unwrap());
+        assert!(!first.delete_preset("dev").unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_history_is_recorded_and_pruned() {
+        let store = SqlConfigStore::connect("sqlite::memory:").unwrap();
+        let mut config = Config::new();
+        for port in 0..3 {
+            config.server.port = 8000 + port;
+            store.record_history(&config, "alice", "custom", vec![], 2).unwrap();
+        }
+
+        let history = store.history().unwrap();
+        assert_eq!(history.iter().map(|e| e.revision).collect::<Vec<_>>(), vec![2, 1]);
+        assert!(history[0].diff.iter().any(|d| d.path == "server.port"));
+        assert_eq!(store.snapshot(1).unwrap().server.port, 8001);
+        assert!(store.snapshot(0).is_err());
+    }
+}
@@ -0,0 +1,228 @@
+//! Tag filter expressions for MCP-ANY-REST
+//! Modules and methods carry `tags` (e.g. `["read"]`, `["write", "admin"]`); a preset can enable
+//! whole classes of operations with a filter such as `tag == "read"` or
+//! `tag == "write" and not tag == "admin"` instead of listing every method.
+
+use anyhow::{bail, Result};
+
+/// Parsed tag filter expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum TagFilter {
+    /// `tag == "name"`: the tags contain `name`
+    Has(String),
+    /// `not expr` / `!expr`
+    Not(Box<TagFilter>),
+    /// `expr and expr` / `expr && expr`
+    And(Box<TagFilter>, Box<TagFilter>),
+    /// `expr or expr` / `expr || expr`
+    Or(Box<TagFilter>, Box<TagFilter>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Eq,
+    Ne,
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+}
+
+impl TagFilter {
+    /// Parse a filter expression. Comparisons are `tag == "name"` and `tag != "name"`, combined
+    /// with `and`/`&&`, `or`/`||`, `not`/`!` and parentheses
+    pub fn parse(expression: &str) -> Result<Self> {
+        let tokens = tokenize(expression)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let filter = parser.parse_or()?;
+        if let Some(token) = parser.peek() {
+            bail!("Unexpected {:?} in tag filter '{}'", token, expression);
+        }
+        Ok(filter)
+    }
+
+    /// Whether a set of tags matches the filter
+    pub fn matches<S: AsRef<str>>(&self, tags: &[S]) -> bool {
+        match self {
+            TagFilter::Has(tag) => tags.iter().any(|t| t.as_ref() == tag),
+            TagFilter::Not(inner) => !inner.matches(tags),
+            TagFilter::And(left, right) => left.matches(tags) && right.matches(tags),
+            TagFilter::Or(left, right) => left.matches(tags) || right.matches(tags),
+        }
+    }
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = expression.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::LParen);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::RParen);
+            }
+            '=' | '!' | '&' | '|' => {
+                chars.next();
+                let next = chars.peek().copied();
+                let token = match (c, next) {
+                    ('=', Some('=')) => Token::Eq,
+                    ('!', Some('=')) => Token::Ne,
+                    ('&', Some('&')) => Token::And,
+                    ('|', Some('|')) => Token::Or,
+                    ('!', _) => {
+                        tokens.push(Token::Not);
+                        continue;
+                    }
+                    _ => bail!("Unexpected '{}' in tag filter '{}'", c, expression),
+                };
+                chars.next();
+                tokens.push(token);
+            }
+            '"' | '\'' => {
+                chars.next();
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some(ch) if ch == c => break,
+                        Some(ch) => value.push(ch),
+                        None => bail!("Unterminated string in tag filter '{}'", expression),
+                    }
+                }
+                tokens.push(Token::Str(value));
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut word = String::new();
+                while let Some(&ch) = chars.peek() {
+                    if !(ch.is_alphanumeric() || ch == '_' || ch == '-') {
+                        break;
+                    }
+                    word.push(ch);
+                    chars.next();
+                }
+                tokens.push(match word.to_lowercase().as_str() {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "not" => Token::Not,
+                    _ => Token::Ident(word),
+                });
+            }
+            _ => bail!("Unexpected '{}' in tag filter '{}'", c, expression),
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<TagFilter> {
+        let mut left = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            left = TagFilter::Or(Box::new(left), Box::new(self.parse_and()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<TagFilter> {
+        let mut left = self.parse_unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            left = TagFilter::And(Box::new(left), Box::new(self.parse_unary()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<TagFilter> {
+        match self.next() {
+            Some(Token::Not) => Ok(TagFilter::Not(Box::new(self.parse_unary()?))),
+            Some(Token::LParen) => {
+                let inner = self.parse_or()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(inner),
+                    _ => bail!("Missing ')' in tag filter"),
+                }
+            }
+            Some(Token::Ident(ident)) if ident == "tag" || ident == "tags" => {
+                let operator = self.next();
+                let tag = match self.next() {
+                    Some(Token::Str(tag)) | Some(Token::Ident(tag)) => tag,
+                    other => bail!("Expected a tag name after '{}', found {:?}", ident, other),
+                };
+                match operator {
+                    Some(Token::Eq) => Ok(TagFilter::Has(tag)),
+                    Some(Token::Ne) => Ok(TagFilter::Not(Box::new(TagFilter::Has(tag)))),
+                    other => bail!("Expected '==' or '!=' after '{}', found {:?}", ident, other),
+                }
+            }
+            other => bail!("Expected 'tag', 'not' or '(' in tag filter, found {:?}", other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::module::{GlobalModuleConfig, MethodConfig, ModuleConfig};
+
+    #[test]
+    fn test_parse_and_match() {
+        let read = TagFilter::parse(r#"tag == "read""#).unwrap();
+        assert!(read.matches(&["read"]));
+        assert!(!read.matches(&["write"]));
+
+        let write = TagFilter::parse(r#"(tag == "read" || tag == 'write') and not tag == "admin""#).unwrap();
+        assert!(write.matches(&["write"]));
+        assert!(!write.matches(&["write", "admin"]));
+        assert!(TagFilter::parse(r#"tag != "admin""#).unwrap().matches::<&str>(&[]));
+    }
+
+    #[test]
+    fn test_enable_filter_uses_module_and_method_tags() {
+        let mut config = GlobalModuleConfig::default();
+        let mut module = ModuleConfig::default();
+        module.tags = vec!["read".to_string()];
+        let mut delete = MethodConfig::default();
+        delete.tags = vec!["admin".to_string()];
+        module.add_method("delete_user".to_string(), delete);
+        config.modules.insert("user".to_string(), module);
+
+        assert!(config.is_method_enabled("user", "delete_user"));
+        config.enable = Some(r#"tag == "read" and tag != "admin""#.to_string());
+        assert!(config.is_method_enabled("user", "list_users"));
+        assert!(!config.is_method_enabled("user", "delete_user"));
+        assert_eq!(config.method_tags("user", "delete_user"), vec!["read", "admin"]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for expression in ["", "tag = \"read\"", "tag == \"read", "name == \"x\"", "(tag == read", "tag == a b"] {
+            assert!(TagFilter::parse(expression).is_err(), "{}", expression);
+        }
+    }
+}
@@ -0,0 +1,283 @@
+//! Persistent token cache for MCP-ANY-REST
+//! Tokens obtained by logging in are kept encrypted (AES-256-GCM) in `token_cache.json` with their
+//! expiry, so a server restarted by its MCP client reuses them instead of logging in again.
+//! The cache is encrypted with the secrets key (`MCP_SECRETS_KEY` / `MCP_SECRETS_KEY_FILE`) when
+//! one is set, otherwise with a key generated into `token_cache.key` next to it.
+
+use super::secrets::{seal, unseal, SecretKey, SECRETS_KEY_ENV, SECRETS_KEY_FILE_ENV};
+use aes_gcm::Aes256Gcm;
+use anyhow::{Context, Result};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Token cache file name inside the configuration directory
+pub const TOKEN_CACHE_FILE_NAME: &str = "token_cache.json";
+
+/// Key file generated when no secrets key is configured
+pub const TOKEN_CACHE_KEY_FILE_NAME: &str = "token_cache.key";
+
+/// Environment variable that disables the cache when set to `off`, `false` or `0`
+pub const TOKEN_CACHE_ENV: &str = "MCP_TOKEN_CACHE";
+
+const CACHE_VERSION: u32 = 1;
+
+/// Process-wide cache used by the authentication strategies
+static GLOBAL_CACHE: RwLock<Option<Arc<TokenCache>>> = RwLock::new(None);
+
+/// A cached token and when it expires
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedToken {
+    /// Token as sent to the API
+    pub token: String,
+    /// Further tokens extracted from the same login response
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_tokens: Vec<String>,
+    /// Expiry in seconds since the epoch
+    pub expires_at: u64,
+    /// OAuth2 refresh token issued with the token
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+}
+
+/// On-disk representation of the token cache
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TokenCacheFile {
+    version: u32,
+    /// Cache key -> base64(nonce || ciphertext) of the JSON-encoded `CachedToken`
+    tokens: BTreeMap<String, String>,
+}
+
+/// Encrypted token cache backed by a JSON file
+pub struct TokenCache {
+    path: PathBuf,
+    cipher: Aes256Gcm,
+    tokens: RwLock<BTreeMap<String, String>>,
+}
+
+impl TokenCache {
+    /// Open a cache file with the given key; a missing or unreadable file yields an empty cache
+    pub fn open(path: impl AsRef<Path>, key: &SecretKey) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let tokens = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| match serde_json::from_str::<TokenCacheFile>(&content) {
+                Ok(file) if file.version <= CACHE_VERSION => Some(file.tokens),
+                Ok(file) => {
+                    warn!("Ignoring token cache {:?} with unsupported version {}", path, file.version);
+                    None
+                }
+                Err(e) => {
+                    warn!("Ignoring unreadable token cache {:?}: {}", path, e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+
+        Self {
+            path,
+            cipher: key.cipher(),
+            tokens: RwLock::new(tokens),
+        }
+    }
+
+    /// Open `token_cache.json` in a configuration directory. Returns `None` when the cache is
+    /// disabled through `MCP_TOKEN_CACHE`.
+    pub fn open_in_dir(config_dir: impl AsRef<Path>) -> Result<Option<Self>> {
+        if let Ok(setting) = std::env::var(TOKEN_CACHE_ENV) {
+            if matches!(setting.trim().to_ascii_lowercase().as_str(), "off" | "false" | "0") {
+                return Ok(None);
+            }
+        }
+        let config_dir = config_dir.as_ref();
+        let key = match SecretKey::from_env()? {
+            Some(key) => key,
+            None => local_key(&config_dir.join(TOKEN_CACHE_KEY_FILE_NAME))?,
+        };
+        Ok(Some(Self::open(config_dir.join(TOKEN_CACHE_FILE_NAME), &key)))
+    }
+
+    /// Path of the backing file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Cached token for `key` unless it has expired or cannot be decrypted
+    pub fn get(&self, key: &str) -> Option<CachedToken> {
+        let encoded = self.tokens.read().unwrap().get(key)?.clone();
+        self.decode(key, &encoded)
+    }
+
+    /// Encrypt and store a token, dropping expired entries, and persist the cache
+    pub fn set(&self, key: &str, token: &CachedToken) -> Result<()> {
+        let encoded = seal(&self.cipher, key, &serde_json::to_string(token)?)
+            .context("Failed to encrypt token")?;
+        {
+            let mut tokens = self.tokens.write().unwrap();
+            tokens.insert(key.to_string(), encoded);
+            tokens.retain(|name, encoded| name == key || self.decode(name, encoded).is_some());
+        }
+        self.save()
+    }
+
+    /// Remove a token and persist the cache
+    pub fn remove(&self, key: &str) -> Result<()> {
+        let removed = self.tokens.write().unwrap().remove(key).is_some();
+        if removed {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    fn decode(&self, key: &str, encoded: &str) -> Option<CachedToken> {
+        let token = unseal(&self.cipher, key, encoded)
+            .and_then(|json| serde_json::from_str::<CachedToken>(&json).map_err(Into::into));
+        match token {
+            Ok(token) if token.expires_at > unix_now() => Some(token),
+            Ok(_) => None,
+            Err(e) => {
+                // Most likely the key changed; the next login overwrites the entry
+                debug!("Ignoring cached token {}: {:#}", key, e);
+                None
+            }
+        }
+    }
+
+    fn save(&self) -> Result<()> {
+        let file = TokenCacheFile {
+            version: CACHE_VERSION,
+            tokens: self.tokens.read().unwrap().clone(),
+        };
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {:?}", parent))?;
+        }
+        write_private(&self.path, &serde_json::to_string_pretty(&file)?)
+            .with_context(|| format!("Failed to write token cache: {:?}", self.path))
+    }
+}
+
+/// Cache key for the credentials described by `identity`, e.g. a serialized login configuration
+pub fn cache_key(identity: &str) -> String {
+    Sha256::digest(identity.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Install the process-wide cache used by `cached_token` and `cache_token`
+pub fn install_token_cache(cache: Option<Arc<TokenCache>>) {
+    if let Some(cache) = &cache {
+        info!("Using token cache: {:?}", cache.path());
+    }
+    *GLOBAL_CACHE.write().unwrap() = cache;
+}
+
+/// Token cached under `key` in the process-wide cache
+pub fn cached_token(key: &str) -> Option<CachedToken> {
+    GLOBAL_CACHE.read().unwrap().as_ref()?.get(key)
+}
+
+/// Store a token in the process-wide cache; failures are logged since the token stays usable
+pub fn cache_token(key: &str, token: &CachedToken) {
+    if let Some(cache) = GLOBAL_CACHE.read().unwrap().as_ref() {
+        if let Err(e) = cache.set(key, token) {
+            warn!("Failed to cache token: {:#}", e);
+        }
+    }
+}
+
+/// Remove a token from the process-wide cache
+pub fn forget_token(key: &str) {
+    if let Some(cache) = GLOBAL_CACHE.read().unwrap().as_ref() {
+        if let Err(e) = cache.remove(key) {
+            warn!("Failed to update token cache: {:#}", e);
+        }
+    }
+}
+
+/// Read the local cache key, generating it on first use
+fn local_key(path: &Path) -> Result<SecretKey> {
+    if path.exists() {
+        let encoded = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read token cache key: {:?}", path))?;
+        return SecretKey::from_base64(&encoded)
+            .with_context(|| format!("Invalid token cache key in {:?}", path));
+    }
+    let key = SecretKey::generate();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {:?}", parent))?;
+    }
+    write_private(path, &key.to_base64())
+        .with_context(|| format!("Failed to write token cache key: {:?}", path))?;
+    info!(
+        "Generated token cache key {:?}; set {} or {} to use the secrets key instead",
+        path, SECRETS_KEY_ENV, SECRETS_KEY_FILE_ENV
+    );
+    Ok(key)
+}
+
+/// Write a file readable only by the current user
+pub(crate) fn write_private(path: &Path, content: &str) -> std::io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    use std::io::Write;
+    options.open(path)?.write_all(content.as_bytes())
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_token_cache_round_trip() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join(TOKEN_CACHE_FILE_NAME);
+        let key = SecretKey::generate();
+        let token = CachedToken {
+            token: "Bearer s3cr3t".to_string(),
+            extra_tokens: vec!["tenant-1".to_string()],
+            expires_at: unix_now() + 3600,
+            refresh_token: Some("refresh-1".to_string()),
+        };
+
+        let cache = TokenCache::open(&path, &key);
+        cache.set("login", &token).unwrap();
+        cache.set("old", &CachedToken { expires_at: unix_now() - 1, ..token.clone() }).unwrap();
+        assert!(cache.get("old").is_none());
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(!content.contains("s3cr3t"), "tokens must be encrypted at rest");
+
+        let reopened = TokenCache::open(&path, &key);
+        assert_eq!(reopened.get("login"), Some(token));
+        assert!(TokenCache::open(&path, &SecretKey::generate()).get("login").is_none());
+
+        reopened.remove("login").unwrap();
+        assert!(TokenCache::open(&path, &key).get("login").is_none());
+    }
+
+    #[test]
+    fn test_local_key_is_generated_once() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join(TOKEN_CACHE_KEY_FILE_NAME);
+        let key = local_key(&path).unwrap();
+        assert_eq!(local_key(&path).unwrap().to_base64(), key.to_base64());
+    }
+}
@@ -2,8 +2,18 @@
 
 use log::info;
 use serde::{Deserialize, Serialize};
-
-use crate::config::module::{AccessLevel, GlobalModuleConfig, MethodConfig, ModuleConfig, RateLimitConfig};
+use std::collections::HashSet;
+
+use crate::config::admin_auth;
+use crate::config::config::{Config, ProxyConfig, TlsConfig};
+use crate::config::dependencies::find_cycle;
+use crate::config::reverse_proxy::{normalize_base_path, parse_proxy_range};
+use crate::config::tag_filter::TagFilter;
+use crate::config::variables::{render, undefined_variables};
+use crate::config::module::{
+    AccessLevel, GlobalModuleConfig, MethodConfig, ModuleConfig, RateLimitConfig, TransformConfig,
+};
+use crate::services::dynamic_service::transform::compile_script;
 
 /// Validation result containing detailed information about validation issues
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,6 +28,25 @@ pub struct ValidationResult {
     pub summary: ValidationSummary,
 }
 
+impl ValidationResult {
+    /// Fail with every validation error listed when the configuration is invalid
+    pub fn ensure_valid(&self) -> anyhow::Result<()> {
+        if self.is_valid {
+            return Ok(());
+        }
+        let error_messages: Vec<String> = self
+            .errors
+            .iter()
+            .map(|error| format!("{} at {}", error.message, error.path))
+            .collect();
+        anyhow::bail!(
+            "Configuration validation failed with {} errors:\n{}",
+            self.errors.len(),
+            error_messages.join("\n")
+        )
+    }
+}
+
 /// Validation error with detailed information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValidationError {
@@ -100,7 +129,397 @@ impl ConfigValidator {
 
     /// Validate GlobalModuleConfig
     pub fn validate_global_module_config(&self, config: &GlobalModuleConfig) -> ValidationResult {
-        let mut result = ValidationResult {
+        let mut result = Self::empty_result();
+
+        info!("Validating GlobalModuleConfig");
+
+        self.validate_modules(&mut result, config);
+        Self::finish(&mut result);
+
+        result
+    }
+
+    /// Validate the main configuration (server, API, backends) together with its module configuration
+    pub fn validate_config(&self, config: &Config) -> ValidationResult {
+        let mut result = Self::empty_result();
+
+        info!("Validating main configuration");
+
+        if config.server.port == 0 {
+            self.add_error(
+                &mut result,
+                ErrorSeverity::Critical,
+                "Server port cannot be 0",
+                "server.port",
+                None,
+            );
+        }
+
+        self.validate_templated_base_url(&mut result, config, "api.base_url", &config.api.base_url);
+
+        if config.api.timeout == 0 {
+            self.add_error(
+                &mut result,
+                ErrorSeverity::High,
+                "API timeout cannot be 0",
+                "api.timeout",
+                None,
+            );
+        }
+
+        self.validate_tls(&mut result, "tls", &config.tls);
+        if let Some(proxy) = &config.proxy {
+            self.validate_proxy(&mut result, "proxy", proxy);
+        }
+
+        for (name, backend) in &config.backends {
+            let backend_path = format!("backends.{}", name);
+            self.validate_templated_base_url(
+                &mut result,
+                config,
+                &format!("{}.base_url", backend_path),
+                &backend.base_url,
+            );
+            self.validate_tls(&mut result, &format!("{}.tls", backend_path), &backend.tls);
+            if let Some(proxy) = &backend.proxy {
+                self.validate_proxy(&mut result, &format!("{}.proxy", backend_path), proxy);
+            }
+
+            if backend.timeout == Some(0) {
+                self.add_error(
+                    &mut result,
+                    ErrorSeverity::High,
+                    "Backend timeout cannot be 0",
+                    &format!("{}.timeout", backend_path),
+                    None,
+                );
+            }
+        }
+
+        if let Some(webhooks) = &config.webhooks {
+            if webhooks.max_events == 0 {
+                self.add_error(
+                    &mut result,
+                    ErrorSeverity::Medium,
+                    "Webhooks must keep at least one event",
+                    "webhooks.max_events",
+                    None,
+                );
+            }
+            if webhooks.secret.is_none() {
+                self.add_warning(
+                    &mut result,
+                    "Webhooks accept unsigned callbacks from anyone reaching the web server",
+                    "webhooks.secret",
+                    None,
+                );
+            }
+        }
+
+        if let Some(https) = &config.server.https {
+            let configured = https.cert.is_some() || https.key.is_some();
+            if https.cert.is_some() != https.key.is_some() || configured == https.self_signed {
+                self.add_error(
+                    &mut result,
+                    ErrorSeverity::High,
+                    "HTTPS needs cert and key, or self_signed",
+                    "server.https",
+                    None,
+                );
+            }
+        }
+        let loopback = admin_auth::is_loopback(&config.server.bind_address);
+        if !loopback && config.server.https.is_none() {
+            self.add_warning(
+                &mut result,
+                "The web server is reachable from the network over plain HTTP",
+                "server.https",
+                None,
+            );
+        }
+        if !loopback && !admin_auth::is_enabled(&config.server) {
+            self.add_error(
+                &mut result,
+                ErrorSeverity::Critical,
+                "The web API cannot be reachable from the network without authentication; set server.admin_token or server.admin_users",
+                "server.admin_users",
+                None,
+            );
+        }
+
+        let mut admin_names = HashSet::new();
+        for (index, user) in config.server.admin_users.iter().enumerate() {
+            let user_path = format!("server.admin_users[{}]", index);
+            if user.name.is_empty() || user.name.contains(':') {
+                self.add_error(
+                    &mut result,
+                    ErrorSeverity::High,
+                    "Web API user names cannot be empty or contain ':'",
+                    &format!("{}.name", user_path),
+                    None,
+                );
+            } else if !admin_names.insert(user.name.as_str()) {
+                self.add_error(
+                    &mut result,
+                    ErrorSeverity::High,
+                    &format!("Web API user '{}' is defined more than once", user.name),
+                    &format!("{}.name", user_path),
+                    None,
+                );
+            }
+            if user.token.is_none() && user.password.is_none() {
+                self.add_error(
+                    &mut result,
+                    ErrorSeverity::High,
+                    &format!("Web API user '{}' needs a token or a password", user.name),
+                    &user_path,
+                    None,
+                );
+            }
+        }
+
+        if let Err(e) = normalize_base_path(&config.server.base_path) {
+            self.add_error(&mut result, ErrorSeverity::High, &e.to_string(), "server.base_path", None);
+        }
+        for (index, range) in config.server.trusted_proxies.iter().enumerate() {
+            if let Err(e) = parse_proxy_range(range) {
+                self.add_error(
+                    &mut result,
+                    ErrorSeverity::High,
+                    &e.to_string(),
+                    &format!("server.trusted_proxies[{}]", index),
+                    None,
+                );
+            }
+        }
+        if let Some(cors) = &config.server.cors {
+            if cors.allow_credentials && cors.allowed_origins.iter().any(|origin| origin == "*") {
+                self.add_error(
+                    &mut result,
+                    ErrorSeverity::High,
+                    "CORS with credentials needs explicit origins instead of '*'",
+                    "server.cors.allowed_origins",
+                    None,
+                );
+            }
+        }
+
+        if let Some(mcp_auth) = &config.mcp_auth {
+            if mcp_auth.tokens.is_empty() && mcp_auth.introspection.is_none() {
+                self.add_error(
+                    &mut result,
+                    ErrorSeverity::High,
+                    "MCP authentication needs tokens or an introspection endpoint",
+                    "mcp_auth",
+                    None,
+                );
+            }
+            for (index, token) in mcp_auth.tokens.iter().enumerate() {
+                if token.token.is_empty() {
+                    self.add_error(
+                        &mut result,
+                        ErrorSeverity::High,
+                        &format!("MCP token '{}' cannot be empty", token.name),
+                        &format!("mcp_auth.tokens[{}].token", index),
+                        None,
+                    );
+                }
+                for tool in token.tools.iter().filter(|tool| !tool.contains('/')) {
+                    self.add_error(
+                        &mut result,
+                        ErrorSeverity::Medium,
+                        &format!("MCP token tool '{}' must be written as <module>/<method>", tool),
+                        &format!("mcp_auth.tokens[{}].tools", index),
+                        None,
+                    );
+                }
+            }
+        } else if !loopback {
+            self.add_warning(
+                &mut result,
+                "The MCP HTTP endpoint is reachable from the network without authentication",
+                "mcp_auth",
+                None,
+            );
+        }
+
+        self.validate_modules(&mut result, &config.module_config);
+        self.validate_header_variables(&mut result, config);
+        Self::finish(&mut result);
+
+        result
+    }
+
+    /// Validate that a client certificate comes with its key and that pins are SHA-256 fingerprints
+    fn validate_tls(&self, result: &mut ValidationResult, path: &str, tls: &TlsConfig) {
+        if let Err(e) = tls.pins() {
+            self.add_error(
+                result,
+                ErrorSeverity::High,
+                &e.to_string(),
+                &format!("{}.pinned_fingerprints", path),
+                None,
+            );
+        }
+        if tls.client_cert.is_some() != tls.client_key.is_some() {
+            self.add_error(
+                result,
+                ErrorSeverity::High,
+                "client_cert and client_key must be set together",
+                path,
+                None,
+            );
+        }
+    }
+
+    /// Validate that a proxy URL has a supported scheme and that a password comes with a user name
+    fn validate_proxy(&self, result: &mut ValidationResult, path: &str, proxy: &ProxyConfig) {
+        if !proxy.url.is_empty() {
+            let scheme = url::Url::parse(&proxy.url).map(|url| url.scheme().to_string());
+            if !matches!(scheme.as_deref(), Ok("http" | "https" | "socks5" | "socks5h")) {
+                self.add_error(
+                    result,
+                    ErrorSeverity::High,
+                    "Proxy URL must be an http, https, socks5 or socks5h URL",
+                    &format!("{}.url", path),
+                    None,
+                );
+            }
+        }
+        if proxy.password.is_some() && proxy.username.is_none() {
+            self.add_error(
+                result,
+                ErrorSeverity::Medium,
+                "Proxy password is set without a username and is not used",
+                &format!("{}.password", path),
+                None,
+            );
+        }
+    }
+
+    /// Validate a base URL after substituting the configuration variables
+    fn validate_templated_base_url(&self, result: &mut ValidationResult, config: &Config, path: &str, base_url: &str) {
+        match render(base_url, &config.variables) {
+            Ok(rendered) => self.validate_base_url(result, path, &rendered),
+            Err(e) => self.add_error(result, ErrorSeverity::Critical, &e.to_string(), path, Some(base_url)),
+        }
+    }
+
+    /// Check that the headers of the module configuration only reference defined variables
+    fn validate_header_variables(&self, result: &mut ValidationResult, config: &Config) {
+        let module_config = &config.module_config;
+        let mut headers: Vec<(String, &String)> = module_config
+            .default_headers
+            .iter()
+            .map(|(name, value)| (format!("default_headers.{}", name), value))
+            .collect();
+        for (module_name, module) in &module_config.modules {
+            headers.extend(
+                module
+                    .headers
+                    .iter()
+                    .map(|(name, value)| (format!("modules.{}.headers.{}", module_name, name), value)),
+            );
+            for (method_name, method) in module.methods.iter().flatten() {
+                headers.extend(method.headers.iter().map(|(name, value)| {
+                    (
+                        format!("modules.{}.methods.{}.headers.{}", module_name, method_name, name),
+                        value,
+                    )
+                }));
+            }
+        }
+
+        for (path, value) in headers {
+            let undefined = undefined_variables(value, &config.variables);
+            if !undefined.is_empty() {
+                self.add_error(
+                    result,
+                    ErrorSeverity::High,
+                    &format!("Undefined configuration variables: {}", undefined.join(", ")),
+                    &path,
+                    Some("Define them in the \"variables\" section"),
+                );
+            }
+        }
+    }
+
+    /// Validate every module of a module configuration
+    fn validate_modules(&self, result: &mut ValidationResult, config: &GlobalModuleConfig) {
+        if let Some(enable) = &config.enable {
+            if let Err(e) = TagFilter::parse(enable) {
+                self.add_error(result, ErrorSeverity::High, &e.to_string(), "enable", Some(enable));
+            }
+        }
+
+        for (module_name, module_config) in &config.modules {
+            result.summary.total_modules += 1;
+            
+            self.validate_module_config(result, module_name, module_config);
+        }
+
+        self.validate_module_dependencies(result, config);
+    }
+
+    /// An enabled module must not depend on disabled or unknown modules, and dependencies must not form a cycle
+    fn validate_module_dependencies(&self, result: &mut ValidationResult, config: &GlobalModuleConfig) {
+        let mut names: Vec<&String> = config.modules.keys().collect();
+        names.sort();
+        for module_name in names {
+            if !config.is_module_enabled(module_name) {
+                continue;
+            }
+            let unmet = config.unmet_dependencies(module_name);
+            if !unmet.is_empty() {
+                self.add_error(
+                    result,
+                    ErrorSeverity::High,
+                    &format!(
+                        "Module '{}' depends on disabled or unknown modules: {}",
+                        module_name,
+                        unmet.join(", ")
+                    ),
+                    &format!("modules.{}.depends_on", module_name),
+                    None,
+                );
+            }
+        }
+
+        let cycle = find_cycle(config);
+        if !cycle.is_empty() {
+            self.add_error(
+                result,
+                ErrorSeverity::High,
+                &format!("Dependency cycle between modules: {}", cycle.join(" -> ")),
+                &format!("modules.{}.depends_on", cycle[0]),
+                None,
+            );
+        }
+    }
+
+    /// Validate that a base URL is an absolute http(s) URL
+    fn validate_base_url(&self, result: &mut ValidationResult, path: &str, base_url: &str) {
+        match url::Url::parse(base_url) {
+            Ok(url) if url.scheme() == "http" || url.scheme() == "https" => {}
+            Ok(url) => self.add_error(
+                result,
+                ErrorSeverity::Critical,
+                &format!("Unsupported URL scheme '{}'", url.scheme()),
+                path,
+                Some(base_url),
+            ),
+            Err(e) => self.add_error(
+                result,
+                ErrorSeverity::Critical,
+                &format!("Invalid base URL: {}", e),
+                path,
+                Some(base_url),
+            ),
+        }
+    }
+
+    fn empty_result() -> ValidationResult {
+        ValidationResult {
             is_valid: true,
             errors: Vec::new(),
             warnings: Vec::new(),
@@ -111,23 +530,14 @@ impl ConfigValidator {
                 error_count: 0,
                 warning_count: 0,
             },
-        };
-
-        info!("Validating GlobalModuleConfig");
-
-        // Validate each module
-        for (module_name, module_config) in &config.modules {
-            result.summary.total_modules += 1;
-            
-            self.validate_module_config(&mut result, module_name, module_config);
         }
+    }
 
-        // Update summary counts
+    /// Update summary counts and validity
+    fn finish(result: &mut ValidationResult) {
         result.summary.error_count = result.errors.len();
         result.summary.warning_count = result.warnings.len();
         result.is_valid = result.errors.is_empty();
-
-        result
     }
 
     /// Validate module configuration
@@ -162,6 +572,48 @@ impl ConfigValidator {
             }
         }
 
+        // Environment variable prefixes must produce valid variable names
+        if let Some(env_prefix) = &module_config.env_prefix {
+            if env_prefix.is_empty() || !env_prefix.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                self.add_error(
+                    result,
+                    ErrorSeverity::High,
+                    &format!("Invalid environment variable prefix: '{}'", env_prefix),
+                    &format!("{}.env_prefix", module_path),
+                    Some("Use letters, digits and underscores, e.g. \"JIRA_\""),
+                );
+            }
+        }
+
+        // Validate module-level rate limit
+        if let Some(rate_limit) = &module_config.rate_limit {
+            self.validate_rate_limit_config(result, &module_path, rate_limit);
+        }
+
+        self.validate_transform_config(result, &module_path, &module_config.transform);
+
+        for (index, watch) in module_config.watch.iter().enumerate() {
+            let watch_path = format!("{}.watch[{}]", module_path, index);
+            if watch.method.is_empty() {
+                self.add_error(
+                    result,
+                    ErrorSeverity::High,
+                    "Watched method name cannot be empty",
+                    &format!("{}.method", watch_path),
+                    None,
+                );
+            }
+            if watch.interval_secs == 0 {
+                self.add_error(
+                    result,
+                    ErrorSeverity::Medium,
+                    "Watch interval cannot be 0",
+                    &format!("{}.interval_secs", watch_path),
+                    None,
+                );
+            }
+        }
+
         // Validate methods
         if let Some(methods) = &module_config.methods {
             for (method_name, method_config) in methods {
@@ -220,6 +672,8 @@ impl ConfigValidator {
             self.validate_rate_limit_config(result, method_path, rate_limit);
         }
 
+        self.validate_transform_config(result, method_path, &method_config.transform);
+
         // Validate access level
         if let Some(access_level) = &method_config.access_level {
             match access_level {
@@ -230,6 +684,22 @@ impl ConfigValidator {
         }
     }
 
+    /// Transformation scripts must compile
+    fn validate_transform_config(&self, result: &mut ValidationResult, path: &str, transform: &TransformConfig) {
+        let scripts = [("pre_request", &transform.pre_request), ("post_response", &transform.post_response)];
+        for (hook, script) in scripts {
+            if let Some(Err(e)) = script.as_deref().map(compile_script) {
+                self.add_error(
+                    result,
+                    ErrorSeverity::High,
+                    &format!("Invalid {} script: {}", hook, e),
+                    &format!("{}.transform.{}", path, hook),
+                    Some("Transformation scripts are written in Rhai"),
+                );
+            }
+        }
+    }
+
        /// Validate rate limit configuration
     fn validate_rate_limit_config(
         &self,
@@ -398,6 +868,90 @@ mod tests {
         assert_eq!(result.errors[0].message, "Module name cannot be empty");
     }
 
+    #[test]
+    fn test_validate_main_config() {
+        let validator = ConfigValidator::new();
+        assert!(validator.validate_config(&Config::default()).is_valid);
+
+        let mut config = Config::default();
+        config.server.port = 0;
+        config.api.base_url = "api.example.com".to_string();
+        let result = validator.validate_config(&config);
+
+        assert!(!result.is_valid);
+        let paths: Vec<&str> = result.errors.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, vec!["server.port", "api.base_url"]);
+    }
+
+    #[test]
+    fn test_exposed_web_api_requires_authentication() {
+        let validator = ConfigValidator::new();
+        let mut config = Config::default();
+        config.server.bind_address = "0.0.0.0".to_string();
+        let result = validator.validate_config(&config);
+        assert!(!result.is_valid);
+        assert!(result.errors.iter().any(|e| e.path == "server.admin_users"));
+
+        config.server.admin_token = Some("env://ADMIN_TOKEN".to_string());
+        let result = validator.validate_config(&config);
+        assert!(!result.errors.iter().any(|e| e.path == "server.admin_users"));
+    }
+
+    #[test]
+    fn test_validate_config_variables() {
+        let validator = ConfigValidator::new();
+        let mut config = Config::default();
+        config.api.base_url = "https://{{tenant}}.example.com".to_string();
+        config
+            .module_config
+            .default_headers
+            .insert("X-Region".to_string(), "{{region}}".to_string());
+
+        let result = validator.validate_config(&config);
+        let paths: Vec<&str> = result.errors.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, vec!["api.base_url", "default_headers.X-Region"]);
+
+        config.variables.insert("tenant".to_string(), "acme".to_string());
+        config.variables.insert("region".to_string(), "eu".to_string());
+        assert!(validator.validate_config(&config).is_valid);
+    }
+
+    #[test]
+    fn test_validate_module_dependencies() {
+        let validator = ConfigValidator::new();
+        let mut config = GlobalModuleConfig::default();
+        let mut bug = ModuleConfig::default();
+        bug.depends_on = vec!["core".to_string()];
+        config.modules.insert("bug".to_string(), bug);
+        let mut core = ModuleConfig::default();
+        core.enabled = false;
+        config.modules.insert("core".to_string(), core);
+
+        let result = validator.validate_global_module_config(&config);
+        assert!(result.errors.iter().any(|e| e.path == "modules.bug.depends_on"));
+
+        config.modules.get_mut("core").unwrap().enabled = true;
+        assert!(validator.validate_global_module_config(&config).is_valid);
+        config.modules.get_mut("core").unwrap().depends_on = vec!["bug".to_string()];
+        assert!(!validator.validate_global_module_config(&config).is_valid);
+    }
+
+    #[test]
+    fn test_validate_transform_scripts() {
+        let validator = ConfigValidator::new();
+        let mut config = GlobalModuleConfig::default();
+        let mut orders = ModuleConfig::default();
+        orders.transform.pre_request = Some("params.total = params.price * params.quantity;".to_string());
+        let mut method = MethodConfig::default();
+        method.transform.post_response = Some("response.remove(\"secret\"".to_string());
+        orders.methods = Some(std::collections::HashMap::from([("getOrder".to_string(), method)]));
+        config.modules.insert("orders".to_string(), orders);
+
+        let result = validator.validate_global_module_config(&config);
+        let paths: Vec<&str> = result.errors.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, vec!["modules.orders.methods.getOrder.transform.post_response"]);
+    }
+
     #[test]
     fn test_validation_report() {
         let validator = ConfigValidator::new();
@@ -0,0 +1,108 @@
+//! Configuration variables for MCP-ANY-REST
+//! The `variables` section of the configuration (`{"tenant": "acme"}`) is substituted into
+//! `{{tenant}}` placeholders of base URLs, endpoint URIs, headers and preset values when they are
+//! used, so one module set can serve several tenants by swapping variables.
+
+use anyhow::{bail, Result};
+use regex::{Captures, Regex};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+/// `{{name}}` placeholder; whitespace inside the braces is allowed
+fn placeholder() -> &'static Regex {
+    static PLACEHOLDER: OnceLock<Regex> = OnceLock::new();
+    PLACEHOLDER.get_or_init(|| Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_.-]*)\s*\}\}").unwrap())
+}
+
+/// Names of the variables referenced by a template, in order of appearance
+pub fn referenced_variables(template: &str) -> Vec<String> {
+    placeholder()
+        .captures_iter(template)
+        .map(|caps| caps[1].to_string())
+        .collect()
+}
+
+/// Variables referenced by a template but not defined
+pub fn undefined_variables(template: &str, variables: &BTreeMap<String, String>) -> Vec<String> {
+    referenced_variables(template)
+        .into_iter()
+        .filter(|name| !variables.contains_key(name))
+        .collect()
+}
+
+/// Substitute the variables of a template; fails on an undefined variable
+pub fn render(template: &str, variables: &BTreeMap<String, String>) -> Result<String> {
+    let undefined = undefined_variables(template, variables);
+    if !undefined.is_empty() {
+        bail!("Undefined configuration variables in '{}': {}", template, undefined.join(", "));
+    }
+
+    Ok(placeholder()
+        .replace_all(template, |caps: &Captures| variables[&caps[1]].clone())
+        .into_owned())
+}
+
+/// Substitute the variables of every string (object keys excluded) inside a JSON value
+pub fn render_value(value: &mut Value, variables: &BTreeMap<String, String>) -> Result<()> {
+    match value {
+        Value::String(s) => *s = render(s, variables)?,
+        Value::Array(items) => {
+            for item in items {
+                render_value(item, variables)?;
+            }
+        }
+        Value::Object(map) => {
+            for item in map.values_mut() {
+                render_value(item, variables)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Substitute the variables of every string inside a serializable value (e.g. a preset module)
+pub fn render_serde<T: Serialize + DeserializeOwned>(value: &T, variables: &BTreeMap<String, String>) -> Result<T> {
+    let mut json = serde_json::to_value(value)?;
+    render_value(&mut json, variables)?;
+    Ok(serde_json::from_value(json)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn variables() -> BTreeMap<String, String> {
+        BTreeMap::from([
+            ("tenant".to_string(), "acme".to_string()),
+            ("region".to_string(), "eu".to_string()),
+        ])
+    }
+
+    #[test]
+    fn test_render_substitutes_variables() {
+        let vars = variables();
+        assert_eq!(
+            render("https://{{tenant}}.{{ region }}.example.com/api", &vars).unwrap(),
+            "https://acme.eu.example.com/api"
+        );
+        assert_eq!(render("/users/{id}", &vars).unwrap(), "/users/{id}");
+        assert!(render("{{tenant}}-{{missing}}", &vars).is_err());
+        assert_eq!(undefined_variables("{{tenant}}-{{missing}}", &vars), vec!["missing".to_string()]);
+    }
+
+    #[test]
+    fn test_render_value_walks_nested_strings() {
+        let mut value = json!({
+            "user": { "headers": { "X-Tenant": "{{tenant}}" }, "enabled": true },
+            "tags": ["{{region}}"]
+        });
+        render_value(&mut value, &variables()).unwrap();
+        assert_eq!(value["user"]["headers"]["X-Tenant"], "acme");
+        assert_eq!(value["tags"][0], "eu");
+    }
+}
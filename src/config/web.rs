@@ -2,31 +2,56 @@
 
 use anyhow::Result;
 use axum::{
-    extract::{Path, State},
-    response::{Html, Json},
+    body::Bytes,
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        ConnectInfo, Path, Query, Request, State,
+    },
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+    middleware::{self, Next},
+    response::{Html, IntoResponse, Json, Redirect, Response},
     routing::{delete, get, patch, post, put},
     Router,
 };
 
-use log::{error, info};
+use log::{debug, error, info, warn};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::broadcast;
 
 use rmcp::{
     transport::{
+        sse_server::{SseServer, SseServerConfig},
         streamable_http_server::session::local::LocalSessionManager, StreamableHttpService,
     },
 };
-
-use crate::config::config::Config;
+use tokio_util::sync::CancellationToken;
+
+use crate::config::admin_auth;
+use crate::config::backup::BackupEntry;
+use crate::config::clients::ClientOverride;
+use crate::config::config::{AdminRole, AuthConfig, Config};
+use crate::config::credentials::{is_credential_ref, EXEC_SCHEME, FILE_SCHEME};
+use crate::config::dependencies::DependencyGraph;
+use crate::config::doctor::{diagnose, DoctorReport};
 use crate::config::dynamic::ConfigChangeEvent;
-use crate::config::dynamic::DynamicConfigManager;
+use crate::config::dynamic::{DynamicConfigManager, PresetPreview};
+use crate::config::effective::{resolve_config, EffectiveModuleSettings};
+use crate::config::history::{with_actor, HistoryEntry};
+use crate::config::https::server_tls_config;
 use crate::config::loader::ConfigLoader;
+use crate::config::mcp_auth;
 use crate::config::module::GlobalModuleConfig;
 use crate::config::module::ModuleConfig;
+use crate::config::redact::{redact_at_path, redact_value, restore_redacted};
+use crate::config::reverse_proxy::{self, ForwardedRequest};
+use crate::config::validator::{ConfigValidator, ValidationResult};
+use crate::config::zml_loader::ZmlModuleLoader;
 
 /// Web configuration server state (compatible with both old and new config systems)
 #[derive(Clone)]
@@ -48,6 +73,11 @@ impl WebConfigState {
         }
     }
 
+    /// Current version of the configuration and its presets
+    pub fn version(&self) -> String {
+        config_version(&self.get_config(), &self.get_preset_info().unwrap_or_default())
+    }
+
     /// Get available presets as string values
     pub fn get_available_presets(&self) -> Result<Vec<String>> {
         match self {
@@ -126,20 +156,24 @@ impl WebConfigState {
                                 .get_preset(&preset.id)
                                 .map(|config| config.clone())
                                 .unwrap_or_else(|| crate::config::preset_loader::PresetConfig {
+                                    config_version: crate::config::migrate::CURRENT_CONFIG_VERSION,
                                     name: preset.name.clone(),
                                     description: preset.description.clone(),
                                     default_access_level: None,
                                     default_rate_limit: None,
                                     modules: std::collections::HashMap::new(),
+                                    enable: None,
                                 })
                         } else {
                             // If loading fails, create a default preset config
                             crate::config::preset_loader::PresetConfig {
+                                config_version: crate::config::migrate::CURRENT_CONFIG_VERSION,
                                 name: preset.name.clone(),
                                 description: preset.description.clone(),
                                 default_access_level: None,
                                 default_rate_limit: None,
                                 modules: std::collections::HashMap::new(),
+                                enable: None,
                             }
                         };
 
@@ -186,6 +220,22 @@ impl WebConfigState {
         }
     }
 
+    /// Validate a configuration update without applying it.
+    /// Returns `None` when the request carries no configuration.
+    pub fn validate_update(&self, request: &ConfigUpdateRequest) -> Option<ValidationResult> {
+        let validator = ConfigValidator::new();
+        match (&request.config, &request.module_config) {
+            (Some(config), Some(module_config)) => {
+                let mut config = config.clone();
+                config.module_config = module_config.clone();
+                Some(validator.validate_config(&config))
+            }
+            (Some(config), None) => Some(validator.validate_config(config)),
+            (None, Some(module_config)) => Some(validator.validate_global_module_config(module_config)),
+            (None, None) => None,
+        }
+    }
+
     /// Update module configuration
     pub fn update_module_config(&self, module_config: GlobalModuleConfig) -> Result<()> {
         match self {
@@ -227,6 +277,14 @@ impl WebConfigState {
         }
     }
 
+    /// Whether configuration changes are rejected (`--read-only-config` or `lock` in the configuration)
+    pub fn is_read_only(&self) -> bool {
+        match self {
+            WebConfigState::Dynamic(manager) => manager.is_read_only(),
+            WebConfigState::Loader(_loader) => self.get_config().lock,
+        }
+    }
+
     /// Reload configuration if modified
     pub fn reload_if_modified(&self) -> Result<bool> {
         match self {
@@ -238,6 +296,135 @@ impl WebConfigState {
         }
     }
 
+    /// Get persistent configuration history, newest first
+    pub fn get_history(&self) -> Result<Vec<HistoryEntry>> {
+        match self {
+            WebConfigState::Dynamic(manager) => Ok(manager.get_history()),
+            WebConfigState::Loader(_loader) => {
+                Err(anyhow::anyhow!("Configuration history is not available for ConfigLoader"))
+            }
+        }
+    }
+
+    /// Roll back to a configuration history revision
+    pub fn rollback(&self, revision: u64) -> Result<()> {
+        match self {
+            WebConfigState::Dynamic(manager) => manager.rollback(revision),
+            WebConfigState::Loader(_loader) => {
+                Err(anyhow::anyhow!("Configuration rollback is not available for ConfigLoader"))
+            }
+        }
+    }
+
+    /// Diagnose the configuration directory, the backends and the exposed tools
+    pub async fn diagnose(&self) -> DoctorReport {
+        let (config_path, _, _) = self.get_config_paths();
+        let config_dir = config_path
+            .parent()
+            .unwrap_or_else(|| std::path::Path::new("."))
+            .to_path_buf();
+        let profile = match self {
+            WebConfigState::Dynamic(manager) => manager.profile().map(str::to_string),
+            WebConfigState::Loader(_loader) => None,
+        };
+        diagnose(&config_dir, profile.as_deref(), &self.get_config()).await
+    }
+
+    /// What applying a preset would change
+    pub fn preview_preset(&self, preset_id: &str) -> Result<PresetPreview> {
+        match self {
+            WebConfigState::Dynamic(manager) => manager.preview_preset(preset_id),
+            WebConfigState::Loader(_loader) => {
+                Err(anyhow::anyhow!("Preset previews are not available for ConfigLoader"))
+            }
+        }
+    }
+
+    /// Dependency graph and activation order of the configured modules
+    pub fn dependency_graph(&self) -> DependencyGraph {
+        DependencyGraph::build(&self.get_config().module_config)
+    }
+
+    /// Backups of the configuration files, newest first
+    pub fn list_backups(&self) -> Result<Vec<BackupEntry>> {
+        match self {
+            WebConfigState::Dynamic(manager) => manager.list_backups(),
+            WebConfigState::Loader(_loader) => {
+                Err(anyhow::anyhow!("Configuration backups are not available for ConfigLoader"))
+            }
+        }
+    }
+
+    /// Restore a configuration file from a backup
+    pub fn restore_backup(&self, name: &str) -> Result<()> {
+        match self {
+            WebConfigState::Dynamic(manager) => manager.restore_backup(name),
+            WebConfigState::Loader(_loader) => {
+                Err(anyhow::anyhow!("Configuration backups are not available for ConfigLoader"))
+            }
+        }
+    }
+
+    /// Resolve the effective settings of every module and method, including the methods declared in ZML
+    pub fn get_effective_config(&self) -> BTreeMap<String, EffectiveModuleSettings> {
+        let (config_path, _, _) = self.get_config_paths();
+        let zml_dir = config_path
+            .parent()
+            .unwrap_or_else(|| std::path::Path::new("."))
+            .join("zml");
+
+        let mut known_methods = HashMap::new();
+        match ZmlModuleLoader::from_dir(&zml_dir) {
+            Ok(loader) => {
+                for module_name in loader.get_all_module_names() {
+                    if let Some(module) = loader.get_module(&module_name) {
+                        let methods: HashMap<String, Option<u64>> = module
+                            .methods
+                            .iter()
+                            .map(|(name, method)| (name.clone(), method.timeout_ms))
+                            .collect();
+                        known_methods.insert(module_name, methods);
+                    }
+                }
+            }
+            Err(e) => warn!("Failed to load ZML modules from {}: {}", zml_dir.display(), e),
+        }
+
+        resolve_config(&self.get_config(), &known_methods)
+    }
+
+    /// Get per-client overrides by client key
+    pub fn get_clients(&self) -> Result<HashMap<String, ClientOverride>> {
+        match self {
+            WebConfigState::Dynamic(manager) => Ok(manager.clients().list()),
+            WebConfigState::Loader(_loader) => {
+                Err(anyhow::anyhow!("Client overrides are not available for ConfigLoader"))
+            }
+        }
+    }
+
+    /// Create or replace the override of a client (MCP client name or HTTP session id)
+    pub fn set_client(&self, client: &str, client_override: ClientOverride) -> Result<()> {
+        match self {
+            WebConfigState::Dynamic(manager) => {
+                manager.clients().set(client, client_override, &manager.get_config())
+            }
+            WebConfigState::Loader(_loader) => {
+                Err(anyhow::anyhow!("Client overrides are not available for ConfigLoader"))
+            }
+        }
+    }
+
+    /// Remove the override of a client; returns whether it existed
+    pub fn remove_client(&self, client: &str) -> Result<bool> {
+        match self {
+            WebConfigState::Dynamic(manager) => manager.clients().remove(client),
+            WebConfigState::Loader(_loader) => {
+                Err(anyhow::anyhow!("Client overrides are not available for ConfigLoader"))
+            }
+        }
+    }
+
     /// Save a preset configuration
     pub fn save_preset(
         &self,
@@ -247,12 +434,9 @@ impl WebConfigState {
         info!("Saving preset: {}", preset_id);
         match self {
             WebConfigState::Dynamic(manager) => {
-                // For DynamicConfigManager, we need to implement save_preset functionality
-                // Since DynamicConfigManager doesn't have save_preset, we'll create a new PresetLoader
-                let preset_path = manager.get_config_paths().2;
-                let mut preset_loader =
-                    crate::config::preset_loader::PresetLoader::new(preset_path);
-                preset_loader.save_preset(&preset_id, &preset_config)
+                // The manager writes to the configuration store if configured, otherwise backs up
+                // preset files before writing them
+                manager.save_preset(&preset_id, &preset_config)
             }
             WebConfigState::Loader(loader) => {
                 // For ConfigLoader, use the preset loader
@@ -272,13 +456,7 @@ impl WebConfigState {
     /// Delete a preset
     pub fn delete_preset(&self, preset_id: String) -> Result<()> {
         match self {
-            WebConfigState::Dynamic(manager) => {
-                // For DynamicConfigManager, we need to implement delete_preset functionality
-                let preset_path = manager.get_config_paths().2;
-                let mut preset_loader =
-                    crate::config::preset_loader::PresetLoader::new(preset_path);
-                preset_loader.delete_preset(&preset_id)
-            }
+            WebConfigState::Dynamic(manager) => manager.delete_preset(&preset_id),
             WebConfigState::Loader(loader) => {
                 // For ConfigLoader, use the preset loader
                 let preset_path = loader
@@ -321,6 +499,218 @@ pub struct PresetListResponse {
     pub presets: Vec<serde_json::Value>,
 }
 
+/// Query parameters of configuration writes
+#[derive(Debug, Default, Deserialize)]
+pub struct ConfigWriteQuery {
+    /// Only validate the request, do not persist anything
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Query parameters of configuration reads
+#[derive(Debug, Default, Deserialize)]
+pub struct RevealQuery {
+    /// Return credentials unmasked; requires `Authorization: Bearer <server.admin_token>`
+    #[serde(default)]
+    pub reveal: bool,
+}
+
+/// Dry-run validation response
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ValidationResponse {
+    pub success: bool,
+    pub message: String,
+    pub validation: serde_json::Value,
+}
+
+/// Request header naming the author of configuration changes
+const ACTOR_HEADER: &str = "x-config-actor";
+
+/// Serializes configuration changes, so a change is checked against the version it is applied to
+static CONFIG_WRITES: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// Version of a configuration and its presets, returned as the `ETag` of configuration API
+/// responses. Derived from the content, so it is stable across reloads and instances
+pub fn config_version(config: &Config, presets: &[serde_json::Value]) -> String {
+    fn hash_value(value: &serde_json::Value, hasher: &mut Sha256) {
+        match value {
+            serde_json::Value::Object(map) => {
+                // Hash keys in sorted order; map iteration order is not guaranteed
+                let mut keys: Vec<&String> = map.keys().collect();
+                keys.sort();
+                hasher.update(b"{");
+                for key in keys {
+                    hasher.update(serde_json::Value::String(key.clone()).to_string().as_bytes());
+                    hasher.update(b":");
+                    hash_value(&map[key], hasher);
+                    hasher.update(b",");
+                }
+                hasher.update(b"}");
+            }
+            serde_json::Value::Array(items) => {
+                hasher.update(b"[");
+                for item in items {
+                    hash_value(item, hasher);
+                    hasher.update(b",");
+                }
+                hasher.update(b"]");
+            }
+            other => hasher.update(other.to_string().as_bytes()),
+        }
+    }
+
+    let mut hasher = Sha256::new();
+    let value = serde_json::json!({
+        "config": serde_json::to_value(config).unwrap_or_default(),
+        "presets": presets,
+    });
+    hash_value(&value, &mut hasher);
+    let digest = format!("{:x}", hasher.finalize());
+    digest[..16].to_string()
+}
+
+/// Change event as sent to the web UI, with the credentials in the old and new values of its
+/// diff masked by their configuration path
+fn config_event_message(event: &ConfigChangeEvent) -> serde_json::Value {
+    let mut value = serde_json::to_value(event).unwrap_or_default();
+    if let Some(diff) = value.get_mut("diff").and_then(serde_json::Value::as_array_mut) {
+        for entry in diff {
+            let path = entry["path"].as_str().unwrap_or_default().to_string();
+            for side in ["old", "new"] {
+                if let Some(changed) = entry.get_mut(side) {
+                    redact_at_path(&path, changed);
+                }
+            }
+        }
+    }
+    value
+}
+
+/// Preset preview as sent to the web UI, with the credentials in the changed settings masked
+/// by their configuration path
+fn redacted_preview(preview: &PresetPreview) -> serde_json::Value {
+    let mut value = serde_json::to_value(preview).unwrap_or_default();
+    if let Some(settings) = value.get_mut("settings").and_then(serde_json::Value::as_array_mut) {
+        for entry in settings {
+            let path = entry["path"].as_str().unwrap_or_default().to_string();
+            for side in ["old", "new"] {
+                if let Some(changed) = entry.get_mut(side) {
+                    redact_at_path(&path, changed);
+                }
+            }
+        }
+    }
+    value
+}
+
+/// Whether a request changes configuration; reads, reloads from disk, auth tests and dry runs do not
+fn changes_config(request: &Request) -> bool {
+    !matches!(*request.method(), Method::GET | Method::HEAD | Method::OPTIONS)
+        && request.uri().path() != "/config/reload"
+        && request.uri().path() != "/config/auth/test"
+        && !request.uri().path().starts_with("/webhooks/")
+        && !Query::<ConfigWriteQuery>::try_from_uri(request.uri())
+            .map(|Query(query)| query.dry_run)
+            .unwrap_or(false)
+}
+
+/// Whether a request needs the editor role: configuration changes, and auth tests, which resolve
+/// the stored credentials and may run credential commands
+fn requires_editor(request: &Request) -> bool {
+    changes_config(request) || request.uri().path() == "/config/auth/test"
+}
+
+/// Whether a configuration key says where requests are sent, e.g. `url`, `login_url`,
+/// `redirect_uri` or `server_name`
+fn is_endpoint_key(key: &str) -> bool {
+    let key = key.to_lowercase();
+    key.ends_with("url")
+        || key.ends_with("uri")
+        || key.contains("host")
+        || key.contains("endpoint")
+        || key.contains("domain")
+        || key == "server_name"
+}
+
+/// Endpoint values of a JSON document by dotted path
+fn collect_endpoints(value: &serde_json::Value, path: &str, endpoints: &mut BTreeMap<String, serde_json::Value>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, child) in map {
+                let child_path = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                if is_endpoint_key(key) && !child.is_null() {
+                    endpoints.insert(child_path.clone(), child.clone());
+                }
+                collect_endpoints(child, &child_path, endpoints);
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                collect_endpoints(item, &format!("{}.{}", path, index), endpoints);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Credential references (`secret://`, `env://`, `file://`, `keyring://`, `exec://`) in a JSON document
+fn collect_credential_refs(value: &serde_json::Value, refs: &mut Vec<String>) {
+    match value {
+        serde_json::Value::String(s) if is_credential_ref(s) => refs.push(s.clone()),
+        serde_json::Value::Object(map) => map.values().for_each(|child| collect_credential_refs(child, refs)),
+        serde_json::Value::Array(items) => items.iter().for_each(|item| collect_credential_refs(item, refs)),
+        _ => {}
+    }
+}
+
+/// The auth section an auth test runs with: the saved one, or an unsaved `update`. Masked
+/// credentials of the update are only replaced by the stored ones, and credential references
+/// only resolved, when every URL and host of the update matches the saved section, so stored
+/// credentials are never sent to another server. Files and commands are only read when the
+/// saved section already references them.
+fn auth_under_test(update: Option<serde_json::Value>, saved: &AuthConfig) -> Result<AuthConfig, String> {
+    let Some(update) = update else {
+        return Ok(saved.clone());
+    };
+    let parse = |value: serde_json::Value| {
+        serde_json::from_value::<AuthConfig>(value).map_err(|e| format!("Invalid auth configuration: {}", e))
+    };
+    // Round trip the update so credential source objects become references and defaults are filled in
+    let mut update = serde_json::to_value(parse(update)?).unwrap_or_default();
+    let saved = serde_json::to_value(saved).unwrap_or_default();
+
+    let mut saved_endpoints = BTreeMap::new();
+    collect_endpoints(&saved, "", &mut saved_endpoints);
+    let mut endpoints = BTreeMap::new();
+    collect_endpoints(&update, "", &mut endpoints);
+    let same_endpoints = endpoints.iter().all(|(path, value)| saved_endpoints.get(path) == Some(value));
+
+    let mut saved_refs = Vec::new();
+    collect_credential_refs(&saved, &mut saved_refs);
+    let mut refs = Vec::new();
+    collect_credential_refs(&update, &mut refs);
+    for reference in refs {
+        if !same_endpoints {
+            return Err(format!(
+                "Credential reference {} is only resolved for the saved URLs and hosts; save the auth configuration before testing it",
+                reference
+            ));
+        }
+        let reads_local = reference.starts_with(EXEC_SCHEME) || reference.starts_with(FILE_SCHEME);
+        if reads_local && !saved_refs.contains(&reference) {
+            return Err(format!(
+                "Credential reference {} is not in the saved configuration; save the auth configuration before testing it",
+                reference
+            ));
+        }
+    }
+
+    if same_endpoints {
+        restore_redacted(&mut update, &saved);
+    }
+    parse(update)
+}
+
 /// History response
 #[derive(Debug, Deserialize, Serialize)]
 pub struct HistoryResponse {
@@ -329,11 +719,71 @@ pub struct HistoryResponse {
     pub history: Vec<serde_json::Value>,
 }
 
+/// Backups response
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BackupsResponse {
+    pub success: bool,
+    pub message: String,
+    pub backups: Vec<BackupEntry>,
+}
+
+/// Auth test request
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct AuthTestRequest {
+    /// Unsaved `auth` section to test instead of the current one; masked credentials keep the
+    /// stored values when its URLs and hosts are the saved ones
+    #[serde(default)]
+    pub auth: Option<serde_json::Value>,
+}
+
+/// Backup restore request
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RestoreBackupRequest {
+    /// Backup name as listed by `GET /config/backups`
+    pub name: String,
+}
+
+use crate::config::variables::render;
+use crate::services::auth_service::audit;
+use crate::services::auth_service::UnifiedAuthService;
+use crate::services::composer_service::backend_registry::{
+    backend_client_builder, configure_compression, configure_proxy,
+};
+use crate::services::composer_service::service_composer::auth_strategy_config;
 use crate::services::composer_service::ServiceComposer;
+use rmcp::model::JsonObject;
+use crate::services::dynamic_service::drift::drift_report;
+use crate::services::dynamic_service::rate_limiter;
+use crate::services::dynamic_service::notifications::notify_resources_changed;
+use crate::services::dynamic_service::webhooks::{record_event, verify_signature};
+
+/// Path of the event stream of the SSE transport
+pub const SSE_PATH: &str = "/sse";
 
-// Use port from configuration
+/// Path SSE clients post their messages to
+pub const SSE_POST_PATH: &str = "/message";
+
+/// Path REST clients post `batch_call` arguments to
+pub const BATCH_CALL_PATH: &str = "/tools/batch_call";
+
+// Use bind address and port from configuration
 fn get_bind_address(config: &Config) -> String {
-    format!("127.0.0.1:{}", config.server.port)
+    let host = &config.server.bind_address;
+    // IPv6 addresses are bracketed before the port
+    if host.contains(':') && !host.starts_with('[') {
+        format!("[{}]:{}", host, config.server.port)
+    } else {
+        format!("{}:{}", host, config.server.port)
+    }
+}
+
+/// URL scheme of the web server
+fn get_scheme(config: &Config) -> &'static str {
+    if config.server.https.is_some() {
+        "https"
+    } else {
+        "http"
+    }
 }
 
 /// Web configuration server
@@ -342,6 +792,8 @@ pub struct WebServer {
     _router: Router,
     _change_receiver: Option<broadcast::Receiver<ConfigChangeEvent>>,
     _service_composer: Option<crate::services::composer_service::ServiceComposer>,
+    _base_path: Option<String>,
+    _sse_router: Option<Router>,
 }
 
 impl WebServer {
@@ -358,7 +810,9 @@ impl WebServer {
             .route("/config/presets", post(Self::save_preset))
             .route("/config/presets/:preset_id", delete(Self::delete_preset))
             .route("/config/preset/:preset", post(Self::apply_preset))
+            .route("/config/preset/:preset/diff", get(Self::get_preset_diff))
             .route("/config/reload", post(Self::reload_config))
+            .route("/config/ws", get(Self::config_events))
             .route("/config/save", post(Self::save_config))
             .route("/config/status", get(Self::get_status))
             .route("/config/modules", get(Self::get_modules))
@@ -378,19 +832,65 @@ impl WebServer {
             )
             .route("/config/server", get(Self::get_server_config))
             .route("/config/server", put(Self::update_server_config))
+            .route("/config/history", get(Self::get_history))
+            .route("/config/rollback/:revision", post(Self::rollback))
+            .route("/config/backups", get(Self::get_backups))
+            .route("/config/backups/restore", post(Self::restore_backup))
+            .route("/config/effective", get(Self::get_effective_config))
+            .route("/config/doctor", get(Self::get_doctor))
+            .route("/config/auth/test", post(Self::test_auth))
+            .route("/config/auth/audit", get(Self::get_auth_audit))
+            .route("/config/drift", get(Self::get_drift))
+            .route("/webhooks/:module/:event", post(Self::receive_webhook))
+            .route("/config/dependencies", get(Self::get_dependencies))
+            .route("/config/clients", get(Self::get_clients))
+            .route(
+                "/config/clients/:client",
+                get(Self::get_client)
+                    .put(Self::set_client)
+                    .delete(Self::delete_client),
+            )
+            .layer(middleware::from_fn_with_state(
+                state.clone(),
+                Self::check_config_version,
+            ))
+            .layer(middleware::from_fn_with_state(
+                state.clone(),
+                Self::reject_when_read_only,
+            ))
+            .layer(middleware::from_fn_with_state(
+                state.clone(),
+                Self::require_admin_auth,
+            ))
+            .layer(middleware::from_fn(Self::track_actor))
             .with_state(state.clone());
         Self {
             _state: state,
             _router: router,
             _change_receiver: change_receiver,
             _service_composer: None,
+            _base_path: None,
+            _sse_router: None,
         }
     }
 
+    /// Serve every route under `base_path` (e.g. from `--base-path`) instead of `server.base_path`
+    pub fn with_base_path(mut self, base_path: Option<String>) -> Self {
+        self._base_path = base_path;
+        self
+    }
+
+    /// Path prefix of all routes, without trailing `/`
+    fn base_path(&self) -> Result<String> {
+        let config = self._state.get_config();
+        reverse_proxy::normalize_base_path(self._base_path.as_deref().unwrap_or(&config.server.base_path))
+    }
+
     /// Get the router
     pub fn register_service_composer(mut self, service_composer: ServiceComposer) -> Self {
         // Store composer for runtime updates
         self._service_composer = Some(service_composer.clone());
+        let batch_composer = service_composer.clone();
         let service: StreamableHttpService<ServiceComposer, LocalSessionManager> =
             StreamableHttpService::new(
                 move || Ok(service_composer.clone()),
@@ -402,11 +902,56 @@ impl WebServer {
 
         // Start MCP server using HTTP transport
         let address = get_bind_address(&config);
-        info!("  - Web configuration: http://{}", address);
-        self._router = self._router.nest_service("/mcp", service);
+        let base_path = self.base_path().unwrap_or_default();
+        info!("  - Web configuration: {}://{}{}", get_scheme(&config), address, base_path);
+        info!("  - Batch tool calls: {}://{}{}{}", get_scheme(&config), address, base_path, BATCH_CALL_PATH);
+        let mcp_router = Router::new()
+            .nest_service("/mcp", service)
+            .route(
+                BATCH_CALL_PATH,
+                post(move |parts: axum::http::request::Parts, Json(arguments): Json<JsonObject>| {
+                    let composer = batch_composer.clone();
+                    async move { Self::batch_call(composer, parts, arguments).await }
+                }),
+            )
+            .layer(middleware::from_fn_with_state(
+                self._state.clone(),
+                Self::require_mcp_auth,
+            ));
+        self._router = self._router.merge(mcp_router);
         self
     }
 
+    /// Serve the composer over the MCP SSE transport instead: clients open the event stream on
+    /// `/sse` and post their messages to `/message`
+    pub fn register_sse_service_composer(mut self, service_composer: ServiceComposer) -> Result<Self> {
+        self._service_composer = Some(service_composer.clone());
+        let config = self._state.get_config();
+        let address = get_bind_address(&config);
+        let base_path = self.base_path()?;
+        let sse_path = format!("{}{}", base_path, SSE_PATH);
+        let post_path = format!("{}{}", base_path, SSE_POST_PATH);
+        let (sse_server, sse_router) = SseServer::new(SseServerConfig {
+            // Only used by `SseServer::serve`; the web server binds the address itself
+            bind: address.parse().unwrap_or_else(|_| ([127, 0, 0, 1], config.server.port).into()),
+            sse_path: sse_path.clone(),
+            post_path: post_path.clone(),
+            ct: CancellationToken::new(),
+            sse_keep_alive: Some(Duration::from_secs(15)),
+        });
+        sse_server.with_service(move || service_composer.clone());
+
+        info!("  - MCP SSE endpoint: {}://{}{}", get_scheme(&config), address, sse_path);
+        // The SSE router keeps the full paths, which clients are told to post to, so it is routed
+        // next to the base path rather than nested in it
+        let sse_router = Router::new()
+            .route_service(&sse_path, sse_router.clone())
+            .route_service(&post_path, sse_router)
+            .layer(middleware::from_fn_with_state(self._state.clone(), Self::require_mcp_auth));
+        self._sse_router = Some(sse_router);
+        Ok(self)
+    }
+
     /// Start the web configuration server
     pub async fn start(self) -> Result<()> {
         info!("Configuration loaded successfully");
@@ -418,174 +963,245 @@ impl WebServer {
 
         // Start MCP server using HTTP transport
         let address = get_bind_address(&config);
-        info!("Starting MCP-ANY-REST server (HTTP) on {}...", address);
+        let scheme = get_scheme(&config);
+        info!("Starting MCP-ANY-REST server ({}) on {}...", scheme.to_uppercase(), address);
 
+        // The certificate is loaded before binding, so a bad one fails the start right away
+        let tls = match &config.server.https {
+            Some(https) => Some(server_tls_config(https, &config.server.bind_address).await?),
+            None => None,
+        };
+        let loopback = admin_auth::is_loopback(&config.server.bind_address);
+        if !loopback && !admin_auth::is_enabled(&config.server) {
+            anyhow::bail!(
+                "Refusing to serve the web API on {} without authentication; set server.admin_token or server.admin_users, or bind to 127.0.0.1",
+                address
+            );
+        }
+        // `/mcp`, the SSE endpoints and the REST batch calls run tools with the API's credentials
+        if self._service_composer.is_some() && !loopback && config.mcp_auth.is_none() && !config.server.allow_unauthenticated_mcp {
+            anyhow::bail!(
+                "Refusing to serve MCP on {} without authentication; configure mcp_auth, set server.allow_unauthenticated_mcp, or bind to 127.0.0.1",
+                address
+            );
+        }
+        let base_path = self.base_path()?;
         let tcp_listener = tokio::net::TcpListener::bind(&address).await?;
         info!("Available endpoints:");
-        info!("  - MCP-ANY-REST web configuration: http://{}", address);
+        info!("  - MCP-ANY-REST web configuration: {}://{}{}/", scheme, address, base_path);
 
-        // Listen for dynamic configuration changes and apply to runtime services
+        let mut router = if base_path.is_empty() {
+            self._router
+        } else {
+            // `/base/` serves the web UI like `/base`, and `/` redirects there
+            Router::new()
+                .nest(&base_path, self._router)
+                .route(
+                    &format!("{}/", base_path),
+                    get(Self::index).with_state(self._state.clone()),
+                )
+                .route("/", get(Self::redirect_to_base).with_state(self._state.clone()))
+        };
+        if let Some(sse_router) = self._sse_router {
+            router = router.merge(sse_router);
+        }
+        let router = router
+            .layer(middleware::from_fn_with_state(
+                (self._state.clone(), base_path),
+                Self::resolve_forwarded,
+            ))
+            .layer(middleware::from_fn_with_state(self._state.clone(), Self::apply_cors));
+
+        // Listen for authentication changes and apply them to the running auth service
         if let (Some(mut receiver), Some(composer)) = (self._change_receiver, self._service_composer.clone()) {
             let state = self._state.clone();
             tokio::spawn(async move {
                 loop {
                     match receiver.recv().await {
-                        Ok(_evt) => {
-                            // Rebuild auth configuration from latest state
-                            let cfg = state.get_config();
-                            let auth_cfg = crate::services::auth_service::auth_strategy::AuthConfig {
-                                mode: match cfg.auth.mode {
-                                    crate::config::config::AuthMode::Direct => crate::services::auth_service::auth_strategy::AuthMode::Direct,
-                                    crate::config::config::AuthMode::Login => crate::services::auth_service::auth_strategy::AuthMode::Login,
-                                },
-                                direct_config: cfg.auth.direct_config.map(|dc| {
-                                    crate::services::auth_service::auth_strategy::DirectAuthConfig {
-                                        auth_type: match dc.auth_type {
-                                            crate::config::config::DirectAuthType::Bearer => crate::services::auth_service::auth_strategy::DirectAuthType::Bearer,
-                                            crate::config::config::DirectAuthType::ApiKey => crate::services::auth_service::auth_strategy::DirectAuthType::ApiKey,
-                                            crate::config::config::DirectAuthType::Basic => crate::services::auth_service::auth_strategy::DirectAuthType::Basic,
-                                            crate::config::config::DirectAuthType::Token => crate::services::auth_service::auth_strategy::DirectAuthType::Token,
-                                            crate::config::config::DirectAuthType::CustomHeaders => crate::services::auth_service::auth_strategy::DirectAuthType::CustomHeaders,
-                                        },
-                                        token: dc.token,
-                                        api_key_name: dc.api_key_name,
-                                        username: dc.username,
-                                        password: dc.password,
-                                        custom_headers: dc.custom_headers,
-                                    }
-                                }),
-                                login_config: cfg.auth.login_config.map(|lc| {
-                                    crate::services::auth_service::auth_strategy::LoginAuthConfig {
-                                        auth_type: match lc.auth_type {
-                                            crate::config::config::LoginAuthType::Json => crate::services::auth_service::auth_strategy::LoginAuthType::Json,
-                                            crate::config::config::LoginAuthType::Form => crate::services::auth_service::auth_strategy::LoginAuthType::Form,
-                                            crate::config::config::LoginAuthType::OAuth2 => crate::services::auth_service::auth_strategy::LoginAuthType::OAuth2,
-                                            crate::config::config::LoginAuthType::ApiKey => crate::services::auth_service::auth_strategy::LoginAuthType::ApiKey,
-                                            crate::config::config::LoginAuthType::Custom => crate::services::auth_service::auth_strategy::LoginAuthType::Custom,
-                                        },
-                                        url: lc.url,
-                                        method: match lc.method {
-                                            crate::config::config::HttpMethod::Get => crate::services::auth_service::auth_strategy::HttpMethod::GET,
-                                            crate::config::config::HttpMethod::Post => crate::services::auth_service::auth_strategy::HttpMethod::POST,
-                                            crate::config::config::HttpMethod::Put => crate::services::auth_service::auth_strategy::HttpMethod::PUT,
-                                            crate::config::config::HttpMethod::Delete => crate::services::auth_service::auth_strategy::HttpMethod::DELETE,
-                                            crate::config::config::HttpMethod::Patch => crate::services::auth_service::auth_strategy::HttpMethod::PATCH,
-                                        },
-                                        headers: lc.headers,
-                                        body: lc.body.map(|b| {
-                                            crate::services::auth_service::auth_strategy::LoginRequestBody {
-                                                format: match b.format {
-                                                    crate::config::config::BodyFormat::Json => crate::services::auth_service::auth_strategy::BodyFormat::Json,
-                                                    crate::config::config::BodyFormat::Form => crate::services::auth_service::auth_strategy::BodyFormat::Form,
-                                                },
-                                                content: b.content,
-                                            }
-                                        }),
-                                        response_format: match lc.response_format {
-                                            crate::config::config::ResponseFormat::Json => crate::services::auth_service::auth_strategy::ResponseFormat::Json,
-                                            crate::config::config::ResponseFormat::Xml => crate::services::auth_service::auth_strategy::ResponseFormat::Xml,
-                                            crate::config::config::ResponseFormat::Text => crate::services::auth_service::auth_strategy::ResponseFormat::Text,
-                                        },
-                                        token_extraction: if !lc.token_extraction.tokens.is_empty() {
-                                            crate::services::auth_service::auth_strategy::TokenExtraction {
-                                                tokens: lc.token_extraction.tokens.into_iter().map(|token| {
-                                                    crate::services::auth_service::auth_strategy::TokenExtractionItem {
-                                                        source_location: match token.source_location {
-                                                            crate::config::config::TokenLocation::Header => crate::services::auth_service::auth_strategy::TokenLocation::Header,
-                                                            crate::config::config::TokenLocation::Body => crate::services::auth_service::auth_strategy::TokenLocation::Body,
-                                                            crate::config::config::TokenLocation::Query => crate::services::auth_service::auth_strategy::TokenLocation::Query,
-                                                        },
-                                                        source_key: token.source_key,
-                                                        format: match token.format {
-                                                            crate::config::config::TokenFormat::Bearer => crate::services::auth_service::auth_strategy::TokenFormat::Bearer,
-                                                            crate::config::config::TokenFormat::Token => crate::services::auth_service::auth_strategy::TokenFormat::Raw,
-                                                            crate::config::config::TokenFormat::ApiKey => crate::services::auth_service::auth_strategy::TokenFormat::Raw,
-                                                            crate::config::config::TokenFormat::Raw => crate::services::auth_service::auth_strategy::TokenFormat::Raw,
-                                                            crate::config::config::TokenFormat::Basic => crate::services::auth_service::auth_strategy::TokenFormat::Basic,
-                                                        },
-                                                        target_location: match token.target_location {
-                                                            crate::config::config::TokenTargetLocation::Header => crate::services::auth_service::auth_strategy::TokenTargetLocation::Header,
-                                                            crate::config::config::TokenTargetLocation::Query => crate::services::auth_service::auth_strategy::TokenTargetLocation::Query,
-                                                            crate::config::config::TokenTargetLocation::Cookie => crate::services::auth_service::auth_strategy::TokenTargetLocation::Header,
-                                                            crate::config::config::TokenTargetLocation::Body => crate::services::auth_service::auth_strategy::TokenTargetLocation::Body,
-                                                        },
-                                                        target_key: token.target_key,
-                                                    }
-                                                }).collect(),
-                                            }
-                                        } else {
-                                            crate::services::auth_service::auth_strategy::TokenExtraction::default()
-                                        },
-                                        refresh_url: lc.refresh_url,
-                                        refresh_method: lc.refresh_method.map(|m| {
-                                            match m {
-                                                crate::config::config::HttpMethod::Get => crate::services::auth_service::auth_strategy::HttpMethod::GET,
-                                                crate::config::config::HttpMethod::Post => crate::services::auth_service::auth_strategy::HttpMethod::POST,
-                                                crate::config::config::HttpMethod::Put => crate::services::auth_service::auth_strategy::HttpMethod::PUT,
-                                                crate::config::config::HttpMethod::Delete => crate::services::auth_service::auth_strategy::HttpMethod::DELETE,
-                                                crate::config::config::HttpMethod::Patch => crate::services::auth_service::auth_strategy::HttpMethod::PATCH,
-                                            }
-                                        }),
-                                    }
-                                }),
-                                token_expiry: cfg.auth.token_expiry,
-                                refresh_buffer: cfg.auth.refresh_buffer,
-                                max_retry_attempts: cfg.auth.max_retry_attempts,
-                            };
-
-                            match composer.auth_service().update_config(auth_cfg).await {
-                                Ok(()) => info!("Applied dynamic auth configuration update"),
-                                Err(e) => error!("Failed to update auth configuration dynamically: {:?}", e),
-                            }
-                        }
-                        Err(e) => {
-                            error!("Configuration change receiver error: {}", e);
-                            break;
-                        }
+                        Ok(ConfigChangeEvent::Auth { .. }) => {}
+                        Ok(_) => continue,
+                        // Events were dropped; one of them may have been an authentication change
+                        Err(broadcast::error::RecvError::Lagged(_)) => {}
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    }
+
+                    // Rebuild auth configuration from latest state
+                    let auth_cfg = auth_strategy_config(state.get_config().auth);
+                    match composer.auth_service().update_config(auth_cfg).await {
+                        Ok(()) => info!("Applied dynamic auth configuration update"),
+                        Err(e) => error!("Failed to update auth configuration dynamically: {:?}", e),
                     }
                 }
             });
         }
 
-        // Wait for either server to stop
-        tokio::select! {
-            result = axum::serve(tcp_listener, self._router.into_make_service())
-                .with_graceful_shutdown(async { tokio::signal::ctrl_c().await.unwrap() }) => {
-                if let Err(e) = result {
-                    error!("MCP-ANY-REST server error: {}", e);
-                }
+        if let Some(tls) = tls {
+            let handle = axum_server::Handle::new();
+            let shutdown = handle.clone();
+            tokio::spawn(async move {
+                let _ = tokio::signal::ctrl_c().await;
+                shutdown.graceful_shutdown(None);
+            });
+            let result = axum_server::from_tcp_rustls(tcp_listener.into_std()?, tls)
+                .handle(handle)
+                .serve(router.into_make_service_with_connect_info::<SocketAddr>())
+                .await;
+            if let Err(e) = result {
+                error!("MCP-ANY-REST server error: {}", e);
             }
+            return Ok(());
+        }
+
+        if let Err(e) = axum::serve(
+            tcp_listener,
+            router.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await
+        {
+            error!("MCP-ANY-REST server error: {}", e);
         }
 
         Ok(())
     }
 
     /// Index page handler
-    async fn index(State(_state): State<WebConfigState>) -> Html<String> {
+    async fn index(State(state): State<WebConfigState>, request: Request) -> Html<String> {
         // include to bin "config/config.html"
         let html_content = include_str!("config.html");
-  
-        Html(html_content.to_string())
+        // The page sends its requests below the path it is served from
+        let base_path = request
+            .extensions()
+            .get::<ForwardedRequest>()
+            .map(|forwarded| forwarded.base_path.clone())
+            .unwrap_or_default();
+        // Changes made from the page are checked against the version it was loaded with
+        Html(
+            html_content
+                .replace("\"{{BASE_PATH}}\"", &serde_json::to_string(&base_path).unwrap_or_default())
+                .replace("\"{{CONFIG_VERSION}}\"", &serde_json::to_string(&state.version()).unwrap_or_default()),
+        )
+    }
+
+    /// Redirect to the web UI under the base path, at the scheme and host the client used
+    async fn redirect_to_base(State(state): State<WebConfigState>, request: Request) -> Response {
+        let Some(forwarded) = request.extensions().get::<ForwardedRequest>() else {
+            return StatusCode::NOT_FOUND.into_response();
+        };
+        let location = match forwarded.base_url(get_scheme(&state.get_config())) {
+            Some(base_url) => format!("{}/", base_url),
+            None => format!("{}/", forwarded.base_path),
+        };
+        Redirect::temporary(&location).into_response()
     }
 
     /// Get current configuration
-    async fn get_config(State(state): State<WebConfigState>) -> Json<ConfigResponse> {
+    async fn get_config(
+        State(state): State<WebConfigState>,
+        Query(query): Query<RevealQuery>,
+        headers: HeaderMap,
+    ) -> Response {
         let config = state.get_config();
-        let module_config = config.module_config.clone();
+        let reveal = match Self::authorize_reveal(&config, &query, &headers) {
+            Ok(reveal) => reveal,
+            Err(response) => return response,
+        };
+
+        let mut config_value = serde_json::to_value(&config).unwrap_or_default();
+        let mut module_config_value = serde_json::to_value(&config.module_config).unwrap_or_default();
+        if !reveal {
+            redact_value(&mut config_value);
+            redact_value(&mut module_config_value);
+        }
 
         Json(ConfigResponse {
             success: true,
             message: "Configuration retrieved successfully".to_string(),
-            config: Some(serde_json::to_value(config).unwrap_or_default()),
-            module_config: Some(serde_json::to_value(module_config).unwrap_or_default()),
+            config: Some(config_value),
+            module_config: Some(module_config_value),
         })
+        .into_response()
     }
 
-    /// Update configuration
+    /// Whether credentials may be returned unmasked: `?reveal=true` with the admin token.
+    /// Fails with 403 when revealing is requested without it
+    fn authorize_reveal(config: &Config, query: &RevealQuery, headers: &HeaderMap) -> Result<bool, Response> {
+        if !query.reveal {
+            return Ok(false);
+        }
+        let bearer = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        match bearer {
+            Some(bearer) if admin_auth::matches(&config.server.admin_token, bearer) => Ok(true),
+            _ => Err((
+                StatusCode::FORBIDDEN,
+                Json(serde_json::json!({
+                    "success": false,
+                    "message": "Revealing credentials requires the admin token (server.admin_token)"
+                })),
+            )
+                .into_response()),
+        }
+    }
+
+    /// Put stored credentials back where an update carries the masked values from a read
+    fn restore_credentials(state: &WebConfigState, update_request: &mut ConfigUpdateRequest) {
+        let current = state.get_config();
+        if let Some(config) = &mut update_request.config {
+            let mut value = serde_json::to_value(&*config).unwrap_or_default();
+            restore_redacted(&mut value, &serde_json::to_value(&current).unwrap_or_default());
+            if let Ok(restored) = serde_json::from_value(value) {
+                *config = restored;
+            }
+        }
+        if let Some(module_config) = &mut update_request.module_config {
+            let mut value = serde_json::to_value(&*module_config).unwrap_or_default();
+            restore_redacted(&mut value, &serde_json::to_value(&current.module_config).unwrap_or_default());
+            if let Ok(restored) = serde_json::from_value(value) {
+                *module_config = restored;
+            }
+        }
+    }
+
+    /// Update configuration, or only validate it with `?dry_run=true`
     async fn update_config(
         State(state): State<WebConfigState>,
-        Json(update_request): Json<ConfigUpdateRequest>,
-    ) -> Json<ConfigResponse> {
+        Query(query): Query<ConfigWriteQuery>,
+        Json(mut update_request): Json<ConfigUpdateRequest>,
+    ) -> Response {
+        Self::restore_credentials(&state, &mut update_request);
+        if query.dry_run {
+            return Self::dry_run_config(&state, &update_request).into_response();
+        }
+        Self::apply_config_update(&state, update_request).into_response()
+    }
+
+    /// Report validation results of a configuration update without persisting it
+    fn dry_run_config(state: &WebConfigState, update_request: &ConfigUpdateRequest) -> Json<ValidationResponse> {
+        match state.validate_update(update_request) {
+            Some(result) => Json(ValidationResponse {
+                success: result.is_valid,
+                message: if result.is_valid {
+                    "Configuration is valid".to_string()
+                } else {
+                    format!("Configuration has {} validation errors", result.errors.len())
+                },
+                validation: ConfigValidator::new().get_validation_report(&result),
+            }),
+            None => Json(ValidationResponse {
+                success: false,
+                message: "No valid configuration data provided".to_string(),
+                validation: serde_json::Value::Null,
+            }),
+        }
+    }
+
+    /// Apply a configuration update
+    fn apply_config_update(state: &WebConfigState, update_request: ConfigUpdateRequest) -> Json<ConfigResponse> {
         match update_request {
             ConfigUpdateRequest {
                 config: Some(new_config),
@@ -667,14 +1283,17 @@ impl WebServer {
         }
     }
 
-    /// Get available preset configurations
+    /// Get available preset configurations, with the credentials of their module settings masked
     async fn get_presets(State(state): State<WebConfigState>) -> Json<PresetListResponse> {
         match state.get_preset_info() {
-            Ok(presets) => Json(PresetListResponse {
-                success: true,
-                message: "Presets loaded successfully".to_string(),
-                presets: presets,
-            }),
+            Ok(mut presets) => {
+                presets.iter_mut().for_each(redact_value);
+                Json(PresetListResponse {
+                    success: true,
+                    message: "Presets loaded successfully".to_string(),
+                    presets,
+                })
+            }
             Err(e) => Json(PresetListResponse {
                 success: false,
                 message: format!("Failed to load presets: {}", e),
@@ -710,42 +1329,691 @@ impl WebServer {
             });
         }
 
-        match state.apply_preset(preset_name.clone()) {
-            Ok(()) => {
-                // After applying preset, automatically reload configuration to ensure changes take effect
-                match state.reload_if_modified() {
-                    Ok(true) => Json(ConfigResponse {
-                        success: true,
-                        message: format!(
-                            "Configuration preset '{}' applied successfully and configuration reloaded",
-                            preset_name
-                        ),
-                        config: None,
-                        module_config: None,
-                    }),
-                    Ok(false) => Json(ConfigResponse {
-                        success: true,
-                        message: format!(
-                            "Configuration preset '{}' applied successfully (no reload needed)",
-                            preset_name
-                        ),
-                        config: None,
-                        module_config: None,
-                    }),
-                    Err(e) => Json(ConfigResponse {
-                        success: false,
-                        message: format!("Preset applied but failed to reload configuration: {}", e),
-                        config: None,
-                        module_config: None,
-                    }),
-                }
+        match state.apply_preset(preset_name.clone()) {
+            Ok(()) => {
+                // After applying preset, automatically reload configuration to ensure changes take effect
+                match state.reload_if_modified() {
+                    Ok(true) => Json(ConfigResponse {
+                        success: true,
+                        message: format!(
+                            "Configuration preset '{}' applied successfully and configuration reloaded",
+                            preset_name
+                        ),
+                        config: None,
+                        module_config: None,
+                    }),
+                    Ok(false) => Json(ConfigResponse {
+                        success: true,
+                        message: format!(
+                            "Configuration preset '{}' applied successfully (no reload needed)",
+                            preset_name
+                        ),
+                        config: None,
+                        module_config: None,
+                    }),
+                    Err(e) => Json(ConfigResponse {
+                        success: false,
+                        message: format!("Preset applied but failed to reload configuration: {}", e),
+                        config: None,
+                        module_config: None,
+                    }),
+                }
+            }
+            Err(e) => Json(ConfigResponse {
+                success: false,
+                message: format!("Failed to apply preset: {}", e),
+                config: None,
+                module_config: None,
+            }),
+        }
+    }
+
+    /// Record the caller (`X-Config-Actor` header) as the author of configuration changes
+    async fn track_actor(request: Request, next: Next) -> Response {
+        let actor = request
+            .headers()
+            .get(ACTOR_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .filter(|value| !value.is_empty())
+            .unwrap_or("web")
+            .to_string();
+        with_actor(actor, next.run(request)).await
+    }
+
+    /// Authenticate `/config/*` requests when web API users are configured: 401 without valid
+    /// credentials, 403 for viewers changing configuration. The user is recorded as the author of
+    /// the changes instead of `X-Config-Actor`.
+    async fn require_admin_auth(
+        State(state): State<WebConfigState>,
+        request: Request,
+        next: Next,
+    ) -> Response {
+        let config = state.get_config();
+        if !request.uri().path().starts_with("/config") || !admin_auth::is_enabled(&config.server) {
+            return next.run(request).await;
+        }
+        let identity = match admin_auth::authenticate(&config.server, request.headers()) {
+            Ok(identity) => identity,
+            Err(message) => {
+                return (
+                    StatusCode::UNAUTHORIZED,
+                    [(header::WWW_AUTHENTICATE, "Basic realm=\"mcp-any-rest\"")],
+                    Json(serde_json::json!({ "success": false, "message": message })),
+                )
+                    .into_response();
+            }
+        };
+        if identity.role == AdminRole::Viewer && requires_editor(&request) {
+            return (
+                StatusCode::FORBIDDEN,
+                Json(serde_json::json!({
+                    "success": false,
+                    "message": format!("User '{}' is a viewer; changing configuration requires the editor role", identity.name)
+                })),
+            )
+                .into_response();
+        }
+        with_actor(identity.name, next.run(request)).await
+    }
+
+    /// Record the client and public path of a request, as reported by a trusted reverse proxy
+    async fn resolve_forwarded(
+        State((state, base_path)): State<(WebConfigState, String)>,
+        mut request: Request,
+        next: Next,
+    ) -> Response {
+        let peer = request
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(address)| address.ip());
+        let forwarded = reverse_proxy::forwarded_request(
+            &state.get_config().server.trusted_proxies,
+            peer,
+            request.headers(),
+            &base_path,
+        );
+        request.extensions_mut().insert(forwarded);
+        next.run(request).await
+    }
+
+    /// Answer CORS preflight requests and add CORS headers for the origins of `server.cors`.
+    /// Other origins get no CORS headers, so browsers keep their pages from reading the answers.
+    async fn apply_cors(State(state): State<WebConfigState>, request: Request, next: Next) -> Response {
+        let config = state.get_config();
+        let origin = request.headers().get(header::ORIGIN).cloned();
+        let (Some(cors), Some(origin)) = (&config.server.cors, origin) else {
+            return next.run(request).await;
+        };
+        if !origin.to_str().is_ok_and(|origin| reverse_proxy::is_origin_allowed(cors, origin)) {
+            return next.run(request).await;
+        }
+        if request.method() == Method::OPTIONS && request.headers().contains_key(header::ACCESS_CONTROL_REQUEST_METHOD) {
+            return (StatusCode::NO_CONTENT, reverse_proxy::cors_headers(cors, &origin, true)).into_response();
+        }
+        let mut response = next.run(request).await;
+        response.headers_mut().extend(reverse_proxy::cors_headers(cors, &origin, false));
+        response
+    }
+
+    /// Authenticate MCP HTTP requests when `mcp_auth` is configured: 401 without an accepted
+    /// bearer token. The grant of the token goes with the request to the service registry, which
+    /// limits the modules and tools of the client to it.
+    async fn require_mcp_auth(
+        State(state): State<WebConfigState>,
+        mut request: Request,
+        next: Next,
+    ) -> Response {
+        let config = state.get_config();
+        let Some(mcp_auth) = &config.mcp_auth else {
+            return next.run(request).await;
+        };
+        match mcp_auth::authenticate(mcp_auth, &config, request.headers()).await {
+            Ok(grant) => {
+                debug!("MCP request authenticated as '{}'", grant.name);
+                request.extensions_mut().insert(grant);
+                next.run(request).await
+            }
+            Err(message) => {
+                let client = request.extensions().get::<ForwardedRequest>().and_then(|forwarded| forwarded.client);
+                match client {
+                    Some(client) => warn!("Rejected MCP request from {}: {}", client, message),
+                    None => warn!("Rejected MCP request: {}", message),
+                }
+                (
+                    StatusCode::UNAUTHORIZED,
+                    [(header::WWW_AUTHENTICATE, "Bearer")],
+                    Json(serde_json::json!({ "success": false, "message": message })),
+                )
+                    .into_response()
+            }
+        }
+    }
+
+    /// Run a `batch_call` for a REST client; the body holds the tool's arguments and the response
+    /// lists the outcome of each call
+    async fn batch_call(composer: ServiceComposer, parts: axum::http::request::Parts, arguments: JsonObject) -> Response {
+        let outcome = composer.call_batch(arguments, parts).await.and_then(|result| {
+            let text = result
+                .content
+                .first()
+                .and_then(|content| content.as_text())
+                .map(|content| content.text.clone())
+                .unwrap_or_default();
+            serde_json::from_str::<serde_json::Value>(&text)
+                .map_err(|e| rmcp::ErrorData::internal_error(format!("Invalid batch result: {}", e), None))
+        });
+        match outcome {
+            Ok(results) => Json(results).into_response(),
+            Err(error) => {
+                let status = if error.code == rmcp::model::ErrorCode::INVALID_PARAMS {
+                    StatusCode::BAD_REQUEST
+                } else {
+                    StatusCode::INTERNAL_SERVER_ERROR
+                };
+                (status, Json(serde_json::json!({ "success": false, "message": error.message }))).into_response()
+            }
+        }
+    }
+
+    /// Reject requests that change configuration with 403 while the configuration is read-only.
+    /// Reads, reloads from disk and dry runs stay available.
+    async fn reject_when_read_only(
+        State(state): State<WebConfigState>,
+        request: Request,
+        next: Next,
+    ) -> Response {
+        if changes_config(&request) && state.is_read_only() {
+            return (
+                StatusCode::FORBIDDEN,
+                Json(serde_json::json!({
+                    "success": false,
+                    "message": "Configuration is read-only"
+                })),
+            )
+                .into_response();
+        }
+        next.run(request).await
+    }
+
+    /// Optimistic concurrency control: changes must send the version they were based on in
+    /// `If-Match` (`*` to overwrite unconditionally) and are rejected with 409 and the current
+    /// version when the configuration changed in the meantime. Changes are applied one at a time,
+    /// so two changes based on the same version cannot both pass. Responses carry the current
+    /// version in `ETag`.
+    async fn check_config_version(
+        State(state): State<WebConfigState>,
+        request: Request,
+        next: Next,
+    ) -> Response {
+        // Held from the check until the change is written
+        let _write = if changes_config(&request) {
+            let write = CONFIG_WRITES.lock().await;
+            let current = state.version();
+            let if_match = request
+                .headers()
+                .get(header::IF_MATCH)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string);
+            let rejection = match if_match {
+                None => Some((
+                    StatusCode::PRECONDITION_REQUIRED,
+                    "Configuration changes require an If-Match header with the version from GET /config",
+                )),
+                Some(if_match)
+                    if !if_match.split(',').any(|tag| {
+                        let tag = tag.trim().trim_start_matches("W/").trim_matches('"');
+                        tag == "*" || tag == current
+                    }) =>
+                {
+                    Some((
+                        StatusCode::CONFLICT,
+                        "Configuration was changed since it was loaded; reload it and retry",
+                    ))
+                }
+                Some(_) => None,
+            };
+            if let Some((status, message)) = rejection {
+                let mut response = (
+                    status,
+                    Json(serde_json::json!({
+                        "success": false,
+                        "message": message,
+                        "version": current
+                    })),
+                )
+                    .into_response();
+                Self::set_etag(&mut response, &current);
+                return response;
+            }
+            Some(write)
+        } else {
+            None
+        };
+
+        let tagged = request.uri().path().starts_with("/config");
+        let mut response = next.run(request).await;
+        if tagged {
+            Self::set_etag(&mut response, &state.version());
+        }
+        response
+    }
+
+    fn set_etag(response: &mut Response, version: &str) {
+        if let Ok(value) = HeaderValue::from_str(&format!("\"{}\"", version)) {
+            response.headers_mut().insert(header::ETAG, value);
+        }
+    }
+
+    /// Get configuration change history
+    async fn get_history(State(state): State<WebConfigState>) -> Json<HistoryResponse> {
+        match state.get_history() {
+            Ok(entries) => Json(HistoryResponse {
+                success: true,
+                message: format!("{} revisions in history", entries.len()),
+                history: entries
+                    .into_iter()
+                    .map(|entry| serde_json::to_value(entry).unwrap_or_default())
+                    .collect(),
+            }),
+            Err(e) => Json(HistoryResponse {
+                success: false,
+                message: format!("Failed to load configuration history: {}", e),
+                history: Vec::new(),
+            }),
+        }
+    }
+
+    /// List configuration file backups
+    async fn get_backups(State(state): State<WebConfigState>) -> Json<BackupsResponse> {
+        match state.list_backups() {
+            Ok(backups) => Json(BackupsResponse {
+                success: true,
+                message: format!("{} backups", backups.len()),
+                backups,
+            }),
+            Err(e) => Json(BackupsResponse {
+                success: false,
+                message: format!("Failed to list configuration backups: {}", e),
+                backups: Vec::new(),
+            }),
+        }
+    }
+
+    /// Restore a configuration file from a backup
+    async fn restore_backup(
+        State(state): State<WebConfigState>,
+        Json(request): Json<RestoreBackupRequest>,
+    ) -> Json<ConfigResponse> {
+        match state.restore_backup(&request.name) {
+            Ok(()) => Json(ConfigResponse {
+                success: true,
+                message: format!("Restored backup {}", request.name),
+                config: None,
+                module_config: None,
+            }),
+            Err(e) => Json(ConfigResponse {
+                success: false,
+                message: format!("Failed to restore backup: {}", e),
+                config: None,
+                module_config: None,
+            }),
+        }
+    }
+
+    /// Roll back configuration to a history revision
+    async fn rollback(
+        State(state): State<WebConfigState>,
+        Path(revision): Path<u64>,
+    ) -> Json<ConfigResponse> {
+        match state.rollback(revision) {
+            Ok(()) => Json(ConfigResponse {
+                success: true,
+                message: format!("Configuration rolled back to revision {}", revision),
+                config: None,
+                module_config: None,
+            }),
+            Err(e) => Json(ConfigResponse {
+                success: false,
+                message: format!("Failed to roll back configuration: {}", e),
+                config: None,
+                module_config: None,
+            }),
+        }
+    }
+
+    /// Get the effective module and method settings after cascading the defaults, with
+    /// credentials in the cascaded headers masked
+    async fn get_effective_config(State(state): State<WebConfigState>) -> Json<serde_json::Value> {
+        let modules = state.get_effective_config();
+        let mut modules_value = serde_json::to_value(&modules).unwrap_or_default();
+        redact_value(&mut modules_value);
+        Json(serde_json::json!({
+            "success": true,
+            "message": format!("Effective settings of {} modules", modules.len()),
+            "modules": modules_value
+        }))
+    }
+
+    /// Diagnose why the configuration might not work (e.g. no tools show up)
+    async fn get_doctor(State(state): State<WebConfigState>) -> Json<serde_json::Value> {
+        let report = state.diagnose().await;
+        Json(serde_json::json!({
+            "success": true,
+            "message": format!("{} findings", report.findings.len()),
+            "healthy": report.healthy,
+            "findings": report.findings
+        }))
+    }
+
+    /// Run the authentication flow against the live API without saving anything and report the
+    /// token shape, its expiry and what API requests would carry
+    async fn test_auth(
+        State(state): State<WebConfigState>,
+        request: Option<Json<AuthTestRequest>>,
+    ) -> Json<serde_json::Value> {
+        let config = state.get_config();
+        let auth = match auth_under_test(request.and_then(|Json(request)| request.auth), &config.auth) {
+            Ok(auth) => auth,
+            Err(message) => return Json(serde_json::json!({ "success": false, "message": message })),
+        };
+
+        let base_url = render(&config.api.base_url, &config.variables).unwrap_or_else(|_| config.api.base_url.clone());
+        let auth_service = backend_client_builder(&config.tls, config.api.timeout, &base_url)
+            .and_then(|builder| configure_proxy(builder, config.proxy.as_ref()))
+            .map(|builder| configure_compression(builder, &config.api.compression))
+            .map_err(|e| format!("Failed to build HTTP client: {:#}", e))
+            .and_then(|builder| {
+                UnifiedAuthService::with_client_builder(auth_strategy_config(auth), builder)
+                    .map_err(|e| format!("Failed to create auth service: {}", e))
+            });
+        let auth_service = match auth_service {
+            Ok(auth_service) => auth_service,
+            Err(message) => return Json(serde_json::json!({ "success": false, "message": message })),
+        };
+
+        match auth_service.check().await {
+            Ok(report) => Json(serde_json::json!({
+                "success": true,
+                "message": format!("Authenticated with {} auth in {} ms", report.mode, report.elapsed_ms),
+                "report": report
+            })),
+            Err(e) => Json(serde_json::json!({
+                "success": false,
+                "message": format!("Authentication failed: {}", e)
+            })),
+        }
+    }
+
+    /// Recorded logins, refreshes, failures and token invalidations, newest first
+    async fn get_auth_audit() -> Json<serde_json::Value> {
+        let events = audit::events();
+        Json(serde_json::json!({
+            "success": true,
+            "message": format!("{} authentication events", events.len()),
+            "events": events
+        }))
+    }
+
+    /// Response fields of each method that no longer match their ZML declaration
+    async fn get_drift() -> Json<serde_json::Value> {
+        let report = drift_report();
+        let methods: usize = report.values().map(|methods| methods.len()).sum();
+        Json(serde_json::json!({
+            "success": true,
+            "message": format!("{} methods with drifted responses", methods),
+            "drift": report
+        }))
+    }
+
+    /// Receive a callback of the API as a `webhook://<module>/<event>` resource and notify the MCP clients
+    async fn receive_webhook(
+        State(state): State<WebConfigState>,
+        Path((module, event)): Path<(String, String)>,
+        headers: HeaderMap,
+        body: Bytes,
+    ) -> (StatusCode, Json<serde_json::Value>) {
+        let config = state.get_config();
+        let Some(webhooks) = &config.webhooks else {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({
+                    "success": false,
+                    "message": "Webhooks are not enabled (webhooks)"
+                })),
+            );
+        };
+
+        if let Some(secret) = &webhooks.secret {
+            let secret = match crate::config::secrets::resolve_secret_ref(secret) {
+                Ok(secret) => secret,
+                Err(e) => {
+                    error!("Failed to resolve the webhook secret: {}", e);
+                    return (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(serde_json::json!({
+                            "success": false,
+                            "message": "Webhook secret is not available"
+                        })),
+                    );
+                }
+            };
+            let signature = headers
+                .get(webhooks.signature_header.as_str())
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default();
+            if !verify_signature(&secret, &body, signature) {
+                warn!("Rejected '{}' webhook of module '{}': invalid signature", event, module);
+                return (
+                    StatusCode::UNAUTHORIZED,
+                    Json(serde_json::json!({
+                        "success": false,
+                        "message": format!("Invalid or missing {} signature", webhooks.signature_header)
+                    })),
+                );
+            }
+        }
+
+        let payload = serde_json::from_slice(&body)
+            .unwrap_or_else(|_| serde_json::Value::String(String::from_utf8_lossy(&body).into_owned()));
+        let received = record_event(&module, &event, payload, webhooks.max_events);
+        info!("Received '{}' webhook of module '{}'", event, module);
+        notify_resources_changed().await;
+
+        (
+            StatusCode::ACCEPTED,
+            Json(serde_json::json!({
+                "success": true,
+                "message": format!("Event '{}' of module '{}' received", event, module),
+                "received_at": received.received_at
+            })),
+        )
+    }
+
+    /// Preview a preset: modules and methods it would enable or disable and the settings it would change
+    async fn get_preset_diff(
+        State(state): State<WebConfigState>,
+        Path(preset_id): Path<String>,
+    ) -> Json<serde_json::Value> {
+        match state.preview_preset(&preset_id) {
+            Ok(preview) => Json(serde_json::json!({
+                "success": true,
+                "message": format!(
+                    "Preset '{}' would enable {} and disable {} methods and change {} settings",
+                    preset_id,
+                    preview.methods_enabled.len(),
+                    preview.methods_disabled.len(),
+                    preview.settings.len()
+                ),
+                "diff": redacted_preview(&preview)
+            })),
+            Err(e) => Json(serde_json::json!({
+                "success": false,
+                "message": format!("Failed to preview preset '{}': {}", preset_id, e)
+            })),
+        }
+    }
+
+    /// Module dependency graph: `depends_on`, unmet dependencies and the activation order
+    async fn get_dependencies(State(state): State<WebConfigState>) -> Json<serde_json::Value> {
+        let graph = state.dependency_graph();
+        Json(serde_json::json!({
+            "success": graph.cycle.is_empty(),
+            "message": if graph.cycle.is_empty() {
+                format!("{} modules active", graph.activation_order.len())
+            } else {
+                format!("Dependency cycle between modules: {}", graph.cycle.join(" -> "))
+            },
+            "graph": graph
+        }))
+    }
+
+    /// List per-client overrides
+    async fn get_clients(State(state): State<WebConfigState>) -> Json<serde_json::Value> {
+        match state.get_clients() {
+            Ok(clients) => Json(serde_json::json!({
+                "success": true,
+                "message": format!("{} client overrides", clients.len()),
+                "clients": clients
+            })),
+            Err(e) => Json(serde_json::json!({
+                "success": false,
+                "message": format!("Failed to load client overrides: {}", e)
+            })),
+        }
+    }
+
+    /// Get the override of a client together with the modules it enables
+    async fn get_client(
+        Path(client): Path<String>,
+        State(state): State<WebConfigState>,
+    ) -> Json<serde_json::Value> {
+        let client_override = match state.get_clients() {
+            Ok(mut clients) => clients.remove(&client),
+            Err(e) => {
+                return Json(serde_json::json!({
+                    "success": false,
+                    "message": format!("Failed to load client overrides: {}", e)
+                }))
+            }
+        };
+
+        match client_override {
+            Some(client_override) => {
+                let effective = client_override.apply(&state.get_config());
+                match effective {
+                    Ok(config) => {
+                        let mut enabled_modules: Vec<&String> = config
+                            .module_config
+                            .modules
+                            .iter()
+                            .filter(|(_, module)| module.enabled)
+                            .map(|(name, _)| name)
+                            .collect();
+                        enabled_modules.sort();
+                        Json(serde_json::json!({
+                            "success": true,
+                            "message": format!("Override for client '{}'", client),
+                            "client": client_override,
+                            "enabled_modules": enabled_modules
+                        }))
+                    }
+                    Err(e) => Json(serde_json::json!({
+                        "success": false,
+                        "message": format!("Override for client '{}' is invalid: {:#}", client, e),
+                        "client": client_override
+                    })),
+                }
+            }
+            None => Json(serde_json::json!({
+                "success": false,
+                "message": format!("No override for client '{}'", client)
+            })),
+        }
+    }
+
+    /// Create or replace the override of a client
+    async fn set_client(
+        Path(client): Path<String>,
+        State(state): State<WebConfigState>,
+        Json(client_override): Json<ClientOverride>,
+    ) -> Json<serde_json::Value> {
+        match state.set_client(&client, client_override) {
+            Ok(()) => Json(serde_json::json!({
+                "success": true,
+                "message": format!("Override for client '{}' saved", client)
+            })),
+            Err(e) => Json(serde_json::json!({
+                "success": false,
+                "message": format!("Failed to save client override: {:#}", e)
+            })),
+        }
+    }
+
+    /// Remove the override of a client
+    async fn delete_client(
+        Path(client): Path<String>,
+        State(state): State<WebConfigState>,
+    ) -> Json<serde_json::Value> {
+        match state.remove_client(&client) {
+            Ok(true) => Json(serde_json::json!({
+                "success": true,
+                "message": format!("Override for client '{}' removed", client)
+            })),
+            Ok(false) => Json(serde_json::json!({
+                "success": false,
+                "message": format!("No override for client '{}'", client)
+            })),
+            Err(e) => Json(serde_json::json!({
+                "success": false,
+                "message": format!("Failed to remove client override: {}", e)
+            })),
+        }
+    }
+
+    /// Stream configuration changes to the web UI over a WebSocket: one JSON text message per
+    /// `ConfigChangeEvent`, with credentials masked. `{"type": "lagged"}` reports dropped events.
+    async fn config_events(
+        State(state): State<WebConfigState>,
+        forwarded: Option<axum::Extension<ForwardedRequest>>,
+        headers: HeaderMap,
+        ws: WebSocketUpgrade,
+    ) -> Response {
+        let config = state.get_config();
+        let host = match &forwarded {
+            Some(axum::Extension(forwarded)) => forwarded.host.clone(),
+            None => headers.get(header::HOST).and_then(|value| value.to_str().ok()).map(str::to_string),
+        };
+        if !reverse_proxy::is_websocket_origin_allowed(config.server.cors.as_ref(), &headers, host.as_deref()) {
+            return (
+                StatusCode::FORBIDDEN,
+                Json(serde_json::json!({ "success": false, "message": "Origin not allowed" })),
+            )
+                .into_response();
+        }
+        let receiver = state.subscribe();
+        ws.on_upgrade(move |socket| Self::send_config_events(socket, receiver))
+    }
+
+    /// Forward change events to a WebSocket until either side closes
+    async fn send_config_events(mut socket: WebSocket, mut receiver: broadcast::Receiver<ConfigChangeEvent>) {
+        loop {
+            let message = tokio::select! {
+                event = receiver.recv() => match event {
+                    Ok(event) => config_event_message(&event),
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        serde_json::json!({ "type": "lagged", "skipped": skipped })
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                incoming = socket.recv() => match incoming {
+                    // Pings are answered by axum; nothing else is expected from the browser
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => continue,
+                },
+            };
+            if socket.send(Message::Text(message.to_string())).await.is_err() {
+                break;
             }
-            Err(e) => Json(ConfigResponse {
-                success: false,
-                message: format!("Failed to apply preset: {}", e),
-                config: None,
-                module_config: None,
-            }),
         }
     }
 
@@ -774,16 +2042,18 @@ impl WebServer {
     }
 
     /// Get server status
-    async fn get_status(State(state): State<WebConfigState>) -> Json<HashMap<String, String>> {
+    async fn get_status(State(state): State<WebConfigState>) -> Json<HashMap<String, serde_json::Value>> {
         let mut status = HashMap::new();
-        status.insert("status".to_string(), "running".to_string());
+        status.insert("status".to_string(), "running".into());
 
         let (config_path, module_config_path, _preset_config_path) = state.get_config_paths();
-        status.insert("config_path".to_string(), config_path.display().to_string());
+        status.insert("config_path".to_string(), config_path.display().to_string().into());
         status.insert(
             "module_config_path".to_string(),
-            module_config_path.display().to_string(),
+            module_config_path.display().to_string().into(),
         );
+        status.insert("read_only".to_string(), state.is_read_only().to_string().into());
+        status.insert("rate_limits".to_string(), serde_json::json!(rate_limiter::stats()));
 
         Json(status)
     }
@@ -1201,36 +2471,66 @@ impl WebServer {
     }
 
     /// Get server-specific configuration
-    async fn get_server_config(State(state): State<WebConfigState>) -> Json<serde_json::Value> {
+    async fn get_server_config(
+        State(state): State<WebConfigState>,
+        Query(query): Query<RevealQuery>,
+        headers: HeaderMap,
+    ) -> Response {
         let config = state.get_config();
+        let reveal = match Self::authorize_reveal(&config, &query, &headers) {
+            Ok(reveal) => reveal,
+            Err(response) => return response,
+        };
+
+        let mut server_config = Self::server_config_view(&config);
+        if !reveal {
+            redact_value(&mut server_config);
+        }
+
+        Json(serde_json::json!({
+            "success": true,
+            "message": "Server configuration retrieved successfully",
+            "config": server_config
+        }))
+        .into_response()
+    }
 
-        // Extract server-specific settings
-        let server_config = serde_json::json!({
+    /// Server-specific settings as returned by `GET /config/server`
+    fn server_config_view(config: &Config) -> serde_json::Value {
+        serde_json::json!({
             "base_url": config.api.base_url,
             "server_port": config.server.port,
             "log_level": config.server.log_level,
             "auth_mode": config.auth.mode,
             "direct_config": config.auth.direct_config,
             "login_config": config.auth.login_config,
+            "jwt_config": config.auth.jwt_config,
+            "aws_sigv4_config": config.auth.aws_sigv4_config,
+            "hmac_config": config.auth.hmac_config,
+            "cookie_config": config.auth.cookie_config,
+            "custom_config": config.auth.custom_config,
             "token_refresh_buffer": config.auth.refresh_buffer,
             "token_expiry_time": config.auth.token_expiry,
             "max_retry_attempts": config.auth.max_retry_attempts,
-        });
-
-        Json(serde_json::json!({
-            "success": true,
-            "message": "Server configuration retrieved successfully",
-            "config": server_config
-        }))
+            "reauth_markers": config.auth.reauth_markers,
+        })
     }
 
     /// Update server-specific configuration
     async fn update_server_config(
         State(state): State<WebConfigState>,
-        Json(server_config): Json<serde_json::Value>,
+        Json(mut server_config): Json<serde_json::Value>,
     ) -> Json<serde_json::Value> {
         // Log incoming update request
         info!("Received server configuration update request");
+        // Keep stored credentials where the update carries their masked form, in either the full
+        // configuration layout or the layout of GET /config/server
+        let current = state.get_config();
+        restore_redacted(&mut server_config, &serde_json::to_value(&current).unwrap_or_default());
+        restore_redacted(&mut server_config, &Self::server_config_view(&current));
+        if let Some(auth) = server_config.get_mut("auth") {
+            restore_redacted(auth, &Self::server_config_view(&current));
+        }
         // Try to parse the entire configuration object first
         match serde_json::from_value::<crate::config::config::Config>(server_config.clone()) {
             Ok(new_config) => {
@@ -1270,6 +2570,11 @@ impl WebServer {
                                 let normalized = match mode.to_lowercase().as_str() {
                                     "direct" => crate::config::config::AuthMode::Direct,
                                     "login" => crate::config::config::AuthMode::Login,
+                                    "jwt" => crate::config::config::AuthMode::Jwt,
+                                    "aws_sigv4" => crate::config::config::AuthMode::AwsSigV4,
+                                    "hmac" => crate::config::config::AuthMode::Hmac,
+                                    "cookie" => crate::config::config::AuthMode::Cookie,
+                                    "custom" => crate::config::config::AuthMode::Custom,
                                     _ => return Json(serde_json::json!({
                                         "success": false,
                                         "message": format!("Invalid auth mode: {}", mode)
@@ -1362,6 +2667,111 @@ impl WebServer {
                                 }
                             }
                             
+                            // Update JWT auth config (allow null to clear)
+                            if let Some(jwt_config_val) = auth_obj.get("jwt_config") {
+                                if jwt_config_val.is_null() {
+                                    config.auth.jwt_config = None;
+                                    info!("Cleared JWT auth configuration");
+                                } else {
+                                    match serde_json::from_value::<crate::config::config::JwtAuthConfig>(jwt_config_val.clone()) {
+                                        Ok(jwt_auth) => {
+                                            config.auth.jwt_config = Some(jwt_auth);
+                                            info!("Updated JWT auth configuration");
+                                        }
+                                        Err(e) => {
+                                            return Json(serde_json::json!({
+                                                "success": false,
+                                                "message": format!("Invalid JWT auth configuration: {}", e)
+                                            }));
+                                        }
+                                    }
+                                }
+                            }
+                            
+                            // Update AWS SigV4 config (allow null to clear)
+                            if let Some(sigv4_config_val) = auth_obj.get("aws_sigv4_config") {
+                                if sigv4_config_val.is_null() {
+                                    config.auth.aws_sigv4_config = None;
+                                    info!("Cleared AWS SigV4 configuration");
+                                } else {
+                                    match serde_json::from_value::<crate::config::config::AwsSigV4Config>(sigv4_config_val.clone()) {
+                                        Ok(sigv4) => {
+                                            config.auth.aws_sigv4_config = Some(sigv4);
+                                            info!("Updated AWS SigV4 configuration");
+                                        }
+                                        Err(e) => {
+                                            return Json(serde_json::json!({
+                                                "success": false,
+                                                "message": format!("Invalid AWS SigV4 configuration: {}", e)
+                                            }));
+                                        }
+                                    }
+                                }
+                            }
+                            
+                            // Update HMAC signing config (allow null to clear)
+                            if let Some(hmac_config_val) = auth_obj.get("hmac_config") {
+                                if hmac_config_val.is_null() {
+                                    config.auth.hmac_config = None;
+                                    info!("Cleared HMAC signing configuration");
+                                } else {
+                                    match serde_json::from_value::<crate::config::config::HmacAuthConfig>(hmac_config_val.clone()) {
+                                        Ok(hmac) => {
+                                            config.auth.hmac_config = Some(hmac);
+                                            info!("Updated HMAC signing configuration");
+                                        }
+                                        Err(e) => {
+                                            return Json(serde_json::json!({
+                                                "success": false,
+                                                "message": format!("Invalid HMAC signing configuration: {}", e)
+                                            }));
+                                        }
+                                    }
+                                }
+                            }
+                            
+                            // Update cookie session config (allow null to clear)
+                            if let Some(cookie_config_val) = auth_obj.get("cookie_config") {
+                                if cookie_config_val.is_null() {
+                                    config.auth.cookie_config = None;
+                                    info!("Cleared cookie session configuration");
+                                } else {
+                                    match serde_json::from_value::<crate::config::config::CookieSessionConfig>(cookie_config_val.clone()) {
+                                        Ok(cookie) => {
+                                            config.auth.cookie_config = Some(cookie);
+                                            info!("Updated cookie session configuration");
+                                        }
+                                        Err(e) => {
+                                            return Json(serde_json::json!({
+                                                "success": false,
+                                                "message": format!("Invalid cookie session configuration: {}", e)
+                                            }));
+                                        }
+                                    }
+                                }
+                            }
+                            
+                            // Update custom strategy config (allow null to clear)
+                            if let Some(custom_config_val) = auth_obj.get("custom_config") {
+                                if custom_config_val.is_null() {
+                                    config.auth.custom_config = None;
+                                    info!("Cleared custom auth configuration");
+                                } else {
+                                    match serde_json::from_value::<crate::config::config::CustomAuthConfig>(custom_config_val.clone()) {
+                                        Ok(custom) => {
+                                            config.auth.custom_config = Some(custom);
+                                            info!("Updated custom auth configuration");
+                                        }
+                                        Err(e) => {
+                                            return Json(serde_json::json!({
+                                                "success": false,
+                                                "message": format!("Invalid custom auth configuration: {}", e)
+                                            }));
+                                        }
+                                    }
+                                }
+                            }
+                            
                             // Update auth token settings (support legacy names)
                             if let Some(token_expiry) = auth_obj
                                 .get("token_expiry")
@@ -1386,6 +2796,13 @@ impl WebServer {
                                 info!("Updated max_retry_attempts: {}", max_retry);
                             }
                             
+                            if let Some(markers) = auth_obj.get("reauth_markers").and_then(|v| v.as_array()) {
+                                config.auth.reauth_markers = markers.iter()
+                                    .filter_map(|v| v.as_str().map(str::to_string))
+                                    .collect();
+                                info!("Updated reauth_markers: {:?}", config.auth.reauth_markers);
+                            }
+                            
                             has_changes = true;
                         }
                     }
@@ -1425,6 +2842,11 @@ impl WebServer {
                         let normalized = match mode.to_lowercase().as_str() {
                             "direct" => crate::config::config::AuthMode::Direct,
                             "login" => crate::config::config::AuthMode::Login,
+                            "jwt" => crate::config::config::AuthMode::Jwt,
+                            "aws_sigv4" => crate::config::config::AuthMode::AwsSigV4,
+                            "hmac" => crate::config::config::AuthMode::Hmac,
+                            "cookie" => crate::config::config::AuthMode::Cookie,
+                            "custom" => crate::config::config::AuthMode::Custom,
                             _ => {
                                 return Json(serde_json::json!({
                                     "success": false,
@@ -1639,6 +3061,11 @@ impl WebServer {
                         let normalized = match mode.to_lowercase().as_str() {
                             "direct" => crate::config::config::AuthMode::Direct,
                             "login" => crate::config::config::AuthMode::Login,
+                            "jwt" => crate::config::config::AuthMode::Jwt,
+                            "aws_sigv4" => crate::config::config::AuthMode::AwsSigV4,
+                            "hmac" => crate::config::config::AuthMode::Hmac,
+                            "cookie" => crate::config::config::AuthMode::Cookie,
+                            "custom" => crate::config::config::AuthMode::Custom,
                             _ => {
                                 return Json(serde_json::json!({
                                     "success": false,
@@ -1724,6 +3151,126 @@ impl WebServer {
                         }
                     }
 
+                    // jwt_config (nullable to clear)
+                    if sc_obj.contains_key("jwt_config") {
+                        match sc_obj.get("jwt_config") {
+                            Some(v) if !v.is_null() => {
+                                match serde_json::from_value::<crate::config::config::JwtAuthConfig>(v.clone()) {
+                                    Ok(jc) => {
+                                        config.auth.jwt_config = Some(jc);
+                                        has_changes = true;
+                                    }
+                                    Err(e) => {
+                                        return Json(serde_json::json!({
+                                            "success": false,
+                                            "message": format!("Invalid JWT auth configuration: {}", e)
+                                        }))
+                                    }
+                                }
+                            }
+                            _ => {
+                                config.auth.jwt_config = None;
+                                has_changes = true;
+                            }
+                        }
+                    }
+
+                    // aws_sigv4_config (nullable to clear)
+                    if sc_obj.contains_key("aws_sigv4_config") {
+                        match sc_obj.get("aws_sigv4_config") {
+                            Some(v) if !v.is_null() => {
+                                match serde_json::from_value::<crate::config::config::AwsSigV4Config>(v.clone()) {
+                                    Ok(sc) => {
+                                        config.auth.aws_sigv4_config = Some(sc);
+                                        has_changes = true;
+                                    }
+                                    Err(e) => {
+                                        return Json(serde_json::json!({
+                                            "success": false,
+                                            "message": format!("Invalid AWS SigV4 configuration: {}", e)
+                                        }))
+                                    }
+                                }
+                            }
+                            _ => {
+                                config.auth.aws_sigv4_config = None;
+                                has_changes = true;
+                            }
+                        }
+                    }
+
+                    // hmac_config (nullable to clear)
+                    if sc_obj.contains_key("hmac_config") {
+                        match sc_obj.get("hmac_config") {
+                            Some(v) if !v.is_null() => {
+                                match serde_json::from_value::<crate::config::config::HmacAuthConfig>(v.clone()) {
+                                    Ok(hc) => {
+                                        config.auth.hmac_config = Some(hc);
+                                        has_changes = true;
+                                    }
+                                    Err(e) => {
+                                        return Json(serde_json::json!({
+                                            "success": false,
+                                            "message": format!("Invalid HMAC signing configuration: {}", e)
+                                        }))
+                                    }
+                                }
+                            }
+                            _ => {
+                                config.auth.hmac_config = None;
+                                has_changes = true;
+                            }
+                        }
+                    }
+
+                    // cookie_config (nullable to clear)
+                    if sc_obj.contains_key("cookie_config") {
+                        match sc_obj.get("cookie_config") {
+                            Some(v) if !v.is_null() => {
+                                match serde_json::from_value::<crate::config::config::CookieSessionConfig>(v.clone()) {
+                                    Ok(cc) => {
+                                        config.auth.cookie_config = Some(cc);
+                                        has_changes = true;
+                                    }
+                                    Err(e) => {
+                                        return Json(serde_json::json!({
+                                            "success": false,
+                                            "message": format!("Invalid cookie session configuration: {}", e)
+                                        }))
+                                    }
+                                }
+                            }
+                            _ => {
+                                config.auth.cookie_config = None;
+                                has_changes = true;
+                            }
+                        }
+                    }
+
+                    // custom_config (nullable to clear)
+                    if sc_obj.contains_key("custom_config") {
+                        match sc_obj.get("custom_config") {
+                            Some(v) if !v.is_null() => {
+                                match serde_json::from_value::<crate::config::config::CustomAuthConfig>(v.clone()) {
+                                    Ok(cc) => {
+                                        config.auth.custom_config = Some(cc);
+                                        has_changes = true;
+                                    }
+                                    Err(e) => {
+                                        return Json(serde_json::json!({
+                                            "success": false,
+                                            "message": format!("Invalid custom auth configuration: {}", e)
+                                        }))
+                                    }
+                                }
+                            }
+                            _ => {
+                                config.auth.custom_config = None;
+                                has_changes = true;
+                            }
+                        }
+                    }
+
                     // token settings (legacy names)
                     if let Some(expiry) = sc_obj.get("token_expiry_time").and_then(|v| v.as_u64()) {
                         config.auth.token_expiry = expiry;
@@ -1847,11 +3394,13 @@ impl WebServer {
             if let Some(id) = preset_id {
                 // Build PresetConfig from the data - now directly using the module configs
                 let preset_config = crate::config::preset_loader::PresetConfig {
+                    config_version: crate::config::migrate::CURRENT_CONFIG_VERSION,
                     name: preset_name,
                     description: preset_description,
                     default_access_level: Some(module_config.default_access_level.clone()),
                     default_rate_limit: module_config.default_rate_limit.clone(),
                     modules: module_config.modules.clone(),
+                    enable: module_config.enable.clone(),
                 };
 
                 let id_clone = id.clone();
@@ -1956,3 +3505,307 @@ pub fn create_default_presets() -> HashMap<String, GlobalModuleConfig> {
 
     presets
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::history::ConfigDiffEntry;
+    use axum::body::Body;
+    use tempfile::tempdir;
+    use tower::ServiceExt;
+
+    fn test_server(dir: &std::path::Path) -> WebServer {
+        let manager = DynamicConfigManager::with_options(
+            dir.join("config.json"),
+            dir.join("modules.json"),
+            dir.join("presets"),
+            None,
+            false,
+        )
+        .unwrap();
+        WebServer::new_dynamic(Arc::new(manager))
+    }
+
+    fn save_preset(if_match: Option<&str>) -> Request {
+        let mut builder = axum::http::Request::builder()
+            .method(Method::POST)
+            .uri("/config/presets")
+            .header(header::CONTENT_TYPE, "application/json");
+        if let Some(if_match) = if_match {
+            builder = builder.header(header::IF_MATCH, if_match);
+        }
+        builder
+            .body(Body::from(r#"{"name":"custom","description":"Custom"}"#))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_config_events_mask_credentials_in_their_diff() {
+        let event = ConfigChangeEvent::Auth {
+            diff: vec![
+                ConfigDiffEntry {
+                    path: "auth.direct_config.token".to_string(),
+                    old: Some(serde_json::json!("old-secret-token")),
+                    new: Some(serde_json::json!("new-secret-token")),
+                },
+                ConfigDiffEntry {
+                    path: "auth.direct_config.custom_headers".to_string(),
+                    old: None,
+                    new: Some(serde_json::json!({ "X-Api-Key": "header-secret" })),
+                },
+                ConfigDiffEntry {
+                    path: "auth.token_expiry".to_string(),
+                    old: Some(serde_json::json!(3600)),
+                    new: Some(serde_json::json!(7200)),
+                },
+            ],
+        };
+
+        let message = config_event_message(&event);
+        assert_eq!(message["type"], "auth");
+        let text = message.to_string();
+        for secret in ["old-secret-token", "new-secret-token", "header-secret"] {
+            assert!(!text.contains(secret), "{} leaked in {}", secret, text);
+        }
+        assert_eq!(message["diff"][0]["new"], "****oken");
+        assert_eq!(message["diff"][2]["new"], 7200);
+    }
+
+    #[test]
+    fn test_config_version_covers_config_and_presets() {
+        let config = Config::default();
+        let presets = vec![serde_json::json!({"id": "dev", "enabled": true})];
+        let version = config_version(&config, &presets);
+        assert_eq!(version.len(), 16);
+        assert_eq!(version, config_version(&config.clone(), &presets.clone()));
+
+        let mut changed = config.clone();
+        changed.server.port += 1;
+        assert_ne!(version, config_version(&changed, &presets));
+
+        let disabled = vec![serde_json::json!({"id": "dev", "enabled": false})];
+        assert_ne!(version, config_version(&config, &disabled));
+    }
+
+    #[tokio::test]
+    async fn test_config_responses_carry_the_version_in_etag() {
+        let temp_dir = tempdir().unwrap();
+        let server = test_server(temp_dir.path());
+        let expected = format!("\"{}\"", server._state.version());
+
+        let request = axum::http::Request::builder().uri("/config").body(Body::empty()).unwrap();
+        let response = server._router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::ETAG], expected.as_str());
+    }
+
+    #[tokio::test]
+    async fn test_changes_require_the_current_version() {
+        let temp_dir = tempdir().unwrap();
+        let server = test_server(temp_dir.path());
+        let current = server._state.version();
+
+        let response = server._router.clone().oneshot(save_preset(None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PRECONDITION_REQUIRED);
+
+        let response = server
+            ._router
+            .clone()
+            .oneshot(save_preset(Some("\"0000000000000000\"")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert_eq!(response.headers()[header::ETAG], format!("\"{}\"", current).as_str());
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["version"], current.as_str());
+        assert_eq!(server._state.version(), current);
+    }
+
+    fn saved_login_auth() -> AuthConfig {
+        let content = HashMap::from([
+            ("username".to_string(), "ops".to_string()),
+            ("password".to_string(), "stored-password-1234".to_string()),
+        ]);
+        AuthConfig {
+            mode: crate::config::config::AuthMode::Login,
+            login_config: Some(crate::config::config::LoginAuthConfig {
+                url: "https://api.example.com/login".to_string(),
+                body: Some(crate::config::config::LoginRequestBody {
+                    format: crate::config::config::BodyFormat::Json,
+                    content,
+                }),
+                ..Default::default()
+            }),
+            ..AuthConfig::default()
+        }
+    }
+
+    fn login_password(auth: &AuthConfig) -> &str {
+        &auth.login_config.as_ref().unwrap().body.as_ref().unwrap().content["password"]
+    }
+
+    #[test]
+    fn test_auth_test_keeps_masked_credentials_away_from_other_hosts() {
+        let saved = saved_login_auth();
+        let mut update = serde_json::to_value(&saved).unwrap();
+        redact_value(&mut update);
+
+        let same_host = auth_under_test(Some(update.clone()), &saved).unwrap();
+        assert_eq!(login_password(&same_host), "stored-password-1234");
+
+        update["login_config"]["url"] = serde_json::json!("https://attacker.example/steal");
+        let other_host = auth_under_test(Some(update.clone()), &saved).unwrap();
+        assert_eq!(login_password(&other_host), "****1234");
+
+        update["login_config"]["body"]["content"]["password"] = serde_json::json!("env://API_PASSWORD");
+        assert!(auth_under_test(Some(update), &saved).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_auth_test_refuses_unsaved_commands_and_files() {
+        let temp_dir = tempdir().unwrap();
+        let server = test_server(temp_dir.path());
+        let marker = temp_dir.path().join("pwned");
+        let test_auth = |token: String| {
+            let body = serde_json::json!({
+                "auth": { "mode": "direct", "direct_config": { "auth_type": "bearer", "token": token } }
+            });
+            axum::http::Request::builder()
+                .method(Method::POST)
+                .uri("/config/auth/test")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        for token in [format!("exec://touch {}", marker.display()), "file:///etc/passwd".to_string()] {
+            let response = server._router.clone().oneshot(test_auth(token)).await.unwrap();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(body["success"], false);
+            assert!(body["message"].as_str().unwrap().contains("not in the saved configuration"));
+        }
+        assert!(!marker.exists());
+    }
+
+    #[test]
+    fn test_auth_tests_require_the_editor_role() {
+        let request = |method: Method, uri: &str| axum::http::Request::builder().method(method).uri(uri).body(Body::empty()).unwrap();
+        assert!(requires_editor(&request(Method::POST, "/config/auth/test")));
+        assert!(!changes_config(&request(Method::POST, "/config/auth/test")));
+        assert!(requires_editor(&request(Method::POST, "/config/presets")));
+        assert!(!requires_editor(&request(Method::GET, "/config")));
+    }
+
+    /// Server whose module defaults, module settings and preset carry header credentials
+    fn server_with_header_credentials(dir: &std::path::Path) -> WebServer {
+        let manager = DynamicConfigManager::with_options(
+            dir.join("config.json"),
+            dir.join("modules.json"),
+            dir.join("presets"),
+            None,
+            false,
+        )
+        .unwrap();
+
+        let mut module_config = GlobalModuleConfig::default();
+        module_config
+            .default_headers
+            .insert("Authorization".to_string(), "Bearer default-secret-1111".to_string());
+        let mut user = ModuleConfig::default();
+        user.headers.insert("X-Api-Key".to_string(), "module-secret-2222".to_string());
+        module_config.modules.insert("user".to_string(), user);
+        manager.update_module_config(module_config).unwrap();
+
+        let mut bugs = ModuleConfig::default();
+        bugs.headers.insert("X-Api-Key".to_string(), "preset-secret-3333".to_string());
+        let preset = crate::config::preset_loader::PresetConfig {
+            config_version: crate::config::migrate::CURRENT_CONFIG_VERSION,
+            name: "Bugs".to_string(),
+            description: "Bug tracking only".to_string(),
+            default_access_level: None,
+            default_rate_limit: None,
+            modules: HashMap::from([("bug".to_string(), bugs)]),
+            enable: None,
+        };
+        manager.save_preset("bugs", &preset).unwrap();
+        WebServer::new_dynamic(Arc::new(manager))
+    }
+
+    async fn get_body(server: &WebServer, uri: &str) -> String {
+        let request = axum::http::Request::builder().uri(uri).body(Body::empty()).unwrap();
+        let response = server._router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_effective_config_masks_cascaded_headers() {
+        let temp_dir = tempdir().unwrap();
+        let server = server_with_header_credentials(temp_dir.path());
+        let body = get_body(&server, "/config/effective").await;
+        assert!(body.contains("****1111") && body.contains("****2222"), "{}", body);
+        assert!(!body.contains("default-secret") && !body.contains("module-secret"), "{}", body);
+    }
+
+    #[tokio::test]
+    async fn test_presets_mask_module_credentials() {
+        let temp_dir = tempdir().unwrap();
+        let server = server_with_header_credentials(temp_dir.path());
+        let body = get_body(&server, "/config/presets").await;
+        assert!(body.contains("****3333"), "{}", body);
+        assert!(!body.contains("preset-secret"), "{}", body);
+    }
+
+    #[tokio::test]
+    async fn test_preset_diff_masks_changed_credentials() {
+        let temp_dir = tempdir().unwrap();
+        let server = server_with_header_credentials(temp_dir.path());
+        let body = get_body(&server, "/config/preset/bugs/diff").await;
+        assert!(body.contains("\"success\":true"), "{}", body);
+        for secret in ["default-secret", "module-secret", "preset-secret"] {
+            assert!(!body.contains(secret), "{} leaked in {}", secret, body);
+        }
+    }
+
+    #[test]
+    fn test_reveal_resolves_a_referenced_admin_token() {
+        std::env::set_var("MCP_WEB_TEST_ADMIN_TOKEN", "root-token");
+        let mut config = Config::default();
+        config.server.admin_token = Some("env://MCP_WEB_TEST_ADMIN_TOKEN".to_string());
+        let reveal = RevealQuery { reveal: true };
+        let bearer = |token: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::AUTHORIZATION, format!("Bearer {}", token).parse().unwrap());
+            headers
+        };
+
+        assert!(WebServer::authorize_reveal(&config, &reveal, &bearer("root-token")).unwrap());
+        let rejected = WebServer::authorize_reveal(&config, &reveal, &bearer("env://MCP_WEB_TEST_ADMIN_TOKEN")).unwrap_err();
+        assert_eq!(rejected.status(), StatusCode::FORBIDDEN);
+        assert!(!WebServer::authorize_reveal(&config, &RevealQuery::default(), &HeaderMap::new()).unwrap());
+
+        config.server.admin_token = Some("env://MCP_WEB_TEST_ADMIN_TOKEN_MISSING".to_string());
+        assert!(WebServer::authorize_reveal(&config, &reveal, &bearer("")).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_mcp_endpoints_refuse_a_network_address_without_authentication() {
+        let temp_dir = tempdir().unwrap();
+        let dir = temp_dir.path();
+        let manager = Arc::new(
+            DynamicConfigManager::with_options(dir.join("config.json"), dir.join("modules.json"), dir.join("presets"), None, false)
+                .unwrap(),
+        );
+        let mut config = manager.get_config();
+        config.server.bind_address = "0.0.0.0".to_string();
+        config.server.admin_token = Some("admin-secret".to_string());
+        manager.update_config(config).unwrap();
+
+        let composer = ServiceComposer::new(manager.clone()).unwrap();
+        let error = WebServer::new_dynamic(manager).register_service_composer(composer).start().await.unwrap_err();
+        assert!(error.to_string().contains("Refusing to serve MCP"), "{}", error);
+    }
+}
@@ -12,6 +12,7 @@ use std::path::Path;
 use crate::zml::ast::Module;
 use crate::zml::parser::ZMLParserWrapper;
 use crate::config::module::{GlobalModuleConfig, ModuleConfig};
+use crate::config::dependencies::activation_order;
 
 /// Loader that parses ZML modules from a directory and caches them by name.
 #[derive(Debug, Clone)]
@@ -45,7 +46,7 @@ impl ZmlModuleLoader {
             if path.extension().map(|e| e == "zml").unwrap_or(false) {
                 let source = fs::read_to_string(&path)
                     .with_context(|| format!("Failed to read ZML file: {}", path.display()))?;
-                match parser.parse(&source) {
+                match parser.parse_with_file(&source, &path.display().to_string()) {
                     Ok(module) => {
                         debug!("Parsed ZML module: {}", module.name);
                         modules.insert(module.name.clone(), module);
@@ -75,13 +76,31 @@ impl ZmlModuleLoader {
         self.modules.keys().cloned().collect()
     }
 
+    /// Names of the modules added, removed or changed in `other` compared to this loader
+    pub fn changed_modules(&self, other: &ZmlModuleLoader) -> Vec<String> {
+        let mut names: Vec<&String> = self.modules.keys().chain(other.modules.keys()).collect();
+        names.sort();
+        names.dedup();
+        names
+            .into_iter()
+            .filter(|name| {
+                self.modules.get(*name).map(|m| serde_json::to_value(m).ok())
+                    != other.modules.get(*name).map(|m| serde_json::to_value(m).ok())
+            })
+            .cloned()
+            .collect()
+    }
+
     /// Return enabled module names according to GlobalModuleConfig
+    /// Enabled modules whose dependencies are active, in activation order
     pub fn get_enabled_modules(&self, global: &GlobalModuleConfig) -> Vec<String> {
-        self.modules
+        let enabled: Vec<String> = self
+            .modules
             .keys()
-            .filter(|name| global.is_module_enabled(name))
+            .filter(|name| global.is_module_active(name))
             .cloned()
-            .collect()
+            .collect();
+        activation_order(global, &enabled).unwrap_or(enabled)
     }
 }
 
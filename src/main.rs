@@ -1,29 +1,115 @@
-//! MCP-ANY-REST with stdio transport
+//! MCP-ANY-REST with stdio, HTTP, SSE, Unix socket and TCP transports
 
 use anyhow::Result;
-use clap::Parser;
-use log::info;
+use clap::{Parser, Subcommand};
+use log::{info, warn};
 use rmcp::{transport::stdio, ServerHandler, ServiceExt};
-use std::path::PathBuf;
+use std::future::Future;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
 use tracing_subscriber::{
     layer::SubscriberExt,
     util::SubscriberInitExt,
     EnvFilter, {self},
 };
+use mcp_any_rest::config::check::check_config_dir;
+use mcp_any_rest::config::format::ConfigFormat;
+use mcp_any_rest::config::init::{init_config_dir, InitAuth, InitOptions};
+use mcp_any_rest::config::profile::active_profile;
+use mcp_any_rest::config::remote::RemoteSync;
 use mcp_any_rest::{DynamicConfigManager, ServiceComposer, WebServer};
 
 /// Command line arguments for MCP-ANY-REST
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Configuration directory path
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     config_dir: Option<PathBuf>,
 
     /// Transport mode
     #[arg(long, default_value = "stdio")]
     transport: TransportMode,
+
+    /// Socket path of the unix transport
+    #[arg(long, required_if_eq("transport", "unix"))]
+    socket: Option<PathBuf>,
+
+    /// Address (host:port) of the tcp transport
+    #[arg(long, required_if_eq("transport", "tcp"))]
+    listen: Option<String>,
+
+    /// In stdio mode, also serve MCP over streamable HTTP (`/mcp`) from the same service composer
+    #[arg(long)]
+    serve_http: bool,
+
+    /// Path prefix of the web server's routes (e.g. /mcp-any-rest); overrides server.base_path
+    #[arg(long)]
+    base_path: Option<String>,
+
+    /// Configuration profile overlay to apply (e.g. dev, prod); defaults to MCP_PROFILE
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
+    /// Seconds between checks for changed configuration, module, preset and ZML files (0 disables)
+    #[arg(long, default_value_t = 2)]
+    watch_interval: u64,
+
+    /// Reject configuration changes through the web API and never write configuration files
+    #[arg(long)]
+    read_only_config: bool,
+}
+
+/// Subcommands that run instead of the server
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Check configuration, modules, presets and ZML sources; exits non-zero on errors
+    Validate {
+        /// Report format
+        #[arg(long, default_value = "text")]
+        format: ReportFormat,
+    },
+    /// Generate a skeleton configuration directory, prompting for missing answers
+    Init {
+        /// Base URL of the REST API
+        #[arg(long)]
+        base_url: Option<String>,
+
+        /// Authentication mode
+        #[arg(long)]
+        auth: Option<InitAuthMode>,
+
+        /// Overwrite existing files
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+/// Authentication mode of a generated configuration
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum InitAuthMode {
+    /// No credentials
+    None,
+    /// Bearer token
+    Bearer,
+    /// API key header
+    ApiKey,
+    /// HTTP basic authentication
+    Basic,
+}
+
+/// Output format of the validation report
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum ReportFormat {
+    /// Human readable lines, one per issue
+    Text,
+    /// JSON document
+    Json,
 }
 
 /// Transport mode for the server
@@ -33,6 +119,12 @@ enum TransportMode {
     Stdio,
     /// Use HTTP transport
     Http,
+    /// Use the SSE transport, for clients that do not speak streamable HTTP yet
+    Sse,
+    /// Accept MCP connections on a Unix domain socket (`--socket`)
+    Unix,
+    /// Accept MCP connections on a TCP address (`--listen`)
+    Tcp,
 }
 
 /// Initialize tracing subscriber for stdio mode
@@ -66,9 +158,23 @@ fn init_http_logging() {
 }
 
 /// Create configuration manager with config directory
-fn create_config_manager(config_dir: &PathBuf) -> Result<Arc<DynamicConfigManager>> {
-    let config_path = config_dir.join("config.json");
-    let modules_path = config_dir.join("modules.json");
+async fn create_config_manager(
+    config_dir: &PathBuf,
+    profile: Option<&str>,
+    watch_interval: u64,
+    read_only: bool,
+) -> Result<Arc<DynamicConfigManager>> {
+    // Mirror a remote configuration source (remote.json) before loading local files
+    let remote = RemoteSync::from_config_dir(config_dir)?.map(Arc::new);
+    if let Some(remote) = &remote {
+        info!("Syncing configuration from remote source: {}", remote.source().describe());
+        if let Err(e) = remote.sync().await {
+            warn!("Initial remote configuration sync failed, using local files: {:#}", e);
+        }
+    }
+
+    let config_path = ConfigFormat::resolve_path(config_dir, "config", ConfigFormat::Json);
+    let modules_path = ConfigFormat::resolve_path(config_dir, "modules", ConfigFormat::Json);
     let presets_dir = config_dir.join("presets");
 
     info!("Config directory: {:?}", config_dir);
@@ -76,12 +182,22 @@ fn create_config_manager(config_dir: &PathBuf) -> Result<Arc<DynamicConfigManage
     info!("Modules file: {:?}", modules_path);
     info!("Presets directory: {:?}", presets_dir);
 
-    let config_manager = Arc::new(DynamicConfigManager::new(
+    let config_manager = Arc::new(DynamicConfigManager::with_options(
         config_path,
         modules_path,
         presets_dir,
+        active_profile(profile),
+        read_only,
     )?);
 
+    if let Some(remote) = remote {
+        config_manager.start_remote_refresh(remote);
+    }
+
+    if watch_interval > 0 {
+        config_manager.start_file_watch(Duration::from_secs(watch_interval));
+    }
+
     Ok(config_manager)
 }
 
@@ -97,12 +213,33 @@ fn create_service_composer(config_manager: &Arc<DynamicConfigManager>) -> Result
     Ok(service_composer)
 }
 
-/// Start server in stdio mode with web config server
-async fn start_stdio_mode(config_manager: Arc<DynamicConfigManager>) -> Result<()> {
+/// Web server of stdio mode: the web configuration, and `/mcp` on the stdio composer with `serve_http`
+fn stdio_web_server(
+    config_manager: Arc<DynamicConfigManager>,
+    service_composer: &ServiceComposer,
+    serve_http: bool,
+    base_path: Option<String>,
+) -> WebServer {
+    let web_server = WebServer::new_dynamic(config_manager).with_base_path(base_path);
+    // Both transports share the composer, so caches, rate limits and tokens are shared too
+    if serve_http {
+        info!("Also serving MCP over streamable HTTP");
+        web_server.register_service_composer(service_composer.clone())
+    } else {
+        web_server
+    }
+}
+
+/// Start server in stdio mode with web config server, optionally serving streamable HTTP as well
+async fn start_stdio_mode(
+    config_manager: Arc<DynamicConfigManager>,
+    serve_http: bool,
+    base_path: Option<String>,
+) -> Result<()> {
     info!("Starting MCP server in stdio mode with web configuration server...");
 
     let service_composer = create_service_composer(&config_manager)?;
-    let web_server = WebServer::new_dynamic(config_manager.clone());
+    let web_server = stdio_web_server(config_manager.clone(), &service_composer, serve_http, base_path);
 
     // new thread to start web server
     let web_server_handle = tokio::spawn(async move { web_server.start().await });
@@ -120,12 +257,137 @@ async fn start_stdio_mode(config_manager: Arc<DynamicConfigManager>) -> Result<(
     Ok(())
 }
 
+/// Start server accepting MCP connections with `accept`, each served like stdio, with web config server
+async fn start_socket_mode<S, F, Fut>(
+    config_manager: Arc<DynamicConfigManager>,
+    base_path: Option<String>,
+    mut accept: F,
+) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Send + 'static,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = std::io::Result<(S, String)>>,
+{
+    let service_composer = create_service_composer(&config_manager)?;
+    let web_server = WebServer::new_dynamic(config_manager.clone()).with_base_path(base_path);
+
+    // new thread to start web server
+    let web_server_handle = tokio::spawn(async move { web_server.start().await });
+
+    loop {
+        let (stream, peer) = tokio::select! {
+            accepted = accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    warn!("Failed to accept MCP connection: {}", e);
+                    continue;
+                }
+            },
+            _ = tokio::signal::ctrl_c() => break,
+        };
+        info!("MCP connection from {}", peer);
+        tokio::spawn(serve_connection(service_composer.clone(), stream, peer));
+    }
+
+    // wait for web server to shutdown
+    let _ = web_server_handle.await?;
+
+    info!("Server shutdown completed");
+    Ok(())
+}
+
+/// Serve one MCP session on an accepted connection until the client disconnects
+async fn serve_connection<S>(service_composer: ServiceComposer, stream: S, peer: String)
+where
+    S: AsyncRead + AsyncWrite + Send + 'static,
+{
+    match service_composer.serve(tokio::io::split(stream)).await {
+        Ok(service) => {
+            if let Err(e) = service.waiting().await {
+                warn!("MCP connection from {} failed: {:?}", peer, e);
+            }
+        }
+        Err(e) => warn!("Failed to initialize MCP connection from {}: {:?}", peer, e),
+    }
+    info!("MCP connection from {} closed", peer);
+}
+
+/// Start server accepting MCP connections on a TCP address
+async fn start_tcp_mode(config_manager: Arc<DynamicConfigManager>, listen: &str, base_path: Option<String>) -> Result<()> {
+    info!("Starting MCP server in TCP mode with web configuration server...");
+
+    let listener = tokio::net::TcpListener::bind(listen).await?;
+    let address = listener.local_addr()?;
+    info!("Accepting MCP connections on tcp://{}", address);
+    if !address.ip().is_loopback() {
+        // Connections are not authenticated, so exposing them takes an explicit opt-in
+        if !config_manager.get_config().server.allow_unauthenticated_mcp {
+            anyhow::bail!(
+                "Refusing to accept unauthenticated MCP connections on tcp://{}; listen on 127.0.0.1, use the unix transport, or set server.allow_unauthenticated_mcp",
+                address
+            );
+        }
+        warn!(
+            "The tcp transport on {} is reachable from the network and does not authenticate clients",
+            address
+        );
+    }
+    let listener = &listener;
+    start_socket_mode(config_manager, base_path, move || async move {
+        listener.accept().await.map(|(stream, address)| (stream, address.to_string()))
+    })
+    .await
+}
+
+/// Start server accepting MCP connections on a Unix domain socket
+#[cfg(unix)]
+async fn start_unix_mode(config_manager: Arc<DynamicConfigManager>, socket: &Path, base_path: Option<String>) -> Result<()> {
+    info!("Starting MCP server in Unix socket mode with web configuration server...");
+
+    remove_stale_socket(socket)?;
+    let listener = tokio::net::UnixListener::bind(socket)?;
+    info!("Accepting MCP connections on unix://{}", socket.display());
+    let listener = &listener;
+    let mut connections = 0u64;
+    let result = start_socket_mode(config_manager, base_path, move || {
+        connections += 1;
+        let peer = format!("{} #{}", socket.display(), connections);
+        async move { listener.accept().await.map(|(stream, _)| (stream, peer)) }
+    })
+    .await;
+    let _ = std::fs::remove_file(socket);
+    result
+}
+
+/// Remove a socket left by a previous run, which would make the bind fail;
+/// any other file at the path is kept and fails the start
+#[cfg(unix)]
+fn remove_stale_socket(socket: &Path) -> Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+
+    match std::fs::symlink_metadata(socket) {
+        Ok(metadata) if metadata.file_type().is_socket() => Ok(std::fs::remove_file(socket)?),
+        Ok(_) => anyhow::bail!("{} exists and is not a socket; refusing to replace it", socket.display()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(not(unix))]
+async fn start_unix_mode(
+    _config_manager: Arc<DynamicConfigManager>,
+    _socket: &Path,
+    _base_path: Option<String>,
+) -> Result<()> {
+    anyhow::bail!("The unix transport is only available on Unix platforms")
+}
+
 /// Start server in HTTP mode
-async fn start_http_mode(config_manager: Arc<DynamicConfigManager>) -> Result<()> {
+async fn start_http_mode(config_manager: Arc<DynamicConfigManager>, base_path: Option<String>) -> Result<()> {
     info!("Starting MCP server in HTTP mode...");
 
     let service_composer = create_service_composer(&config_manager)?;
-    let web_server = WebServer::new_dynamic(config_manager);
+    let web_server = WebServer::new_dynamic(config_manager).with_base_path(base_path);
 
     let web_server = web_server.register_service_composer(service_composer);
 
@@ -141,6 +403,102 @@ async fn start_http_mode(config_manager: Arc<DynamicConfigManager>) -> Result<()
     }
 }
 
+/// Start server in SSE mode
+async fn start_sse_mode(config_manager: Arc<DynamicConfigManager>, base_path: Option<String>) -> Result<()> {
+    info!("Starting MCP server in SSE mode...");
+
+    let service_composer = create_service_composer(&config_manager)?;
+    let web_server = WebServer::new_dynamic(config_manager).with_base_path(base_path);
+
+    let web_server = web_server.register_sse_service_composer(service_composer)?;
+
+    match web_server.start().await {
+        Ok(_) => {
+            info!("SSE server shutdown completed");
+            Ok(())
+        }
+        Err(e) => {
+            tracing::error!("SSE server error: {:?}", e);
+            Err(e)
+        }
+    }
+}
+
+/// Validate a configuration directory and exit with status 1 when errors are found
+fn run_validate(config_dir: &Path, profile: Option<&str>, format: &ReportFormat) -> Result<()> {
+    let report = check_config_dir(config_dir, active_profile(profile).as_deref());
+
+    match format {
+        ReportFormat::Text => println!("{}", report),
+        ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+    }
+
+    if !report.is_ok() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Ask a question on stdin; an empty answer (or a non-interactive stdin) gives `default`
+fn prompt(question: &str, default: &str) -> Result<String> {
+    use std::io::{BufRead, IsTerminal, Write};
+
+    if !std::io::stdin().is_terminal() {
+        return Ok(default.to_string());
+    }
+    if default.is_empty() {
+        print!("{}: ", question);
+    } else {
+        print!("{} [{}]: ", question, default);
+    }
+    std::io::stdout().flush()?;
+
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    let answer = answer.trim();
+    Ok(if answer.is_empty() { default.to_string() } else { answer.to_string() })
+}
+
+/// Generate a skeleton configuration directory
+fn run_init(config_dir: &Path, base_url: Option<String>, auth: Option<InitAuthMode>, force: bool) -> Result<()> {
+    let base_url = match base_url {
+        Some(base_url) => base_url,
+        None => prompt("API base URL", "http://localhost:8080/api")?,
+    };
+    let auth = match auth {
+        Some(auth) => auth,
+        None => {
+            let answer = prompt("Authentication (none, bearer, api-key, basic)", "none")?;
+            <InitAuthMode as clap::ValueEnum>::from_str(&answer, true)
+                .map_err(|e| anyhow::anyhow!("Invalid authentication mode '{}': {}", answer, e))?
+        }
+    };
+    let auth = match auth {
+        InitAuthMode::None => InitAuth::None,
+        InitAuthMode::Bearer => InitAuth::Bearer {
+            token: prompt("Bearer token", "")?,
+        },
+        InitAuthMode::ApiKey => InitAuth::ApiKey {
+            name: prompt("API key header", "X-API-Key")?,
+            token: prompt("API key", "")?,
+        },
+        InitAuthMode::Basic => InitAuth::Basic {
+            username: prompt("Username", "")?,
+            password: prompt("Password", "")?,
+        },
+    };
+
+    let files = init_config_dir(config_dir, &InitOptions { base_url, auth, force })?;
+    for file in files {
+        println!("Created {}", file.display());
+    }
+    println!(
+        "Start the server with: mcp-any-rest --config-dir {} --transport http",
+        config_dir.display()
+    );
+    Ok(())
+}
+
 /// Get executable directory path
 fn get_executable_dir() -> Result<PathBuf> {
     let current_exe = std::env::current_exe()
@@ -176,25 +534,231 @@ async fn main() -> Result<()> {
     // Determine configuration directory
     let config_dir = determine_config_dir(&args)?;
 
+    match &args.command {
+        Some(Command::Validate { format }) => {
+            return run_validate(&config_dir, args.profile.as_deref(), format);
+        }
+        Some(Command::Init { base_url, auth, force }) => {
+            return run_init(&config_dir, base_url.clone(), auth.clone(), *force);
+        }
+        None => {}
+    }
+
     // Handle transport mode selection
     match args.transport {
         TransportMode::Stdio => {
             init_stdio_logging();
             // Create configuration manager
-            let config_manager = create_config_manager(&config_dir)?;
+            let config_manager = create_config_manager(
+                &config_dir,
+                args.profile.as_deref(),
+                args.watch_interval,
+                args.read_only_config,
+            )
+            .await?;
 
             info!("MCP-ANY-REST with stdio transport started successfully");
             info!("Using config directory: {:?}", config_dir);
-            start_stdio_mode(config_manager).await
+            start_stdio_mode(config_manager, args.serve_http, args.base_path.clone()).await
         }
         TransportMode::Http => {
             init_http_logging();
             // Create configuration manager
-            let config_manager = create_config_manager(&config_dir)?;
+            let config_manager = create_config_manager(
+                &config_dir,
+                args.profile.as_deref(),
+                args.watch_interval,
+                args.read_only_config,
+            )
+            .await?;
 
             info!("MCP-ANY-REST with HTTP transport started successfully");
             info!("Using config directory: {:?}", config_dir);
-            start_http_mode(config_manager).await
+            start_http_mode(config_manager, args.base_path.clone()).await
+        }
+        TransportMode::Sse => {
+            init_http_logging();
+            // Create configuration manager
+            let config_manager = create_config_manager(
+                &config_dir,
+                args.profile.as_deref(),
+                args.watch_interval,
+                args.read_only_config,
+            )
+            .await?;
+
+            info!("MCP-ANY-REST with SSE transport started successfully");
+            info!("Using config directory: {:?}", config_dir);
+            start_sse_mode(config_manager, args.base_path.clone()).await
         }
+        TransportMode::Unix | TransportMode::Tcp => {
+            init_http_logging();
+            // Create configuration manager
+            let config_manager = create_config_manager(
+                &config_dir,
+                args.profile.as_deref(),
+                args.watch_interval,
+                args.read_only_config,
+            )
+            .await?;
+
+            info!("Using config directory: {:?}", config_dir);
+            match (&args.socket, &args.listen) {
+                (Some(socket), _) if matches!(args.transport, TransportMode::Unix) => {
+                    start_unix_mode(config_manager, socket, args.base_path.clone()).await
+                }
+                (_, Some(listen)) => start_tcp_mode(config_manager, listen, args.base_path.clone()).await,
+                _ => anyhow::bail!("--transport unix requires --socket and --transport tcp requires --listen"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::transport::{SseClientTransport, StreamableHttpClientTransport};
+    use tempfile::TempDir;
+
+    /// Configuration manager in `dir`, with the web server on a free local port
+    fn test_config_manager(dir: &TempDir) -> (Arc<DynamicConfigManager>, u16) {
+        let config_manager = Arc::new(
+            DynamicConfigManager::with_options(
+                dir.path().join("config.json"),
+                dir.path().join("modules.json"),
+                dir.path().join("presets"),
+                None,
+                false,
+            )
+            .unwrap(),
+        );
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let mut config = config_manager.get_config();
+        config.server.bind_address = "127.0.0.1".to_string();
+        config.server.port = port;
+        config_manager.update_config(config).unwrap();
+        (config_manager, port)
+    }
+
+    /// Wait until the web server accepts connections
+    async fn wait_for_server(port: u16) {
+        for _ in 0..100 {
+            if tokio::net::TcpStream::connect(("127.0.0.1", port)).await.is_ok() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        panic!("Web server did not start on port {}", port);
+    }
+
+    #[tokio::test]
+    async fn test_sse_transport_serves_the_composer_under_the_base_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let (config_manager, port) = test_config_manager(&dir);
+        let service_composer = create_service_composer(&config_manager).unwrap();
+        let web_server = WebServer::new_dynamic(config_manager)
+            .with_base_path(Some("/tools".to_string()))
+            .register_sse_service_composer(service_composer.clone())
+            .unwrap();
+        let server = tokio::spawn(web_server.start());
+        wait_for_server(port).await;
+
+        let transport = SseClientTransport::start(format!("http://127.0.0.1:{}/tools/sse", port)).await.unwrap();
+        let client = ().serve(transport).await.unwrap();
+        assert_eq!(client.peer_info().unwrap().server_info.name, service_composer.get_info().server_info.name);
+        client.list_all_tools().await.unwrap();
+        client.cancel().await.unwrap();
+
+        // The endpoints are only served under the base path
+        let response = reqwest::get(format!("http://127.0.0.1:{}/sse", port)).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn test_socket_connections_each_get_a_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let (config_manager, _) = test_config_manager(&dir);
+        let service_composer = create_service_composer(&config_manager).unwrap();
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let accept = async {
+            let (stream, peer) = listener.accept().await.unwrap();
+            tokio::spawn(serve_connection(service_composer.clone(), stream, peer.to_string()))
+        };
+        let connect = async { ().serve(tokio::net::TcpStream::connect(address).await.unwrap()).await.unwrap() };
+        let (connection, client) = tokio::join!(accept, connect);
+        client.list_all_tools().await.unwrap();
+
+        // Another connection gets its own session while the first one is open
+        #[cfg(unix)]
+        {
+            let (client_stream, server_stream) = tokio::net::UnixStream::pair().unwrap();
+            let unix_connection = tokio::spawn(serve_connection(service_composer.clone(), server_stream, "unix".to_string()));
+            let unix_client = ().serve(client_stream).await.unwrap();
+            unix_client.list_all_tools().await.unwrap();
+            unix_client.cancel().await.unwrap();
+            tokio::time::timeout(Duration::from_secs(5), unix_connection).await.unwrap().unwrap();
+        }
+
+        // The session ends when the client disconnects
+        client.cancel().await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), connection).await.unwrap().unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_only_stale_sockets_are_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("mcp.sock");
+        remove_stale_socket(&socket).unwrap();
+
+        drop(std::os::unix::net::UnixListener::bind(&socket).unwrap());
+        remove_stale_socket(&socket).unwrap();
+        assert!(!socket.exists());
+
+        // A regular file or a link at the path is not deleted
+        let file = dir.path().join("config.json");
+        std::fs::write(&file, "{}").unwrap();
+        assert!(remove_stale_socket(&file).is_err());
+        assert!(file.exists());
+        let link = dir.path().join("link.sock");
+        std::os::unix::fs::symlink(&file, &link).unwrap();
+        assert!(remove_stale_socket(&link).is_err());
+        assert!(link.exists());
+    }
+
+    #[tokio::test]
+    async fn test_stdio_mode_shares_its_composer_with_streamable_http() {
+        let dir = tempfile::tempdir().unwrap();
+        let (config_manager, port) = test_config_manager(&dir);
+        let service_composer = create_service_composer(&config_manager).unwrap();
+        let server = tokio::spawn(stdio_web_server(config_manager, &service_composer, true, None).start());
+        wait_for_server(port).await;
+
+        // The stdio session and an HTTP session are served by the same composer
+        let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+        tokio::spawn(serve_connection(service_composer.clone(), server_io, "stdio".to_string()));
+        let stdio_client = ().serve(client_io).await.unwrap();
+        let transport = StreamableHttpClientTransport::from_uri(format!("http://127.0.0.1:{}/mcp", port));
+        let http_client = ().serve(transport).await.unwrap();
+        let stdio_tools: Vec<String> =
+            stdio_client.list_all_tools().await.unwrap().into_iter().map(|tool| tool.name.to_string()).collect();
+        let http_tools: Vec<String> =
+            http_client.list_all_tools().await.unwrap().into_iter().map(|tool| tool.name.to_string()).collect();
+        assert_eq!(stdio_tools, http_tools);
+        stdio_client.cancel().await.unwrap();
+        http_client.cancel().await.unwrap();
+        server.abort();
+
+        // Without --serve-http, the web server only serves the configuration
+        let dir = tempfile::tempdir().unwrap();
+        let (config_manager, port) = test_config_manager(&dir);
+        let server = tokio::spawn(stdio_web_server(config_manager, &service_composer, false, None).start());
+        wait_for_server(port).await;
+        let response = reqwest::get(format!("http://127.0.0.1:{}/mcp", port)).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+        server.abort();
     }
 }
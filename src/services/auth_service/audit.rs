@@ -0,0 +1,194 @@
+//! Authentication event audit log for MCP-ANY-REST
+//!
+//! Logins, token refreshes and token invalidations are recorded with their time, strategy, outcome
+//! and latency. The last `AUDIT_CAPACITY` events are kept in memory for `GET /config/auth/audit`
+//! and the `auth://audit` MCP resource; when `MCP_AUTH_AUDIT_FILE` is set, every event is also
+//! appended to that file as a JSON line.
+
+use super::auth_strategy::AuthError;
+use chrono::{DateTime, Utc};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::OpenOptions;
+use std::future::Future;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Number of events kept in memory
+pub const AUDIT_CAPACITY: usize = 200;
+
+/// Environment variable naming the file events are appended to, relative to the configuration directory
+pub const AUTH_AUDIT_FILE_ENV: &str = "MCP_AUTH_AUDIT_FILE";
+
+/// URI of the MCP resource listing the recorded events
+pub const AUDIT_RESOURCE_URI: &str = "auth://audit";
+
+/// Process-wide audit log written by the authentication strategies
+static AUDIT_LOG: Mutex<AuditLog> = Mutex::new(AuditLog {
+    events: VecDeque::new(),
+    file: None,
+});
+
+/// What happened to the credentials
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthEventKind {
+    /// A login, OAuth2 grant or cookie session login
+    Login,
+    /// A token renewed with a refresh token
+    Refresh,
+    /// A token or session dropped after the API rejected it
+    Invalidation,
+}
+
+/// Outcome of the event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthEventStatus {
+    Success,
+    Failure,
+}
+
+/// A recorded authentication event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthEvent {
+    pub timestamp: DateTime<Utc>,
+    pub kind: AuthEventKind,
+    /// Strategy that produced the event, e.g. `login`, `oauth2` or `cookie`
+    pub strategy: String,
+    pub status: AuthEventStatus,
+    /// Time taken by the login or refresh; `None` for invalidations
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    /// Error of a failure or reason of an invalidation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Bounded event buffer and the optional file it is mirrored to
+struct AuditLog {
+    events: VecDeque<AuthEvent>,
+    file: Option<PathBuf>,
+}
+
+impl AuditLog {
+    fn push(&mut self, event: AuthEvent) {
+        if let Some(path) = &self.file {
+            if let Err(e) = append_line(path, &event) {
+                warn!("Failed to write auth audit file {:?}: {}", path, e);
+            }
+        }
+        if self.events.len() == AUDIT_CAPACITY {
+            self.events.pop_front();
+        }
+        self.events.push_back(event);
+    }
+}
+
+/// Mirror events to `path` from now on; `None` keeps them in memory only
+pub fn install_audit_file(path: Option<PathBuf>) {
+    AUDIT_LOG.lock().unwrap().file = path;
+}
+
+/// Record an event
+pub fn record(event: AuthEvent) {
+    AUDIT_LOG.lock().unwrap().push(event);
+}
+
+/// Recorded events, newest first
+pub fn events() -> Vec<AuthEvent> {
+    AUDIT_LOG.lock().unwrap().events.iter().rev().cloned().collect()
+}
+
+/// Run a login or refresh and record its outcome and latency
+pub async fn audited<T>(
+    kind: AuthEventKind,
+    strategy: &str,
+    future: impl Future<Output = Result<T, AuthError>>,
+) -> Result<T, AuthError> {
+    let started = Instant::now();
+    let result = future.await;
+    record(AuthEvent {
+        timestamp: Utc::now(),
+        kind,
+        strategy: strategy.to_string(),
+        status: if result.is_ok() { AuthEventStatus::Success } else { AuthEventStatus::Failure },
+        latency_ms: Some(started.elapsed().as_millis() as u64),
+        detail: result.as_ref().err().map(ToString::to_string),
+    });
+    result
+}
+
+/// Record that the token or session of `strategy` was dropped
+pub fn record_invalidation(strategy: &str, reason: &str) {
+    record(AuthEvent {
+        timestamp: Utc::now(),
+        kind: AuthEventKind::Invalidation,
+        strategy: strategy.to_string(),
+        status: AuthEventStatus::Success,
+        latency_ms: None,
+        detail: Some(reason.to_string()),
+    });
+}
+
+fn append_line(path: &Path, event: &AuthEvent) -> std::io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(event)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_audited_records_outcome_and_latency() {
+        let token = audited(AuthEventKind::Login, "audit-test", async { Ok("token".to_string()) }).await;
+        assert_eq!(token.unwrap(), "token");
+        let failed: Result<String, AuthError> = audited(AuthEventKind::Refresh, "audit-test", async {
+            Err(AuthError::LoginFailed("status 400".to_string()))
+        })
+        .await;
+        assert!(failed.is_err());
+        record_invalidation("audit-test", "401 Unauthorized");
+
+        let recorded: Vec<AuthEvent> = events().into_iter().filter(|event| event.strategy == "audit-test").collect();
+        assert_eq!(recorded.len(), 3);
+        assert_eq!(recorded[0].kind, AuthEventKind::Invalidation);
+        assert_eq!(recorded[0].latency_ms, None);
+        assert_eq!(recorded[1].kind, AuthEventKind::Refresh);
+        assert_eq!(recorded[1].status, AuthEventStatus::Failure);
+        assert!(recorded[1].detail.as_deref().unwrap().contains("status 400"));
+        assert_eq!(recorded[2].status, AuthEventStatus::Success);
+        assert!(recorded[2].latency_ms.is_some());
+    }
+
+    #[test]
+    fn test_log_keeps_the_latest_events_and_mirrors_them_to_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("auth_audit.jsonl");
+        let mut log = AuditLog {
+            events: VecDeque::new(),
+            file: Some(path.clone()),
+        };
+        for index in 0..AUDIT_CAPACITY + 5 {
+            log.push(AuthEvent {
+                timestamp: Utc::now(),
+                kind: AuthEventKind::Login,
+                strategy: format!("login-{}", index),
+                status: AuthEventStatus::Success,
+                latency_ms: Some(1),
+                detail: None,
+            });
+        }
+        assert_eq!(log.events.len(), AUDIT_CAPACITY);
+        assert_eq!(log.events.front().unwrap().strategy, "login-5");
+
+        let lines: Vec<String> = std::fs::read_to_string(&path).unwrap().lines().map(String::from).collect();
+        assert_eq!(lines.len(), AUDIT_CAPACITY + 5);
+        let first: AuthEvent = serde_json::from_str(&lines[0]).unwrap();
+        assert_eq!(first.strategy, "login-0");
+    }
+}
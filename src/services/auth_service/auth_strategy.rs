@@ -8,9 +8,17 @@
 //! 1. Direct Authentication - Authentication information is directly configured and used in each request
 //! 2. Login-based Authentication - Login information is configured first, then authentication is obtained after login
 
+use crate::config::config::ContentEncoding;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+pub use crate::config::config::{
+    ApiKeyPoolConfig, AwsSigV4Config, CookieSessionConfig, CustomAuthConfig, HmacAlgorithm, HmacAuthConfig, HmacEncoding, HmacTimestampFormat,
+    JwtAlgorithm, JwtAuthConfig, KeyRotation, OAuth2ClientAuth, OAuth2Config, OAuth2GrantType, RefreshTokenConfig,
+};
 
 /// Authentication mode enumeration
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -19,6 +27,16 @@ pub enum AuthMode {
     Direct,
     /// Login-based authentication - obtain authentication after login
     Login,
+    /// JWT authentication - sign tokens locally with a private key
+    Jwt,
+    /// AWS Signature Version 4 - sign every request with AWS credentials
+    AwsSigV4,
+    /// HMAC signing - sign every request with a shared secret
+    Hmac,
+    /// Cookie session - log in through a form and send its session cookies
+    Cookie,
+    /// Custom - a strategy registered with `AuthServiceFactory::register_auth_strategy`
+    Custom,
 }
 
 impl Default for AuthMode {
@@ -32,6 +50,11 @@ impl std::fmt::Display for AuthMode {
         match self {
             AuthMode::Direct => write!(f, "direct"),
             AuthMode::Login => write!(f, "login"),
+            AuthMode::Jwt => write!(f, "jwt"),
+            AuthMode::AwsSigV4 => write!(f, "aws_sigv4"),
+            AuthMode::Hmac => write!(f, "hmac"),
+            AuthMode::Cookie => write!(f, "cookie"),
+            AuthMode::Custom => write!(f, "custom"),
         }
     }
 }
@@ -43,6 +66,11 @@ impl std::str::FromStr for AuthMode {
         match s.to_lowercase().as_str() {
             "direct" => Ok(AuthMode::Direct),
             "login" => Ok(AuthMode::Login),
+            "jwt" => Ok(AuthMode::Jwt),
+            "aws_sigv4" | "sigv4" => Ok(AuthMode::AwsSigV4),
+            "hmac" => Ok(AuthMode::Hmac),
+            "cookie" | "cookie_session" => Ok(AuthMode::Cookie),
+            "custom" => Ok(AuthMode::Custom),
             _ => Err(format!("Unknown authentication mode: {}", s)),
         }
     }
@@ -61,6 +89,12 @@ pub enum DirectAuthType {
     ApiKey,
     /// Custom headers authentication
     CustomHeaders,
+    /// HTTP Digest authentication (RFC 7616), answering the server's challenge per request
+    Digest,
+    /// NTLM handshake with the `NTLM` scheme (requires the `ntlm` feature)
+    Ntlm,
+    /// NTLM handshake with the `Negotiate` scheme (requires the `ntlm` feature)
+    Negotiate,
 }
 
 impl Default for DirectAuthType {
@@ -77,6 +111,9 @@ impl std::fmt::Display for DirectAuthType {
             DirectAuthType::Basic => write!(f, "basic"),
             DirectAuthType::ApiKey => write!(f, "apikey"),
             DirectAuthType::CustomHeaders => write!(f, "customheaders"),
+            DirectAuthType::Digest => write!(f, "digest"),
+            DirectAuthType::Ntlm => write!(f, "ntlm"),
+            DirectAuthType::Negotiate => write!(f, "negotiate"),
         }
     }
 }
@@ -91,6 +128,9 @@ impl std::str::FromStr for DirectAuthType {
             "basic" => Ok(DirectAuthType::Basic),
             "apikey" => Ok(DirectAuthType::ApiKey),
             "customheaders" => Ok(DirectAuthType::CustomHeaders),
+            "digest" => Ok(DirectAuthType::Digest),
+            "ntlm" => Ok(DirectAuthType::Ntlm),
+            "negotiate" => Ok(DirectAuthType::Negotiate),
             _ => Err(format!("Unknown direct authentication type: {}", s)),
         }
     }
@@ -160,6 +200,159 @@ impl Default for HttpMethod {
     }
 }
 
+/// Per-request timeout, retry behaviour and token scope
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RequestPolicy {
+    /// Overall request timeout; the client default applies when unset
+    pub timeout: Option<Duration>,
+    /// Delay before each retry; its length is the number of retries allowed
+    pub retry_delays: Vec<Duration>,
+    /// Status codes that trigger a retry; empty retries 429 and any 5xx response
+    pub retry_on: Vec<u16>,
+    /// Wait a random 50% to 100% of each retry delay
+    pub jitter: bool,
+    /// Time after which no further retry is started; `DEFAULT_MAX_RETRY_ELAPSED` when unset
+    pub max_retry_elapsed: Option<Duration>,
+    /// Also retry connection errors and timeouts of POST and PATCH requests, which the API may
+    /// have applied already
+    pub retry_non_idempotent: bool,
+    /// Time a successful GET response is served from the response cache; `None` does not cache it
+    pub cache_ttl: Option<Duration>,
+    /// Scopes and audience the request's token must be issued for; empty uses the configured token
+    pub token_scope: TokenScope,
+    /// Compression of the request body; `None` sends it as it is
+    pub compression: Option<BodyCompression>,
+    /// Receiver of a successful JSON array or NDJSON body as it arrives; `None` reads it whole
+    pub body_sink: Option<BodySink>,
+}
+
+/// Takes the body of a successful response chunk by chunk as it arrives, instead of it being read
+/// into the response; bodies it does not accept are read whole as usual
+#[derive(Debug, Clone)]
+pub struct BodySink {
+    pub sender: mpsc::Sender<Vec<u8>>,
+    /// Also take NDJSON bodies, and bodies of a generic media type, besides JSON arrays
+    pub ndjson: bool,
+}
+
+impl BodySink {
+    /// Whether a body of `media_type` whose first non-blank byte is `first` is forwarded
+    pub fn accepts(&self, media_type: Option<&str>, first: u8) -> bool {
+        match media_type {
+            None => first == b'[' || self.ndjson,
+            Some(media_type) if media_type == "application/json" || media_type.ends_with("+json") => first == b'[',
+            Some(media_type) if is_ndjson(media_type) || matches!(media_type, "text/plain" | "application/octet-stream") => {
+                self.ndjson
+            }
+            Some(_) => false,
+        }
+    }
+}
+
+impl PartialEq for BodySink {
+    fn eq(&self, other: &Self) -> bool {
+        self.sender.same_channel(&other.sender) && self.ndjson == other.ndjson
+    }
+}
+
+/// Media types of newline-delimited JSON
+pub fn is_ndjson(media_type: &str) -> bool {
+    matches!(
+        media_type,
+        "application/x-ndjson" | "application/ndjson" | "application/jsonl" | "application/x-jsonlines"
+    )
+}
+
+/// Encoding request bodies of at least `min_bytes` are compressed with
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BodyCompression {
+    pub encoding: ContentEncoding,
+    pub min_bytes: usize,
+}
+
+/// Scope set and audience of an access token; requests needing the same ones share a token
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct TokenScope {
+    /// Scopes, sorted and without duplicates
+    pub scopes: Vec<String>,
+    pub audience: Option<String>,
+}
+
+impl TokenScope {
+    pub fn new(mut scopes: Vec<String>, audience: Option<String>) -> Self {
+        scopes.sort();
+        scopes.dedup();
+        Self { scopes, audience }
+    }
+    
+    /// Whether the request has no scope requirement
+    pub fn is_empty(&self) -> bool {
+        self.scopes.is_empty() && self.audience.is_none()
+    }
+}
+
+impl RequestPolicy {
+    /// Whether a response with the given status should be retried
+    pub fn should_retry_status(&self, status: u16) -> bool {
+        if self.retry_on.is_empty() {
+            status == 429 || (500..=599).contains(&status)
+        } else {
+            self.retry_on.contains(&status)
+        }
+    }
+}
+
+/// Tokens sent outside the request headers, as `(key, token)` pairs
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AuthParams {
+    /// Query parameters appended to the URL
+    pub query: Vec<(String, String)>,
+    /// Cookies added to the `Cookie` header
+    pub cookies: Vec<(String, String)>,
+    /// Fields added to JSON or form bodies
+    pub body: Vec<(String, String)>,
+}
+
+impl AuthParams {
+    /// Whether there is nothing to add
+    pub fn is_empty(&self) -> bool {
+        self.query.is_empty() && self.cookies.is_empty() && self.body.is_empty()
+    }
+}
+
+/// Body of an API request
+#[derive(Debug, Clone, PartialEq)]
+pub enum RequestBody {
+    Empty,
+    Json(serde_json::Value),
+    /// multipart/form-data fields, used for file uploads
+    Multipart(Vec<MultipartField>),
+    /// Body sent as-is with its content type, e.g. a SOAP envelope
+    Raw { content_type: String, body: Vec<u8> },
+}
+
+impl Default for RequestBody {
+    fn default() -> Self {
+        RequestBody::Empty
+    }
+}
+
+/// Single field of a multipart/form-data request body
+#[derive(Debug, Clone, PartialEq)]
+pub enum MultipartField {
+    /// Plain text form field
+    Text { name: String, value: String },
+    /// File part with its raw content and, when known, its media type
+    File { name: String, file_name: String, content_type: Option<String>, content: Vec<u8> },
+}
+
+impl HttpMethod {
+    /// Whether sending the request twice has the same effect as sending it once
+    pub fn is_idempotent(&self) -> bool {
+        matches!(self, HttpMethod::GET | HttpMethod::PUT | HttpMethod::DELETE)
+    }
+}
+
 impl std::fmt::Display for HttpMethod {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -345,8 +538,55 @@ pub trait AuthStrategy: Send + Sync {
     /// Get authentication headers
     async fn get_auth_headers(&self) -> Result<reqwest::header::HeaderMap, AuthError>;
     
+    /// Tokens sent as query parameters, cookies or body fields instead of headers
+    async fn get_auth_params(&self) -> Result<AuthParams, AuthError> {
+        Ok(AuthParams::default())
+    }
+    
     /// Login and get token at specific index
     async fn login_and_get_token(&self, token_index: usize) -> Result<String, AuthError>;
+    
+    /// Sign a fully built request; strategies that authenticate through headers leave it unchanged
+    fn sign_request(&self, _request: &mut reqwest::Request) -> Result<(), AuthError> {
+        Ok(())
+    }
+    
+    /// Inspect the response to an authenticated request, e.g. to keep the cookies it sets
+    fn observe_response(&self, _response: &reqwest::Response) {}
+    
+    /// Whether a response shows that the session ended even though its status is not 401,
+    /// e.g. a redirect to the login page
+    fn session_expired(&self, _response: &reqwest::Response) -> bool {
+        false
+    }
+    
+    /// Whether a response throttled the credential it was sent with and another one can be tried
+    fn credential_throttled(&self, _response: &reqwest::Response) -> bool {
+        false
+    }
+    
+    /// Forget the current session so the next request logs in again
+    fn clear_session(&self) {}
+    
+    /// When the current token expires; `None` before the first token and for strategies whose
+    /// credentials do not expire
+    async fn token_expires_at(&self) -> Option<std::time::Instant> {
+        None
+    }
+}
+
+/// Creates the strategy of a custom authentication scheme. Applications embedding mcp-any-rest
+/// register factories with `AuthServiceFactory::register_auth_strategy` and select them with
+/// `mode: custom` and `custom_config.strategy`.
+pub trait AuthStrategyFactory: Send + Sync {
+    /// Create the strategy for `config`; `client` carries the API's TLS settings for any
+    /// requests the strategy makes itself
+    fn create(
+        &self,
+        config: &CustomAuthConfig,
+        auth: &AuthConfig,
+        client: reqwest::Client,
+    ) -> Result<Box<dyn AuthStrategy>, AuthError>;
 }
 
 /// Direct authentication configuration
@@ -364,6 +604,8 @@ pub struct DirectAuthConfig {
     pub password: Option<String>,
     /// Custom headers (for CustomHeaders type)
     pub custom_headers: Option<HashMap<String, String>>,
+    /// API keys used in turn instead of `token` (for Token, Bearer, ApiKey types)
+    pub key_pool: Option<ApiKeyPoolConfig>,
 }
 
 impl Default for DirectAuthConfig {
@@ -375,6 +617,7 @@ impl Default for DirectAuthConfig {
             username: None,
             password: None,
             custom_headers: None,
+            key_pool: None,
         }
     }
 }
@@ -400,6 +643,12 @@ pub struct LoginAuthConfig {
     pub refresh_url: Option<String>,
     /// Token refresh method (optional, defaults to login method)
     pub refresh_method: Option<HttpMethod>,
+    /// OAuth2 grant settings (optional, replaces the configured body)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oauth2: Option<OAuth2Config>,
+    /// Refresh token location in responses and in refresh requests (optional)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<RefreshTokenConfig>,
 }
 
 /// Login request body configuration
@@ -416,7 +665,7 @@ pub struct LoginRequestBody {
 pub struct TokenExtractionItem {
     /// Source location where to extract the token from
     pub source_location: TokenLocation,
-    /// Key to extract the token from the source
+    /// Key to extract the token from the source (a dot path or JSONPath for JSON bodies)
     pub source_key: String,
     /// Token format
     pub format: TokenFormat,
@@ -458,12 +707,30 @@ pub struct AuthConfig {
     pub direct_config: Option<DirectAuthConfig>,
     /// Login-based authentication configuration (if mode is Login)
     pub login_config: Option<LoginAuthConfig>,
+    /// JWT authentication configuration (if mode is Jwt)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jwt_config: Option<JwtAuthConfig>,
+    /// AWS SigV4 signing configuration (if mode is AwsSigV4)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aws_sigv4_config: Option<AwsSigV4Config>,
+    /// HMAC signing configuration (if mode is Hmac)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hmac_config: Option<HmacAuthConfig>,
+    /// Cookie session configuration (if mode is Cookie)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cookie_config: Option<CookieSessionConfig>,
+    /// Registered custom strategy and its settings (if mode is Custom)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_config: Option<CustomAuthConfig>,
     /// Token expiry time in seconds
     pub token_expiry: u64,
     /// Refresh buffer time in seconds
     pub refresh_buffer: u64,
     /// Maximum retry attempts
     pub max_retry_attempts: u32,
+    /// Response body texts that mark a rejected token, triggering a new login and a retry
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reauth_markers: Vec<String>,
 }
 
 impl Default for AuthConfig {
@@ -472,9 +739,15 @@ impl Default for AuthConfig {
             mode: AuthMode::Direct,
             direct_config: Some(DirectAuthConfig::default()),
             login_config: None,
+            jwt_config: None,
+            aws_sigv4_config: None,
+            hmac_config: None,
+            cookie_config: None,
+            custom_config: None,
             token_expiry: 3600, // 1 hour
             refresh_buffer: 300, // 5 minutes
             max_retry_attempts: 3,
+            reauth_markers: Vec::new(),
         }
     }
 }
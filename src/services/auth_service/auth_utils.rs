@@ -26,6 +26,7 @@
 use log::{debug, warn};
 use rmcp::ErrorData as McpError;
 use serde_json::Value;
+use crate::config::redact::redact_value;
 
 /// Validate authentication parameters
 pub fn validate_auth_params(account: &str, password: &str) -> Result<(), McpError> {
@@ -59,7 +60,9 @@ pub fn parse_auth_response(response: &Value) -> Result<String, McpError> {
         }
         Ok(token.to_string())
     } else {
-        warn!("Authentication response missing token field: {:?}", response);
+        let mut redacted = response.clone();
+        redact_value(&mut redacted);
+        warn!("Authentication response missing token field: {}", redacted);
         Err(McpError::internal_error(
             "Authentication response missing token field",
             None,
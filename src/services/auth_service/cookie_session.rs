@@ -0,0 +1,134 @@
+//! Session cookies for MCP-ANY-REST
+//!
+//! Cookie-session authentication keeps the cookies set by the login form and by later API
+//! responses and sends them back with every request to the same host. The store is installed as
+//! the HTTP client's cookie provider, so cookies set on redirects during the login are kept too.
+
+use chrono::{DateTime, Utc};
+use reqwest::cookie::CookieStore;
+use reqwest::header::{HeaderMap, HeaderValue, SET_COOKIE};
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+use url::Url;
+
+/// Cookies of the current session by domain and name
+#[derive(Debug, Default)]
+pub struct SessionCookieStore {
+    cookies: RwLock<BTreeMap<String, BTreeMap<String, String>>>,
+}
+
+impl SessionCookieStore {
+    /// Keep the cookies set by a response from `url`
+    pub fn store(&self, headers: &HeaderMap, url: &Url) {
+        self.set_cookies(&mut headers.get_all(SET_COOKIE).iter(), url);
+    }
+
+    /// `Cookie` header value for a request to `url`
+    pub fn header(&self, url: &Url) -> Option<String> {
+        let host = url.host_str()?;
+        let cookies = self.cookies.read().unwrap();
+        let pairs: Vec<String> = cookies
+            .iter()
+            .filter(|(domain, _)| host == domain.as_str() || host.ends_with(&format!(".{}", domain)))
+            .flat_map(|(_, cookies)| cookies.iter().map(|(name, value)| format!("{}={}", name, value)))
+            .collect();
+        (!pairs.is_empty()).then(|| pairs.join("; "))
+    }
+
+    /// Whether a cookie called `name` is set for any domain
+    pub fn contains(&self, name: &str) -> bool {
+        self.cookies.read().unwrap().values().any(|cookies| cookies.contains_key(name))
+    }
+
+    /// Whether no cookie is set
+    pub fn is_empty(&self) -> bool {
+        self.cookies.read().unwrap().values().all(BTreeMap::is_empty)
+    }
+
+    /// Forget all cookies
+    pub fn clear(&self) {
+        self.cookies.write().unwrap().clear();
+    }
+
+    fn set_cookie(&self, set_cookie: &str, url: &Url) {
+        let mut attributes = set_cookie.split(';').map(str::trim);
+        let Some((name, value)) = attributes.next().and_then(|pair| pair.split_once('=')) else {
+            return;
+        };
+        let (name, value) = (name.trim(), value.trim().trim_matches('"'));
+        if name.is_empty() {
+            return;
+        }
+
+        let mut domain = url.host_str().unwrap_or_default().to_string();
+        let mut expired = false;
+        for attribute in attributes {
+            let (key, attribute_value) = attribute.split_once('=').unwrap_or((attribute, ""));
+            match key.trim().to_ascii_lowercase().as_str() {
+                "domain" if !attribute_value.trim().is_empty() => {
+                    domain = attribute_value.trim().trim_start_matches('.').to_ascii_lowercase();
+                }
+                "max-age" => expired = attribute_value.trim().parse::<i64>().map(|age| age <= 0).unwrap_or(false),
+                "expires" => {
+                    if let Ok(expires) = DateTime::parse_from_rfc2822(attribute_value.trim()) {
+                        expired = expired || expires.with_timezone(&Utc) <= Utc::now();
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut cookies = self.cookies.write().unwrap();
+        let domain_cookies = cookies.entry(domain).or_default();
+        if expired {
+            domain_cookies.remove(name);
+        } else {
+            domain_cookies.insert(name.to_string(), value.to_string());
+        }
+    }
+}
+
+impl CookieStore for SessionCookieStore {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        for header in cookie_headers {
+            if let Ok(set_cookie) = header.to_str() {
+                self.set_cookie(set_cookie, url);
+            }
+        }
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        self.header(url).and_then(|header| HeaderValue::from_str(&header).ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_keeps_updates_and_expires_cookies() {
+        let store = SessionCookieStore::default();
+        let url = Url::parse("https://app.example.com/login").unwrap();
+        let mut headers = HeaderMap::new();
+        headers.append(SET_COOKIE, HeaderValue::from_static("SESSION=abc; Path=/; HttpOnly"));
+        headers.append(SET_COOKIE, HeaderValue::from_static("lang=en; Domain=.example.com"));
+        headers.append(SET_COOKIE, HeaderValue::from_static("old=1; Expires=Thu, 01 Jan 1970 00:00:00 GMT"));
+        store.store(&headers, &url);
+
+        assert!(store.contains("SESSION"));
+        assert!(!store.contains("old"));
+        assert_eq!(store.header(&url).as_deref(), Some("SESSION=abc; lang=en"));
+        let other = Url::parse("https://api.example.com/").unwrap();
+        assert_eq!(store.header(&other).as_deref(), Some("lang=en"));
+
+        let mut headers = HeaderMap::new();
+        headers.append(SET_COOKIE, HeaderValue::from_static("SESSION=; Max-Age=0"));
+        store.store(&headers, &url);
+        assert!(!store.contains("SESSION"));
+
+        store.clear();
+        assert!(store.is_empty());
+        assert!(store.header(&url).is_none());
+    }
+}
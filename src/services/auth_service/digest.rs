@@ -0,0 +1,213 @@
+//! HTTP Digest authentication for MCP-ANY-REST
+//!
+//! Digest authentication (RFC 7616) answers the server's `WWW-Authenticate: Digest` challenge with
+//! a hash of the credentials, the nonce and the request instead of sending the password. The first
+//! request gets a 401 with the challenge; the retry and later requests reuse its nonce with an
+//! increasing nonce count until the server issues a new one.
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::OsRng;
+use md5::Md5;
+use reqwest::header::{HeaderMap, WWW_AUTHENTICATE};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+/// Digest challenge sent by the server
+#[derive(Debug, Clone, PartialEq)]
+pub struct DigestChallenge {
+    pub realm: String,
+    pub nonce: String,
+    pub opaque: Option<String>,
+    /// `MD5`, `MD5-sess`, `SHA-256` or `SHA-256-sess`, as sent by the server
+    pub algorithm: String,
+    /// `auth` when the server offers it; `None` for RFC 2069 servers without `qop`
+    pub qop: Option<String>,
+    /// Requests answered with this nonce so far
+    pub nonce_count: u32,
+}
+
+impl DigestChallenge {
+    /// Supported challenge of a 401 response, preferring SHA-256 over MD5
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        headers
+            .get_all(WWW_AUTHENTICATE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .filter_map(Self::parse)
+            .max_by_key(|challenge| challenge.is_sha256())
+    }
+
+    /// Parse a `WWW-Authenticate` value; `None` for other schemes and unsupported algorithms or qop
+    pub fn parse(header: &str) -> Option<Self> {
+        let (scheme, params) = header.trim().split_once(' ')?;
+        if !scheme.eq_ignore_ascii_case("digest") {
+            return None;
+        }
+        let params = auth_params(params);
+        let algorithm = params.get("algorithm").cloned().unwrap_or_else(|| "MD5".to_string());
+        if !matches!(
+            algorithm.to_ascii_uppercase().as_str(),
+            "MD5" | "MD5-SESS" | "SHA-256" | "SHA-256-SESS"
+        ) {
+            return None;
+        }
+        // Only qop=auth is supported; auth-int would need the request body hashed
+        let qop = match params.get("qop") {
+            Some(qop) => Some(qop.split(',').map(str::trim).find(|qop| qop.eq_ignore_ascii_case("auth"))?.to_string()),
+            None => None,
+        };
+        Some(Self {
+            realm: params.get("realm").cloned().unwrap_or_default(),
+            nonce: params.get("nonce")?.clone(),
+            opaque: params.get("opaque").cloned(),
+            algorithm,
+            qop,
+            nonce_count: 0,
+        })
+    }
+
+    /// `Authorization` header for the next request to `uri` (path and query)
+    pub fn authorization(&mut self, username: &str, password: &str, method: &str, uri: &str) -> String {
+        self.nonce_count += 1;
+        let nonce_count = format!("{:08x}", self.nonce_count);
+        let mut cnonce = [0u8; 16];
+        OsRng.fill_bytes(&mut cnonce);
+        let cnonce: String = cnonce.iter().map(|byte| format!("{:02x}", byte)).collect();
+        let response = self.response(username, password, method, uri, &nonce_count, &cnonce);
+
+        let mut header = format!(
+            r#"Digest username="{}", realm="{}", nonce="{}", uri="{}", algorithm={}, response="{}""#,
+            quote(username),
+            quote(&self.realm),
+            quote(&self.nonce),
+            quote(uri),
+            self.algorithm,
+            response
+        );
+        if let Some(qop) = &self.qop {
+            header.push_str(&format!(r#", qop={}, nc={}, cnonce="{}""#, qop, nonce_count, cnonce));
+        }
+        if let Some(opaque) = &self.opaque {
+            header.push_str(&format!(r#", opaque="{}""#, quote(opaque)));
+        }
+        header
+    }
+
+    /// `response` value of the authorization (RFC 7616 section 3.4.1)
+    pub fn response(&self, username: &str, password: &str, method: &str, uri: &str, nonce_count: &str, cnonce: &str) -> String {
+        let mut ha1 = self.hash(&format!("{}:{}:{}", username, self.realm, password));
+        if self.algorithm.to_ascii_uppercase().ends_with("-SESS") {
+            ha1 = self.hash(&format!("{}:{}:{}", ha1, self.nonce, cnonce));
+        }
+        let ha2 = self.hash(&format!("{}:{}", method, uri));
+        match &self.qop {
+            Some(qop) => self.hash(&format!("{}:{}:{}:{}:{}:{}", ha1, self.nonce, nonce_count, cnonce, qop, ha2)),
+            None => self.hash(&format!("{}:{}:{}", ha1, self.nonce, ha2)),
+        }
+    }
+
+    fn is_sha256(&self) -> bool {
+        self.algorithm.to_ascii_uppercase().starts_with("SHA-256")
+    }
+
+    fn hash(&self, data: &str) -> String {
+        let digest = if self.is_sha256() {
+            Sha256::digest(data.as_bytes()).to_vec()
+        } else {
+            Md5::digest(data.as_bytes()).to_vec()
+        };
+        digest.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+}
+
+/// Parse `key=token, key="quoted, string"` parameters; keys are lowercased
+fn auth_params(input: &str) -> HashMap<String, String> {
+    let mut params = HashMap::new();
+    let mut rest = input.trim();
+    while let Some((key, after)) = rest.split_once('=') {
+        let key = key.trim().trim_start_matches(',').trim().to_ascii_lowercase();
+        let after = after.trim_start();
+        let (value, remaining) = match after.strip_prefix('"') {
+            Some(quoted) => {
+                let mut value = String::new();
+                let mut end = quoted.len();
+                let mut chars = quoted.char_indices();
+                while let Some((index, c)) = chars.next() {
+                    match c {
+                        '\\' => {
+                            if let Some((_, escaped)) = chars.next() {
+                                value.push(escaped);
+                            }
+                        }
+                        '"' => {
+                            end = index + 1;
+                            break;
+                        }
+                        _ => value.push(c),
+                    }
+                }
+                (value, &quoted[end..])
+            }
+            None => {
+                let end = after.find(',').unwrap_or(after.len());
+                (after[..end].trim().to_string(), &after[end..])
+            }
+        };
+        params.insert(key, value);
+        rest = remaining.trim_start().trim_start_matches(',').trim_start();
+    }
+    params
+}
+
+/// Escape a value for a quoted string
+fn quote(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    // Example of RFC 7616 section 3.9.1
+    const CHALLENGE: &str = r#"Digest realm="http-auth@example.org", qop="auth, auth-int", algorithm=SHA-256, nonce="7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v", opaque="FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS""#;
+    const CNONCE: &str = "f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ";
+
+    #[test]
+    fn test_digest_response_matches_rfc_7616_example() {
+        let sha256 = DigestChallenge::parse(CHALLENGE).unwrap();
+        assert_eq!(sha256.qop.as_deref(), Some("auth"));
+        assert_eq!(sha256.opaque.as_deref(), Some("FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS"));
+        assert_eq!(
+            sha256.response("Mufasa", "Circle of Life", "GET", "/dir/index.html", "00000001", CNONCE),
+            "753927fa0e85d155564e2e272a28d1802ca10daf4496794697cf8db5856cb6c1"
+        );
+
+        let md5 = DigestChallenge::parse(&CHALLENGE.replace("algorithm=SHA-256", "algorithm=MD5")).unwrap();
+        assert_eq!(
+            md5.response("Mufasa", "Circle of Life", "GET", "/dir/index.html", "00000001", CNONCE),
+            "8ca523f5e9506fed4657c9700eebdbec"
+        );
+    }
+
+    #[test]
+    fn test_challenge_selection_and_authorization() {
+        let mut headers = HeaderMap::new();
+        headers.append(WWW_AUTHENTICATE, HeaderValue::from_static(r#"Basic realm="device""#));
+        headers.append(WWW_AUTHENTICATE, HeaderValue::from_str(&CHALLENGE.replace("SHA-256", "MD5")).unwrap());
+        headers.append(WWW_AUTHENTICATE, HeaderValue::from_static(CHALLENGE));
+        let mut challenge = DigestChallenge::from_headers(&headers).unwrap();
+        assert_eq!(challenge.algorithm, "SHA-256");
+
+        let first = challenge.authorization("Mufasa", "Circle of Life", "GET", "/dir/index.html?x=1");
+        assert!(first.starts_with(r#"Digest username="Mufasa", realm="http-auth@example.org""#));
+        assert!(first.contains(r#"uri="/dir/index.html?x=1""#));
+        assert!(first.contains("qop=auth, nc=00000001"));
+        let second = challenge.authorization("Mufasa", "Circle of Life", "GET", "/dir/index.html");
+        assert!(second.contains("nc=00000002"));
+
+        assert!(DigestChallenge::parse(r#"Digest realm="x", nonce="n", qop="auth-int""#).is_none());
+        assert!(DigestChallenge::parse(r#"Digest realm="x", nonce="n", algorithm=SHA-512-256"#).is_none());
+        assert_eq!(DigestChallenge::parse(r#"Digest realm="x", nonce="n""#).unwrap().qop, None);
+    }
+}
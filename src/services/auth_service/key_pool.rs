@@ -0,0 +1,155 @@
+//! Rotating API key pools for MCP-ANY-REST
+//!
+//! A pool spreads requests over several API keys: `round_robin` uses the keys in turn, `failover`
+//! stays on one key until the API throttles it. A key answered with 429 is left out until its
+//! cooldown (`Retry-After` or `cooldown_secs`) ends. The rotation position and cooldowns are kept
+//! per process, so the authentication services rebuilt by a configuration reload carry on where the
+//! previous ones stopped.
+
+use super::auth_strategy::{ApiKeyPoolConfig, AuthError, KeyRotation};
+use crate::config::credentials::resolve_credential_ref;
+use crate::config::token_cache::cache_key;
+use log::warn;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Rotation state of every pool of the process
+static STATE: Mutex<Option<PoolState>> = Mutex::new(None);
+
+#[derive(Default)]
+struct PoolState {
+    /// Position of the next key, by pool
+    cursors: HashMap<String, usize>,
+    /// End of the cooldown of throttled keys, by key reference
+    cooldowns: HashMap<String, Instant>,
+    /// Key used by the last request, by pool
+    last_used: HashMap<String, usize>,
+}
+
+/// Pool of API keys of a direct authentication configuration
+#[derive(Debug, Clone)]
+pub struct KeyPool {
+    /// Identifies the pool across configuration reloads
+    id: String,
+    config: ApiKeyPoolConfig,
+}
+
+impl KeyPool {
+    /// Pool of the configured keys; `None` without keys
+    pub fn new(config: &ApiKeyPoolConfig) -> Option<Self> {
+        if config.api_keys.is_empty() {
+            return None;
+        }
+        Some(Self {
+            id: cache_key(&config.api_keys.join("\n")),
+            config: config.clone(),
+        })
+    }
+
+    /// Key the next request would use, without moving the rotation
+    pub fn current(&self) -> Result<String, AuthError> {
+        let index = with_state(|state| self.available(state, Instant::now()));
+        self.resolve(index)
+    }
+
+    /// Key for the next request; round-robin pools move on to the following key
+    pub fn next(&self) -> Result<String, AuthError> {
+        let index = with_state(|state| {
+            let index = self.available(state, Instant::now());
+            if self.config.rotation == KeyRotation::RoundRobin {
+                state.cursors.insert(self.id.clone(), index + 1);
+            }
+            state.last_used.insert(self.id.clone(), index);
+            index
+        });
+        self.resolve(index)
+    }
+
+    /// Leave out the key of the last request for `retry_after` (or `cooldown_secs`) after a 429.
+    /// Returns whether another key is available for a retry
+    pub fn throttled(&self, retry_after: Option<Duration>) -> bool {
+        let cooldown = retry_after.unwrap_or(Duration::from_secs(self.config.cooldown_secs));
+        with_state(|state| {
+            let now = Instant::now();
+            let index = state.last_used.get(&self.id).copied().unwrap_or(0) % self.config.api_keys.len();
+            state.cooldowns.insert(self.config.api_keys[index].clone(), now + cooldown);
+            // Failover pools switch to the following key for good
+            if self.config.rotation == KeyRotation::Failover {
+                state.cursors.insert(self.id.clone(), index + 1);
+            }
+            let available = self.config.api_keys.iter()
+                .any(|key| state.cooldowns.get(key).filter(|until| **until > now).is_none());
+            if !available {
+                warn!("All {} keys of the API key pool are throttled", self.config.api_keys.len());
+            }
+            available
+        })
+    }
+
+    /// Index of the key at the cursor or after it that is not cooling down; when all are, the one
+    /// available first
+    fn available(&self, state: &PoolState, now: Instant) -> usize {
+        let len = self.config.api_keys.len();
+        let cursor = state.cursors.get(&self.id).copied().unwrap_or(0);
+        let until = |index: usize| state.cooldowns.get(&self.config.api_keys[index]).copied();
+        (0..len)
+            .map(|offset| (cursor + offset) % len)
+            .find(|index| until(*index).filter(|until| *until > now).is_none())
+            .or_else(|| (0..len).min_by_key(|index| until(*index)))
+            .unwrap_or(0)
+    }
+
+    fn resolve(&self, index: usize) -> Result<String, AuthError> {
+        resolve_credential_ref(&self.config.api_keys[index])
+            .map_err(|e| AuthError::ConfigurationError(format!("Failed to read API key {} of the pool: {}", index + 1, e)))
+    }
+}
+
+fn with_state<T>(f: impl FnOnce(&mut PoolState) -> T) -> T {
+    let mut state = STATE.lock().unwrap();
+    f(state.get_or_insert_with(PoolState::default))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(keys: &[&str], rotation: KeyRotation) -> KeyPool {
+        KeyPool::new(&ApiKeyPoolConfig {
+            api_keys: keys.iter().map(|key| key.to_string()).collect(),
+            rotation,
+            cooldown_secs: 60,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_round_robin_skips_throttled_keys() {
+        let keys = pool(&["rr-a", "rr-b", "rr-c"], KeyRotation::RoundRobin);
+        assert_eq!(keys.current().unwrap(), "rr-a");
+        assert_eq!(keys.next().unwrap(), "rr-a");
+        assert_eq!(keys.next().unwrap(), "rr-b");
+        assert!(keys.throttled(None));
+        assert_eq!(keys.next().unwrap(), "rr-c");
+        assert_eq!(keys.next().unwrap(), "rr-a");
+        assert_eq!(keys.next().unwrap(), "rr-c");
+
+        // A pool rebuilt from the same keys, e.g. after a reload, continues the rotation
+        let reloaded = pool(&["rr-a", "rr-b", "rr-c"], KeyRotation::RoundRobin);
+        assert_eq!(reloaded.next().unwrap(), "rr-a");
+    }
+
+    #[test]
+    fn test_failover_stays_on_a_key_until_throttled() {
+        let keys = pool(&["fo-a", "fo-b"], KeyRotation::Failover);
+        assert_eq!(keys.next().unwrap(), "fo-a");
+        assert_eq!(keys.next().unwrap(), "fo-a");
+        assert!(keys.throttled(Some(Duration::from_secs(30))));
+        assert_eq!(keys.next().unwrap(), "fo-b");
+        assert_eq!(keys.next().unwrap(), "fo-b");
+        assert!(!keys.throttled(None));
+        // With every key throttled, the one available first is used
+        assert_eq!(keys.next().unwrap(), "fo-a");
+    }
+}
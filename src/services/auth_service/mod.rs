@@ -31,11 +31,18 @@
 //!         username: None,
 //!         password: None,
 //!         custom_headers: None,
+//!         key_pool: None,
 //!     }),
 //!     login_config: None,
+//!     jwt_config: None,
+//!     aws_sigv4_config: None,
+//!     hmac_config: None,
+//!     cookie_config: None,
+//!     custom_config: None,
 //!     token_expiry: 3600,
 //!     refresh_buffer: 300,
 //!     max_retry_attempts: 3,
+//!     reauth_markers: Vec::new(),
 //! };
 //!
 //! let auth_service = UnifiedAuthService::new(auth_config).unwrap();
@@ -55,17 +62,24 @@
 //! - Factory pattern for flexible strategy creation
 //! - Backward compatibility with existing code
 
+pub mod audit;
 pub mod auth_factory;
 pub mod auth_strategy;
 pub mod auth_utils;
+pub mod cookie_session;
+pub mod digest;
+pub mod key_pool;
+#[cfg(feature = "ntlm")]
+pub mod ntlm;
+pub mod oauth2_callback;
 pub mod unified_auth_service;
 
 // Re-export the unified authentication service and related types
-pub use unified_auth_service::{UnifiedAuthService, AuthService};
+pub use unified_auth_service::{ApiResponse, AuthCheckReport, SendError, TokenShape, UnifiedAuthService, AuthService};
 pub use auth_factory::{AuthServiceFactory, AuthServiceFactoryBuilder};
 pub use auth_strategy::{
-    AuthConfig, AuthStrategy, AuthMode, DirectAuthConfig, LoginAuthConfig,
-    HttpMethod, ResponseFormat, TokenExtraction, TokenExtractionItem, TokenLocation, TokenTargetLocation, 
+    AuthConfig, AuthParams, AuthStrategy, BodyCompression, BodySink, AuthStrategyFactory, AuthMode, CustomAuthConfig, DirectAuthConfig, JwtAuthConfig, LoginAuthConfig,
+    HttpMethod, MultipartField, RequestBody, RequestPolicy, ResponseFormat, TokenExtraction, TokenExtractionItem, TokenLocation, TokenScope, TokenTargetLocation, 
     AuthError, DirectAuthType, LoginAuthType, TokenFormat
 };
 
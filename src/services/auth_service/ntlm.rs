@@ -0,0 +1,223 @@
+//! NTLM authentication for MCP-ANY-REST (`ntlm` feature)
+//!
+//! Windows integrated authentication (IIS with `NTLM` or `Negotiate` providers) is a three message
+//! handshake on one connection: the client sends a NEGOTIATE message, the server answers 401 with a
+//! CHALLENGE, and the client repeats the request with an AUTHENTICATE message holding the NTLMv2
+//! response (MS-NLMP). Under `Negotiate` the same NTLM messages are sent, which servers accept as the
+//! NTLM fallback of SPNEGO; Kerberos tickets are not obtained.
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::OsRng;
+use hmac::{Hmac, Mac};
+use md4::{Digest, Md4};
+use md5::Md5;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SIGNATURE: &[u8; 8] = b"NTLMSSP\0";
+
+const NEGOTIATE_UNICODE: u32 = 0x0000_0001;
+const NEGOTIATE_OEM: u32 = 0x0000_0002;
+const REQUEST_TARGET: u32 = 0x0000_0004;
+const NEGOTIATE_NTLM: u32 = 0x0000_0200;
+const NEGOTIATE_ALWAYS_SIGN: u32 = 0x0000_8000;
+const NEGOTIATE_EXTENDED_SESSIONSECURITY: u32 = 0x0008_0000;
+const NEGOTIATE_TARGET_INFO: u32 = 0x0080_0000;
+const NEGOTIATE_128: u32 = 0x2000_0000;
+const NEGOTIATE_56: u32 = 0x8000_0000;
+
+/// Flags of the NEGOTIATE message
+const NEGOTIATE_FLAGS: u32 = NEGOTIATE_UNICODE
+    | NEGOTIATE_OEM
+    | REQUEST_TARGET
+    | NEGOTIATE_NTLM
+    | NEGOTIATE_ALWAYS_SIGN
+    | NEGOTIATE_EXTENDED_SESSIONSECURITY
+    | NEGOTIATE_128
+    | NEGOTIATE_56;
+
+/// Seconds between 1601-01-01 (Windows FILETIME epoch) and 1970-01-01
+const FILETIME_UNIX_OFFSET: u64 = 11_644_473_600;
+
+/// CHALLENGE message sent by the server
+#[derive(Debug, Clone, PartialEq)]
+pub struct NtlmChallenge {
+    pub flags: u32,
+    pub server_challenge: [u8; 8],
+    /// AV pairs describing the server, echoed in the NTLMv2 response
+    pub target_info: Vec<u8>,
+}
+
+impl NtlmChallenge {
+    /// Parse a CHALLENGE message
+    pub fn parse(message: &[u8]) -> Option<Self> {
+        if message.len() < 32 || &message[..8] != SIGNATURE || u32_at(message, 8)? != 2 {
+            return None;
+        }
+        let flags = u32_at(message, 20)?;
+        let mut server_challenge = [0u8; 8];
+        server_challenge.copy_from_slice(message.get(24..32)?);
+        let target_info = if flags & NEGOTIATE_TARGET_INFO != 0 && message.len() >= 48 {
+            let length = u16::from_le_bytes([message[40], message[41]]) as usize;
+            let offset = u32_at(message, 44)? as usize;
+            message.get(offset..offset + length)?.to_vec()
+        } else {
+            Vec::new()
+        };
+        Some(Self { flags, server_challenge, target_info })
+    }
+}
+
+/// NEGOTIATE message starting the handshake
+pub fn negotiate_message() -> Vec<u8> {
+    let mut message = Vec::with_capacity(32);
+    message.extend_from_slice(SIGNATURE);
+    message.extend_from_slice(&1u32.to_le_bytes());
+    message.extend_from_slice(&NEGOTIATE_FLAGS.to_le_bytes());
+    // Empty domain and workstation security buffers
+    message.extend_from_slice(&[0u8; 16]);
+    message
+}
+
+/// AUTHENTICATE message answering `challenge`; `username` may be `DOMAIN\user` or `user@domain`
+pub fn authenticate_message(challenge: &NtlmChallenge, username: &str, password: &str) -> Vec<u8> {
+    let (domain, user) = split_username(username);
+    let mut client_challenge = [0u8; 8];
+    OsRng.fill_bytes(&mut client_challenge);
+    let timestamp = (SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() + FILETIME_UNIX_OFFSET)
+        * 10_000_000;
+    let (lm_response, nt_response) =
+        ntlmv2_responses(challenge, domain, user, password, client_challenge, timestamp);
+
+    let flags = (challenge.flags & NEGOTIATE_FLAGS & !NEGOTIATE_OEM) | NEGOTIATE_UNICODE;
+    let fields = [lm_response, nt_response, utf16(domain), utf16(user), Vec::new(), Vec::new()];
+
+    // Header of 64 bytes: six security buffers and the flags, followed by the payload
+    let mut message = Vec::new();
+    message.extend_from_slice(SIGNATURE);
+    message.extend_from_slice(&3u32.to_le_bytes());
+    let mut offset = 64u32;
+    for field in &fields {
+        message.extend_from_slice(&(field.len() as u16).to_le_bytes());
+        message.extend_from_slice(&(field.len() as u16).to_le_bytes());
+        message.extend_from_slice(&offset.to_le_bytes());
+        offset += field.len() as u32;
+    }
+    message.extend_from_slice(&flags.to_le_bytes());
+    for field in &fields {
+        message.extend_from_slice(field);
+    }
+    message
+}
+
+/// LMv2 and NTLMv2 responses (MS-NLMP section 3.3.2)
+fn ntlmv2_responses(
+    challenge: &NtlmChallenge,
+    domain: &str,
+    user: &str,
+    password: &str,
+    client_challenge: [u8; 8],
+    timestamp: u64,
+) -> (Vec<u8>, Vec<u8>) {
+    let key = ntowf_v2(domain, user, password);
+
+    let mut blob = vec![1u8, 1, 0, 0, 0, 0, 0, 0];
+    blob.extend_from_slice(&timestamp.to_le_bytes());
+    blob.extend_from_slice(&client_challenge);
+    blob.extend_from_slice(&[0u8; 4]);
+    blob.extend_from_slice(&challenge.target_info);
+    blob.extend_from_slice(&[0u8; 4]);
+
+    let mut nt_response = hmac_md5(&key, &[&challenge.server_challenge, &blob]);
+    nt_response.extend_from_slice(&blob);
+    let mut lm_response = hmac_md5(&key, &[&challenge.server_challenge, &client_challenge]);
+    lm_response.extend_from_slice(&client_challenge);
+    (lm_response, nt_response)
+}
+
+/// NTLMv2 response key: HMAC-MD5 of the uppercased user and the domain, keyed with the MD4 password hash
+fn ntowf_v2(domain: &str, user: &str, password: &str) -> Vec<u8> {
+    let nt_hash = Md4::digest(utf16(password));
+    hmac_md5(&nt_hash, &[&utf16(&format!("{}{}", user.to_uppercase(), domain))])
+}
+
+fn hmac_md5(key: &[u8], parts: &[&[u8]]) -> Vec<u8> {
+    let mut mac = <Hmac<Md5> as Mac>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    for part in parts {
+        mac.update(part);
+    }
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Domain and user of `DOMAIN\user`; user principal names (`user@domain`) are sent with an empty domain
+fn split_username(username: &str) -> (&str, &str) {
+    username.split_once('\\').unwrap_or(("", username))
+}
+
+fn utf16(value: &str) -> Vec<u8> {
+    value.encode_utf16().flat_map(u16::to_le_bytes).collect()
+}
+
+fn u32_at(message: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(message.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    // Example of MS-NLMP section 4.2.4
+    fn challenge() -> NtlmChallenge {
+        let mut target_info = vec![2u8, 0, 12, 0];
+        target_info.extend(utf16("Domain"));
+        target_info.extend([1u8, 0, 12, 0]);
+        target_info.extend(utf16("Server"));
+        target_info.extend([0u8; 4]);
+        NtlmChallenge {
+            flags: 0xe28a_8233,
+            server_challenge: [0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef],
+            target_info,
+        }
+    }
+
+    #[test]
+    fn test_ntlmv2_responses_match_ms_nlmp_example() {
+        assert_eq!(hex(&ntowf_v2("Domain", "User", "Password")), "0c868a403bfd7a93a3001ef22ef02e3f");
+
+        let (lm_response, nt_response) = ntlmv2_responses(&challenge(), "Domain", "User", "Password", [0xaa; 8], 0);
+        assert_eq!(hex(&lm_response), "86c35097ac9cec102554764a57cccc19aaaaaaaaaaaaaaaa");
+        assert_eq!(hex(&nt_response[..16]), "68cd0ab851e51c96aabc927bebef6a1c");
+    }
+
+    #[test]
+    fn test_challenge_round_trip() {
+        let challenge = challenge();
+        let mut message = SIGNATURE.to_vec();
+        message.extend(2u32.to_le_bytes());
+        message.extend([0u8; 8]);
+        message.extend(challenge.flags.to_le_bytes());
+        message.extend(challenge.server_challenge);
+        message.extend([0u8; 8]);
+        message.extend((challenge.target_info.len() as u16).to_le_bytes());
+        message.extend((challenge.target_info.len() as u16).to_le_bytes());
+        message.extend(48u32.to_le_bytes());
+        message.extend(&challenge.target_info);
+        assert_eq!(NtlmChallenge::parse(&message), Some(challenge.clone()));
+        assert_eq!(NtlmChallenge::parse(&negotiate_message()), None);
+
+        let authenticate = authenticate_message(&challenge, "Domain\\User", "Password");
+        assert_eq!(&authenticate[..12], b"NTLMSSP\0\x03\0\0\0");
+        // Domain and user security buffers point at their UTF-16 values in the payload
+        let field = |index: usize| {
+            let length = u16::from_le_bytes([authenticate[12 + index * 8], authenticate[13 + index * 8]]) as usize;
+            let offset = u32_at(&authenticate, 16 + index * 8).unwrap() as usize;
+            authenticate[offset..offset + length].to_vec()
+        };
+        assert_eq!(field(2), utf16("Domain"));
+        assert_eq!(field(3), utf16("User"));
+        assert_eq!(field(1).len(), 16 + 28 + challenge.target_info.len() + 4);
+    }
+}
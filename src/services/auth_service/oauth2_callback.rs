@@ -0,0 +1,243 @@
+//! OAuth2 authorization code flow for MCP-ANY-REST
+//!
+//! APIs that only offer interactive consent (Google, Microsoft Graph) need a user to approve access
+//! in a browser once. The authorization URL is printed (and opened when possible), a temporary
+//! listener on the redirect URI captures the authorization code, and PKCE (RFC 7636) binds the code
+//! to this process.
+
+use super::auth_strategy::{AuthError, OAuth2Config};
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::OsRng;
+use base64::Engine;
+use log::{debug, info};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use url::Url;
+
+/// Redirect URI used when none is configured
+pub const DEFAULT_REDIRECT_URI: &str = "http://127.0.0.1:8976/callback";
+
+/// How long to wait for the user to complete the authorization
+const CALLBACK_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Largest callback request read from the browser
+const MAX_REQUEST_SIZE: usize = 16 * 1024;
+
+/// Authorization code captured by the callback listener
+#[derive(Debug, Clone)]
+pub struct AuthorizationCode {
+    pub code: String,
+    /// PKCE verifier to send with the token request
+    pub code_verifier: String,
+    /// Redirect URI to send with the token request
+    pub redirect_uri: String,
+}
+
+/// Parameters of an authorization request
+struct AuthorizationRequest {
+    url: Url,
+    state: String,
+    code_verifier: String,
+}
+
+/// Run the interactive authorization: print the authorization URL, wait for the browser to be
+/// redirected to the local callback listener and return the captured code
+pub async fn authorize(oauth2: &OAuth2Config) -> Result<AuthorizationCode, AuthError> {
+    let redirect_uri = oauth2.redirect_uri.clone().unwrap_or_else(|| DEFAULT_REDIRECT_URI.to_string());
+    let redirect = Url::parse(&redirect_uri)
+        .map_err(|e| AuthError::ConfigurationError(format!("Invalid OAuth2 redirect_uri '{}': {}", redirect_uri, e)))?;
+    let host = redirect.host_str().unwrap_or("127.0.0.1").to_string();
+    let port = redirect.port_or_known_default().ok_or_else(|| {
+        AuthError::ConfigurationError(format!("OAuth2 redirect_uri '{}' has no port", redirect_uri))
+    })?;
+    let request = authorization_request(oauth2, &redirect_uri)?;
+
+    // Listen before showing the URL so a fast redirect cannot be missed
+    let listener = TcpListener::bind((host.as_str(), port)).await
+        .map_err(|e| AuthError::NetworkError(format!("Failed to listen for the OAuth2 callback on {}:{}: {}", host, port, e)))?;
+    eprintln!("\nOpen the following URL in a browser to authorize access:\n\n  {}\n", request.url);
+    info!("Waiting for the OAuth2 authorization callback on {}", redirect_uri);
+    open_browser(request.url.as_str());
+
+    let params = tokio::time::timeout(CALLBACK_TIMEOUT, wait_for_callback(&listener, redirect.path())).await
+        .map_err(|_| AuthError::LoginFailed("Timed out waiting for the OAuth2 authorization callback".to_string()))??;
+
+    if params.get("state") != Some(&request.state) {
+        return Err(AuthError::LoginFailed("OAuth2 callback state does not match the authorization request".to_string()));
+    }
+    if let Some(error) = params.get("error") {
+        let detail = match params.get("error_description") {
+            Some(description) => format!("{}: {}", error, description),
+            None => error.clone(),
+        };
+        return Err(AuthError::LoginFailed(format!("OAuth2 authorization was denied: {}", detail)));
+    }
+    let code = params.get("code").cloned().ok_or_else(|| {
+        AuthError::TokenNotFound("OAuth2 callback carries no authorization code".to_string())
+    })?;
+
+    Ok(AuthorizationCode {
+        code,
+        code_verifier: request.code_verifier,
+        redirect_uri,
+    })
+}
+
+/// Build the authorization URL with a fresh state and PKCE challenge
+fn authorization_request(oauth2: &OAuth2Config, redirect_uri: &str) -> Result<AuthorizationRequest, AuthError> {
+    let authorization_url = oauth2.authorization_url.as_deref().ok_or_else(|| {
+        AuthError::ConfigurationError("OAuth2 authorization_code grant requires an authorization_url".to_string())
+    })?;
+    let mut url = Url::parse(authorization_url)
+        .map_err(|e| AuthError::ConfigurationError(format!("Invalid OAuth2 authorization_url '{}': {}", authorization_url, e)))?;
+
+    let state = random_string(16);
+    let code_verifier = random_string(32);
+    let code_challenge = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()));
+    {
+        let mut query = url.query_pairs_mut();
+        query
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &oauth2.client_id)
+            .append_pair("redirect_uri", redirect_uri)
+            .append_pair("state", &state)
+            .append_pair("code_challenge", &code_challenge)
+            .append_pair("code_challenge_method", "S256");
+        if !oauth2.scopes.is_empty() {
+            query.append_pair("scope", &oauth2.scopes.join(" "));
+        }
+        if let Some(audience) = &oauth2.audience {
+            query.append_pair("audience", audience);
+        }
+    }
+
+    Ok(AuthorizationRequest { url, state, code_verifier })
+}
+
+/// URL-safe random string from `bytes` random bytes
+fn random_string(bytes: usize) -> String {
+    let mut buffer = vec![0u8; bytes];
+    OsRng.fill_bytes(&mut buffer);
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(buffer)
+}
+
+/// Open the URL in the default browser; failures are ignored since the URL is also printed
+fn open_browser(url: &str) {
+    let result = if cfg!(target_os = "macos") {
+        std::process::Command::new("open").arg(url).spawn()
+    } else if cfg!(target_os = "windows") {
+        std::process::Command::new("cmd").args(["/C", "start", "", url]).spawn()
+    } else {
+        std::process::Command::new("xdg-open").arg(url).spawn()
+    };
+    if let Err(e) = result {
+        debug!("Could not open a browser: {}", e);
+    }
+}
+
+/// Accept connections until a request for `path` arrives and return its query parameters
+async fn wait_for_callback(listener: &TcpListener, path: &str) -> Result<HashMap<String, String>, AuthError> {
+    loop {
+        let (mut stream, _) = listener.accept().await
+            .map_err(|e| AuthError::NetworkError(format!("Failed to accept the OAuth2 callback: {}", e)))?;
+        let target = match read_request_target(&mut stream).await {
+            Some(target) => target,
+            None => continue,
+        };
+        let url = match Url::parse(&format!("http://localhost{}", target)) {
+            Ok(url) if url.path() == path => url,
+            // Browsers also ask for favicons and the like
+            _ => {
+                respond(&mut stream, "404 Not Found", "Not found").await;
+                continue;
+            }
+        };
+
+        let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+        let message = if params.contains_key("code") {
+            "Authorization complete. You can close this window."
+        } else {
+            "Authorization failed. Check the server log for details."
+        };
+        respond(&mut stream, "200 OK", message).await;
+        return Ok(params);
+    }
+}
+
+/// Read an HTTP request head and return the request target of a GET request
+async fn read_request_target(stream: &mut TcpStream) -> Option<String> {
+    let mut request = Vec::new();
+    let mut buffer = [0u8; 1024];
+    while !request.windows(4).any(|window| window == b"\r\n\r\n") && request.len() < MAX_REQUEST_SIZE {
+        let read = stream.read(&mut buffer).await.ok()?;
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&buffer[..read]);
+    }
+
+    let head = String::from_utf8_lossy(&request);
+    let mut request_line = head.lines().next()?.split_whitespace();
+    match (request_line.next(), request_line.next()) {
+        (Some("GET"), Some(target)) => Some(target.to_string()),
+        _ => None,
+    }
+}
+
+async fn respond(stream: &mut TcpStream, status: &str, message: &str) {
+    let body = format!("<!DOCTYPE html><html><body><p>{}</p></body></html>", message);
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_authorization_request_uses_pkce() {
+        let oauth2 = OAuth2Config {
+            client_id: "client".to_string(),
+            authorization_url: Some("https://accounts.example.com/authorize?prompt=consent".to_string()),
+            scopes: vec!["read".to_string(), "offline_access".to_string()],
+            ..OAuth2Config::default()
+        };
+        let request = authorization_request(&oauth2, DEFAULT_REDIRECT_URI).unwrap();
+        let params: HashMap<String, String> = request.url.query_pairs().into_owned().collect();
+
+        assert_eq!(params["prompt"], "consent");
+        assert_eq!(params["response_type"], "code");
+        assert_eq!(params["redirect_uri"], DEFAULT_REDIRECT_URI);
+        assert_eq!(params["scope"], "read offline_access");
+        assert_eq!(params["state"], request.state);
+        assert_eq!(params["code_challenge_method"], "S256");
+        let challenge = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(Sha256::digest(request.code_verifier.as_bytes()));
+        assert_eq!(params["code_challenge"], challenge);
+    }
+
+    #[tokio::test]
+    async fn test_callback_returns_query_parameters() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let browser = tokio::spawn(async move {
+            let client = reqwest::Client::new();
+            let favicon = client.get(format!("http://{}/favicon.ico", address)).send().await.unwrap();
+            assert_eq!(favicon.status(), 404);
+            client.get(format!("http://{}/callback?code=abc&state=xyz", address)).send().await.unwrap()
+        });
+
+        let params = wait_for_callback(&listener, "/callback").await.unwrap();
+        assert_eq!(params["code"], "abc");
+        assert_eq!(params["state"], "xyz");
+        assert_eq!(browser.await.unwrap().status(), 200);
+    }
+}
@@ -36,6 +36,7 @@ mod auth_factory_tests {
             username: None,
             password: None,
             custom_headers: None,
+            key_pool: None,
         };
         
         assert_eq!(config.token, Some("test-token".to_string()));
@@ -72,6 +73,8 @@ mod auth_factory_tests {
             },
             refresh_url: None,
             refresh_method: None,
+            oauth2: None,
+            refresh_token: None,
         };
         
         assert_eq!(config.url, "http://localhost/login");
@@ -93,15 +96,22 @@ mod auth_factory_tests {
             username: None,
             password: None,
             custom_headers: None,
+            key_pool: None,
         };
         
         let auth_config = AuthConfig {
             mode: AuthMode::Direct,
             direct_config: Some(direct_config),
             login_config: None,
+            jwt_config: None,
+            aws_sigv4_config: None,
+            hmac_config: None,
+            cookie_config: None,
+            custom_config: None,
             token_expiry: 3600,
             refresh_buffer: 300,
             max_retry_attempts: 3,
+            reauth_markers: Vec::new(),
         };
         
         let auth_service = UnifiedAuthService::new(auth_config).unwrap();
@@ -124,9 +134,15 @@ mod auth_factory_tests {
             mode: AuthMode::Direct,
             direct_config: Some(DirectAuthConfig::default()),
             login_config: None,
+            jwt_config: None,
+            aws_sigv4_config: None,
+            hmac_config: None,
+            cookie_config: None,
+            custom_config: None,
             token_expiry: 3600,
             refresh_buffer: 300,
             max_retry_attempts: 3,
+            reauth_markers: Vec::new(),
         };
         
         let auth_service = UnifiedAuthService::new(auth_config).unwrap();
@@ -144,9 +160,15 @@ mod auth_factory_tests {
             mode: AuthMode::Direct,
             direct_config: Some(DirectAuthConfig::default()),
             login_config: None,
+            jwt_config: None,
+            aws_sigv4_config: None,
+            hmac_config: None,
+            cookie_config: None,
+            custom_config: None,
             token_expiry: 3600,
             refresh_buffer: 300,
             max_retry_attempts: 3,
+            reauth_markers: Vec::new(),
         };
         
         let auth_service = UnifiedAuthService::new(auth_config).unwrap();
@@ -188,15 +210,23 @@ mod auth_factory_tests {
             },
             refresh_url: None,
             refresh_method: None,
+            oauth2: None,
+            refresh_token: None,
         };
         
         let auth_config = AuthConfig {
             mode: AuthMode::Login,
             direct_config: None,
             login_config: Some(login_auth_config),
+            jwt_config: None,
+            aws_sigv4_config: None,
+            hmac_config: None,
+            cookie_config: None,
+            custom_config: None,
             token_expiry: 3600,
             refresh_buffer: 300,
             max_retry_attempts: 3,
+            reauth_markers: Vec::new(),
         };
         
         let auth_service = UnifiedAuthService::new(auth_config).unwrap();
@@ -214,9 +244,15 @@ mod auth_factory_tests {
             mode: AuthMode::Direct,
             direct_config: Some(DirectAuthConfig::default()),
             login_config: None,
+            jwt_config: None,
+            aws_sigv4_config: None,
+            hmac_config: None,
+            cookie_config: None,
+            custom_config: None,
             token_expiry: 3600,
             refresh_buffer: 300,
             max_retry_attempts: 3,
+            reauth_markers: Vec::new(),
         };
         
         let auth_service = UnifiedAuthService::new(auth_config).unwrap();
@@ -234,15 +270,22 @@ mod auth_factory_tests {
             username: None,
             password: None,
             custom_headers: None,
+            key_pool: None,
         };
         
         let auth_config = AuthConfig {
             mode: AuthMode::Direct,
             direct_config: Some(config),
             login_config: None,
+            jwt_config: None,
+            aws_sigv4_config: None,
+            hmac_config: None,
+            cookie_config: None,
+            custom_config: None,
             token_expiry: 3600,
             refresh_buffer: 300,
             max_retry_attempts: 3,
+            reauth_markers: Vec::new(),
         };
         
         let factory = AuthServiceFactoryBuilder::new()
@@ -272,15 +315,22 @@ mod auth_factory_tests {
             username: None,
             password: None,
             custom_headers: Some(custom_headers),
+            key_pool: None,
         };
         
         let auth_config = AuthConfig {
             mode: AuthMode::Direct,
             direct_config: Some(config),
             login_config: None,
+            jwt_config: None,
+            aws_sigv4_config: None,
+            hmac_config: None,
+            cookie_config: None,
+            custom_config: None,
             token_expiry: 3600,
             refresh_buffer: 300,
             max_retry_attempts: 3,
+            reauth_markers: Vec::new(),
         };
         
         let factory = AuthServiceFactoryBuilder::new()
@@ -327,15 +377,23 @@ mod auth_factory_tests {
             },
             refresh_url: None,
             refresh_method: None,
+            oauth2: None,
+            refresh_token: None,
         };
         
         let auth_config = AuthConfig {
             mode: AuthMode::Login,
             direct_config: None,
             login_config: Some(login_auth_config),
+            jwt_config: None,
+            aws_sigv4_config: None,
+            hmac_config: None,
+            cookie_config: None,
+            custom_config: None,
             token_expiry: 3600,
             refresh_buffer: 300,
             max_retry_attempts: 3,
+            reauth_markers: Vec::new(),
         };
         
         let factory = AuthServiceFactoryBuilder::new()
@@ -373,15 +431,22 @@ mod auth_factory_tests {
             username: None,
             password: None,
             custom_headers: None,
+            key_pool: None,
         };
         
         let auth_config = AuthConfig {
             mode: AuthMode::Direct,
             direct_config: Some(config),
             login_config: None,
+            jwt_config: None,
+            aws_sigv4_config: None,
+            hmac_config: None,
+            cookie_config: None,
+            custom_config: None,
             token_expiry: 3600,
             refresh_buffer: 300,
             max_retry_attempts: 3,
+            reauth_markers: Vec::new(),
         };
         
         let factory = AuthServiceFactoryBuilder::new()
@@ -412,11 +477,18 @@ mod auth_factory_tests {
                 username: None,
                 password: None,
                 custom_headers: None,
+                key_pool: None,
             }),
             login_config: None,
+            jwt_config: None,
+            aws_sigv4_config: None,
+            hmac_config: None,
+            cookie_config: None,
+            custom_config: None,
             token_expiry: 3600,
             refresh_buffer: 300,
             max_retry_attempts: 3,
+            reauth_markers: Vec::new(),
         };
         
         let auth_service = UnifiedAuthService::new(invalid_auth_config).unwrap();
@@ -434,9 +506,15 @@ mod auth_factory_tests {
             mode: AuthMode::Direct,
             direct_config: Some(DirectAuthConfig::default()),
             login_config: None,
+            jwt_config: None,
+            aws_sigv4_config: None,
+            hmac_config: None,
+            cookie_config: None,
+            custom_config: None,
             token_expiry: 3600,
             refresh_buffer: 300,
             max_retry_attempts: 3,
+            reauth_markers: Vec::new(),
         };
         
         let valid_auth_service = UnifiedAuthService::new(valid_auth_config).unwrap();
@@ -450,9 +528,15 @@ mod auth_factory_tests {
             mode: AuthMode::Direct,
             direct_config: Some(DirectAuthConfig::default()),
             login_config: None,
+            jwt_config: None,
+            aws_sigv4_config: None,
+            hmac_config: None,
+            cookie_config: None,
+            custom_config: None,
             token_expiry: 3600,
             refresh_buffer: 300,
             max_retry_attempts: 3,
+            reauth_markers: Vec::new(),
         };
         
         let start = std::time::Instant::now();
@@ -473,9 +557,15 @@ mod auth_factory_tests {
             mode: AuthMode::Direct,
             direct_config: Some(DirectAuthConfig::default()),
             login_config: None,
+            jwt_config: None,
+            aws_sigv4_config: None,
+            hmac_config: None,
+            cookie_config: None,
+            custom_config: None,
             token_expiry: 3600,
             refresh_buffer: 300,
             max_retry_attempts: 3,
+            reauth_markers: Vec::new(),
         };
         
         let factory = Arc::new(AuthServiceFactoryBuilder::new()
@@ -510,4 +600,57 @@ mod auth_factory_tests {
             handle.await.unwrap();
         }
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_direct_auth_resolves_secret_references() {
+        use crate::config::secrets::{install_global_store, SecretKey, SecretStore};
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = SecretStore::open(temp_dir.path().join("secrets.json"), &SecretKey::generate()).unwrap();
+        store.set("auth_factory_test_token", "decrypted-token").unwrap();
+        install_global_store(Some(Arc::new(store)));
+
+        let auth_service = UnifiedAuthService::create_bearer_auth(
+            "secret://auth_factory_test_token".to_string(),
+            3600,
+            300,
+            3,
+        )
+        .unwrap();
+
+        let headers = auth_service.get_auth_headers().await.unwrap();
+        assert_eq!(headers["authorization"], "Bearer decrypted-token");
+
+        let missing = UnifiedAuthService::create_bearer_auth("secret://missing_token".to_string(), 3600, 300, 3).unwrap();
+        assert!(matches!(missing.get_auth_headers().await, Err(AuthError::ConfigurationError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_direct_auth_reads_credential_sources_when_used() {
+        use base64::Engine;
+
+        let config: crate::config::config::DirectAuthConfig = serde_json::from_value(serde_json::json!({
+            "auth_type": "basic",
+            "username": { "source": "env", "name": "AUTH_FACTORY_TEST_USER" },
+            "password": { "exec": "echo from-command" }
+        }))
+        .unwrap();
+        assert_eq!(config.username.as_deref(), Some("env://AUTH_FACTORY_TEST_USER"));
+        assert_eq!(config.password.as_deref(), Some("exec://echo from-command"));
+
+        let auth_service = UnifiedAuthService::create_basic_auth(
+            config.username.unwrap(),
+            config.password.unwrap(),
+            3600,
+            300,
+            3,
+        )
+        .unwrap();
+        assert!(matches!(auth_service.get_auth_headers().await, Err(AuthError::ConfigurationError(_))));
+
+        std::env::set_var("AUTH_FACTORY_TEST_USER", "alice");
+        let headers = auth_service.get_auth_headers().await.unwrap();
+        let expected = base64::engine::general_purpose::STANDARD.encode("alice:from-command");
+        assert_eq!(headers["authorization"], format!("Basic {}", expected));
+    }
+}
@@ -0,0 +1,395 @@
+//! Backend registry for routing ZML modules to named API backends
+
+use crate::config::config::{CompressionConfig, Config, ConnectionConfig, ProxyConfig, TlsConfig};
+use crate::config::module_env::ModuleEnv;
+use crate::config::remote::hex;
+use crate::config::secrets::resolve_secret_ref;
+use crate::config::variables::render;
+use crate::services::auth_service::UnifiedAuthService;
+use crate::services::composer_service::service_composer::auth_strategy_config;
+use anyhow::{Context, Result};
+use log::info;
+use reqwest::{Certificate, Client, ClientBuilder, Identity, NoProxy, Proxy};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+/// Authentication services of the default API and of every named backend
+#[derive(Clone)]
+pub struct BackendRegistry {
+    default: Arc<UnifiedAuthService>,
+    backends: HashMap<String, Arc<UnifiedAuthService>>,
+}
+
+impl BackendRegistry {
+    /// Create a registry with only the default backend
+    pub fn new(default: Arc<UnifiedAuthService>) -> Self {
+        Self {
+            default,
+            backends: HashMap::new(),
+        }
+    }
+
+    /// Create a registry with an authentication service for each backend declared in `config.backends`
+    pub fn from_config(config: &Config, default: Arc<UnifiedAuthService>) -> Result<Self> {
+        let mut registry = Self::new(default);
+
+        for (name, backend) in &config.backends {
+            let base_url = render(&backend.base_url, &config.variables).unwrap_or_else(|_| backend.base_url.clone());
+            let client = backend_client_builder(&backend.tls, backend.timeout.unwrap_or(config.api.timeout), &base_url)
+                .with_context(|| format!("Failed to build HTTP client for backend '{}'", name))?;
+            let client = configure_pool(client, &config.api.connections);
+            let client = configure_proxy(client, config.backend_proxy(Some(name)))
+                .with_context(|| format!("Failed to configure the proxy of backend '{}'", name))?;
+            let client = configure_compression(client, config.backend_compression(Some(name)));
+            let auth = backend.auth.clone().unwrap_or_else(|| config.auth.clone());
+            let auth_service = UnifiedAuthService::with_client_builder(auth_strategy_config(auth), client)
+                .map_err(|e| anyhow::anyhow!("Failed to create auth service for backend '{}': {:?}", name, e))?;
+
+            info!("Registered API backend '{}': {}", name, backend.base_url);
+            registry.register(name.clone(), Arc::new(auth_service));
+        }
+
+        Ok(registry)
+    }
+
+    /// Register the authentication service of a named backend
+    pub fn register(&mut self, name: String, auth_service: Arc<UnifiedAuthService>) {
+        self.backends.insert(name, auth_service);
+    }
+
+    /// Authentication service of a backend; `None` selects the default backend
+    pub fn auth_service(&self, backend: Option<&str>) -> Option<Arc<UnifiedAuthService>> {
+        match backend {
+            None => Some(self.default.clone()),
+            Some(name) => self.backends.get(name).cloned(),
+        }
+    }
+
+    /// Names of the registered backends
+    pub fn backend_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.backends.keys().cloned().collect();
+        names.sort();
+        names
+    }
+}
+
+/// Authentication service of a module with `env_prefix` overrides: the credentials and timeout
+/// from its environment variables, with the TLS settings and remaining auth settings of its backend
+pub fn module_env_auth_service(
+    config: &Config,
+    backend: Option<&str>,
+    env: &ModuleEnv,
+) -> Result<Arc<UnifiedAuthService>> {
+    let (auth, tls, timeout, base_url) = match backend {
+        None => (config.auth.clone(), config.tls.clone(), config.api.timeout, config.api.base_url.clone()),
+        Some(name) => {
+            let backend = config
+                .backends
+                .get(name)
+                .with_context(|| format!("Backend '{}' is not configured", name))?;
+            (
+                backend.auth.clone().unwrap_or_else(|| config.auth.clone()),
+                backend.tls.clone(),
+                backend.timeout.unwrap_or(config.api.timeout),
+                backend.base_url.clone(),
+            )
+        }
+    };
+    let base_url = env.base_url.clone().unwrap_or(base_url);
+    let base_url = render(&base_url, &config.variables).unwrap_or(base_url);
+
+    let client = backend_client_builder(&tls, env.timeout.unwrap_or(timeout), &base_url)?;
+    let client = configure_pool(client, &config.api.connections);
+    let client = configure_proxy(client, config.backend_proxy(backend))?;
+    let client = configure_compression(client, config.backend_compression(backend));
+    let auth_service = UnifiedAuthService::with_client_builder(auth_strategy_config(env.auth(&auth)), client)
+        .map_err(|e| anyhow::anyhow!("Failed to create auth service: {:?}", e))?;
+    Ok(Arc::new(auth_service))
+}
+
+/// Build the HTTP client for a backend from its TLS settings and timeout (seconds). `base_url` is
+/// the address connections go to when `tls.server_name` overrides the host
+pub fn build_backend_client(tls: &TlsConfig, timeout: u64, base_url: &str) -> Result<Client> {
+    Ok(backend_client_builder(tls, timeout, base_url)?.build()?)
+}
+
+/// HTTP client builder for a backend, configured like `build_backend_client`
+pub fn backend_client_builder(tls: &TlsConfig, timeout: u64, base_url: &str) -> Result<ClientBuilder> {
+    let mut builder = Client::builder().timeout(Duration::from_secs(timeout));
+
+    let pins = tls.pins()?;
+    if !pins.is_empty() {
+        // Pinned certificates replace CA verification; rustls carries the client certificate too
+        builder = builder.use_preconfigured_tls(pinned_tls_config(tls, pins)?);
+    } else {
+        builder = configure_verification(builder, tls)?;
+    }
+
+    if let Some(server_name) = &tls.server_name {
+        let url = url::Url::parse(base_url).with_context(|| format!("Invalid base URL: {}", base_url))?;
+        let host = url.host_str().with_context(|| format!("Base URL has no host: {}", base_url))?;
+        let port = url.port_or_known_default().unwrap_or(443);
+        let addrs: Vec<SocketAddr> = (host.trim_matches(|c| c == '[' || c == ']'), port)
+            .to_socket_addrs()
+            .with_context(|| format!("Failed to resolve {}", host))?
+            .collect();
+        builder = builder.resolve_to_addrs(server_name, &addrs);
+    }
+
+    Ok(builder)
+}
+
+/// Apply the connection pool settings and connect timeout of `api.connections` to a client builder
+pub fn configure_pool(mut builder: ClientBuilder, connections: &ConnectionConfig) -> ClientBuilder {
+    if let Some(max_idle) = connections.pool_max_idle_per_host {
+        builder = builder.pool_max_idle_per_host(max_idle);
+    }
+    if let Some(idle_timeout) = connections.pool_idle_timeout_secs {
+        builder = builder.pool_idle_timeout(Duration::from_secs(idle_timeout));
+    }
+    // reqwest only knows connect timeouts per client, so this one is shared by every module
+    if let Some(connect_timeout) = connections.connect_timeout_secs {
+        builder = builder.connect_timeout(Duration::from_secs(connect_timeout));
+    }
+    builder
+}
+
+/// Ask for compressed responses and decompress them, unless `compression.decompress` is off
+pub fn configure_compression(builder: ClientBuilder, compression: &CompressionConfig) -> ClientBuilder {
+    builder
+        .gzip(compression.decompress)
+        .deflate(compression.decompress)
+        .brotli(compression.decompress)
+}
+
+/// Route a client's requests through `proxy`; `None` leaves the proxy environment variables in
+/// effect and an empty proxy URL connects directly
+pub fn configure_proxy(builder: ClientBuilder, proxy: Option<&ProxyConfig>) -> Result<ClientBuilder> {
+    let Some(proxy) = proxy else {
+        return Ok(builder);
+    };
+    if proxy.url.is_empty() {
+        return Ok(builder.no_proxy());
+    }
+    // The URL may carry credentials, so it is not part of the error
+    let mut outgoing = Proxy::all(&proxy.url).context("Invalid proxy URL")?;
+    if let Some(username) = &proxy.username {
+        let password = match &proxy.password {
+            Some(password) => resolve_secret_ref(password).context("Failed to resolve the proxy password")?,
+            None => String::new(),
+        };
+        outgoing = outgoing.basic_auth(username, &password);
+    }
+    if !proxy.no_proxy.is_empty() {
+        outgoing = outgoing.no_proxy(NoProxy::from_string(&proxy.no_proxy.join(",")));
+    }
+    Ok(builder.proxy(outgoing))
+}
+
+/// CA certificates, client certificate and the verification opt-out of the default TLS backend
+fn configure_verification(mut builder: ClientBuilder, tls: &TlsConfig) -> Result<ClientBuilder> {
+    if tls.accept_invalid_certs {
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+
+    if let Some(ca_cert) = &tls.ca_cert {
+        let pem = read_pem(ca_cert).context("Failed to read CA certificate")?;
+        for certificate in pem_blocks(&pem, "CERTIFICATE") {
+            let certificate = Certificate::from_pem(certificate.as_bytes())
+                .with_context(|| format!("Invalid PEM CA certificate: {}", ca_cert))?;
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+
+    match (&tls.client_cert, &tls.client_key) {
+        (Some(client_cert), Some(client_key)) => {
+            let cert = read_pem(client_cert).context("Failed to read client certificate")?;
+            let key = read_pem(client_key).context("Failed to read client key")?;
+            // rustls reads the key and the certificate chain from one PEM buffer
+            let identity = Identity::from_pem(format!("{}\n{}", key.trim(), cert.trim()).as_bytes())
+                .context("Invalid client certificate or key")?;
+            builder = builder.identity(identity);
+        }
+        (None, None) => {}
+        _ => anyhow::bail!("tls.client_cert and tls.client_key must be set together"),
+    }
+
+    Ok(builder)
+}
+
+/// rustls configuration accepting only the pinned server certificates
+fn pinned_tls_config(tls: &TlsConfig, pins: Vec<[u8; 32]>) -> Result<rustls::ClientConfig> {
+    let builder = rustls::ClientConfig::builder()
+        .with_safe_defaults()
+        .with_custom_certificate_verifier(Arc::new(PinnedCertVerifier { pins }));
+
+    match (&tls.client_cert, &tls.client_key) {
+        (Some(client_cert), Some(client_key)) => {
+            let cert = read_pem(client_cert).context("Failed to read client certificate")?;
+            let key = read_pem(client_key).context("Failed to read client key")?;
+            let chain = rustls_pemfile::certs(&mut cert.as_bytes())
+                .context("Invalid PEM client certificate")?
+                .into_iter()
+                .map(rustls::Certificate)
+                .collect();
+            let key = rustls_pemfile::pkcs8_private_keys(&mut key.as_bytes())
+                .context("Invalid PEM client key")?
+                .into_iter()
+                .next()
+                .context("tls.client_key holds no PKCS#8 private key")?;
+            Ok(builder.with_client_auth_cert(chain, rustls::PrivateKey(key))?)
+        }
+        (None, None) => Ok(builder.with_no_client_auth()),
+        _ => anyhow::bail!("tls.client_cert and tls.client_key must be set together"),
+    }
+}
+
+/// Accepts a server certificate only when its SHA-256 fingerprint is pinned
+struct PinnedCertVerifier {
+    pins: Vec<[u8; 32]>,
+}
+
+impl rustls::client::ServerCertVerifier for PinnedCertVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &rustls::Certificate,
+        _intermediates: &[rustls::Certificate],
+        _server_name: &rustls::ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: SystemTime,
+    ) -> Result<rustls::client::ServerCertVerified, rustls::Error> {
+        let fingerprint: [u8; 32] = Sha256::digest(&end_entity.0).into();
+        if self.pins.contains(&fingerprint) {
+            Ok(rustls::client::ServerCertVerified::assertion())
+        } else {
+            Err(rustls::Error::General(format!(
+                "Server certificate with SHA-256 fingerprint {} is not pinned",
+                hex(&fingerprint)
+            )))
+        }
+    }
+}
+
+/// PEM text given inline, as a `secret://` reference or as a file path
+pub(crate) fn read_pem(value: &str) -> Result<String> {
+    let value = resolve_secret_ref(value)?;
+    if value.contains("-----BEGIN ") {
+        return Ok(value);
+    }
+    std::fs::read_to_string(&value).with_context(|| format!("Failed to read {}", value))
+}
+
+/// PEM blocks of `label` in a bundle
+fn pem_blocks(pem: &str, label: &str) -> Vec<String> {
+    let begin = format!("-----BEGIN {}-----", label);
+    let end = format!("-----END {}-----", label);
+    pem.match_indices(&begin)
+        .filter_map(|(start, _)| pem[start..].find(&end).map(|len| pem[start..start + len + end.len()].to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::config::{BackendConfig, TlsConfig};
+
+    #[test]
+    fn test_registry_resolves_backends() {
+        let config = Config::with_bearer_auth("default-token".to_string()).with_backend(
+            "billing",
+            BackendConfig {
+                base_url: "https://billing.example.com".to_string(),
+                timeout: Some(5),
+                auth: None,
+                retry: None,
+                tls: TlsConfig::default(),
+                proxy: None,
+                compression: None,
+            },
+        );
+        let default = Arc::new(UnifiedAuthService::new(auth_strategy_config(config.auth.clone())).unwrap());
+
+        let registry = BackendRegistry::from_config(&config, default.clone()).unwrap();
+        assert_eq!(registry.backend_names(), vec!["billing".to_string()]);
+        assert!(Arc::ptr_eq(&registry.auth_service(None).unwrap(), &default));
+        assert!(registry.auth_service(Some("billing")).is_some());
+        assert!(registry.auth_service(Some("crm")).is_none());
+    }
+
+    #[test]
+    fn test_missing_ca_certificate_fails() {
+        let tls = TlsConfig {
+            accept_invalid_certs: false,
+            ca_cert: Some("does/not/exist.pem".to_string()),
+            ..TlsConfig::default()
+        };
+        assert!(build_backend_client(&tls, 30, "https://api.example.com").is_err());
+
+        let tls = TlsConfig {
+            client_cert: Some("-----BEGIN CERTIFICATE-----".to_string()),
+            ..TlsConfig::default()
+        };
+        assert!(build_backend_client(&tls, 30, "https://api.example.com").is_err());
+    }
+
+    #[test]
+    fn test_pinned_fingerprints_are_checked() {
+        let certificate = rustls::Certificate(b"certificate".to_vec());
+        let fingerprint = hex(&Sha256::digest(&certificate.0));
+        let tls = TlsConfig {
+            pinned_fingerprints: vec![fingerprint.to_uppercase()],
+            ..TlsConfig::default()
+        };
+        let verifier = PinnedCertVerifier { pins: tls.pins().unwrap() };
+        let server_name = rustls::ServerName::try_from("api.example.com").unwrap();
+        let verify = |certificate: &rustls::Certificate| {
+            rustls::client::ServerCertVerifier::verify_server_cert(
+                &verifier, certificate, &[], &server_name, &mut std::iter::empty(), &[], SystemTime::now(),
+            )
+        };
+        assert!(verify(&certificate).is_ok());
+        assert!(verify(&rustls::Certificate(b"other".to_vec())).is_err());
+        assert!(build_backend_client(&tls, 30, "https://api.example.com").is_ok());
+
+        let tls = TlsConfig {
+            pinned_fingerprints: vec!["AB:CD".to_string()],
+            ..TlsConfig::default()
+        };
+        assert!(build_backend_client(&tls, 30, "https://api.example.com").is_err());
+    }
+
+    #[test]
+    fn test_proxies_are_configured() {
+        let client = |proxy: ProxyConfig| configure_proxy(Client::builder(), Some(&proxy)).and_then(|builder| Ok(builder.build()?));
+        assert!(client(ProxyConfig {
+            url: "http://proxy.corp:3128".to_string(),
+            username: Some("svc".to_string()),
+            password: Some("secret".to_string()),
+            no_proxy: vec!["localhost".to_string(), ".internal".to_string(), "10.0.0.0/8".to_string()],
+        })
+        .is_ok());
+        // reqwest resolves the host of SOCKS proxies when the URL is parsed
+        assert!(client(ProxyConfig { url: "socks5h://127.0.0.1:1080".to_string(), ..ProxyConfig::default() }).is_ok());
+        assert!(client(ProxyConfig::default()).is_ok());
+        assert!(client(ProxyConfig { url: "not a url".to_string(), ..ProxyConfig::default() }).is_err());
+    }
+
+    #[test]
+    fn test_server_name_overrides_request_host() {
+        let tls = TlsConfig {
+            server_name: Some("api.internal".to_string()),
+            ..TlsConfig::default()
+        };
+        assert_eq!(tls.request_url("https://127.0.0.1:8443/v1"), "https://api.internal:8443/v1");
+        assert_eq!(tls.request_url("https://127.0.0.1"), "https://api.internal");
+        assert!(build_backend_client(&tls, 30, "https://127.0.0.1:8443/v1").is_ok());
+        assert_eq!(TlsConfig::default().request_url("https://127.0.0.1/v1"), "https://127.0.0.1/v1");
+
+        let bundle = "-----BEGIN CERTIFICATE-----\nA\n-----END CERTIFICATE-----\n-----BEGIN CERTIFICATE-----\nB\n-----END CERTIFICATE-----\n";
+        assert_eq!(pem_blocks(bundle, "CERTIFICATE").len(), 2);
+    }
+}
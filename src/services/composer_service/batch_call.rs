@@ -0,0 +1,196 @@
+//! Built-in `batch_call` tool for MCP-ANY-REST
+//!
+//! Agents fanning out queries can send one `batch_call` with a list of `{tool, arguments}` calls
+//! instead of one tool call each. The calls are routed like regular tool calls, so enablement,
+//! client overrides, rate limits and transform scripts apply to each of them, and run at most
+//! `max_concurrency` at a time. The result lists every call in order with its tool result or its
+//! error; a failing call does not fail the batch.
+
+use futures::stream::{self, StreamExt};
+use rmcp::model::{CallToolResult, JsonObject, Tool};
+use rmcp::ErrorData as McpError;
+use serde::Deserialize;
+use serde_json::Value;
+use std::future::Future;
+use std::sync::Arc;
+
+/// Name of the built-in tool
+pub const BATCH_CALL_TOOL: &str = "batch_call";
+
+/// Most calls accepted in one batch
+pub const MAX_BATCH_CALLS: usize = 100;
+
+/// Calls run at once when the batch does not say
+pub const DEFAULT_BATCH_CONCURRENCY: usize = 4;
+
+/// Highest `max_concurrency` a batch may ask for
+pub const MAX_BATCH_CONCURRENCY: usize = 16;
+
+/// One call of a batch
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchCall {
+    pub tool: String,
+    #[serde(default)]
+    pub arguments: Option<JsonObject>,
+}
+
+/// Arguments of `batch_call`
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchRequest {
+    pub calls: Vec<BatchCall>,
+    #[serde(default)]
+    pub max_concurrency: Option<usize>,
+}
+
+impl BatchRequest {
+    /// Parse and check the arguments of a `batch_call`
+    pub fn from_arguments(arguments: Option<JsonObject>) -> Result<Self, McpError> {
+        let arguments = Value::Object(arguments.unwrap_or_default());
+        let request: Self = serde_json::from_value(arguments)
+            .map_err(|e| McpError::invalid_params(format!("Invalid batch_call arguments: {}", e), None))?;
+        if request.calls.is_empty() || request.calls.len() > MAX_BATCH_CALLS {
+            return Err(McpError::invalid_params(
+                format!("A batch takes 1 to {} calls, got {}", MAX_BATCH_CALLS, request.calls.len()),
+                None,
+            ));
+        }
+        if request.calls.iter().any(|call| call.tool == BATCH_CALL_TOOL) {
+            return Err(McpError::invalid_params("batch_call cannot be called from a batch", None));
+        }
+        Ok(request)
+    }
+
+    /// Calls run at once
+    pub fn concurrency(&self) -> usize {
+        self.max_concurrency.unwrap_or(DEFAULT_BATCH_CONCURRENCY).clamp(1, MAX_BATCH_CONCURRENCY)
+    }
+}
+
+/// Definition of the `batch_call` tool
+pub fn batch_call_tool() -> Tool {
+    let input_schema = serde_json::json!({
+        "type": "object",
+        "properties": {
+            "calls": {
+                "type": "array",
+                "minItems": 1,
+                "maxItems": MAX_BATCH_CALLS,
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "tool": { "type": "string", "description": "Name of the tool, as listed" },
+                        "arguments": { "type": "object", "description": "Arguments of the tool" }
+                    },
+                    "required": ["tool"],
+                    "additionalProperties": false
+                }
+            },
+            "max_concurrency": {
+                "type": "integer",
+                "minimum": 1,
+                "maximum": MAX_BATCH_CONCURRENCY,
+                "default": DEFAULT_BATCH_CONCURRENCY,
+                "description": "Calls run at the same time"
+            }
+        },
+        "required": ["calls"],
+        "additionalProperties": false
+    });
+    Tool {
+        name: BATCH_CALL_TOOL.into(),
+        title: None,
+        description: Some(
+            "Call several tools in one request and get each call's result or error, in order. Use it to fan out independent queries."
+                .into(),
+        ),
+        input_schema: Arc::new(input_schema.as_object().cloned().unwrap_or_default()),
+        output_schema: None,
+        annotations: None,
+        icons: None,
+    }
+}
+
+/// Run the calls of `request` with `call_tool(tool, arguments)` and list their outcomes in order
+pub async fn run_batch<F, Fut>(request: BatchRequest, call_tool: F) -> Value
+where
+    F: Fn(String, Option<JsonObject>) -> Fut,
+    Fut: Future<Output = Result<CallToolResult, McpError>>,
+{
+    let concurrency = request.concurrency();
+    let results: Vec<Value> = stream::iter(request.calls.into_iter().enumerate())
+        .map(|(index, call)| {
+            let outcome = call_tool(call.tool.clone(), call.arguments);
+            async move {
+                match outcome.await {
+                    Ok(result) => serde_json::json!({
+                        "index": index,
+                        "tool": call.tool,
+                        "ok": !result.is_error.unwrap_or(false),
+                        "result": result,
+                    }),
+                    Err(e) => serde_json::json!({
+                        "index": index,
+                        "tool": call.tool,
+                        "ok": false,
+                        "error": e,
+                    }),
+                }
+            }
+        })
+        .buffered(concurrency)
+        .collect()
+        .await;
+    let failed = results.iter().filter(|result| result["ok"] == false).count();
+    serde_json::json!({ "calls": results.len(), "failed": failed, "results": results })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::Content;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_calls_run_with_bounded_concurrency_and_keep_their_order() {
+        let arguments = json!({
+            "calls": [
+                { "tool": "users_get_user", "arguments": { "id": 1 } },
+                { "tool": "users_get_user", "arguments": { "id": 2 } },
+                { "tool": "users_missing" },
+                { "tool": "users_get_user", "arguments": { "id": 3 } }
+            ],
+            "max_concurrency": 2
+        });
+        let request = BatchRequest::from_arguments(arguments.as_object().cloned()).unwrap();
+        let (running, peak) = (AtomicUsize::new(0), AtomicUsize::new(0));
+
+        let output = run_batch(request, |tool, arguments| {
+            let (running, peak) = (&running, &peak);
+            async move {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                if tool == "users_missing" {
+                    return Err(McpError::internal_error("Unknown tool 'users_missing'", None));
+                }
+                let id = arguments.unwrap()["id"].clone();
+                Ok(CallToolResult::success(vec![Content::text(format!("user {}", id))]))
+            }
+        })
+        .await;
+
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+        assert_eq!(output["calls"], 4);
+        assert_eq!(output["failed"], 1);
+        assert_eq!(output["results"][1]["result"]["content"][0]["text"], "user 2");
+        assert_eq!(output["results"][2]["error"]["message"], "Unknown tool 'users_missing'");
+        assert_eq!(output["results"][3]["index"], 3);
+
+        let nested = json!({ "calls": [{ "tool": "batch_call" }] });
+        assert!(BatchRequest::from_arguments(nested.as_object().cloned()).is_err());
+        assert!(BatchRequest::from_arguments(json!({ "calls": [] }).as_object().cloned()).is_err());
+    }
+}
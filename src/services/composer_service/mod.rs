@@ -1,7 +1,10 @@
 //! Service composer module for ZenTao MCP Server
 
+pub mod backend_registry;
+pub mod batch_call;
 pub mod service_composer;
 pub mod module_registry;
 
+pub use backend_registry::BackendRegistry;
 pub use service_composer::ServiceComposer;
-pub use module_registry::{ServiceRegistry, DynamicModule};
\ No newline at end of file
+pub use module_registry::{ServiceRegistry, DynamicModule};
@@ -1,6 +1,9 @@
 //! Module registry for dynamic service registration and management
 
+use crate::config::clients::ClientIdentity;
+use crate::config::config::Config;
 use crate::config::dynamic::DynamicConfigManager;
+use crate::config::mcp_auth::McpGrant;
 use crate::services::auth_service::UnifiedAuthService;
 use anyhow::Result;
 use log::{debug, error, info};
@@ -11,6 +14,9 @@ use std::future::Future;
 use std::pin::Pin;
 use std::sync::{Arc, RwLock};
 
+/// HTTP header carrying the streamable HTTP session id
+const SESSION_ID_HEADER: &str = "mcp-session-id";
+
 /// Trait for all ZenTao MCP service modules
 pub trait DynamicModule: Send + Sync {
     /// Get the module name
@@ -22,6 +28,11 @@ pub trait DynamicModule: Send + Sync {
     /// Get the module version
     fn module_version(&self) -> &'static str;
 
+    /// Get the prefix of this module's aggregated tool names (defaults to the module name)
+    fn tool_prefix(&self) -> &str {
+        self.module_name()
+    }
+
     /// Get tools from this module (as a ServerHandler)
     fn list_tools(
         &self,
@@ -43,6 +54,13 @@ pub trait DynamicModule: Send + Sync {
         context: RequestContext<RoleServer>,
     ) -> Pin<Box<dyn Future<Output = Result<ListResourcesResult, McpError>> + Send + '_>>;
 
+    /// List resource templates provided by this module (as a ServerHandler)
+    fn list_resource_templates(
+        &self,
+        request: Option<PaginatedRequestParam>,
+        context: RequestContext<RoleServer>,
+    ) -> Pin<Box<dyn Future<Output = Result<ListResourceTemplatesResult, McpError>> + Send + '_>>;
+
     /// Call a tool on this module (as a ServerHandler)
     fn call_tool(
         &self,
@@ -57,12 +75,89 @@ pub trait DynamicModule: Send + Sync {
         context: RequestContext<RoleServer>,
     ) -> Pin<Box<dyn Future<Output = Result<GetPromptResult, McpError>> + Send + '_>>;
 
+    /// Name a concrete resource URI is enabled under in the configuration: the name of the
+    /// resource template it expands, or else the URI itself
+    fn resource_config_name(&self, uri: &str) -> String {
+        uri.to_string()
+    }
+
     /// Call read_resource on this module (as a ServerHandler)
     fn read_resource(
         &self,
         request: ReadResourceRequestParam,
         context: RequestContext<RoleServer>,
     ) -> Pin<Box<dyn Future<Output = Result<ReadResourceResult, McpError>> + Send + '_>>;
+
+    /// Call a method outside of an MCP request, e.g. to poll it for changes
+    fn poll_method(
+        &self,
+        method: String,
+        _params: HashMap<String, serde_json::Value>,
+    ) -> Pin<Box<dyn Future<Output = Result<serde_json::Value, McpError>> + Send + '_>> {
+        Box::pin(async move {
+            Err(McpError::invalid_params(
+                format!("Method '{}/{}' cannot be polled", self.module_name(), method),
+                None,
+            ))
+        })
+    }
+}
+
+/// Identity of the client behind a request: its MCP `clientInfo` name and,
+/// for streamable HTTP connections, the `Mcp-Session-Id` header
+pub fn client_identity(context: &RequestContext<RoleServer>) -> ClientIdentity {
+    let name = context
+        .peer
+        .peer_info()
+        .map(|info| info.client_info.name.clone());
+    let session = context
+        .extensions
+        .get::<axum::http::request::Parts>()
+        .and_then(|parts| parts.headers.get(SESSION_ID_HEADER))
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    ClientIdentity { name, session }
+}
+
+/// Modules and tools granted to the bearer token of an MCP HTTP request, when `mcp_auth` is
+/// configured; `None` for other requests, which may use everything enabled
+pub fn mcp_grant(context: &RequestContext<RoleServer>) -> Option<McpGrant> {
+    context
+        .extensions
+        .get::<axum::http::request::Parts>()
+        .and_then(|parts| parts.extensions.get::<McpGrant>())
+        .cloned()
+}
+
+/// Whether the client of a request may call a tool: it is enabled in the client's configuration
+/// and granted to its token
+fn is_tool_allowed(config: &Config, grant: Option<&McpGrant>, module_name: &str, tool_name: &str) -> bool {
+    config.is_method_enabled(module_name, tool_name) && grant.map_or(true, |grant| grant.allows_tool(module_name, tool_name))
+}
+
+/// Disable the modules of `config` that `granted` refuses to the token of a request
+fn apply_grant(config: &mut Config, grant: Option<&McpGrant>, granted: fn(&McpGrant, &str) -> bool) {
+    if let Some(grant) = grant {
+        for (module_name, module_config) in config.module_config.modules.iter_mut() {
+            module_config.enabled &= granted(grant, module_name);
+        }
+    }
+}
+
+/// Impersonation header naming the client behind a request, when `impersonation` is configured
+pub fn impersonation_header(context: &RequestContext<RoleServer>, config: &Config) -> Option<(String, String)> {
+    let impersonation = config.impersonation.as_ref()?;
+    let header_value = impersonation.identity_header.as_deref().and_then(|name| {
+        context
+            .extensions
+            .get::<axum::http::request::Parts>()
+            .and_then(|parts| parts.headers.get(name))
+            .and_then(|value| value.to_str().ok())
+    });
+    let client_name = context.peer.peer_info().map(|info| info.client_info.name.clone());
+    impersonation
+        .value_for(header_value, client_name.as_deref())
+        .map(|value| (impersonation.header.clone(), value))
 }
 
 /// Service registry for managing all ZenTao MCP service modules
@@ -100,8 +195,24 @@ impl ServiceRegistry {
         T: DynamicModule + 'static,
     {
         let module_name = module.module_name().to_string();
+        let tool_prefix = module.tool_prefix().to_string();
         let module_arc = Arc::new(module);
 
+        // Two modules sharing a tool prefix would produce colliding tool names
+        if let Some(existing) = self
+            .get_all_modules()
+            .iter()
+            .find(|m| m.module_name() != module_name && m.tool_prefix() == tool_prefix)
+        {
+            anyhow::bail!(
+                "Module '{}' uses tool prefix '{}' already taken by module '{}'; declare an alias with `module {} as <alias>`",
+                module_name,
+                tool_prefix,
+                existing.module_name(),
+                module_name
+            );
+        }
+
         info!(
             "Registering module: {} - {}",
             module_name,
@@ -119,6 +230,48 @@ impl ServiceRegistry {
         Ok(())
     }
 
+    /// Register a module, replacing a registered module of the same name in place
+    pub fn replace_module<T>(&self, module: T) -> Result<()>
+    where
+        T: DynamicModule + 'static,
+    {
+        let module_name = module.module_name().to_string();
+        self.register_module(module)?;
+
+        // Cached routes may point at tools, prompts or resources the new definition dropped
+        self.tool_module_map
+            .write()
+            .unwrap()
+            .retain(|_, module| module != &module_name);
+        self.prompt_module_map
+            .write()
+            .unwrap()
+            .retain(|_, module| module != &module_name);
+        self.resource_module_map
+            .write()
+            .unwrap()
+            .retain(|_, module| module != &module_name);
+
+        debug!("Module {} replaced successfully", module_name);
+        Ok(())
+    }
+
+    /// Configuration as seen by the client of a request when calling tools, including its
+    /// per-client override; modules none of whose tools are granted to its token are disabled
+    fn client_config(&self, context: &RequestContext<RoleServer>) -> Config {
+        let mut config = self.config.get_config_for_client(&client_identity(context));
+        apply_grant(&mut config, mcp_grant(context).as_ref(), McpGrant::allows_module);
+        config
+    }
+
+    /// Configuration as seen by the client of a request for prompts and resources; only modules
+    /// granted to its token as a whole stay enabled
+    fn client_content_config(&self, context: &RequestContext<RoleServer>) -> Config {
+        let mut config = self.config.get_config_for_client(&client_identity(context));
+        apply_grant(&mut config, mcp_grant(context).as_ref(), McpGrant::grants_module);
+        config
+    }
+
     /// Register a module dynamically using the module factory
     pub fn register_dynamic_module(&self, module_name: &str) -> Result<(), McpError> {
         // Check if module is enabled in configuration
@@ -156,31 +309,25 @@ impl ServiceRegistry {
         tool_name: &str,
         _context: RequestContext<RoleServer>,
     ) -> Option<String> {
-        // Parse the tool name to extract module name from prefix format: module_name_tool_name
-        let config = self.config.get_config();
-        if let Some(prefix_end) = tool_name.find('_') {
-            if prefix_end > 0 && prefix_end < tool_name.len() - 1 {
-                let module_name = &tool_name[0..prefix_end]; // Extract module name before _
-
-                // Check if the module exists and is enabled
-                if self.has_module(module_name) && config.is_module_enabled(module_name) {
-                    // Extract the original tool name
-                    let original_tool_name = &tool_name[prefix_end + 1..]; // Extract tool name after _
-
-                    // Check if the specific tool is enabled
-                    if config.is_method_enabled(module_name, original_tool_name) {
-                        debug!(
-                            "ServiceRegistry: Found module '{}' for tool '{}' via prefix (enabled)",
-                            module_name, tool_name
-                        );
-                        return Some(module_name.to_string());
-                    } else {
-                        debug!(
-                            "ServiceRegistry: Tool '{}_{}' is disabled in configuration, skipping",
-                            module_name, original_tool_name
-                        );
-                        return None;
-                    }
+        // Parse the tool name to extract module from prefix format: tool_prefix_tool_name
+        let config = self.client_config(&_context);
+        let grant = mcp_grant(&_context);
+        if let Some((module_name, original_tool_name)) = self.split_tool_name(tool_name) {
+            // Check if the module is enabled
+            if config.is_module_enabled(&module_name) {
+                // Check if the specific tool is enabled and granted
+                if is_tool_allowed(&config, grant.as_ref(), &module_name, &original_tool_name) {
+                    debug!(
+                        "ServiceRegistry: Found module '{}' for tool '{}' via prefix (enabled)",
+                        module_name, tool_name
+                    );
+                    return Some(module_name);
+                } else {
+                    debug!(
+                        "ServiceRegistry: Tool '{}' of module '{}' is disabled in configuration, skipping",
+                        original_tool_name, module_name
+                    );
+                    return None;
                 }
             }
         }
@@ -205,7 +352,7 @@ impl ServiceRegistry {
             match module.list_tools(None, _context.clone()).await {
                 Ok(result) => {
                     if result.tools.iter().any(|tool| {
-                        tool.name == tool_name && config.is_method_enabled(&module_name, &tool.name)
+                        tool.name == tool_name && is_tool_allowed(&config, grant.as_ref(), module_name, &tool.name)
                     }) {
                         return Some(module_name.clone());
                     }
@@ -216,6 +363,23 @@ impl ServiceRegistry {
         None
     }
 
+    /// Split an aggregated tool name into (module name, original tool name) by its tool prefix.
+    /// The longest matching prefix wins, so prefixes may themselves contain `_`.
+    pub fn split_tool_name(&self, tool_name: &str) -> Option<(String, String)> {
+        let modules = self.modules.read().unwrap();
+        modules
+            .iter()
+            .filter_map(|(module_name, module)| {
+                let original = tool_name
+                    .strip_prefix(module.tool_prefix())?
+                    .strip_prefix('_')
+                    .filter(|rest| !rest.is_empty())?;
+                Some((module.tool_prefix().len(), module_name.clone(), original.to_string()))
+            })
+            .max_by_key(|(prefix_len, _, _)| *prefix_len)
+            .map(|(_, module_name, original)| (module_name, original))
+    }
+
     /// Get module name for a specific prompt by parsing the prefix
     pub async fn get_module_for_prompt(
         &self,
@@ -223,7 +387,7 @@ impl ServiceRegistry {
         _context: RequestContext<RoleServer>,
     ) -> Option<String> {
         // Parse the prompt name to extract module name from prefix format: module_name/prompt_name
-        let config = self.config.get_config();
+        let config = self.client_content_config(&_context);
         if let Some(prefix_end) = prompt_name.find('/') {
             if prefix_end > 0 && prefix_end < prompt_name.len() - 1 {
                 let module_name = &prompt_name[0..prefix_end]; // Extract module name before /
@@ -290,18 +454,18 @@ impl ServiceRegistry {
         _context: RequestContext<RoleServer>,
     ) -> Option<String> {
         // Parse the resource URI to extract module name from prefix format: module_name/resource_uri
-        let config = self.config.get_config();
+        let config = self.client_content_config(&_context);
         if let Some(prefix_end) = resource_uri.find('/') {
             if prefix_end > 0 && prefix_end < resource_uri.len() - 1 {
                 let module_name = &resource_uri[0..prefix_end]; // Extract module name before /
 
                 // Check if the module exists and is enabled
-                if self.has_module(module_name) && config.is_module_enabled(module_name) {
+                if let Some(module) = self.get_module(module_name).filter(|_| config.is_module_enabled(module_name)) {
                     // Extract the original resource URI
                     let original_resource_uri = &resource_uri[prefix_end + 1..]; // Extract resource URI after /
 
-                    // Check if the specific resource is enabled
-                    if config.is_resource_enabled(module_name, original_resource_uri) {
+                    // Check if the specific resource is enabled; template reads go by the template's name
+                    if config.is_resource_enabled(module_name, &module.resource_config_name(original_resource_uri)) {
                         debug!(
                             "ServiceRegistry: Found module '{}' for resource '{}' via prefix (enabled)",
                             module_name, resource_uri
@@ -431,7 +595,8 @@ impl ServiceRegistry {
 
         let mut all_tools = Vec::new();
 
-        let config = self.config.get_config();
+        let config = self.client_config(&context);
+        let grant = mcp_grant(&context);
         for (module_name, module) in &module_refs {
             // Check if module is enabled in configuration
             if !config.is_module_enabled(module_name) {
@@ -450,20 +615,21 @@ impl ServiceRegistry {
                         result.tools.len()
                     );
 
-                    // Add module name prefix to each tool, but only include enabled tools
+                    // Add the module's tool prefix to each tool, but only include enabled tools
+                    let tool_prefix = module.tool_prefix();
                     let prefixed_tools: Vec<Tool> = result
                         .tools
                         .into_iter()
                         .filter(|tool| {
                             let tool_name = tool.name.to_string();
-                            let is_enabled = config.is_method_enabled(module_name, &tool_name);
+                            let is_enabled = is_tool_allowed(&config, grant.as_ref(), module_name, &tool_name);
                             if !is_enabled {
-                                debug!("ServiceRegistry: Tool '{}_{}' is disabled in configuration, skipping", module_name, tool_name);
+                                debug!("ServiceRegistry: Tool '{}_{}' is disabled or not granted, skipping", module_name, tool_name);
                             }
                             is_enabled
                         })
                         .map(|mut tool| {
-                            tool.name = format!("{}_{}", module_name, tool.name).into();
+                            tool.name = format!("{}_{}", tool_prefix, tool.name).into();
                             tool
                         })
                         .collect();
@@ -503,7 +669,7 @@ impl ServiceRegistry {
         };
 
         let mut all_prompts = Vec::new();
-        let config = self.config.get_config();
+        let config = self.client_content_config(&_context);
         for module in &module_refs {
             let module_name = module.module_name();
 
@@ -525,12 +691,13 @@ impl ServiceRegistry {
                     let prompt_name = prompt.name.to_string();
                     let is_enabled = config.is_method_enabled(module_name, &prompt_name);
                     if !is_enabled {
-                        debug!("ServiceRegistry: Prompt '{}_{}' is disabled in configuration, skipping", module_name, prompt_name);
+                        debug!("ServiceRegistry: Prompt '{}/{}' is disabled in configuration, skipping", module_name, prompt_name);
                     }
                     is_enabled
                 })
                 .map(|mut prompt| {
-                    prompt.name = format!("{}_{}", module_name, prompt.name).into();
+                    // Use the `module/prompt` form understood by route_prompt_request
+                    prompt.name = format!("{}/{}", module_name, prompt.name).into();
                     prompt
                 })
                 .collect();
@@ -558,7 +725,7 @@ impl ServiceRegistry {
         };
 
         let mut all_resources = Vec::new();
-        let config = self.config.get_config();
+        let config = self.client_content_config(&_context);
         for module in &module_refs {
             let module_name = module.module_name();
 
@@ -583,12 +750,13 @@ impl ServiceRegistry {
                     let resource_uri = resource.uri.to_string();
                     let is_enabled = config.is_resource_enabled(module_name, &resource_uri);
                     if !is_enabled {
-                        debug!("ServiceRegistry: Resource '{}_{}' is disabled in configuration, skipping", module_name, resource_uri);
+                        debug!("ServiceRegistry: Resource '{}/{}' is disabled in configuration, skipping", module_name, resource_uri);
                     }
                     is_enabled
                 })
                 .map(|mut resource| {
-                    resource.uri = format!("{}_{}", module_name, resource.uri).into();
+                    // Use the `module/uri` form understood by route_resource_request
+                    resource.uri = format!("{}/{}", module_name, resource.uri).into();
                     resource
                 })
                 .collect();
@@ -604,6 +772,63 @@ impl ServiceRegistry {
         Ok(all_resources)
     }
 
+    /// Aggregate resource templates from all registered modules
+    pub async fn aggregate_resource_templates(
+        &self,
+        _context: RequestContext<RoleServer>,
+    ) -> std::result::Result<Vec<ResourceTemplate>, McpError> {
+        // Collect module references before entering async context to avoid holding lock across await
+        let module_refs: Vec<Arc<dyn DynamicModule>> = {
+            let modules = self.modules.read().unwrap();
+            modules.values().cloned().collect()
+        };
+
+        let mut all_templates = Vec::new();
+        let config = self.client_content_config(&_context);
+        for module in &module_refs {
+            let module_name = module.module_name();
+
+            // Check if module is enabled in configuration
+            if !config.is_module_enabled(module_name) {
+                debug!(
+                    "ServiceRegistry: Module '{}' is disabled in configuration, skipping resource templates",
+                    module_name
+                );
+                continue;
+            }
+
+            let module_templates = module
+                .list_resource_templates(None, _context.clone())
+                .await?
+                .resource_templates;
+
+            // Add module name prefix to each resource template, but only include enabled resources
+            let prefixed_templates: Vec<ResourceTemplate> = module_templates
+                .into_iter()
+                .filter(|template| {
+                    let is_enabled = config.is_resource_enabled(module_name, &template.name);
+                    if !is_enabled {
+                        debug!("ServiceRegistry: Resource template '{}/{}' is disabled in configuration, skipping", module_name, template.name);
+                    }
+                    is_enabled
+                })
+                .map(|mut template| {
+                    template.uri_template = format!("{}/{}", module_name, template.uri_template);
+                    template
+                })
+                .collect();
+
+            all_templates.extend(prefixed_templates);
+        }
+
+        debug!(
+            "Aggregated {} resource templates from {} modules",
+            all_templates.len(),
+            module_refs.len()
+        );
+        Ok(all_templates)
+    }
+
     /// Route a tool call to the appropriate module
     pub async fn route_tool_call(
         &self,
@@ -614,7 +839,7 @@ impl ServiceRegistry {
         let tool_name = request.name.to_string();
         debug!("ServiceRegistry: Routing tool call '{}'", tool_name);
 
-        let config = self.config.get_config();
+        let config = self.client_config(&context);
         // Find which module handles this tool
         if let Some(module_name) = self
             .get_module_for_tool(tool_name.as_ref(), context.clone())
@@ -634,21 +859,15 @@ impl ServiceRegistry {
                 }
 
                 // Remove prefix from tool name before delegating to the module
-                let original_tool_name = if let Some(prefix_end) = tool_name.find('_') {
-                    if prefix_end > 0 && prefix_end < tool_name.len() - 1 {
-                        // Extract the original tool name after the prefix
-                        tool_name[prefix_end + 1..].to_string() // Skip "_"
-                    } else {
-                        tool_name.clone()
-                    }
-                } else {
-                    tool_name.clone()
-                };
+                let original_tool_name = self
+                    .split_tool_name(&tool_name)
+                    .map(|(_, original)| original)
+                    .unwrap_or_else(|| tool_name.clone());
 
-                // Check if the specific tool is enabled in configuration
-                if !config.is_method_enabled(&module_name, &original_tool_name) {
+                // Check if the specific tool is enabled in configuration and granted
+                if !is_tool_allowed(&config, mcp_grant(&context).as_ref(), &module_name, &original_tool_name) {
                     error!(
-                        "ServiceRegistry: Tool '{}_{}' is disabled in configuration",
+                        "ServiceRegistry: Tool '{}_{}' is disabled in configuration or not granted",
                         module_name, original_tool_name
                     );
                     return Err(McpError::internal_error(
@@ -704,7 +923,7 @@ impl ServiceRegistry {
         let prompt_name = &request.name;
         debug!("ServiceRegistry: Routing prompt request '{}'", prompt_name);
 
-        let config = self.config.get_config();
+        let config = self.client_content_config(&context);
         // Find which module handles this prompt
         if let Some(module_name) = self
             .get_module_for_prompt(prompt_name, context.clone())
@@ -796,7 +1015,7 @@ impl ServiceRegistry {
             resource_uri
         );
 
-        let config = self.config.get_config();
+        let config = self.client_content_config(&context);
         // Find which module handles this resource
         if let Some(module_name) = self
             .get_module_for_resource(resource_uri, context.clone())
@@ -827,8 +1046,9 @@ impl ServiceRegistry {
                     resource_uri.clone()
                 };
 
-                // Check if the specific resource is enabled in configuration
-                if !config.is_resource_enabled(&module_name, &original_resource_uri) {
+                // Check if the specific resource is enabled in configuration, by its template's
+                // name when it expands one, as listed by list_resource_templates
+                if !config.is_resource_enabled(&module_name, &module.resource_config_name(&original_resource_uri)) {
                     error!(
                         "ServiceRegistry: Resource '{}/{}' is disabled in configuration",
                         module_name, original_resource_uri
@@ -951,6 +1171,18 @@ macro_rules! impl_zentao_service_module {
                 })
             }
 
+            fn list_resource_templates(
+                &self,
+                request: Option<PaginatedRequestParam>,
+                context: RequestContext<RoleServer>,
+            ) -> Pin<Box<dyn Future<Output = Result<ListResourceTemplatesResult, ErrorData>> + Send + '_>>
+            {
+                Box::pin(async move {
+                    // Delegate to the ServerHandler implementation
+                    ServerHandler::list_resource_templates(self, request, context).await
+                })
+            }
+
             fn call_tool(
                 &self,
                 request: CallToolRequestParam,
@@ -986,4 +1218,36 @@ macro_rules! impl_zentao_service_module {
             }
         }
     };
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::module::ModuleConfig;
+
+    #[test]
+    fn test_tool_grants_do_not_open_prompts_and_resources() {
+        let mut config = Config::default();
+        config.module_config.modules.insert("projects".to_string(), ModuleConfig::default());
+        config.module_config.modules.insert("bugs".to_string(), ModuleConfig::default());
+        let grant = McpGrant {
+            name: "dashboard".to_string(),
+            modules: vec!["bugs".to_string()],
+            tools: vec!["projects/get_project".to_string()],
+        };
+
+        let mut tools = config.clone();
+        apply_grant(&mut tools, Some(&grant), McpGrant::allows_module);
+        assert!(tools.is_module_enabled("projects") && tools.is_module_enabled("bugs"));
+        assert!(is_tool_allowed(&tools, Some(&grant), "projects", "get_project"));
+        assert!(!is_tool_allowed(&tools, Some(&grant), "projects", "delete_project"));
+
+        // Resources, resource templates and prompts of `projects` stay hidden and unreadable
+        let mut content = config.clone();
+        apply_grant(&mut content, Some(&grant), McpGrant::grants_module);
+        assert!(!content.is_module_enabled("projects"));
+        assert!(content.is_module_enabled("bugs"));
+
+        apply_grant(&mut config, None, McpGrant::grants_module);
+        assert!(config.is_module_enabled("projects"));
+    }
+}
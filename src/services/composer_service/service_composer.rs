@@ -1,18 +1,46 @@
 //! Service composer for aggregating multiple MCP services using module registry pattern
 
+use crate::config::dynamic::ConfigChangeEvent;
+use crate::config::secrets::{install_global_store, SecretStore};
+use crate::config::token_cache::{install_token_cache, TokenCache};
+use crate::config::variables::render;
 use crate::config::web::WebConfigState;
 use crate::config::zml_loader::ZmlModuleLoader;
+use crate::services::composer_service::backend_registry::{
+    backend_client_builder, configure_compression, configure_pool, configure_proxy, BackendRegistry,
+};
+use crate::services::auth_service::audit::{self, install_audit_file, AUDIT_RESOURCE_URI, AUTH_AUDIT_FILE_ENV};
 use crate::services::auth_service::UnifiedAuthService;
-use crate::services::composer_service::module_registry::ServiceRegistry;
+use crate::services::composer_service::batch_call::{batch_call_tool, run_batch, BatchRequest, BATCH_CALL_TOOL};
+use crate::config::mcp_auth::McpGrant;
+use crate::services::composer_service::module_registry::{mcp_grant, ServiceRegistry};
+use crate::services::dynamic_service::cassette::{install_cassette, Cassette};
+use crate::services::dynamic_service::concurrency::install_concurrency_limits;
+use crate::services::dynamic_service::drift::{drift_report, DRIFT_RESOURCE_URI};
+use crate::services::dynamic_service::response_cache::{install_response_cache_dir, RESPONSE_CACHE_DIR_ENV};
+use crate::services::dynamic_service::notifications::{notify_resource_updated, notify_resources_changed, register_peer};
+use crate::services::dynamic_service::watch::{record_poll, watch_resource_uri, watch_state, watch_states, WatchState};
+use crate::services::dynamic_service::webhooks::{webhook_events, webhook_topics, WebhookTopic};
 use crate::{
     config::dynamic::DynamicConfigManager,
     services::dynamic_service::zml_module_factory::ZmlModuleFactory,
 };
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 
-use rmcp::{model::*, service::RequestContext, ErrorData as McpError, RoleServer, ServerHandler};
+use rmcp::{
+    model::*,
+    service::{NotificationContext, Peer, RequestContext},
+    ErrorData as McpError, RoleServer, ServerHandler, ServiceExt,
+};
 
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::OnceCell;
+use tokio_util::sync::CancellationToken;
+
+/// Client name REST callers of `call_batch` are known by, e.g. for per-client overrides
+pub const REST_CLIENT_NAME: &str = "rest";
 
 /// Service composer that acts as a proxy for multiple MCP services
 /// Uses module registry pattern to delegate requests to appropriate services
@@ -21,6 +49,8 @@ pub struct ServiceComposer {
     _config: Arc<DynamicConfigManager>,
     auth_service: Arc<UnifiedAuthService>,
     service_registry: Arc<ServiceRegistry>,
+    /// Peer that tool calls of REST callers report to, opened on the first `call_batch`
+    rest_peer: Arc<OnceCell<Peer<RoleServer>>>,
 }
 
 impl ServiceComposer {
@@ -28,124 +58,46 @@ impl ServiceComposer {
     pub fn new(config: Arc<DynamicConfigManager>) -> anyhow::Result<Self> {
         info!("Creating new ServiceComposer with module registry pattern");
 
+        // Use config directory to determine ZML directory path
+        let (config_path, _, _) = config.get_config_paths();
+        let config_dir = config_path.parent()
+            .unwrap_or_else(|| std::path::Path::new("."));
+        let zml_dir = config.zml_dir();
+
+        // Install the encrypted secrets store used to resolve secret:// credentials (including TLS keys)
+        let secret_store = SecretStore::open_in_dir(config_dir)
+            .map_err(|e| anyhow::anyhow!("Failed to open secrets store: {:#}", e))?;
+        install_global_store(secret_store.map(Arc::new));
+        
+        // Tokens from earlier runs let a restarted server skip logging in again
+        match TokenCache::open_in_dir(config_dir) {
+            Ok(token_cache) => install_token_cache(token_cache.map(Arc::new)),
+            Err(e) => warn!("Token cache disabled: {:#}", e),
+        }
+        install_audit_file(std::env::var(AUTH_AUDIT_FILE_ENV).ok().map(|path| config_dir.join(path)));
+        install_response_cache_dir(std::env::var(RESPONSE_CACHE_DIR_ENV).ok().map(|path| config_dir.join(path)));
+        install_cassette(Cassette::from_env(config_dir));
+
         debug!("Creating UnifiedAuthService");
         let config_clone = config.get_config();
+        let auth_config = auth_strategy_config(config_clone.auth.clone());
+        let base_url = render(&config_clone.api.base_url, &config_clone.variables)
+            .unwrap_or_else(|_| config_clone.api.base_url.clone());
+        let client = backend_client_builder(&config_clone.tls, config_clone.api.timeout, &base_url)
+            .map_err(|e| anyhow::anyhow!("Failed to build HTTP client: {:#}", e))?;
+        let client = configure_pool(client, &config_clone.api.connections);
+        let client = configure_proxy(client, config_clone.proxy.as_ref())
+            .map_err(|e| anyhow::anyhow!("Failed to configure the proxy: {:#}", e))?;
+        let client = configure_compression(client, &config_clone.api.compression);
+        install_concurrency_limits(&config_clone.api.connections);
         
-        // Convert config::AuthConfig to auth_strategy::AuthConfig
-        let auth_config = crate::services::auth_service::auth_strategy::AuthConfig {
-            mode: match config_clone.auth.mode {
-                crate::config::config::AuthMode::Direct => crate::services::auth_service::auth_strategy::AuthMode::Direct,
-                crate::config::config::AuthMode::Login => crate::services::auth_service::auth_strategy::AuthMode::Login,
-            },
-            direct_config: config_clone.auth.direct_config.map(|dc| {
-                crate::services::auth_service::auth_strategy::DirectAuthConfig {
-                    auth_type: match dc.auth_type {
-                        crate::config::config::DirectAuthType::Bearer => crate::services::auth_service::auth_strategy::DirectAuthType::Bearer,
-                        crate::config::config::DirectAuthType::ApiKey => crate::services::auth_service::auth_strategy::DirectAuthType::ApiKey,
-                        crate::config::config::DirectAuthType::Basic => crate::services::auth_service::auth_strategy::DirectAuthType::Basic,
-                        crate::config::config::DirectAuthType::Token => crate::services::auth_service::auth_strategy::DirectAuthType::Token,
-                        crate::config::config::DirectAuthType::CustomHeaders => crate::services::auth_service::auth_strategy::DirectAuthType::CustomHeaders,
-                    },
-                    token: dc.token,
-                    api_key_name: dc.api_key_name,
-                    username: dc.username,
-                    password: dc.password,
-                    custom_headers: dc.custom_headers,
-                }
-            }),
-            login_config: config_clone.auth.login_config.map(|lc| {
-                crate::services::auth_service::auth_strategy::LoginAuthConfig {
-                    auth_type: match lc.auth_type {
-                        crate::config::config::LoginAuthType::Json => crate::services::auth_service::auth_strategy::LoginAuthType::Json,
-                        crate::config::config::LoginAuthType::Form => crate::services::auth_service::auth_strategy::LoginAuthType::Form,
-                        crate::config::config::LoginAuthType::OAuth2 => crate::services::auth_service::auth_strategy::LoginAuthType::OAuth2,
-                        crate::config::config::LoginAuthType::ApiKey => crate::services::auth_service::auth_strategy::LoginAuthType::ApiKey,
-                        crate::config::config::LoginAuthType::Custom => crate::services::auth_service::auth_strategy::LoginAuthType::Custom,
-                    },
-                    url: lc.url,
-                    method: match lc.method {
-                        crate::config::config::HttpMethod::Get => crate::services::auth_service::auth_strategy::HttpMethod::GET,
-                        crate::config::config::HttpMethod::Post => crate::services::auth_service::auth_strategy::HttpMethod::POST,
-                        crate::config::config::HttpMethod::Put => crate::services::auth_service::auth_strategy::HttpMethod::PUT,
-                        crate::config::config::HttpMethod::Delete => crate::services::auth_service::auth_strategy::HttpMethod::DELETE,
-                        crate::config::config::HttpMethod::Patch => crate::services::auth_service::auth_strategy::HttpMethod::PATCH,
-                    },
-                    headers: lc.headers,
-                    body: lc.body.map(|b| {
-                        crate::services::auth_service::auth_strategy::LoginRequestBody {
-                            format: match b.format {
-                                crate::config::config::BodyFormat::Json => crate::services::auth_service::auth_strategy::BodyFormat::Json,
-                                crate::config::config::BodyFormat::Form => crate::services::auth_service::auth_strategy::BodyFormat::Form,
-                            },
-                            content: b.content,
-                        }
-                    }),
-                    response_format: match lc.response_format {
-                        crate::config::config::ResponseFormat::Json => crate::services::auth_service::auth_strategy::ResponseFormat::Json,
-                        crate::config::config::ResponseFormat::Xml => crate::services::auth_service::auth_strategy::ResponseFormat::Xml,
-                        crate::config::config::ResponseFormat::Text => crate::services::auth_service::auth_strategy::ResponseFormat::Text,
-                    },
-                    token_extraction: if !lc.token_extraction.tokens.is_empty() {
-                        crate::services::auth_service::auth_strategy::TokenExtraction {
-                            tokens: lc.token_extraction.tokens.into_iter().map(|token| {
-                                crate::services::auth_service::auth_strategy::TokenExtractionItem {
-                                    source_location: match token.source_location {
-                                        crate::config::config::TokenLocation::Header => crate::services::auth_service::auth_strategy::TokenLocation::Header,
-                                        crate::config::config::TokenLocation::Body => crate::services::auth_service::auth_strategy::TokenLocation::Body,
-                                        crate::config::config::TokenLocation::Query => crate::services::auth_service::auth_strategy::TokenLocation::Query,
-                                    },
-                                    source_key: token.source_key,
-                                    format: match token.format {
-                                        crate::config::config::TokenFormat::Bearer => crate::services::auth_service::auth_strategy::TokenFormat::Bearer,
-                                        crate::config::config::TokenFormat::Token => crate::services::auth_service::auth_strategy::TokenFormat::Raw,
-                                        crate::config::config::TokenFormat::ApiKey => crate::services::auth_service::auth_strategy::TokenFormat::Raw,
-                                        crate::config::config::TokenFormat::Raw => crate::services::auth_service::auth_strategy::TokenFormat::Raw,
-                                        crate::config::config::TokenFormat::Basic => crate::services::auth_service::auth_strategy::TokenFormat::Basic,
-                                    },
-                                    target_location: match token.target_location {
-                                        crate::config::config::TokenTargetLocation::Header => crate::services::auth_service::auth_strategy::TokenTargetLocation::Header,
-                                        crate::config::config::TokenTargetLocation::Query => crate::services::auth_service::auth_strategy::TokenTargetLocation::Query,
-                                        crate::config::config::TokenTargetLocation::Cookie => crate::services::auth_service::auth_strategy::TokenTargetLocation::Header, // Default to Header for Cookie
-                                        crate::config::config::TokenTargetLocation::Body => crate::services::auth_service::auth_strategy::TokenTargetLocation::Body,
-                                    },
-                                    target_key: token.target_key,
-                                }
-                            }).collect(),
-                        }
-                    } else {
-                        // Fallback for old format if tokens is empty
-                        crate::services::auth_service::auth_strategy::TokenExtraction::default()
-                    },
-                    refresh_url: lc.refresh_url,
-                    refresh_method: lc.refresh_method.map(|m| {
-                        match m {
-                            crate::config::config::HttpMethod::Get => crate::services::auth_service::auth_strategy::HttpMethod::GET,
-                            crate::config::config::HttpMethod::Post => crate::services::auth_service::auth_strategy::HttpMethod::POST,
-                            crate::config::config::HttpMethod::Put => crate::services::auth_service::auth_strategy::HttpMethod::PUT,
-                            crate::config::config::HttpMethod::Delete => crate::services::auth_service::auth_strategy::HttpMethod::DELETE,
-                            crate::config::config::HttpMethod::Patch => crate::services::auth_service::auth_strategy::HttpMethod::PATCH,
-                        }
-                    }),
-                }
-            }),
-            token_expiry: config_clone.auth.token_expiry,
-            refresh_buffer: config_clone.auth.refresh_buffer,
-            max_retry_attempts: config_clone.auth.max_retry_attempts,
-        };
-        
-        let auth_service = Arc::new(UnifiedAuthService::new(auth_config)
+        let auth_service = Arc::new(UnifiedAuthService::with_client_builder(auth_config, client)
             .map_err(|e| anyhow::anyhow!("Failed to create auth service: {:?}", e))?);
 
         debug!("Creating ServiceRegistry");
-        let mut service_registry = ServiceRegistry::new(config.clone(), auth_service.clone());
+        let service_registry = ServiceRegistry::new(config.clone(), auth_service.clone());
 
         // Register ZML-based modules
-        // Use config directory to determine ZML directory path
-        let (config_path, _, _) = config.get_config_paths();
-        let config_dir = config_path.parent()
-            .unwrap_or_else(|| std::path::Path::new("."));
-        let zml_dir = config_dir.join("zml");
-        
         info!("Loading ZML modules from: {:?}", zml_dir);
         
         let zml_loader = match ZmlModuleLoader::from_dir(&zml_dir) {
@@ -157,11 +109,26 @@ impl ServiceComposer {
             }
         };
         
-        let zml_factory = ZmlModuleFactory::new(zml_loader.clone(), config.clone(), auth_service.clone());
-        zml_factory.register_modules(&mut service_registry).unwrap();
+        // Authentication services for the default API and every named backend
+        let backends = Arc::new(BackendRegistry::from_config(&config_clone, auth_service.clone())?);
+
+        let zml_factory = ZmlModuleFactory::new(zml_loader.clone(), config.clone(), backends.clone());
+        zml_factory.register_modules(&service_registry).unwrap();
 
         let service_registry = Arc::new(service_registry);
 
+        // Rebuild affected modules when module settings or ZML sources change
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            handle.spawn(Self::rebuild_modules_on_change(
+                config.clone(),
+                backends,
+                service_registry.clone(),
+                zml_dir.clone(),
+                zml_loader,
+            ));
+            handle.spawn(Self::poll_watched_methods(config.clone(), service_registry.clone()));
+        }
+
         info!(
             "ServiceComposer created successfully with {} modules registered",
             service_registry.get_module_count()
@@ -171,7 +138,70 @@ impl ServiceComposer {
             _config: config,
             auth_service,
             service_registry,
+            rest_peer: Arc::new(OnceCell::new()),
+        })
+    }
+
+    /// Run a `batch_call` outside of an MCP session, e.g. for the web server's REST endpoint.
+    /// The calls go to the service registry like those of an MCP HTTP request `parts`, so the
+    /// grant of its token and its headers apply
+    pub async fn call_batch(
+        &self,
+        arguments: JsonObject,
+        parts: axum::http::request::Parts,
+    ) -> std::result::Result<CallToolResult, McpError> {
+        let mut extensions = Extensions::default();
+        extensions.insert(parts);
+        let context = RequestContext {
+            ct: CancellationToken::new(),
+            id: RequestId::String(BATCH_CALL_TOOL.into()),
+            meta: Meta::default(),
+            extensions,
+            peer: self.rest_peer().await?,
+        };
+        self.run_batch_call(Some(arguments), context).await
+    }
+
+    /// Peer of an in-process session kept open for REST callers: tool calls need a peer to report
+    /// progress to, and a peer can only be had from a session
+    async fn rest_peer(&self) -> std::result::Result<Peer<RoleServer>, McpError> {
+        self.rest_peer
+            .get_or_try_init(|| async {
+                let (server_io, client_io) = tokio::io::duplex(64 * 1024);
+                let mut client_info = ClientInfo::default();
+                client_info.client_info.name = REST_CLIENT_NAME.to_string();
+                let (server, client) = tokio::join!(self.clone().serve(server_io), client_info.serve(client_io));
+                let failed = |e: String| McpError::internal_error(format!("Failed to open the REST session: {}", e), None);
+                let (server, client) = (server.map_err(|e| failed(e.to_string()))?, client.map_err(|e| failed(e.to_string()))?);
+                let peer = server.peer().clone();
+                tokio::spawn(async move {
+                    let _ = server.waiting().await;
+                });
+                tokio::spawn(async move {
+                    let _ = client.waiting().await;
+                });
+                Ok::<_, McpError>(peer)
+            })
+            .await
+            .cloned()
+    }
+
+    /// Run the calls of a `batch_call` through the service registry, each routed like a tool call
+    async fn run_batch_call(
+        &self,
+        arguments: Option<JsonObject>,
+        context: RequestContext<RoleServer>,
+    ) -> std::result::Result<CallToolResult, McpError> {
+        let batch = BatchRequest::from_arguments(arguments)?;
+        info!("Running a batch of {} tool calls", batch.calls.len());
+        let output = run_batch(batch, |tool, arguments| {
+            let request = CallToolRequestParam { name: tool.into(), arguments };
+            self.service_registry.route_tool_call(request, context.clone())
         })
+        .await;
+        let text = serde_json::to_string(&output)
+            .map_err(|e| McpError::internal_error(format!("Failed to serialize batch results: {}", e), None))?;
+        Ok(CallToolResult::success(vec![Content::text(text)]))
     }
 
     /// Create a new service composer from WebConfigState
@@ -216,6 +246,101 @@ impl ServiceComposer {
         Ok(Self::new(config)?)
     }
 
+    /// Keep the registered modules in sync with configuration changes: a module change rebuilds
+    /// that module, a change of the module defaults rebuilds every module, and a ZML change reloads
+    /// the sources and rebuilds the modules whose definition changed
+    async fn rebuild_modules_on_change(
+        config: Arc<DynamicConfigManager>,
+        backends: Arc<BackendRegistry>,
+        service_registry: Arc<ServiceRegistry>,
+        zml_dir: std::path::PathBuf,
+        mut zml_loader: Arc<ZmlModuleLoader>,
+    ) {
+        let mut changes = config.subscribe();
+        loop {
+            let (reload_zml, rebuild_all, mut affected) = match changes.recv().await {
+                Ok(ConfigChangeEvent::Module { name, .. }) => (false, false, vec![name]),
+                Ok(ConfigChangeEvent::ModuleDefaults { .. }) => (false, true, Vec::new()),
+                Ok(ConfigChangeEvent::Zml { .. }) => (true, false, Vec::new()),
+                Ok(_) => continue,
+                // Events were dropped, so the affected modules are unknown
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => (true, true, Vec::new()),
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            };
+
+            if reload_zml {
+                let reloaded = match ZmlModuleLoader::from_dir(&zml_dir) {
+                    Ok(loader) => Arc::new(loader),
+                    Err(e) => {
+                        error!("Failed to reload ZML modules from {}: {}", zml_dir.display(), e);
+                        continue;
+                    }
+                };
+                affected.extend(zml_loader.changed_modules(&reloaded));
+                zml_loader = reloaded;
+            }
+            if rebuild_all {
+                affected.extend(service_registry.get_module_names());
+                affected.extend(zml_loader.get_all_module_names());
+            }
+            // Modules depending on a changed module may be activated or deactivated with it
+            let module_config = config.get_config().module_config;
+            let dependents: Vec<String> = affected.iter().flat_map(|name| module_config.dependents(name)).collect();
+            affected.extend(dependents);
+            affected.sort();
+            affected.dedup();
+
+            if affected.is_empty() {
+                continue;
+            }
+            let zml_factory = ZmlModuleFactory::new(zml_loader.clone(), config.clone(), backends.clone());
+            zml_factory.refresh_modules(&service_registry, &affected);
+            info!("Rebuilt ZML modules after configuration change: {}", affected.join(", "));
+        }
+    }
+
+    /// Poll the `watch` methods of the modules on their intervals and notify the clients of the
+    /// changes found; the watches are read from the current configuration on every tick
+    async fn poll_watched_methods(config: Arc<DynamicConfigManager>, service_registry: Arc<ServiceRegistry>) {
+        let mut last_polled: HashMap<(String, String), Instant> = HashMap::new();
+        let mut ticks = tokio::time::interval(Duration::from_secs(1));
+        loop {
+            ticks.tick().await;
+            let module_config = config.get_config().module_config;
+            for (module_name, module) in &module_config.modules {
+                let Some(service) = service_registry.get_module(module_name) else {
+                    continue;
+                };
+                for watch in &module.watch {
+                    let key = (module_name.clone(), watch.method.clone());
+                    let interval = Duration::from_secs(watch.interval_secs.max(1));
+                    if last_polled.get(&key).is_some_and(|polled| polled.elapsed() < interval) {
+                        continue;
+                    }
+                    last_polled.insert(key, Instant::now());
+
+                    let params = watch.params.clone().into_iter().collect();
+                    let result = match service.poll_method(watch.method.clone(), params).await {
+                        Ok(result) => result,
+                        Err(e) => {
+                            warn!("Polling {}::{} failed: {}", module_name, watch.method, e.message);
+                            continue;
+                        }
+                    };
+                    let uri = watch_resource_uri(module_name, &watch.method);
+                    let first_poll = watch_state(&uri).is_none();
+                    let changes = record_poll(module_name, &watch.method, result, watch.key.as_deref());
+                    if first_poll {
+                        notify_resources_changed().await;
+                    } else if !changes.is_empty() {
+                        info!("{} changes found polling {}::{}", changes.len(), module_name, watch.method);
+                        notify_resource_updated(&uri).await;
+                    }
+                }
+            }
+        }
+    }
+
     /// Get auth service reference
     pub fn auth_service(&self) -> &UnifiedAuthService {
         &self.auth_service
@@ -227,12 +352,239 @@ impl ServiceComposer {
     }
 }
 
+/// Convert config::AuthConfig to auth_strategy::AuthConfig
+pub(crate) fn auth_strategy_config(
+    auth: crate::config::config::AuthConfig,
+) -> crate::services::auth_service::auth_strategy::AuthConfig {
+    crate::services::auth_service::auth_strategy::AuthConfig {
+        mode: match auth.mode {
+            crate::config::config::AuthMode::Direct => crate::services::auth_service::auth_strategy::AuthMode::Direct,
+            crate::config::config::AuthMode::Login => crate::services::auth_service::auth_strategy::AuthMode::Login,
+            crate::config::config::AuthMode::Jwt => crate::services::auth_service::auth_strategy::AuthMode::Jwt,
+            crate::config::config::AuthMode::AwsSigV4 => crate::services::auth_service::auth_strategy::AuthMode::AwsSigV4,
+            crate::config::config::AuthMode::Hmac => crate::services::auth_service::auth_strategy::AuthMode::Hmac,
+            crate::config::config::AuthMode::Cookie => crate::services::auth_service::auth_strategy::AuthMode::Cookie,
+            crate::config::config::AuthMode::Custom => crate::services::auth_service::auth_strategy::AuthMode::Custom,
+        },
+        direct_config: auth.direct_config.map(|dc| {
+            crate::services::auth_service::auth_strategy::DirectAuthConfig {
+                auth_type: match dc.auth_type {
+                    crate::config::config::DirectAuthType::Bearer => crate::services::auth_service::auth_strategy::DirectAuthType::Bearer,
+                    crate::config::config::DirectAuthType::ApiKey => crate::services::auth_service::auth_strategy::DirectAuthType::ApiKey,
+                    crate::config::config::DirectAuthType::Basic => crate::services::auth_service::auth_strategy::DirectAuthType::Basic,
+                    crate::config::config::DirectAuthType::Token => crate::services::auth_service::auth_strategy::DirectAuthType::Token,
+                    crate::config::config::DirectAuthType::CustomHeaders => crate::services::auth_service::auth_strategy::DirectAuthType::CustomHeaders,
+                    crate::config::config::DirectAuthType::Digest => crate::services::auth_service::auth_strategy::DirectAuthType::Digest,
+                    crate::config::config::DirectAuthType::Ntlm => crate::services::auth_service::auth_strategy::DirectAuthType::Ntlm,
+                    crate::config::config::DirectAuthType::Negotiate => crate::services::auth_service::auth_strategy::DirectAuthType::Negotiate,
+                },
+                token: dc.token,
+                api_key_name: dc.api_key_name,
+                username: dc.username,
+                password: dc.password,
+                custom_headers: dc.custom_headers,
+                key_pool: dc.key_pool,
+            }
+        }),
+        login_config: auth.login_config.map(|lc| {
+            crate::services::auth_service::auth_strategy::LoginAuthConfig {
+                auth_type: match lc.auth_type {
+                    crate::config::config::LoginAuthType::Json => crate::services::auth_service::auth_strategy::LoginAuthType::Json,
+                    crate::config::config::LoginAuthType::Form => crate::services::auth_service::auth_strategy::LoginAuthType::Form,
+                    crate::config::config::LoginAuthType::OAuth2 => crate::services::auth_service::auth_strategy::LoginAuthType::OAuth2,
+                    crate::config::config::LoginAuthType::ApiKey => crate::services::auth_service::auth_strategy::LoginAuthType::ApiKey,
+                    crate::config::config::LoginAuthType::Custom => crate::services::auth_service::auth_strategy::LoginAuthType::Custom,
+                },
+                url: lc.url,
+                method: match lc.method {
+                    crate::config::config::HttpMethod::Get => crate::services::auth_service::auth_strategy::HttpMethod::GET,
+                    crate::config::config::HttpMethod::Post => crate::services::auth_service::auth_strategy::HttpMethod::POST,
+                    crate::config::config::HttpMethod::Put => crate::services::auth_service::auth_strategy::HttpMethod::PUT,
+                    crate::config::config::HttpMethod::Delete => crate::services::auth_service::auth_strategy::HttpMethod::DELETE,
+                    crate::config::config::HttpMethod::Patch => crate::services::auth_service::auth_strategy::HttpMethod::PATCH,
+                },
+                headers: lc.headers,
+                body: lc.body.map(|b| {
+                    crate::services::auth_service::auth_strategy::LoginRequestBody {
+                        format: match b.format {
+                            crate::config::config::BodyFormat::Json => crate::services::auth_service::auth_strategy::BodyFormat::Json,
+                            crate::config::config::BodyFormat::Form => crate::services::auth_service::auth_strategy::BodyFormat::Form,
+                        },
+                        content: b.content,
+                    }
+                }),
+                response_format: match lc.response_format {
+                    crate::config::config::ResponseFormat::Json => crate::services::auth_service::auth_strategy::ResponseFormat::Json,
+                    crate::config::config::ResponseFormat::Xml => crate::services::auth_service::auth_strategy::ResponseFormat::Xml,
+                    crate::config::config::ResponseFormat::Text => crate::services::auth_service::auth_strategy::ResponseFormat::Text,
+                },
+                token_extraction: if !lc.token_extraction.tokens.is_empty() {
+                    crate::services::auth_service::auth_strategy::TokenExtraction {
+                        tokens: lc.token_extraction.tokens.into_iter().map(|token| {
+                            crate::services::auth_service::auth_strategy::TokenExtractionItem {
+                                source_location: match token.source_location {
+                                    crate::config::config::TokenLocation::Header => crate::services::auth_service::auth_strategy::TokenLocation::Header,
+                                    crate::config::config::TokenLocation::Body => crate::services::auth_service::auth_strategy::TokenLocation::Body,
+                                    crate::config::config::TokenLocation::Query => crate::services::auth_service::auth_strategy::TokenLocation::Query,
+                                },
+                                source_key: token.source_key,
+                                format: match token.format {
+                                    crate::config::config::TokenFormat::Bearer => crate::services::auth_service::auth_strategy::TokenFormat::Bearer,
+                                    crate::config::config::TokenFormat::Token => crate::services::auth_service::auth_strategy::TokenFormat::Raw,
+                                    crate::config::config::TokenFormat::ApiKey => crate::services::auth_service::auth_strategy::TokenFormat::Raw,
+                                    crate::config::config::TokenFormat::Raw => crate::services::auth_service::auth_strategy::TokenFormat::Raw,
+                                    crate::config::config::TokenFormat::Basic => crate::services::auth_service::auth_strategy::TokenFormat::Basic,
+                                },
+                                target_location: match token.target_location {
+                                    crate::config::config::TokenTargetLocation::Header => crate::services::auth_service::auth_strategy::TokenTargetLocation::Header,
+                                    crate::config::config::TokenTargetLocation::Query => crate::services::auth_service::auth_strategy::TokenTargetLocation::Query,
+                                    crate::config::config::TokenTargetLocation::Cookie => crate::services::auth_service::auth_strategy::TokenTargetLocation::Header, // Default to Header for Cookie
+                                    crate::config::config::TokenTargetLocation::Body => crate::services::auth_service::auth_strategy::TokenTargetLocation::Body,
+                                },
+                                target_key: token.target_key,
+                            }
+                        }).collect(),
+                    }
+                } else {
+                    // Fallback for old format if tokens is empty
+                    crate::services::auth_service::auth_strategy::TokenExtraction::default()
+                },
+                refresh_url: lc.refresh_url,
+                refresh_method: lc.refresh_method.map(|m| {
+                    match m {
+                        crate::config::config::HttpMethod::Get => crate::services::auth_service::auth_strategy::HttpMethod::GET,
+                        crate::config::config::HttpMethod::Post => crate::services::auth_service::auth_strategy::HttpMethod::POST,
+                        crate::config::config::HttpMethod::Put => crate::services::auth_service::auth_strategy::HttpMethod::PUT,
+                        crate::config::config::HttpMethod::Delete => crate::services::auth_service::auth_strategy::HttpMethod::DELETE,
+                        crate::config::config::HttpMethod::Patch => crate::services::auth_service::auth_strategy::HttpMethod::PATCH,
+                    }
+                }),
+                oauth2: lc.oauth2,
+                refresh_token: lc.refresh_token,
+            }
+        }),
+        jwt_config: auth.jwt_config,
+        aws_sigv4_config: auth.aws_sigv4_config,
+        hmac_config: auth.hmac_config,
+        cookie_config: auth.cookie_config,
+        custom_config: auth.custom_config,
+        token_expiry: auth.token_expiry,
+        refresh_buffer: auth.refresh_buffer,
+        max_retry_attempts: auth.max_retry_attempts,
+        reauth_markers: auth.reauth_markers,
+    }
+}
+
+/// Built-in resource listing the recorded authentication events
+fn audit_resource() -> Resource {
+    let mut raw = RawResource::new(AUDIT_RESOURCE_URI, "auth_audit");
+    raw.description = Some("Recent logins, token refreshes, failures and token invalidations, newest first".to_string());
+    raw.mime_type = Some("application/json".to_string());
+    raw.no_annotation()
+}
+
+/// Built-in resources the token of a request may read: the webhooks of its granted modules, the
+/// watched methods of its granted tools, and the auth audit for unrestricted tokens only
+fn builtin_resources(grant: Option<&McpGrant>) -> Vec<Resource> {
+    let mut resources = Vec::new();
+    if grant.map_or(true, McpGrant::is_unrestricted) {
+        resources.push(audit_resource());
+    }
+    resources.push(drift_resource());
+    resources.extend(
+        webhook_topics()
+            .into_iter()
+            .filter(|topic| grant.map_or(true, |grant| grant.grants_module(&topic.module)))
+            .map(webhook_resource),
+    );
+    resources.extend(
+        watch_states()
+            .into_iter()
+            .filter(|state| grant.map_or(true, |grant| grant.allows_tool(&state.module, &state.method)))
+            .map(watch_resource),
+    );
+    resources
+}
+
+/// Content of a built-in resource the token of a request may read; `None` for other URIs, so
+/// resources that are not granted are looked up in the modules and not found
+fn read_builtin_resource(uri: &str, grant: Option<&McpGrant>) -> std::result::Result<Option<String>, McpError> {
+    let content = if uri == AUDIT_RESOURCE_URI {
+        if !grant.map_or(true, McpGrant::is_unrestricted) {
+            return Ok(None);
+        }
+        serde_json::to_string(&audit::events())
+            .map_err(|e| McpError::internal_error(format!("Failed to serialize auth events: {}", e), None))?
+    } else if uri == DRIFT_RESOURCE_URI {
+        let mut report = drift_report();
+        report.retain(|module, methods| {
+            methods.retain(|method, _| grant.map_or(true, |grant| grant.allows_tool(module, method)));
+            !methods.is_empty()
+        });
+        serde_json::to_string(&report)
+            .map_err(|e| McpError::internal_error(format!("Failed to serialize drift report: {}", e), None))?
+    } else if let Some(events) = webhook_events(uri) {
+        if events.first().is_some_and(|event| !grant.map_or(true, |grant| grant.grants_module(&event.module))) {
+            return Ok(None);
+        }
+        serde_json::to_string(&events)
+            .map_err(|e| McpError::internal_error(format!("Failed to serialize webhook events: {}", e), None))?
+    } else if let Some(state) = watch_state(uri) {
+        if !grant.map_or(true, |grant| grant.allows_tool(&state.module, &state.method)) {
+            return Ok(None);
+        }
+        serde_json::to_string(&state)
+            .map_err(|e| McpError::internal_error(format!("Failed to serialize watch state: {}", e), None))?
+    } else {
+        return Ok(None);
+    };
+    Ok(Some(content))
+}
+
+/// Built-in resource serving the response schema drift report
+fn drift_resource() -> Resource {
+    let mut raw = RawResource::new(DRIFT_RESOURCE_URI, "schema_drift");
+    raw.description = Some("Response fields of each method that no longer match their ZML declaration".to_string());
+    raw.mime_type = Some("application/json".to_string());
+    raw.no_annotation()
+}
+
+/// Built-in resource serving the received events of a webhook
+fn webhook_resource(topic: WebhookTopic) -> Resource {
+    let mut raw = RawResource::new(topic.uri, format!("webhook_{}_{}", topic.module, topic.event));
+    raw.description = Some(format!(
+        "Last {} '{}' events received from the {} module, newest first",
+        topic.events, topic.event, topic.module
+    ));
+    raw.mime_type = Some("application/json".to_string());
+    raw.no_annotation()
+}
+
+/// Built-in resource serving the latest result and changes of a watched method
+fn watch_resource(state: WatchState) -> Resource {
+    let mut raw = RawResource::new(
+        watch_resource_uri(&state.module, &state.method),
+        format!("watch_{}_{}", state.module, state.method),
+    );
+    raw.description = Some(format!(
+        "Latest result of {}::{}, polled at {}, with its {} most recent changes",
+        state.module,
+        state.method,
+        state.polled_at.to_rfc3339(),
+        state.changes.len()
+    ));
+    raw.mime_type = Some("application/json".to_string());
+    raw.no_annotation()
+}
+
 impl ServerHandler for ServiceComposer {
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
             protocol_version: ProtocolVersion::V_2024_11_05,
             capabilities: ServerCapabilities::builder()
                 .enable_prompts()
+                .enable_resources()
+                .enable_resources_list_changed()
                 .enable_tools()
                 .build(),
             server_info: Implementation::from_build_env(),
@@ -242,6 +594,11 @@ impl ServerHandler for ServiceComposer {
         }
     }
 
+    async fn on_initialized(&self, context: NotificationContext<RoleServer>) {
+        // Clients are told when webhook events or watched methods add or update resources
+        register_peer(context.peer);
+    }
+
     async fn list_tools(
         &self,
         _request: Option<PaginatedRequestParam>,
@@ -250,10 +607,11 @@ impl ServerHandler for ServiceComposer {
         debug!("ServiceComposer: Delegating tool listing to service registry");
 
         // Use service registry to aggregate tools from all modules
-        let all_tools = self
+        let mut all_tools = self
             .service_registry
             .aggregate_tools(_context.clone())
             .await?;
+        all_tools.tools.push(batch_call_tool());
 
         debug!(
             "ServiceComposer: Total tools available: {}",
@@ -306,10 +664,11 @@ impl ServerHandler for ServiceComposer {
         debug!("ServiceComposer: Delegating resource listing to service registry");
 
         // Use service registry to aggregate resources from all modules
-        let all_resources = self
+        let mut all_resources = self
             .service_registry
             .aggregate_resources(_context.clone())
             .await?;
+        all_resources.extend(builtin_resources(mcp_grant(&_context).as_ref()));
 
         info!(
             "ServiceComposer successfully aggregated {} resources from all modules",
@@ -322,6 +681,30 @@ impl ServerHandler for ServiceComposer {
         })
     }
 
+    async fn list_resource_templates(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> std::result::Result<ListResourceTemplatesResult, McpError> {
+        debug!("ServiceComposer: Delegating resource template listing to service registry");
+
+        // Use service registry to aggregate resource templates from all modules
+        let all_templates = self
+            .service_registry
+            .aggregate_resource_templates(_context.clone())
+            .await?;
+
+        info!(
+            "ServiceComposer successfully aggregated {} resource templates from all modules",
+            all_templates.len()
+        );
+
+        Ok(ListResourceTemplatesResult {
+            resource_templates: all_templates,
+            next_cursor: None,
+        })
+    }
+
     async fn call_tool(
         &self,
         request: CallToolRequestParam,
@@ -331,6 +714,9 @@ impl ServerHandler for ServiceComposer {
             "ServiceComposer: Routing tool call '{}' through service registry",
             request.name
         );
+        if request.name == BATCH_CALL_TOOL {
+            return self.run_batch_call(request.arguments, context).await;
+        }
 
         // Use service registry to route the tool call to the appropriate module
         self.service_registry
@@ -364,9 +750,84 @@ impl ServerHandler for ServiceComposer {
             request.uri
         );
 
+        if let Some(text) = read_builtin_resource(&request.uri, mcp_grant(&context).as_ref())? {
+            return Ok(ReadResourceResult {
+                contents: vec![ResourceContents::text(text, request.uri)],
+            });
+        }
+
         // Use service registry to route the resource read request to the appropriate module
         self.service_registry
             .route_resource_request(request, context)
             .await
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::dynamic_service::webhooks::record_event;
+
+    #[test]
+    fn test_builtin_resources_follow_the_token_grant() {
+        record_event("granted-hooks", "created", serde_json::json!({ "id": 1 }), 10);
+        record_event("hidden-hooks", "created", serde_json::json!({ "id": 2 }), 10);
+        record_poll("granted-hooks", "list_items", serde_json::json!([]), None);
+        let grant = McpGrant { name: "ci".to_string(), modules: vec!["granted-hooks".to_string()], tools: Vec::new() };
+
+        let uris: Vec<String> = builtin_resources(Some(&grant)).into_iter().map(|resource| resource.raw.uri).collect();
+        assert!(uris.contains(&"webhook://granted-hooks/created".to_string()));
+        assert!(uris.contains(&"watch://granted-hooks/list_items".to_string()));
+        assert!(!uris.contains(&"webhook://hidden-hooks/created".to_string()));
+        assert!(!uris.contains(&AUDIT_RESOURCE_URI.to_string()));
+        assert!(builtin_resources(None).iter().any(|resource| resource.raw.uri == AUDIT_RESOURCE_URI));
+
+        assert!(read_builtin_resource("webhook://granted-hooks/created", Some(&grant)).unwrap().is_some());
+        assert!(read_builtin_resource("webhook://hidden-hooks/created", Some(&grant)).unwrap().is_none());
+        assert!(read_builtin_resource(AUDIT_RESOURCE_URI, Some(&grant)).unwrap().is_none());
+        assert!(read_builtin_resource(AUDIT_RESOURCE_URI, None).unwrap().is_some());
+
+        // A token granted one tool of the module does not see its webhooks
+        let tool_only = McpGrant {
+            name: "dashboard".to_string(),
+            modules: Vec::new(),
+            tools: vec!["granted-hooks/list_items".to_string()],
+        };
+        let uris: Vec<String> = builtin_resources(Some(&tool_only)).into_iter().map(|resource| resource.raw.uri).collect();
+        assert!(!uris.contains(&"webhook://granted-hooks/created".to_string()));
+        assert!(uris.contains(&"watch://granted-hooks/list_items".to_string()));
+        assert!(read_builtin_resource("webhook://granted-hooks/created", Some(&tool_only)).unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_rest_batches_are_routed_through_the_registry() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = DynamicConfigManager::with_options(
+            dir.path().join("config.json"),
+            dir.path().join("modules.json"),
+            dir.path().join("presets"),
+            None,
+            false,
+        )
+        .unwrap();
+        let composer = ServiceComposer::new(Arc::new(config)).unwrap();
+        let request = || {
+            let (mut parts, _) = axum::http::Request::new(()).into_parts();
+            parts.extensions.insert(McpGrant { name: "ci".to_string(), modules: vec!["user".to_string()], tools: Vec::new() });
+            parts
+        };
+        let arguments = serde_json::json!({ "calls": [{ "tool": "user_get_user" }, { "tool": "bug_get_bug" }] });
+
+        // Every batch reuses the peer opened by the first one
+        for _ in 0..2 {
+            let result = composer.call_batch(arguments.as_object().cloned().unwrap(), request()).await.unwrap();
+            let output: serde_json::Value = serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+            assert_eq!(output["calls"], 2);
+            assert_eq!(output["failed"], 2);
+            assert!(output["results"][1]["error"]["message"].as_str().unwrap().contains("Unknown tool"));
+        }
+        assert_eq!(composer.rest_peer.get().unwrap().peer_info().unwrap().client_info.name, REST_CLIENT_NAME);
+
+        let error = composer.call_batch(JsonObject::new(), request()).await.unwrap_err();
+        assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+    }
+}
@@ -1,20 +1,31 @@
 //! API request builder for dynamic module service
 
-use crate::zml::ast::{MethodDef as ZmlMethodDef, Module as ZmlModule, HttpMethod as ZmlHttpMethod};
-use anyhow::Result;
+use crate::config::module::RetryConfig;
+use crate::services::auth_service::{MultipartField, RequestPolicy};
+use crate::services::dynamic_service::scalar_codec::encode_params_zml;
+use crate::zml::ast::{
+    HttpMethod as ZmlHttpMethod, MethodDef as ZmlMethodDef, Module as ZmlModule, TypeExpr as ZmlTypeExpr,
+};
+use anyhow::{anyhow, Result};
+use base64::Engine;
 use log::debug;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 use reqwest::Method;
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 /// ===================== ZML Support =====================
 /// Build API request for ZML MethodDef
 pub fn build_api_request_zml(
     params: &HashMap<String, Value>,
-    _module: &ZmlModule,
+    module: &ZmlModule,
     method: &ZmlMethodDef,
 ) -> Result<(String, Method, Option<Value>)> {
     debug!("Building API request for ZML method: {:?}", method);
+    // Apply custom scalar encode rules before the values reach path, query or body
+    let params = &encode_params_zml(params, module, method)?;
     let http_method = determine_http_method_zml(&method.http_method);
     let endpoint = build_endpoint_zml(method, params)?;
     let request_body = build_request_body_for_method_zml(&http_method, params, method)?;
@@ -24,6 +35,75 @@ pub fn build_api_request_zml(
     Ok((endpoint, http_method, request_body))
 }
 
+/// Build the HTTP timeout/retry policy of a ZML method. Retries configured for the method or its
+/// module (`configured`) replace the ZML `retry`, which replaces the retries of the backend
+pub fn build_request_policy_zml(
+    method: &ZmlMethodDef,
+    configured: Option<&RetryConfig>,
+    backend: Option<&RetryConfig>,
+) -> RequestPolicy {
+    let mut policy = RequestPolicy {
+        timeout: method.timeout_ms.map(Duration::from_millis),
+        ..RequestPolicy::default()
+    };
+    // Retries of a method sending an idempotency key are not applied twice
+    let idempotent_key = method.idempotent_key.is_some();
+    match (configured, &method.retry) {
+        (None, Some(retry)) => {
+            policy.retry_delays = (1..retry.attempts)
+                .map(|n| Duration::from_millis(retry.delay_ms(n)))
+                .collect();
+            policy.retry_on = retry.on.clone();
+            policy.jitter = true;
+        }
+        (configured, _) => {
+            if let Some(retry) = configured.or(backend) {
+                apply_retry_config(&mut policy, retry);
+            }
+        }
+    }
+    policy.retry_non_idempotent |= idempotent_key;
+    policy
+}
+
+/// Set the retries of a policy from a configured `retry`
+pub fn apply_retry_config(policy: &mut RequestPolicy, retry: &RetryConfig) {
+    policy.retry_delays = retry.delays();
+    policy.retry_on = retry.on.clone();
+    policy.jitter = retry.jitter;
+    policy.max_retry_elapsed = retry.max_elapsed_ms.map(Duration::from_millis);
+    policy.retry_non_idempotent = retry.retry_non_idempotent;
+}
+
+/// Build the extra request headers resolved from configuration; `None` when there are none
+pub fn build_request_headers(headers: &BTreeMap<String, String>) -> Result<Option<HeaderMap>> {
+    if headers.is_empty() {
+        return Ok(None);
+    }
+    let mut header_map = HeaderMap::new();
+    for (name, value) in headers {
+        let header_name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|e| anyhow!("Invalid header name '{}': {}", name, e))?;
+        let header_value =
+            HeaderValue::from_str(value).map_err(|e| anyhow!("Invalid value for header '{}': {}", name, e))?;
+        header_map.insert(header_name, header_value);
+    }
+    Ok(Some(header_map))
+}
+
+/// Add a fresh idempotency key to the headers of a method declaring `idempotent_key`. The key is
+/// generated once per tool call; retries resend the same request, so the API sees it only once
+pub fn add_idempotency_key(method: &ZmlMethodDef, headers: &mut HeaderMap) -> Result<()> {
+    if let Some(name) = &method.idempotent_key {
+        let header_name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|e| anyhow!("Invalid idempotency key header '{}': {}", name, e))?;
+        let key = uuid::Uuid::new_v4().to_string();
+        debug!("Idempotency key of {}: {}", method.name, key);
+        headers.insert(header_name, HeaderValue::from_str(&key)?);
+    }
+    Ok(())
+}
+
 /// Determine HTTP method from ZML HttpMethod enum
 fn determine_http_method_zml(http_method: &ZmlHttpMethod) -> Method {
     match http_method {
@@ -65,11 +145,14 @@ pub fn build_endpoint_zml(method: &ZmlMethodDef, params: &HashMap<String, Value>
     }
 
     // Add query parameters to endpoint if any non-path params are left
-    let has_non_path_params = params.keys().any(|k| !used_path_params.contains(k));
+    // (file params are only ever sent as multipart parts, XML body params in the body)
+    let is_query_param =
+        |k: &String| !used_path_params.contains(k) && !is_file_param(method, k) && method.xml_body.is_none();
+    let has_non_path_params = params.keys().any(|k| is_query_param(k));
     if has_non_path_params {
         let query_params = params
             .iter()
-            .filter(|(k, _)| !used_path_params.contains(k))
+            .filter(|(k, _)| is_query_param(k))
             .map(|(k, v)| format!("{}={}", k, json_value_to_string(v)))
             .collect::<Vec<_>>()
             .join("&");
@@ -131,6 +214,129 @@ pub fn build_request_body_zml(
     Ok(Value::Object(body))
 }
 
+/// Convert a JSON request body into multipart/form-data fields.
+/// `file` params are loaded from a data: URL, a file below `upload_root`, raw base64 or MCP
+/// resource contents; other values become text fields. Without `upload_root` no local file is read.
+pub fn build_multipart_fields_zml(
+    body: &Value,
+    method: &ZmlMethodDef,
+    upload_root: Option<&Path>,
+) -> Result<Vec<MultipartField>> {
+    let mut fields = Vec::new();
+    let Some(entries) = body.as_object() else {
+        return Ok(fields);
+    };
+
+    for (name, value) in entries {
+        if is_file_param(method, name) {
+            let (file_name, content_type, content) = match value {
+                Value::String(source) => load_file_content(name, source, upload_root)?,
+                Value::Object(_) => load_resource_content(name, value)?,
+                _ => return Err(anyhow!("File parameter '{}' must be a string or an MCP resource", name)),
+            };
+            fields.push(MultipartField::File { name: name.clone(), file_name, content_type, content });
+        } else {
+            fields.push(MultipartField::Text { name: name.clone(), value: json_value_to_string(value) });
+        }
+    }
+
+    Ok(fields)
+}
+
+/// Whether the named param is declared with the `file` type
+fn is_file_param(method: &ZmlMethodDef, name: &str) -> bool {
+    method
+        .params
+        .get(name)
+        .map(|p| matches!(p.type_expr, ZmlTypeExpr::File))
+        .unwrap_or(false)
+}
+
+/// Resource link (`{"type": "resource_link", "uri": ...}`) given for a file param, whose
+/// contents must be read before the upload
+pub fn resource_link_uri(value: &Value) -> Option<&str> {
+    if value.get("type").and_then(Value::as_str) != Some("resource_link") {
+        return None;
+    }
+    value.get("uri").and_then(Value::as_str)
+}
+
+/// Resolve a file param value into (file name, media type, raw bytes)
+fn load_file_content(name: &str, source: &str, upload_root: Option<&Path>) -> Result<(String, Option<String>, Vec<u8>)> {
+    let engine = base64::engine::general_purpose::STANDARD;
+
+    // data:[<mediatype>];base64,<payload>
+    if let Some(rest) = source.strip_prefix("data:") {
+        let (media_type, payload) = rest
+            .split_once(";base64,")
+            .ok_or_else(|| anyhow!("File parameter '{}' must be a base64 data: URL", name))?;
+        let content = engine
+            .decode(payload)
+            .map_err(|e| anyhow!("File parameter '{}' has invalid base64 content: {}", name, e))?;
+        let media_type = (!media_type.is_empty()).then(|| media_type.to_string());
+        return Ok((name.to_string(), media_type, content));
+    }
+
+    if let Some(path) = upload_root.map(|root| resolve_upload_path(name, source, root)).transpose()?.flatten() {
+        let content = std::fs::read(&path)
+            .map_err(|e| anyhow!("Failed to read file '{}' for parameter '{}': {}", source, name, e))?;
+        let file_name = path
+            .file_name()
+            .map(|f| f.to_string_lossy().to_string())
+            .unwrap_or_else(|| name.to_string());
+        return Ok((file_name, None, content));
+    }
+
+    let content = engine.decode(source.trim()).map_err(|_| {
+        anyhow!("File parameter '{}' is neither a file below api.upload_root nor valid base64 content", name)
+    })?;
+    Ok((name.to_string(), None, content))
+}
+
+/// Existing file named by a file param, relative to `root` unless absolute. Both paths are
+/// canonicalized first, so `..` and symlinks cannot lead outside `root`; files outside it are rejected.
+fn resolve_upload_path(name: &str, source: &str, root: &Path) -> Result<Option<PathBuf>> {
+    let root = root
+        .canonicalize()
+        .map_err(|e| anyhow!("Upload root {:?} is not accessible: {}", root, e))?;
+    let Ok(path) = root.join(source).canonicalize() else {
+        return Ok(None);
+    };
+    if !path.starts_with(&root) {
+        return Err(anyhow!("File parameter '{}' names a file outside api.upload_root", name));
+    }
+    Ok(path.is_file().then_some(path))
+}
+
+/// Resolve MCP resource contents (`{"uri", "mimeType", "blob" | "text"}`), bare or embedded as
+/// `{"type": "resource", "resource": {...}}`, into (file name, media type, raw bytes). The file
+/// name is the last segment of the resource URI.
+fn load_resource_content(name: &str, value: &Value) -> Result<(String, Option<String>, Vec<u8>)> {
+    if resource_link_uri(value).is_some() {
+        return Err(anyhow!("File parameter '{}' links to a resource that was not read", name));
+    }
+    let resource = value.get("resource").unwrap_or(value);
+    let content = if let Some(blob) = resource.get("blob").and_then(Value::as_str) {
+        base64::engine::general_purpose::STANDARD
+            .decode(blob)
+            .map_err(|e| anyhow!("File parameter '{}' has an invalid base64 blob: {}", name, e))?
+    } else if let Some(text) = resource.get("text").and_then(Value::as_str) {
+        text.as_bytes().to_vec()
+    } else {
+        return Err(anyhow!("File parameter '{}' must be an MCP resource with `blob` or `text` content", name));
+    };
+    let file_name = resource
+        .get("uri")
+        .and_then(Value::as_str)
+        .and_then(|uri| uri.split(['?', '#']).next())
+        .and_then(|uri| uri.rsplit('/').next())
+        .filter(|segment| !segment.is_empty())
+        .unwrap_or(name)
+        .to_string();
+    let media_type = resource.get("mimeType").and_then(Value::as_str).map(String::from);
+    Ok((file_name, media_type, content))
+}
+
 /// Helper to convert serde_json::Value to string for path substitution
 fn json_value_to_string(v: &Value) -> String {
     match v {
@@ -0,0 +1,258 @@
+//! Record-and-replay of API responses for MCP-ANY-REST
+//!
+//! With `MCP_RECORD_DIR` set, every response the API returns is written to that cassette directory,
+//! keyed by the request signature: method, URL and body. With `MCP_REPLAY_DIR` set, requests are
+//! answered from the cassette without network access, and a request that was never recorded
+//! fails. This gives demos and integration tests of generated tools deterministic, offline API
+//! responses. Cassettes are plain JSON files, one per response under `<module>/<operation>-<hash>.json`,
+//! so recorded responses can be reviewed and edited. Credentials are not part of the signature
+//! and are never written.
+
+use super::middleware::{ApiRequest, Next, RequestMiddleware};
+use crate::config::token_cache::cache_key;
+use crate::services::auth_service::auth_strategy::MultipartField;
+use crate::services::auth_service::{ApiResponse, RequestBody, SendError};
+use base64::Engine;
+use log::{debug, info, warn};
+use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+use reqwest::StatusCode;
+use rmcp::ErrorData as McpError;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+/// Environment variable naming the directory API responses are recorded to, relative to the configuration directory
+pub const RECORD_DIR_ENV: &str = "MCP_RECORD_DIR";
+
+/// Environment variable naming the directory API responses are replayed from, relative to the configuration directory
+pub const REPLAY_DIR_ENV: &str = "MCP_REPLAY_DIR";
+
+/// Cassette installed for the process; `None` sends requests to the API
+static CASSETTE: RwLock<Option<Arc<Cassette>>> = RwLock::new(None);
+
+/// Whether responses are written to or served from the cassette directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    Record,
+    Replay,
+}
+
+/// Directory of recorded responses
+#[derive(Debug, Clone)]
+pub struct Cassette {
+    pub mode: CassetteMode,
+    pub dir: PathBuf,
+}
+
+/// Recorded response with the request it answers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Recording {
+    method: String,
+    url: String,
+    status: u16,
+    content_type: Option<String>,
+    /// Body as text when it is UTF-8, so recordings stay readable
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body_text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body_base64: Option<String>,
+}
+
+impl Recording {
+    fn of(request: &ApiRequest, response: &ApiResponse) -> Self {
+        let (body_text, body_base64) = match String::from_utf8(response.body.clone()) {
+            Ok(text) => (Some(text), None),
+            Err(_) => (None, Some(base64::engine::general_purpose::STANDARD.encode(&response.body))),
+        };
+        Self {
+            method: request.method.to_string(),
+            url: request.url.clone(),
+            status: response.status.as_u16(),
+            content_type: response.headers.get(CONTENT_TYPE).and_then(|value| value.to_str().ok()).map(String::from),
+            body_text,
+            body_base64,
+        }
+    }
+
+    fn response(&self) -> ApiResponse {
+        let mut headers = HeaderMap::new();
+        if let Some(value) = self.content_type.as_deref().and_then(|value| HeaderValue::from_str(value).ok()) {
+            headers.insert(CONTENT_TYPE, value);
+        }
+        let body = match (&self.body_text, &self.body_base64) {
+            (Some(text), _) => text.clone().into_bytes(),
+            (None, Some(encoded)) => base64::engine::general_purpose::STANDARD.decode(encoded).unwrap_or_default(),
+            (None, None) => Vec::new(),
+        };
+        ApiResponse { status: StatusCode::from_u16(self.status).unwrap_or(StatusCode::OK), headers, body }
+    }
+}
+
+impl Cassette {
+    /// Cassette selected by `MCP_RECORD_DIR` or `MCP_REPLAY_DIR` (replay wins when both are set)
+    pub fn from_env(config_dir: &Path) -> Option<Self> {
+        let dir = |name: &str| std::env::var(name).ok().filter(|path| !path.is_empty()).map(|path| config_dir.join(path));
+        match (dir(REPLAY_DIR_ENV), dir(RECORD_DIR_ENV)) {
+            (Some(dir), _) => Some(Self { mode: CassetteMode::Replay, dir }),
+            (None, Some(dir)) => Some(Self { mode: CassetteMode::Record, dir }),
+            (None, None) => None,
+        }
+    }
+
+    /// File of the recording answering `request`
+    fn path(&self, request: &ApiRequest) -> PathBuf {
+        let file = format!("{}-{}.json", sanitize(&request.operation), &signature(request)[..16]);
+        self.dir.join(sanitize(&request.module)).join(file)
+    }
+
+    fn load(&self, request: &ApiRequest) -> Option<Recording> {
+        serde_json::from_str(&std::fs::read_to_string(self.path(request)).ok()?).ok()
+    }
+
+    fn save(&self, request: &ApiRequest, response: &ApiResponse) -> std::io::Result<PathBuf> {
+        let path = self.path(request);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, serde_json::to_string_pretty(&Recording::of(request, response))?)?;
+        Ok(path)
+    }
+}
+
+/// Record responses to, or replay them from, `cassette` from now on; `None` sends requests to the API
+pub fn install_cassette(cassette: Option<Cassette>) {
+    if let Some(cassette) = &cassette {
+        info!("{:?} mode: API responses in {:?}", cassette.mode, cassette.dir);
+    }
+    *CASSETTE.write().unwrap() = cassette.map(Arc::new);
+}
+
+/// Cassette installed for the process
+pub fn installed_cassette() -> Option<Arc<Cassette>> {
+    CASSETTE.read().unwrap().clone()
+}
+
+/// Records responses to, or answers requests from, a cassette
+pub struct CassetteMiddleware {
+    cassette: Arc<Cassette>,
+}
+
+impl CassetteMiddleware {
+    pub fn new(cassette: Arc<Cassette>) -> Self {
+        Self { cassette }
+    }
+}
+
+#[async_trait::async_trait]
+impl RequestMiddleware for CassetteMiddleware {
+    fn name(&self) -> &str {
+        "cassette"
+    }
+
+    async fn handle(&self, mut request: ApiRequest, next: Next<'_>) -> Result<ApiResponse, SendError> {
+        match self.cassette.mode {
+            CassetteMode::Replay => match self.cassette.load(&request) {
+                Some(recording) => {
+                    debug!("Replaying {} {} from {:?}", request.method, request.url, self.cassette.path(&request));
+                    Ok(recording.response())
+                }
+                None => Err(SendError::Failed(McpError::internal_error(
+                    format!("No recorded response for {} {} in replay mode", request.method, request.url),
+                    Some(serde_json::json!({ "cassette": self.cassette.path(&request) })),
+                ))),
+            },
+            CassetteMode::Record => {
+                // Recordings need the whole body, so it is not streamed
+                request.policy.body_sink = None;
+                let recorded = request.clone();
+                let response = next.run(request).await?;
+                match self.cassette.save(&recorded, &response) {
+                    Ok(path) => debug!("Recorded {} {} to {:?}", recorded.method, recorded.url, path),
+                    Err(e) => warn!("Failed to record the response of {}: {}", recorded.url, e),
+                }
+                Ok(response)
+            }
+        }
+    }
+}
+
+/// Signature of a request: method, URL and body; headers carry credentials and are left out
+fn signature(request: &ApiRequest) -> String {
+    let body = match &request.body {
+        RequestBody::Empty => String::new(),
+        RequestBody::Json(body) => body.to_string(),
+        RequestBody::Multipart(fields) => fields
+            .iter()
+            .map(|field| match field {
+                MultipartField::Text { name, value } => format!("{}={}", name, value),
+                MultipartField::File { name, file_name, content, .. } => {
+                    format!("{}=@{}:{}", name, file_name, cache_key(&String::from_utf8_lossy(content)))
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("&"),
+        RequestBody::Raw { body, .. } => String::from_utf8_lossy(body).into_owned(),
+    };
+    cache_key(&format!("{} {}\n{}", request.method, request.url, body))
+}
+
+/// Name usable as a file or directory name
+fn sanitize(name: &str) -> String {
+    name.chars().map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' { c } else { '_' }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::auth_service::{DirectAuthType, HttpMethod, RequestPolicy, UnifiedAuthService};
+    use crate::services::dynamic_service::middleware::{AuthMiddleware, MiddlewareChain};
+    use tempfile::tempdir;
+
+    fn request(url: String, body: serde_json::Value) -> ApiRequest {
+        ApiRequest {
+            module: "orders".to_string(),
+            operation: "create_order".to_string(),
+            method: HttpMethod::POST,
+            url,
+            headers: HeaderMap::new(),
+            body: RequestBody::Json(body),
+            policy: RequestPolicy::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_recorded_responses_are_replayed_offline() {
+        let mut server = mockito::Server::new_async().await;
+        let created = server.mock("POST", "/orders")
+            .with_status(201)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id":42}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let dir = tempdir().unwrap();
+        let auth_service = Arc::new(
+            UnifiedAuthService::create_direct_auth(DirectAuthType::Bearer, Some("token".to_string()), None, None, None, None, 3600, 300, 3)
+                .unwrap(),
+        );
+
+        let cassette = |mode| Arc::new(Cassette { mode, dir: dir.path().to_path_buf() });
+        let recording = MiddlewareChain::new(vec![
+            Arc::new(CassetteMiddleware::new(cassette(CassetteMode::Record))),
+            Arc::new(AuthMiddleware::new(auth_service)),
+        ]);
+        let url = format!("{}/orders", server.url());
+        let recorded: serde_json::Value = recording.execute(request(url.clone(), serde_json::json!({ "sku": "A-1" }))).await.unwrap();
+        assert_eq!(recorded["id"], 42);
+        created.assert_async().await;
+
+        // Nothing after the cassette: every answer comes from the recordings
+        let replaying = MiddlewareChain::new(vec![Arc::new(CassetteMiddleware::new(cassette(CassetteMode::Replay)))]);
+        let replayed = replaying.send(request(url.clone(), serde_json::json!({ "sku": "A-1" }))).await.unwrap();
+        assert_eq!(replayed.status, StatusCode::CREATED);
+        assert_eq!(replayed.body, br#"{"id":42}"#.to_vec());
+
+        let error = McpError::from(replaying.send(request(url, serde_json::json!({ "sku": "B-2" }))).await.unwrap_err());
+        assert!(error.message.contains("No recorded response for POST"), "{}", error.message);
+    }
+}
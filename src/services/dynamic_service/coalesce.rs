@@ -0,0 +1,128 @@
+//! Coalescing of identical concurrent GET requests for MCP-ANY-REST
+//!
+//! LLM clients often issue the same lookup several times in parallel. While a GET request is in
+//! flight, identical GET requests (same URL with its query parameters, extra headers and token
+//! scope, as keyed by the response cache) wait for it and share its response or error instead of
+//! reaching the API again. Requests made after it completes are sent as usual.
+
+use super::middleware::{ApiRequest, Next, RequestMiddleware};
+use super::response_cache::request_key;
+use crate::services::auth_service::{ApiResponse, HttpMethod, SendError};
+use log::debug;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::OnceCell;
+
+/// Outcome of an in-flight request, set once it completes
+type Shared = Arc<OnceCell<Result<ApiResponse, SendError>>>;
+
+/// In-flight GET requests by key
+static IN_FLIGHT: Mutex<Option<HashMap<String, Shared>>> = Mutex::new(None);
+
+/// Shares the outcome of an in-flight GET request with identical requests made meanwhile
+pub struct CoalesceMiddleware;
+
+#[async_trait::async_trait]
+impl RequestMiddleware for CoalesceMiddleware {
+    fn name(&self) -> &str {
+        "coalesce"
+    }
+
+    async fn handle(&self, request: ApiRequest, next: Next<'_>) -> Result<ApiResponse, SendError> {
+        // A streamed body goes to the sink of its own request only
+        if request.method != HttpMethod::GET || request.policy.body_sink.is_some() {
+            return next.run(request).await;
+        }
+        let key = request_key(&request);
+        let url = request.url.clone();
+        let shared = IN_FLIGHT
+            .lock()
+            .unwrap()
+            .get_or_insert_with(HashMap::new)
+            .entry(key.clone())
+            .or_default()
+            .clone();
+
+        // The first caller sends the request; the others wait for its outcome. Should it be
+        // cancelled, a waiting caller sends its own.
+        let mut sent = false;
+        let outcome = shared
+            .get_or_init(|| {
+                sent = true;
+                next.run(request)
+            })
+            .await
+            .clone();
+        if !sent {
+            debug!("Shared the response of the in-flight request to {}", url);
+        }
+
+        let mut in_flight = IN_FLIGHT.lock().unwrap();
+        if let Some(requests) = in_flight.as_mut() {
+            if requests.get(&key).is_some_and(|current| Arc::ptr_eq(current, &shared)) {
+                requests.remove(&key);
+            }
+        }
+        outcome
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::auth_service::{RequestBody, RequestPolicy};
+    use crate::services::dynamic_service::middleware::MiddlewareChain;
+    use reqwest::header::HeaderMap;
+    use reqwest::StatusCode;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    /// Answers every request after a delay, counting them
+    struct SlowApi(Arc<AtomicUsize>);
+
+    #[async_trait::async_trait]
+    impl RequestMiddleware for SlowApi {
+        fn name(&self) -> &str {
+            "slow"
+        }
+
+        async fn handle(&self, _request: ApiRequest, _next: Next<'_>) -> Result<ApiResponse, SendError> {
+            let count = self.0.fetch_add(1, Ordering::SeqCst) + 1;
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Ok(ApiResponse { status: StatusCode::OK, headers: HeaderMap::new(), body: count.to_string().into_bytes() })
+        }
+    }
+
+    fn request(method: HttpMethod) -> ApiRequest {
+        ApiRequest {
+            module: "items".to_string(),
+            operation: "get_item".to_string(),
+            method,
+            url: "https://coalesce.example.com/items/1".to_string(),
+            headers: HeaderMap::new(),
+            body: RequestBody::Empty,
+            policy: RequestPolicy::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_identical_gets_share_one_request() {
+        let sent = Arc::new(AtomicUsize::new(0));
+        let chain = MiddlewareChain::new(vec![Arc::new(CoalesceMiddleware), Arc::new(SlowApi(sent.clone()))]);
+
+        let (a, b, c) = tokio::join!(
+            chain.send(request(HttpMethod::GET)),
+            chain.send(request(HttpMethod::GET)),
+            chain.send(request(HttpMethod::GET))
+        );
+        assert_eq!(sent.load(Ordering::SeqCst), 1);
+        assert!([a, b, c].iter().all(|response| response.as_ref().unwrap().body == b"1"));
+
+        // Completed requests are not reused, and writes are never coalesced
+        chain.send(request(HttpMethod::GET)).await.unwrap();
+        assert_eq!(sent.load(Ordering::SeqCst), 2);
+        let (first, second) = tokio::join!(chain.send(request(HttpMethod::POST)), chain.send(request(HttpMethod::POST)));
+        assert!(first.is_ok() && second.is_ok());
+        assert_eq!(sent.load(Ordering::SeqCst), 4);
+    }
+}
@@ -0,0 +1,174 @@
+//! Request body compression for MCP-ANY-REST
+//!
+//! With `compression.request` set in `api` or on a backend, JSON and raw request bodies of at
+//! least `min_request_bytes` are compressed with gzip, deflate or brotli and sent with a
+//! `Content-Encoding` header. An API answering a compressed request with 415 Unsupported Media
+//! Type gets the request again uncompressed, and later requests to the same origin are no longer
+//! compressed. Responses are decompressed by the HTTP client (`compression.decompress`).
+
+use super::middleware::{ApiRequest, Next, RequestMiddleware};
+use crate::config::config::ContentEncoding;
+use crate::services::auth_service::{ApiResponse, RequestBody, SendError};
+use log::{debug, warn};
+use reqwest::header::{HeaderValue, CONTENT_ENCODING};
+use reqwest::StatusCode;
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::sync::Mutex;
+
+/// Origins that rejected compressed request bodies
+static UNSUPPORTED_ORIGINS: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
+/// Compresses request bodies as their policy says
+pub struct CompressionMiddleware;
+
+#[async_trait::async_trait]
+impl RequestMiddleware for CompressionMiddleware {
+    fn name(&self) -> &str {
+        "compression"
+    }
+
+    async fn handle(&self, request: ApiRequest, next: Next<'_>) -> Result<ApiResponse, SendError> {
+        let origin = origin(&request.url);
+        let rejected = UNSUPPORTED_ORIGINS.lock().unwrap().contains(&origin);
+        let compressed = match request.policy.compression.filter(|_| !rejected) {
+            Some(compression) => compress_request(&request, compression.encoding, compression.min_bytes),
+            None => None,
+        };
+        let Some(compressed) = compressed else {
+            return next.run(request).await;
+        };
+
+        let response = next.run(compressed).await?;
+        if response.status != StatusCode::UNSUPPORTED_MEDIA_TYPE {
+            return Ok(response);
+        }
+        warn!("{} does not accept compressed request bodies, sending them uncompressed", origin);
+        UNSUPPORTED_ORIGINS.lock().unwrap().insert(origin);
+        next.run(request).await
+    }
+}
+
+/// `request` with its body compressed; `None` when the body is empty, multipart or too small
+fn compress_request(request: &ApiRequest, encoding: ContentEncoding, min_bytes: usize) -> Option<ApiRequest> {
+    let (content_type, body) = match &request.body {
+        RequestBody::Json(body) => ("application/json".to_string(), serde_json::to_vec(body).ok()?),
+        RequestBody::Raw { content_type, body } => (content_type.clone(), body.clone()),
+        RequestBody::Empty | RequestBody::Multipart(_) => return None,
+    };
+    if body.len() < min_bytes {
+        return None;
+    }
+    let compressed_body = match compress(encoding, &body) {
+        Ok(compressed_body) => compressed_body,
+        Err(e) => {
+            warn!("Failed to compress the request body of {}: {}", request.url, e);
+            return None;
+        }
+    };
+    debug!(
+        "Compressed the request body of {} with {} from {} to {} bytes",
+        request.url,
+        encoding.as_str(),
+        body.len(),
+        compressed_body.len()
+    );
+
+    let mut compressed = request.clone();
+    compressed.headers.insert(CONTENT_ENCODING, HeaderValue::from_static(encoding.as_str()));
+    compressed.body = RequestBody::Raw { content_type, body: compressed_body };
+    Some(compressed)
+}
+
+/// `body` compressed with `encoding`
+pub fn compress(encoding: ContentEncoding, body: &[u8]) -> std::io::Result<Vec<u8>> {
+    match encoding {
+        ContentEncoding::Gzip => {
+            let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(body)?;
+            encoder.finish()
+        }
+        ContentEncoding::Deflate => {
+            // HTTP's "deflate" is the zlib format
+            let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(body)?;
+            encoder.finish()
+        }
+        ContentEncoding::Brotli => {
+            let mut compressed = Vec::new();
+            {
+                let mut encoder = brotli::CompressorWriter::new(&mut compressed, 4096, 5, 22);
+                encoder.write_all(body)?;
+            }
+            Ok(compressed)
+        }
+    }
+}
+
+fn origin(url: &str) -> String {
+    url::Url::parse(url)
+        .map(|url| url.origin().ascii_serialization())
+        .unwrap_or_else(|_| url.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::auth_service::{
+        BodyCompression, DirectAuthType, HttpMethod, RequestPolicy, UnifiedAuthService,
+    };
+    use crate::services::dynamic_service::middleware::{AuthMiddleware, MiddlewareChain};
+    use reqwest::header::HeaderMap;
+    use std::io::Read;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_bodies_are_compressed_until_the_api_rejects_them() {
+        let body = serde_json::json!({ "note": "x".repeat(2048) });
+        let mut gzipped = Vec::new();
+        flate2::read::GzDecoder::new(&compress(ContentEncoding::Gzip, body.to_string().as_bytes()).unwrap()[..])
+            .read_to_end(&mut gzipped)
+            .unwrap();
+        assert_eq!(gzipped, body.to_string().into_bytes());
+
+        let mut server = mockito::Server::new_async().await;
+        let rejected = server
+            .mock("POST", "/notes")
+            .match_header("content-encoding", "gzip")
+            .with_status(415)
+            .expect(1)
+            .create_async()
+            .await;
+        let accepted = server
+            .mock("POST", "/notes")
+            .match_header("content-encoding", mockito::Matcher::Missing)
+            .with_status(201)
+            .with_body(r#"{"id":1}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        let auth_service = Arc::new(
+            UnifiedAuthService::create_direct_auth(DirectAuthType::Bearer, Some("token".to_string()), None, None, None, None, 3600, 300, 3)
+                .unwrap(),
+        );
+        let chain = MiddlewareChain::new(vec![Arc::new(CompressionMiddleware), Arc::new(AuthMiddleware::new(auth_service))]);
+        let request = || ApiRequest {
+            module: "notes".to_string(),
+            operation: "create_note".to_string(),
+            method: HttpMethod::POST,
+            url: format!("{}/notes", server.url()),
+            headers: HeaderMap::new(),
+            body: RequestBody::Json(body.clone()),
+            policy: RequestPolicy {
+                compression: Some(BodyCompression { encoding: ContentEncoding::Gzip, min_bytes: 1024 }),
+                ..RequestPolicy::default()
+            },
+        };
+
+        // The rejected request is sent again uncompressed, and so is the next one
+        assert_eq!(chain.send(request()).await.unwrap().status, StatusCode::CREATED);
+        assert_eq!(chain.send(request()).await.unwrap().status, StatusCode::CREATED);
+        rejected.assert_async().await;
+        accepted.assert_async().await;
+    }
+}
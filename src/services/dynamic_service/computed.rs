@@ -0,0 +1,106 @@
+//! Computed response fields for MCP-ANY-REST
+//!
+//! ZML methods may declare `computed { total_cost = price * quantity }` fields, evaluated as Rhai
+//! expressions over the decoded response before it is returned, so common post-processing does not
+//! fall on the client. An object response gets the fields itself; an array response gets them on
+//! each object item. The response fields, the fields computed before and the call's `params` are in
+//! scope, and `concat(base, id)` joins values as text. A field whose expression fails, e.g. over a
+//! missing field, is set to `null`.
+
+use crate::services::dynamic_service::transform::eval_with_scope;
+use crate::zml::ast::ComputedField;
+use log::debug;
+use rhai::serde::{from_dynamic, to_dynamic};
+use rhai::Scope;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// Add the computed fields to an object response, or to each object of an array response
+pub fn apply_computed_fields(fields: &[ComputedField], response: Value, params: &HashMap<String, Value>) -> Value {
+    if fields.is_empty() {
+        return response;
+    }
+    match response {
+        Value::Object(object) => Value::Object(compute(fields, object, params)),
+        Value::Array(items) => Value::Array(
+            items
+                .into_iter()
+                .map(|item| match item {
+                    Value::Object(object) => Value::Object(compute(fields, object, params)),
+                    item => item,
+                })
+                .collect(),
+        ),
+        response => response,
+    }
+}
+
+fn compute(fields: &[ComputedField], mut object: Map<String, Value>, params: &HashMap<String, Value>) -> Map<String, Value> {
+    let mut scope = Scope::new();
+    if let Ok(params) = to_dynamic(params) {
+        scope.push_constant_dynamic("params", params);
+    }
+    for (name, value) in &object {
+        if let Ok(value) = to_dynamic(value) {
+            scope.push_dynamic(name.as_str(), value);
+        }
+    }
+    for field in fields {
+        let value = eval_with_scope(&field.expression, &mut scope)
+            .and_then(|value| from_dynamic::<Value>(&value).map_err(|e| e.to_string()))
+            .unwrap_or_else(|e| {
+                debug!("Computed field '{}' = {} failed: {}", field.name, field.expression, e);
+                Value::Null
+            });
+        if let Ok(dynamic) = to_dynamic(&value) {
+            scope.push_dynamic(field.name.as_str(), dynamic);
+        }
+        object.insert(field.name.clone(), value);
+    }
+    object
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::zml::ZMLParserWrapper;
+    use serde_json::json;
+
+    #[test]
+    fn test_computed_fields_are_added_to_each_item() {
+        let module = ZMLParserWrapper::new()
+            .parse(
+                r#"
+module Shop {
+    method list_order_lines {
+        http_method: GET
+        uri: "/orders/{order_id}/lines"
+        params {
+            order_id: string
+        }
+        computed {
+            total_cost = price * quantity
+            discounted = total_cost * 0.9
+            url = concat("https://shop.example.com/orders/", params.order_id, "/", id)
+        }
+        response: any
+    }
+}
+"#,
+            )
+            .unwrap();
+        let fields = &module.methods["list_order_lines"].computed;
+        assert_eq!(fields[0].expression, "price * quantity");
+
+        let params = HashMap::from([("order_id".to_string(), json!("o-7"))]);
+        let response = json!([{ "id": 1, "price": 2.5, "quantity": 4 }, { "id": 2, "quantity": 1 }, "note"]);
+        let response = apply_computed_fields(fields, response, &params);
+        assert_eq!(response[0]["total_cost"], json!(10.0));
+        assert_eq!(response[0]["discounted"], json!(9.0));
+        assert_eq!(response[0]["url"], "https://shop.example.com/orders/o-7/1");
+        // No price: the fields depending on it are null
+        assert_eq!(response[1]["total_cost"], Value::Null);
+        assert_eq!(response[1]["url"], "https://shop.example.com/orders/o-7/2");
+        assert_eq!(response[2], "note");
+    }
+}
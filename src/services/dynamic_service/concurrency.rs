@@ -0,0 +1,149 @@
+//! Concurrent request limits for MCP-ANY-REST
+//!
+//! `api.connections.max_concurrent` caps the REST calls in flight at once and
+//! `max_concurrent_per_host` the calls to a single host, so a burst of parallel tool calls cannot
+//! overwhelm a fragile API. Calls over a limit wait for a slot. The limits are installed at startup,
+//! with the HTTP clients, and enforced by `ConcurrencyMiddleware` around each attempt.
+
+use super::middleware::{ApiRequest, Next, RequestMiddleware};
+use crate::config::config::ConnectionConfig;
+use crate::services::auth_service::{ApiResponse, SendError};
+use log::debug;
+use rmcp::ErrorData as McpError;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Limits installed for the process; `None` leaves requests unlimited
+static LIMITS: RwLock<Option<Arc<ConcurrencyLimits>>> = RwLock::new(None);
+
+/// Semaphores of the global limit and of each host
+struct ConcurrencyLimits {
+    global: Option<Arc<Semaphore>>,
+    per_host: Option<usize>,
+    hosts: Mutex<HashMap<String, Arc<Semaphore>>>,
+}
+
+impl ConcurrencyLimits {
+    fn host(&self, url: &str) -> Option<Arc<Semaphore>> {
+        let per_host = self.per_host?;
+        let url = url::Url::parse(url).ok()?;
+        let host = format!("{}:{}", url.host_str()?, url.port_or_known_default().unwrap_or_default());
+        let mut hosts = self.hosts.lock().unwrap();
+        Some(hosts.entry(host).or_insert_with(|| Arc::new(Semaphore::new(per_host))).clone())
+    }
+}
+
+/// Enforce the concurrency limits of `connections` from now on
+pub fn install_concurrency_limits(connections: &ConnectionConfig) {
+    let limits = (connections.max_concurrent.is_some() || connections.max_concurrent_per_host.is_some()).then(|| {
+        Arc::new(ConcurrencyLimits {
+            global: connections.max_concurrent.map(|max| Arc::new(Semaphore::new(max.max(1)))),
+            per_host: connections.max_concurrent_per_host.map(|max| max.max(1)),
+            hosts: Mutex::new(HashMap::new()),
+        })
+    });
+    *LIMITS.write().unwrap() = limits;
+}
+
+/// Holds a slot of the global and the host limit while the request is in flight
+pub struct ConcurrencyMiddleware;
+
+#[async_trait::async_trait]
+impl RequestMiddleware for ConcurrencyMiddleware {
+    fn name(&self) -> &str {
+        "concurrency"
+    }
+
+    async fn handle(&self, request: ApiRequest, next: Next<'_>) -> Result<ApiResponse, SendError> {
+        let limits = LIMITS.read().unwrap().clone();
+        let Some(limits) = limits else {
+            return next.run(request).await;
+        };
+        // The host slot is taken first, so a call waiting on a busy host does not hold a global slot
+        let _host = match limits.host(&request.url) {
+            Some(semaphore) => Some(slot(semaphore, &request.url).await?),
+            None => None,
+        };
+        let _global = match &limits.global {
+            Some(semaphore) => Some(slot(semaphore.clone(), &request.url).await?),
+            None => None,
+        };
+        next.run(request).await
+    }
+}
+
+async fn slot(semaphore: Arc<Semaphore>, url: &str) -> Result<OwnedSemaphorePermit, SendError> {
+    if let Ok(permit) = semaphore.clone().try_acquire_owned() {
+        return Ok(permit);
+    }
+    debug!("Waiting for a free request slot for {}", url);
+    semaphore
+        .acquire_owned()
+        .await
+        .map_err(|e| SendError::Failed(McpError::internal_error(format!("Request slot unavailable: {}", e), None)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::auth_service::{HttpMethod, RequestBody, RequestPolicy};
+    use crate::services::dynamic_service::middleware::MiddlewareChain;
+    use reqwest::header::HeaderMap;
+    use reqwest::StatusCode;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    /// Answers after a pause, recording the most requests it saw at once
+    #[derive(Default)]
+    struct SlowApi {
+        in_flight: AtomicUsize,
+        peak: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl RequestMiddleware for SlowApi {
+        fn name(&self) -> &str {
+            "slow-api"
+        }
+
+        async fn handle(&self, _request: ApiRequest, _next: Next<'_>) -> Result<ApiResponse, SendError> {
+            let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(in_flight, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            Ok(ApiResponse { status: StatusCode::OK, headers: HeaderMap::new(), body: b"{}".to_vec() })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_requests_to_a_host_wait_for_a_slot() {
+        install_concurrency_limits(&ConnectionConfig {
+            max_concurrent: Some(3),
+            max_concurrent_per_host: Some(2),
+            ..ConnectionConfig::default()
+        });
+        let api = Arc::new(SlowApi::default());
+        let middlewares: Vec<Arc<dyn RequestMiddleware>> = vec![Arc::new(ConcurrencyMiddleware), api.clone()];
+        let chain = MiddlewareChain::new(middlewares);
+        let request = |host: &str| ApiRequest {
+            module: "items".to_string(),
+            operation: "list_items".to_string(),
+            method: HttpMethod::GET,
+            url: format!("https://{}/items", host),
+            headers: HeaderMap::new(),
+            body: RequestBody::Empty,
+            policy: RequestPolicy::default(),
+        };
+
+        let calls = (0..6).map(|_| chain.send(request("fragile.example.com")));
+        assert!(futures::future::join_all(calls).await.iter().all(Result::is_ok));
+        assert_eq!(api.peak.load(Ordering::SeqCst), 2);
+
+        let calls = (0..6).map(|index| chain.send(request(&format!("host-{}.example.com", index))));
+        assert!(futures::future::join_all(calls).await.iter().all(Result::is_ok));
+        assert_eq!(api.peak.load(Ordering::SeqCst), 3);
+
+        install_concurrency_limits(&ConnectionConfig::default());
+    }
+}
@@ -0,0 +1,171 @@
+//! Per-method content negotiation for MCP-ANY-REST
+//!
+//! A method declaring `produces: "text/csv"` asks for that media type in its `Accept` header, and
+//! the response is decoded as that type when the API sends no specific one; CSV and XML responses
+//! are converted to JSON. A method declaring `accepts` has its body encoded as that media type
+//! instead of JSON: XML bodies are a `<request>` document with an element per parameter, and
+//! form-urlencoded bodies a field per parameter, with objects sent as JSON text.
+
+use super::xml_body::json_to_xml;
+use crate::services::auth_service::RequestBody;
+use crate::zml::ast::{BodyEncoding, MethodDef};
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT};
+use serde_json::Value;
+
+/// Root element of XML request bodies
+pub const XML_BODY_ROOT: &str = "request";
+
+/// Ask for the media type the method `produces`
+pub fn add_accept_header(method: &MethodDef, headers: &mut HeaderMap) -> Result<(), String> {
+    if let Some(media_type) = &method.produces {
+        let value = HeaderValue::from_str(media_type).map_err(|e| format!("Invalid produces '{}': {}", media_type, e))?;
+        headers.insert(ACCEPT, value);
+    }
+    Ok(())
+}
+
+/// Request body of `body` in the encoding the method `accepts`
+pub fn encode_request_body(method: &MethodDef, body: Option<Value>) -> RequestBody {
+    let Some(body) = body else {
+        return RequestBody::Empty;
+    };
+    let content_type = || method.accepts.clone().unwrap_or_default();
+    match method.body_encoding() {
+        // Vendor JSON types (`application/vnd.api+json`) keep their content type
+        BodyEncoding::Json => match method.accepts.as_deref() {
+            Some(content_type) if content_type != "application/json" => RequestBody::Raw {
+                content_type: content_type.to_string(),
+                body: serde_json::to_vec(&body).unwrap_or_default(),
+            },
+            _ => RequestBody::Json(body),
+        },
+        BodyEncoding::Xml => RequestBody::Raw {
+            content_type: content_type(),
+            body: json_to_xml(XML_BODY_ROOT, &body).into_bytes(),
+        },
+        BodyEncoding::Form => RequestBody::Raw {
+            content_type: content_type(),
+            body: form_encode(&body).into_bytes(),
+        },
+    }
+}
+
+/// Form-urlencoded fields of an object in name order; arrays repeat their field
+fn form_encode(body: &Value) -> String {
+    let mut form = url::form_urlencoded::Serializer::new(String::new());
+    if let Value::Object(fields) = body {
+        // Sorted, so the body does not depend on the order the parameters were collected in
+        let mut fields: Vec<_> = fields.iter().collect();
+        fields.sort_by(|(a, _), (b, _)| a.cmp(b));
+        for (name, value) in fields {
+            match value {
+                Value::Array(items) => {
+                    for item in items {
+                        form.append_pair(name, &form_value(item));
+                    }
+                }
+                Value::Null => {}
+                value => {
+                    form.append_pair(name, &form_value(value));
+                }
+            }
+        }
+    }
+    form.finish()
+}
+
+fn form_value(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::auth_service::{ApiResponse, HttpMethod};
+    use crate::services::dynamic_service::response_content::{decode_response_payload, ResponsePayload};
+    use crate::zml::ZMLParserWrapper;
+    use reqwest::StatusCode;
+    use serde_json::json;
+
+    const REPORT_MODULE: &str = r#"
+module Reports {
+    method export_orders {
+        http_method: GET
+        uri: "/orders/export"
+        produces: "text/csv"
+        response: any
+    }
+
+    method create_order {
+        http_method: POST
+        uri: "/orders"
+        accepts: "application/xml"
+        params {
+            sku: string
+            tags: array<string>
+        }
+        response: any
+    }
+
+    method login {
+        http_method: POST
+        uri: "/login"
+        accepts: "application/x-www-form-urlencoded"
+        params {
+            user: string
+            remember: boolean
+        }
+        response: any
+    }
+}
+"#;
+
+    #[test]
+    fn test_methods_negotiate_request_and_response_media_types() {
+        let module = ZMLParserWrapper::new().parse(REPORT_MODULE).unwrap();
+
+        let export = &module.methods["export_orders"];
+        let mut headers = HeaderMap::new();
+        add_accept_header(export, &mut headers).unwrap();
+        assert_eq!(headers[ACCEPT], "text/csv");
+        // The API labels its CSV as plain text
+        let mut response_headers = HeaderMap::new();
+        response_headers.insert("content-type", HeaderValue::from_static("text/plain"));
+        let response = ApiResponse {
+            status: StatusCode::OK,
+            headers: response_headers,
+            body: b"id,sku,paid\r\n1,\"A-1, large\",true\r\n2,B-2,\r\n".to_vec(),
+        };
+        let payload = decode_response_payload(HttpMethod::GET, "/orders/export", response, 1024, export.produces.as_deref()).unwrap();
+        assert_eq!(
+            payload,
+            ResponsePayload::Json(json!([
+                { "id": 1, "sku": "A-1, large", "paid": true },
+                { "id": 2, "sku": "B-2", "paid": null }
+            ]))
+        );
+
+        let create = &module.methods["create_order"];
+        let RequestBody::Raw { content_type, body } = encode_request_body(create, Some(json!({ "sku": "A&1", "tags": ["x", "y"] }))) else {
+            panic!("XML body expected");
+        };
+        assert_eq!(content_type, "application/xml");
+        assert_eq!(
+            String::from_utf8(body).unwrap(),
+            r#"<?xml version="1.0" encoding="UTF-8"?><request><sku>A&amp;1</sku><tags>x</tags><tags>y</tags></request>"#
+        );
+
+        let login = &module.methods["login"];
+        let RequestBody::Raw { body, .. } = encode_request_body(login, Some(json!({ "user": "ada lovelace", "remember": true }))) else {
+            panic!("form body expected");
+        };
+        assert_eq!(String::from_utf8(body).unwrap(), "remember=true&user=ada+lovelace");
+
+        let unsupported = REPORT_MODULE.replace("accepts: \"application/xml\"", "accepts: \"text/csv\"");
+        let error = ZMLParserWrapper::new().parse(&unsupported).unwrap_err();
+        assert!(error.to_string().contains("accepts 'text/csv' is not supported"), "{}", error);
+    }
+}
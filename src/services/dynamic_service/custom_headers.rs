@@ -0,0 +1,92 @@
+//! Per-call custom headers for MCP-ANY-REST
+//!
+//! Methods listing `allowed_headers` in `modules.json` take an optional `_headers` object, whose
+//! headers (e.g. `Accept-Language` or a correlation ID) are added to the request as given by the
+//! caller. Only the listed headers are accepted, compared case-insensitively; they override the
+//! configured headers but never the impersonation header.
+
+use crate::config::config::Config;
+use rmcp::ErrorData as McpError;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Synthetic object parameter of the call's custom headers
+pub const HEADERS_PARAM: &str = "_headers";
+
+/// Headers the callers of a method may set
+pub fn allowed_headers<'a>(config: &'a Config, module_name: &str, method_name: &str) -> &'a [String] {
+    config
+        .module_config
+        .get_method_config(module_name, method_name)
+        .map(|method| method.allowed_headers.as_slice())
+        .unwrap_or_default()
+}
+
+/// Input schema of the `_headers` parameter
+pub fn headers_param_schema(allowed: &[String]) -> Value {
+    let properties: serde_json::Map<String, Value> = allowed
+        .iter()
+        .map(|name| (name.clone(), serde_json::json!({ "type": "string" })))
+        .collect();
+    serde_json::json!({
+        "type": "object",
+        "properties": properties,
+        "additionalProperties": false,
+        "description": "HTTP headers added to the request"
+    })
+}
+
+/// Take the `_headers` parameter out of the call's `params`, checking each header is allowed
+pub fn take_custom_headers(params: &mut HashMap<String, Value>, allowed: &[String]) -> Result<Vec<(String, String)>, McpError> {
+    let Some(headers) = params.remove(HEADERS_PARAM) else {
+        return Ok(Vec::new());
+    };
+    let Value::Object(headers) = headers else {
+        return Err(McpError::invalid_params(format!("'{}' must be an object of header values", HEADERS_PARAM), None));
+    };
+    headers
+        .into_iter()
+        .map(|(name, value)| {
+            if !allowed.iter().any(|allowed| allowed.eq_ignore_ascii_case(&name)) {
+                return Err(McpError::invalid_params(
+                    format!("Header '{}' is not allowed; allowed headers: {}", name, allowed.join(", ")),
+                    None,
+                ));
+            }
+            match value {
+                Value::String(value) => Ok((name, value)),
+                value => Err(McpError::invalid_params(format!("Header '{}' must be a string, got {}", name, value), None)),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_only_allowed_headers_are_taken() {
+        let allowed = vec!["Accept-Language".to_string(), "X-Correlation-Id".to_string()];
+        let mut params: HashMap<String, Value> = serde_json::from_value(json!({
+            "id": 7,
+            "_headers": { "accept-language": "de-DE", "X-Correlation-Id": "c-42" }
+        }))
+        .unwrap();
+        let mut headers = take_custom_headers(&mut params, &allowed).unwrap();
+        headers.sort();
+        assert_eq!(headers, vec![
+            ("X-Correlation-Id".to_string(), "c-42".to_string()),
+            ("accept-language".to_string(), "de-DE".to_string()),
+        ]);
+        assert!(!params.contains_key(HEADERS_PARAM));
+
+        let mut params = HashMap::from([(HEADERS_PARAM.to_string(), json!({ "Authorization": "Bearer x" }))]);
+        let error = take_custom_headers(&mut params, &allowed).unwrap_err();
+        assert!(error.message.contains("'Authorization' is not allowed"), "{}", error.message);
+
+        let mut params = HashMap::from([(HEADERS_PARAM.to_string(), json!({ "X-Correlation-Id": 42 }))]);
+        assert!(take_custom_headers(&mut params, &allowed).is_err());
+    }
+}
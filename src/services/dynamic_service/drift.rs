@@ -0,0 +1,112 @@
+//! Response schema drift report for MCP-ANY-REST
+//!
+//! Responses that do not match their declared ZML response type are recorded per method and
+//! field: how the field differs, what was found, how many responses showed it and when. The report
+//! is served by `GET /config/drift` and the `schema://drift` MCP resource, so maintainers notice
+//! when an upstream API changed shape. Tool calls are not failed for drift; the report lives in
+//! memory until the process restarts.
+
+use crate::services::dynamic_service::response_validator::{DriftKind, ResponseMismatch};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// URI of the MCP resource serving the drift report
+pub const DRIFT_RESOURCE_URI: &str = "schema://drift";
+
+/// Drifted fields by module, method and field path
+pub type DriftReport = BTreeMap<String, BTreeMap<String, BTreeMap<String, FieldDrift>>>;
+
+/// Process-wide drift report written by the response validation
+static DRIFT: Mutex<DriftReport> = Mutex::new(BTreeMap::new());
+
+/// A response field differing from its declaration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldDrift {
+    pub kind: DriftKind,
+    pub expected: String,
+    /// What the latest drifted response had
+    pub found: String,
+    /// Number of responses with the drift
+    pub occurrences: u64,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+}
+
+/// Record the mismatches of one response of `module::method`
+pub fn record_drift(module: &str, method: &str, mismatches: &[ResponseMismatch]) {
+    let now = Utc::now();
+    let mut report = DRIFT.lock().unwrap();
+    let fields = report.entry(module.to_string()).or_default().entry(method.to_string()).or_default();
+    for mismatch in mismatches {
+        let path = if mismatch.path.is_empty() { "(response)".to_string() } else { mismatch.path.clone() };
+        let drift = fields.entry(path).or_insert_with(|| FieldDrift {
+            kind: mismatch.kind,
+            expected: mismatch.expected.clone(),
+            found: mismatch.found.clone(),
+            occurrences: 0,
+            first_seen: now,
+            last_seen: now,
+        });
+        drift.kind = mismatch.kind;
+        drift.expected = mismatch.expected.clone();
+        drift.found = mismatch.found.clone();
+        drift.occurrences += 1;
+        drift.last_seen = now;
+    }
+}
+
+/// Drift recorded since the process started
+pub fn drift_report() -> DriftReport {
+    DRIFT.lock().unwrap().clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::dynamic_service::response_validator::validate_response_zml;
+    use crate::zml::ZMLParserWrapper;
+    use serde_json::json;
+
+    const USER_MODULE: &str = r#"
+module DriftUsers {
+    type User {
+        id: integer
+        name: string
+        email: string?
+        status: enum ["active", "disabled"]
+    }
+
+    method list_drift_users {
+        http_method: GET
+        uri: "/users"
+        response: array<User>
+    }
+}
+"#;
+
+    #[test]
+    fn test_mismatched_fields_are_reported_per_method() {
+        let module = ZMLParserWrapper::new().parse(USER_MODULE).unwrap();
+        let method = &module.methods["list_drift_users"];
+        let matching = json!([{ "id": 1, "name": "Ada", "status": "active" }]);
+        assert!(validate_response_zml(&matching, method, &module, None).is_ok());
+
+        let drifted = json!([
+            { "id": "1", "status": "active" },
+            { "id": "2", "status": "archived", "email": "bob@example.com" }
+        ]);
+        let error = validate_response_zml(&drifted, method, &module, None).unwrap_err();
+        assert!(error.message.contains("Response field '[].id' must be integer, got string"), "{}", error.message);
+        validate_response_zml(&drifted, method, &module, None).unwrap_err();
+
+        let report = drift_report();
+        let fields = &report["DriftUsers"]["list_drift_users"];
+        assert_eq!(fields["[].id"].occurrences, 2);
+        assert_eq!(fields["[].id"].found, "string");
+        assert_eq!(fields["[].name"].kind, DriftKind::Missing);
+        assert_eq!(fields["[].status"].found, "\"archived\"");
+        assert!(!fields.contains_key("[].email"));
+    }
+}
@@ -0,0 +1,108 @@
+//! Dry-run mode for MCP-ANY-REST
+//!
+//! With `api.dry_run` set, or a tool called with `dry_run: true`, the request is built as usual
+//! (parameters, `pre_request` scripts, URL, headers and body) and returned instead of being sent,
+//! so mappings can be checked without touching the API. Credential headers are masked, and since
+//! authentication is only applied when a request is sent, the auth mode is shown in its place.
+//! Dry runs do not count against the rate limits.
+
+use crate::config::config::Config;
+use crate::config::redact::{is_sensitive_key, mask};
+use crate::services::auth_service::auth_strategy::{AuthMode, MultipartField};
+use crate::services::auth_service::RequestBody;
+use crate::services::dynamic_service::middleware::ApiRequest;
+use crate::zml::ast::MethodDef;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// Synthetic boolean parameter returning the request instead of sending it
+pub const DRY_RUN_PARAM: &str = "dry_run";
+
+/// Whether `method` takes the synthetic `dry_run` parameter, i.e. does not declare one itself
+pub fn supports_dry_run(method: &MethodDef) -> bool {
+    !method.params.contains_key(DRY_RUN_PARAM)
+}
+
+/// Take the `dry_run` parameter out of the call's `params`; true when it or `api.dry_run` is set
+pub fn take_dry_run(params: &mut HashMap<String, Value>, method: &MethodDef, config: &Config) -> bool {
+    let requested = supports_dry_run(method)
+        && params.remove(DRY_RUN_PARAM).and_then(|value| value.as_bool()).unwrap_or(false);
+    requested || config.api.dry_run
+}
+
+/// The request as it would be sent: method, URL, headers with credentials masked and body
+pub fn describe_request(request: &ApiRequest, auth_mode: AuthMode) -> Value {
+    let headers: Map<String, Value> = request
+        .headers
+        .iter()
+        .map(|(name, value)| {
+            let value = value.to_str().map(String::from).unwrap_or_else(|_| "<binary>".to_string());
+            let value = if is_sensitive_key(name.as_str()) { mask(&value) } else { value };
+            (name.to_string(), Value::String(value))
+        })
+        .collect();
+    let body = match &request.body {
+        RequestBody::Empty => Value::Null,
+        RequestBody::Json(body) => body.clone(),
+        RequestBody::Multipart(fields) => Value::Array(fields.iter().map(describe_field).collect()),
+        RequestBody::Raw { content_type, body } => serde_json::json!({
+            "content_type": content_type,
+            "text": String::from_utf8_lossy(body),
+        }),
+    };
+
+    serde_json::json!({
+        "dry_run": true,
+        "method": request.method.to_string(),
+        "url": request.url,
+        "headers": headers,
+        "auth": auth_mode.to_string(),
+        "body": body,
+    })
+}
+
+/// Multipart field with file contents replaced by their size
+fn describe_field(field: &MultipartField) -> Value {
+    match field {
+        MultipartField::Text { name, value } => serde_json::json!({ "name": name, "value": value }),
+        MultipartField::File { name, file_name, content_type, content } => serde_json::json!({
+            "name": name,
+            "file_name": file_name,
+            "content_type": content_type,
+            "bytes": content.len(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::auth_service::auth_strategy::HttpMethod;
+    use crate::services::auth_service::RequestPolicy;
+    use reqwest::header::{HeaderMap, HeaderValue};
+    use serde_json::json;
+
+    #[test]
+    fn test_dry_runs_describe_the_request_with_masked_credentials() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", HeaderValue::from_static("k-1234567890"));
+        headers.insert("x-tenant", HeaderValue::from_static("acme"));
+        let request = ApiRequest {
+            module: "orders".to_string(),
+            operation: "create_order".to_string(),
+            method: HttpMethod::POST,
+            url: "https://api.example.com/orders".to_string(),
+            headers,
+            body: RequestBody::Json(json!({ "sku": "A-1", "quantity": 2 })),
+            policy: RequestPolicy::default(),
+        };
+
+        let described = describe_request(&request, AuthMode::Direct);
+        assert_eq!(described["method"], "POST");
+        assert_eq!(described["url"], "https://api.example.com/orders");
+        assert_eq!(described["headers"]["x-api-key"], "****7890");
+        assert_eq!(described["headers"]["x-tenant"], "acme");
+        assert_eq!(described["auth"], "direct");
+        assert_eq!(described["body"], json!({ "sku": "A-1", "quantity": 2 }));
+    }
+}
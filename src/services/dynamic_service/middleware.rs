@@ -0,0 +1,312 @@
+//! Outgoing request middleware for MCP-ANY-REST
+//!
+//! Every REST call made for a tool or resource passes through a chain of `RequestMiddleware`s.
+//! Each one receives the request and the rest of the chain: it can change the request before
+//! passing it on, answer it without calling the rest, or inspect the response coming back. The
+//! built-in chain ends with cache, coalesce, compression, retry, concurrency, logging and auth
+//! middlewares, preceded by the cassette middleware in record or replay mode; applications
+//! embedding mcp-any-rest add theirs in front with `register_request_middleware`.
+
+use super::cassette::{installed_cassette, CassetteMiddleware};
+use super::coalesce::CoalesceMiddleware;
+use super::compression::CompressionMiddleware;
+use super::concurrency::ConcurrencyMiddleware;
+use super::provenance::record;
+use super::response_cache::CacheMiddleware;
+use crate::services::auth_service::unified_auth_service::{parse_response, retry_delay};
+use crate::services::auth_service::{
+    ApiResponse, HttpMethod, RequestBody, RequestPolicy, SendError, UnifiedAuthService,
+};
+use log::{debug, info, warn};
+use reqwest::header::HeaderMap;
+use rmcp::ErrorData as McpError;
+use serde::de::DeserializeOwned;
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
+
+/// Middlewares registered by applications, run before the built-in ones
+static REGISTERED_MIDDLEWARES: RwLock<Vec<Arc<dyn RequestMiddleware>>> = RwLock::new(Vec::new());
+
+/// REST call made for a tool or resource
+#[derive(Debug, Clone)]
+pub struct ApiRequest {
+    /// Module of the tool or resource
+    pub module: String,
+    /// Tool or resource name
+    pub operation: String,
+    pub method: HttpMethod,
+    pub url: String,
+    /// Headers sent in addition to the credentials
+    pub headers: HeaderMap,
+    pub body: RequestBody,
+    pub policy: RequestPolicy,
+}
+
+/// Step of the outgoing request chain
+#[async_trait::async_trait]
+pub trait RequestMiddleware: Send + Sync {
+    /// Name shown in logs
+    fn name(&self) -> &str;
+
+    /// Handle `request`, usually by passing it to `next.run`
+    async fn handle(&self, request: ApiRequest, next: Next<'_>) -> Result<ApiResponse, SendError>;
+}
+
+/// Rest of the chain after the running middleware; it can be copied to send a request more than once
+#[derive(Clone, Copy)]
+pub struct Next<'a> {
+    middlewares: &'a [Arc<dyn RequestMiddleware>],
+}
+
+impl Next<'_> {
+    /// Pass the request to the next middleware
+    pub async fn run(self, request: ApiRequest) -> Result<ApiResponse, SendError> {
+        match self.middlewares.split_first() {
+            Some((middleware, rest)) => middleware.handle(request, Next { middlewares: rest }).await,
+            None => Err(SendError::Failed(McpError::internal_error(
+                format!("No middleware sent the API request to {}", request.url),
+                None,
+            ))),
+        }
+    }
+}
+
+/// Chain of middlewares a request passes through
+#[derive(Clone)]
+pub struct MiddlewareChain {
+    middlewares: Vec<Arc<dyn RequestMiddleware>>,
+}
+
+impl MiddlewareChain {
+    /// Chain of `middlewares`, the last of which must send the request
+    pub fn new(middlewares: Vec<Arc<dyn RequestMiddleware>>) -> Self {
+        Self { middlewares }
+    }
+
+    /// Registered middlewares followed by the built-in cassette (when installed), cache, coalesce,
+    /// compression, retry, concurrency, logging and auth middlewares
+    pub fn standard(auth_service: Arc<UnifiedAuthService>) -> Self {
+        let mut middlewares = REGISTERED_MIDDLEWARES.read().unwrap().clone();
+        if let Some(cassette) = installed_cassette() {
+            middlewares.push(Arc::new(CassetteMiddleware::new(cassette)));
+        }
+        middlewares.push(Arc::new(CacheMiddleware));
+        middlewares.push(Arc::new(CoalesceMiddleware));
+        middlewares.push(Arc::new(CompressionMiddleware));
+        middlewares.push(Arc::new(RetryMiddleware));
+        middlewares.push(Arc::new(ConcurrencyMiddleware));
+        middlewares.push(Arc::new(LoggingMiddleware));
+        middlewares.push(Arc::new(AuthMiddleware::new(auth_service)));
+        Self { middlewares }
+    }
+
+    /// Names of the middlewares in order
+    pub fn names(&self) -> Vec<String> {
+        self.middlewares.iter().map(|middleware| middleware.name().to_string()).collect()
+    }
+
+    /// Send a request through the chain
+    pub async fn send(&self, request: ApiRequest) -> Result<ApiResponse, SendError> {
+        Next { middlewares: &self.middlewares }.run(request).await
+    }
+
+    /// Send a request through the chain and parse the JSON response
+    pub async fn execute<T: DeserializeOwned>(&self, request: ApiRequest) -> Result<T, McpError> {
+        let (method, url) = (request.method, request.url.clone());
+        parse_response(method, &url, self.send(request).await?)
+    }
+}
+
+/// Add a middleware to the chain of every later REST call, after those registered before it
+pub fn register_request_middleware(middleware: Arc<dyn RequestMiddleware>) {
+    info!("Registering request middleware: {}", middleware.name());
+    REGISTERED_MIDDLEWARES.write().unwrap().push(middleware);
+}
+
+/// Retries transport errors and the statuses of the request's policy, waiting as `retry_delay` says
+pub struct RetryMiddleware;
+
+#[async_trait::async_trait]
+impl RequestMiddleware for RetryMiddleware {
+    fn name(&self) -> &str {
+        "retry"
+    }
+
+    async fn handle(&self, request: ApiRequest, next: Next<'_>) -> Result<ApiResponse, SendError> {
+        let started = Instant::now();
+        let mut attempt = 0;
+        loop {
+            let outcome = Next { middlewares: next.middlewares }.run(request.clone()).await;
+            match retry_delay(request.method, &request.policy, &outcome, attempt, started.elapsed()) {
+                Some((delay, reason)) => {
+                    attempt += 1;
+                    record(|provenance| provenance.retries += 1);
+                    warn!(
+                        "API request to {} {}, retrying in {:?} (attempt {}/{})",
+                        request.url, reason, delay, attempt, request.policy.retry_delays.len()
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                None => return outcome,
+            }
+        }
+    }
+}
+
+/// Logs every attempt with its outcome and duration
+pub struct LoggingMiddleware;
+
+#[async_trait::async_trait]
+impl RequestMiddleware for LoggingMiddleware {
+    fn name(&self) -> &str {
+        "logging"
+    }
+
+    async fn handle(&self, request: ApiRequest, next: Next<'_>) -> Result<ApiResponse, SendError> {
+        let started = Instant::now();
+        let (module, operation, method, url) =
+            (request.module.clone(), request.operation.clone(), request.method, request.url.clone());
+        debug!("{}::{} -> {} {}", module, operation, method, url);
+        let outcome = next.run(request).await;
+        record(|provenance| {
+            provenance.url = Some(url.clone());
+            provenance.status = outcome.as_ref().ok().map(|response| response.status.as_u16());
+            provenance.requests += 1;
+        });
+        match &outcome {
+            Ok(response) => info!(
+                "{}::{} {} {} returned {} in {} ms",
+                module, operation, method, url, response.status, started.elapsed().as_millis()
+            ),
+            Err(_) => warn!(
+                "{}::{} {} {} failed after {} ms",
+                module, operation, method, url, started.elapsed().as_millis()
+            ),
+        }
+        outcome
+    }
+}
+
+/// Sends the request with the credentials of the auth service; ends the chain
+pub struct AuthMiddleware {
+    auth_service: Arc<UnifiedAuthService>,
+}
+
+impl AuthMiddleware {
+    pub fn new(auth_service: Arc<UnifiedAuthService>) -> Self {
+        Self { auth_service }
+    }
+}
+
+#[async_trait::async_trait]
+impl RequestMiddleware for AuthMiddleware {
+    fn name(&self) -> &str {
+        "auth"
+    }
+
+    async fn handle(&self, request: ApiRequest, _next: Next<'_>) -> Result<ApiResponse, SendError> {
+        let headers = (!request.headers.is_empty()).then_some(request.headers);
+        self.auth_service
+            .send_request(request.method, &request.url, headers, &request.body, &request.policy)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::auth_service::DirectAuthType;
+    use reqwest::header::HeaderValue;
+    use reqwest::StatusCode;
+
+    /// Adds a header before the request is sent
+    struct TenantHeader;
+
+    #[async_trait::async_trait]
+    impl RequestMiddleware for TenantHeader {
+        fn name(&self) -> &str {
+            "tenant"
+        }
+
+        async fn handle(&self, mut request: ApiRequest, next: Next<'_>) -> Result<ApiResponse, SendError> {
+            request.headers.insert("x-tenant", HeaderValue::from_static("acme"));
+            next.run(request).await
+        }
+    }
+
+    /// Answers `/canned` requests without calling the API
+    struct Canned;
+
+    #[async_trait::async_trait]
+    impl RequestMiddleware for Canned {
+        fn name(&self) -> &str {
+            "canned"
+        }
+
+        async fn handle(&self, request: ApiRequest, next: Next<'_>) -> Result<ApiResponse, SendError> {
+            if request.url.ends_with("/canned") {
+                return Ok(ApiResponse {
+                    status: StatusCode::OK,
+                    headers: HeaderMap::new(),
+                    body: br#"{"canned":true}"#.to_vec(),
+                });
+            }
+            next.run(request).await
+        }
+    }
+
+    fn request(url: String) -> ApiRequest {
+        ApiRequest {
+            module: "items".to_string(),
+            operation: "list_items".to_string(),
+            method: HttpMethod::GET,
+            url,
+            headers: HeaderMap::new(),
+            body: RequestBody::Empty,
+            policy: RequestPolicy::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chain_runs_middlewares_around_the_request() {
+        let mut server = mockito::Server::new_async().await;
+        let items = server.mock("GET", "/items")
+            .match_header("x-tenant", "acme")
+            .match_header("authorization", "Bearer token")
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"items":[]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let auth_service = Arc::new(
+            UnifiedAuthService::create_direct_auth(
+                DirectAuthType::Bearer,
+                Some("token".to_string()),
+                None,
+                None,
+                None,
+                None,
+                3600,
+                300,
+                3,
+            )
+            .unwrap(),
+        );
+        let mut middlewares: Vec<Arc<dyn RequestMiddleware>> = vec![Arc::new(Canned), Arc::new(TenantHeader)];
+        middlewares.extend(MiddlewareChain::standard(auth_service).middlewares);
+        let chain = MiddlewareChain::new(middlewares);
+        assert_eq!(chain.names()[..2], ["canned", "tenant"]);
+        assert_eq!(
+            chain.names()[chain.names().len() - 7..],
+            ["cache", "coalesce", "compression", "retry", "concurrency", "logging", "auth"]
+        );
+
+        let canned: serde_json::Value = chain.execute(request(format!("{}/canned", server.url()))).await.unwrap();
+        assert_eq!(canned["canned"], true);
+        let listed: serde_json::Value = chain.execute(request(format!("{}/items", server.url()))).await.unwrap();
+        assert_eq!(listed["items"], serde_json::json!([]));
+
+        items.assert_async().await;
+    }
+}
@@ -0,0 +1,76 @@
+//! Mock responses for MCP-ANY-REST
+//!
+//! Methods may configure static `mocks` in `modules.json`. With `mock: true` set globally or on
+//! the module, tool calls are answered with the first mock whose `when` matches the call's
+//! parameters instead of calling the API, so the MCP surface can be designed and tried before the
+//! backend exists. A call no mock matches fails rather than reaching the API.
+
+use crate::config::module::MockResponse;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// First of `mocks` matching the call's `params`
+pub fn find_mock<'a>(mocks: &'a [MockResponse], params: &HashMap<String, Value>) -> Option<&'a MockResponse> {
+    mocks
+        .iter()
+        .find(|mock| mock.when.iter().all(|(name, expected)| params.get(name).is_some_and(|actual| matches(expected, actual))))
+}
+
+/// Whether a parameter value matches the expected one; expected strings may use `*` wildcards and
+/// are matched against numbers and booleans as text
+fn matches(expected: &Value, actual: &Value) -> bool {
+    match (expected, actual) {
+        (Value::String(pattern), Value::String(text)) => wildcard_match(pattern, text),
+        (Value::String(pattern), Value::Number(_) | Value::Bool(_)) => wildcard_match(pattern, &actual.to_string()),
+        (Value::Number(expected), Value::Number(actual)) => expected.as_f64() == actual.as_f64(),
+        _ => expected == actual,
+    }
+}
+
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No wildcard
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_first_matching_mock_is_served() {
+        let mocks: Vec<MockResponse> = serde_json::from_value(json!([
+            { "when": { "id": 1 }, "response": { "id": 1, "name": "Ada" } },
+            { "when": { "email": "*@example.com", "active": "true" }, "response": { "id": 2, "name": "Bob" } },
+            { "response": { "error": "not found" } }
+        ]))
+        .unwrap();
+        let params = |value: Value| -> HashMap<String, Value> { serde_json::from_value(value).unwrap() };
+
+        assert_eq!(find_mock(&mocks, &params(json!({ "id": 1.0 }))).unwrap().response["name"], "Ada");
+        let bob = params(json!({ "email": "bob@example.com", "active": true }));
+        assert_eq!(find_mock(&mocks, &bob).unwrap().response["name"], "Bob");
+        let other = params(json!({ "email": "bob@example.org", "active": true }));
+        assert_eq!(find_mock(&mocks, &other).unwrap().response["error"], "not found");
+        assert!(find_mock(&mocks[..2], &params(json!({ "id": 3 }))).is_none());
+
+        assert!(wildcard_match("a*b*c", "a-b-c"));
+        assert!(!wildcard_match("a*b", "ab-"));
+        assert!(wildcard_match("*", ""));
+    }
+}
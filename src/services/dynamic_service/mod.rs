@@ -3,13 +3,48 @@
 pub mod zml_dynamic_service;
 pub mod zml_module_factory;
 pub mod api_request_builder;
+pub mod cassette;
+pub mod coalesce;
+pub mod compression;
+pub mod computed;
+pub mod content_negotiation;
+pub mod concurrency;
+pub mod custom_headers;
+pub mod drift;
+pub mod dry_run;
+pub mod middleware;
+pub mod mock;
+pub mod notifications;
+pub mod pagination;
 pub mod schema_builder;
 pub mod parameter_validator;
 pub mod response_validator;
+pub mod prompt_builder;
+pub mod provenance;
+pub mod rate_limiter;
+pub mod resource_builder;
+pub mod response_cache;
+pub mod response_content;
+pub mod response_filter;
+pub mod response_limits;
+pub mod scalar_codec;
+pub mod streaming;
+pub mod transform;
+pub mod watch;
+pub mod webhooks;
+pub mod workflow;
+pub mod xml_body;
 
 pub use zml_dynamic_service::ZmlDynamicService;
 pub use zml_module_factory::ZmlModuleFactory;
-pub use api_request_builder::{build_api_request_zml, build_endpoint_zml, build_request_body_zml};
+pub use api_request_builder::{
+    apply_retry_config, build_api_request_zml, build_endpoint_zml, build_multipart_fields_zml,
+    build_request_body_zml, build_request_policy_zml,
+};
+pub use middleware::{register_request_middleware, ApiRequest, MiddlewareChain, Next, RequestMiddleware};
 pub use schema_builder::{build_input_schema_zml, build_output_schema_zml};
-pub use parameter_validator::validate_parameters_zml;
-pub use response_validator::validate_response_zml;
\ No newline at end of file
+pub use parameter_validator::{validate_parameters_zml, with_source_location};
+pub use response_validator::validate_response_zml;
+pub use prompt_builder::{build_prompt_zml, render_prompt_zml};
+pub use resource_builder::{build_resource_template_zml, build_resource_zml, expand_resource_uri_zml, match_resource_zml};
+pub use scalar_codec::{decode_response_zml, encode_params_zml};
\ No newline at end of file
@@ -0,0 +1,51 @@
+//! Resource notifications of MCP-ANY-REST
+//!
+//! Clients register when their session is initialized and are sent notifications when resources
+//! change outside of their own calls, e.g. on webhook events or changes found by polling. Clients
+//! whose session is gone are forgotten the first time a notification fails.
+
+use log::debug;
+use rmcp::model::ResourceUpdatedNotificationParam;
+use rmcp::service::ServiceError;
+use rmcp::{Peer, RoleServer};
+use std::future::Future;
+use std::sync::Mutex;
+
+/// MCP clients notified of resource changes
+static PEERS: Mutex<Vec<Peer<RoleServer>>> = Mutex::new(Vec::new());
+
+/// Notify `peer` of resource changes until its session ends
+pub fn register_peer(peer: Peer<RoleServer>) {
+    PEERS.lock().unwrap().push(peer);
+}
+
+/// Send `notifications/resources/list_changed` to the registered clients
+pub async fn notify_resources_changed() {
+    notify_peers(|peer| async move { peer.notify_resource_list_changed().await.map(|()| peer) }).await;
+}
+
+/// Send `notifications/resources/updated` for `uri` to the registered clients
+pub async fn notify_resource_updated(uri: &str) {
+    notify_peers(|peer| async move {
+        peer.notify_resource_updated(ResourceUpdatedNotificationParam { uri: uri.to_string() })
+            .await
+            .map(|()| peer)
+    })
+    .await;
+}
+
+async fn notify_peers<F, Fut>(notify: F)
+where
+    F: Fn(Peer<RoleServer>) -> Fut,
+    Fut: Future<Output = Result<Peer<RoleServer>, ServiceError>>,
+{
+    let peers = std::mem::take(&mut *PEERS.lock().unwrap());
+    let mut connected = Vec::with_capacity(peers.len());
+    for peer in peers {
+        match notify(peer).await {
+            Ok(peer) => connected.push(peer),
+            Err(e) => debug!("Dropping MCP client from resource notifications: {}", e),
+        }
+    }
+    PEERS.lock().unwrap().extend(connected);
+}
@@ -0,0 +1,199 @@
+//! Server-side pagination for MCP-ANY-REST
+//!
+//! Methods declaring `paginate { ... }` in ZML take a synthetic `fetch_all` parameter. With it set,
+//! the tool requests page after page, each one a regular call subject to the rate limits, merges
+//! the item arrays and returns them with their count, so clients do not have to page through
+//! results themselves. Fetching stops at an empty or short page, when the API returns no next
+//! cursor, or at the page limit: the ZML `max_pages`, else `fetch_all_max_pages`.
+
+use crate::config::module::GlobalModuleConfig;
+use crate::zml::ast::{MethodDef, Pagination, PaginationStyle, Value as ZmlValue};
+use rmcp::ErrorData as McpError;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+
+/// Pages fetched at most when neither ZML nor the configuration sets a limit
+pub const DEFAULT_FETCH_ALL_MAX_PAGES: u32 = 10;
+
+/// Pages `fetch_all` requests at most for a method paginated as `pagination`
+pub fn max_pages(pagination: &Pagination, config: &GlobalModuleConfig) -> u32 {
+    pagination
+        .max_pages
+        .or(config.fetch_all_max_pages)
+        .unwrap_or(DEFAULT_FETCH_ALL_MAX_PAGES)
+        .max(1)
+}
+
+/// Request every page of `method` with `fetch_page`, starting from `params`, and merge the items
+/// into `{"items": [...], "total_count": n, "pages": n, "complete": bool}`; `complete` is false
+/// when the page limit cut the results short
+pub async fn fetch_all_pages<F, Fut>(
+    method: &MethodDef,
+    mut params: HashMap<String, Value>,
+    max_pages: u32,
+    mut fetch_page: F,
+) -> Result<Value, McpError>
+where
+    F: FnMut(HashMap<String, Value>) -> Fut,
+    Fut: Future<Output = Result<Value, McpError>>,
+{
+    let Some(pagination) = &method.paginate else {
+        return fetch_page(params).await;
+    };
+    let page_size = pagination.size_param.as_ref().and_then(|name| {
+        params.get(name).and_then(as_number).or_else(|| match method.params.get(name)?.default_value {
+            Some(ZmlValue::Integer(size)) => u64::try_from(size).ok(),
+            _ => None,
+        })
+    });
+
+    let mut items = Vec::new();
+    let mut pages = 0;
+    let mut complete = false;
+    while pages < max_pages {
+        let response = fetch_page(params.clone()).await?;
+        pages += 1;
+        let page_items = lookup(&response, pagination.items.as_deref()).and_then(Value::as_array).ok_or_else(|| {
+            McpError::internal_error(
+                format!(
+                    "Page {} of {} has no item array at '{}'",
+                    pages,
+                    method.name,
+                    pagination.items.as_deref().unwrap_or("<response>")
+                ),
+                None,
+            )
+        })?;
+        let count = page_items.len();
+        items.extend(page_items.iter().cloned());
+
+        let current = params.get(&pagination.param).and_then(as_number);
+        let next = match pagination.style {
+            PaginationStyle::Page => (count > 0).then(|| Value::from(current.unwrap_or(1) + 1)),
+            PaginationStyle::Offset => (count > 0).then(|| Value::from(current.unwrap_or(0) + count as u64)),
+            PaginationStyle::Cursor => lookup(&response, pagination.next_cursor.as_deref())
+                .filter(|cursor| !cursor.is_null() && cursor.as_str() != Some(""))
+                .cloned(),
+        };
+        let short_page = page_size.is_some_and(|size| (count as u64) < size);
+        match next {
+            Some(next) if !short_page => {
+                params.insert(pagination.param.clone(), next);
+            }
+            _ => {
+                complete = true;
+                break;
+            }
+        }
+    }
+
+    Ok(serde_json::json!({
+        "items": items,
+        "total_count": items.len(),
+        "pages": pages,
+        "complete": complete,
+    }))
+}
+
+/// Value at a dotted path (`data.items`); the value itself for no path
+fn lookup<'a>(value: &'a Value, path: Option<&str>) -> Option<&'a Value> {
+    let Some(path) = path else {
+        return Some(value);
+    };
+    path.split('.').try_fold(value, |value, key| value.get(key))
+}
+
+fn as_number(value: &Value) -> Option<u64> {
+    value.as_u64().or_else(|| value.as_str()?.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::zml::ZMLParserWrapper;
+    use std::sync::Mutex;
+
+    const PAGED_MODULE: &str = r#"
+module Paged {
+    method listOrders {
+        http_method: GET
+        uri: "/orders"
+        paginate {
+            style: page
+            param: "page"
+            size_param: "per_page"
+            items: "data.orders"
+        }
+        params {
+            page: integer?
+            per_page: integer? = 2
+        }
+        response: any
+    }
+
+    method listEvents {
+        http_method: GET
+        uri: "/events"
+        paginate {
+            style: cursor
+            param: "cursor"
+            items: "events"
+            next_cursor: "meta.next"
+            max_pages: 2
+        }
+        params {
+            cursor: string?
+        }
+        response: any
+    }
+}
+"#;
+
+    #[tokio::test]
+    async fn test_pages_are_fetched_until_a_short_page() {
+        let module = ZMLParserWrapper::new().parse(PAGED_MODULE).unwrap();
+        let requested = Mutex::new(Vec::new());
+        let merged = fetch_all_pages(&module.methods["listOrders"], HashMap::new(), 10, |params| {
+            let page = params.get("page").and_then(as_number).unwrap_or(1);
+            requested.lock().unwrap().push(page);
+            let orders: Vec<u64> = match page {
+                1 => vec![1, 2],
+                2 => vec![3, 4],
+                _ => vec![5],
+            };
+            async move { Ok(serde_json::json!({ "data": { "orders": orders } })) }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(*requested.lock().unwrap(), vec![1, 2, 3]);
+        assert_eq!(merged["items"], serde_json::json!([1, 2, 3, 4, 5]));
+        assert_eq!(merged["total_count"], 5);
+        assert_eq!(merged["complete"], true);
+    }
+
+    #[tokio::test]
+    async fn test_cursor_pages_stop_at_the_page_limit() {
+        let module = ZMLParserWrapper::new().parse(PAGED_MODULE).unwrap();
+        let method = &module.methods["listEvents"];
+        let limit = max_pages(method.paginate.as_ref().unwrap(), &GlobalModuleConfig::default());
+        assert_eq!(limit, 2);
+
+        // The API always has a next page; fetching stops at the limit
+        let merged = fetch_all_pages(method, HashMap::new(), limit, |params| async move {
+            let cursor = params.get("cursor").and_then(Value::as_str).unwrap_or("start").to_string();
+            Ok(serde_json::json!({ "events": [cursor.clone()], "meta": { "next": format!("{}+", cursor) } }))
+        })
+        .await
+        .unwrap();
+        assert_eq!(merged["items"], serde_json::json!(["start", "start+"]));
+        assert_eq!(merged["pages"], 2);
+        assert_eq!(merged["complete"], false);
+
+        let error = fetch_all_pages(method, HashMap::new(), limit, |_| async { Ok(serde_json::json!({ "data": [] })) })
+            .await
+            .unwrap_err();
+        assert!(error.message.contains("no item array at 'events'"), "{}", error.message);
+    }
+}
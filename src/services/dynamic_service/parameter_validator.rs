@@ -1,6 +1,7 @@
 //! Parameter validation for dynamic module service
 
 use crate::config::zml_loader::ZmlModuleLoader;
+use crate::services::dynamic_service::scalar_codec::{check_scalar_wire, encode_scalar, resolve_scalar};
 use crate::zml::ast::{MethodDef as ZmlMethodDef, Module as ZmlModule, TypeExpr as ZmlTypeExpr, TypeDef as ZmlTypeDef, Value as ZmlValue};
 use log::debug;
 use rmcp::ErrorData as McpError;
@@ -29,8 +30,10 @@ pub fn validate_parameters_zml(
         }
 
         if let Some(value) = normalized.get(name) {
-            let converted = maybe_convert_basic(value, &def.type_expr)?;
-            validate_value_against_typeexpr(&converted, &def.type_expr, module, loader)?;
+            let path = format!("methods.{}.params.{}", method.name, name);
+            let converted = maybe_convert_basic(value, &def.type_expr)
+                .and_then(|v| validate_value_against_typeexpr(&v, &def.type_expr, module, loader).map(|_| v))
+                .map_err(|e| with_source_location(e, module, &path))?;
             // Save potentially converted value back
             normalized.insert(name.clone(), converted);
         }
@@ -39,6 +42,18 @@ pub fn validate_parameters_zml(
     Ok(normalized)
 }
 
+/// Append the ZML source location of `path` to a validation error
+pub fn with_source_location(err: McpError, module: &ZmlModule, path: &str) -> McpError {
+    match module.source_location(path) {
+        Some(location) => McpError::new(
+            err.code,
+            format!("{} (defined at {})", err.message, location),
+            Some(serde_json::json!({ "source": location, "path": path })),
+        ),
+        None => err,
+    }
+}
+
 /// Attempt basic normalization: parse strings to integer/number/boolean
 fn maybe_convert_basic(value: &Value, t: &ZmlTypeExpr) -> Result<Value, McpError> {
     match t {
@@ -92,6 +107,7 @@ fn validate_value_against_typeexpr(
         ZmlTypeExpr::Number => { if !value.is_number() { return Err(McpError::invalid_params("Parameter must be number", None)); } }
         ZmlTypeExpr::Boolean => { if !value.is_boolean() { return Err(McpError::invalid_params("Parameter must be boolean", None)); } }
         ZmlTypeExpr::Date | ZmlTypeExpr::DateTime => { if !value.is_string() { return Err(McpError::invalid_params("Parameter must be string date/datetime", None)); } }
+        ZmlTypeExpr::File => { if !value.is_string() && !value.is_object() { return Err(McpError::invalid_params("File parameter must be base64 content, a file path or an MCP resource", None)); } }
         ZmlTypeExpr::Any => {}
         ZmlTypeExpr::Array(item) => {
             if !value.is_array() { return Err(McpError::invalid_params("Parameter must be array", None)); }
@@ -111,6 +127,15 @@ fn validate_value_against_typeexpr(
             } else { return Err(McpError::invalid_params("Enum parameter must be string", None)); }
         }
         ZmlTypeExpr::Ref(name) | ZmlTypeExpr::Alias(name) => {
+            if let Some(scalar) = resolve_scalar(name, module) {
+                // Encoded scalars accept the client-facing form; plain scalars are checked as-is
+                let result = if scalar.encode.is_some() {
+                    encode_scalar(value, scalar).map(|_| ())
+                } else {
+                    check_scalar_wire(value, scalar)
+                };
+                return result.map_err(|e| McpError::invalid_params(format!("Invalid parameter: {}", e), None));
+            }
             let (tdef, edef) = resolve_named(name, module, loader);
             if let Some(td) = tdef {
                 let as_object = ZmlTypeExpr::Object(td.fields.clone());
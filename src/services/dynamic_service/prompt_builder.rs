@@ -0,0 +1,78 @@
+//! Prompt builder for dynamic module service
+
+use crate::zml::ast::PromptDef as ZmlPromptDef;
+use rmcp::model::{GetPromptResult, JsonObject, Prompt, PromptArgument, PromptMessage, PromptMessageRole};
+use rmcp::ErrorData as McpError;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// ===================== ZML Support =====================
+/// Build MCP prompt metadata from a ZML prompt definition
+pub fn build_prompt_zml(prompt: &ZmlPromptDef) -> Prompt {
+    let mut arg_names: Vec<&String> = prompt.arguments.keys().collect();
+    arg_names.sort();
+
+    let arguments: Vec<PromptArgument> = arg_names
+        .into_iter()
+        .map(|name| {
+            let def = &prompt.arguments[name];
+            PromptArgument {
+                name: name.clone(),
+                title: None,
+                description: def.description.clone(),
+                required: Some(!def.optional),
+            }
+        })
+        .collect();
+
+    Prompt::new(
+        prompt.name.clone(),
+        prompt.description.clone(),
+        if arguments.is_empty() { None } else { Some(arguments) },
+    )
+}
+
+/// Render a ZML prompt template with the supplied arguments
+pub fn render_prompt_zml(
+    prompt: &ZmlPromptDef,
+    arguments: Option<&JsonObject>,
+) -> Result<GetPromptResult, McpError> {
+    let mut values: HashMap<String, String> = HashMap::new();
+    if let Some(args) = arguments {
+        for (name, value) in args.iter() {
+            if !prompt.arguments.contains_key(name) {
+                return Err(McpError::invalid_params(
+                    format!("Unknown prompt argument: {}", name),
+                    None,
+                ));
+            }
+            let text = match value {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            values.insert(name.clone(), text);
+        }
+    }
+
+    for (name, def) in &prompt.arguments {
+        if values.contains_key(name) {
+            continue;
+        }
+        if let Some(default) = &def.default_value {
+            values.insert(name.clone(), default.to_string());
+        } else if !def.optional {
+            return Err(McpError::invalid_params(
+                format!("Missing required prompt argument: {}", name),
+                None,
+            ));
+        }
+    }
+
+    Ok(GetPromptResult {
+        description: prompt.description.clone(),
+        messages: vec![PromptMessage::new_text(
+            PromptMessageRole::User,
+            prompt.render(&values),
+        )],
+    })
+}
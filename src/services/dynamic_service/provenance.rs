@@ -0,0 +1,119 @@
+//! Provenance metadata of tool results for MCP-ANY-REST
+//!
+//! Every tool call records how its result was obtained: the URL and HTTP status of the last API
+//! request, the requests sent and retried, whether the response cache served it, whether the
+//! result was truncated, and how long the call took. The record is attached to the result as
+//! `_meta.provenance`, or to the `data` of the error when the call fails, so agent frameworks can
+//! log where results came from and debug failures without parsing the logs.
+
+use rmcp::model::{CallToolResult, Meta};
+use rmcp::ErrorData as McpError;
+use serde::Serialize;
+use serde_json::Value;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Key of the provenance record in result metadata and error data
+pub const PROVENANCE_KEY: &str = "provenance";
+
+tokio::task_local! {
+    static CURRENT_PROVENANCE: Arc<Mutex<Provenance>>;
+}
+
+/// How a tool result was obtained
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Provenance {
+    /// URL of the last API request, sent or served from the cache
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// HTTP status of the last API response
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    /// Duration of the whole tool call
+    pub latency_ms: u64,
+    /// Requests sent to the API, retries included
+    pub requests: u32,
+    pub retries: u32,
+    pub cache_hit: bool,
+    pub truncated: bool,
+}
+
+impl Provenance {
+    /// Attach the record to the result of the call, or to the data of its error
+    pub fn attach(self, result: Result<CallToolResult, McpError>) -> Result<CallToolResult, McpError> {
+        let record = serde_json::to_value(&self).unwrap_or_default();
+        match result {
+            Ok(mut result) => {
+                result.meta.get_or_insert_with(Meta::new).insert(PROVENANCE_KEY.to_string(), record);
+                Ok(result)
+            }
+            Err(mut error) => {
+                match &mut error.data {
+                    Some(Value::Object(data)) => {
+                        data.insert(PROVENANCE_KEY.to_string(), record);
+                    }
+                    // Errors carrying other data keep it as it is
+                    Some(_) => {}
+                    None => error.data = Some(serde_json::json!({ PROVENANCE_KEY: record })),
+                }
+                Err(error)
+            }
+        }
+    }
+}
+
+/// Run a tool call, recording its provenance
+pub async fn with_provenance<F: Future>(future: F) -> (F::Output, Provenance) {
+    let provenance = Arc::new(Mutex::new(Provenance::default()));
+    let started = Instant::now();
+    let output = CURRENT_PROVENANCE.scope(provenance.clone(), future).await;
+    let mut provenance = provenance.lock().unwrap().clone();
+    provenance.latency_ms = started.elapsed().as_millis() as u64;
+    (output, provenance)
+}
+
+/// Update the provenance of the tool call being run; requests made outside of tool calls, such as
+/// resource reads and watches, record nothing
+pub fn record(update: impl FnOnce(&mut Provenance)) {
+    let _ = CURRENT_PROVENANCE.try_with(|provenance| update(&mut provenance.lock().unwrap()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::Content;
+
+    #[tokio::test]
+    async fn test_provenance_is_attached_to_results_and_errors() {
+        let (result, provenance) = with_provenance(async {
+            record(|p| {
+                p.url = Some("https://api.example.com/orders".to_string());
+                p.status = Some(503);
+                p.requests += 1;
+            });
+            record(|p| {
+                p.status = Some(200);
+                p.requests += 1;
+                p.retries += 1;
+            });
+            Ok(CallToolResult::success(vec![Content::text("[]")]))
+        })
+        .await;
+        assert_eq!((provenance.status, provenance.requests, provenance.retries), (Some(200), 2, 1));
+        let meta = provenance.attach(result).unwrap().meta.unwrap();
+        assert_eq!(meta.get(PROVENANCE_KEY).unwrap()["url"], "https://api.example.com/orders");
+        assert_eq!(meta.get(PROVENANCE_KEY).unwrap()["cache_hit"], false);
+
+        let (result, provenance) = with_provenance(async {
+            record(|p| p.cache_hit = true);
+            Err::<CallToolResult, _>(McpError::internal_error("API request failed", None))
+        })
+        .await;
+        let error = provenance.attach(result).unwrap_err();
+        assert_eq!(error.data.unwrap()[PROVENANCE_KEY]["cache_hit"], true);
+
+        // Outside of a tool call nothing is recorded
+        record(|p| p.retries += 1);
+    }
+}
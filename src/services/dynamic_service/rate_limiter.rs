@@ -0,0 +1,294 @@
+//! Rate limiting of tool calls for MCP-ANY-REST
+//!
+//! Every module has a token bucket filled at its effective `rate_limit` (the module's own or
+//! `default_rate_limit`), and methods with a limit of their own (`rate_limit` in the method
+//! configuration or in ZML) have one more. A call takes a token from each; when one is empty the
+//! call is rejected with a "rate limited" error telling when to retry, or, with `rate_limit_action:
+//! queue`, waits for its token. Buckets live for the process and are rebuilt when their limit changes.
+
+use crate::config::config::Config;
+use crate::config::module::{RateLimitAction, RateLimitConfig};
+use crate::zml::ast::RateLimit as ZmlRateLimit;
+use log::{debug, warn};
+use rmcp::model::ErrorCode;
+use rmcp::ErrorData as McpError;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Longest a queued call waits for its token; calls that would wait longer are rejected
+pub const MAX_QUEUE_WAIT: Duration = Duration::from_secs(60);
+
+/// Buckets of every limit of the process, by limit key
+static LIMITERS: Mutex<Option<HashMap<String, Limiter>>> = Mutex::new(None);
+
+/// Rate a bucket fills at and the tokens it holds at most
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rate {
+    pub capacity: f64,
+    pub per_second: f64,
+}
+
+impl Rate {
+    /// Per-minute bucket holding `burst_capacity` tokens and per-hour bucket; zero rates are unlimited
+    pub fn from_config(config: &RateLimitConfig) -> Vec<Rate> {
+        let mut rates = Vec::new();
+        if config.requests_per_minute > 0 {
+            rates.push(Rate {
+                capacity: config.burst_capacity.max(1) as f64,
+                per_second: config.requests_per_minute as f64 / 60.0,
+            });
+        }
+        if config.requests_per_hour > 0 {
+            rates.push(Rate {
+                capacity: config.requests_per_hour as f64,
+                per_second: config.requests_per_hour as f64 / 3600.0,
+            });
+        }
+        rates
+    }
+
+    /// Bucket of a ZML `rate_limit: <requests>/<per_seconds>`
+    pub fn from_zml(limit: &ZmlRateLimit) -> Vec<Rate> {
+        if limit.requests == 0 || limit.per_seconds == 0 {
+            return Vec::new();
+        }
+        vec![Rate {
+            capacity: limit.requests as f64,
+            per_second: limit.requests as f64 / limit.per_seconds as f64,
+        }]
+    }
+}
+
+/// A limit a call is subject to
+#[derive(Debug, Clone, PartialEq)]
+pub struct Limit {
+    /// `module:<module>` or `method:<module>::<method>`
+    pub key: String,
+    pub rates: Vec<Rate>,
+}
+
+/// Counters of a limit, as shown by `GET /config/status`
+#[derive(Debug, Clone, Serialize)]
+pub struct RateLimitStats {
+    pub allowed: u64,
+    pub queued: u64,
+    pub rejected: u64,
+    /// Calls that can be made right now without waiting
+    pub available: u64,
+}
+
+struct Bucket {
+    rate: Rate,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate.per_second).min(self.rate.capacity);
+        self.updated = now;
+    }
+
+    /// Time until the bucket holds a whole token
+    fn wait(&self) -> Duration {
+        if self.tokens >= 1.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((1.0 - self.tokens) / self.rate.per_second)
+        }
+    }
+}
+
+struct Limiter {
+    rates: Vec<Rate>,
+    buckets: Vec<Bucket>,
+    allowed: u64,
+    queued: u64,
+    rejected: u64,
+}
+
+impl Limiter {
+    fn new(rates: &[Rate], now: Instant) -> Self {
+        Self {
+            rates: rates.to_vec(),
+            buckets: rates
+                .iter()
+                .map(|rate| Bucket { rate: *rate, tokens: rate.capacity, updated: now })
+                .collect(),
+            allowed: 0,
+            queued: 0,
+            rejected: 0,
+        }
+    }
+}
+
+/// Limits of a tool call: the module's effective limit and the method's own one, configured or
+/// declared in ZML (`declared`)
+pub fn tool_limits(config: &Config, module_name: &str, method_name: &str, declared: Option<&ZmlRateLimit>) -> Vec<Limit> {
+    let global = &config.module_config;
+    let mut limits = Vec::new();
+    let module_limit = global
+        .get_module_config(module_name)
+        .and_then(|module| module.rate_limit.as_ref())
+        .or(global.default_rate_limit.as_ref());
+    if let Some(limit) = module_limit {
+        limits.push(Limit { key: format!("module:{}", module_name), rates: Rate::from_config(limit) });
+    }
+    let method_rates = match global.get_method_config(module_name, method_name).and_then(|m| m.rate_limit.as_ref()) {
+        Some(limit) => Some(Rate::from_config(limit)),
+        None => declared.map(Rate::from_zml),
+    };
+    if let Some(rates) = method_rates {
+        limits.push(Limit { key: format!("method:{}::{}", module_name, method_name), rates });
+    }
+    limits.retain(|limit| !limit.rates.is_empty());
+    limits
+}
+
+/// Take a token of every limit, waiting for them under `RateLimitAction::Queue`
+pub async fn acquire(limits: &[Limit], action: RateLimitAction) -> Result<(), McpError> {
+    if limits.is_empty() {
+        return Ok(());
+    }
+    let wait = with_limiters(|limiters| {
+        let now = Instant::now();
+        for limit in limits {
+            let limiter = limiters.entry(limit.key.clone()).or_insert_with(|| Limiter::new(&limit.rates, now));
+            if limiter.rates != limit.rates {
+                debug!("Rate limit of {} changed, resetting its buckets", limit.key);
+                *limiter = Limiter::new(&limit.rates, now);
+            }
+            limiter.buckets.iter_mut().for_each(|bucket| bucket.refill(now));
+        }
+        // The limit making the call wait longest decides
+        let (key, wait) = limits
+            .iter()
+            .flat_map(|limit| limiters[&limit.key].buckets.iter().map(move |bucket| (&limit.key, bucket.wait())))
+            .max_by_key(|(_, wait)| *wait)
+            .unwrap_or((&limits[0].key, Duration::ZERO));
+        if !wait.is_zero() && (action == RateLimitAction::Reject || wait > MAX_QUEUE_WAIT) {
+            limiters.get_mut(key).unwrap().rejected += 1;
+            return Err((key.clone(), wait));
+        }
+        // Queued calls take their token now, so later calls queue behind them
+        for limit in limits {
+            let limiter = limiters.get_mut(&limit.key).unwrap();
+            limiter.buckets.iter_mut().for_each(|bucket| bucket.tokens -= 1.0);
+            if wait.is_zero() {
+                limiter.allowed += 1;
+            } else {
+                limiter.queued += 1;
+            }
+        }
+        Ok(wait)
+    });
+
+    match wait {
+        Ok(wait) if wait.is_zero() => Ok(()),
+        Ok(wait) => {
+            debug!("Rate limited call queued for {:?}", wait);
+            tokio::time::sleep(wait).await;
+            Ok(())
+        }
+        Err((key, wait)) => {
+            let retry_after_secs = wait.as_secs_f64().ceil() as u64;
+            warn!("Call rejected by rate limit {}, retry in {}s", key, retry_after_secs);
+            Err(McpError::new(
+                ErrorCode::INVALID_REQUEST,
+                format!("Rate limited by {}, retry in {}s", key, retry_after_secs),
+                Some(serde_json::json!({
+                    "rate_limited": { "limit": key, "retry_after_secs": retry_after_secs }
+                })),
+            ))
+        }
+    }
+}
+
+/// Counters of every limit used so far, by limit key
+pub fn stats() -> BTreeMap<String, RateLimitStats> {
+    with_limiters(|limiters| {
+        let now = Instant::now();
+        limiters
+            .iter_mut()
+            .map(|(key, limiter)| {
+                limiter.buckets.iter_mut().for_each(|bucket| bucket.refill(now));
+                let available = limiter
+                    .buckets
+                    .iter()
+                    .map(|bucket| bucket.tokens.max(0.0).floor() as u64)
+                    .min()
+                    .unwrap_or_default();
+                let stats = RateLimitStats {
+                    allowed: limiter.allowed,
+                    queued: limiter.queued,
+                    rejected: limiter.rejected,
+                    available,
+                };
+                (key.clone(), stats)
+            })
+            .collect()
+    })
+}
+
+fn with_limiters<T>(f: impl FnOnce(&mut HashMap<String, Limiter>) -> T) -> T {
+    let mut limiters = LIMITERS.lock().unwrap();
+    f(limiters.get_or_insert_with(HashMap::new))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::module::{MethodConfig, ModuleConfig};
+
+    fn limit(key: &str, capacity: f64, per_second: f64) -> Limit {
+        Limit { key: key.to_string(), rates: vec![Rate { capacity, per_second }] }
+    }
+
+    #[tokio::test]
+    async fn test_calls_over_the_limit_are_rejected_or_queued() {
+        let limits = [limit("module:rl-test", 10.0, 1.0), limit("method:rl-test::burst", 2.0, 0.5)];
+        acquire(&limits, RateLimitAction::Reject).await.unwrap();
+        acquire(&limits, RateLimitAction::Reject).await.unwrap();
+        let error = acquire(&limits, RateLimitAction::Reject).await.unwrap_err();
+        assert!(error.message.contains("method:rl-test::burst, retry in 2s"), "{}", error.message);
+        assert_eq!(error.data.unwrap()["rate_limited"]["retry_after_secs"], 2);
+
+        let counters = stats();
+        assert_eq!(counters["method:rl-test::burst"].allowed, 2);
+        assert_eq!(counters["method:rl-test::burst"].rejected, 1);
+        assert_eq!(counters["module:rl-test"].available, 8);
+
+        // A fast bucket queues the call for its next token instead
+        let fast = [limit("method:rl-test::fast", 1.0, 20.0)];
+        acquire(&fast, RateLimitAction::Queue).await.unwrap();
+        let started = Instant::now();
+        acquire(&fast, RateLimitAction::Queue).await.unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(40));
+        assert_eq!(stats()["method:rl-test::fast"].queued, 1);
+    }
+
+    #[test]
+    fn test_tool_limits_combine_module_and_method_limits() {
+        let mut config = Config::default();
+        let mut module = ModuleConfig::default();
+        let mut method = MethodConfig::default();
+        method.rate_limit = Some(RateLimitConfig { requests_per_minute: 6, requests_per_hour: 0, burst_capacity: 2 });
+        module.add_method("delete_user".to_string(), method);
+        config.module_config.modules.insert("user".to_string(), module);
+
+        let list = tool_limits(&config, "user", "list_users", None);
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].key, "module:user");
+        assert_eq!(list[0].rates.len(), 2);
+
+        let delete = tool_limits(&config, "user", "delete_user", Some(&ZmlRateLimit { requests: 1, per_seconds: 1 }));
+        assert_eq!(delete[1].key, "method:user::delete_user");
+        assert_eq!(delete[1].rates, vec![Rate { capacity: 2.0, per_second: 0.1 }]);
+
+        let declared = tool_limits(&config, "user", "get_user", Some(&ZmlRateLimit { requests: 10, per_seconds: 60 }));
+        assert_eq!(declared[1].rates[0].capacity, 10.0);
+    }
+}
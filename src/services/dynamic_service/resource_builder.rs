@@ -0,0 +1,121 @@
+//! Resource builder for dynamic module service
+
+use crate::zml::ast::{Module as ZmlModule, ResourceDef as ZmlResourceDef};
+use rmcp::model::{AnnotateAble, RawResource, RawResourceTemplate, Resource, ResourceTemplate};
+use std::collections::HashMap;
+
+/// ===================== ZML Support =====================
+/// Build an MCP resource for a ZML resource without URI variables
+pub fn build_resource_zml(resource: &ZmlResourceDef) -> Resource {
+    let mut raw = RawResource::new(resource_uri_zml(resource), resource.name.clone());
+    raw.description = resource.description.clone();
+    raw.mime_type = Some("application/json".to_string());
+    raw.no_annotation()
+}
+
+/// Build an MCP resource template for a ZML resource with URI variables
+pub fn build_resource_template_zml(resource: &ZmlResourceDef) -> ResourceTemplate {
+    RawResourceTemplate {
+        uri_template: resource_uri_zml(resource),
+        name: resource.name.clone(),
+        title: None,
+        description: resource.description.clone(),
+        mime_type: Some("application/json".to_string()),
+    }
+    .no_annotation()
+}
+
+/// Resource URI as exposed to MCP clients (without leading slash)
+pub fn resource_uri_zml(resource: &ZmlResourceDef) -> String {
+    resource.uri.trim_start_matches('/').to_string()
+}
+
+/// Find the resource matching a concrete URI and resolve its template variables; a resource
+/// without variables wins over a template matching the same URI
+pub fn match_resource_zml<'a>(
+    uri: &str,
+    module: &'a ZmlModule,
+) -> Option<(&'a ZmlResourceDef, HashMap<String, String>)> {
+    let uri = uri.trim_start_matches('/');
+    let fixed = module
+        .resources
+        .values()
+        .find(|resource| !resource.is_template() && resource_uri_zml(resource) == uri);
+    if let Some(resource) = fixed {
+        return Some((resource, HashMap::new()));
+    }
+    module.resources.values().filter(|resource| resource.is_template()).find_map(|resource| {
+        match_uri_template(&resource_uri_zml(resource), uri).map(|vars| (resource, vars))
+    })
+}
+
+/// Expand a resource URI template into the REST endpoint path; each variable becomes one
+/// percent-encoded path segment, and `.` and `..` are refused
+pub fn expand_resource_uri_zml(resource: &ZmlResourceDef, variables: &HashMap<String, String>) -> Result<String, String> {
+    let mut endpoint = resource.uri.clone();
+    for (name, value) in variables {
+        // Values arrive as URI text, so they are decoded first and not encoded twice
+        let value = decode_segment(value)?;
+        if value == "." || value == ".." {
+            return Err(format!("'{}' is not a valid value of {{{}}}", value, name));
+        }
+        endpoint = endpoint.replace(&format!("{{{}}}", name), &encode_segment(&value));
+    }
+    Ok(endpoint)
+}
+
+/// Percent-encode a path segment, keeping the unreserved characters of RFC 3986
+fn encode_segment(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// Decode the percent-encoded octets of a path segment
+fn decode_segment(value: &str) -> Result<String, String> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != b'%' {
+            decoded.push(bytes[i]);
+            i += 1;
+            continue;
+        }
+        let hex = value.get(i + 1..i + 3).filter(|hex| hex.bytes().all(|byte| byte.is_ascii_hexdigit()));
+        let hex = hex.ok_or_else(|| format!("invalid percent-encoding in '{}'", value))?;
+        decoded.push(u8::from_str_radix(hex, 16).map_err(|e| e.to_string())?);
+        i += 3;
+    }
+    String::from_utf8(decoded).map_err(|_| format!("'{}' is not UTF-8 once decoded", value))
+}
+
+/// Match a concrete URI against a `{variable}` template (RFC 6570 level 1)
+fn match_uri_template(template: &str, uri: &str) -> Option<HashMap<String, String>> {
+    let mut pattern = String::from("^");
+    let mut names = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let after = &rest[start + 1..];
+        let end = after.find('}')?;
+        pattern.push_str(&regex::escape(&rest[..start]));
+        pattern.push_str("([^/?#]+)");
+        names.push(after[..end].to_string());
+        rest = &after[end + 1..];
+    }
+    pattern.push_str(&regex::escape(rest));
+    pattern.push('$');
+
+    let re = regex::Regex::new(&pattern).ok()?;
+    let captures = re.captures(uri)?;
+    let mut variables = HashMap::new();
+    for (i, name) in names.into_iter().enumerate() {
+        let value = captures.get(i + 1)?.as_str();
+        variables.insert(name, value.to_string());
+    }
+    Some(variables)
+}
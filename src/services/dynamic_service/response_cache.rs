@@ -0,0 +1,312 @@
+//! Response cache for MCP-ANY-REST
+//!
+//! Successful GET responses of methods with a `cache_ttl` are kept in memory, keyed by the URL (path
+//! and query parameters), the extra request headers and the token scope, so repeated lookups do not
+//! reach the API. `Cache-Control: no-store` responses are not kept, and `max-age` or `no-cache`
+//! shorten the time an entry stays fresh. A stale entry with an `ETag` is revalidated with
+//! `If-None-Match`; a 304 answer serves the cached body again. When `MCP_RESPONSE_CACHE_DIR` is set,
+//! entries are also written to that directory and survive restarts.
+
+use super::middleware::{ApiRequest, Next, RequestMiddleware};
+use super::provenance::record;
+use crate::config::token_cache::cache_key;
+use crate::services::auth_service::{ApiResponse, HttpMethod, SendError};
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use log::{debug, warn};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CACHE_CONTROL, CONTENT_TYPE, ETAG, IF_NONE_MATCH};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Number of responses kept in memory
+pub const CACHE_CAPACITY: usize = 1000;
+
+/// Environment variable naming the directory responses are persisted to, relative to the configuration directory
+pub const RESPONSE_CACHE_DIR_ENV: &str = "MCP_RESPONSE_CACHE_DIR";
+
+/// Process-wide response cache
+static RESPONSE_CACHE: Mutex<Option<ResponseCache>> = Mutex::new(None);
+
+/// Cached response of a GET request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedResponse {
+    status: u16,
+    content_type: Option<String>,
+    /// Body as base64, so binary responses survive the JSON cache files
+    body_base64: String,
+    etag: Option<String>,
+    /// End of the freshness; later requests revalidate the entry or fetch it again
+    expires_at: DateTime<Utc>,
+}
+
+impl CachedResponse {
+    fn response(&self) -> ApiResponse {
+        let mut headers = HeaderMap::new();
+        if let Some(value) = self.content_type.as_deref().and_then(|value| HeaderValue::from_str(value).ok()) {
+            headers.insert(CONTENT_TYPE, value);
+        }
+        ApiResponse {
+            status: StatusCode::from_u16(self.status).unwrap_or(StatusCode::OK),
+            headers,
+            body: base64::engine::general_purpose::STANDARD.decode(&self.body_base64).unwrap_or_default(),
+        }
+    }
+}
+
+/// Bounded entry map and the optional directory it is mirrored to
+#[derive(Default)]
+struct ResponseCache {
+    entries: HashMap<String, CachedResponse>,
+    dir: Option<PathBuf>,
+}
+
+impl ResponseCache {
+    fn get(&mut self, key: &str) -> Option<CachedResponse> {
+        if let Some(entry) = self.entries.get(key) {
+            return Some(entry.clone());
+        }
+        let path = self.dir.as_ref()?.join(format!("{}.json", key));
+        let entry: CachedResponse = serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()?;
+        self.insert(key.to_string(), entry.clone());
+        Some(entry)
+    }
+
+    fn put(&mut self, key: String, entry: CachedResponse) {
+        if let Some(dir) = &self.dir {
+            let path = dir.join(format!("{}.json", key));
+            if let Err(e) = write_entry(&path, &entry) {
+                warn!("Failed to write response cache file {:?}: {}", path, e);
+            }
+        }
+        self.insert(key, entry);
+    }
+
+    /// Keep an entry in memory, dropping the one expiring first when the cache is full
+    fn insert(&mut self, key: String, entry: CachedResponse) {
+        if self.entries.len() >= CACHE_CAPACITY && !self.entries.contains_key(&key) {
+            let oldest = self.entries.iter().min_by_key(|(_, entry)| entry.expires_at).map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(key, entry);
+    }
+}
+
+/// Persist cached responses to `dir` from now on; `None` keeps them in memory only
+pub fn install_response_cache_dir(dir: Option<PathBuf>) {
+    if let Some(dir) = &dir {
+        if let Err(e) = std::fs::create_dir_all(dir) {
+            warn!("Failed to create response cache directory {:?}: {}", dir, e);
+        }
+    }
+    with_cache(|cache| cache.dir = dir);
+}
+
+/// Serves GET requests with a `cache_ttl` from the response cache
+pub struct CacheMiddleware;
+
+#[async_trait::async_trait]
+impl RequestMiddleware for CacheMiddleware {
+    fn name(&self) -> &str {
+        "cache"
+    }
+
+    async fn handle(&self, mut request: ApiRequest, next: Next<'_>) -> Result<ApiResponse, SendError> {
+        let ttl = match request.policy.cache_ttl {
+            // Streamed bodies never reach the response, so there is nothing to cache
+            Some(ttl) if !ttl.is_zero() && request.method == HttpMethod::GET && request.policy.body_sink.is_none() => ttl,
+            _ => return next.run(request).await,
+        };
+        let key = request_key(&request);
+        let cached = with_cache(|cache| cache.get(&key));
+        if let Some(entry) = &cached {
+            if entry.expires_at > Utc::now() {
+                debug!("Serving {} from the response cache", request.url);
+                let response = entry.response();
+                record(|provenance| {
+                    provenance.url = Some(request.url.clone());
+                    provenance.status = Some(response.status.as_u16());
+                    provenance.cache_hit = true;
+                });
+                return Ok(response);
+            }
+            if let Some(etag) = entry.etag.as_deref().and_then(|etag| HeaderValue::from_str(etag).ok()) {
+                request.headers.insert(IF_NONE_MATCH, etag);
+            }
+        }
+
+        let url = request.url.clone();
+        let response = next.run(request).await?;
+        match cached {
+            Some(mut entry) if response.status == StatusCode::NOT_MODIFIED => {
+                debug!("Cached response of {} revalidated", url);
+                entry.expires_at = expiry(freshness(&response.headers, ttl).unwrap_or_default());
+                let revalidated = entry.response();
+                record(|provenance| provenance.cache_hit = true);
+                with_cache(|cache| cache.put(key, entry));
+                Ok(revalidated)
+            }
+            _ => {
+                if response.status.is_success() {
+                    let etag = header_str(&response.headers, &ETAG);
+                    // Entries that are never fresh are only worth keeping for revalidation
+                    if let Some(fresh) = freshness(&response.headers, ttl).filter(|fresh| !fresh.is_zero() || etag.is_some()) {
+                        let entry = CachedResponse {
+                            status: response.status.as_u16(),
+                            content_type: header_str(&response.headers, &CONTENT_TYPE),
+                            body_base64: base64::engine::general_purpose::STANDARD.encode(&response.body),
+                            etag,
+                            expires_at: expiry(fresh),
+                        };
+                        with_cache(|cache| cache.put(key, entry));
+                    }
+                }
+                Ok(response)
+            }
+        }
+    }
+}
+
+/// Time a response stays fresh: the method's `ttl`, shortened by `max-age` and zero under
+/// `no-cache`; `None` when `no-store` forbids keeping it
+fn freshness(headers: &HeaderMap, ttl: Duration) -> Option<Duration> {
+    let mut fresh = ttl;
+    for directive in headers
+        .get_all(CACHE_CONTROL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|directive| directive.trim().to_ascii_lowercase())
+    {
+        if directive == "no-store" {
+            return None;
+        }
+        if directive == "no-cache" {
+            fresh = Duration::ZERO;
+        } else if let Some(seconds) = directive.strip_prefix("max-age=").and_then(|s| s.parse::<u64>().ok()) {
+            fresh = fresh.min(Duration::from_secs(seconds));
+        }
+    }
+    Some(fresh)
+}
+
+/// Key of a request: its URL, extra headers (e.g. the impersonated identity) and token scope
+pub(crate) fn request_key(request: &ApiRequest) -> String {
+    let mut headers: Vec<String> = request
+        .headers
+        .iter()
+        .map(|(name, value)| format!("{}: {}", name, value.to_str().unwrap_or_default()))
+        .collect();
+    headers.sort();
+    cache_key(&format!(
+        "{} {}\n{}\n{:?}",
+        request.method,
+        request.url,
+        headers.join("\n"),
+        request.policy.token_scope
+    ))
+}
+
+/// End of a freshness starting now
+fn expiry(fresh: Duration) -> DateTime<Utc> {
+    Utc::now() + chrono::Duration::from_std(fresh).unwrap_or_else(|_| chrono::Duration::zero())
+}
+
+fn write_entry(path: &Path, entry: &CachedResponse) -> std::io::Result<()> {
+    std::fs::write(path, serde_json::to_string(entry)?)
+}
+
+fn header_str(headers: &HeaderMap, name: &HeaderName) -> Option<String> {
+    headers.get(name).and_then(|value| value.to_str().ok()).map(String::from)
+}
+
+fn with_cache<T>(f: impl FnOnce(&mut ResponseCache) -> T) -> T {
+    let mut cache = RESPONSE_CACHE.lock().unwrap();
+    f(cache.get_or_insert_with(ResponseCache::default))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::auth_service::{DirectAuthType, RequestBody, RequestPolicy, UnifiedAuthService};
+    use crate::services::dynamic_service::middleware::{AuthMiddleware, MiddlewareChain};
+    use std::sync::Arc;
+
+    fn request(url: String) -> ApiRequest {
+        ApiRequest {
+            module: "items".to_string(),
+            operation: "get_item".to_string(),
+            method: HttpMethod::GET,
+            url,
+            headers: HeaderMap::new(),
+            body: RequestBody::Empty,
+            policy: RequestPolicy {
+                cache_ttl: Some(Duration::from_secs(60)),
+                ..RequestPolicy::default()
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cached_responses_are_revalidated_with_their_etag() {
+        let mut server = mockito::Server::new_async().await;
+        let fetched = server.mock("GET", "/items/1")
+            .match_header("if-none-match", mockito::Matcher::Missing)
+            .with_header("content-type", "application/json")
+            .with_header("etag", "\"v1\"")
+            .with_header("cache-control", "no-cache")
+            .with_body(r#"{"id":1}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let revalidated = server.mock("GET", "/items/1")
+            .match_header("if-none-match", "\"v1\"")
+            .with_status(304)
+            .expect(1)
+            .create_async()
+            .await;
+        let uncacheable = server.mock("GET", "/items/2")
+            .with_header("content-type", "application/json")
+            .with_header("cache-control", "private, no-store")
+            .with_body(r#"{"id":2}"#)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let auth_service = Arc::new(
+            UnifiedAuthService::create_direct_auth(DirectAuthType::Bearer, Some("token".to_string()), None, None, None, None, 3600, 300, 3)
+                .unwrap(),
+        );
+        let chain = MiddlewareChain::new(vec![Arc::new(CacheMiddleware), Arc::new(AuthMiddleware::new(auth_service))]);
+
+        // Fetched, revalidated with a 304 (fresh again for the TTL), then served from the cache
+        for _ in 0..3 {
+            let item: serde_json::Value = chain.execute(request(format!("{}/items/1", server.url()))).await.unwrap();
+            assert_eq!(item["id"], 1);
+        }
+        for _ in 0..2 {
+            let item: serde_json::Value = chain.execute(request(format!("{}/items/2", server.url()))).await.unwrap();
+            assert_eq!(item["id"], 2);
+        }
+
+        fetched.assert_async().await;
+        revalidated.assert_async().await;
+        uncacheable.assert_async().await;
+    }
+
+    #[test]
+    fn test_freshness_follows_cache_control() {
+        let ttl = Duration::from_secs(300);
+        let headers = |value: &'static str| HeaderMap::from_iter([(CACHE_CONTROL, HeaderValue::from_static(value))]);
+        assert_eq!(freshness(&HeaderMap::new(), ttl), Some(ttl));
+        assert_eq!(freshness(&headers("public, max-age=60"), ttl), Some(Duration::from_secs(60)));
+        assert_eq!(freshness(&headers("max-age=3600"), ttl), Some(ttl));
+        assert_eq!(freshness(&headers("no-cache"), ttl), Some(Duration::ZERO));
+        assert_eq!(freshness(&headers("No-Store"), ttl), None);
+    }
+}
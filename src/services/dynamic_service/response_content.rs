@@ -0,0 +1,368 @@
+//! Content-type aware decoding of API responses for MCP-ANY-REST
+//!
+//! Tool responses are decoded by their `Content-Type`: JSON (the default when the API sends none)
+//! is parsed, XML is converted to JSON, text is returned as-is, and anything else (PDFs, images,
+//! archives) is returned base64-encoded with its media type, up to `api.max_binary_response_bytes`.
+//! Methods declaring `produces` have their responses decoded as that media type when the API sends
+//! none or a generic one, and CSV responses of methods producing CSV are converted to an array of
+//! objects keyed by the header row.
+
+use crate::services::auth_service::unified_auth_service::parse_response;
+use crate::services::auth_service::{ApiResponse, HttpMethod};
+use base64::Engine;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use rmcp::model::{Content, ErrorCode, ResourceContents};
+use rmcp::ErrorData as McpError;
+use serde_json::{Map, Value};
+
+/// Largest binary response returned when `api.max_binary_response_bytes` is unset
+pub const DEFAULT_MAX_BINARY_RESPONSE_BYTES: usize = 10 * 1024 * 1024;
+
+/// Decoded body of a successful API response
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResponsePayload {
+    /// JSON, or XML converted to JSON
+    Json(Value),
+    /// Text returned as-is
+    Text(String),
+    /// Binary payload of the request URL
+    Binary { uri: String, mime_type: String, data: Vec<u8> },
+}
+
+impl ResponsePayload {
+    /// Content of the tool result: text, an image, or an embedded blob resource
+    pub fn into_content(self) -> Result<Content, McpError> {
+        match self {
+            ResponsePayload::Json(value) => serde_json::to_string(&value)
+                .map(Content::text)
+                .map_err(|e| McpError::internal_error(format!("Failed to serialize result: {}", e), None)),
+            ResponsePayload::Text(text) => Ok(Content::text(text)),
+            ResponsePayload::Binary { uri, mime_type, data } => {
+                let data = base64::engine::general_purpose::STANDARD.encode(data);
+                if mime_type.starts_with("image/") {
+                    return Ok(Content::image(data, mime_type));
+                }
+                let blob = serde_json::json!({ "uri": uri, "mimeType": mime_type, "blob": data });
+                serde_json::from_value::<ResourceContents>(blob)
+                    .map(Content::resource)
+                    .map_err(|e| McpError::internal_error(format!("Failed to build blob resource: {}", e), None))
+            }
+        }
+    }
+}
+
+/// Decode a response by its content type, or the `declared_media_type` the method produces when
+/// the API sends none or a generic one; binary bodies over `max_binary_bytes` are refused
+pub fn decode_response_payload(
+    method: HttpMethod,
+    url: &str,
+    response: ApiResponse,
+    max_binary_bytes: usize,
+    declared_media_type: Option<&str>,
+) -> Result<ResponsePayload, McpError> {
+    let declared = declared_media_type
+        .and_then(|media_type| media_type.split(';').next())
+        .map(|media_type| media_type.trim().to_ascii_lowercase());
+    let media_type = match response.media_type() {
+        Some(media_type) if !is_generic(&media_type) => Some(media_type),
+        media_type => declared.clone().or(media_type),
+    };
+    let Some(media_type) = media_type.filter(|media_type| !is_json(media_type)) else {
+        return parse_response(method, url, response).map(ResponsePayload::Json);
+    };
+    if !response.status.is_success() {
+        return Err(McpError::internal_error(
+            format!("API request failed with status {}: {}", response.status, response.text()),
+            None,
+        ));
+    }
+
+    if !media_type.starts_with("image/") && is_xml(&media_type) {
+        return xml_to_json(&response.text()).map(ResponsePayload::Json).map_err(|e| {
+            McpError::internal_error(format!("Failed to parse XML response of {} {}: {}", method, url, e), None)
+        });
+    }
+    if let Some(delimiter) = csv_delimiter(&media_type).filter(|_| declared.as_deref().and_then(csv_delimiter).is_some()) {
+        return csv_to_json(&response.text(), delimiter).map(ResponsePayload::Json).map_err(|e| {
+            McpError::internal_error(format!("Failed to parse CSV response of {} {}: {}", method, url, e), None)
+        });
+    }
+    if is_text(&media_type) {
+        return Ok(ResponsePayload::Text(response.text().into_owned()));
+    }
+    if response.body.len() > max_binary_bytes {
+        return Err(McpError::new(
+            ErrorCode::INVALID_REQUEST,
+            format!(
+                "Response of {} {} is {} bytes of {}, more than the {} bytes allowed by api.max_binary_response_bytes",
+                method,
+                url,
+                response.body.len(),
+                media_type,
+                max_binary_bytes
+            ),
+            None,
+        ));
+    }
+    Ok(ResponsePayload::Binary { uri: url.to_string(), mime_type: media_type, data: response.body })
+}
+
+fn is_json(media_type: &str) -> bool {
+    media_type == "application/json" || media_type.ends_with("+json")
+}
+
+fn is_xml(media_type: &str) -> bool {
+    matches!(media_type, "application/xml" | "text/xml") || media_type.ends_with("+xml")
+}
+
+/// Media types that say nothing about the body, overridden by the one a method produces
+fn is_generic(media_type: &str) -> bool {
+    matches!(media_type, "text/plain" | "application/octet-stream")
+}
+
+/// Field delimiter of CSV and TSV media types
+fn csv_delimiter(media_type: &str) -> Option<char> {
+    match media_type {
+        "text/csv" | "application/csv" => Some(','),
+        "text/tab-separated-values" => Some('\t'),
+        _ => None,
+    }
+}
+
+fn is_text(media_type: &str) -> bool {
+    media_type.starts_with("text/")
+        || matches!(
+            media_type,
+            "application/javascript" | "application/yaml" | "application/x-yaml" | "application/x-www-form-urlencoded"
+        )
+}
+
+/// Element being read, with its attributes (`@name`), children and text
+struct XmlElement {
+    name: String,
+    fields: Map<String, Value>,
+    text: String,
+}
+
+impl XmlElement {
+    fn start(start: &BytesStart) -> Result<Self, String> {
+        let mut fields = Map::new();
+        for attribute in start.attributes() {
+            let attribute = attribute.map_err(|e| e.to_string())?;
+            let value = attribute.unescape_value().map_err(|e| e.to_string())?;
+            fields.insert(
+                format!("@{}", String::from_utf8_lossy(attribute.key.as_ref())),
+                Value::String(value.into_owned()),
+            );
+        }
+        Ok(Self { name: String::from_utf8_lossy(start.name().as_ref()).into_owned(), fields, text: String::new() })
+    }
+
+    /// Value of the element: its text when it has neither attributes nor children
+    fn into_value(self) -> Value {
+        if self.fields.is_empty() {
+            return Value::String(self.text);
+        }
+        let mut fields = self.fields;
+        if !self.text.is_empty() {
+            fields.insert("#text".to_string(), Value::String(self.text));
+        }
+        Value::Object(fields)
+    }
+
+    /// Add a child element; repeated children become an array
+    fn push(&mut self, child: XmlElement) {
+        let name = child.name.clone();
+        let value = child.into_value();
+        match self.fields.get_mut(&name) {
+            None => {
+                self.fields.insert(name, value);
+            }
+            Some(Value::Array(items)) => items.push(value),
+            Some(existing) => *existing = Value::Array(vec![existing.take(), value]),
+        }
+    }
+}
+
+/// Convert an XML document to JSON: elements become objects keyed by the child names, attributes
+/// `@name` keys, text alongside attributes or children `#text`, and repeated children arrays
+pub fn xml_to_json(xml: &str) -> Result<Value, String> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+    let mut stack = vec![XmlElement { name: String::new(), fields: Map::new(), text: String::new() }];
+    loop {
+        match reader.read_event().map_err(|e| e.to_string())? {
+            Event::Start(start) => stack.push(XmlElement::start(&start)?),
+            Event::Empty(start) => {
+                let element = XmlElement::start(&start)?;
+                stack.last_mut().unwrap().push(element);
+            }
+            Event::End(_) => {
+                let element = stack.pop().filter(|_| !stack.is_empty()).ok_or("unexpected closing tag")?;
+                stack.last_mut().unwrap().push(element);
+            }
+            Event::Text(text) => stack.last_mut().unwrap().text.push_str(&text.unescape().map_err(|e| e.to_string())?),
+            Event::CData(data) => stack.last_mut().unwrap().text.push_str(&String::from_utf8_lossy(&data.into_inner())),
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    match stack.pop() {
+        Some(root) if stack.is_empty() => Ok(Value::Object(root.fields)),
+        _ => Err("unclosed element".to_string()),
+    }
+}
+
+/// Convert CSV to an array of objects keyed by the header row; quoted fields may hold delimiters,
+/// newlines and doubled quotes, numbers and booleans are typed, and empty cells are null
+pub fn csv_to_json(csv: &str, delimiter: char) -> Result<Value, String> {
+    let mut rows = csv_rows(csv, delimiter)?.into_iter();
+    let Some(header) = rows.next() else {
+        return Ok(Value::Array(Vec::new()));
+    };
+    let records = rows
+        .enumerate()
+        .map(|(index, row)| {
+            if row.len() > header.len() {
+                return Err(format!("row {} has {} fields, the header {}", index + 2, row.len(), header.len()));
+            }
+            let mut record = Map::new();
+            for (position, name) in header.iter().enumerate() {
+                record.insert(name.clone(), row.get(position).map(|cell| csv_value(cell)).unwrap_or(Value::Null));
+            }
+            Ok(Value::Object(record))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    Ok(Value::Array(records))
+}
+
+/// Rows of fields, skipping blank lines
+fn csv_rows(csv: &str, delimiter: char) -> Result<Vec<Vec<String>>, String> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = csv.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            c if quoted => field.push(c),
+            c if c == delimiter => row.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                row.push(std::mem::take(&mut field));
+                if row.iter().any(|cell| !cell.is_empty()) {
+                    rows.push(std::mem::take(&mut row));
+                }
+                row.clear();
+            }
+            c => field.push(c),
+        }
+    }
+    if quoted {
+        return Err("unclosed quoted field".to_string());
+    }
+    row.push(field);
+    if row.iter().any(|cell| !cell.is_empty()) {
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+fn csv_value(cell: &str) -> Value {
+    if cell.is_empty() {
+        return Value::Null;
+    }
+    if let Ok(integer) = cell.parse::<i64>() {
+        return Value::from(integer);
+    }
+    if let Some(number) = cell.parse::<f64>().ok().and_then(serde_json::Number::from_f64) {
+        return Value::Number(number);
+    }
+    match cell {
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        _ => Value::String(cell.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+    use reqwest::StatusCode;
+
+    fn response(content_type: Option<&'static str>, body: &[u8]) -> ApiResponse {
+        let mut headers = HeaderMap::new();
+        if let Some(content_type) = content_type {
+            headers.insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+        }
+        ApiResponse { status: StatusCode::OK, headers, body: body.to_vec() }
+    }
+
+    fn decode(response: ApiResponse) -> Result<ResponsePayload, McpError> {
+        decode_response_payload(HttpMethod::GET, "https://api.example.com/files/1", response, 8, None)
+    }
+
+    #[test]
+    fn test_responses_are_decoded_by_content_type() {
+        assert_eq!(decode(response(None, br#"{"id":1}"#)).unwrap(), ResponsePayload::Json(serde_json::json!({"id": 1})));
+        assert_eq!(
+            decode(response(Some("text/csv; charset=utf-8"), b"id\n1\n")).unwrap(),
+            ResponsePayload::Text("id\n1\n".to_string())
+        );
+        let xml = br#"<order id="7"><item>a</item><item>b</item><note lang="en">fragile</note></order>"#;
+        assert_eq!(
+            decode(response(Some("application/xml"), xml)).unwrap(),
+            ResponsePayload::Json(serde_json::json!({
+                "order": { "@id": "7", "item": ["a", "b"], "note": { "@lang": "en", "#text": "fragile" } }
+            }))
+        );
+
+        let pdf = decode(response(Some("application/pdf"), b"%PDF-1")).unwrap();
+        assert_eq!(
+            pdf,
+            ResponsePayload::Binary {
+                uri: "https://api.example.com/files/1".to_string(),
+                mime_type: "application/pdf".to_string(),
+                data: b"%PDF-1".to_vec(),
+            }
+        );
+        let content = serde_json::to_value(pdf.into_content().unwrap()).unwrap();
+        assert_eq!(content["resource"]["blob"], "JVBERi0x");
+        assert_eq!(content["resource"]["mimeType"], "application/pdf");
+        let image = serde_json::to_value(decode(response(Some("image/png"), b"png")).unwrap().into_content().unwrap()).unwrap();
+        assert_eq!(image["type"], "image");
+        assert_eq!(image["mimeType"], "image/png");
+
+        let error = decode(response(Some("application/zip"), b"too large for the cap")).unwrap_err();
+        assert!(error.message.contains("more than the 8 bytes"), "{}", error.message);
+    }
+
+    #[test]
+    fn test_csv_is_converted_to_records() {
+        let csv = "name,note,qty\r\n\"Smith, J\",\"said \"\"hi\"\"\nthen left\",2.5\r\n\r\nDoe,,3\r\n";
+        assert_eq!(
+            csv_to_json(csv, ',').unwrap(),
+            serde_json::json!([
+                { "name": "Smith, J", "note": "said \"hi\"\nthen left", "qty": 2.5 },
+                { "name": "Doe", "note": null, "qty": 3 }
+            ])
+        );
+        assert_eq!(csv_to_json("a\tb\n1\n", '\t').unwrap(), serde_json::json!([{ "a": 1, "b": null }]));
+        assert!(csv_to_json("a\n1,2\n", ',').is_err());
+        assert!(csv_to_json("a\n\"1\n", ',').is_err());
+    }
+
+    #[test]
+    fn test_malformed_xml_is_an_error() {
+        assert!(xml_to_json("<order><item>a</order>").is_err());
+        assert!(xml_to_json("<order>").is_err());
+    }
+}
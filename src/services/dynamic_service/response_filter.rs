@@ -0,0 +1,370 @@
+//! Response filtering for MCP-ANY-REST
+//!
+//! Every tool takes two optional synthetic parameters that shrink JSON results before they are
+//! returned, so huge API payloads do not fill the client's context window:
+//! - `_fields`: dotted paths to keep (`["id", "owner.name"]` or `"id,owner.name"`); arrays are
+//!   projected item by item.
+//! - `_jq`: a jq-like pipeline of paths (`.data`, `.items[]`, `.[0]`, `.[2:5]`, `."odd key"`) and the
+//!   `length` and `keys` functions, e.g. `.data.items[] | .id`. A pipeline yielding several values
+//!   returns them as an array.
+//!
+//! `_jq` runs first and `_fields` projects its result. Methods declaring a parameter of the same
+//! name keep it as a regular parameter.
+
+use crate::zml::ast::MethodDef;
+use rmcp::ErrorData as McpError;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// Synthetic parameter listing the fields to keep
+pub const FIELDS_PARAM: &str = "_fields";
+
+/// Synthetic parameter holding a jq-like expression
+pub const JQ_PARAM: &str = "_jq";
+
+/// Filter parameters `method` accepts, those it does not declare itself
+pub fn filter_params(method: &MethodDef) -> Vec<&'static str> {
+    [FIELDS_PARAM, JQ_PARAM]
+        .into_iter()
+        .filter(|name| !method.params.contains_key(*name))
+        .collect()
+}
+
+/// Filter requested by a tool call
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ResponseFilter {
+    fields: Vec<Vec<String>>,
+    jq: Vec<Stage>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Stage {
+    Path(Vec<Segment>),
+    Length,
+    Keys,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Field(String),
+    Index(i64),
+    Slice(Option<i64>, Option<i64>),
+    Iterate,
+}
+
+impl ResponseFilter {
+    /// Take the filter parameters out of the call's `params`; `None` when none was given
+    pub fn from_params(params: &mut HashMap<String, Value>, method: &MethodDef) -> Result<Option<Self>, McpError> {
+        let mut filter = ResponseFilter::default();
+        for name in filter_params(method) {
+            let Some(value) = params.remove(name) else {
+                continue;
+            };
+            let invalid = |message: String| McpError::invalid_params(format!("Invalid {}: {}", name, message), None);
+            match (name, value) {
+                (_, Value::Null) => {}
+                (FIELDS_PARAM, Value::String(fields)) => {
+                    filter.fields = fields.split(',').map(str::trim).filter(|f| !f.is_empty()).map(field_path).collect();
+                }
+                (FIELDS_PARAM, Value::Array(fields)) => {
+                    for field in fields {
+                        let field = field.as_str().ok_or_else(|| invalid("fields must be strings".to_string()))?;
+                        filter.fields.push(field_path(field));
+                    }
+                }
+                (JQ_PARAM, Value::String(expression)) => filter.jq = parse_jq(&expression).map_err(invalid)?,
+                _ => return Err(invalid("unexpected value type".to_string())),
+            }
+        }
+        Ok((filter != ResponseFilter::default()).then_some(filter))
+    }
+
+    /// Filter a JSON result
+    pub fn apply(&self, value: Value) -> Result<Value, McpError> {
+        let value = if self.jq.is_empty() {
+            value
+        } else {
+            let mut outputs = vec![value];
+            for stage in &self.jq {
+                outputs = run_stage(outputs, stage)
+                    .map_err(|e| McpError::invalid_params(format!("{} failed: {}", JQ_PARAM, e), None))?;
+            }
+            if outputs.len() == 1 { outputs.pop().unwrap() } else { Value::Array(outputs) }
+        };
+        Ok(if self.fields.is_empty() { value } else { project(&value, &self.fields) })
+    }
+}
+
+fn field_path(field: &str) -> Vec<String> {
+    field.split('.').map(String::from).collect()
+}
+
+/// Keep the `paths` of objects, item by item for arrays
+fn project(value: &Value, paths: &[Vec<String>]) -> Value {
+    match value {
+        Value::Array(items) => Value::Array(items.iter().map(|item| project(item, paths)).collect()),
+        Value::Object(fields) => {
+            let mut projected = Map::new();
+            for (key, child) in fields {
+                let matching: Vec<&Vec<String>> = paths.iter().filter(|path| path.first() == Some(key)).collect();
+                if matching.is_empty() {
+                    continue;
+                }
+                if matching.iter().any(|path| path.len() == 1) {
+                    projected.insert(key.clone(), child.clone());
+                } else {
+                    let rest: Vec<Vec<String>> = matching.iter().map(|path| path[1..].to_vec()).collect();
+                    projected.insert(key.clone(), project(child, &rest));
+                }
+            }
+            Value::Object(projected)
+        }
+        other => other.clone(),
+    }
+}
+
+fn parse_jq(expression: &str) -> Result<Vec<Stage>, String> {
+    split_pipeline(expression)?
+        .into_iter()
+        .map(|stage| match stage.trim() {
+            "length" => Ok(Stage::Length),
+            "keys" => Ok(Stage::Keys),
+            path => parse_path(path).map(Stage::Path),
+        })
+        .collect()
+}
+
+/// Split at the `|`s outside quoted keys
+fn split_pipeline(expression: &str) -> Result<Vec<&str>, String> {
+    let mut stages = Vec::new();
+    let (mut start, mut quoted) = (0, false);
+    for (index, c) in expression.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '|' if !quoted => {
+                stages.push(&expression[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    if quoted {
+        return Err("unterminated string".to_string());
+    }
+    stages.push(&expression[start..]);
+    Ok(stages)
+}
+
+fn parse_path(path: &str) -> Result<Vec<Segment>, String> {
+    if !path.starts_with('.') {
+        return Err(format!("'{}' is not a path (paths start with '.'), length or keys", path));
+    }
+    let chars: Vec<char> = path.chars().collect();
+    let mut segments = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '.' => {
+                i += 1;
+                if i < chars.len() && chars[i] == '"' {
+                    let end = chars[i + 1..].iter().position(|c| *c == '"').ok_or("unterminated string")? + i + 1;
+                    segments.push(Segment::Field(chars[i + 1..end].iter().collect()));
+                    i = end + 1;
+                } else {
+                    let start = i;
+                    while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '-') {
+                        i += 1;
+                    }
+                    if i > start {
+                        segments.push(Segment::Field(chars[start..i].iter().collect()));
+                    } else if i < chars.len() && chars[i] != '[' {
+                        return Err(format!("unexpected '{}' in '{}'", chars[i], path));
+                    }
+                }
+            }
+            '[' => {
+                let end = chars[i..].iter().position(|c| *c == ']').ok_or(format!("missing ']' in '{}'", path))? + i;
+                let inner: String = chars[i + 1..end].iter().collect();
+                segments.push(parse_bracket(inner.trim())?);
+                i = end + 1;
+            }
+            c => return Err(format!("unexpected '{}' in '{}'", c, path)),
+        }
+    }
+    Ok(segments)
+}
+
+fn parse_bracket(inner: &str) -> Result<Segment, String> {
+    let number = |text: &str| text.trim().parse::<i64>().map_err(|_| format!("invalid index '{}'", text));
+    if inner.is_empty() {
+        return Ok(Segment::Iterate);
+    }
+    if let Some(key) = inner.strip_prefix('"').and_then(|key| key.strip_suffix('"')) {
+        return Ok(Segment::Field(key.to_string()));
+    }
+    if let Some((from, to)) = inner.split_once(':') {
+        let bound = |text: &str| if text.trim().is_empty() { Ok(None) } else { number(text).map(Some) };
+        return Ok(Segment::Slice(bound(from)?, bound(to)?));
+    }
+    number(inner).map(Segment::Index)
+}
+
+fn run_stage(inputs: Vec<Value>, stage: &Stage) -> Result<Vec<Value>, String> {
+    match stage {
+        Stage::Length => inputs.iter().map(length).collect(),
+        Stage::Keys => inputs.iter().map(keys).collect(),
+        Stage::Path(segments) => {
+            let mut values = inputs;
+            for segment in segments {
+                let mut next = Vec::new();
+                for value in values {
+                    step(value, segment, &mut next)?;
+                }
+                values = next;
+            }
+            Ok(values)
+        }
+    }
+}
+
+fn step(value: Value, segment: &Segment, outputs: &mut Vec<Value>) -> Result<(), String> {
+    match (segment, value) {
+        (Segment::Field(key), Value::Object(mut fields)) => outputs.push(fields.remove(key).unwrap_or(Value::Null)),
+        (Segment::Field(_) | Segment::Index(_) | Segment::Slice(..), Value::Null) => outputs.push(Value::Null),
+        (Segment::Index(index), Value::Array(items)) => {
+            let index = if *index < 0 { items.len() as i64 + index } else { *index };
+            outputs.push(usize::try_from(index).ok().and_then(|index| items.get(index).cloned()).unwrap_or(Value::Null));
+        }
+        (Segment::Slice(from, to), Value::Array(items)) => {
+            let bound = |bound: Option<i64>, default: usize| match bound {
+                None => default,
+                Some(bound) if bound < 0 => items.len().saturating_sub(bound.unsigned_abs() as usize),
+                Some(bound) => (bound as usize).min(items.len()),
+            };
+            let (from, to) = (bound(*from, 0), bound(*to, items.len()));
+            outputs.push(Value::Array(items.get(from..to.max(from)).unwrap_or_default().to_vec()));
+        }
+        (Segment::Iterate, Value::Array(items)) => outputs.extend(items),
+        (Segment::Iterate, Value::Object(fields)) => outputs.extend(fields.into_iter().map(|(_, value)| value)),
+        (segment, value) => return Err(format!("cannot apply {:?} to {}", segment, type_name(&value))),
+    }
+    Ok(())
+}
+
+fn length(value: &Value) -> Result<Value, String> {
+    match value {
+        Value::Null => Ok(Value::from(0)),
+        Value::Array(items) => Ok(Value::from(items.len())),
+        Value::Object(fields) => Ok(Value::from(fields.len())),
+        Value::String(text) => Ok(Value::from(text.chars().count())),
+        Value::Number(number) => Ok(Value::from(number.as_f64().map(f64::abs))),
+        Value::Bool(_) => Err("boolean has no length".to_string()),
+    }
+}
+
+fn keys(value: &Value) -> Result<Value, String> {
+    match value {
+        Value::Object(fields) => {
+            let mut keys: Vec<&String> = fields.keys().collect();
+            keys.sort();
+            Ok(Value::from(keys.into_iter().cloned().collect::<Vec<_>>()))
+        }
+        Value::Array(items) => Ok(Value::from((0..items.len()).collect::<Vec<_>>())),
+        other => Err(format!("{} has no keys", type_name(other))),
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::zml::ZMLParserWrapper;
+    use serde_json::json;
+
+    const ORDERS_MODULE: &str = r#"
+module Orders {
+    method listOrders {
+        http_method: GET
+        uri: "/orders"
+        params {
+            status: string?
+        }
+        response: any
+    }
+
+    method search {
+        http_method: GET
+        uri: "/search"
+        params {
+            _jq: string?
+        }
+        response: any
+    }
+}
+"#;
+
+    fn filter(params: Value) -> ResponseFilter {
+        let module = ZMLParserWrapper::new().parse(ORDERS_MODULE).unwrap();
+        let mut params: HashMap<String, Value> = serde_json::from_value(params).unwrap();
+        let filter = ResponseFilter::from_params(&mut params, &module.methods["listOrders"]).unwrap().unwrap();
+        assert!(params.is_empty());
+        filter
+    }
+
+    fn orders() -> Value {
+        json!({
+            "total": 2,
+            "data": [
+                { "id": 1, "status": "open", "owner": { "name": "ada", "email": "ada@example.com" } },
+                { "id": 2, "status": "closed", "owner": { "name": "bob", "email": "bob@example.com" } }
+            ]
+        })
+    }
+
+    #[test]
+    fn test_fields_project_nested_paths_through_arrays() {
+        let projected = filter(json!({ "_fields": "total, data.id,data.owner.name" })).apply(orders()).unwrap();
+        assert_eq!(
+            projected,
+            json!({ "total": 2, "data": [{ "id": 1, "owner": { "name": "ada" } }, { "id": 2, "owner": { "name": "bob" } }] })
+        );
+        let projected = filter(json!({ "_fields": ["id"], "_jq": ".data" })).apply(orders()).unwrap();
+        assert_eq!(projected, json!([{ "id": 1 }, { "id": 2 }]));
+    }
+
+    #[test]
+    fn test_jq_paths_iterate_slice_and_count() {
+        let run = |expression: &str| filter(json!({ "_jq": expression })).apply(orders());
+        assert_eq!(run(".data[].owner.name").unwrap(), json!(["ada", "bob"]));
+        assert_eq!(run(".data[-1].id").unwrap(), json!(2));
+        assert_eq!(run(".data[0:1] | length").unwrap(), json!(1));
+        assert_eq!(run(".data[0] | keys").unwrap(), json!(["id", "owner", "status"]));
+        assert_eq!(run(".missing.deeper").unwrap(), Value::Null);
+        assert!(run(".total[]").unwrap_err().message.contains("cannot apply Iterate to number"));
+    }
+
+    #[test]
+    fn test_invalid_and_declared_filter_params() {
+        let module = ZMLParserWrapper::new().parse(ORDERS_MODULE).unwrap();
+        let mut params = HashMap::from([("_jq".to_string(), json!("data"))]);
+        let error = ResponseFilter::from_params(&mut params, &module.methods["listOrders"]).unwrap_err();
+        assert!(error.message.contains("Invalid _jq"), "{}", error.message);
+
+        // A declared `_jq` parameter is sent to the API, not applied
+        let search = &module.methods["search"];
+        assert_eq!(filter_params(search), vec![FIELDS_PARAM]);
+        let mut params = HashMap::from([("_jq".to_string(), json!("data"))]);
+        assert!(ResponseFilter::from_params(&mut params, search).unwrap().is_none());
+        assert!(params.contains_key("_jq"));
+    }
+}
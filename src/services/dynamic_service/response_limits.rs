@@ -0,0 +1,160 @@
+//! Size limits of tool results for MCP-ANY-REST
+//!
+//! `api.response_limits` keeps multi-megabyte results out of the MCP transport. Its `omit_fields`
+//! are dropped from every JSON result. A result still larger than `max_bytes` has its arrays cut to
+//! `max_array_items` items, halved until it fits, each cut array ending with a "N more items
+//! omitted" marker. Text, and JSON that cannot be made to fit that way, is cut at `max_bytes`.
+//! Truncated results come with a note giving their original size.
+
+use super::response_content::ResponsePayload;
+use crate::config::config::ResponseLimitsConfig;
+use serde_json::Value;
+
+/// Items first kept of each array when `max_array_items` is unset
+pub const DEFAULT_MAX_ARRAY_ITEMS: usize = 100;
+
+/// Apply `limits` to a result; the note describes the truncation, if any
+pub fn limit_response(payload: ResponsePayload, limits: &ResponseLimitsConfig) -> (ResponsePayload, Option<String>) {
+    match payload {
+        ResponsePayload::Json(mut value) => {
+            omit_fields(&mut value, &limits.omit_fields);
+            let size = json_size(&value);
+            let Some(max_bytes) = limits.max_bytes.filter(|max_bytes| size > *max_bytes) else {
+                return (ResponsePayload::Json(value), None);
+            };
+
+            let mut max_items = limits.max_array_items.unwrap_or(DEFAULT_MAX_ARRAY_ITEMS);
+            loop {
+                let truncated = truncate_arrays(&value, max_items);
+                let truncated_size = json_size(&truncated);
+                if truncated_size <= max_bytes {
+                    let note = format!(
+                        "Response truncated from {} to {} bytes (limit {}): arrays keep their first {} items",
+                        size, truncated_size, max_bytes, max_items
+                    );
+                    return (ResponsePayload::Json(truncated), Some(note));
+                }
+                if max_items == 0 {
+                    break;
+                }
+                max_items /= 2;
+            }
+            let text = truncate_text(&serde_json::to_string(&value).unwrap_or_default(), max_bytes);
+            let note = format!("Response truncated from {} to {} bytes (limit {}): JSON cut short", size, text.len(), max_bytes);
+            (ResponsePayload::Text(text), Some(note))
+        }
+        ResponsePayload::Text(text) => match limits.max_bytes {
+            Some(max_bytes) if text.len() > max_bytes => {
+                let size = text.len();
+                let text = truncate_text(&text, max_bytes);
+                let note = format!("Response truncated from {} to {} bytes (limit {})", size, text.len(), max_bytes);
+                (ResponsePayload::Text(text), Some(note))
+            }
+            _ => (ResponsePayload::Text(text), None),
+        },
+        // Binary payloads are capped by `api.max_binary_response_bytes` when decoded
+        binary => (binary, None),
+    }
+}
+
+fn json_size(value: &Value) -> usize {
+    serde_json::to_vec(value).map(|bytes| bytes.len()).unwrap_or_default()
+}
+
+/// Remove the `fields` from every object of `value`
+fn omit_fields(value: &mut Value, fields: &[String]) {
+    if fields.is_empty() {
+        return;
+    }
+    match value {
+        Value::Object(object) => {
+            object.retain(|key, _| !fields.contains(key));
+            object.values_mut().for_each(|child| omit_fields(child, fields));
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| omit_fields(item, fields)),
+        _ => {}
+    }
+}
+
+/// Copy of `value` whose arrays keep `max_items` items and a marker counting the others
+fn truncate_arrays(value: &Value, max_items: usize) -> Value {
+    match value {
+        Value::Object(object) => {
+            Value::Object(object.iter().map(|(key, child)| (key.clone(), truncate_arrays(child, max_items))).collect())
+        }
+        Value::Array(items) => {
+            let mut kept: Vec<Value> = items.iter().take(max_items).map(|item| truncate_arrays(item, max_items)).collect();
+            if items.len() > max_items {
+                kept.push(Value::String(format!("{} more items omitted", items.len() - max_items)));
+            }
+            Value::Array(kept)
+        }
+        other => other.clone(),
+    }
+}
+
+/// `text` cut at a character boundary so that it and the omission marker fit in `max_bytes`
+fn truncate_text(text: &str, max_bytes: usize) -> String {
+    let marker = |omitted: usize| format!("... [{} more bytes omitted]", omitted);
+    let mut end = max_bytes.saturating_sub(marker(text.len()).len()).min(text.len());
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}{}", &text[..end], marker(text.len() - end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn limits(max_bytes: Option<usize>, omit_fields: &[&str]) -> ResponseLimitsConfig {
+        ResponseLimitsConfig {
+            max_bytes,
+            max_array_items: Some(8),
+            omit_fields: omit_fields.iter().map(|field| field.to_string()).collect(),
+        }
+    }
+
+    fn orders(count: u64) -> Value {
+        let orders: Vec<Value> = (0..count).map(|id| json!({ "id": id, "_links": { "self": format!("/orders/{}", id) } })).collect();
+        json!({ "total": count, "orders": orders })
+    }
+
+    #[test]
+    fn test_arrays_are_cut_until_the_result_fits() {
+        let (payload, note) = limit_response(ResponsePayload::Json(orders(50)), &limits(Some(100), &["_links"]));
+        let ResponsePayload::Json(value) = payload else {
+            panic!("expected JSON, got {:?}", payload);
+        };
+        // 8 items are too many for 100 bytes, 4 fit
+        assert_eq!(value["orders"].as_array().unwrap().len(), 5);
+        assert_eq!(value["orders"][0], json!({ "id": 0 }));
+        assert_eq!(value["orders"][4], "46 more items omitted");
+        let note = note.unwrap();
+        assert!(note.contains("keep their first 4 items") && note.contains("limit 100"), "{}", note);
+
+        // Small results only lose the omitted fields
+        let (payload, note) = limit_response(ResponsePayload::Json(orders(1)), &limits(Some(100), &["_links"]));
+        assert_eq!(payload, ResponsePayload::Json(json!({ "total": 1, "orders": [{ "id": 0 }] })));
+        assert!(note.is_none());
+    }
+
+    #[test]
+    fn test_text_and_oversized_json_are_cut_at_the_limit() {
+        let (payload, note) = limit_response(ResponsePayload::Text("é".repeat(100)), &limits(Some(64), &[]));
+        let ResponsePayload::Text(text) = payload else {
+            panic!("expected text, got {:?}", payload);
+        };
+        assert!(text.len() <= 64 && text.ends_with("more bytes omitted]"), "{}", text);
+        assert!(note.unwrap().contains("from 200 to"));
+
+        let long = json!({ "description": "x".repeat(500) });
+        let (payload, note) = limit_response(ResponsePayload::Json(long), &limits(Some(100), &[]));
+        assert!(matches!(payload, ResponsePayload::Text(text) if text.len() <= 100));
+        assert!(note.unwrap().contains("JSON cut short"));
+
+        let unlimited = limit_response(ResponsePayload::Json(orders(50)), &ResponseLimitsConfig::default());
+        assert_eq!(unlimited, (ResponsePayload::Json(orders(50)), None));
+    }
+}
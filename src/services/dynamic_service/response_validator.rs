@@ -1,91 +1,213 @@
 //! Response validation for dynamic module service
+//!
+//! Responses are checked against the declared response type in full rather than stopping at the
+//! first problem, and every mismatch is recorded in the schema drift report (see `drift`), so
+//! maintainers learn which fields of an upstream API changed shape.
 
 use crate::config::zml_loader::ZmlModuleLoader;
+use crate::services::dynamic_service::drift::record_drift;
+use crate::services::dynamic_service::parameter_validator::with_source_location;
+use crate::services::dynamic_service::scalar_codec::{check_scalar_wire, resolve_scalar};
 use crate::zml::ast::{EnumDef as ZmlEnumDef, MethodDef as ZmlMethodDef, Module as ZmlModule, TypeDef as ZmlTypeDef, TypeExpr as ZmlTypeExpr, Value as ZmlValue};
 use rmcp::ErrorData as McpError;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::fmt;
+
+/// How a response field differs from its declaration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DriftKind {
+    /// A required field is absent
+    Missing,
+    /// The field has another type, or a value outside its enum or scalar format
+    UnexpectedType,
+}
+
+/// Response field not matching the declared response type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResponseMismatch {
+    /// Dotted path of the field, with `[]` for array items; empty for the response itself
+    pub path: String,
+    pub kind: DriftKind,
+    pub expected: String,
+    /// JSON type or value found; `missing` for missing fields
+    pub found: String,
+}
+
+impl fmt::Display for ResponseMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            DriftKind::Missing => write!(f, "Missing required field: {}", self.path),
+            DriftKind::UnexpectedType if self.path.is_empty() => {
+                write!(f, "Response must be {}, got {}", self.expected, self.found)
+            }
+            DriftKind::UnexpectedType => {
+                write!(f, "Response field '{}' must be {}, got {}", self.path, self.expected, self.found)
+            }
+        }
+    }
+}
 
 /// ===================== ZML Support =====================
-/// Validate response against ZML method response type
+/// Validate response against ZML method response type; every mismatch is recorded as drift of the
+/// method and the first one is returned as the error
 pub fn validate_response_zml(
     response: &Value,
     method: &ZmlMethodDef,
     module: &ZmlModule,
     loader: Option<&ZmlModuleLoader>,
 ) -> Result<(), McpError> {
-    validate_value_against_typeexpr(response, &method.response, module, loader)
+    let mismatches = response_mismatches_zml(response, method, module, loader);
+    let Some(first) = mismatches.first() else {
+        return Ok(());
+    };
+    record_drift(&module.name, &method.name, &mismatches);
+    Err(with_source_location(
+        McpError::internal_error(first.to_string(), None),
+        module,
+        &format!("methods.{}", method.name),
+    ))
 }
 
-/// Validate a JSON value against a ZML TypeExpr (recursive)
+/// Every field of `response` not matching the method's response type, once per path
+pub fn response_mismatches_zml(
+    response: &Value,
+    method: &ZmlMethodDef,
+    module: &ZmlModule,
+    loader: Option<&ZmlModuleLoader>,
+) -> Vec<ResponseMismatch> {
+    let mut mismatches = Vec::new();
+    validate_value_against_typeexpr(response, &method.response, module, loader, "", &mut mismatches);
+    mismatches
+}
+
+/// Validate a JSON value against a ZML TypeExpr (recursive), collecting the mismatches
 fn validate_value_against_typeexpr(
     value: &Value,
     type_expr: &ZmlTypeExpr,
     module: &ZmlModule,
     loader: Option<&ZmlModuleLoader>,
-) -> Result<(), McpError> {
+    path: &str,
+    mismatches: &mut Vec<ResponseMismatch>,
+) {
+    let unexpected = |mismatches: &mut Vec<ResponseMismatch>, expected: &str, found: String| {
+        push(mismatches, ResponseMismatch { path: path.to_string(), kind: DriftKind::UnexpectedType, expected: expected.to_string(), found })
+    };
     match type_expr {
-        ZmlTypeExpr::String => {
-            if !value.is_string() { return Err(McpError::internal_error("Response must be string", None)); }
+        ZmlTypeExpr::String | ZmlTypeExpr::File | ZmlTypeExpr::Date | ZmlTypeExpr::DateTime => {
+            if !value.is_string() { unexpected(mismatches, "string", json_type(value)); }
         }
         ZmlTypeExpr::Integer => {
             if !(value.is_number() && (value.as_i64().is_some() || value.as_u64().is_some())) {
-                return Err(McpError::internal_error("Response must be integer", None));
+                unexpected(mismatches, "integer", json_type(value));
             }
         }
         ZmlTypeExpr::Number => {
-            if !value.is_number() { return Err(McpError::internal_error("Response must be number", None)); }
+            if !value.is_number() { unexpected(mismatches, "number", json_type(value)); }
         }
         ZmlTypeExpr::Boolean => {
-            if !value.is_boolean() { return Err(McpError::internal_error("Response must be boolean", None)); }
-        }
-        ZmlTypeExpr::Date | ZmlTypeExpr::DateTime => {
-            if !value.is_string() { return Err(McpError::internal_error("Response must be string date/datetime", None)); }
+            if !value.is_boolean() { unexpected(mismatches, "boolean", json_type(value)); }
         }
         ZmlTypeExpr::Any => { /* always valid */ }
-        ZmlTypeExpr::Array(item) => {
-            if !value.is_array() { return Err(McpError::internal_error("Response must be array", None)); }
-            for v in value.as_array().unwrap() {
-                validate_value_against_typeexpr(v, item, module, loader)?;
+        ZmlTypeExpr::Array(item) => match value.as_array() {
+            Some(items) => {
+                let item_path = format!("{}[]", path);
+                for v in items {
+                    validate_value_against_typeexpr(v, item, module, loader, &item_path, mismatches);
+                }
             }
-        }
+            None => unexpected(mismatches, "array", json_type(value)),
+        },
         ZmlTypeExpr::Object(fields) => {
-            if !value.is_object() { return Err(McpError::internal_error("Response must be object", None)); }
-            let obj = value.as_object().unwrap();
-            for (fname, fdef) in fields.iter() {
-                if !fdef.optional && !obj.contains_key(fname) {
-                    return Err(McpError::internal_error(format!("Missing required field: {}", fname), None));
-                }
-                if let Some(v) = obj.get(fname) {
-                    validate_value_against_typeexpr(v, &fdef.type_expr, module, loader)?;
+            let Some(obj) = value.as_object() else {
+                unexpected(mismatches, "object", json_type(value));
+                return;
+            };
+            // Fields are checked by name, so the first mismatch reported does not depend on map order
+            let mut sorted: Vec<_> = fields.iter().collect();
+            sorted.sort_by(|(a, _), (b, _)| a.cmp(b));
+            for (fname, fdef) in sorted {
+                let field_path = if path.is_empty() { fname.clone() } else { format!("{}.{}", path, fname) };
+                match obj.get(fname) {
+                    Some(v) => validate_value_against_typeexpr(v, &fdef.type_expr, module, loader, &field_path, mismatches),
+                    None if !fdef.optional => push(mismatches, ResponseMismatch {
+                        path: field_path,
+                        kind: DriftKind::Missing,
+                        expected: type_name(&fdef.type_expr),
+                        found: "missing".to_string(),
+                    }),
+                    None => {}
                 }
             }
         }
         ZmlTypeExpr::Enum(values) => {
-            if let Some(s) = value.as_str() {
-                if !values.iter().any(|v| v == s) {
-                    return Err(McpError::internal_error(format!("Response value '{}' not in enum", s), None));
-                }
-            } else {
-                return Err(McpError::internal_error("Enum response must be string", None));
+            if !value.as_str().is_some_and(|s| values.iter().any(|v| v == s)) {
+                unexpected(mismatches, &format!("one of {}", values.join(", ")), value.to_string());
             }
         }
         ZmlTypeExpr::Ref(name) | ZmlTypeExpr::Alias(name) => {
+            if let Some(scalar) = resolve_scalar(name, module) {
+                // Responses are validated in their wire form
+                if let Err(e) = check_scalar_wire(value, scalar) {
+                    unexpected(mismatches, name, format!("{} ({})", value, e));
+                }
+                return;
+            }
             let (tdef, edef) = resolve_named(name, module, loader);
             if let Some(td) = tdef {
                 // Treat typedef as object
                 let as_object = ZmlTypeExpr::Object(td.fields.clone());
-                validate_value_against_typeexpr(value, &as_object, module, loader)?;
+                validate_value_against_typeexpr(value, &as_object, module, loader, path, mismatches);
             } else if let Some(ed) = edef {
-                validate_enumdef(value, ed)?;
-            } else {
-                // Unknown reference; fallback to string
-                if !value.is_string() {
-                    return Err(McpError::internal_error("Response must be string (unresolved ref)", None));
+                if !matches_enumdef(value, ed) {
+                    unexpected(mismatches, name, value.to_string());
                 }
+            } else if !value.is_string() {
+                // Unknown reference; fallback to string
+                unexpected(mismatches, "string", json_type(value));
             }
         }
     }
-    Ok(())
+}
+
+/// Add a mismatch unless its path already has one, so array items report a field once
+fn push(mismatches: &mut Vec<ResponseMismatch>, mismatch: ResponseMismatch) {
+    if !mismatches.iter().any(|m| m.path == mismatch.path) {
+        mismatches.push(mismatch);
+    }
+}
+
+/// JSON type of a value, as reported in mismatches
+fn json_type(value: &Value) -> String {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+    .to_string()
+}
+
+/// Declared type of a missing field
+fn type_name(type_expr: &ZmlTypeExpr) -> String {
+    match type_expr {
+        ZmlTypeExpr::String => "string".to_string(),
+        ZmlTypeExpr::Integer => "integer".to_string(),
+        ZmlTypeExpr::Number => "number".to_string(),
+        ZmlTypeExpr::Boolean => "boolean".to_string(),
+        ZmlTypeExpr::Date => "date".to_string(),
+        ZmlTypeExpr::DateTime => "datetime".to_string(),
+        ZmlTypeExpr::File => "file".to_string(),
+        ZmlTypeExpr::Any => "any".to_string(),
+        ZmlTypeExpr::Array(item) => format!("array<{}>", type_name(item)),
+        ZmlTypeExpr::Object(_) => "object".to_string(),
+        ZmlTypeExpr::Enum(_) => "enum".to_string(),
+        ZmlTypeExpr::Ref(name) | ZmlTypeExpr::Alias(name) => name.clone(),
+    }
 }
 
 /// Resolve a named type/enum, supporting qualified module reference: Module.Type
@@ -105,18 +227,13 @@ fn resolve_named<'a>(
     }
 }
 
-/// Validate value against an EnumDef (supports typed enum values)
-fn validate_enumdef(value: &Value, ed: &ZmlEnumDef) -> Result<(), McpError> {
+/// Whether a value belongs to an EnumDef (supports typed enum values)
+fn matches_enumdef(value: &Value, ed: &ZmlEnumDef) -> bool {
     // Accept either explicit typed values or the enum key names as strings
-    for (_name, ev) in ed.values.iter() {
-        if let Some(v) = &ev.value {
-            let expected = zml_value_to_json(v);
-            if &expected == value { return Ok(()); }
-        } else if let Some(s) = value.as_str() {
-            if s == ev.name { return Ok(()); }
-        }
-    }
-    Err(McpError::internal_error("Response value not found in enum", None))
+    ed.values.values().any(|ev| match &ev.value {
+        Some(v) => &zml_value_to_json(v) == value,
+        None => value.as_str() == Some(ev.name.as_str()),
+    })
 }
 
 /// Convert ZML Value to serde_json::Value (local copy)
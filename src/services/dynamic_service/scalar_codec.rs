@@ -0,0 +1,194 @@
+//! Custom scalar encoding/decoding for dynamic module service
+
+use crate::zml::ast::{
+    MethodDef as ZmlMethodDef, Module as ZmlModule, ScalarDef as ZmlScalarDef,
+    ScalarEncoding as ZmlScalarEncoding, TypeExpr as ZmlTypeExpr,
+};
+use anyhow::{anyhow, Result};
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Maximum depth for walking nested types to prevent infinite recursion
+const MAX_NESTING_DEPTH: usize = 10;
+
+/// ===================== ZML Support =====================
+/// Encode tool arguments into their wire form according to custom scalar rules
+pub fn encode_params_zml(
+    params: &HashMap<String, Value>,
+    module: &ZmlModule,
+    method: &ZmlMethodDef,
+) -> Result<HashMap<String, Value>> {
+    let mut encoded = params.clone();
+    for (name, def) in &method.params {
+        if let Some(value) = params.get(name) {
+            let converted = map_scalars(value, &def.type_expr, module, &encode_scalar, 0)
+                .map_err(|e| {
+                    match module.source_location(&format!("methods.{}.params.{}", method.name, name)) {
+                        Some(location) => anyhow!("Parameter '{}': {} (defined at {})", name, e, location),
+                        None => anyhow!("Parameter '{}': {}", name, e),
+                    }
+                })?;
+            encoded.insert(name.clone(), converted);
+        }
+    }
+    Ok(encoded)
+}
+
+/// Decode an API response from its wire form according to custom scalar rules
+pub fn decode_response_zml(response: &Value, method: &ZmlMethodDef, module: &ZmlModule) -> Value {
+    // Decoding is best-effort: values that do not match the wire form are returned untouched
+    map_scalars(response, &method.response, module, &|v: &Value, s: &ZmlScalarDef| Ok(decode_scalar(v, s)), 0)
+        .unwrap_or_else(|_| response.clone())
+}
+
+/// Check a wire value against the scalar base type and pattern
+pub fn check_scalar_wire(value: &Value, scalar: &ZmlScalarDef) -> Result<()> {
+    let type_ok = match scalar.base {
+        ZmlTypeExpr::String | ZmlTypeExpr::Date | ZmlTypeExpr::DateTime => value.is_string(),
+        ZmlTypeExpr::Integer => value.as_i64().is_some() || value.as_u64().is_some(),
+        ZmlTypeExpr::Number => value.is_number(),
+        ZmlTypeExpr::Boolean => value.is_boolean(),
+        _ => true,
+    };
+    if !type_ok {
+        return Err(anyhow!(
+            "value for scalar '{}' must be {}",
+            scalar.name,
+            scalar.base.to_string_repr()
+        ));
+    }
+
+    if let Some(re) = &scalar.pattern_regex {
+        let text = match value {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        if !re.is_match(&text) {
+            return Err(anyhow!(
+                "value '{}' does not match pattern '{}' of scalar '{}'",
+                text,
+                re.as_str(),
+                scalar.name
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Encode a single value into its wire form and validate it
+pub fn encode_scalar(value: &Value, scalar: &ZmlScalarDef) -> Result<Value> {
+    let wire = match &scalar.encode {
+        None => value.clone(),
+        Some(ZmlScalarEncoding::UnixSeconds) => match value {
+            Value::String(s) => Value::from(parse_rfc3339(s)?.timestamp()),
+            other => other.clone(),
+        },
+        Some(ZmlScalarEncoding::UnixMillis) => match value {
+            Value::String(s) => Value::from(parse_rfc3339(s)?.timestamp_millis()),
+            other => other.clone(),
+        },
+        Some(ZmlScalarEncoding::Rfc3339) => match value {
+            Value::String(s) => Value::String(parse_rfc3339(s)?.to_rfc3339()),
+            other => match other.as_i64() {
+                Some(secs) => Value::String(
+                    DateTime::<Utc>::from_timestamp(secs, 0)
+                        .ok_or_else(|| anyhow!("timestamp {} is out of range", secs))?
+                        .to_rfc3339(),
+                ),
+                None => other.clone(),
+            },
+        },
+        Some(ZmlScalarEncoding::Base64) => match value {
+            Value::String(s) => Value::String(base64::engine::general_purpose::STANDARD.encode(s)),
+            other => other.clone(),
+        },
+    };
+    check_scalar_wire(&wire, scalar)?;
+    Ok(wire)
+}
+
+/// Decode a single wire value; values that cannot be decoded are returned untouched
+pub fn decode_scalar(value: &Value, scalar: &ZmlScalarDef) -> Value {
+    let decoded = match &scalar.encode {
+        Some(ZmlScalarEncoding::UnixSeconds) => value
+            .as_i64()
+            .and_then(|secs| DateTime::<Utc>::from_timestamp(secs, 0))
+            .map(|dt| Value::String(dt.to_rfc3339())),
+        Some(ZmlScalarEncoding::UnixMillis) => value
+            .as_i64()
+            .and_then(DateTime::<Utc>::from_timestamp_millis)
+            .map(|dt| Value::String(dt.to_rfc3339())),
+        Some(ZmlScalarEncoding::Base64) => value
+            .as_str()
+            .and_then(|s| base64::engine::general_purpose::STANDARD.decode(s).ok())
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .map(Value::String),
+        Some(ZmlScalarEncoding::Rfc3339) => value
+            .as_str()
+            .and_then(|s| parse_rfc3339(s).ok())
+            .map(|dt| Value::String(dt.to_rfc3339())),
+        None => None,
+    };
+    decoded.unwrap_or_else(|| value.clone())
+}
+
+/// Resolve a custom scalar by alias name
+pub fn resolve_scalar<'a>(name: &str, module: &'a ZmlModule) -> Option<&'a ZmlScalarDef> {
+    module.scalars.get(name)
+}
+
+/// Walk a value alongside its type expression and apply `f` to every custom scalar
+fn map_scalars(
+    value: &Value,
+    type_expr: &ZmlTypeExpr,
+    module: &ZmlModule,
+    f: &dyn Fn(&Value, &ZmlScalarDef) -> Result<Value>,
+    depth: usize,
+) -> Result<Value> {
+    if depth > MAX_NESTING_DEPTH || value.is_null() {
+        return Ok(value.clone());
+    }
+
+    match type_expr {
+        ZmlTypeExpr::Array(item) => match value {
+            Value::Array(items) => Ok(Value::Array(
+                items
+                    .iter()
+                    .map(|v| map_scalars(v, item, module, f, depth + 1))
+                    .collect::<Result<Vec<_>>>()?,
+            )),
+            other => Ok(other.clone()),
+        },
+        ZmlTypeExpr::Object(fields) => match value {
+            Value::Object(obj) => {
+                let mut out = obj.clone();
+                for (fname, fdef) in fields.iter() {
+                    if let Some(v) = obj.get(fname) {
+                        out.insert(fname.clone(), map_scalars(v, &fdef.type_expr, module, f, depth + 1)?);
+                    }
+                }
+                Ok(Value::Object(out))
+            }
+            other => Ok(other.clone()),
+        },
+        ZmlTypeExpr::Ref(name) | ZmlTypeExpr::Alias(name) => {
+            if let Some(scalar) = resolve_scalar(name, module) {
+                f(value, scalar)
+            } else if let Some(td) = module.types.get(name) {
+                let as_object = ZmlTypeExpr::Object(td.fields.clone());
+                map_scalars(value, &as_object, module, f, depth + 1)
+            } else {
+                Ok(value.clone())
+            }
+        }
+        _ => Ok(value.clone()),
+    }
+}
+
+fn parse_rfc3339(s: &str) -> Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|e| anyhow!("'{}' is not an RFC 3339 datetime: {}", s, e))
+}
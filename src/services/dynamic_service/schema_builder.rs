@@ -3,9 +3,13 @@ use serde_json::{Map, Value};
 use std::collections::{HashMap, HashSet};
 
 use crate::config::zml_loader::ZmlModuleLoader;
+use crate::services::dynamic_service::dry_run::{supports_dry_run, DRY_RUN_PARAM};
+use crate::services::dynamic_service::response_filter::{filter_params, FIELDS_PARAM};
+use crate::services::dynamic_service::streaming::{supports_stream, STREAM_PARAM};
 
 use crate::zml::ast::{
-    EnumDef, FieldDef, MethodDef, Module, TypeDef, TypeExpr, Value as ZmlValue,
+    EnumDef, FieldDef, MethodDef, Module, ScalarDef, ScalarEncoding, TypeDef, TypeExpr,
+    Value as ZmlValue, FETCH_ALL_PARAM,
 };
 
 /// Maximum depth for nested object expansion to prevent infinite recursion
@@ -13,7 +17,54 @@ const MAX_NESTING_DEPTH: usize = 10;
 
 /// Build input schema for tool based on ZML method definition
 pub fn build_input_schema_zml(method: &MethodDef, module: &Module, loader: Option<&ZmlModuleLoader>) -> Value {
-    let (properties, required) = build_schema_properties(method, module, loader);
+    let (mut properties, required) = build_schema_properties(method, module, loader);
+    if method.supports_fetch_all() {
+        let mut fetch_all = Map::new();
+        fetch_all.insert("type".to_string(), Value::String("boolean".to_string()));
+        fetch_all.insert("default".to_string(), Value::Bool(false));
+        fetch_all.insert(
+            "description".to_string(),
+            Value::String("Fetch every page (up to the page limit) and return all items with their count".to_string()),
+        );
+        properties.insert(FETCH_ALL_PARAM.to_string(), Value::Object(fetch_all));
+    }
+    if supports_dry_run(method) {
+        properties.insert(
+            DRY_RUN_PARAM.to_string(),
+            serde_json::json!({
+                "type": "boolean",
+                "default": false,
+                "description": "Return the HTTP request (method, URL, headers with credentials masked, body) instead of sending it"
+            }),
+        );
+    }
+    if supports_stream(method) {
+        properties.insert(
+            STREAM_PARAM.to_string(),
+            serde_json::json!({
+                "type": "boolean",
+                "default": false,
+                "description": "Send the items of an array or NDJSON result in chunks through progress notifications while the response arrives; the result then only reports the counts. Needs a progress token and api.stream_chunk_items"
+            }),
+        );
+    }
+    for name in filter_params(method) {
+        let filter = if name == FIELDS_PARAM {
+            serde_json::json!({
+                "anyOf": [
+                    { "type": "array", "items": { "type": "string" } },
+                    { "type": "string" }
+                ],
+                "description": "Fields to return as dotted paths (`id`, `owner.name`), a list or comma-separated; arrays are projected item by item"
+            })
+        } else {
+            serde_json::json!({
+                "type": "string",
+                "description": "jq-like expression applied to the JSON result: paths (`.data.items[]`, `.[0]`, `.[2:5]`), `|`, `length` and `keys`"
+            })
+        };
+        properties.insert(name.to_string(), filter);
+    }
 
     let mut schema = Map::new();
     // Declare JSON Schema dialect for MCP clients
@@ -148,6 +199,11 @@ fn build_schema_properties_with_depth(
                 obj.insert("default".to_string(), zml_value_to_json(default));
             }
         }
+        if !param_def.examples.is_empty() {
+            if let Some(obj) = param_schema.as_object_mut() {
+                obj.insert("examples".to_string(), examples_to_json(&param_def.examples));
+            }
+        }
 
         properties.insert(param_name.clone(), param_schema);
 
@@ -177,6 +233,11 @@ fn zml_value_to_json(v: &ZmlValue) -> Value {
     }
 }
 
+/// Convert ZML example values to a JSON Schema `examples` array
+fn examples_to_json(examples: &[ZmlValue]) -> Value {
+    Value::Array(examples.iter().map(zml_value_to_json).collect())
+}
+
 /// Resolve a named type reference to TypeDef or EnumDef
 fn resolve_named<'a>(
     name: &str,
@@ -222,6 +283,26 @@ fn build_type_schema(
         TypeExpr::Boolean => json_type("boolean"),
         TypeExpr::Date => json_string_with_format("date"),
         TypeExpr::DateTime => json_string_with_format("date-time"),
+        TypeExpr::File => {
+            let mut content = Map::new();
+            content.insert("type".to_string(), Value::String("string".to_string()));
+            content.insert("contentEncoding".to_string(), Value::String("base64".to_string()));
+            let mut resource = Map::new();
+            resource.insert("type".to_string(), Value::String("object".to_string()));
+            let mut m = Map::new();
+            m.insert(
+                "anyOf".to_string(),
+                Value::Array(vec![Value::Object(content), Value::Object(resource)]),
+            );
+            m.insert(
+                "description".to_string(),
+                Value::String(
+                    "File content as base64 (or a data: URL), a local file path, or an MCP embedded resource or resource link"
+                        .to_string(),
+                ),
+            );
+            Value::Object(m)
+        }
         TypeExpr::Any => Value::Object(Map::new()),
         TypeExpr::Array(item) => {
             let mut m = Map::new();
@@ -243,6 +324,9 @@ fn build_type_schema(
             Value::Object(m)
         }
         TypeExpr::Ref(name) | TypeExpr::Alias(name) => {
+            if let Some(sd) = module.scalars.get(name) {
+                return build_scalar_schema(sd, module, loader, depth + 1);
+            }
             let (type_def, enum_def) = resolve_named(name, module, loader);
             if let Some(td) = type_def {
                 build_typedef_schema(td, module, loader, depth + 1)
@@ -256,6 +340,24 @@ fn build_type_schema(
     }
 }
 
+/// Declare the computed fields on the response object, or on the items of an array response
+fn add_computed_properties(schema: &mut Value, method: &MethodDef) {
+    let target = if schema.get("type").and_then(Value::as_str) == Some("array") {
+        schema.get_mut("items")
+    } else {
+        Some(schema)
+    };
+    let Some(properties) = target.and_then(|t| t.get_mut("properties")).and_then(Value::as_object_mut) else {
+        return;
+    };
+    for field in &method.computed {
+        properties.insert(
+            field.name.clone(),
+            serde_json::json!({ "description": format!("Computed: {}", field.expression) }),
+        );
+    }
+}
+
 fn json_type(t: &str) -> Value {
     let mut m = Map::new();
     m.insert("type".to_string(), Value::String(t.to_string()));
@@ -292,6 +394,11 @@ fn build_object_schema(
                 obj.insert("default".to_string(), zml_value_to_json(default));
             }
         }
+        if !field.examples.is_empty() {
+            if let Some(obj) = field_schema.as_object_mut() {
+                obj.insert("examples".to_string(), examples_to_json(&field.examples));
+            }
+        }
 
         props.insert(name.clone(), field_schema);
         if !field.optional {
@@ -322,6 +429,34 @@ fn build_typedef_schema(
     build_object_schema(&td.fields, module, loader, depth + 1)
 }
 
+/// Build schema for a custom scalar in its client-facing form
+fn build_scalar_schema(
+    sd: &ScalarDef,
+    module: &Module,
+    loader: Option<&ZmlModuleLoader>,
+    depth: usize,
+) -> Value {
+    let mut schema = match &sd.encode {
+        // Timestamps are exchanged with clients as RFC 3339 strings
+        Some(ScalarEncoding::UnixSeconds) | Some(ScalarEncoding::UnixMillis) | Some(ScalarEncoding::Rfc3339) => {
+            json_string_with_format("date-time")
+        }
+        Some(ScalarEncoding::Base64) => json_type("string"),
+        None => {
+            let mut base = build_type_schema(&sd.base, module, loader, depth);
+            if let (Some(pattern), Some(obj)) = (&sd.pattern, base.as_object_mut()) {
+                obj.insert("pattern".to_string(), Value::String(pattern.clone()));
+            }
+            base
+        }
+    };
+
+    if let (Some(desc), Some(obj)) = (&sd.description, schema.as_object_mut()) {
+        obj.insert("description".to_string(), Value::String(desc.clone()));
+    }
+    schema
+}
+
 fn build_enumdef_schema(ed: &EnumDef) -> Value {
     // Collect enum values, using explicit value when present; otherwise use name (string)
     let mut enum_values: Vec<Value> = Vec::new();
@@ -392,6 +527,7 @@ pub fn build_output_schema_zml(method: &MethodDef, module: &Module, loader: Opti
             obj.insert("additionalProperties".to_string(), Value::Bool(false));
         }
     }
+    add_computed_properties(&mut schema, method);
 
     // Provide JSON-RPC 2.0 compliant response envelope as a definition
     let mut defs = Map::new();
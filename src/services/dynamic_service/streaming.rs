@@ -0,0 +1,274 @@
+//! Streaming of large tool results for MCP-ANY-REST
+//!
+//! With `api.stream_chunk_items` set, a tool called with `stream: true` by a client that sent a
+//! progress token gets the items of a JSON array or NDJSON response in chunks of that size
+//! through progress notifications, each carrying its chunk as JSON in the message. The response
+//! body is parsed as it arrives, so a chunk is sent as soon as its items are complete and the
+//! whole export is never held in memory. The tool result then only reports how many items and
+//! chunks were sent. Other responses, and calls without `stream` or a progress token, return
+//! the whole result as usual.
+
+use crate::services::auth_service::auth_strategy::is_ndjson;
+use crate::zml::ast::MethodDef;
+use rmcp::ErrorData as McpError;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+/// Synthetic boolean parameter asking for the result in chunks
+pub const STREAM_PARAM: &str = "stream";
+
+/// Chunks of the body buffered between the API connection and the parser
+pub const STREAM_BUFFER_CHUNKS: usize = 16;
+
+/// Whether `method` takes the synthetic `stream` parameter: it sends a single request whose
+/// response is returned as it is, and does not declare such a parameter itself
+pub fn supports_stream(method: &MethodDef) -> bool {
+    method.workflow.is_none() && method.xml_body.is_none() && !method.params.contains_key(STREAM_PARAM)
+}
+
+/// Take the `stream` parameter out of the call's `params`; true when it is set
+pub fn take_stream(params: &mut HashMap<String, Value>, method: &MethodDef) -> bool {
+    supports_stream(method) && params.remove(STREAM_PARAM).and_then(|value| value.as_bool()).unwrap_or(false)
+}
+
+/// Whether `method` declares an NDJSON response
+pub fn produces_ndjson(method: &MethodDef) -> bool {
+    method
+        .produces
+        .as_deref()
+        .and_then(|media_type| media_type.split(';').next())
+        .is_some_and(|media_type| is_ndjson(&media_type.trim().to_ascii_lowercase()))
+}
+
+type ChunkFuture = Pin<Box<dyn Future<Output = Result<(), McpError>> + Send>>;
+
+/// Where the items of a streamed tool call go
+#[derive(Clone)]
+pub struct ChunkStream {
+    pub chunk_items: usize,
+    /// Sends one chunk as a progress notification, given the number of items sent so far and the
+    /// chunk as JSON
+    notify: Arc<dyn Fn(f64, String) -> ChunkFuture + Send + Sync>,
+}
+
+impl ChunkStream {
+    pub fn new<F, Fut>(chunk_items: usize, notify: F) -> Self
+    where
+        F: Fn(f64, String) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), McpError>> + Send + 'static,
+    {
+        let notify = move |progress: f64, message: String| -> ChunkFuture { Box::pin(notify(progress, message)) };
+        Self { chunk_items, notify: Arc::new(notify) }
+    }
+}
+
+/// Incremental parser of a body that is a JSON array or NDJSON, by its first byte, into items
+#[derive(Default)]
+pub struct ItemParser {
+    state: ParserState,
+    /// Bytes of the item being read
+    item: Vec<u8>,
+    /// Nesting of objects and arrays within the array item being read
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    /// Whether a comma announced another array item
+    expect_item: bool,
+}
+
+#[derive(Default)]
+enum ParserState {
+    #[default]
+    Start,
+    Array,
+    End,
+    Lines,
+}
+
+impl ItemParser {
+    /// Whether the body is a JSON array
+    pub fn is_array(&self) -> bool {
+        matches!(self.state, ParserState::Array | ParserState::End)
+    }
+
+    /// Read the next bytes of the body, returning the items they complete
+    pub fn feed(&mut self, bytes: &[u8]) -> Result<Vec<Value>, String> {
+        let mut items = Vec::new();
+        for &byte in bytes {
+            match self.state {
+                ParserState::Start | ParserState::End if byte.is_ascii_whitespace() => {}
+                ParserState::Start if byte == b'[' => self.state = ParserState::Array,
+                ParserState::Start => {
+                    self.state = ParserState::Lines;
+                    self.item.push(byte);
+                }
+                ParserState::Array => items.extend(self.feed_array(byte)?),
+                ParserState::End => return Err("data after the end of the array".to_string()),
+                ParserState::Lines if byte == b'\n' => items.extend(self.take_item()?),
+                ParserState::Lines => self.item.push(byte),
+            }
+        }
+        Ok(items)
+    }
+
+    /// End of the body, returning the last NDJSON item
+    pub fn finish(&mut self) -> Result<Vec<Value>, String> {
+        match self.state {
+            ParserState::Array => Err("the body ended inside the array".to_string()),
+            ParserState::Lines => Ok(self.take_item()?.into_iter().collect()),
+            ParserState::Start | ParserState::End => Ok(Vec::new()),
+        }
+    }
+
+    fn feed_array(&mut self, byte: u8) -> Result<Option<Value>, String> {
+        if self.in_string {
+            match byte {
+                _ if self.escaped => self.escaped = false,
+                b'\\' => self.escaped = true,
+                b'"' => self.in_string = false,
+                _ => {}
+            }
+            self.item.push(byte);
+            return Ok(None);
+        }
+        match byte {
+            b',' | b']' if self.depth == 0 => {
+                let item = self.take_item()?;
+                if item.is_none() && (byte == b',' || self.expect_item) {
+                    return Err("missing array item".to_string());
+                }
+                self.expect_item = byte == b',';
+                if byte == b']' {
+                    self.state = ParserState::End;
+                }
+                return Ok(item);
+            }
+            b'"' => self.in_string = true,
+            b'{' | b'[' => self.depth += 1,
+            b'}' | b']' => self.depth = self.depth.checked_sub(1).ok_or("unbalanced brackets in an array item")?,
+            _ => {}
+        }
+        self.item.push(byte);
+        Ok(None)
+    }
+
+    fn take_item(&mut self) -> Result<Option<Value>, String> {
+        let item = std::mem::take(&mut self.item);
+        let text = std::str::from_utf8(&item).map_err(|e| format!("invalid UTF-8: {}", e))?.trim();
+        if text.is_empty() {
+            return Ok(None);
+        }
+        serde_json::from_str(text).map(Some).map_err(|e| format!("invalid item: {}", e))
+    }
+}
+
+/// Parse a body arriving on `receiver` and send its items in chunks as soon as they are complete,
+/// the items of a JSON array passing through `map` first; returns the summary standing in for the
+/// result, or `None` when nothing arrived because the response was read whole
+pub async fn stream_body<M>(
+    mut receiver: mpsc::Receiver<Vec<u8>>,
+    stream: &ChunkStream,
+    mut map: M,
+) -> Result<Option<Value>, McpError>
+where
+    M: FnMut(Vec<Value>) -> Result<Vec<Value>, McpError>,
+{
+    let invalid = |e: String| McpError::internal_error(format!("Failed to parse the streamed response: {}", e), None);
+    let chunk_items = stream.chunk_items.max(1);
+    let mut parser = ItemParser::default();
+    let mut pending = Vec::new();
+    let (mut sent, mut chunks, mut received) = (0, 0, false);
+    loop {
+        let done = match receiver.recv().await {
+            Some(bytes) => {
+                received = true;
+                pending.extend(parser.feed(&bytes).map_err(invalid)?);
+                false
+            }
+            None if !received => return Ok(None),
+            None => {
+                pending.extend(parser.finish().map_err(invalid)?);
+                true
+            }
+        };
+        while pending.len() >= chunk_items || (done && !pending.is_empty()) {
+            let chunk: Vec<Value> = pending.drain(..chunk_items.min(pending.len())).collect();
+            let chunk = if parser.is_array() { map(chunk)? } else { chunk };
+            let message = serde_json::to_string(&chunk)
+                .map_err(|e| McpError::internal_error(format!("Failed to serialize result chunk: {}", e), None))?;
+            sent += chunk.len();
+            chunks += 1;
+            (stream.notify)(sent as f64, message).await?;
+        }
+        if done {
+            return Ok(Some(serde_json::json!({ "streamed": true, "items": sent, "chunks": chunks })));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    fn parse(parts: &[&str]) -> Result<Vec<Value>, String> {
+        let mut parser = ItemParser::default();
+        let mut items = Vec::new();
+        for part in parts {
+            items.extend(parser.feed(part.as_bytes())?);
+        }
+        items.extend(parser.finish()?);
+        Ok(items)
+    }
+
+    #[test]
+    fn test_items_are_parsed_across_chunk_boundaries() {
+        let items = parse(&[" [{\"id\":1,\"tags\":[\"a,", "b]\"]}, {\"name\":\"say \\\"", "hi\\\"\"},", "3 ,null]\n"]).unwrap();
+        assert_eq!(items, vec![json!({ "id": 1, "tags": ["a,b]"] }), json!({ "name": "say \"hi\"" }), json!(3), Value::Null]);
+        assert_eq!(parse(&["{\"id\":1}\r\n{\"i", "d\":2}\n\n{\"id\":3}"]).unwrap(), vec![json!({ "id": 1 }), json!({ "id": 2 }), json!({ "id": 3 })]);
+        assert!(parse(&["[]"]).unwrap().is_empty());
+
+        assert!(parse(&["[1,]"]).is_err());
+        assert!(parse(&["[1,2"]).is_err());
+        assert!(parse(&["[1] 2"]).is_err());
+        assert!(parse(&["{\"id\":1}\nnot json\n"]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_items_are_sent_in_chunks_as_they_arrive() {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let recorded = sent.clone();
+        let stream = ChunkStream::new(2, move |progress, message| {
+            recorded.lock().unwrap().push((progress, message));
+            async { Ok(()) }
+        });
+
+        let (sender, receiver) = mpsc::channel(STREAM_BUFFER_CHUNKS);
+        let body = async move {
+            for part in ["[{\"id\":1},{\"id\":2},", "{\"id\":3}", ",{\"id\":4},{\"id\":5}]"] {
+                sender.send(part.as_bytes().to_vec()).await.unwrap();
+            }
+        };
+        let (_, summary) = tokio::join!(
+            body,
+            stream_body(receiver, &stream, |items| {
+                Ok(items.into_iter().map(|item| json!({ "id": item["id"], "seen": true })).collect())
+            })
+        );
+
+        assert_eq!(summary.unwrap(), Some(json!({ "streamed": true, "items": 5, "chunks": 3 })));
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent[0], (2.0, r#"[{"id":1,"seen":true},{"id":2,"seen":true}]"#.to_string()));
+        assert_eq!(sent[2], (5.0, r#"[{"id":5,"seen":true}]"#.to_string()));
+
+        // Nothing arrives when the response was not streamed, and its result is returned whole
+        let (sender, receiver) = mpsc::channel(STREAM_BUFFER_CHUNKS);
+        drop(sender);
+        assert_eq!(stream_body(receiver, &stream, Ok).await.unwrap(), None);
+    }
+}
@@ -0,0 +1,125 @@
+//! Request and response transformation scripts for MCP-ANY-REST
+//!
+//! Modules and methods may configure `transform.pre_request` and `transform.post_response` Rhai
+//! scripts to rename fields, compute derived parameters or strip secrets without writing Rust.
+//! `pre_request` sees the tool parameters as the `params` map and may change it before the request
+//! is built; `post_response` sees the decoded JSON response as `response` (and the parameters as
+//! the constant `params`) and may change it before it is returned. Scripts are compiled once and
+//! run with operation and size limits; `print` and `debug` go to the log.
+
+use log::debug;
+use rhai::serde::{from_dynamic, to_dynamic};
+use rhai::{Dynamic, Engine, Scope, AST};
+use rmcp::ErrorData as McpError;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Operations a script may run before it is aborted
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+/// Compiled scripts by source
+static SCRIPTS: Mutex<Option<HashMap<String, Arc<AST>>>> = Mutex::new(None);
+
+fn engine() -> &'static Engine {
+    static ENGINE: OnceLock<Engine> = OnceLock::new();
+    ENGINE.get_or_init(|| {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        engine.set_max_expr_depths(64, 32);
+        engine.set_max_string_size(16 * 1024 * 1024);
+        engine.set_max_array_size(1_000_000);
+        engine.set_max_map_size(1_000_000);
+        // Stdout carries the MCP stdio transport
+        engine.on_print(|text| debug!("transform script: {}", text));
+        engine.on_debug(|text, _, position| debug!("transform script {}: {}", position, text));
+        // `concat(base, id)` joins values as text, for URLs and labels in computed fields
+        engine.register_fn("concat", |a: Dynamic, b: Dynamic| format!("{}{}", a, b));
+        engine.register_fn("concat", |a: Dynamic, b: Dynamic, c: Dynamic| format!("{}{}{}", a, b, c));
+        engine.register_fn("concat", |a: Dynamic, b: Dynamic, c: Dynamic, d: Dynamic| format!("{}{}{}{}", a, b, c, d));
+        engine
+    })
+}
+
+/// Compile a script, or take it from the cache of compiled scripts
+pub fn compile_script(source: &str) -> Result<Arc<AST>, String> {
+    if let Some(ast) = SCRIPTS.lock().unwrap().as_ref().and_then(|scripts| scripts.get(source)) {
+        return Ok(ast.clone());
+    }
+    let ast = Arc::new(engine().compile(source).map_err(|e| e.to_string())?);
+    SCRIPTS.lock().unwrap().get_or_insert_with(HashMap::new).insert(source.to_string(), ast.clone());
+    Ok(ast)
+}
+
+/// Run a `pre_request` script on the tool parameters
+pub fn transform_params(script: &str, params: HashMap<String, Value>) -> Result<HashMap<String, Value>, McpError> {
+    let failed = |e: String| McpError::internal_error(format!("pre_request transform failed: {}", e), None);
+    let mut scope = Scope::new();
+    scope.push_dynamic("params", to_dynamic(&params).map_err(|e| failed(e.to_string()))?);
+    let params = run(script, scope, "params").map_err(failed)?;
+    from_dynamic(&params).map_err(|e| failed(format!("`params` must remain a map: {}", e)))
+}
+
+/// Run a `post_response` script on a decoded JSON response
+pub fn transform_response(script: &str, response: Value, params: &HashMap<String, Value>) -> Result<Value, McpError> {
+    let failed = |e: String| McpError::internal_error(format!("post_response transform failed: {}", e), None);
+    let mut scope = Scope::new();
+    scope.push_dynamic("response", to_dynamic(&response).map_err(|e| failed(e.to_string()))?);
+    scope.push_constant_dynamic("params", to_dynamic(params).map_err(|e| failed(e.to_string()))?);
+    let response = run(script, scope, "response").map_err(failed)?;
+    from_dynamic(&response).map_err(|e| failed(e.to_string()))
+}
+
+/// Evaluate a script or expression with `scope` and return its value
+pub fn eval_with_scope(script: &str, scope: &mut Scope) -> Result<Dynamic, String> {
+    let ast = compile_script(script)?;
+    engine().eval_ast_with_scope::<Dynamic>(scope, &ast).map_err(|e| e.to_string())
+}
+
+/// Run `script` and return the final value of the scope variable `output`
+fn run(script: &str, mut scope: Scope, output: &str) -> Result<Dynamic, String> {
+    let ast = compile_script(script)?;
+    engine().run_ast_with_scope(&mut scope, &ast).map_err(|e| e.to_string())?;
+    scope.get_value::<Dynamic>(output).ok_or_else(|| format!("the script removed `{}`", output))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_pre_request_scripts_rename_and_derive_params() {
+        let params = HashMap::from([
+            ("customer".to_string(), json!("acme")),
+            ("price".to_string(), json!(2.5)),
+            ("quantity".to_string(), json!(4)),
+        ]);
+        let script = r#"
+            params.customer_id = params.customer;
+            params.remove("customer");
+            params.total = params.price * params.quantity;
+        "#;
+        let params = transform_params(script, params).unwrap();
+        assert_eq!(params.get("customer_id"), Some(&json!("acme")));
+        assert!(!params.contains_key("customer"));
+        assert_eq!(params.get("total"), Some(&json!(10.0)));
+
+        let error = transform_params("params = 42;", HashMap::new()).unwrap_err();
+        assert!(error.message.contains("must remain a map"), "{}", error.message);
+        assert!(transform_params("loop { }", HashMap::new()).is_err());
+        assert!(compile_script("params.a = ;").is_err());
+    }
+
+    #[test]
+    fn test_post_response_scripts_strip_secrets() {
+        let response = json!({ "users": [{ "name": "ada", "api_key": "s3cr3t" }, { "name": "bob", "api_key": "hunter2" }] });
+        let script = r#"
+            response.users = response.users.map(|user| { user.remove("api_key"); user });
+            response.requested_by = params.caller;
+        "#;
+        let params = HashMap::from([("caller".to_string(), json!("tests"))]);
+        let response = transform_response(script, response, &params).unwrap();
+        assert_eq!(response, json!({ "users": [{ "name": "ada" }, { "name": "bob" }], "requested_by": "tests" }));
+    }
+}
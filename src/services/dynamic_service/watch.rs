@@ -0,0 +1,184 @@
+//! Polling-based change subscriptions for MCP-ANY-REST
+//!
+//! For APIs without webhooks, modules may list `watch` entries: methods called every
+//! `interval_secs` whose results are compared with the previous poll. With a `key`, array results
+//! are compared entity by entity and report added, removed and changed entities; otherwise a
+//! different result is one change. The latest result and recent changes of each watched method are
+//! served as the `watch://<module>/<method>` MCP resources, and connected clients are sent
+//! `notifications/resources/updated` when changes are found. The first poll only takes the
+//! baseline and adds the resource to the list.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Mutex;
+
+/// URI prefix of the MCP resources serving watched methods
+pub const WATCH_RESOURCE_PREFIX: &str = "watch://";
+
+/// Changes kept per watched method, oldest dropped first
+pub const MAX_WATCH_CHANGES: usize = 100;
+
+/// Latest results and changes by module and method
+static WATCHES: Mutex<BTreeMap<(String, String), WatchState>> = Mutex::new(BTreeMap::new());
+
+/// How an entity changed between two polls
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// A change found by polling
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WatchChange {
+    pub kind: ChangeKind,
+    /// Value of the `key` field of the entity; `None` when whole results are compared
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// The entity as last seen (the whole result when there is no `key`)
+    pub entity: Value,
+    pub detected_at: DateTime<Utc>,
+}
+
+/// Latest result and recent changes of a watched method
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchState {
+    pub module: String,
+    pub method: String,
+    pub polled_at: DateTime<Utc>,
+    pub result: Value,
+    /// Newest last
+    pub changes: VecDeque<WatchChange>,
+}
+
+/// URI of the resource serving the watched `module::method`
+pub fn watch_resource_uri(module: &str, method: &str) -> String {
+    format!("{}{}/{}", WATCH_RESOURCE_PREFIX, module, method)
+}
+
+/// Record the result of a poll and return the changes since the previous one
+pub fn record_poll(module: &str, method: &str, result: Value, key: Option<&str>) -> Vec<WatchChange> {
+    let now = Utc::now();
+    let mut watches = WATCHES.lock().unwrap();
+    let state = match watches.entry((module.to_string(), method.to_string())) {
+        Entry::Occupied(entry) => entry.into_mut(),
+        Entry::Vacant(entry) => {
+            entry.insert(WatchState {
+                module: module.to_string(),
+                method: method.to_string(),
+                polled_at: now,
+                result,
+                changes: VecDeque::new(),
+            });
+            return Vec::new();
+        }
+    };
+
+    let changes: Vec<WatchChange> = diff_results(&state.result, &result, key)
+        .into_iter()
+        .map(|(kind, key, entity)| WatchChange { kind, key, entity, detected_at: now })
+        .collect();
+    state.polled_at = now;
+    state.result = result;
+    state.changes.extend(changes.iter().cloned());
+    while state.changes.len() > MAX_WATCH_CHANGES {
+        state.changes.pop_front();
+    }
+    changes
+}
+
+/// Watched methods polled at least once
+pub fn watch_states() -> Vec<WatchState> {
+    WATCHES.lock().unwrap().values().cloned().collect()
+}
+
+/// State served by a `watch://<module>/<method>` resource; `None` for other URIs and methods not
+/// polled yet
+pub fn watch_state(uri: &str) -> Option<WatchState> {
+    let (module, method) = uri.strip_prefix(WATCH_RESOURCE_PREFIX)?.split_once('/')?;
+    WATCHES.lock().unwrap().get(&(module.to_string(), method.to_string())).cloned()
+}
+
+/// Changes between two results: per entity of array results when `key` is set and both results
+/// are arrays, otherwise the whole result
+fn diff_results(previous: &Value, current: &Value, key: Option<&str>) -> Vec<(ChangeKind, Option<String>, Value)> {
+    let (Some(key), Value::Array(previous_items), Value::Array(current_items)) = (key, previous, current) else {
+        return if previous == current {
+            Vec::new()
+        } else {
+            vec![(ChangeKind::Changed, None, current.clone())]
+        };
+    };
+
+    let by_key = |items: &[Value]| -> BTreeMap<String, Value> {
+        items
+            .iter()
+            .filter_map(|item| Some((entity_key(item.get(key)?), item.clone())))
+            .collect()
+    };
+    let (previous, current) = (by_key(previous_items), by_key(current_items));
+
+    let mut changes = Vec::new();
+    for (id, entity) in &current {
+        match previous.get(id) {
+            None => changes.push((ChangeKind::Added, Some(id.clone()), entity.clone())),
+            Some(before) if before != entity => changes.push((ChangeKind::Changed, Some(id.clone()), entity.clone())),
+            Some(_) => {}
+        }
+    }
+    for (id, entity) in &previous {
+        if !current.contains_key(id) {
+            changes.push((ChangeKind::Removed, Some(id.clone()), entity.clone()));
+        }
+    }
+    changes
+}
+
+/// Key of an entity as text, so `7` and `"7"` both read as `7`
+fn entity_key(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_polls_report_added_removed_and_changed_entities() {
+        let first = json!([{ "id": 1, "status": "open" }, { "id": 2, "status": "open" }]);
+        assert!(record_poll("watched_issues", "list_issues", first, Some("id")).is_empty());
+
+        let second = json!([{ "id": 1, "status": "closed" }, { "id": 3, "status": "open" }]);
+        let changes = record_poll("watched_issues", "list_issues", second.clone(), Some("id"));
+        let summary: Vec<(ChangeKind, Option<&str>)> = changes.iter().map(|c| (c.kind, c.key.as_deref())).collect();
+        assert_eq!(
+            summary,
+            vec![
+                (ChangeKind::Changed, Some("1")),
+                (ChangeKind::Added, Some("3")),
+                (ChangeKind::Removed, Some("2")),
+            ]
+        );
+        assert!(record_poll("watched_issues", "list_issues", second, Some("id")).is_empty());
+
+        let state = watch_state(&watch_resource_uri("watched_issues", "list_issues")).unwrap();
+        assert_eq!(state.changes.len(), 3);
+        assert_eq!(state.result[0]["status"], "closed");
+        assert!(watch_state("watch://watched_issues/get_issue").is_none());
+
+        // Without a key the result is compared as a whole
+        record_poll("watched_issues", "get_stats", json!({ "open": 4 }), None);
+        let changes = record_poll("watched_issues", "get_stats", json!({ "open": 5 }), None);
+        assert_eq!(changes.len(), 1);
+        assert_eq!((changes[0].kind, changes[0].entity["open"].clone()), (ChangeKind::Changed, json!(5)));
+    }
+}
@@ -0,0 +1,144 @@
+//! Webhook events for MCP-ANY-REST
+//!
+//! With `webhooks` configured, the web server accepts callbacks of the API on
+//! `POST /webhooks/:module/:event`, optionally signed with an HMAC-SHA256 of the body. The most
+//! recent events of each module and event name are kept in memory and served as the
+//! `webhook://<module>/<event>` MCP resources, newest first, and connected clients are sent
+//! `notifications/resources/list_changed` whenever an event arrives, so agents can react to
+//! changes made outside of their tool calls.
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::Sha256;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Mutex;
+
+/// URI prefix of the MCP resources serving webhook events
+pub const WEBHOOK_RESOURCE_PREFIX: &str = "webhook://";
+
+/// Recent events by module and event name, newest last
+static EVENTS: Mutex<BTreeMap<(String, String), VecDeque<WebhookEvent>>> = Mutex::new(BTreeMap::new());
+
+/// A callback received from the API
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookEvent {
+    pub module: String,
+    pub event: String,
+    pub received_at: DateTime<Utc>,
+    /// Body of the callback; bodies that are not JSON are kept as text
+    pub payload: Value,
+}
+
+/// Events received for one module and event name
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WebhookTopic {
+    pub uri: String,
+    pub module: String,
+    pub event: String,
+    pub events: usize,
+    pub last_received: DateTime<Utc>,
+}
+
+/// URI of the resource serving the events of `module` named `event`
+pub fn webhook_resource_uri(module: &str, event: &str) -> String {
+    format!("{}{}/{}", WEBHOOK_RESOURCE_PREFIX, module, event)
+}
+
+/// Keep an event, dropping the oldest ones of its module and name beyond `max_events`
+pub fn record_event(module: &str, event: &str, payload: Value, max_events: usize) -> WebhookEvent {
+    let received = WebhookEvent {
+        module: module.to_string(),
+        event: event.to_string(),
+        received_at: Utc::now(),
+        payload,
+    };
+    let mut events = EVENTS.lock().unwrap();
+    let recent = events.entry((module.to_string(), event.to_string())).or_default();
+    recent.push_back(received.clone());
+    while recent.len() > max_events.max(1) {
+        recent.pop_front();
+    }
+    received
+}
+
+/// Module and event names with received events
+pub fn webhook_topics() -> Vec<WebhookTopic> {
+    EVENTS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|((module, event), recent)| {
+            Some(WebhookTopic {
+                uri: webhook_resource_uri(module, event),
+                module: module.clone(),
+                event: event.clone(),
+                events: recent.len(),
+                last_received: recent.back()?.received_at,
+            })
+        })
+        .collect()
+}
+
+/// Events served by a `webhook://<module>/<event>` resource, newest first; `None` for other URIs
+/// and events never received
+pub fn webhook_events(uri: &str) -> Option<Vec<WebhookEvent>> {
+    let (module, event) = uri.strip_prefix(WEBHOOK_RESOURCE_PREFIX)?.split_once('/')?;
+    let events = EVENTS.lock().unwrap();
+    let recent = events.get(&(module.to_string(), event.to_string()))?;
+    Some(recent.iter().rev().cloned().collect())
+}
+
+/// Whether `signature` (`sha256=<hex>`, the prefix being optional) is the HMAC-SHA256 of `body` with `secret`
+pub fn verify_signature(secret: &str, body: &[u8], signature: &str) -> bool {
+    let signature = signature.trim();
+    let Some(expected) = decode_hex(signature.strip_prefix("sha256=").unwrap_or(signature)) else {
+        return false;
+    };
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    mac.verify_slice(&expected).is_ok()
+}
+
+fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    if text.len() % 2 != 0 {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(text.get(index..index + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_recent_events_are_served_newest_first() {
+        for id in 1..=3 {
+            record_event("hooked_orders", "order.created", json!({ "id": id }), 2);
+        }
+        record_event("hooked_orders", "order.deleted", Value::String("plain text".to_string()), 2);
+
+        let uri = webhook_resource_uri("hooked_orders", "order.created");
+        assert_eq!(uri, "webhook://hooked_orders/order.created");
+        let events = webhook_events(&uri).unwrap();
+        assert_eq!(events.iter().map(|e| e.payload["id"].clone()).collect::<Vec<_>>(), vec![json!(3), json!(2)]);
+        assert!(webhook_events("webhook://hooked_orders/order.updated").is_none());
+        assert!(webhook_events("schema://drift").is_none());
+
+        let topics: Vec<WebhookTopic> = webhook_topics().into_iter().filter(|t| t.module == "hooked_orders").collect();
+        assert_eq!(topics.len(), 2);
+        assert_eq!(topics[0].events, 2);
+
+        // HMAC-SHA256 of "hello" with the key "secret"
+        let signature = "sha256=88aab3ede8d3adf94d26ab90d3bafd4a2083070c3bcce9c014ee04a443847c0b";
+        assert!(verify_signature("secret", b"hello", signature));
+        assert!(!verify_signature("secret", b"hello!", signature));
+        assert!(!verify_signature("other", b"hello", signature));
+        assert!(!verify_signature("secret", b"hello", "sha256=not-hex"));
+    }
+}
@@ -0,0 +1,238 @@
+//! Composite workflow tools for MCP-ANY-REST
+//!
+//! A `workflow name { ... }` in ZML becomes one tool calling methods of the module in turn, e.g.
+//! creating a project and then adding its default members. Step arguments are literals or
+//! references to the tool parameters (`params.name`) and to the outputs of earlier steps
+//! (`project.id`); a `when` condition skips a step unless its reference is truthy or compares as
+//! declared. Each step is a regular call subject to the rate limits and transform scripts, and a
+//! failing step stops the workflow with an error listing the steps already completed.
+
+use crate::zml::ast::{Value as ZmlValue, Workflow, WorkflowComparison, WorkflowCondition, WorkflowValue};
+use rmcp::ErrorData as McpError;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::future::Future;
+
+/// Run the steps of `workflow` with `call_method(method, params)` and return the `result`
+/// reference, or an object of every step output when the workflow declares none
+pub async fn run_workflow<F, Fut>(
+    workflow: &Workflow,
+    params: HashMap<String, Value>,
+    mut call_method: F,
+) -> Result<Value, McpError>
+where
+    F: FnMut(String, HashMap<String, Value>) -> Fut,
+    Fut: Future<Output = Result<Value, McpError>>,
+{
+    let mut context = Map::new();
+    context.insert("params".to_string(), Value::Object(params.into_iter().collect()));
+    let mut completed = Vec::new();
+
+    for step in &workflow.steps {
+        if let Some(condition) = &step.when {
+            if !holds(condition, &context) {
+                context.insert(step.name.clone(), Value::Null);
+                continue;
+            }
+        }
+        // Missing references leave optional parameters out rather than sending null
+        let args: HashMap<String, Value> = step
+            .args
+            .iter()
+            .map(|(name, value)| (name.clone(), resolve(value, &context)))
+            .filter(|(_, value)| !value.is_null())
+            .collect();
+        let output = call_method(step.call.clone(), args).await.map_err(|e| {
+            McpError::new(
+                e.code,
+                format!("Workflow step '{}' ({}) failed: {}", step.name, step.call, e.message),
+                Some(serde_json::json!({ "step": step.name, "completed": completed, "error": e.data })),
+            )
+        })?;
+        context.insert(step.name.clone(), output);
+        completed.push(step.name.clone());
+    }
+
+    Ok(match &workflow.result {
+        Some(reference) => lookup(&context, reference).cloned().unwrap_or(Value::Null),
+        None => {
+            context.remove("params");
+            Value::Object(context)
+        }
+    })
+}
+
+/// Whether a step condition holds
+fn holds(condition: &WorkflowCondition, context: &Map<String, Value>) -> bool {
+    let value = lookup(context, &condition.reference).unwrap_or(&Value::Null);
+    match &condition.comparison {
+        None => truthy(value),
+        Some((comparison, operand)) => {
+            let equal = equals(value, &resolve(operand, context));
+            match comparison {
+                WorkflowComparison::Equal => equal,
+                WorkflowComparison::NotEqual => !equal,
+            }
+        }
+    }
+}
+
+/// Equality with numbers compared by value, so `1` equals `1.0`
+fn equals(left: &Value, right: &Value) -> bool {
+    match (left.as_f64(), right.as_f64()) {
+        (Some(left), Some(right)) => left == right,
+        _ => left == right,
+    }
+}
+
+/// `null`, `false`, `0`, `""` and empty arrays and objects are false
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(flag) => *flag,
+        Value::Number(number) => number.as_f64() != Some(0.0),
+        Value::String(text) => !text.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(fields) => !fields.is_empty(),
+    }
+}
+
+fn resolve(value: &WorkflowValue, context: &Map<String, Value>) -> Value {
+    match value {
+        WorkflowValue::Ref(reference) => lookup(context, reference).cloned().unwrap_or(Value::Null),
+        WorkflowValue::Literal(literal) => zml_value_to_json(literal),
+    }
+}
+
+/// Value at a dotted reference: object keys, or indexes into arrays
+fn lookup<'a>(context: &'a Map<String, Value>, reference: &str) -> Option<&'a Value> {
+    let mut segments = reference.split('.');
+    let mut value = context.get(segments.next()?)?;
+    for segment in segments {
+        value = match value {
+            Value::Object(fields) => fields.get(segment)?,
+            Value::Array(items) => items.get(segment.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+    Some(value)
+}
+
+fn zml_value_to_json(value: &ZmlValue) -> Value {
+    match value {
+        ZmlValue::String(text) => Value::String(text.clone()),
+        ZmlValue::Integer(integer) => Value::from(*integer),
+        ZmlValue::Number(number) => Value::from(*number),
+        ZmlValue::Boolean(flag) => Value::Bool(*flag),
+        ZmlValue::Array(items) => Value::Array(items.iter().map(zml_value_to_json).collect()),
+        ZmlValue::Object(fields) => Value::Object(fields.iter().map(|(k, v)| (k.clone(), zml_value_to_json(v))).collect()),
+        ZmlValue::Null => Value::Null,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::zml::ZMLParserWrapper;
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    const PROJECT_MODULE: &str = r#"
+module Projects {
+    method create_project {
+        http_method: POST
+        uri: "/projects"
+        params {
+            name: string
+        }
+        response: any
+    }
+
+    method add_member {
+        http_method: POST
+        uri: "/projects/{project_id}/members"
+        params {
+            project_id: integer
+            user: string
+            role: string?
+        }
+        response: any
+    }
+
+    // Create a project and add its default members
+    workflow create_team {
+        params {
+            name: string
+            lead: string
+            notify: boolean?
+        }
+        step project {
+            call: create_project
+            with { name: params.name }
+        }
+        step lead {
+            call: add_member
+            when: project.status == "active"
+            with { project_id: project.id, user: params.lead, role: "owner" }
+        }
+        step announce {
+            call: add_member
+            when: params.notify
+            with { project_id: project.id, user: "announcements" }
+        }
+    }
+}
+"#;
+
+    #[tokio::test]
+    async fn test_steps_chain_outputs_and_skip_on_conditions() {
+        let module = ZMLParserWrapper::new().parse(PROJECT_MODULE).unwrap();
+        let method = &module.methods["create_team"];
+        assert_eq!(method.description.as_deref(), Some("Create a project and add its default members"));
+        let workflow = method.workflow.as_ref().unwrap();
+
+        let calls = Mutex::new(Vec::new());
+        let params = HashMap::from([("name".to_string(), json!("Apollo")), ("lead".to_string(), json!("ada"))]);
+        let output = run_workflow(workflow, params, |method, args| {
+            calls.lock().unwrap().push((method.clone(), args.clone()));
+            async move {
+                Ok(match method.as_str() {
+                    "create_project" => json!({ "id": 7, "name": args["name"], "status": "active" }),
+                    _ => json!({ "user": args["user"], "role": args.get("role") }),
+                })
+            }
+        })
+        .await
+        .unwrap();
+
+        let calls = calls.into_inner().unwrap();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[1].1["project_id"], json!(7));
+        assert_eq!(calls[1].1["role"], json!("owner"));
+        assert_eq!(output["project"]["name"], "Apollo");
+        assert_eq!(output["lead"], json!({ "user": "ada", "role": "owner" }));
+        // `notify` was not given
+        assert_eq!(output["announce"], Value::Null);
+    }
+
+    #[tokio::test]
+    async fn test_failed_steps_report_the_completed_steps() {
+        let module = ZMLParserWrapper::new().parse(PROJECT_MODULE).unwrap();
+        let workflow = module.methods["create_team"].workflow.as_ref().unwrap();
+        let params = HashMap::from([("name".to_string(), json!("Apollo")), ("lead".to_string(), json!("ada"))]);
+        let error = run_workflow(workflow, params, |method, _| async move {
+            match method.as_str() {
+                "create_project" => Ok(json!({ "id": 7, "status": "active" })),
+                _ => Err(McpError::invalid_params("Unknown user 'ada'", None)),
+            }
+        })
+        .await
+        .unwrap_err();
+        assert!(error.message.contains("step 'lead' (add_member) failed: Unknown user"), "{}", error.message);
+        assert_eq!(error.data.unwrap()["completed"], json!(["project"]));
+
+        let unknown_step = PROJECT_MODULE.replace("user: params.lead", "user: members.lead");
+        let error = ZMLParserWrapper::new().parse(&unknown_step).unwrap_err();
+        assert!(error.to_string().contains("references 'members.lead'"), "{}", error);
+    }
+}
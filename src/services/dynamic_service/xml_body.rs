@@ -0,0 +1,250 @@
+//! XML request bodies for MCP-ANY-REST
+//!
+//! Methods declaring `xml_body { ... }` in ZML, typically legacy SOAP services, send their
+//! parameters in an XML document rendered from a template instead of a JSON body. `{{param}}`
+//! placeholders take the XML-escaped parameter values; objects become child elements and arrays
+//! repeated elements. Placeholders naming no parameter are left to the configuration variables.
+//! The XML response is converted to JSON and narrowed to the `extract` XPath, and a SOAP fault
+//! becomes an error carrying its fault string.
+
+use super::response_content::xml_to_json;
+use crate::services::auth_service::{ApiResponse, RequestBody};
+use crate::zml::ast::{MethodDef, XmlBody};
+use regex::{Captures, Regex};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde_json::Value;
+use std::sync::OnceLock;
+
+/// Content type of XML bodies declaring none
+pub const DEFAULT_XML_CONTENT_TYPE: &str = "text/xml; charset=utf-8";
+
+fn placeholder() -> &'static Regex {
+    static PLACEHOLDER: OnceLock<Regex> = OnceLock::new();
+    PLACEHOLDER.get_or_init(|| Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_]*)\s*\}\}").unwrap())
+}
+
+/// Render the body template with the `params` of `method`; omitted parameters render empty
+pub fn render_xml_body(xml_body: &XmlBody, method: &MethodDef, params: &Value) -> String {
+    placeholder()
+        .replace_all(&xml_body.template, |caps: &Captures| {
+            if !method.params.contains_key(&caps[1]) {
+                return caps[0].to_string();
+            }
+            params.get(&caps[1]).map(xml_content).unwrap_or_default()
+        })
+        .into_owned()
+}
+
+/// Request body of a rendered XML document
+pub fn xml_request_body(xml_body: &XmlBody, document: String) -> RequestBody {
+    RequestBody::Raw {
+        content_type: xml_body.content_type.clone().unwrap_or_else(|| DEFAULT_XML_CONTENT_TYPE.to_string()),
+        body: document.into_bytes(),
+    }
+}
+
+/// Add the `SOAPAction` header of `xml_body`, quoted as SOAP 1.1 expects
+pub fn add_soap_action(xml_body: &XmlBody, headers: &mut HeaderMap) -> Result<(), String> {
+    if let Some(action) = &xml_body.soap_action {
+        let value = HeaderValue::from_str(&format!("\"{}\"", action.trim_matches('"')))
+            .map_err(|e| format!("Invalid SOAPAction '{}': {}", action, e))?;
+        headers.insert(HeaderName::from_static("soapaction"), value);
+    }
+    Ok(())
+}
+
+/// XML document of a JSON value in a `root` element, rendered like the template placeholders
+pub fn json_to_xml(root: &str, value: &Value) -> String {
+    format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>{}", element(root, value))
+}
+
+/// XML content of a parameter value
+fn xml_content(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => escape(text),
+        Value::Array(items) => items.iter().map(xml_content).collect(),
+        Value::Object(fields) => fields
+            .iter()
+            .map(|(name, child)| match child {
+                Value::Array(items) => items.iter().map(|item| element(name, item)).collect(),
+                child => element(name, child),
+            })
+            .collect(),
+        other => other.to_string(),
+    }
+}
+
+fn element(name: &str, value: &Value) -> String {
+    format!("<{}>{}</{}>", name, xml_content(value), name)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Fault string of a SOAP 1.1 or 1.2 fault response
+pub fn soap_fault(response: &ApiResponse) -> Option<String> {
+    let document = xml_to_json(&response.text()).ok()?;
+    let fault = extract_xpath(&document, "//Fault").ok()?;
+    ["faultstring", "Reason/Text", "Reason"]
+        .iter()
+        .find_map(|path| extract_xpath(&fault, &format!("/{}", path)).ok())
+        .map(|reason| match reason {
+            Value::String(text) => text,
+            Value::Object(fields) => fields.get("#text").and_then(Value::as_str).unwrap_or_default().to_string(),
+            other => other.to_string(),
+        })
+        .or_else(|| Some(fault.to_string()))
+}
+
+/// Part of an XML document converted by `xml_to_json` at an XPath: `/` separated steps, `//` for
+/// any depth, `*` for any element, `[n]` (from 1) to pick a repeated element and a final `@name`
+/// for an attribute. Namespace prefixes are ignored, so `Body` matches `soap:Body`. Several
+/// matches are returned as an array.
+pub fn extract_xpath(document: &Value, path: &str) -> Result<Value, String> {
+    let mut nodes = vec![document];
+    let mut rest = path;
+    while !rest.is_empty() {
+        let descendant = rest.starts_with("//");
+        rest = rest.strip_prefix("//").or_else(|| rest.strip_prefix('/')).ok_or_else(|| format!("XPath '{}' must start with '/'", path))?;
+        let end = rest.find('/').unwrap_or(rest.len());
+        let (step, next) = rest.split_at(end);
+        rest = next;
+        if step.is_empty() {
+            return Err(format!("empty step in XPath '{}'", path));
+        }
+
+        let (name, position) = match step.strip_suffix(']').and_then(|step| step.split_once('[')) {
+            Some((name, index)) => {
+                let index = index.parse::<usize>().ok().filter(|index| *index > 0);
+                (name, Some(index.ok_or_else(|| format!("invalid position in XPath step '{}'", step))?))
+            }
+            None => (step, None),
+        };
+        let candidates = if descendant { nodes.into_iter().flat_map(descendants).collect() } else { nodes };
+        let mut matched = Vec::new();
+        for node in candidates {
+            let Value::Object(fields) = node else {
+                continue;
+            };
+            for (key, child) in fields {
+                if !matches_step(key, name) {
+                    continue;
+                }
+                match (child, position) {
+                    (Value::Array(items), Some(position)) => matched.extend(items.get(position - 1)),
+                    (child, Some(1)) => matched.push(child),
+                    (_, Some(_)) => {}
+                    (Value::Array(items), None) if !name.starts_with('@') => matched.extend(items),
+                    (child, None) => matched.push(child),
+                }
+            }
+        }
+        nodes = matched;
+    }
+
+    match nodes.len() {
+        0 => Err(format!("XPath '{}' matched nothing", path)),
+        1 => Ok(nodes[0].clone()),
+        _ => Ok(Value::Array(nodes.into_iter().cloned().collect())),
+    }
+}
+
+/// `node` and every element below it
+fn descendants(node: &Value) -> Vec<&Value> {
+    match node {
+        // Repeated elements: each one is a node
+        Value::Array(items) => items.iter().flat_map(descendants).collect(),
+        Value::Object(fields) => {
+            let children = fields.iter().filter(|(key, _)| !key.starts_with('@') && *key != "#text");
+            std::iter::once(node).chain(children.flat_map(|(_, child)| descendants(child))).collect()
+        }
+        _ => vec![node],
+    }
+}
+
+/// Whether the key of a converted element or attribute matches an XPath step, prefixes ignored
+fn matches_step(key: &str, step: &str) -> bool {
+    let local = |name: &str| name.rsplit(':').next().unwrap_or(name).to_string();
+    match (key.strip_prefix('@'), step.strip_prefix('@')) {
+        (Some(attribute), Some(wanted)) => local(attribute) == local(wanted),
+        (None, None) => key != "#text" && (step == "*" || local(key) == local(step)),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::zml::ZMLParserWrapper;
+    use reqwest::StatusCode;
+    use serde_json::json;
+
+    const SOAP_MODULE: &str = r#"
+module Quotes {
+    method getQuote {
+        http_method: POST
+        uri: "/QuoteService.asmx"
+        xml_body {
+            template: "<soap:Envelope xmlns:soap='http://schemas.xmlsoap.org/soap/envelope/'><soap:Body><GetQuote><Symbol>{{symbol}}</Symbol><Tenant>{{tenant}}</Tenant>{{legs}}</GetQuote></soap:Body></soap:Envelope>"
+            soap_action: "http://example.com/GetQuote"
+            extract: "/Envelope/Body/GetQuoteResponse/Quote"
+        }
+        params {
+            symbol: string
+            legs: any?
+        }
+        response: any
+    }
+}
+"#;
+
+    #[test]
+    fn test_envelope_is_rendered_from_the_template() {
+        let module = ZMLParserWrapper::new().parse(SOAP_MODULE).unwrap();
+        let method = &module.methods["getQuote"];
+        let xml_body = method.xml_body.as_ref().unwrap();
+
+        let document = render_xml_body(xml_body, method, &json!({ "symbol": "AT&T", "legs": { "leg": [1, 2] } }));
+        assert!(document.contains("<Symbol>AT&amp;T</Symbol>"), "{}", document);
+        assert!(document.contains("<leg>1</leg><leg>2</leg>"), "{}", document);
+        // Not a parameter: left for the configuration variables
+        assert!(document.contains("<Tenant>{{tenant}}</Tenant>"), "{}", document);
+
+        let mut headers = HeaderMap::new();
+        add_soap_action(xml_body, &mut headers).unwrap();
+        assert_eq!(headers["soapaction"], "\"http://example.com/GetQuote\"");
+        let RequestBody::Raw { content_type, .. } = xml_request_body(xml_body, document) else {
+            panic!("expected a raw body");
+        };
+        assert_eq!(content_type, DEFAULT_XML_CONTENT_TYPE);
+    }
+
+    #[test]
+    fn test_xpath_extracts_from_soap_responses() {
+        let document = xml_to_json(
+            "<s:Envelope xmlns:s='urn:soap'><s:Body><GetQuoteResponse>\
+             <Quote currency='USD'><Price>12.5</Price></Quote><Quote currency='EUR'><Price>11.0</Price></Quote>\
+             </GetQuoteResponse></s:Body></s:Envelope>",
+        )
+        .unwrap();
+        assert_eq!(extract_xpath(&document, "/Envelope/Body/GetQuoteResponse/Quote[2]/@currency").unwrap(), "EUR");
+        assert_eq!(extract_xpath(&document, "//Price").unwrap(), json!(["12.5", "11.0"]));
+        assert_eq!(extract_xpath(&document, "/Envelope/*/GetQuoteResponse/Quote[1]/Price").unwrap(), "12.5");
+        assert!(extract_xpath(&document, "/Envelope/Header").is_err());
+
+        let fault = ApiResponse {
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+            headers: HeaderMap::new(),
+            body: b"<soap:Envelope xmlns:soap='urn:soap'><soap:Body><soap:Fault><faultcode>soap:Client</faultcode>\
+                    <faultstring>Unknown symbol</faultstring></soap:Fault></soap:Body></soap:Envelope>"
+                .to_vec(),
+        };
+        assert_eq!(soap_fault(&fault).as_deref(), Some("Unknown symbol"));
+    }
+}
@@ -1,29 +1,72 @@
 //! ZML-based dynamic service for MCP-ANY-REST
 
+use crate::config::config::Config;
 use crate::config::dynamic::DynamicConfigManager;
+use crate::config::effective::{resolve_method, resolve_module};
+use crate::config::module::ResponseValidation;
+use crate::config::module_env::ModuleEnv;
+use crate::config::variables::render;
 use crate::config::zml_loader::ZmlModuleLoader;
-use crate::services::auth_service::UnifiedAuthService;
-use crate::services::composer_service::module_registry::DynamicModule;
+use crate::services::auth_service::{BodyCompression, BodySink, RequestBody, RequestPolicy, TokenScope, UnifiedAuthService};
+use crate::services::composer_service::module_registry::{impersonation_header, DynamicModule};
 
-use crate::services::dynamic_service::api_request_builder::build_api_request_zml;
+use crate::services::dynamic_service::api_request_builder::{
+    add_idempotency_key, apply_retry_config, build_api_request_zml, build_multipart_fields_zml,
+    build_request_headers, build_request_policy_zml, resource_link_uri,
+};
+use crate::services::dynamic_service::computed::apply_computed_fields;
+use crate::services::dynamic_service::content_negotiation::{add_accept_header, encode_request_body};
+use crate::services::dynamic_service::custom_headers::{
+    allowed_headers, headers_param_schema, take_custom_headers, HEADERS_PARAM,
+};
+use crate::services::dynamic_service::dry_run::{describe_request, supports_dry_run, take_dry_run, DRY_RUN_PARAM};
+use crate::services::dynamic_service::middleware::{ApiRequest, MiddlewareChain};
+use crate::services::dynamic_service::mock::find_mock;
+use crate::services::dynamic_service::pagination::{fetch_all_pages, max_pages};
+use crate::services::dynamic_service::response_filter::{filter_params, ResponseFilter};
+use crate::services::dynamic_service::response_limits::limit_response;
+use crate::services::dynamic_service::transform::{transform_params, transform_response};
+use crate::services::dynamic_service::workflow::run_workflow;
+use crate::services::dynamic_service::xml_body::{
+    add_soap_action, extract_xpath, render_xml_body, soap_fault, xml_request_body,
+};
+use crate::services::dynamic_service::prompt_builder::{build_prompt_zml, render_prompt_zml};
+use crate::services::dynamic_service::provenance::{record, with_provenance};
+use crate::services::dynamic_service::rate_limiter::{acquire, tool_limits};
+use crate::services::dynamic_service::resource_builder::{
+    build_resource_template_zml, build_resource_zml, expand_resource_uri_zml, match_resource_zml,
+};
+use crate::services::dynamic_service::response_content::{
+    decode_response_payload, ResponsePayload, DEFAULT_MAX_BINARY_RESPONSE_BYTES,
+};
+use crate::services::dynamic_service::response_validator::validate_response_zml;
+use crate::services::dynamic_service::scalar_codec::decode_response_zml;
 use crate::services::dynamic_service::schema_builder::{build_input_schema_zml, build_output_schema_zml};
-use crate::zml::ast::{MethodDef, Module};
+use crate::services::dynamic_service::streaming::{
+    produces_ndjson, stream_body, supports_stream, take_stream, ChunkStream, STREAM_BUFFER_CHUNKS, STREAM_PARAM,
+};
+use crate::zml::ast::{MethodDef, Module, TypeExpr, Workflow, FETCH_ALL_PARAM};
 
-use log::info;
+use log::{info, warn};
+use reqwest::header::HeaderMap;
 use rmcp::{
     handler::server::wrapper::Parameters, model::*, service::RequestContext, ErrorData as McpError,
-    Json, RoleServer,
+    RoleServer,
 };
 use serde_json::Value;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::future::Future;
+use std::path::Path;
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 /// ZML dynamic service that reads methods from ZML AST modules
 #[derive(Clone)]
 pub struct ZmlDynamicService {
     module_name: String,
+    /// Prefix of the aggregated tool names: the module's alias or its name
+    tool_prefix: String,
     module: Arc<Module>,
     loader: Arc<ZmlModuleLoader>,
     config: Arc<DynamicConfigManager>,
@@ -40,6 +83,7 @@ impl ZmlDynamicService {
         info!("Creating ZML dynamic service for: {}", module.name);
         Self {
             module_name: module.name.clone(),
+            tool_prefix: module.tool_prefix().to_string(),
             module,
             loader,
             config,
@@ -47,15 +91,22 @@ impl ZmlDynamicService {
         }
     }
 
-    /// Generate dynamic tool method from ZML method definition
+    /// Generate dynamic tool method from ZML method definition; `call_headers` are the caller's
+    /// `_headers` followed by the impersonation header of the calling client, `dry_run` returns
+    /// the request instead of sending it, `stream` takes the items of the response as it arrives,
+    /// and response validation warnings are added to `warnings`
     fn generate_dynamic_tool_method(
         &self,
         method_name: String,
         method_def: MethodDef,
+        call_headers: Vec<(String, String)>,
+        dry_run: bool,
+        stream: Option<ChunkStream>,
+        warnings: Arc<Mutex<Vec<String>>>,
     ) -> impl Fn(
         &Self,
         Parameters<HashMap<String, Value>>, 
-    ) -> Pin<Box<dyn Future<Output = Result<Json<Value>, McpError>> + Send + '_>> + '_ {
+    ) -> Pin<Box<dyn Future<Output = Result<ResponsePayload, McpError>> + Send + '_>> + '_ {
         let module = self.module.clone();
         let loader = self.loader.clone();
         let auth_service = self.auth_service.clone();
@@ -63,13 +114,16 @@ impl ZmlDynamicService {
         let method_name_owned = method_name.clone();
         let method_def_owned = method_def.clone();
 
-        move |_self, params: Parameters<HashMap<String, Value>>| {
+        move |service, params: Parameters<HashMap<String, Value>>| {
             let module = module.clone();
-            let _loader = loader.clone();
+            let loader = loader.clone();
             let auth_service = auth_service.clone();
             let config = config.clone();
             let method_def = method_def_owned.clone();
             let method_name = method_name_owned.clone();
+            let call_headers = call_headers.clone();
+            let stream = stream.clone();
+            let warnings = warnings.clone();
 
             Box::pin(async move {
                 info!(
@@ -77,12 +131,38 @@ impl ZmlDynamicService {
                     module.name, method_name, params.0
                 );
 
+                // Calls over the module's or method's rate limit wait or fail before reaching the API
+                let config_data = config.get_config();
+                if !dry_run {
+                    let limits = tool_limits(&config_data, &module.name, &method_name, method_def.rate_limit.as_ref());
+                    acquire(&limits, config_data.module_config.rate_limit_action).await?;
+                }
+
                 // Validate and normalize parameters against ZML
                 // let normalized = validate_parameters_zml(&params.0, &module, &method_def, Some(&loader))?;
 
+                // Timeout, retries, headers, token scopes and scripts cascade from the global, module and method configuration
+                let mut settings = resolve_method(&config_data, &module.name, &method_name, method_def.timeout_ms);
+                // Configured scripts may rename, derive or strip parameters before the request is built
+                let params = match &settings.transform.pre_request {
+                    Some(script) => transform_params(script, params.0)?,
+                    None => params.0,
+                };
+                // In mock mode the configured mock responses stand in for an API that may not exist yet
+                if settings.mock {
+                    let mock = find_mock(&settings.mocks, &params).ok_or_else(|| {
+                        McpError::internal_error(
+                            format!("Mock mode: no mock response of {}::{} matches the parameters", module.name, method_name),
+                            None,
+                        )
+                    })?;
+                    info!("Serving a mock response for {}::{}", module.name, method_name);
+                    return Ok(ResponsePayload::Json(mock.response.clone()));
+                }
+
                 // Build API request
                 let (endpoint, http_method, request_body) = 
-                    build_api_request_zml(&params.0, &module, &method_def).map_err(|e| {
+                    build_api_request_zml(&params, &module, &method_def).map_err(|e| {
                         McpError::internal_error(format!("Failed to build API request: {}", e), None)
                     })?;
                 
@@ -97,20 +177,269 @@ impl ZmlDynamicService {
                     _ => crate::services::auth_service::auth_strategy::HttpMethod::GET, // Default to GET
                 };
                 
-                let config_data = config.get_config();
-                let full_url = format!("{}/{}", config_data.api.base_url, endpoint);
-                let response_json: Value = auth_service
-                    .make_authenticated_request(auth_http_method, &full_url, None, request_body)
-                    .await
-                    .map_err(|e| McpError::internal_error(format!("API request failed: {}", e), None))?;
+                let base_url = backend_base_url(&config_data, &module)?;
+                let endpoint = render_variables(&config_data, &endpoint)?;
+                let full_url = format!("{}/{}", base_url, endpoint);
 
-                // Validate response against ZML method response type
-                // validate_response_zml(&response_json, &method_def, &module, Some(&loader))?;
+                settings.headers.extend(call_headers);
+                let mut policy = build_request_policy_zml(
+                    &method_def,
+                    settings.retry.as_ref(),
+                    config_data.backend_retry(module.backend.as_deref()),
+                );
+                policy.timeout = settings.timeout_ms.map(Duration::from_millis);
+                policy.token_scope = TokenScope::new(settings.scopes, settings.audience);
+                policy.cache_ttl = settings.cache_ttl_ms.or(method_def.cache_ttl_ms).map(Duration::from_millis);
+                let compression = config_data.backend_compression(module.backend.as_deref());
+                policy.compression = compression
+                    .request
+                    .map(|encoding| BodyCompression { encoding, min_bytes: compression.min_request_bytes });
+                let headers = build_request_headers(&settings.headers)
+                    .map_err(|e| McpError::internal_error(format!("Failed to build request headers: {}", e), None))?;
+                let body = if let Some(xml_body) = &method_def.xml_body {
+                    // SOAP and other XML services take the parameters in a document rendered from the template
+                    let document = render_xml_body(xml_body, &method_def, request_body.as_ref().unwrap_or(&Value::Null));
+                    xml_request_body(xml_body, render_variables(&config_data, &document)?)
+                } else if method_def.has_file_params() {
+                    // Upload endpoints are sent as multipart/form-data; files given as links to the
+                    // module's resources are read first and uploaded as their contents
+                    let mut request_body = request_body.unwrap_or(Value::Null);
+                    if let Value::Object(fields) = &mut request_body {
+                        for (name, value) in fields.iter_mut() {
+                            let is_file = method_def.params.get(name).is_some_and(|p| matches!(p.type_expr, TypeExpr::File));
+                            if let Some(uri) = resource_link_uri(value).filter(|_| is_file).map(String::from) {
+                                let text = service.fetch_resource(&uri, headers.clone()).await?;
+                                *value = serde_json::json!({ "uri": uri, "mimeType": "application/json", "text": text });
+                            }
+                        }
+                    }
+                    let upload_root = config_data.api.upload_root.as_deref().map(Path::new);
+                    let fields = build_multipart_fields_zml(&request_body, &method_def, upload_root)
+                        .map_err(|e| McpError::invalid_params(format!("Failed to build multipart body: {}", e), None))?;
+                    RequestBody::Multipart(fields)
+                } else {
+                    encode_request_body(&method_def, request_body)
+                };
+                let mut headers = headers.unwrap_or_default();
+                add_accept_header(&method_def, &mut headers).map_err(|e| McpError::internal_error(e, None))?;
+                add_idempotency_key(&method_def, &mut headers)
+                    .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+                if let Some(xml_body) = &method_def.xml_body {
+                    add_soap_action(xml_body, &mut headers).map_err(|e| McpError::internal_error(e, None))?;
+                }
+                let mut request = ApiRequest {
+                    module: module.name.clone(),
+                    operation: method_name.clone(),
+                    method: auth_http_method,
+                    url: full_url,
+                    headers,
+                    body,
+                    policy,
+                };
+                if dry_run {
+                    info!("Dry run of {}::{}: {} {}", module.name, method_name, request.method, request.url);
+                    return Ok(ResponsePayload::Json(describe_request(&request, auth_service.get_auth_mode().await)));
+                }
+                let url = request.url.clone();
+                // Errors keep their code, so clients can tell timeouts and rate limits apart
+                let response = match stream.filter(|_| settings.transform.post_response.is_none()) {
+                    // Array and NDJSON bodies are parsed and sent in chunks while they arrive;
+                    // other bodies are read whole and returned as usual
+                    Some(stream) => {
+                        let (sender, receiver) = tokio::sync::mpsc::channel(STREAM_BUFFER_CHUNKS);
+                        request.policy.body_sink = Some(BodySink { sender, ndjson: produces_ndjson(&method_def) });
+                        let (response, summary) = tokio::join!(
+                            MiddlewareChain::standard(auth_service).send(request),
+                            stream_body(receiver, &stream, |items| {
+                                let chunk = Value::Array(items);
+                                check_response(settings.validation, &chunk, &method_def, &module, Some(loader.as_ref()), &warnings)?;
+                                let chunk = decode_response_zml(&chunk, &method_def, &module);
+                                match apply_computed_fields(&method_def.computed, chunk, &params) {
+                                    Value::Array(items) => Ok(items),
+                                    item => Ok(vec![item]),
+                                }
+                            })
+                        );
+                        if let Some(summary) = summary? {
+                            response?;
+                            return Ok(ResponsePayload::Json(summary));
+                        }
+                        response?
+                    }
+                    None => MiddlewareChain::standard(auth_service).send(request).await?,
+                };
+                if method_def.xml_body.is_some() && !response.status.is_success() {
+                    if let Some(fault) = soap_fault(&response) {
+                        return Err(McpError::internal_error(
+                            format!("SOAP fault from {} (status {}): {}", url, response.status, fault),
+                            Some(serde_json::json!({ "status": response.status.as_u16(), "fault": fault })),
+                        ));
+                    }
+                }
+                let max_binary_bytes = config_data.api.max_binary_response_bytes.unwrap_or(DEFAULT_MAX_BINARY_RESPONSE_BYTES);
+                let payload = decode_response_payload(auth_http_method, &url, response, max_binary_bytes, method_def.produces.as_deref())?;
 
-                Ok(Json(response_json))
+                // Apply custom scalar decode rules to JSON (and XML) responses
+                Ok(match payload {
+                    ResponsePayload::Json(response_json) => {
+                        // XML bodies narrow their (converted) XML response to the `extract` XPath
+                        let response_json = match method_def.xml_body.as_ref().and_then(|xml_body| xml_body.extract.as_deref()) {
+                            Some(path) => extract_xpath(&response_json, path).map_err(|e| {
+                                McpError::internal_error(format!("Failed to extract the response of {}: {}", url, e), None)
+                            })?,
+                            None => response_json,
+                        };
+                        // Responses not matching the declared type are reported as schema drift,
+                        // and fail the call or come with a warning depending on `validation`
+                        check_response(settings.validation, &response_json, &method_def, &module, Some(loader.as_ref()), &warnings)?;
+                        let response_json = decode_response_zml(&response_json, &method_def, &module);
+                        let response_json = apply_computed_fields(&method_def.computed, response_json, &params);
+                        match &settings.transform.post_response {
+                            Some(script) => ResponsePayload::Json(transform_response(script, response_json, &params)?),
+                            None => ResponsePayload::Json(response_json),
+                        }
+                    }
+                    payload => payload,
+                })
             })
         }
     }
+
+    /// Run a workflow tool; each step calls its method as a regular tool call
+    async fn run_workflow_tool(
+        &self,
+        workflow: &Workflow,
+        params: HashMap<String, Value>,
+        on_behalf_of: Option<(String, String)>,
+        dry_run: bool,
+        warnings: Arc<Mutex<Vec<String>>>,
+    ) -> Result<Value, McpError> {
+        let config = self.config.get_config();
+        run_workflow(workflow, params, |method_name, step_params| {
+            let (config, on_behalf_of, warnings) = (&config, on_behalf_of.clone(), warnings.clone());
+            async move {
+                if !config.is_method_enabled(&self.module_name, &method_name) {
+                    return Err(McpError::invalid_params(
+                        format!("Method '{}/{}' is disabled", self.module_name, method_name),
+                        None,
+                    ));
+                }
+                let method_def = self.module.methods.get(&method_name).cloned().ok_or_else(|| {
+                    McpError::invalid_params(format!("Method '{}' not found in ZML module '{}'", method_name, self.module_name), None)
+                })?;
+                let dynamic_method = self.generate_dynamic_tool_method(method_name.clone(), method_def, on_behalf_of.into_iter().collect(), dry_run, None, warnings);
+                match dynamic_method(self, Parameters(step_params)).await? {
+                    ResponsePayload::Json(output) => Ok(output),
+                    ResponsePayload::Text(text) => Ok(Value::String(text)),
+                    ResponsePayload::Binary { .. } => Err(McpError::internal_error(
+                        format!("Method '{}' returned a binary result, which a workflow cannot use", method_name),
+                        None,
+                    )),
+                }
+            }
+        })
+        .await
+    }
+
+    /// Read a resource of the module from the API, as JSON text
+    async fn fetch_resource(&self, uri: &str, headers: Option<HeaderMap>) -> Result<String, McpError> {
+        let (resource_def, variables) = match_resource_zml(uri, &self.module).ok_or_else(|| {
+            McpError::invalid_params(
+                format!("Resource '{}' not found in ZML module '{}'", uri, self.module_name),
+                None,
+            )
+        })?;
+
+        // Resolve URI variables into the REST endpoint and fetch it
+        let endpoint = expand_resource_uri_zml(resource_def, &variables).map_err(|e| {
+            McpError::invalid_params(format!("Invalid resource URI '{}': {}", uri, e), None)
+        })?;
+        let config_data = self.config.get_config();
+        let endpoint = render_variables(&config_data, &endpoint)?;
+        let full_url = format!(
+            "{}/{}",
+            backend_base_url(&config_data, &self.module)?.trim_end_matches('/'),
+            endpoint.trim_start_matches('/')
+        );
+        info!("Reading ZML resource: {}::{} -> {}", self.module_name, resource_def.name, full_url);
+        let mut policy = RequestPolicy::default();
+        let module_retry = resolve_module(&config_data, &self.module_name).retry;
+        if let Some(retry) = module_retry.as_ref().or(config_data.backend_retry(self.module.backend.as_deref())) {
+            apply_retry_config(&mut policy, retry);
+        }
+
+        let request = ApiRequest {
+            module: self.module_name.clone(),
+            operation: resource_def.name.clone(),
+            method: crate::services::auth_service::auth_strategy::HttpMethod::GET,
+            url: full_url,
+            headers: headers.unwrap_or_default(),
+            body: RequestBody::Empty,
+            policy,
+        };
+        let response_json: Value = MiddlewareChain::standard(self.auth_service.clone())
+            .execute(request)
+            .await
+            .map_err(|e| McpError::internal_error(format!("API request failed: {}", e), None))?;
+
+        serde_json::to_string(&response_json)
+            .map_err(|e| McpError::internal_error(format!("Failed to serialize resource: {}", e), None))
+    }
+}
+
+/// Check a response against the declared type of its method: `strict` fails the call with the
+/// first mismatch, `warn` logs it and adds it to the call's warnings, `off` skips the check
+fn check_response(
+    validation: ResponseValidation,
+    response: &Value,
+    method: &MethodDef,
+    module: &Module,
+    loader: Option<&ZmlModuleLoader>,
+    warnings: &Mutex<Vec<String>>,
+) -> Result<(), McpError> {
+    if validation == ResponseValidation::Off {
+        return Ok(());
+    }
+    if let Err(e) = validate_response_zml(response, method, module, loader) {
+        if validation == ResponseValidation::Strict {
+            return Err(e);
+        }
+        warn!("Response of {}::{} drifted from its declaration: {}", module.name, method.name, e.message);
+        warnings.lock().unwrap().push(format!("Response validation warning: {}", e.message));
+    }
+    Ok(())
+}
+
+/// Substitute the configuration variables (`{{name}}`) into a base URL or endpoint
+fn render_variables(config: &Config, template: &str) -> Result<String, McpError> {
+    render(template, &config.variables).map_err(|e| McpError::internal_error(e.to_string(), None))
+}
+
+/// Base URL of a module: `<env_prefix>BASE_URL` when set, otherwise the URL of the backend the
+/// module is bound to (`api.base_url` when unbound). The host is replaced by the backend's TLS
+/// `server_name` when one is set
+fn backend_base_url(config: &Config, module: &Module) -> Result<String, McpError> {
+    let tls = config.backend_tls(module.backend.as_deref()).cloned().unwrap_or_default();
+    if let Some(base_url) = config
+        .module_config
+        .get_module_config(&module.name)
+        .and_then(ModuleEnv::for_module)
+        .and_then(|env| env.base_url)
+    {
+        return Ok(tls.request_url(&render_variables(config, &base_url)?));
+    }
+
+    let base_url = config.backend_base_url(module.backend.as_deref()).ok_or_else(|| {
+        McpError::internal_error(
+            format!(
+                "Backend '{}' of ZML module '{}' is not configured",
+                module.backend.as_deref().unwrap_or_default(),
+                module.name
+            ),
+            None,
+        )
+    })?;
+    Ok(tls.request_url(&render_variables(config, base_url)?))
 }
 
 impl DynamicModule for ZmlDynamicService {
@@ -118,6 +447,17 @@ impl DynamicModule for ZmlDynamicService {
         Box::leak(self.module_name.clone().into_boxed_str())
     }
 
+    fn tool_prefix(&self) -> &str {
+        &self.tool_prefix
+    }
+
+    fn resource_config_name(&self, uri: &str) -> String {
+        match match_resource_zml(uri, &self.module) {
+            Some((resource, _)) if resource.is_template() => resource.name.clone(),
+            _ => uri.to_string(),
+        }
+    }
+
     fn module_description(&self) -> &'static str {
         Box::leak(self.module.description.clone().unwrap_or_default().into_boxed_str())
     }
@@ -139,10 +479,15 @@ impl DynamicModule for ZmlDynamicService {
     ) -> Pin<Box<dyn Future<Output = Result<ListToolsResult, McpError>> + Send + '_>> {
         Box::pin(async move {
             let mut tools = Vec::new();
+            let config = self.config.get_config();
 
             for (method_name, method_def) in &self.module.methods {
                 // Build input/output schemas using ZML
-                let input_schema = build_input_schema_zml(method_def, &self.module, Some(&self.loader));
+                let mut input_schema = build_input_schema_zml(method_def, &self.module, Some(&self.loader));
+                let header_allow_list = allowed_headers(&config, &self.module_name, method_name);
+                if !header_allow_list.is_empty() {
+                    input_schema["properties"][HEADERS_PARAM] = headers_param_schema(header_allow_list);
+                }
                 let output_schema = build_output_schema_zml(method_def, &self.module, Some(&self.loader));
 
                 let tool = Tool {
@@ -166,7 +511,10 @@ impl DynamicModule for ZmlDynamicService {
         _request: Option<PaginatedRequestParam>,
         _context: RequestContext<RoleServer>,
     ) -> Pin<Box<dyn Future<Output = Result<ListPromptsResult, McpError>> + Send + '_>> {
-        Box::pin(async move { Ok(ListPromptsResult { prompts: Vec::new(), next_cursor: None }) })
+        Box::pin(async move {
+            let prompts = self.module.prompts.values().map(build_prompt_zml).collect();
+            Ok(ListPromptsResult { prompts, next_cursor: None })
+        })
     }
 
     fn list_resources(
@@ -174,97 +522,330 @@ impl DynamicModule for ZmlDynamicService {
         _request: Option<PaginatedRequestParam>,
         _context: RequestContext<RoleServer>,
     ) -> Pin<Box<dyn Future<Output = Result<ListResourcesResult, McpError>> + Send + '_>> {
-        Box::pin(async move { Ok(ListResourcesResult { resources: Vec::new(), next_cursor: None }) })
+        Box::pin(async move {
+            let resources = self
+                .module
+                .resources
+                .values()
+                .filter(|r| !r.is_template())
+                .map(build_resource_zml)
+                .collect();
+            Ok(ListResourcesResult { resources, next_cursor: None })
+        })
+    }
+
+    fn list_resource_templates(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Pin<Box<dyn Future<Output = Result<ListResourceTemplatesResult, McpError>> + Send + '_>> {
+        Box::pin(async move {
+            let resource_templates = self
+                .module
+                .resources
+                .values()
+                .filter(|r| r.is_template())
+                .map(build_resource_template_zml)
+                .collect();
+            Ok(ListResourceTemplatesResult { resource_templates, next_cursor: None })
+        })
     }
 
     fn get_prompt(
         &self,
-        _request: GetPromptRequestParam,
+        request: GetPromptRequestParam,
         _context: RequestContext<RoleServer>,
     ) -> Pin<Box<dyn Future<Output = Result<GetPromptResult, McpError>> + Send + '_>> {
         Box::pin(async move {
-            Err(McpError::invalid_params(
-                "Prompts not supported by ZML dynamic services",
-                None,
-            ))
+            let prompt_def = self.module.prompts.get(&request.name).ok_or_else(|| {
+                McpError::invalid_params(
+                    format!("Prompt '{}' not found in ZML module '{}'", request.name, self.module_name),
+                    None,
+                )
+            })?;
+
+            render_prompt_zml(prompt_def, request.arguments.as_ref())
         })
     }
 
     fn read_resource(
         &self,
-        _request: ReadResourceRequestParam,
-        _context: RequestContext<RoleServer>,
+        request: ReadResourceRequestParam,
+        context: RequestContext<RoleServer>,
     ) -> Pin<Box<dyn Future<Output = Result<ReadResourceResult, McpError>> + Send + '_>> {
         Box::pin(async move {
-            Err(McpError::invalid_params(
-                "Resources not supported by ZML dynamic services",
-                None,
-            ))
+            let config_data = self.config.get_config();
+            let headers = impersonation_header(&context, &config_data)
+                .map(|(name, value)| build_request_headers(&BTreeMap::from([(name, value)])))
+                .transpose()
+                .map_err(|e| McpError::internal_error(format!("Failed to build request headers: {}", e), None))?
+                .flatten();
+            let text = self.fetch_resource(&request.uri, headers).await?;
+
+            Ok(ReadResourceResult {
+                contents: vec![ResourceContents::text(text, request.uri.clone())],
+            })
         })
     }
 
     fn call_tool(
         &self,
         request: CallToolRequestParam,
-        _context: RequestContext<RoleServer>,
+        context: RequestContext<RoleServer>,
     ) -> Pin<Box<dyn Future<Output = Result<CallToolResult, McpError>> + Send + '_>> {
         Box::pin(async move {
-            let tool_name = request.name.to_string();
+            // The provenance of the result is recorded by the middlewares its requests pass through
+            let (result, provenance) = with_provenance(async move {
+                let tool_name = request.name.to_string();
 
-            // Check module/method enablement via GlobalModuleConfig
-            let config = self.config.get_config();
-            if !config.is_method_enabled(&self.module_name, &tool_name) {
-                return Err(McpError::invalid_params(
-                    format!("Method '{}/{}' is disabled", self.module_name, tool_name),
-                    None,
-                ));
-            }
+                // Check module/method enablement via GlobalModuleConfig
+                let config = self.config.get_config();
+                if !config.is_method_enabled(&self.module_name, &tool_name) {
+                    return Err(McpError::invalid_params(
+                        format!("Method '{}/{}' is disabled", self.module_name, tool_name),
+                        None,
+                    ));
+                }
 
-            // Get method definition
-            let method_def = self.module.methods.get(&tool_name).ok_or_else(|| {
-                McpError::invalid_params(
-                    format!("Method '{}' not found in ZML module '{}'", tool_name, self.module_name),
-                    None,
-                )
-            })?;
+                // Get method definition
+                let method_def = self.module.methods.get(&tool_name).ok_or_else(|| {
+                    McpError::invalid_params(
+                        format!("Method '{}' not found in ZML module '{}'", tool_name, self.module_name),
+                        None,
+                    )
+                })?;
+
+                // Parse parameters (robust against null and non-object inputs)
+                let args_value: Value = request.arguments.into();
+                let mut params: HashMap<String, Value> = match args_value {
+                    Value::Null => HashMap::new(),
+                    Value::Object(map) => map.into_iter().collect(),
+                    _ => {
+                        return Err(McpError::invalid_params(
+                            "Arguments must be a JSON object",
+                            None,
+                        ));
+                    }
+                };
 
-            // Parse parameters (robust against null and non-object inputs)
-            let args_value: Value = request.arguments.into();
-            let params: HashMap<String, Value> = match args_value {
-                Value::Null => HashMap::new(),
-                Value::Object(map) => map.into_iter().collect(),
-                _ => {
+                // Strictly reject unknown parameters to align with MCP schema additionalProperties: false
+                let mut allowed_keys: HashSet<String> = method_def.params.keys().cloned().collect();
+                if method_def.supports_fetch_all() {
+                    allowed_keys.insert(FETCH_ALL_PARAM.to_string());
+                }
+                if supports_dry_run(method_def) {
+                    allowed_keys.insert(DRY_RUN_PARAM.to_string());
+                }
+                if supports_stream(method_def) {
+                    allowed_keys.insert(STREAM_PARAM.to_string());
+                }
+                let header_allow_list = allowed_headers(&config, &self.module_name, &tool_name);
+                if !header_allow_list.is_empty() {
+                    allowed_keys.insert(HEADERS_PARAM.to_string());
+                }
+                allowed_keys.extend(filter_params(method_def).into_iter().map(String::from));
+                let unknown_keys: Vec<String> = params
+                    .keys()
+                    .filter(|k| !allowed_keys.contains(*k))
+                    .cloned()
+                    .collect();
+                if !unknown_keys.is_empty() {
                     return Err(McpError::invalid_params(
-                        "Arguments must be a JSON object",
+                        format!("Unknown parameter(s): {}", unknown_keys.join(", ")),
                         None,
                     ));
                 }
-            };
-
-            // Strictly reject unknown parameters to align with MCP schema additionalProperties: false
-            let allowed_keys: HashSet<String> = method_def.params.keys().cloned().collect();
-            let unknown_keys: Vec<String> = params
-                .keys()
-                .filter(|k| !allowed_keys.contains(*k))
-                .cloned()
-                .collect();
-            if !unknown_keys.is_empty() {
+
+                let filter = ResponseFilter::from_params(&mut params, method_def)?;
+                let dry_run = take_dry_run(&mut params, method_def, &config);
+                let stream_requested = take_stream(&mut params, method_def);
+                let custom_headers = take_custom_headers(&mut params, header_allow_list)?;
+
+                // Execute dynamic ZML method
+                let on_behalf_of = impersonation_header(&context, &config);
+                let call_headers = custom_headers.into_iter().chain(on_behalf_of.clone()).collect();
+                let warnings = Arc::new(Mutex::new(Vec::new()));
+                // A dry run shows the request of the first page only
+                let fetch_all = method_def.supports_fetch_all()
+                    && params.remove(FETCH_ALL_PARAM).and_then(|value| value.as_bool()).unwrap_or(false)
+                    && !dry_run;
+                // Clients asking for a stream and sending a progress token get the items of large
+                // arrays and NDJSON responses in chunks, unless they are filtered or merged first
+                let stream = match (config.api.stream_chunk_items, context.meta.get_progress_token()) {
+                    (Some(chunk_items), Some(progress_token)) if stream_requested && filter.is_none() && !fetch_all => {
+                        let peer = context.peer.clone();
+                        info!("Streaming the result of '{}' in chunks of {}", tool_name, chunk_items);
+                        Some(ChunkStream::new(chunk_items, move |progress, message| {
+                            let (peer, progress_token) = (peer.clone(), progress_token.clone());
+                            async move {
+                                peer.notify_progress(ProgressNotificationParam {
+                                    progress_token,
+                                    progress,
+                                    total: None,
+                                    message: Some(message),
+                                })
+                                .await
+                                .map_err(|e| McpError::internal_error(format!("Failed to send result chunk: {}", e), None))
+                            }
+                        }))
+                    }
+                    _ => None,
+                };
+                let dynamic_method = self.generate_dynamic_tool_method(
+                    tool_name.clone(),
+                    method_def.clone(),
+                    call_headers,
+                    dry_run,
+                    stream,
+                    warnings.clone(),
+                );
+                let result = match (&method_def.workflow, &method_def.paginate) {
+                    // Workflows call the methods of their steps instead of sending a request
+                    (Some(workflow), _) => {
+                        ResponsePayload::Json(self.run_workflow_tool(workflow, params, on_behalf_of, dry_run, warnings.clone()).await?)
+                    }
+                    // Every page goes through the regular tool call, then the items are merged
+                    (None, Some(pagination)) if fetch_all => {
+                        let max_pages = max_pages(pagination, &config.module_config);
+                        let merged = fetch_all_pages(method_def, params, max_pages, |page_params| {
+                            let (dynamic_method, tool_name) = (&dynamic_method, &tool_name);
+                            async move {
+                                match dynamic_method(self, Parameters(page_params)).await? {
+                                    ResponsePayload::Json(page) => Ok(page),
+                                    _ => Err(McpError::internal_error(
+                                        format!("Method '{}' returned a non-JSON page", tool_name),
+                                        None,
+                                    )),
+                                }
+                            }
+                        })
+                        .await?;
+                        ResponsePayload::Json(merged)
+                    }
+                    _ => dynamic_method(self, Parameters(params)).await?,
+                };
+                let result = match (result, &filter) {
+                    (ResponsePayload::Json(value), Some(filter)) => ResponsePayload::Json(filter.apply(value)?),
+                    (result, _) => result,
+                };
+                let (result, truncation) = limit_response(result, &config.api.response_limits);
+    
+                match &result {
+                    ResponsePayload::Json(value) => info!("Dynamic method '{}' executed successfully with result: {}", tool_name, serde_json::to_string(value).unwrap_or_else(|_| "<unprintable>".to_string())),
+                    ResponsePayload::Text(text) => info!("Dynamic method '{}' executed successfully with a {} byte text result", tool_name, text.len()),
+                    ResponsePayload::Binary { mime_type, data, .. } => info!("Dynamic method '{}' executed successfully with a {} byte {} result", tool_name, data.len(), mime_type),
+                }
+                // JSON and text become text content, binary payloads an image or blob resource
+                let mut content = vec![result.into_content()?];
+                if let Some(note) = truncation {
+                    record(|provenance| provenance.truncated = true);
+                    warn!("Result of '{}' truncated: {}", tool_name, note);
+                    content.push(Content::text(note));
+                }
+                // Pages of a fetch-all call and workflow steps may warn about the same drift
+                let mut warnings = std::mem::take(&mut *warnings.lock().unwrap());
+                warnings.sort();
+                warnings.dedup();
+                content.extend(warnings.into_iter().map(Content::text));
+                Ok(CallToolResult::success(content))
+            })
+            .await;
+            provenance.attach(result)
+        })
+    }
+
+    fn poll_method(
+        &self,
+        method: String,
+        params: HashMap<String, Value>,
+    ) -> Pin<Box<dyn Future<Output = Result<Value, McpError>> + Send + '_>> {
+        Box::pin(async move {
+            if !self.config.get_config().is_method_enabled(&self.module_name, &method) {
                 return Err(McpError::invalid_params(
-                    format!("Unknown parameter(s): {}", unknown_keys.join(", ")),
+                    format!("Method '{}/{}' is disabled", self.module_name, method),
                     None,
                 ));
             }
+            let method_def = self.module.methods.get(&method).cloned().ok_or_else(|| {
+                McpError::invalid_params(format!("Method '{}' not found in ZML module '{}'", method, self.module_name), None)
+            })?;
+            // Drift is still recorded; warnings have no caller to go to
+            let warnings = Arc::new(Mutex::new(Vec::new()));
+            let dynamic_method = self.generate_dynamic_tool_method(method.clone(), method_def, Vec::new(), false, None, warnings);
+            match dynamic_method(self, Parameters(params)).await? {
+                ResponsePayload::Json(output) => Ok(output),
+                ResponsePayload::Text(text) => Ok(Value::String(text)),
+                ResponsePayload::Binary { .. } => Err(McpError::internal_error(
+                    format!("Method '{}' returned a binary result, which cannot be watched", method),
+                    None,
+                )),
+            }
+        })
+    }
+}
 
-            // Execute dynamic ZML method
-            let dynamic_method = self.generate_dynamic_tool_method(tool_name.clone(), method_def.clone());
-            let result = dynamic_method(self, Parameters(params)).await?;
-    
-            info!("Dynamic method '{}' executed successfully with result: {}", tool_name, serde_json::to_string(&result.0).unwrap_or_else(|_| "<unprintable>".to_string()));
-            // Serialize JSON result to string
-            let result_str = serde_json::to_string(&result.0)
-                .map_err(|e| McpError::internal_error(format!("Failed to serialize result: {}", e), None))?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::dynamic_service::drift::drift_report;
+    use crate::zml::ZMLParserWrapper;
+    use serde_json::json;
 
-            Ok(CallToolResult::success(vec![Content::text(result_str)]))
-        })
+    fn validation_module(name: &str) -> Module {
+        ZMLParserWrapper::new()
+            .parse(&format!(
+                r#"
+module {} {{
+    type User {{
+        id: integer
+        name: string
+    }}
+
+    method get_user {{
+        http_method: GET
+        uri: "/user"
+        response: User
+    }}
+}}
+"#,
+                name
+            ))
+            .unwrap()
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_strict_validation_rejects_a_drifted_response() {
+        let module = validation_module("StrictUsers");
+        let warnings = Mutex::new(Vec::new());
+        let drifted = json!({ "id": "1", "name": "Ada" });
+        let error = check_response(ResponseValidation::Strict, &drifted, &module.methods["get_user"], &module, None, &warnings)
+            .unwrap_err();
+        assert!(error.message.contains("Response field 'id' must be integer, got string"), "{}", error.message);
+        assert!(warnings.lock().unwrap().is_empty());
+
+        let matching = json!({ "id": 1, "name": "Ada" });
+        assert!(check_response(ResponseValidation::Strict, &matching, &module.methods["get_user"], &module, None, &warnings).is_ok());
+    }
+
+    #[test]
+    fn test_warn_validation_passes_with_a_warning() {
+        let module = validation_module("WarnUsers");
+        let warnings = Mutex::new(Vec::new());
+        let drifted = json!({ "id": 1 });
+        assert!(check_response(ResponseValidation::Warn, &drifted, &module.methods["get_user"], &module, None, &warnings).is_ok());
+        let warnings = warnings.into_inner().unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("Response validation warning: "), "{}", warnings[0]);
+        assert!(drift_report()["WarnUsers"]["get_user"].contains_key("name"));
+    }
+
+    #[test]
+    fn test_off_validation_skips_the_check() {
+        let module = validation_module("UncheckedUsers");
+        let warnings = Mutex::new(Vec::new());
+        let drifted = json!({ "id": "1" });
+        assert!(check_response(ResponseValidation::Off, &drifted, &module.methods["get_user"], &module, None, &warnings).is_ok());
+        assert!(warnings.lock().unwrap().is_empty());
+        assert!(!drift_report().contains_key("UncheckedUsers"));
+    }
+}
@@ -2,12 +2,13 @@
 
 use std::sync::Arc;
 
-use log::info;
+use log::{error, info};
 use rmcp::ErrorData as McpError;
 
 use crate::config::dynamic::DynamicConfigManager;
+use crate::config::module_env::ModuleEnv;
 use crate::config::zml_loader::ZmlModuleLoader;
-use crate::services::auth_service::UnifiedAuthService;
+use crate::services::composer_service::backend_registry::{module_env_auth_service, BackendRegistry};
 use crate::services::composer_service::module_registry::ServiceRegistry;
 use crate::services::dynamic_service::zml_dynamic_service::ZmlDynamicService;
 
@@ -16,7 +17,7 @@ use crate::services::dynamic_service::zml_dynamic_service::ZmlDynamicService;
 pub struct ZmlModuleFactory {
     loader: Arc<ZmlModuleLoader>,
     config: Arc<DynamicConfigManager>,
-    auth_service: Arc<UnifiedAuthService>,
+    backends: Arc<BackendRegistry>,
 }
 
 impl ZmlModuleFactory {
@@ -24,10 +25,10 @@ impl ZmlModuleFactory {
     pub fn new(
         loader: Arc<ZmlModuleLoader>,
         config: Arc<DynamicConfigManager>,
-        auth_service: Arc<UnifiedAuthService>,
+        backends: Arc<BackendRegistry>,
     ) -> Self {
         info!("Creating ZML module factory");
-        Self { loader, config, auth_service }
+        Self { loader, config, backends }
     }
 
     /// Get all enabled ZML modules based on GlobalModuleConfig
@@ -45,26 +46,107 @@ impl ZmlModuleFactory {
                 None,
             ));
         }
+        if !cfg.module_config.is_module_active(module_name) {
+            return Err(McpError::invalid_params(
+                format!(
+                    "Module '{}' depends on disabled modules: {}",
+                    module_name,
+                    cfg.module_config.unmet_dependencies(module_name).join(", ")
+                ),
+                None,
+            ));
+        }
 
         let module = self
             .loader
             .get_module(module_name)
             .ok_or_else(|| McpError::invalid_params(format!("ZML module '{}' not found", module_name), None))?;
 
+        // Send the module's requests through the backend it is bound to
+        let auth_service = self.backends.auth_service(module.backend.as_deref()).ok_or_else(|| {
+            McpError::invalid_params(
+                format!(
+                    "ZML module '{}' references unknown backend '{}'",
+                    module_name,
+                    module.backend.as_deref().unwrap_or_default()
+                ),
+                None,
+            )
+        })?;
+
+        // Credentials or a timeout from the module's environment variables need a dedicated client
+        let env = cfg
+            .get_module_config(module_name)
+            .and_then(ModuleEnv::for_module)
+            .filter(|env| env.needs_own_client());
+        let auth_service = match env {
+            Some(env) => {
+                info!(
+                    "Module '{}' uses authentication from its environment variables (credentials: {}, timeout: {:?})",
+                    module_name,
+                    env.has_credentials(),
+                    env.timeout
+                );
+                module_env_auth_service(&cfg, module.backend.as_deref(), &env).map_err(|e| {
+                    McpError::internal_error(
+                        format!("Failed to create auth service for module '{}': {}", module_name, e),
+                        None,
+                    )
+                })?
+            }
+            None => auth_service,
+        };
+
         Ok(ZmlDynamicService::new(
             Arc::new(module.clone()),
             self.loader.clone(),
             self.config.clone(),
-            self.auth_service.clone(),
+            auth_service,
         ))
     }
 
+    /// Rebuild modules in the service registry: enabled modules are replaced in place,
+    /// disabled or removed modules are unregistered
+    pub fn refresh_modules(&self, service_registry: &ServiceRegistry, module_names: &[String]) {
+        let enabled_modules = self.get_enabled_modules();
+        // Rebuild dependencies before the modules depending on them
+        let mut module_names = module_names.to_vec();
+        module_names.sort_by_key(|name| enabled_modules.iter().position(|enabled| enabled == name));
+        for module_name in &module_names {
+            if !enabled_modules.contains(module_name) {
+                if service_registry.has_module(module_name) {
+                    if let Err(e) = service_registry.unregister_module(module_name) {
+                        error!("Failed to unregister ZML module '{}': {}", module_name, e);
+                    }
+                }
+                continue;
+            }
+
+            match self.create_module(module_name) {
+                Ok(module) => {
+                    if let Err(e) = service_registry.replace_module(module) {
+                        error!("Failed to replace ZML module '{}': {}", module_name, e);
+                    }
+                }
+                Err(e) => error!("Failed to rebuild ZML module '{}': {}", module_name, e.message),
+            }
+        }
+    }
+
     /// Register all enabled ZML modules into the service registry
-    pub fn register_modules(&self, service_registry: &mut ServiceRegistry) -> Result<(), McpError> {
+    pub fn register_modules(&self, service_registry: &ServiceRegistry) -> Result<(), McpError> {
         let enabled_modules = self.get_enabled_modules();
         for module_name in enabled_modules {
-            let module = self.create_module(&module_name)?;
-            let _ = service_registry.register_module(module);
+            let module = match self.create_module(&module_name) {
+                Ok(module) => module,
+                Err(e) => {
+                    error!("Failed to create ZML module '{}': {}", module_name, e.message);
+                    continue;
+                }
+            };
+            if let Err(e) = service_registry.register_module(module) {
+                error!("Failed to register ZML module '{}': {}", module_name, e);
+            }
         }
         Ok(())
     }
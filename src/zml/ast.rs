@@ -7,17 +7,45 @@ use std::collections::HashMap;
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Module {
     pub name: String,
+    /// Namespace used to prefix tool names (`module Billing as bill`)
+    #[serde(default)]
+    pub alias: Option<String>,
     pub extends: Option<String>,
     pub version: Option<String>,
     pub description: Option<String>,
     pub enabled: Option<bool>,
     pub access_level: Option<AccessLevel>,
     pub category: Option<String>,
+    /// Named API backend (`backends` in config) the module's requests are sent to
+    #[serde(default)]
+    pub backend: Option<String>,
     pub types: HashMap<String, TypeDef>,
     pub enums: HashMap<String, EnumDef>,
+    pub scalars: HashMap<String, ScalarDef>,
     pub methods: HashMap<String, MethodDef>,
     pub resources: HashMap<String, ResourceDef>,
     pub templates: HashMap<String, TemplateDef>,
+    pub prompts: HashMap<String, PromptDef>,
+    /// Side-table from compiled node paths (e.g. `methods.getUser.params.id`) to ZML source locations
+    #[serde(default)]
+    pub source_map: HashMap<String, SourceLocation>,
+}
+
+impl Module {
+    /// Source location of a compiled node path, if recorded
+    pub fn source_location(&self, path: &str) -> Option<&SourceLocation> {
+        self.source_map.get(path)
+    }
+
+    /// Prefix for the module's tool names: the alias if declared, otherwise the module name
+    pub fn tool_prefix(&self) -> &str {
+        self.alias.as_deref().unwrap_or(&self.name)
+    }
+
+    /// Aggregated tool name of a method (`<prefix>_<method>`)
+    pub fn tool_name(&self, method_name: &str) -> String {
+        format!("{}_{}", self.tool_prefix(), method_name)
+    }
 }
 
 /// Access Level
@@ -52,6 +80,66 @@ pub struct EnumValueDef {
     pub description: Option<String>,
 }
 
+/// Custom Scalar Definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScalarDef {
+    pub name: String,
+    /// Basic type used on the wire
+    pub base: TypeExpr,
+    /// Regular expression the wire value must match
+    pub pattern: Option<String>,
+    /// `pattern` compiled when the module is parsed
+    #[serde(skip)]
+    pub pattern_regex: Option<regex::Regex>,
+    /// Conversion applied between tool arguments and the wire value
+    pub encode: Option<ScalarEncoding>,
+    pub description: Option<String>,
+}
+
+/// Scalar Encoding Rule
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum ScalarEncoding {
+    /// RFC 3339 datetime <-> integer seconds since the Unix epoch
+    UnixSeconds,
+    /// RFC 3339 datetime <-> integer milliseconds since the Unix epoch
+    UnixMillis,
+    /// RFC 3339 datetime or Unix seconds <-> RFC 3339 datetime string, normalized to UTC
+    Rfc3339,
+    /// Plain text <-> base64 string
+    Base64,
+}
+
+impl ScalarEncoding {
+    /// Look up an encoding by its ZML name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "unix_seconds" => Some(ScalarEncoding::UnixSeconds),
+            "unix_millis" => Some(ScalarEncoding::UnixMillis),
+            "rfc3339" => Some(ScalarEncoding::Rfc3339),
+            "base64" => Some(ScalarEncoding::Base64),
+            _ => None,
+        }
+    }
+
+    /// ZML name of the encoding
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScalarEncoding::UnixSeconds => "unix_seconds",
+            ScalarEncoding::UnixMillis => "unix_millis",
+            ScalarEncoding::Rfc3339 => "rfc3339",
+            ScalarEncoding::Base64 => "base64",
+        }
+    }
+
+    /// Basic type the encoding produces on the wire
+    pub fn wire_type(&self) -> TypeExpr {
+        match self {
+            ScalarEncoding::UnixSeconds | ScalarEncoding::UnixMillis => TypeExpr::Integer,
+            ScalarEncoding::Rfc3339 | ScalarEncoding::Base64 => TypeExpr::String,
+        }
+    }
+}
+
 /// Field Definition
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FieldDef {
@@ -60,6 +148,7 @@ pub struct FieldDef {
     pub optional: bool,
     pub default_value: Option<Value>,
     pub description: Option<String>,
+    pub examples: Vec<Value>,
 }
 
 /// Type Expression
@@ -73,6 +162,9 @@ pub enum TypeExpr {
     Date,
     DateTime,
     Any,
+
+    /// Binary file (base64 content or local path), sent as multipart/form-data
+    File,
     
     /// Array type
     Array(Box<TypeExpr>),
@@ -99,10 +191,87 @@ pub struct MethodDef {
     pub uri: String,
     pub access_level: AccessLevel,
     pub rate_limit: Option<RateLimit>,
+    /// Request timeout in milliseconds (`timeout: 30s`)
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    /// Retry policy for failed requests (`retry { ... }`)
+    #[serde(default)]
+    pub retry: Option<RetryPolicy>,
+    /// Time in milliseconds a GET response is served from the response cache (`cache_ttl: 5m`)
+    #[serde(default)]
+    pub cache_ttl_ms: Option<u64>,
+    /// How the method pages through its results (`paginate { ... }`), enabling `fetch_all`
+    #[serde(default)]
+    pub paginate: Option<Pagination>,
+    /// XML request body rendered from a template, e.g. a SOAP envelope (`xml_body { ... }`)
+    #[serde(default)]
+    pub xml_body: Option<XmlBody>,
+    /// Header carrying a key generated once per call and kept across retries
+    /// (`idempotent_key: "Idempotency-Key"`), so a retried write is not applied twice
+    #[serde(default)]
+    pub idempotent_key: Option<String>,
+    /// Media type asked for in `Accept` (`produces: "text/csv"`); CSV and XML responses are
+    /// converted to JSON
+    #[serde(default)]
+    pub produces: Option<String>,
+    /// Media type the request body is encoded as (`accepts: "application/xml"`): JSON (the
+    /// default), XML or form-urlencoded
+    #[serde(default)]
+    pub accepts: Option<String>,
+    /// Fields computed over the response before it is returned, in declaration order
+    /// (`computed { total_cost = price * quantity }`)
+    #[serde(default)]
+    pub computed: Vec<ComputedField>,
+    /// Steps of a composite tool declared with `workflow name { ... }`; such methods call the
+    /// module's other methods instead of sending a request of their own
+    #[serde(default)]
+    pub workflow: Option<Workflow>,
     pub params: HashMap<String, ParamDef>,
     pub response: TypeExpr,
 }
 
+impl MethodDef {
+    /// Whether any parameter is a `file`, requiring a multipart/form-data request
+    pub fn has_file_params(&self) -> bool {
+        self.params.values().any(|p| matches!(p.type_expr, TypeExpr::File))
+    }
+
+    /// Whether the method is paginated and takes the synthetic `fetch_all` parameter
+    pub fn supports_fetch_all(&self) -> bool {
+        self.paginate.is_some() && !self.params.contains_key(FETCH_ALL_PARAM)
+    }
+
+    /// Encoding of the request body declared with `accepts`, JSON by default
+    pub fn body_encoding(&self) -> BodyEncoding {
+        let essence = |media_type: &String| media_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+        self.accepts.as_ref().and_then(|media_type| body_encoding(&essence(media_type))).unwrap_or(BodyEncoding::Json)
+    }
+}
+
+/// Encoding of request bodies
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BodyEncoding {
+    Json,
+    Xml,
+    Form,
+}
+
+/// Encoding of bodies of a media type (lowercase, without parameters); `None` for media types
+/// request bodies cannot be encoded as
+pub fn body_encoding(media_type: &str) -> Option<BodyEncoding> {
+    match media_type {
+        "application/json" => Some(BodyEncoding::Json),
+        "application/xml" | "text/xml" => Some(BodyEncoding::Xml),
+        "application/x-www-form-urlencoded" => Some(BodyEncoding::Form),
+        other if other.ends_with("+json") => Some(BodyEncoding::Json),
+        other if other.ends_with("+xml") => Some(BodyEncoding::Xml),
+        _ => None,
+    }
+}
+
+/// Synthetic boolean parameter of paginated methods fetching and merging every page
+pub const FETCH_ALL_PARAM: &str = "fetch_all";
+
 /// HTTP Method
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum HttpMethod {
@@ -120,6 +289,234 @@ pub struct RateLimit {
     pub per_seconds: u32,
 }
 
+/// Retry Policy (`retry { attempts: 3, backoff: exponential, on: [502, 503] }`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first request
+    pub attempts: u32,
+    pub backoff: BackoffStrategy,
+    /// HTTP status codes that trigger a retry; empty retries 429 and any 5xx response
+    pub on: Vec<u16>,
+}
+
+/// Delay of the first retry; later delays grow according to the backoff strategy
+pub const RETRY_BASE_DELAY_MS: u64 = 500;
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: 1,
+            backoff: BackoffStrategy::Fixed,
+            on: Vec::new(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay in milliseconds before the given retry (1-based)
+    pub fn delay_ms(&self, retry: u32) -> u64 {
+        match self.backoff {
+            BackoffStrategy::Fixed => RETRY_BASE_DELAY_MS,
+            BackoffStrategy::Linear => RETRY_BASE_DELAY_MS * retry as u64,
+            BackoffStrategy::Exponential => {
+                RETRY_BASE_DELAY_MS.saturating_mul(1u64 << retry.saturating_sub(1).min(16))
+            }
+        }
+    }
+}
+
+/// Pagination of a list method (`paginate { style: page, param: "page", size_param: "page_size" }`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Pagination {
+    pub style: PaginationStyle,
+    /// Parameter carrying the page number, offset or cursor
+    pub param: String,
+    /// Parameter carrying the page size; a shorter page is the last one
+    pub size_param: Option<String>,
+    /// Dotted path of the item array in the response; the response itself when unset
+    pub items: Option<String>,
+    /// Dotted path of the next page's cursor in the response (`cursor` style)
+    pub next_cursor: Option<String>,
+    /// Pages fetched at most by `fetch_all`
+    pub max_pages: Option<u32>,
+}
+
+impl Default for Pagination {
+    fn default() -> Self {
+        Self {
+            style: PaginationStyle::Page,
+            param: "page".to_string(),
+            size_param: Some("page_size".to_string()),
+            items: None,
+            next_cursor: None,
+            max_pages: None,
+        }
+    }
+}
+
+/// XML request body of a method (`xml_body { template: "<Envelope>...</Envelope>", extract: "//Result" }`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct XmlBody {
+    /// Body template; `{{param}}` placeholders are replaced by the XML-escaped parameter values
+    pub template: String,
+    /// Content type of the body (`text/xml; charset=utf-8` when unset)
+    pub content_type: Option<String>,
+    /// `SOAPAction` header sent with the body
+    pub soap_action: Option<String>,
+    /// XPath of the part of the XML response returned (`/Envelope/Body/GetQuoteResponse`, `//Price`)
+    pub extract: Option<String>,
+}
+
+/// Response field computed by an expression over the other fields (`total_cost = price * quantity`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ComputedField {
+    pub name: String,
+    /// Rhai expression; the response fields, the fields computed before and `params` are in scope
+    pub expression: String,
+}
+
+/// Composite tool chaining methods of the module (`workflow create_team { step project { call: create_project } ... }`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Workflow {
+    /// Steps in execution order
+    pub steps: Vec<WorkflowStep>,
+    /// Reference returned as the result (`result: members`); every step output when unset
+    pub result: Option<String>,
+}
+
+/// Call of one method in a workflow (`step members { call: add_member, when: params.members, with { ... } }`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WorkflowStep {
+    pub name: String,
+    /// Method of the module called by the step
+    pub call: String,
+    /// Condition the step runs under; skipped steps output `null`
+    pub when: Option<WorkflowCondition>,
+    /// Parameters of the call
+    pub args: HashMap<String, WorkflowValue>,
+}
+
+impl WorkflowStep {
+    /// References used by the step's condition and arguments
+    pub fn references(&self) -> Vec<&str> {
+        let mut references: Vec<&str> = self.args.values().filter_map(WorkflowValue::reference).collect();
+        if let Some(condition) = &self.when {
+            references.push(&condition.reference);
+            references.extend(condition.comparison.as_ref().and_then(|(_, value)| value.reference()));
+        }
+        references
+    }
+}
+
+/// Argument or operand of a workflow step: a literal, or a reference to the workflow parameters
+/// (`params.name`) or to the output of an earlier step (`project.id`, `project.members.0`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum WorkflowValue {
+    Ref(String),
+    Literal(Value),
+}
+
+impl WorkflowValue {
+    pub fn reference(&self) -> Option<&str> {
+        match self {
+            WorkflowValue::Ref(reference) => Some(reference),
+            WorkflowValue::Literal(_) => None,
+        }
+    }
+}
+
+/// Condition of a workflow step: the reference is truthy, or compares to a value
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WorkflowCondition {
+    pub reference: String,
+    pub comparison: Option<(WorkflowComparison, WorkflowValue)>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum WorkflowComparison {
+    Equal,
+    NotEqual,
+}
+
+impl WorkflowComparison {
+    /// Look up a comparison by its ZML operator
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "==" => Some(WorkflowComparison::Equal),
+            "!=" => Some(WorkflowComparison::NotEqual),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WorkflowComparison::Equal => "==",
+            WorkflowComparison::NotEqual => "!=",
+        }
+    }
+}
+
+/// What the pagination parameter carries
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum PaginationStyle {
+    /// Page number, starting at 1
+    Page,
+    /// Index of the first item, starting at 0
+    Offset,
+    /// Opaque cursor returned by the previous page
+    Cursor,
+}
+
+impl PaginationStyle {
+    /// Look up a style by its ZML name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "page" => Some(PaginationStyle::Page),
+            "offset" => Some(PaginationStyle::Offset),
+            "cursor" => Some(PaginationStyle::Cursor),
+            _ => None,
+        }
+    }
+
+    /// ZML name of the style
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PaginationStyle::Page => "page",
+            PaginationStyle::Offset => "offset",
+            PaginationStyle::Cursor => "cursor",
+        }
+    }
+}
+
+/// Backoff Strategy between retries
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum BackoffStrategy {
+    Fixed,
+    Linear,
+    Exponential,
+}
+
+impl BackoffStrategy {
+    /// Look up a strategy by its ZML name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "fixed" => Some(BackoffStrategy::Fixed),
+            "linear" => Some(BackoffStrategy::Linear),
+            "exponential" => Some(BackoffStrategy::Exponential),
+            _ => None,
+        }
+    }
+
+    /// ZML name of the strategy
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BackoffStrategy::Fixed => "fixed",
+            BackoffStrategy::Linear => "linear",
+            BackoffStrategy::Exponential => "exponential",
+        }
+    }
+}
+
 /// Parameter Definition
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ParamDef {
@@ -128,6 +525,7 @@ pub struct ParamDef {
     pub optional: bool,
     pub default_value: Option<Value>,
     pub description: Option<String>,
+    pub examples: Vec<Value>,
 }
 
 /// Resource Definition
@@ -139,6 +537,30 @@ pub struct ResourceDef {
     pub description: Option<String>,
 }
 
+impl ResourceDef {
+    /// Names of the `{variable}` placeholders in the URI template, in order of appearance
+    pub fn uri_variables(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        let mut rest = self.uri.as_str();
+        while let Some(start) = rest.find('{') {
+            let after = &rest[start + 1..];
+            match after.find('}') {
+                Some(end) => {
+                    names.push(after[..end].to_string());
+                    rest = &after[end + 1..];
+                }
+                None => break,
+            }
+        }
+        names
+    }
+
+    /// Whether the resource URI is a template with variables
+    pub fn is_template(&self) -> bool {
+        !self.uri_variables().is_empty()
+    }
+}
+
 /// Resource Type
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum ResourceType {
@@ -146,6 +568,183 @@ pub enum ResourceType {
     Entity,
 }
 
+/// CRUD Resource Shorthand (`resource crud Users at "/users" of User`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrudResourceDef {
+    pub name: String,
+    pub uri: String,
+    pub entity: String,
+    pub excluded: Vec<CrudOperation>,
+    /// Location of the shorthand, shared by every generated method
+    pub location: Option<SourceLocation>,
+}
+
+/// Standard CRUD operation generated by the shorthand
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum CrudOperation {
+    List,
+    Get,
+    Create,
+    Update,
+    Delete,
+}
+
+impl CrudOperation {
+    /// All operations in generation order
+    pub const ALL: [CrudOperation; 5] = [
+        CrudOperation::List,
+        CrudOperation::Get,
+        CrudOperation::Create,
+        CrudOperation::Update,
+        CrudOperation::Delete,
+    ];
+
+    /// Look up an operation by its ZML name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "list" => Some(CrudOperation::List),
+            "get" => Some(CrudOperation::Get),
+            "create" => Some(CrudOperation::Create),
+            "update" => Some(CrudOperation::Update),
+            "delete" => Some(CrudOperation::Delete),
+            _ => None,
+        }
+    }
+
+    /// ZML name of the operation
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CrudOperation::List => "list",
+            CrudOperation::Get => "get",
+            CrudOperation::Create => "create",
+            CrudOperation::Update => "update",
+            CrudOperation::Delete => "delete",
+        }
+    }
+}
+
+impl CrudResourceDef {
+    /// Operations that remain after applying the `without` clause
+    pub fn operations(&self) -> Vec<CrudOperation> {
+        CrudOperation::ALL
+            .iter()
+            .copied()
+            .filter(|op| !self.excluded.contains(op))
+            .collect()
+    }
+
+    /// Expand the shorthand into standard methods. `entity` is the resolved entity type;
+    /// create/update take its fields as body params, and its `id` field types the path id.
+    pub fn expand(&self, entity: &TypeDef) -> Vec<MethodDef> {
+        let base_uri = self.uri.trim_end_matches('/').to_string();
+        let item_uri = format!("{}/{{id}}", base_uri);
+        let entity_type = TypeExpr::Alias(self.entity.clone());
+        let id_type = entity
+            .fields
+            .get("id")
+            .map(|f| f.type_expr.clone())
+            .unwrap_or(TypeExpr::String);
+
+        let param = |name: &str, type_expr: TypeExpr, optional: bool, default_value: Option<Value>| ParamDef {
+            name: name.to_string(),
+            type_expr,
+            optional,
+            default_value,
+            description: None,
+            examples: Vec::new(),
+        };
+        let body_params = |all_optional: bool| -> HashMap<String, ParamDef> {
+            entity
+                .fields
+                .values()
+                .filter(|f| f.name != "id")
+                .map(|f| {
+                    let mut p = param(&f.name, f.type_expr.clone(), f.optional || all_optional, f.default_value.clone());
+                    p.description = f.description.clone();
+                    p.examples = f.examples.clone();
+                    (f.name.clone(), p)
+                })
+                .collect()
+        };
+        let id_params = || HashMap::from([("id".to_string(), param("id", id_type.clone(), false, None))]);
+
+        self.operations()
+            .into_iter()
+            .map(|op| {
+                let (name, description, http_method, uri, params, response) = match op {
+                    CrudOperation::List => (
+                        format!("list{}", self.name),
+                        format!("List {} with pagination", self.name),
+                        HttpMethod::Get,
+                        base_uri.clone(),
+                        HashMap::from([
+                            ("page".to_string(), param("page", TypeExpr::Integer, true, Some(Value::Integer(1)))),
+                            ("page_size".to_string(), param("page_size", TypeExpr::Integer, true, Some(Value::Integer(20)))),
+                        ]),
+                        TypeExpr::Array(Box::new(entity_type.clone())),
+                    ),
+                    CrudOperation::Get => (
+                        format!("get{}", self.entity),
+                        format!("Get a {} by id", self.entity),
+                        HttpMethod::Get,
+                        item_uri.clone(),
+                        id_params(),
+                        entity_type.clone(),
+                    ),
+                    CrudOperation::Create => (
+                        format!("create{}", self.entity),
+                        format!("Create a {}", self.entity),
+                        HttpMethod::Post,
+                        base_uri.clone(),
+                        body_params(false),
+                        entity_type.clone(),
+                    ),
+                    CrudOperation::Update => {
+                        let mut params = body_params(true);
+                        params.extend(id_params());
+                        (
+                            format!("update{}", self.entity),
+                            format!("Update a {} by id", self.entity),
+                            HttpMethod::Patch,
+                            item_uri.clone(),
+                            params,
+                            entity_type.clone(),
+                        )
+                    }
+                    CrudOperation::Delete => (
+                        format!("delete{}", self.entity),
+                        format!("Delete a {} by id", self.entity),
+                        HttpMethod::Delete,
+                        item_uri.clone(),
+                        id_params(),
+                        TypeExpr::Any,
+                    ),
+                };
+                MethodDef {
+                    name,
+                    description: Some(description),
+                    http_method,
+                    uri,
+                    access_level: AccessLevel::Public,
+                    rate_limit: None,
+                    timeout_ms: None,
+                    retry: None,
+                    cache_ttl_ms: None,
+                    paginate: (op == CrudOperation::List).then(Pagination::default),
+                    xml_body: None,
+                    idempotent_key: None,
+                    produces: None,
+                    accepts: None,
+                    computed: Vec::new(),
+                    workflow: None,
+                    params,
+                    response,
+                }
+            })
+            .collect()
+    }
+}
+
 /// Template Definition
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TemplateDef {
@@ -153,6 +752,60 @@ pub struct TemplateDef {
     pub content: HashMap<String, Value>,
 }
 
+/// Prompt Definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptDef {
+    pub name: String,
+    pub description: Option<String>,
+    pub arguments: HashMap<String, FieldDef>,
+    /// Prompt text with `{{argument}}` placeholders
+    pub template: String,
+}
+
+impl PromptDef {
+    /// Names of the `{{argument}}` placeholders used by the template, in order of appearance
+    pub fn template_variables(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        let mut rest = self.template.as_str();
+        while let Some(start) = rest.find("{{") {
+            let after = &rest[start + 2..];
+            match after.find("}}") {
+                Some(end) => {
+                    let name = after[..end].trim().to_string();
+                    if !names.contains(&name) {
+                        names.push(name);
+                    }
+                    rest = &after[end + 2..];
+                }
+                None => break,
+            }
+        }
+        names
+    }
+
+    /// Render the template, substituting placeholders with argument values
+    /// Missing arguments are replaced with an empty string
+    pub fn render(&self, arguments: &HashMap<String, String>) -> String {
+        let mut rendered = String::with_capacity(self.template.len());
+        let mut rest = self.template.as_str();
+        while let Some(start) = rest.find("{{") {
+            let after = &rest[start + 2..];
+            match after.find("}}") {
+                Some(end) => {
+                    rendered.push_str(&rest[..start]);
+                    if let Some(value) = arguments.get(after[..end].trim()) {
+                        rendered.push_str(value);
+                    }
+                    rest = &after[end + 2..];
+                }
+                None => break,
+            }
+        }
+        rendered.push_str(rest);
+        rendered
+    }
+}
+
 /// Value Type
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum Value {
@@ -174,6 +827,26 @@ pub struct Span {
     pub column: usize,
 }
 
+/// Location of a definition in ZML source
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SourceLocation {
+    pub file: Option<String>,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl std::fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}",
+            self.file.as_deref().unwrap_or("<source>"),
+            self.line,
+            self.column
+        )
+    }
+}
+
 /// AST Node with Location Information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Node<T> {
@@ -199,6 +872,7 @@ impl TypeExpr {
                 | TypeExpr::Boolean
                 | TypeExpr::Date
                 | TypeExpr::DateTime
+                | TypeExpr::File
                 | TypeExpr::Any
         )
     }
@@ -223,6 +897,7 @@ impl TypeExpr {
             TypeExpr::Date => "date".to_string(),
             TypeExpr::DateTime => "datetime".to_string(),
             TypeExpr::Any => "any".to_string(),
+            TypeExpr::File => "file".to_string(),
             TypeExpr::Array(inner) => format!("array<{}>", inner.to_string_repr()),
             TypeExpr::Object(_fields) => {
                 // Keep object representation concise for template usage
@@ -48,9 +48,18 @@ impl Compiler {
         // Compile enum definitions
         self.compile_enums(module, &mut module_json)?;
 
+        // Compile custom scalar definitions
+        self.compile_scalars(module, &mut module_json)?;
+
         // Compile resource definitions
         self.compile_resources(module, &mut module_json)?;
 
+        // Compile prompt definitions
+        self.compile_prompts(module, &mut module_json)?;
+
+        // Emit source map side-table
+        self.compile_source_map(module, &mut module_json)?;
+
         // Cache module
         let module_json_value = JsonValue::Object(module_json);
         self.module_cache
@@ -68,6 +77,10 @@ impl Compiler {
         // Basic information
         module_json.insert("name".to_string(), JsonValue::String(module.name.clone()));
 
+        if let Some(alias) = &module.alias {
+            module_json.insert("alias".to_string(), JsonValue::String(alias.clone()));
+        }
+
         if let Some(version) = &module.version {
             module_json.insert("version".to_string(), JsonValue::String(version.clone()));
         }
@@ -93,6 +106,10 @@ impl Compiler {
             module_json.insert("category".to_string(), JsonValue::String(category.clone()));
         }
 
+        if let Some(backend) = &module.backend {
+            module_json.insert("backend".to_string(), JsonValue::String(backend.clone()));
+        }
+
         Ok(())
     }
 
@@ -157,6 +174,54 @@ impl Compiler {
         Ok(JsonValue::Object(value_json))
     }
 
+    /// Compile custom scalar definitions
+    fn compile_scalars(
+        &mut self,
+        module: &Module,
+        module_json: &mut Map<String, JsonValue>,
+    ) -> Result<(), CompileError> {
+        let mut scalars_json = Map::new();
+
+        for (scalar_name, scalar_def) in &module.scalars {
+            let scalar_json = self.compile_scalar_def(scalar_def, module)?;
+            scalars_json.insert(scalar_name.clone(), scalar_json);
+        }
+
+        if !scalars_json.is_empty() {
+            module_json.insert("scalars".to_string(), JsonValue::Object(scalars_json));
+        }
+
+        Ok(())
+    }
+
+    /// Compile single custom scalar definition
+    fn compile_scalar_def(
+        &mut self,
+        scalar_def: &ScalarDef,
+        module: &Module,
+    ) -> Result<JsonValue, CompileError> {
+        let mut scalar_json = Map::new();
+
+        scalar_json.insert("name".to_string(), JsonValue::String(scalar_def.name.clone()));
+
+        let base_json = self.compile_type_expr(&scalar_def.base, module)?;
+        scalar_json.insert("base".to_string(), base_json);
+
+        if let Some(pattern) = &scalar_def.pattern {
+            scalar_json.insert("pattern".to_string(), JsonValue::String(pattern.clone()));
+        }
+
+        if let Some(encoding) = &scalar_def.encode {
+            scalar_json.insert("encode".to_string(), JsonValue::String(encoding.as_str().to_string()));
+        }
+
+        if let Some(description) = &scalar_def.description {
+            scalar_json.insert("description".to_string(), JsonValue::String(description.clone()));
+        }
+
+        Ok(JsonValue::Object(scalar_json))
+    }
+
     /// Compile type definitions
     fn compile_types(
         &mut self,
@@ -249,6 +314,11 @@ impl Compiler {
             field_json.insert("description".to_string(), JsonValue::String(description.clone()));
         }
 
+        // Examples
+        if !field_def.examples.is_empty() {
+            field_json.insert("examples".to_string(), self.compile_value(&Value::Array(field_def.examples.clone()))?);
+        }
+
         Ok(JsonValue::Object(field_json))
     }
 
@@ -265,6 +335,7 @@ impl Compiler {
             TypeExpr::Boolean => Ok(JsonValue::String("boolean".to_string())),
             TypeExpr::Date => Ok(JsonValue::String("date".to_string())),
             TypeExpr::DateTime => Ok(JsonValue::String("datetime".to_string())),
+            TypeExpr::File => Ok(JsonValue::String("file".to_string())),
             TypeExpr::Any => Ok(JsonValue::String("any".to_string())),
             
             TypeExpr::Array(element_type) => {
@@ -332,8 +403,11 @@ impl Compiler {
             }
             
             TypeExpr::Alias(alias_name) => {
-                // Check if type alias exists (including type definitions and enum definitions)
-                if !module.types.contains_key(alias_name) && !module.enums.contains_key(alias_name) {
+                // Check if type alias exists (including type, enum and scalar definitions)
+                if !module.types.contains_key(alias_name)
+                    && !module.enums.contains_key(alias_name)
+                    && !module.scalars.contains_key(alias_name)
+                {
                     return Err(CompileError::ReferenceError {
                         message: format!("Type alias '{}' does not exist", alias_name),
                     });
@@ -405,6 +479,7 @@ impl Compiler {
 
         // Basic information
         method_json.insert("name".to_string(), JsonValue::String(method_def.name.clone()));
+        method_json.insert("tool_name".to_string(), JsonValue::String(module.tool_name(&method_def.name)));
 
         if let Some(description) = &method_def.description {
             method_json.insert("description".to_string(), JsonValue::String(description.clone()));
@@ -439,6 +514,115 @@ impl Compiler {
             method_json.insert("rate_limit".to_string(), JsonValue::Object(rate_limit_json));
         }
 
+        // Timeout, cache TTL, retry policy and pagination
+        if let Some(timeout_ms) = method_def.timeout_ms {
+            method_json.insert("timeout_ms".to_string(), JsonValue::Number(timeout_ms.into()));
+        }
+
+        if let Some(cache_ttl_ms) = method_def.cache_ttl_ms {
+            method_json.insert("cache_ttl_ms".to_string(), JsonValue::Number(cache_ttl_ms.into()));
+        }
+
+        if let Some(retry) = &method_def.retry {
+            let mut retry_json = Map::new();
+            retry_json.insert("attempts".to_string(), JsonValue::Number(retry.attempts.into()));
+            retry_json.insert("backoff".to_string(), JsonValue::String(retry.backoff.as_str().to_string()));
+            retry_json.insert(
+                "on".to_string(),
+                JsonValue::Array(retry.on.iter().map(|code| JsonValue::Number((*code).into())).collect()),
+            );
+            method_json.insert("retry".to_string(), JsonValue::Object(retry_json));
+        }
+
+        if let Some(pagination) = &method_def.paginate {
+            let mut paginate_json = Map::new();
+            paginate_json.insert("style".to_string(), JsonValue::String(pagination.style.as_str().to_string()));
+            paginate_json.insert("param".to_string(), JsonValue::String(pagination.param.clone()));
+            if let Some(size_param) = &pagination.size_param {
+                paginate_json.insert("size_param".to_string(), JsonValue::String(size_param.clone()));
+            }
+            if let Some(items) = &pagination.items {
+                paginate_json.insert("items".to_string(), JsonValue::String(items.clone()));
+            }
+            if let Some(next_cursor) = &pagination.next_cursor {
+                paginate_json.insert("next_cursor".to_string(), JsonValue::String(next_cursor.clone()));
+            }
+            if let Some(max_pages) = pagination.max_pages {
+                paginate_json.insert("max_pages".to_string(), JsonValue::Number(max_pages.into()));
+            }
+            method_json.insert("paginate".to_string(), JsonValue::Object(paginate_json));
+        }
+
+        if let Some(xml_body) = &method_def.xml_body {
+            let mut xml_json = Map::new();
+            xml_json.insert("template".to_string(), JsonValue::String(xml_body.template.clone()));
+            if let Some(content_type) = &xml_body.content_type {
+                xml_json.insert("content_type".to_string(), JsonValue::String(content_type.clone()));
+            }
+            if let Some(soap_action) = &xml_body.soap_action {
+                xml_json.insert("soap_action".to_string(), JsonValue::String(soap_action.clone()));
+            }
+            if let Some(extract) = &xml_body.extract {
+                xml_json.insert("extract".to_string(), JsonValue::String(extract.clone()));
+            }
+            method_json.insert("xml_body".to_string(), JsonValue::Object(xml_json));
+        }
+
+        if let Some(header) = &method_def.idempotent_key {
+            method_json.insert("idempotent_key".to_string(), JsonValue::String(header.clone()));
+        }
+
+        if let Some(media_type) = &method_def.produces {
+            method_json.insert("produces".to_string(), JsonValue::String(media_type.clone()));
+        }
+
+        if let Some(media_type) = &method_def.accepts {
+            method_json.insert("accepts".to_string(), JsonValue::String(media_type.clone()));
+        }
+
+        if !method_def.computed.is_empty() {
+            let computed_json: Vec<JsonValue> = method_def
+                .computed
+                .iter()
+                .map(|field| serde_json::json!({ "name": field.name, "expression": field.expression }))
+                .collect();
+            method_json.insert("computed".to_string(), JsonValue::Array(computed_json));
+        }
+
+        if let Some(workflow) = &method_def.workflow {
+            let operand_json = |value: &WorkflowValue| match value {
+                WorkflowValue::Ref(reference) => Ok(serde_json::json!({ "ref": reference })),
+                WorkflowValue::Literal(literal) => self.compile_value(literal),
+            };
+            let mut steps_json = Vec::new();
+            for step in &workflow.steps {
+                let mut step_json = Map::new();
+                step_json.insert("name".to_string(), JsonValue::String(step.name.clone()));
+                step_json.insert("call".to_string(), JsonValue::String(step.call.clone()));
+                if let Some(condition) = &step.when {
+                    let mut when_json = Map::new();
+                    when_json.insert("ref".to_string(), JsonValue::String(condition.reference.clone()));
+                    if let Some((comparison, operand)) = &condition.comparison {
+                        when_json.insert("op".to_string(), JsonValue::String(comparison.as_str().to_string()));
+                        when_json.insert("value".to_string(), operand_json(operand)?);
+                    }
+                    step_json.insert("when".to_string(), JsonValue::Object(when_json));
+                }
+                let mut with_json = Map::new();
+                for (name, value) in &step.args {
+                    with_json.insert(name.clone(), operand_json(value)?);
+                }
+                step_json.insert("with".to_string(), JsonValue::Object(with_json));
+                steps_json.push(JsonValue::Object(step_json));
+            }
+            let mut workflow_json = Map::new();
+            workflow_json.insert("steps".to_string(), JsonValue::Array(steps_json));
+            if let Some(result) = &workflow.result {
+                workflow_json.insert("result".to_string(), JsonValue::String(result.clone()));
+            }
+            method_json.insert("workflow".to_string(), JsonValue::Object(workflow_json));
+        }
+
         // Parameter definitions
         if !method_def.params.is_empty() {
             let mut params_json = Map::new();
@@ -482,6 +666,10 @@ impl Compiler {
             param_json.insert("description".to_string(), JsonValue::String(description.clone()));
         }
 
+        if !param_def.examples.is_empty() {
+            param_json.insert("examples".to_string(), self.compile_value(&Value::Array(param_def.examples.clone()))?);
+        }
+
         Ok(JsonValue::Object(param_json))
     }
 
@@ -519,6 +707,16 @@ impl Compiler {
 
         resource_json.insert("uri".to_string(), JsonValue::String(resource_def.uri.clone()));
 
+        // URI templates are exposed as MCP resource templates
+        let variables = resource_def.uri_variables();
+        if !variables.is_empty() {
+            resource_json.insert("uri_template".to_string(), JsonValue::Bool(true));
+            resource_json.insert(
+                "uri_variables".to_string(),
+                JsonValue::Array(variables.into_iter().map(JsonValue::String).collect()),
+            );
+        }
+
         if let Some(description) = &resource_def.description {
             resource_json.insert("description".to_string(), JsonValue::String(description.clone()));
         }
@@ -526,6 +724,82 @@ impl Compiler {
         Ok(JsonValue::Object(resource_json))
     }
 
+    /// Compile prompt definitions
+    fn compile_prompts(
+        &mut self,
+        module: &Module,
+        module_json: &mut Map<String, JsonValue>,
+    ) -> Result<(), CompileError> {
+        let mut prompts_json = Map::new();
+
+        for (prompt_name, prompt_def) in &module.prompts {
+            let prompt_json = self.compile_prompt_def(prompt_def)?;
+            prompts_json.insert(prompt_name.clone(), prompt_json);
+        }
+
+        if !prompts_json.is_empty() {
+            module_json.insert("prompts".to_string(), JsonValue::Object(prompts_json));
+        }
+
+        Ok(())
+    }
+
+    /// Compile single prompt definition into MCP prompt metadata
+    fn compile_prompt_def(&self, prompt_def: &PromptDef) -> Result<JsonValue, CompileError> {
+        let mut prompt_json = Map::new();
+
+        prompt_json.insert("name".to_string(), JsonValue::String(prompt_def.name.clone()));
+
+        if let Some(description) = &prompt_def.description {
+            prompt_json.insert("description".to_string(), JsonValue::String(description.clone()));
+        }
+
+        // Arguments follow the MCP PromptArgument shape, sorted for stable output
+        let mut arg_names: Vec<&String> = prompt_def.arguments.keys().collect();
+        arg_names.sort();
+        let mut arguments_json = Vec::new();
+        for arg_name in arg_names {
+            let arg_def = &prompt_def.arguments[arg_name];
+            let mut arg_json = Map::new();
+            arg_json.insert("name".to_string(), JsonValue::String(arg_name.clone()));
+            if let Some(description) = &arg_def.description {
+                arg_json.insert("description".to_string(), JsonValue::String(description.clone()));
+            }
+            arg_json.insert("required".to_string(), JsonValue::Bool(!arg_def.optional));
+            arguments_json.push(JsonValue::Object(arg_json));
+        }
+        prompt_json.insert("arguments".to_string(), JsonValue::Array(arguments_json));
+
+        prompt_json.insert("template".to_string(), JsonValue::String(prompt_def.template.clone()));
+
+        Ok(JsonValue::Object(prompt_json))
+    }
+
+    /// Compile source map side-table (compiled node path -> ZML file, line and column)
+    fn compile_source_map(
+        &self,
+        module: &Module,
+        module_json: &mut Map<String, JsonValue>,
+    ) -> Result<(), CompileError> {
+        let mut source_map_json = Map::new();
+
+        for (path, location) in &module.source_map {
+            let mut location_json = Map::new();
+            if let Some(file) = &location.file {
+                location_json.insert("file".to_string(), JsonValue::String(file.clone()));
+            }
+            location_json.insert("line".to_string(), JsonValue::Number(location.line.into()));
+            location_json.insert("column".to_string(), JsonValue::Number(location.column.into()));
+            source_map_json.insert(path.clone(), JsonValue::Object(location_json));
+        }
+
+        if !source_map_json.is_empty() {
+            module_json.insert("source_map".to_string(), JsonValue::Object(source_map_json));
+        }
+
+        Ok(())
+    }
+
     /// Clear cache
     pub fn clear_cache(&mut self) {
         self.type_cache.clear();
@@ -548,17 +822,22 @@ mod tests {
         let mut compiler = Compiler::new();
         let module = Module {
             name: "TestModule".to_string(),
+            alias: None,
             extends: None,
             version: Some("1.0.0".to_string()),
             description: Some("Test module".to_string()),
             enabled: Some(true),
             access_level: Some(AccessLevel::Public),
             category: Some("test".to_string()),
+            backend: None,
             types: HashMap::new(),
             enums: HashMap::new(),
+            scalars: HashMap::new(),
             methods: HashMap::new(),
             resources: HashMap::new(),
             templates: HashMap::new(),
+            prompts: HashMap::new(),
+            source_map: HashMap::new(),
         };
 
         let result = compiler.compile_module(&module);
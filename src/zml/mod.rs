@@ -19,8 +19,13 @@ pub struct ZMLProcessor {
 
 impl ZMLProcessor {
     pub fn new() -> Self {
+        Self::with_options(ParseOptions::default())
+    }
+
+    /// Create a processor whose parser uses the given options
+    pub fn with_options(options: ParseOptions) -> Self {
         Self {
-            parser: ZMLParserWrapper::new(),
+            parser: ZMLParserWrapper::with_options(options),
             compiler: Compiler::new(),
         }
     }
@@ -40,8 +45,11 @@ impl ZMLProcessor {
     pub fn process_file(&mut self, file_path: &str) -> Result<serde_json::Value, ZMLError> {
         let source = std::fs::read_to_string(file_path)
             .map_err(|e| ZMLError::IoError { source: e })?;
-        
-        self.process(&source)
+
+        let module = self.parser.parse_with_file(&source, file_path)?;
+        let json_config = self.compiler.compile_module(&module)?;
+
+        Ok(json_config)
     }
 
     /// Clear all caches
@@ -3,6 +3,7 @@
 use pest::Parser;
 use pest_derive::Parser;
 use std::collections::HashMap;
+use tracing::debug;
 
 use crate::zml::ast::*;
 
@@ -35,13 +36,37 @@ pub enum ParseError {
 struct ParseContext {
     current_line: usize,
     current_column: usize,
+    file: Option<String>,
+    source: String,
 }
 
 impl ParseContext {
-    fn new(_source: &str) -> Self {
+    fn new(source: &str, file: Option<&str>) -> Self {
         Self {
             current_line: 1,
             current_column: 1,
+            file: file.map(|f| f.to_string()),
+            source: source.to_string(),
+        }
+    }
+
+    /// Comment block on the lines directly above a definition
+    fn leading_comment(&self, pair: &pest::iterators::Pair<Rule>) -> Option<String> {
+        leading_comment(&self.source, pair.as_span().start())
+    }
+
+    /// Comment following a definition on the line where it starts, falling back to the leading block
+    fn attached_comment(&self, pair: &pest::iterators::Pair<Rule>) -> Option<String> {
+        trailing_comment(&self.source, pair.as_span().start()).or_else(|| self.leading_comment(pair))
+    }
+
+    /// Source location of a pair, for the module source map
+    fn location(&self, pair: &pest::iterators::Pair<Rule>) -> SourceLocation {
+        let (line, column) = pair.as_span().start_pos().line_col();
+        SourceLocation {
+            file: self.file.clone(),
+            line,
+            column,
         }
     }
     
@@ -72,38 +97,66 @@ impl ParseContext {
     }
 }
 
+/// Parser options
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ParseOptions {
+    /// Reject unrecognized content (unknown rules and properties) instead of ignoring it
+    pub strict: bool,
+}
+
 /// ZML parser wrapper
 pub struct ZMLParserWrapper {
     modules: HashMap<String, Module>,
+    options: ParseOptions,
 }
 
 impl ZMLParserWrapper {
     pub fn new() -> Self {
+        Self::with_options(ParseOptions::default())
+    }
+
+    /// Create a parser with the given options
+    pub fn with_options(options: ParseOptions) -> Self {
         Self {
             modules: HashMap::new(),
+            options,
         }
     }
 
     /// Parse ZML source code
     pub fn parse(&mut self, source: &str) -> Result<Module, ParseError> {
-        let mut context = ParseContext::new(source);
+        self.parse_source(source, None)
+    }
+
+    /// Parse ZML source code read from `file`, recording the file name in the source map
+    pub fn parse_with_file(&mut self, source: &str, file: &str) -> Result<Module, ParseError> {
+        self.parse_source(source, Some(file))
+    }
+
+    fn parse_source(&mut self, source: &str, file: Option<&str>) -> Result<Module, ParseError> {
+        let mut context = ParseContext::new(source, file);
         
         let pairs = ZMLParser::parse(Rule::file, source)
             .map_err(|e| self.convert_pest_error(e, source))?;
 
         let mut module = Module {
             name: String::new(),
+            alias: None,
             extends: None,
             version: None,
             description: None,
             enabled: None,
             access_level: None,
             category: None,
+            backend: None,
             types: HashMap::new(),
             enums: HashMap::new(),
+            scalars: HashMap::new(),
             methods: HashMap::new(),
             resources: HashMap::new(),
             templates: HashMap::new(),
+            prompts: HashMap::new(),
+            source_map: HashMap::new(),
         };
 
         for pair in pairs {
@@ -114,7 +167,9 @@ impl ZMLParserWrapper {
                         match inner_pair.as_rule() {
                             Rule::module_def => {
                                 context.update_position(&inner_pair);
+                                let leading = context.leading_comment(&inner_pair);
                                 self.parse_module_def(inner_pair, &mut module, &context)?;
+                                module.description = module.description.take().or(leading);
                             }
                             Rule::template_def => {
                                 // Parse file-level template definition
@@ -123,8 +178,8 @@ impl ZMLParserWrapper {
                             }
                             Rule::EOI => break,
                             Rule::WHITESPACE => continue,
-                            _ => {
-                                // Unknown file-level rule: ignored
+                            rule => {
+                                self.unknown_construct(&context, format!("Unknown file-level rule: {:?}", rule))?;
                             }
                         }
                     }
@@ -158,9 +213,17 @@ impl ZMLParserWrapper {
             module.name = name_pair.as_str().to_string();
         }
 
+        // CRUD shorthands are expanded once all types of the module are known
+        let mut crud_resources = Vec::new();
+
         // Parse inheritance relationship and other content
         for pair in inner_pairs {
             match pair.as_rule() {
+                Rule::alias_clause => {
+                    if let Some(alias_name) = pair.into_inner().next() {
+                        module.alias = Some(alias_name.as_str().to_string());
+                    }
+                }
                 Rule::extends_clause => {
                     let mut extends_pairs = pair.into_inner();
                     if let Some(extends_name) = extends_pairs.next() {
@@ -168,20 +231,19 @@ impl ZMLParserWrapper {
                     }
                 }
                 Rule::module_content => {
-                    self.parse_module_content(pair, module, context)?;
+                    self.parse_module_content(pair, module, &mut crud_resources, context)?;
                 }
                 Rule::WHITESPACE => {
                     // Ignore whitespace characters
                     continue;
                 }
-                _ => {
-                    // Debug output for unknown rules
-                    println!("Unknown rule: {:?}", pair.as_rule());
+                rule => {
+                    self.unknown_construct(context, format!("Unknown module rule: {:?}", rule))?;
                 }
             }
         }
 
-        Ok(())
+        self.expand_crud_resources(module, crud_resources, context)
     }
 
     /// Parse module content
@@ -189,6 +251,7 @@ impl ZMLParserWrapper {
         &self,
         pair: pest::iterators::Pair<Rule>,
         module: &mut Module,
+        crud_resources: &mut Vec<CrudResourceDef>,
         context: &ParseContext,
     ) -> Result<(), ParseError> {
         let inner_pairs = pair.into_inner();
@@ -198,21 +261,45 @@ impl ZMLParserWrapper {
             // Parsing module content entry
             match content_pair.as_rule() {
                 Rule::type_def => {
-                    let type_def = self.parse_type_def(content_pair, context)?;
+                    let mut type_def = self.parse_type_def(content_pair.clone(), context)?;
+                    type_def.description = type_def.description.take().or_else(|| context.leading_comment(&content_pair));
+                    let path = format!("types.{}", type_def.name);
+                    self.record_locations(module, &path, "properties", Rule::field_def, &content_pair, context);
                     module.types.insert(type_def.name.clone(), type_def);
                 }
                 Rule::enum_def => {
-                    println!("Found enum definition, starting parsing...");
-                    let enum_def = self.parse_enum_def(content_pair, context)?;
-                    println!("Enum parsing completed: name={}, values_count={}", enum_def.name, enum_def.values.len());
+                    debug!("Found enum definition, starting parsing...");
+                    let mut enum_def = self.parse_enum_def(content_pair.clone(), context)?;
+                    enum_def.description = enum_def.description.take().or_else(|| context.leading_comment(&content_pair));
+                    let path = format!("enums.{}", enum_def.name);
+                    self.record_locations(module, &path, "values", Rule::enum_value_def, &content_pair, context);
+                    debug!("Enum parsing completed: name={}, values_count={}", enum_def.name, enum_def.values.len());
                     module.enums.insert(enum_def.name.clone(), enum_def);
                 }
+                Rule::scalar_def => {
+                    let mut scalar_def = self.parse_scalar_def(content_pair.clone(), context)?;
+                    scalar_def.description = scalar_def.description.take().or_else(|| context.leading_comment(&content_pair));
+                    module
+                        .source_map
+                        .insert(format!("scalars.{}", scalar_def.name), context.location(&content_pair));
+                    module.scalars.insert(scalar_def.name.clone(), scalar_def);
+                }
                 Rule::method_def => {
-                    let method_def = self.parse_method_def(content_pair, context)?;
+                    let mut method_def = self.parse_method_def(content_pair.clone(), context)?;
+                    method_def.description = method_def.description.take().or_else(|| context.leading_comment(&content_pair));
+                    let path = format!("methods.{}", method_def.name);
+                    self.record_locations(module, &path, "params", Rule::param_def, &content_pair, context);
                     module.methods.insert(method_def.name.clone(), method_def);
                 }
+                Rule::crud_resource_def => {
+                    crud_resources.push(self.parse_crud_resource_def(content_pair, context)?);
+                }
                 Rule::resource_def => {
-                    let resource_def = self.parse_resource_def(content_pair, context)?;
+                    let mut resource_def = self.parse_resource_def(content_pair.clone(), context)?;
+                    resource_def.description = resource_def.description.take().or_else(|| context.leading_comment(&content_pair));
+                    module
+                        .source_map
+                        .insert(format!("resources.{}", resource_def.name), context.location(&content_pair));
                     module
                         .resources
                         .insert(resource_def.name.clone(), resource_def);
@@ -223,12 +310,34 @@ impl ZMLParserWrapper {
                         .templates
                         .insert(template_def.name.clone(), template_def);
                 }
+                Rule::prompt_def => {
+                    let mut prompt_def = self.parse_prompt_def(content_pair.clone(), context)?;
+                    prompt_def.description = prompt_def.description.take().or_else(|| context.leading_comment(&content_pair));
+                    let path = format!("prompts.{}", prompt_def.name);
+                    self.record_locations(module, &path, "arguments", Rule::field_def, &content_pair, context);
+                    module.prompts.insert(prompt_def.name.clone(), prompt_def);
+                }
+                Rule::workflow_def => {
+                    let mut method_def = self.parse_workflow_def(content_pair.clone(), context)?;
+                    method_def.description = method_def.description.take().or_else(|| context.leading_comment(&content_pair));
+                    if module.methods.contains_key(&method_def.name) {
+                        return Err(context.semantic_error(format!(
+                            "Workflow '{}' has the name of a method",
+                            method_def.name
+                        )));
+                    }
+                    let path = format!("methods.{}", method_def.name);
+                    self.record_locations(module, &path, "params", Rule::param_def, &content_pair, context);
+                    module.methods.insert(method_def.name.clone(), method_def);
+                }
                 Rule::property_def => {
                     let (key, value) = self.parse_property_def(content_pair, context)?;
-                    self.set_module_property(module, &key, value);
+                    if !self.set_module_property(module, &key, value) {
+                        self.unknown_construct(context, format!("Unknown or invalid module property '{}'", key))?;
+                    }
                 }
-                _ => {
-                    // Unknown module content rule - ignored
+                rule => {
+                    self.unknown_construct(context, format!("Unknown module content rule: {:?}", rule))?;
                 }
             }
         }
@@ -277,6 +386,7 @@ impl ZMLParserWrapper {
         pair: pest::iterators::Pair<Rule>,
         context: &ParseContext,
     ) -> Result<EnumValueDef, ParseError> {
+        let comment = context.attached_comment(&pair);
         let mut inner_pairs = pair.into_inner();
         let mut enum_value_def = EnumValueDef {
             name: String::new(),
@@ -289,24 +399,89 @@ impl ZMLParserWrapper {
             enum_value_def.name = name_pair.as_str().to_string();
         }
 
-        // Parse enum value content and comments
+        // Parse enum value content
         for pair in inner_pairs {
-            match pair.as_rule() {
-                Rule::value => {
-                    enum_value_def.value = Some(self.parse_value(pair, context)?);
-                }
-                Rule::comment => {
-                    // Parse comment as description
-                    let comment = pair.as_str().trim();
-                    if comment.starts_with("//") {
-                        enum_value_def.description = Some(comment[2..].trim().to_string());
+            if pair.as_rule() == Rule::value {
+                enum_value_def.value = Some(self.parse_value(pair, context)?);
+            }
+        }
+
+        // Use the attached comment as description
+        enum_value_def.description = comment;
+
+        Ok(enum_value_def)
+    }
+
+    /// Parse custom scalar definition
+    fn parse_scalar_def(
+        &self,
+        pair: pest::iterators::Pair<Rule>,
+        context: &ParseContext,
+    ) -> Result<ScalarDef, ParseError> {
+        let mut inner_pairs = pair.into_inner();
+        let mut scalar_def = ScalarDef {
+            name: String::new(),
+            base: TypeExpr::String,
+            pattern: None,
+            pattern_regex: None,
+            encode: None,
+            description: None,
+        };
+
+        // Parse scalar name
+        if let Some(name_pair) = inner_pairs.next() {
+            scalar_def.name = name_pair.as_str().to_string();
+        }
+
+        // Parse scalar attributes
+        for pair in inner_pairs {
+            if pair.as_rule() != Rule::scalar_field {
+                continue;
+            }
+            for field_pair in pair.into_inner() {
+                match field_pair.as_rule() {
+                    Rule::scalar_base_def => {
+                        if let Some(base_pair) = field_pair.into_inner().next() {
+                            scalar_def.base = self.parse_type_expr(base_pair, context)?;
+                        }
+                    }
+                    Rule::scalar_pattern_def => {
+                        scalar_def.pattern = self
+                            .parse_string_content(field_pair)?
+                            .map(|pattern| unescape_backslashes(&pattern));
+                        scalar_def.pattern_regex = scalar_def
+                            .pattern
+                            .as_deref()
+                            .map(regex::Regex::new)
+                            .transpose()
+                            .map_err(|e| {
+                                context.semantic_error(format!(
+                                    "Scalar '{}' has an invalid pattern: {}",
+                                    scalar_def.name, e
+                                ))
+                            })?;
                     }
+                    Rule::scalar_encode_def => {
+                        if let Some(encode_pair) = field_pair.into_inner().next() {
+                            let encoding = ScalarEncoding::from_name(encode_pair.as_str()).ok_or_else(|| {
+                                context.semantic_error(format!(
+                                    "Scalar '{}' uses unknown encoding '{}'",
+                                    scalar_def.name,
+                                    encode_pair.as_str()
+                                ))
+                            })?;
+                            scalar_def.encode = Some(encoding);
+                        }
+                    }
+                    Rule::description_def => {
+                        scalar_def.description = self.parse_string_content(field_pair)?;
+                    }
+                    _ => {}
                 }
-                _ => {}
             }
         }
 
-        Ok(enum_value_def)
+        Ok(scalar_def)
     }
 
     /// Parse type definition
@@ -344,6 +519,7 @@ impl ZMLParserWrapper {
         pair: pest::iterators::Pair<Rule>,
         context: &ParseContext,
     ) -> Result<FieldDef, ParseError> {
+        let comment = context.attached_comment(&pair);
         let mut inner_pairs = pair.into_inner();
         let mut field_def = FieldDef {
             name: String::new(),
@@ -351,6 +527,7 @@ impl ZMLParserWrapper {
             optional: false,
             default_value: None,
             description: None,
+            examples: Vec::new(),
         };
 
         // Parse field name
@@ -382,17 +559,16 @@ impl ZMLParserWrapper {
                         }
                     }
                 }
-                Rule::field_comment => {
-                    // Parse comment as description
-                    let comment = pair.as_str().trim();
-                    if comment.starts_with("//") {
-                        field_def.description = Some(comment[2..].trim().to_string());
-                    }
+                Rule::example_value => {
+                    field_def.examples = self.parse_example_value(pair, context)?;
                 }
                 _ => {}
             }
         }
 
+        // Use the attached comment as description
+        field_def.description = comment;
+
         Ok(field_def)
     }
 
@@ -419,6 +595,7 @@ impl ZMLParserWrapper {
                 "boolean" => Ok(TypeExpr::Boolean),
                 "date" => Ok(TypeExpr::Date),
                 "datetime" => Ok(TypeExpr::DateTime),
+                "file" => Ok(TypeExpr::File),
                 "any" => Ok(TypeExpr::Any),
                 _ => Err(context.type_error(format!("Unknown basic type: {}", pair.as_str()))),
             },
@@ -474,6 +651,33 @@ impl ZMLParserWrapper {
         }
     }
 
+    /// Parse example annotation into a list of example values
+    fn parse_example_value(
+        &self,
+        pair: pest::iterators::Pair<Rule>,
+        context: &ParseContext,
+    ) -> Result<Vec<Value>, ParseError> {
+        let mut examples = Vec::new();
+        for inner_pair in pair.into_inner() {
+            match inner_pair.as_rule() {
+                Rule::example_literal => {
+                    if let Some(literal) = inner_pair.into_inner().next() {
+                        examples.push(self.parse_value(literal, context)?);
+                    }
+                }
+                Rule::example_array => {
+                    for item_pair in inner_pair.into_inner() {
+                        if let Some(literal) = item_pair.into_inner().next() {
+                            examples.push(self.parse_value(literal, context)?);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(examples)
+    }
+
     /// Parse value
     fn parse_value(
         &self,
@@ -538,6 +742,16 @@ impl ZMLParserWrapper {
             uri: String::new(),
             access_level: AccessLevel::Public,
             rate_limit: None,
+            timeout_ms: None,
+            retry: None,
+            cache_ttl_ms: None,
+            paginate: None,
+            xml_body: None,
+            idempotent_key: None,
+            produces: None,
+            accepts: None,
+            computed: Vec::new(),
+            workflow: None,
             params: HashMap::new(),
             response: TypeExpr::Any,
         };
@@ -554,6 +768,38 @@ impl ZMLParserWrapper {
             }
         }
 
+        // The parameters of an XML body are sent in the body, so it needs a method carrying one
+        if method_def.xml_body.is_some() && matches!(method_def.http_method, HttpMethod::Get | HttpMethod::Delete) {
+            return Err(context.semantic_error(format!(
+                "Method '{}' has an xml_body and must use POST, PUT or PATCH",
+                method_def.name
+            )));
+        }
+
+        // `accepts` encodes the JSON body; XML templates and file uploads bring their own encoding
+        if method_def.accepts.is_some() {
+            if matches!(method_def.http_method, HttpMethod::Get | HttpMethod::Delete) {
+                return Err(context.semantic_error(format!(
+                    "Method '{}' declares accepts and must use POST, PUT or PATCH",
+                    method_def.name
+                )));
+            }
+            if method_def.xml_body.is_some() || method_def.has_file_params() {
+                return Err(context.semantic_error(format!(
+                    "Method '{}' cannot declare accepts together with an xml_body or file parameters",
+                    method_def.name
+                )));
+            }
+        }
+
+        // GET and DELETE are idempotent already; the key is for requests creating or changing resources
+        if method_def.idempotent_key.is_some() && matches!(method_def.http_method, HttpMethod::Get | HttpMethod::Delete) {
+            return Err(context.semantic_error(format!(
+                "Method '{}' has an idempotent_key and must use POST, PUT or PATCH",
+                method_def.name
+            )));
+        }
+
         Ok(method_def)
     }
 
@@ -583,6 +829,33 @@ impl ZMLParserWrapper {
                 Rule::rate_limit_def => {
                     method_def.rate_limit = self.parse_rate_limit(content_pair, context)?;
                 }
+                Rule::timeout_def => {
+                    method_def.timeout_ms = self.parse_duration(content_pair, "timeout", context)?;
+                }
+                Rule::retry_def => {
+                    method_def.retry = Some(self.parse_retry_def(content_pair, context)?);
+                }
+                Rule::cache_ttl_def => {
+                    method_def.cache_ttl_ms = self.parse_duration(content_pair, "cache_ttl", context)?;
+                }
+                Rule::paginate_def => {
+                    method_def.paginate = Some(self.parse_paginate_def(content_pair, context)?);
+                }
+                Rule::xml_body_def => {
+                    method_def.xml_body = Some(self.parse_xml_body_def(content_pair, context)?);
+                }
+                Rule::idempotent_key_def => {
+                    method_def.idempotent_key = self.parse_idempotent_key_def(content_pair, context)?;
+                }
+                Rule::produces_def => {
+                    method_def.produces = self.parse_media_type_def(content_pair, "produces", context)?;
+                }
+                Rule::accepts_def => {
+                    method_def.accepts = self.parse_media_type_def(content_pair, "accepts", context)?;
+                }
+                Rule::computed_def => {
+                    method_def.computed = self.parse_computed_def(content_pair, context)?;
+                }
                 Rule::params_def => {
                     self.parse_params_def(content_pair, method_def, context)?;
                 }
@@ -696,6 +969,401 @@ impl ZMLParserWrapper {
         Ok(None)
     }
 
+    /// Parse a duration attribute (`timeout: 30s`, `cache_ttl: 5m`) into milliseconds
+    fn parse_duration(
+        &self,
+        pair: pest::iterators::Pair<Rule>,
+        attribute: &str,
+        context: &ParseContext,
+    ) -> Result<Option<u64>, ParseError> {
+        let Some(duration_pair) = pair.into_inner().next() else {
+            return Ok(None);
+        };
+        let text = duration_pair.as_str();
+        let (digits, multiplier) = if let Some(ms) = text.strip_suffix("ms") {
+            (ms, 1)
+        } else if let Some(s) = text.strip_suffix('s') {
+            (s, 1_000)
+        } else if let Some(m) = text.strip_suffix('m') {
+            (m, 60_000)
+        } else {
+            (text, 1)
+        };
+        let amount = digits
+            .parse::<u64>()
+            .map_err(|_| context.semantic_error(format!("Invalid {} '{}'", attribute, text)))?;
+        if amount == 0 {
+            return Err(context.semantic_error(format!("`{}` must be greater than zero", attribute)));
+        }
+        let millis = amount
+            .checked_mul(multiplier)
+            .ok_or_else(|| context.semantic_error(format!("`{}` is too long: '{}'", attribute, text)))?;
+        Ok(Some(millis))
+    }
+
+    /// Parse retry policy (`retry { attempts: 3, backoff: exponential, on: [502, 503] }`)
+    fn parse_retry_def(
+        &self,
+        pair: pest::iterators::Pair<Rule>,
+        context: &ParseContext,
+    ) -> Result<RetryPolicy, ParseError> {
+        let mut policy = RetryPolicy::default();
+
+        for field in pair.into_inner().filter(|p| p.as_rule() == Rule::retry_field) {
+            for field_pair in field.into_inner() {
+                match field_pair.as_rule() {
+                    Rule::retry_attempts_def => {
+                        if let Some(value_pair) = field_pair.into_inner().next() {
+                            policy.attempts = value_pair
+                                .as_str()
+                                .parse::<u32>()
+                                .ok()
+                                .filter(|attempts| *attempts > 0)
+                                .ok_or_else(|| {
+                                    context.semantic_error(format!(
+                                        "Retry attempts must be a positive integer, got '{}'",
+                                        value_pair.as_str()
+                                    ))
+                                })?;
+                        }
+                    }
+                    Rule::retry_backoff_def => {
+                        if let Some(value_pair) = field_pair.into_inner().next() {
+                            policy.backoff = BackoffStrategy::from_name(value_pair.as_str()).ok_or_else(|| {
+                                context.semantic_error(format!(
+                                    "Unknown retry backoff '{}' (expected fixed, linear or exponential)",
+                                    value_pair.as_str()
+                                ))
+                            })?;
+                        }
+                    }
+                    Rule::retry_on_def => {
+                        for status_pair in field_pair.into_inner() {
+                            let status = status_pair
+                                .as_str()
+                                .parse::<u16>()
+                                .ok()
+                                .filter(|code| (100..=599).contains(code))
+                                .ok_or_else(|| {
+                                    context.semantic_error(format!(
+                                        "Invalid retry status code '{}'",
+                                        status_pair.as_str()
+                                    ))
+                                })?;
+                            policy.on.push(status);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(policy)
+    }
+
+    /// Parse pagination (`paginate { style: cursor, param: "cursor", next_cursor: "meta.next" }`)
+    fn parse_paginate_def(
+        &self,
+        pair: pest::iterators::Pair<Rule>,
+        context: &ParseContext,
+    ) -> Result<Pagination, ParseError> {
+        let mut pagination = Pagination::default();
+
+        for field in pair.into_inner().filter(|p| p.as_rule() == Rule::paginate_field) {
+            for field_pair in field.into_inner() {
+                match field_pair.as_rule() {
+                    Rule::paginate_style_def => {
+                        if let Some(value_pair) = field_pair.into_inner().next() {
+                            pagination.style = PaginationStyle::from_name(value_pair.as_str()).ok_or_else(|| {
+                                context.semantic_error(format!(
+                                    "Unknown pagination style '{}' (expected page, offset or cursor)",
+                                    value_pair.as_str()
+                                ))
+                            })?;
+                        }
+                    }
+                    Rule::paginate_param_def => {
+                        if let Some(param) = self.parse_string_content(field_pair)? {
+                            pagination.param = param;
+                        }
+                    }
+                    Rule::paginate_size_param_def => {
+                        pagination.size_param = self.parse_string_content(field_pair)?.filter(|param| !param.is_empty());
+                    }
+                    Rule::paginate_items_def => {
+                        pagination.items = self.parse_string_content(field_pair)?.filter(|path| !path.is_empty());
+                    }
+                    Rule::paginate_next_cursor_def => {
+                        pagination.next_cursor = self.parse_string_content(field_pair)?.filter(|path| !path.is_empty());
+                    }
+                    Rule::paginate_max_pages_def => {
+                        if let Some(value_pair) = field_pair.into_inner().next() {
+                            let max_pages = value_pair.as_str().parse::<u32>().ok().filter(|pages| *pages > 0);
+                            pagination.max_pages = Some(max_pages.ok_or_else(|| {
+                                context.semantic_error(format!(
+                                    "Pagination max_pages must be a positive integer, got '{}'",
+                                    value_pair.as_str()
+                                ))
+                            })?);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        if pagination.style == PaginationStyle::Cursor && pagination.next_cursor.is_none() {
+            return Err(context.semantic_error("Cursor pagination requires `next_cursor`, the path of the next cursor in the response".to_string()));
+        }
+        Ok(pagination)
+    }
+
+    /// Parse an XML request body (`xml_body { template: "...", soap_action: "...", extract: "//Result" }`)
+    fn parse_xml_body_def(
+        &self,
+        pair: pest::iterators::Pair<Rule>,
+        context: &ParseContext,
+    ) -> Result<XmlBody, ParseError> {
+        let mut xml_body = XmlBody { template: String::new(), content_type: None, soap_action: None, extract: None };
+
+        for field in pair.into_inner().filter(|p| p.as_rule() == Rule::xml_body_field) {
+            for field_pair in field.into_inner() {
+                match field_pair.as_rule() {
+                    Rule::xml_template_def => {
+                        xml_body.template = self.parse_string_content(field_pair)?.unwrap_or_default();
+                    }
+                    Rule::xml_content_type_def => {
+                        xml_body.content_type = self.parse_string_content(field_pair)?.filter(|value| !value.is_empty());
+                    }
+                    Rule::xml_soap_action_def => {
+                        xml_body.soap_action = self.parse_string_content(field_pair)?;
+                    }
+                    Rule::xml_extract_def => {
+                        xml_body.extract = self.parse_string_content(field_pair)?.filter(|path| !path.is_empty());
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        if xml_body.template.trim().is_empty() {
+            return Err(context.semantic_error("An xml_body requires a `template`, the XML sent as the request body".to_string()));
+        }
+        if let Some(extract) = &xml_body.extract {
+            if !extract.starts_with('/') {
+                return Err(context.semantic_error(format!(
+                    "xml_body extract '{}' must be an absolute XPath starting with '/' or '//'",
+                    extract
+                )));
+            }
+        }
+        Ok(xml_body)
+    }
+
+    /// Parse a workflow into a method calling the steps in turn (`workflow name { params { ... } step ... result: ... }`)
+    fn parse_workflow_def(
+        &self,
+        pair: pest::iterators::Pair<Rule>,
+        context: &ParseContext,
+    ) -> Result<MethodDef, ParseError> {
+        let mut inner_pairs = pair.into_inner();
+        let mut method_def = MethodDef {
+            name: inner_pairs.next().map(|name_pair| name_pair.as_str().to_string()).unwrap_or_default(),
+            description: None,
+            http_method: HttpMethod::Post,
+            uri: String::new(),
+            access_level: AccessLevel::Public,
+            rate_limit: None,
+            timeout_ms: None,
+            retry: None,
+            cache_ttl_ms: None,
+            paginate: None,
+            xml_body: None,
+            idempotent_key: None,
+            produces: None,
+            accepts: None,
+            computed: Vec::new(),
+            workflow: None,
+            params: HashMap::new(),
+            response: TypeExpr::Any,
+        };
+        let mut workflow = Workflow { steps: Vec::new(), result: None };
+
+        for content in inner_pairs.filter(|p| p.as_rule() == Rule::workflow_content) {
+            for content_pair in content.into_inner() {
+                match content_pair.as_rule() {
+                    Rule::description_def => {
+                        method_def.description = self.parse_string_content(content_pair)?;
+                    }
+                    Rule::params_def => {
+                        self.parse_params_def(content_pair, &mut method_def, context)?;
+                    }
+                    Rule::workflow_step_def => {
+                        let step = self.parse_workflow_step_def(content_pair, context)?;
+                        if step.name == "params" || workflow.steps.iter().any(|s| s.name == step.name) {
+                            return Err(context.semantic_error(format!(
+                                "Workflow '{}' step name '{}' is reserved or already used",
+                                method_def.name, step.name
+                            )));
+                        }
+                        workflow.steps.push(step);
+                    }
+                    Rule::workflow_result_def => {
+                        workflow.result = content_pair.into_inner().next().map(|ref_pair| ref_pair.as_str().to_string());
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        if workflow.steps.is_empty() {
+            return Err(context.semantic_error(format!("Workflow '{}' has no steps", method_def.name)));
+        }
+        method_def.workflow = Some(workflow);
+        Ok(method_def)
+    }
+
+    /// Parse a workflow step (`step project { call: create_project, when: params.name, with { name: params.name } }`)
+    fn parse_workflow_step_def(
+        &self,
+        pair: pest::iterators::Pair<Rule>,
+        context: &ParseContext,
+    ) -> Result<WorkflowStep, ParseError> {
+        let mut inner_pairs = pair.into_inner();
+        let mut step = WorkflowStep {
+            name: inner_pairs.next().map(|name_pair| name_pair.as_str().to_string()).unwrap_or_default(),
+            call: String::new(),
+            when: None,
+            args: HashMap::new(),
+        };
+
+        for field in inner_pairs.filter(|p| p.as_rule() == Rule::workflow_step_field) {
+            for field_pair in field.into_inner() {
+                match field_pair.as_rule() {
+                    Rule::workflow_call_def => {
+                        step.call = field_pair.into_inner().next().map(|p| p.as_str().to_string()).unwrap_or_default();
+                    }
+                    Rule::workflow_when_def => {
+                        let mut when_pairs = field_pair.into_inner();
+                        let reference = when_pairs.next().map(|p| p.as_str().to_string()).unwrap_or_default();
+                        let comparison = match (when_pairs.next(), when_pairs.next()) {
+                            (Some(operator), Some(operand)) => {
+                                let comparison = WorkflowComparison::from_name(operator.as_str()).ok_or_else(|| {
+                                    context.syntax_error(format!("Unknown comparison '{}'", operator.as_str()))
+                                })?;
+                                Some((comparison, self.parse_workflow_operand(operand, context)?))
+                            }
+                            _ => None,
+                        };
+                        step.when = Some(WorkflowCondition { reference, comparison });
+                    }
+                    Rule::workflow_with_def => {
+                        for arg in field_pair.into_inner().filter(|p| p.as_rule() == Rule::workflow_arg) {
+                            let mut arg_pairs = arg.into_inner();
+                            if let (Some(name), Some(operand)) = (arg_pairs.next(), arg_pairs.next()) {
+                                step.args.insert(name.as_str().to_string(), self.parse_workflow_operand(operand, context)?);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        if step.call.is_empty() {
+            return Err(context.semantic_error(format!("Workflow step '{}' requires `call`, the method it calls", step.name)));
+        }
+        Ok(step)
+    }
+
+    /// Parse a workflow operand: a number, a string, `true`/`false`/`null` or a reference
+    fn parse_workflow_operand(
+        &self,
+        pair: pest::iterators::Pair<Rule>,
+        context: &ParseContext,
+    ) -> Result<WorkflowValue, ParseError> {
+        let Some(pair) = pair.into_inner().next() else {
+            return Err(context.syntax_error("Workflow operand missing value".to_string()));
+        };
+        let text = pair.as_str();
+        Ok(match pair.as_rule() {
+            Rule::number => WorkflowValue::Literal(match text.parse::<i64>() {
+                Ok(integer) => Value::Integer(integer),
+                Err(_) => Value::Number(text.parse().map_err(|_| context.type_error(format!("Invalid number '{}'", text)))?),
+            }),
+            Rule::string => WorkflowValue::Literal(Value::String(text[1..text.len() - 1].to_string())),
+            _ => match text {
+                "true" => WorkflowValue::Literal(Value::Boolean(true)),
+                "false" => WorkflowValue::Literal(Value::Boolean(false)),
+                "null" => WorkflowValue::Literal(Value::Null),
+                reference => WorkflowValue::Ref(reference.to_string()),
+            },
+        })
+    }
+
+    /// Parse the idempotency key header (`idempotent_key: "Idempotency-Key"`)
+    fn parse_idempotent_key_def(
+        &self,
+        pair: pest::iterators::Pair<Rule>,
+        context: &ParseContext,
+    ) -> Result<Option<String>, ParseError> {
+        let Some(header) = self.parse_string_content(pair)? else {
+            return Ok(None);
+        };
+        if reqwest::header::HeaderName::from_bytes(header.as_bytes()).is_err() {
+            return Err(context.semantic_error(format!("idempotent_key '{}' is not a valid header name", header)));
+        }
+        Ok(Some(header))
+    }
+
+    /// Parse the media type of `produces` or `accepts` (`produces: "text/csv"`); request bodies can
+    /// only be encoded as JSON, XML or form-urlencoded
+    fn parse_media_type_def(
+        &self,
+        pair: pest::iterators::Pair<Rule>,
+        field: &str,
+        context: &ParseContext,
+    ) -> Result<Option<String>, ParseError> {
+        let Some(media_type) = self.parse_string_content(pair)? else {
+            return Ok(None);
+        };
+        let essence = media_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+        let valid = essence.split_once('/').is_some_and(|(kind, subtype)| !kind.is_empty() && !subtype.is_empty())
+            && reqwest::header::HeaderValue::from_str(&media_type).is_ok();
+        if !valid {
+            return Err(context.semantic_error(format!("{} '{}' is not a valid media type", field, media_type)));
+        }
+        if field == "accepts" && body_encoding(&essence).is_none() {
+            return Err(context.semantic_error(format!(
+                "accepts '{}' is not supported; request bodies are sent as JSON, XML or application/x-www-form-urlencoded",
+                media_type
+            )));
+        }
+        Ok(Some(media_type))
+    }
+
+    /// Parse computed response fields (`computed { total_cost = price * quantity }`), one per line
+    fn parse_computed_def(
+        &self,
+        pair: pest::iterators::Pair<Rule>,
+        context: &ParseContext,
+    ) -> Result<Vec<ComputedField>, ParseError> {
+        let mut fields: Vec<ComputedField> = Vec::new();
+        for field_pair in pair.into_inner().filter(|p| p.as_rule() == Rule::computed_field) {
+            let mut inner = field_pair.into_inner();
+            let name = inner.next().map(|p| p.as_str().to_string()).unwrap_or_default();
+            let expression = inner.next().map(|p| p.as_str().trim().to_string()).unwrap_or_default();
+            if fields.iter().any(|field| field.name == name) {
+                return Err(context.semantic_error(format!("Computed field '{}' is declared twice", name)));
+            }
+            if let Err(e) = rhai::Engine::new().compile_expression(&expression) {
+                return Err(context.semantic_error(format!("Computed field '{}' has an invalid expression: {}", name, e)));
+            }
+            fields.push(ComputedField { name, expression });
+        }
+        Ok(fields)
+    }
+
     /// Parse parameter definition
     fn parse_params_def(
         &self,
@@ -720,6 +1388,7 @@ impl ZMLParserWrapper {
         pair: pest::iterators::Pair<Rule>,
         context: &ParseContext,
     ) -> Result<ParamDef, ParseError> {
+        let comment = context.attached_comment(&pair);
         let mut inner_pairs = pair.into_inner();
         let mut param_def = ParamDef {
             name: String::new(),
@@ -727,6 +1396,7 @@ impl ZMLParserWrapper {
             optional: false,
             default_value: None,
             description: None,
+            examples: Vec::new(),
         };
 
         // Parse parameter name
@@ -762,17 +1432,16 @@ impl ZMLParserWrapper {
                         }
                     }
                 }
-                Rule::comment => {
-                    // Parse comment as description
-                    let comment = pair.as_str().trim();
-                    if comment.starts_with("//") {
-                        param_def.description = Some(comment[2..].trim().to_string());
-                    }
+                Rule::example_value => {
+                    param_def.examples = self.parse_example_value(pair, context)?;
                 }
                 _ => {}
             }
         }
 
+        // Use the attached comment as description
+        param_def.description = comment;
+
         Ok(param_def)
     }
 
@@ -813,9 +1482,23 @@ impl ZMLParserWrapper {
                 // template_content can contain module_content rules and method_content
                 for content_pair in pair.into_inner() {
                     match content_pair.as_rule() {
-                        Rule::property_def => {
-                            let (key, value) = self.parse_property_def(content_pair, context)?;
-                            template_def.content.insert(key, value);
+                        Rule::module_content => {
+                            // Only properties are meaningful inside a template
+                            for module_pair in content_pair.into_inner() {
+                                if module_pair.as_rule() == Rule::property_def {
+                                    let (key, value) = self.parse_property_def(module_pair, context)?;
+                                    template_def.content.insert(key, value);
+                                } else {
+                                    self.unknown_construct(
+                                        context,
+                                        format!(
+                                            "Unsupported content in template '{}': {:?}",
+                                            template_def.name,
+                                            module_pair.as_rule()
+                                        ),
+                                    )?;
+                                }
+                            }
                         }
                         Rule::method_content => {
                             // Process method content (description, http_method, uri, access_level, rate_limit, params, response)
@@ -858,6 +1541,84 @@ impl ZMLParserWrapper {
                                             template_def.content.insert("rate_limit".to_string(), Value::Object(obj));
                                         }
                                     }
+                                    Rule::timeout_def => {
+                                        if let Some(timeout_ms) = self.parse_duration(method_pair, "timeout", context)? {
+                                            template_def.content.insert("timeout_ms".to_string(), Value::Integer(timeout_ms as i64));
+                                        }
+                                    }
+                                    Rule::cache_ttl_def => {
+                                        if let Some(ttl_ms) = self.parse_duration(method_pair, "cache_ttl", context)? {
+                                            template_def.content.insert("cache_ttl_ms".to_string(), Value::Integer(ttl_ms as i64));
+                                        }
+                                    }
+                                    Rule::paginate_def => {
+                                        let pagination = self.parse_paginate_def(method_pair, context)?;
+                                        let mut obj = HashMap::new();
+                                        obj.insert("style".to_string(), Value::String(pagination.style.as_str().to_string()));
+                                        obj.insert("param".to_string(), Value::String(pagination.param));
+                                        if let Some(size_param) = pagination.size_param {
+                                            obj.insert("size_param".to_string(), Value::String(size_param));
+                                        }
+                                        if let Some(items) = pagination.items {
+                                            obj.insert("items".to_string(), Value::String(items));
+                                        }
+                                        if let Some(next_cursor) = pagination.next_cursor {
+                                            obj.insert("next_cursor".to_string(), Value::String(next_cursor));
+                                        }
+                                        if let Some(max_pages) = pagination.max_pages {
+                                            obj.insert("max_pages".to_string(), Value::Integer(max_pages as i64));
+                                        }
+                                        template_def.content.insert("paginate".to_string(), Value::Object(obj));
+                                    }
+                                    Rule::xml_body_def => {
+                                        let xml_body = self.parse_xml_body_def(method_pair, context)?;
+                                        let mut obj = HashMap::new();
+                                        obj.insert("template".to_string(), Value::String(xml_body.template));
+                                        if let Some(content_type) = xml_body.content_type {
+                                            obj.insert("content_type".to_string(), Value::String(content_type));
+                                        }
+                                        if let Some(soap_action) = xml_body.soap_action {
+                                            obj.insert("soap_action".to_string(), Value::String(soap_action));
+                                        }
+                                        if let Some(extract) = xml_body.extract {
+                                            obj.insert("extract".to_string(), Value::String(extract));
+                                        }
+                                        template_def.content.insert("xml_body".to_string(), Value::Object(obj));
+                                    }
+                                    Rule::idempotent_key_def => {
+                                        if let Some(header) = self.parse_idempotent_key_def(method_pair, context)? {
+                                            template_def.content.insert("idempotent_key".to_string(), Value::String(header));
+                                        }
+                                    }
+                                    Rule::produces_def => {
+                                        if let Some(media_type) = self.parse_media_type_def(method_pair, "produces", context)? {
+                                            template_def.content.insert("produces".to_string(), Value::String(media_type));
+                                        }
+                                    }
+                                    Rule::accepts_def => {
+                                        if let Some(media_type) = self.parse_media_type_def(method_pair, "accepts", context)? {
+                                            template_def.content.insert("accepts".to_string(), Value::String(media_type));
+                                        }
+                                    }
+                                    Rule::computed_def => {
+                                        let fields = self
+                                            .parse_computed_def(method_pair, context)?
+                                            .into_iter()
+                                            .map(|field| (field.name, Value::String(field.expression)))
+                                            .collect();
+                                        template_def.content.insert("computed".to_string(), Value::Object(fields));
+                                    }
+                                    Rule::retry_def => {
+                                        let retry = self.parse_retry_def(method_pair, context)?;
+                                        let mut obj = HashMap::new();
+                                        obj.insert("attempts".to_string(), Value::Integer(retry.attempts as i64));
+                                        obj.insert("backoff".to_string(), Value::String(retry.backoff.as_str().to_string()));
+                                        obj.insert(
+                                            "on".to_string(),
+                                            Value::Array(retry.on.iter().map(|code| Value::Integer(*code as i64)).collect()),
+                                        );
+                                        template_def.content.insert("retry".to_string(), Value::Object(obj));
+                                    }
                                     Rule::params_def => {
                                         // Parameter definitions require special handling
                                         let params = self.parse_params_template(method_pair, context)?;
@@ -867,14 +1628,20 @@ impl ZMLParserWrapper {
                                         let ty = self.parse_response_def(method_pair, context)?;
                                         template_def.content.insert("response".to_string(), Value::String(ty.to_string_repr()));
                                     }
-                                    _ => {
-                                        // Unknown template method content rule - ignored
+                                    rule => {
+                                        self.unknown_construct(
+                                            context,
+                                            format!("Unknown method content rule in template '{}': {:?}", template_def.name, rule),
+                                        )?;
                                     }
                                 }
                             }
                         }
-                        _ => {
-                            // Unknown template content rule - ignored
+                        rule => {
+                            self.unknown_construct(
+                                context,
+                                format!("Unsupported content in template '{}': {:?}", template_def.name, rule),
+                            )?;
                         }
                     }
                 }
@@ -884,6 +1651,54 @@ impl ZMLParserWrapper {
         Ok(template_def)
     }
 
+    /// Parse prompt definition
+    fn parse_prompt_def(
+        &self,
+        pair: pest::iterators::Pair<Rule>,
+        context: &ParseContext,
+    ) -> Result<PromptDef, ParseError> {
+        let mut inner_pairs = pair.into_inner();
+        let mut prompt_def = PromptDef {
+            name: String::new(),
+            description: None,
+            arguments: HashMap::new(),
+            template: String::new(),
+        };
+
+        // Parse prompt name
+        if let Some(name_pair) = inner_pairs.next() {
+            prompt_def.name = name_pair.as_str().to_string();
+        }
+
+        // Parse prompt content
+        for pair in inner_pairs {
+            if pair.as_rule() != Rule::prompt_content {
+                continue;
+            }
+            for content_pair in pair.into_inner() {
+                match content_pair.as_rule() {
+                    Rule::description_def => {
+                        prompt_def.description = self.parse_string_content(content_pair)?;
+                    }
+                    Rule::prompt_arguments_def => {
+                        for arg_pair in content_pair.into_inner() {
+                            if arg_pair.as_rule() == Rule::field_def {
+                                let field_def = self.parse_field_def(arg_pair, context)?;
+                                prompt_def.arguments.insert(field_def.name.clone(), field_def);
+                            }
+                        }
+                    }
+                    Rule::prompt_template_def => {
+                        prompt_def.template = self.parse_string_content(content_pair)?.unwrap_or_default();
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(prompt_def)
+    }
+
     /// Parse property definition
     fn parse_property_def(
         &self,
@@ -939,6 +1754,10 @@ impl ZMLParserWrapper {
                     param_obj.insert("description".to_string(), Value::String(desc));
                 }
 
+                if !param_def.examples.is_empty() {
+                    param_obj.insert("examples".to_string(), Value::Array(param_def.examples));
+                }
+
                 params.insert(param_def.name, Value::Object(param_obj));
             }
         }
@@ -995,6 +1814,116 @@ impl ZMLParserWrapper {
         }
     }
 
+    /// Record source locations of a definition and its named children (params, fields, ...)
+    fn record_locations(
+        &self,
+        module: &mut Module,
+        path: &str,
+        child_key: &str,
+        child_rule: Rule,
+        pair: &pest::iterators::Pair<Rule>,
+        context: &ParseContext,
+    ) {
+        module.source_map.insert(path.to_string(), context.location(pair));
+
+        // Do not descend into matched children so nested object fields are not recorded
+        let mut pending: Vec<_> = pair.clone().into_inner().collect();
+        while let Some(child) = pending.pop() {
+            if child.as_rule() != child_rule {
+                pending.extend(child.into_inner());
+                continue;
+            }
+            if let Some(name_pair) = child.clone().into_inner().next() {
+                module.source_map.insert(
+                    format!("{}.{}.{}", path, child_key, name_pair.as_str()),
+                    context.location(&child),
+                );
+            }
+        }
+    }
+
+    /// Parse CRUD resource shorthand
+    fn parse_crud_resource_def(
+        &self,
+        pair: pest::iterators::Pair<Rule>,
+        context: &ParseContext,
+    ) -> Result<CrudResourceDef, ParseError> {
+        let mut crud_def = CrudResourceDef {
+            name: String::new(),
+            uri: String::new(),
+            entity: String::new(),
+            excluded: Vec::new(),
+            location: Some(context.location(&pair)),
+        };
+
+        for pair in pair.into_inner() {
+            match pair.as_rule() {
+                Rule::identifier if crud_def.name.is_empty() => {
+                    crud_def.name = pair.as_str().to_string();
+                }
+                Rule::identifier => {
+                    crud_def.entity = pair.as_str().to_string();
+                }
+                Rule::string => {
+                    let content = pair.as_str();
+                    crud_def.uri = content[1..content.len() - 1].to_string();
+                }
+                Rule::crud_without => {
+                    for op_pair in pair.into_inner() {
+                        let op = CrudOperation::from_name(op_pair.as_str()).ok_or_else(|| {
+                            context.syntax_error(format!("Unknown CRUD operation '{}'", op_pair.as_str()))
+                        })?;
+                        if !crud_def.excluded.contains(&op) {
+                            crud_def.excluded.push(op);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(crud_def)
+    }
+
+    /// Expand CRUD shorthands into methods; explicitly declared methods take precedence
+    fn expand_crud_resources(
+        &self,
+        module: &mut Module,
+        crud_resources: Vec<CrudResourceDef>,
+        context: &ParseContext,
+    ) -> Result<(), ParseError> {
+        for crud_def in crud_resources {
+            let entity = module.types.get(&crud_def.entity).ok_or_else(|| {
+                context.reference_error(format!(
+                    "CRUD resource '{}' references non-existent type '{}'",
+                    crud_def.name, crud_def.entity
+                ))
+            })?;
+            if crud_def.operations().is_empty() {
+                return Err(context.semantic_error(format!(
+                    "CRUD resource '{}' excludes every operation",
+                    crud_def.name
+                )));
+            }
+
+            for method_def in crud_def.expand(entity) {
+                if !module.methods.contains_key(&method_def.name) {
+                    if let Some(location) = &crud_def.location {
+                        let path = format!("methods.{}", method_def.name);
+                        for param_name in method_def.params.keys() {
+                            module
+                                .source_map
+                                .insert(format!("{}.params.{}", path, param_name), location.clone());
+                        }
+                        module.source_map.insert(path, location.clone());
+                    }
+                    module.methods.insert(method_def.name.clone(), method_def);
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Parse resource definition
     fn parse_resource_def(
         &self,
@@ -1014,8 +1943,11 @@ impl ZMLParserWrapper {
             resource_def.name = name_pair.as_str().to_string();
         }
 
-        // Parse resource content
-        for content_pair in inner_pairs {
+        // Parse resource content; each item is wrapped in `resource_content`
+        let content_pairs = inner_pairs
+            .filter(|pair| pair.as_rule() == Rule::resource_content)
+            .flat_map(|pair| pair.into_inner());
+        for content_pair in content_pairs {
             match content_pair.as_rule() {
                 Rule::type_def => {
                     // Parse resource type
@@ -1040,27 +1972,33 @@ impl ZMLParserWrapper {
                         let key = key_pair.as_str();
                         let value = self.parse_property_value(value_pair, context)?;
                         
-                        match key {
-                            "type" => {
-                                if let Value::String(s) = value {
-                                    match s.as_str() {
-                                        "collection" => resource_def.resource_type = ResourceType::Collection,
-                                        "entity" => resource_def.resource_type = ResourceType::Entity,
-                                        _ => {}
-                                    }
+                        let applied = match (key, value) {
+                            ("type", Value::String(s)) => match s.as_str() {
+                                "collection" => {
+                                    resource_def.resource_type = ResourceType::Collection;
+                                    true
                                 }
-                            }
-                            "uri" => {
-                                if let Value::String(s) = value {
-                                    resource_def.uri = s;
+                                "entity" => {
+                                    resource_def.resource_type = ResourceType::Entity;
+                                    true
                                 }
+                                _ => false,
+                            },
+                            ("uri", Value::String(s)) => {
+                                resource_def.uri = s;
+                                true
                             }
-                            "description" => {
-                                if let Value::String(s) = value {
-                                    resource_def.description = Some(s);
-                                }
+                            ("description", Value::String(s)) => {
+                                resource_def.description = Some(s);
+                                true
                             }
-                            _ => {}
+                            _ => false,
+                        };
+                        if !applied {
+                            self.unknown_construct(
+                                context,
+                                format!("Unknown or invalid property '{}' in resource '{}'", key, resource_def.name),
+                            )?;
                         }
                     }
                 }
@@ -1071,41 +2009,34 @@ impl ZMLParserWrapper {
         Ok(resource_def)
     }
 
-    /// Set module properties
-    fn set_module_property(&self, module: &mut Module, key: &str, value: Value) {
-        match key {
-            "version" => {
-                if let Value::String(s) = value {
-                    module.version = Some(s);
-                }
-            }
-            "description" => {
-                if let Value::String(s) = value {
-                    module.description = Some(s);
-                }
-            }
-            "enabled" => {
-                if let Value::Boolean(b) = value {
-                    module.enabled = Some(b);
-                }
+    /// Set module properties; returns false for unknown keys or values of the wrong type
+    fn set_module_property(&self, module: &mut Module, key: &str, value: Value) -> bool {
+        match (key, value) {
+            ("version", Value::String(s)) => module.version = Some(s),
+            ("description", Value::String(s)) => module.description = Some(s),
+            ("enabled", Value::Boolean(b)) => module.enabled = Some(b),
+            ("access_level", Value::String(s)) => {
+                module.access_level = match s.as_str() {
+                    "public" => Some(AccessLevel::Public),
+                    "private" => Some(AccessLevel::Private),
+                    "internal" => Some(AccessLevel::Internal),
+                    _ => return false,
+                };
             }
-            "access_level" => {
-                if let Value::String(s) = value {
-                    module.access_level = match s.as_str() {
-                        "public" => Some(AccessLevel::Public),
-                        "private" => Some(AccessLevel::Private),
-                        "internal" => Some(AccessLevel::Internal),
-                        _ => None,
-                    };
-                }
-            }
-            "category" => {
-                if let Value::String(s) = value {
-                    module.category = Some(s);
-                }
-            }
-            _ => {}
+            ("category", Value::String(s)) => module.category = Some(s),
+            ("backend", Value::String(s)) => module.backend = Some(s),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Report content the parser does not recognize: an error in strict mode, a debug trace otherwise
+    fn unknown_construct(&self, context: &ParseContext, message: String) -> Result<(), ParseError> {
+        if self.options.strict {
+            return Err(context.syntax_error(message));
         }
+        debug!("Ignoring unrecognized ZML content: {}", message);
+        Ok(())
     }
 
     /// Validate module
@@ -1129,6 +2060,83 @@ impl ZMLParserWrapper {
             }
         }
 
+        // Validate custom scalars
+        for (scalar_name, scalar_def) in &module.scalars {
+            if !scalar_def.base.is_basic() {
+                return Err(context.type_error(format!(
+                    "Scalar '{}' must use a basic base type",
+                    scalar_name
+                )));
+            }
+            if let Some(encoding) = &scalar_def.encode {
+                if encoding.wire_type().to_string_repr() != scalar_def.base.to_string_repr() {
+                    return Err(context.type_error(format!(
+                        "Scalar '{}' encoding '{}' requires base type '{}'",
+                        scalar_name,
+                        encoding.as_str(),
+                        encoding.wire_type().to_string_repr()
+                    )));
+                }
+            }
+        }
+
+        // Validate resource URI template variables
+        for (resource_name, resource_def) in &module.resources {
+            for variable in resource_def.uri_variables() {
+                let valid = !variable.is_empty()
+                    && variable.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+                if !valid {
+                    return Err(context.syntax_error(format!(
+                        "Resource '{}' has invalid URI variable '{{{}}}'",
+                        resource_name, variable
+                    )));
+                }
+            }
+        }
+
+        // Validate prompt template placeholders
+        for (prompt_name, prompt_def) in &module.prompts {
+            for variable in prompt_def.template_variables() {
+                if !prompt_def.arguments.contains_key(&variable) {
+                    return Err(context.reference_error(format!(
+                        "Prompt '{}' template references undeclared argument '{}'",
+                        prompt_name, variable
+                    )));
+                }
+            }
+        }
+
+        // Validate workflow steps: each calls a plain method and references the parameters or an earlier step
+        for (workflow_name, workflow) in module.methods.iter().filter_map(|(name, m)| Some((name, m.workflow.as_ref()?))) {
+            let mut known = vec!["params"];
+            for step in &workflow.steps {
+                if !module.methods.get(&step.call).is_some_and(|m| m.workflow.is_none()) {
+                    return Err(context.reference_error(format!(
+                        "Workflow '{}' step '{}' calls '{}', which is not a method of the module",
+                        workflow_name, step.name, step.call
+                    )));
+                }
+                for reference in step.references() {
+                    let root = reference.split('.').next().unwrap_or_default();
+                    if !known.contains(&root) {
+                        return Err(context.reference_error(format!(
+                            "Workflow '{}' step '{}' references '{}', which is neither `params` nor an earlier step",
+                            workflow_name, step.name, reference
+                        )));
+                    }
+                }
+                known.push(&step.name);
+            }
+            if let Some(result) = &workflow.result {
+                if !known.contains(&result.split('.').next().unwrap_or_default()) {
+                    return Err(context.reference_error(format!(
+                        "Workflow '{}' result references unknown step '{}'",
+                        workflow_name, result
+                    )));
+                }
+            }
+        }
+
         // Validate method parameter types
         for (_, method_def) in &module.methods {
             for (_, param_def) in &method_def.params {
@@ -1192,6 +2200,88 @@ impl ZMLParserWrapper {
     }
 }
 
+/// Collapse escaped backslashes (`\\` -> `\`) in a raw string literal
+fn unescape_backslashes(raw: &str) -> String {
+    raw.replace("\\\\", "\\")
+}
+
+/// Text of the comment block on the lines directly above `offset`.
+/// Only whitespace may precede the definition on its line; a blank line or code ends the block.
+fn leading_comment(source: &str, offset: usize) -> Option<String> {
+    let line_start = source[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    if !source[line_start..offset].trim().is_empty() {
+        return None;
+    }
+
+    let mut lines = source[..line_start].lines().rev();
+    let mut blocks = Vec::new();
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("//") {
+            blocks.push(comment_text(trimmed));
+        } else if trimmed.ends_with("*/") {
+            // Walk back to the line opening the block comment
+            let mut block_lines = vec![trimmed];
+            while !block_lines[block_lines.len() - 1].contains("/*") {
+                block_lines.push(lines.next()?.trim());
+            }
+            if !block_lines[block_lines.len() - 1].starts_with("/*") {
+                break;
+            }
+            block_lines.reverse();
+            blocks.push(comment_text(&block_lines.join("\n")));
+        } else {
+            break;
+        }
+    }
+
+    blocks.reverse();
+    let text = blocks.join("\n").trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+/// Text of a comment that follows `offset` on the same line (ignoring `//` inside strings)
+fn trailing_comment(source: &str, offset: usize) -> Option<String> {
+    let line_end = source[offset..].find('\n').map(|i| offset + i).unwrap_or(source.len());
+    let bytes = source[offset..line_end].as_bytes();
+    let mut in_string = false;
+    for (i, byte) in bytes.iter().enumerate() {
+        match byte {
+            b'"' => in_string = !in_string,
+            b'/' if !in_string && matches!(bytes.get(i + 1), Some(b'/') | Some(b'*')) => {
+                let start = offset + i;
+                let comment = if bytes[i + 1] == b'*' {
+                    // A block comment may continue past this line
+                    let end = source[start..].find("*/").map(|e| start + e + 2)?;
+                    &source[start..end]
+                } else {
+                    &source[start..line_end]
+                };
+                let text = comment_text(comment);
+                return (!text.is_empty()).then_some(text);
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Strip comment markers (`//`, `///`, `/* */` and leading `*`) from a comment
+fn comment_text(raw: &str) -> String {
+    let raw = raw.trim();
+    match raw.strip_prefix("/*") {
+        Some(body) => body
+            .strip_suffix("*/")
+            .unwrap_or(body)
+            .lines()
+            .map(|line| line.trim().trim_start_matches('*').trim())
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        None => raw.trim_start_matches('/').trim().to_string(),
+    }
+}
+
 impl Default for ZMLParserWrapper {
     fn default() -> Self {
         Self::new()
@@ -1235,6 +2325,26 @@ module UserModule {
         assert!(module.types.contains_key("User"));
     }
 
+    #[test]
+    fn test_parse_idempotent_key() {
+        let source = r#"
+module Orders {
+    method create_order {
+        http_method: POST
+        uri: "/orders"
+        idempotent_key: "Idempotency-Key"
+        response: any
+    }
+}
+"#;
+        let module = ZMLParserWrapper::new().parse(source).unwrap();
+        assert_eq!(module.methods["create_order"].idempotent_key.as_deref(), Some("Idempotency-Key"));
+
+        let error = ZMLParserWrapper::new().parse(&source.replace("POST", "GET")).unwrap_err();
+        assert!(error.to_string().contains("must use POST, PUT or PATCH"), "{}", error);
+        assert!(ZMLParserWrapper::new().parse(&source.replace("Idempotency-Key", "Idempotency Key")).is_err());
+    }
+
     #[test]
     fn test_parse_type_module() {
         let source = r#"
@@ -0,0 +1,59 @@
+// ZML module alias test suite
+use mcp_any_rest::zml::{process_zml, ZMLParserWrapper};
+
+const ALIASED_MODULE: &str = r#"
+module Billing as bill {
+    method createInvoice {
+        http_method: POST
+        uri: "/invoices"
+        response: any
+    }
+}
+"#;
+
+#[test]
+fn test_module_alias_parsed() {
+    let mut parser = ZMLParserWrapper::new();
+    let module = parser.parse(ALIASED_MODULE).expect("Aliased module should parse");
+    assert_eq!(module.name, "Billing");
+    assert_eq!(module.alias.as_deref(), Some("bill"));
+    assert_eq!(module.tool_prefix(), "bill");
+    assert_eq!(module.tool_name("createInvoice"), "bill_createInvoice");
+}
+
+#[test]
+fn test_module_without_alias_uses_name() {
+    let source = r#"
+module Billing {
+    method createInvoice {
+        http_method: POST
+        uri: "/invoices"
+        response: any
+    }
+}
+"#;
+    let mut parser = ZMLParserWrapper::new();
+    let module = parser.parse(source).unwrap();
+    assert!(module.alias.is_none());
+    assert_eq!(module.tool_name("createInvoice"), "Billing_createInvoice");
+}
+
+#[test]
+fn test_module_alias_compiled_to_json() {
+    let json = process_zml(ALIASED_MODULE).expect("Aliased module should compile");
+    assert_eq!(json["alias"], "bill");
+    assert_eq!(json["methods"]["createInvoice"]["tool_name"], "bill_createInvoice");
+}
+
+#[test]
+fn test_module_alias_with_extends() {
+    let source = r#"
+module AdminBilling as admin_bill extends Billing {
+    version: "1.0.0"
+}
+"#;
+    let mut parser = ZMLParserWrapper::new();
+    let module = parser.parse(source).expect("Alias followed by extends should parse");
+    assert_eq!(module.alias.as_deref(), Some("admin_bill"));
+    assert_eq!(module.extends.as_deref(), Some("Billing"));
+}
@@ -0,0 +1,91 @@
+// ZML comment and description test suite
+use mcp_any_rest::zml::ZMLParserWrapper;
+
+const DOCUMENTED_MODULE: &str = r#"
+/// User management
+module UserModule {
+    /*
+     * A registered user.
+     * Created on sign-up.
+     */
+    type User {
+        id: integer // Unique id
+        // Display name
+        name: string
+        bio: string = "a // b"
+    }
+
+    enum Status {
+        ACTIVE = 1 // Can log in
+        /* Blocked by an admin */
+        BLOCKED = 2
+    }
+
+    // Look up a user by id
+    method getUser {
+        http_method: GET
+        uri: "/users/{id}"
+        params {
+            id: integer /* Path id */
+        }
+        response: User
+    }
+
+    // Not attached: separated by a blank line
+
+    method listUsers {
+        description: "Explicit description"
+        http_method: GET
+        uri: "/users"
+        response: array<User>
+    }
+}
+"#;
+
+#[test]
+fn test_leading_comments_become_descriptions() {
+    let mut parser = ZMLParserWrapper::new();
+    let module = parser.parse(DOCUMENTED_MODULE).expect("Documented module should parse");
+
+    assert_eq!(module.description.as_deref(), Some("User management"));
+    assert_eq!(module.types["User"].description.as_deref(), Some("A registered user.\nCreated on sign-up."));
+    assert_eq!(module.methods["getUser"].description.as_deref(), Some("Look up a user by id"));
+    assert_eq!(module.methods["listUsers"].description.as_deref(), Some("Explicit description"));
+}
+
+#[test]
+fn test_field_param_and_enum_value_comments() {
+    let mut parser = ZMLParserWrapper::new();
+    let module = parser.parse(DOCUMENTED_MODULE).unwrap();
+
+    let user = &module.types["User"];
+    assert_eq!(user.fields["id"].description.as_deref(), Some("Unique id"));
+    assert_eq!(user.fields["name"].description.as_deref(), Some("Display name"));
+    assert!(user.fields["bio"].description.is_none(), "`//` inside a string is not a comment");
+
+    let status = &module.enums["Status"];
+    assert_eq!(status.values["ACTIVE"].description.as_deref(), Some("Can log in"));
+    assert_eq!(status.values["BLOCKED"].description.as_deref(), Some("Blocked by an admin"));
+
+    assert_eq!(module.methods["getUser"].params["id"].description.as_deref(), Some("Path id"));
+}
+
+#[test]
+fn test_block_comments_between_definitions() {
+    let source = r#"
+module CommentModule {
+    /* leading */ type A { x: integer }
+    /*
+       spanning
+       several lines
+    */
+
+    type B { y: string /* inline */ }
+}
+"#;
+    let mut parser = ZMLParserWrapper::new();
+    let module = parser.parse(source).expect("Block comments should parse cleanly");
+    assert!(module.types.contains_key("A"));
+    assert!(module.types["B"].description.is_none());
+    assert_eq!(module.types["B"].fields["y"].description.as_deref(), Some("inline"));
+}
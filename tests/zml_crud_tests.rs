@@ -0,0 +1,97 @@
+// ZML CRUD resource shorthand test suite
+use mcp_any_rest::zml::{process_zml, HttpMethod, ZMLParserWrapper};
+
+const CRUD_MODULE: &str = r#"
+module UserModule {
+    type User {
+        id: integer
+        name: string
+        email: string?
+    }
+
+    resource crud Users at "/users" of User
+}
+"#;
+
+#[test]
+fn test_crud_expands_to_standard_methods() {
+    let mut parser = ZMLParserWrapper::new();
+    let module = parser.parse(CRUD_MODULE).expect("CRUD module should parse");
+
+    let mut names: Vec<&String> = module.methods.keys().collect();
+    names.sort();
+    assert_eq!(names, ["createUser", "deleteUser", "getUser", "listUsers", "updateUser"]);
+
+    let list = &module.methods["listUsers"];
+    assert_eq!(list.http_method, HttpMethod::Get);
+    assert_eq!(list.uri, "/users");
+    assert!(list.params["page"].optional);
+    assert!(list.params.contains_key("page_size"));
+
+    let create = &module.methods["createUser"];
+    assert_eq!(create.http_method, HttpMethod::Post);
+    assert!(!create.params.contains_key("id"));
+    assert!(!create.params["name"].optional);
+
+    let update = &module.methods["updateUser"];
+    assert_eq!(update.http_method, HttpMethod::Patch);
+    assert_eq!(update.uri, "/users/{id}");
+    assert!(update.params["name"].optional);
+    assert!(!update.params["id"].optional);
+}
+
+#[test]
+fn test_crud_without_excludes_operations() {
+    let source = r#"
+module UserModule {
+    type User {
+        id: string
+        name: string
+    }
+
+    resource crud Users at "/users" of User without delete, update
+}
+"#;
+    let json = process_zml(source).expect("CRUD module should compile");
+    let methods = json["methods"].as_object().unwrap();
+    assert_eq!(methods.len(), 3);
+    assert!(!methods.contains_key("deleteUser"));
+    assert_eq!(methods["getUser"]["params"]["id"]["type"], "string");
+}
+
+#[test]
+fn test_crud_explicit_method_takes_precedence() {
+    let source = r#"
+module UserModule {
+    type User {
+        id: integer
+        name: string
+    }
+
+    resource crud Users at "/users" of User
+
+    method getUser {
+        description: "Custom lookup"
+        http_method: GET
+        uri: "/users/lookup/{id}"
+        params {
+            id: integer
+        }
+        response: User
+    }
+}
+"#;
+    let mut parser = ZMLParserWrapper::new();
+    let module = parser.parse(source).expect("CRUD module should parse");
+    assert_eq!(module.methods["getUser"].uri, "/users/lookup/{id}");
+}
+
+#[test]
+fn test_crud_unknown_entity_rejected() {
+    let source = r#"
+module UserModule {
+    resource crud Users at "/users" of Missing
+}
+"#;
+    assert!(process_zml(source).is_err(), "Unknown entity type should be rejected");
+}
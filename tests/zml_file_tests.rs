@@ -0,0 +1,172 @@
+// ZML file type test suite
+use mcp_any_rest::services::auth_service::MultipartField;
+use mcp_any_rest::services::dynamic_service::{
+    build_endpoint_zml, build_input_schema_zml, build_multipart_fields_zml,
+};
+use mcp_any_rest::zml::{process_zml, TypeExpr, ZMLParserWrapper};
+use serde_json::json;
+use std::collections::HashMap;
+use std::io::Write;
+
+const UPLOAD_MODULE: &str = r#"
+module UploadModule {
+    method uploadAttachment {
+        http_method: POST
+        uri: "/bugs/{bugId}/files"
+        params {
+            bugId: integer
+            attachment: file
+            title: string?
+        }
+        response: any
+    }
+}
+"#;
+
+#[test]
+fn test_file_type_parsed_and_compiled() {
+    let mut parser = ZMLParserWrapper::new();
+    let module = parser.parse(UPLOAD_MODULE).expect("Upload module should parse");
+    let method = &module.methods["uploadAttachment"];
+    assert!(matches!(method.params["attachment"].type_expr, TypeExpr::File));
+    assert!(method.has_file_params());
+
+    let json = process_zml(UPLOAD_MODULE).expect("Upload module should compile");
+    assert_eq!(json["methods"]["uploadAttachment"]["params"]["attachment"]["type"], "file");
+}
+
+#[test]
+fn test_file_type_input_schema() {
+    let mut parser = ZMLParserWrapper::new();
+    let module = parser.parse(UPLOAD_MODULE).unwrap();
+    let schema = build_input_schema_zml(&module.methods["uploadAttachment"], &module, None);
+    let any_of = &schema["properties"]["attachment"]["anyOf"];
+    assert_eq!(any_of[0]["type"], "string");
+    assert_eq!(any_of[0]["contentEncoding"], "base64");
+    assert_eq!(any_of[1]["type"], "object");
+}
+
+#[test]
+fn test_file_params_not_in_query() {
+    let mut parser = ZMLParserWrapper::new();
+    let module = parser.parse(UPLOAD_MODULE).unwrap();
+    let method = &module.methods["uploadAttachment"];
+
+    let mut params = HashMap::new();
+    params.insert("bugId".to_string(), json!(7));
+    params.insert("attachment".to_string(), json!("aGVsbG8="));
+    let endpoint = build_endpoint_zml(method, &params).unwrap();
+    assert_eq!(endpoint, "/bugs/7/files");
+}
+
+#[test]
+fn test_multipart_fields_from_base64_and_path() {
+    let mut parser = ZMLParserWrapper::new();
+    let module = parser.parse(UPLOAD_MODULE).unwrap();
+    let method = &module.methods["uploadAttachment"];
+
+    let fields = build_multipart_fields_zml(&json!({ "attachment": "aGVsbG8=", "title": "log" }), method, None).unwrap();
+    assert!(fields.contains(&MultipartField::File {
+        name: "attachment".to_string(),
+        file_name: "attachment".to_string(),
+        content_type: None,
+        content: b"hello".to_vec(),
+    }));
+    assert!(fields.contains(&MultipartField::Text { name: "title".to_string(), value: "log".to_string() }));
+
+    let fields = build_multipart_fields_zml(&json!({ "attachment": "data:text/plain;base64,aGk=" }), method, None).unwrap();
+    assert_eq!(
+        fields[0],
+        MultipartField::File {
+            name: "attachment".to_string(),
+            file_name: "attachment".to_string(),
+            content_type: Some("text/plain".to_string()),
+            content: b"hi".to_vec(),
+        }
+    );
+
+    let dir = tempfile::tempdir().unwrap();
+    let mut file = tempfile::NamedTempFile::new_in(dir.path()).unwrap();
+    file.write_all(b"from disk").unwrap();
+    let path = file.path().to_string_lossy().to_string();
+    let fields = build_multipart_fields_zml(&json!({ "attachment": path }), method, Some(dir.path())).unwrap();
+    match &fields[0] {
+        MultipartField::File { content, .. } => assert_eq!(content, b"from disk"),
+        other => panic!("Expected file part, got {:?}", other),
+    }
+
+    assert!(build_multipart_fields_zml(&json!({ "attachment": "not base64!" }), method, None).is_err());
+}
+
+#[test]
+fn test_multipart_fields_only_read_files_below_upload_root() {
+    let mut parser = ZMLParserWrapper::new();
+    let module = parser.parse(UPLOAD_MODULE).unwrap();
+    let method = &module.methods["uploadAttachment"];
+
+    let outside = tempfile::tempdir().unwrap();
+    let secret = outside.path().join("secrets.json");
+    std::fs::write(&secret, "{}").unwrap();
+    let root = tempfile::tempdir().unwrap();
+    let uploads = root.path().join("uploads");
+    std::fs::create_dir(&uploads).unwrap();
+
+    // Without an upload root no local file is read
+    let path = secret.to_string_lossy().to_string();
+    assert!(build_multipart_fields_zml(&json!({ "attachment": path }), method, None).is_err());
+
+    // Absolute paths and `..` outside the root are rejected
+    assert!(build_multipart_fields_zml(&json!({ "attachment": path }), method, Some(uploads.as_path())).is_err());
+    std::fs::write(root.path().join("token_cache.key"), "key").unwrap();
+    let climb = json!({ "attachment": "../token_cache.key" });
+    assert!(build_multipart_fields_zml(&climb, method, Some(uploads.as_path())).is_err());
+
+    std::fs::write(uploads.join("report.txt"), "report").unwrap();
+    let fields = build_multipart_fields_zml(&json!({ "attachment": "report.txt" }), method, Some(uploads.as_path())).unwrap();
+    match &fields[0] {
+        MultipartField::File { file_name, content, .. } => {
+            assert_eq!(file_name, "report.txt");
+            assert_eq!(content, b"report");
+        }
+        other => panic!("Expected file part, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_multipart_fields_from_mcp_resources() {
+    let mut parser = ZMLParserWrapper::new();
+    let module = parser.parse(UPLOAD_MODULE).unwrap();
+    let method = &module.methods["uploadAttachment"];
+
+    let embedded = json!({
+        "type": "resource",
+        "resource": { "uri": "file:///logs/app.log?v=2", "mimeType": "text/plain", "blob": "aGVsbG8=" }
+    });
+    let fields = build_multipart_fields_zml(&json!({ "attachment": embedded }), method, None).unwrap();
+    assert_eq!(
+        fields[0],
+        MultipartField::File {
+            name: "attachment".to_string(),
+            file_name: "app.log".to_string(),
+            content_type: Some("text/plain".to_string()),
+            content: b"hello".to_vec(),
+        }
+    );
+
+    let contents = json!({ "uri": "notes://today", "text": "remember" });
+    let fields = build_multipart_fields_zml(&json!({ "attachment": contents }), method, None).unwrap();
+    match &fields[0] {
+        MultipartField::File { file_name, content_type, content, .. } => {
+            assert_eq!(file_name, "today");
+            assert_eq!(content_type, &None);
+            assert_eq!(content, b"remember");
+        }
+        other => panic!("Expected file part, got {:?}", other),
+    }
+
+    // Links are read by the service before the body is built
+    let link = json!({ "type": "resource_link", "uri": "bugs/7" });
+    assert!(build_multipart_fields_zml(&json!({ "attachment": link }), method, None).is_err());
+    assert!(build_multipart_fields_zml(&json!({ "attachment": { "uri": "empty://" } }), method, None).is_err());
+    assert!(build_multipart_fields_zml(&json!({ "attachment": 42 }), method, None).is_err());
+}
@@ -553,4 +553,42 @@ module SpecialEnumModule {
     let ct_values = content_type["values"].as_object().expect("Enum should contain values");
     assert_eq!(ct_values["APPLICATION_JSON"]["value"], "application/json");
     assert_eq!(ct_values["TEXT_HTML"]["value"], "text/html");
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_zml_examples_in_tool_schema() {
+    use mcp_any_rest::services::dynamic_service::build_input_schema_zml;
+    use mcp_any_rest::zml::ZMLParserWrapper;
+
+    let source = r#"
+module SearchModule {
+    type Filter {
+        status: string example: ["open", "closed"]
+        example: string?
+    }
+
+    method search {
+        http_method: GET
+        uri: "/search"
+        params {
+            keyword: string example: "login crash"
+            limit: integer? = 20 example: 50
+            filter: Filter?
+        }
+        response: any
+    }
+}
+"#;
+
+    let json = process_zml(source).expect("ZML with examples should compile");
+    assert_eq!(json["methods"]["search"]["params"]["keyword"]["examples"][0], "login crash");
+    assert_eq!(json["types"]["Filter"]["properties"]["status"]["examples"][1], "closed");
+    assert!(json["types"]["Filter"]["properties"].get("example").is_some());
+
+    let mut parser = ZMLParserWrapper::new();
+    let module = parser.parse(source).unwrap();
+    let schema = build_input_schema_zml(&module.methods["search"], &module, None);
+    assert_eq!(schema["properties"]["keyword"]["examples"][0], "login crash");
+    assert_eq!(schema["properties"]["limit"]["examples"][0], 50);
+    assert_eq!(schema["properties"]["filter"]["properties"]["status"]["examples"][0], "open");
+}
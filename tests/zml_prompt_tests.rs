@@ -0,0 +1,54 @@
+// ZML prompt test suite
+use mcp_any_rest::services::dynamic_service::render_prompt_zml;
+use mcp_any_rest::zml::{process_zml, ZMLParserWrapper};
+use serde_json::json;
+
+const PROMPT_MODULE: &str = r#"
+module BugModule {
+    prompt triage_bug {
+        description: "Triage a bug report"
+        arguments {
+            bug_id: integer
+            severity: string? = "normal"
+        }
+        template: "Review bug {{bug_id}} with {{ severity }} severity."
+    }
+}
+"#;
+
+#[test]
+fn test_prompt_compiled_to_json() {
+    let json = process_zml(PROMPT_MODULE).expect("Prompt module should compile");
+    let prompt = &json["prompts"]["triage_bug"];
+    assert_eq!(prompt["description"], "Triage a bug report");
+    assert_eq!(prompt["arguments"][0]["name"], "bug_id");
+    assert_eq!(prompt["arguments"][0]["required"], true);
+    assert_eq!(prompt["arguments"][1]["name"], "severity");
+    assert_eq!(prompt["arguments"][1]["required"], false);
+}
+
+#[test]
+fn test_prompt_rejects_undeclared_placeholder() {
+    let source = r#"
+module BugModule {
+    prompt broken {
+        template: "Hello {{missing}}"
+    }
+}
+"#;
+    assert!(process_zml(source).is_err(), "Undeclared placeholder should be rejected");
+}
+
+#[test]
+fn test_prompt_rendering() {
+    let mut parser = ZMLParserWrapper::new();
+    let module = parser.parse(PROMPT_MODULE).expect("Prompt module should parse");
+    let prompt = &module.prompts["triage_bug"];
+
+    let args = json!({ "bug_id": 42 });
+    let result = render_prompt_zml(prompt, args.as_object()).expect("Prompt should render");
+    let rendered = serde_json::to_value(&result.messages[0]).unwrap();
+    assert_eq!(rendered["content"]["text"], "Review bug 42 with normal severity.");
+
+    assert!(render_prompt_zml(prompt, None).is_err(), "Missing required argument should fail");
+}
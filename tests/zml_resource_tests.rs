@@ -0,0 +1,82 @@
+// ZML resource template test suite
+use mcp_any_rest::services::dynamic_service::{expand_resource_uri_zml, match_resource_zml};
+use mcp_any_rest::zml::{process_zml, ZMLParserWrapper};
+
+const RESOURCE_MODULE: &str = r#"
+module ProjectModule {
+    resource projects {
+        uri: "/projects"
+        type: collection
+    }
+
+    resource project_report {
+        uri: "/projects/{id}/report"
+        description: "Project report"
+    }
+
+    resource current_project {
+        uri: "/projects/current"
+        description: "Project of the current user"
+    }
+
+    resource project {
+        uri: "/projects/{id}"
+        description: "Project"
+    }
+}
+"#;
+
+#[test]
+fn test_resource_template_compiled() {
+    let json = process_zml(RESOURCE_MODULE).expect("Resource module should compile");
+    let report = &json["resources"]["project_report"];
+    assert_eq!(report["uri_template"], true);
+    assert_eq!(report["uri_variables"][0], "id");
+    assert!(json["resources"]["projects"].get("uri_template").is_none());
+}
+
+#[test]
+fn test_resource_template_matching() {
+    let mut parser = ZMLParserWrapper::new();
+    let module = parser.parse(RESOURCE_MODULE).expect("Resource module should parse");
+
+    let (resource, variables) = match_resource_zml("projects/42/report", &module).expect("Template should match");
+    assert_eq!(resource.name, "project_report");
+    assert_eq!(variables.get("id").map(String::as_str), Some("42"));
+
+    let (resource, variables) = match_resource_zml("/projects", &module).expect("Static resource should match");
+    assert_eq!(resource.name, "projects");
+    assert!(variables.is_empty());
+
+    assert!(match_resource_zml("projects/42/unknown", &module).is_none());
+}
+
+#[test]
+fn test_fixed_resources_win_over_templates() {
+    let mut parser = ZMLParserWrapper::new();
+    let module = parser.parse(RESOURCE_MODULE).expect("Resource module should parse");
+
+    for _ in 0..10 {
+        let (resource, _) = match_resource_zml("projects/current", &module).expect("Static resource should match");
+        assert_eq!(resource.name, "current_project");
+    }
+    let (resource, variables) = match_resource_zml("projects/7", &module).expect("Template should match");
+    assert_eq!(resource.name, "project");
+    assert_eq!(variables.get("id").map(String::as_str), Some("7"));
+}
+
+#[test]
+fn test_template_values_are_encoded_path_segments() {
+    let mut parser = ZMLParserWrapper::new();
+    let module = parser.parse(RESOURCE_MODULE).expect("Resource module should parse");
+
+    let (resource, variables) = match_resource_zml("projects/a b%2Fc/report", &module).unwrap();
+    assert_eq!(expand_resource_uri_zml(resource, &variables).unwrap(), "/projects/a%20b%2Fc/report");
+
+    for traversal in ["..", ".", "%2E%2E", "%2e"] {
+        let (resource, variables) = match_resource_zml(&format!("projects/{}/report", traversal), &module).unwrap();
+        assert!(expand_resource_uri_zml(resource, &variables).is_err(), "{}", traversal);
+    }
+    let (resource, variables) = match_resource_zml("projects/%zz/report", &module).unwrap();
+    assert!(expand_resource_uri_zml(resource, &variables).is_err());
+}
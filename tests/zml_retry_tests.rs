@@ -0,0 +1,145 @@
+// ZML timeout, retry policy and cache TTL test suite
+use mcp_any_rest::config::module::RetryConfig;
+use mcp_any_rest::services::dynamic_service::build_request_policy_zml;
+use mcp_any_rest::zml::{process_zml, BackoffStrategy, ZMLParserWrapper};
+use std::time::Duration;
+
+const RETRY_MODULE: &str = r#"
+module ReportModule {
+    method generateReport {
+        http_method: POST
+        uri: "/reports"
+        timeout: 30s
+        retry { attempts: 3, backoff: exponential, on: [502, 503] }
+        response: any
+    }
+
+    method quickPing {
+        http_method: GET
+        uri: "/ping"
+        timeout: 250ms
+        response: any
+    }
+}
+"#;
+
+#[test]
+fn test_timeout_and_retry_parsed() {
+    let mut parser = ZMLParserWrapper::new();
+    let module = parser.parse(RETRY_MODULE).expect("Retry module should parse");
+
+    let report = &module.methods["generateReport"];
+    assert_eq!(report.timeout_ms, Some(30_000));
+    let retry = report.retry.as_ref().expect("Retry policy should be parsed");
+    assert_eq!(retry.attempts, 3);
+    assert_eq!(retry.backoff, BackoffStrategy::Exponential);
+    assert_eq!(retry.on, vec![502, 503]);
+
+    let ping = &module.methods["quickPing"];
+    assert_eq!(ping.timeout_ms, Some(250));
+    assert!(ping.retry.is_none());
+}
+
+#[test]
+fn test_timeout_and_retry_compiled_to_json() {
+    let json = process_zml(RETRY_MODULE).expect("Retry module should compile");
+    let report = &json["methods"]["generateReport"];
+    assert_eq!(report["timeout_ms"], 30_000);
+    assert_eq!(report["retry"]["attempts"], 3);
+    assert_eq!(report["retry"]["backoff"], "exponential");
+    assert_eq!(report["retry"]["on"][1], 503);
+}
+
+#[test]
+fn test_cache_ttl_parsed_and_compiled() {
+    let source = r#"
+module CatalogModule {
+    method getProduct {
+        http_method: GET
+        uri: "/products/{id}"
+        cache_ttl: 5m
+        params {
+            id: integer
+        }
+        response: any
+    }
+}
+"#;
+    let mut parser = ZMLParserWrapper::new();
+    let module = parser.parse(source).expect("Cache TTL module should parse");
+    assert_eq!(module.methods["getProduct"].cache_ttl_ms, Some(300_000));
+    assert_eq!(module.methods["getProduct"].timeout_ms, None);
+
+    let json = process_zml(source).expect("Cache TTL module should compile");
+    assert_eq!(json["methods"]["getProduct"]["cache_ttl_ms"], 300_000);
+
+    let zero = source.replace("cache_ttl: 5m", "cache_ttl: 0s");
+    assert!(parser.parse(&zero).is_err(), "A zero cache TTL should be rejected");
+
+    // Durations whose milliseconds do not fit in 64 bits are rejected rather than wrapped
+    let overflow = source.replace("cache_ttl: 5m", "timeout: 307445734561825861m");
+    let error = parser.parse(&overflow).expect_err("An overflowing timeout should be rejected");
+    assert!(error.to_string().contains("too long"), "{}", error);
+}
+
+#[test]
+fn test_unknown_backoff_rejected() {
+    let source = r#"
+module BadModule {
+    method ping {
+        http_method: GET
+        uri: "/ping"
+        retry { attempts: 2, backoff: random }
+        response: any
+    }
+}
+"#;
+    let mut parser = ZMLParserWrapper::new();
+    assert!(parser.parse(source).is_err(), "Unknown backoff should be rejected");
+}
+
+#[test]
+fn test_request_policy_from_method() {
+    let mut parser = ZMLParserWrapper::new();
+    let module = parser.parse(RETRY_MODULE).unwrap();
+
+    let policy = build_request_policy_zml(&module.methods["generateReport"], None, None);
+    assert_eq!(policy.timeout, Some(Duration::from_secs(30)));
+    assert_eq!(policy.retry_delays, vec![Duration::from_millis(500), Duration::from_millis(1000)]);
+    assert!(policy.should_retry_status(503));
+    assert!(!policy.should_retry_status(500));
+
+    let policy = build_request_policy_zml(&module.methods["quickPing"], None, None);
+    assert_eq!(policy.timeout, Some(Duration::from_millis(250)));
+    assert!(policy.retry_delays.is_empty());
+}
+
+#[test]
+fn test_configured_retry_overrides_the_declaration() {
+    let mut parser = ZMLParserWrapper::new();
+    let module = parser.parse(RETRY_MODULE).unwrap();
+    let backend = RetryConfig { attempts: 2, ..RetryConfig::default() };
+    let configured = RetryConfig {
+        attempts: 5,
+        base_delay_ms: 1_000,
+        max_delay_ms: 3_000,
+        max_elapsed_ms: Some(10_000),
+        ..RetryConfig::default()
+    };
+
+    let policy = build_request_policy_zml(&module.methods["generateReport"], Some(&configured), Some(&backend));
+    assert_eq!(
+        policy.retry_delays,
+        [1_000, 2_000, 3_000, 3_000].map(Duration::from_millis).to_vec()
+    );
+    assert!(policy.jitter);
+    assert_eq!(policy.max_retry_elapsed, Some(Duration::from_secs(10)));
+    assert!(!policy.retry_non_idempotent);
+    assert!(policy.should_retry_status(429) && policy.should_retry_status(500));
+
+    // The backend's retries apply to methods declaring none
+    let policy = build_request_policy_zml(&module.methods["generateReport"], None, Some(&backend));
+    assert_eq!(policy.retry_delays.len(), 2);
+    let policy = build_request_policy_zml(&module.methods["quickPing"], None, Some(&backend));
+    assert_eq!(policy.retry_delays, vec![Duration::from_millis(500)]);
+}
@@ -0,0 +1,122 @@
+// ZML custom scalar test suite
+use mcp_any_rest::services::dynamic_service::{decode_response_zml, encode_params_zml};
+use mcp_any_rest::zml::{process_zml, ScalarEncoding, ZMLParserWrapper};
+use serde_json::json;
+use std::collections::HashMap;
+
+const SCALAR_MODULE: &str = r#"
+module BillingModule {
+    scalar Money { base: string, pattern: "^\\d+\\.\\d{2}$" }
+    scalar Timestamp { base: integer, encode: unix_seconds }
+
+    method createInvoice {
+        http_method: POST
+        uri: "/invoices"
+        params {
+            amount: Money
+            due: Timestamp
+        }
+        response: Timestamp
+    }
+}
+"#;
+
+#[test]
+fn test_scalar_definition_parsed() {
+    let mut parser = ZMLParserWrapper::new();
+    let module = parser.parse(SCALAR_MODULE).expect("Scalar module should parse");
+
+    let money = &module.scalars["Money"];
+    assert_eq!(money.pattern.as_deref(), Some(r"^\d+\.\d{2}$"));
+    assert!(money.encode.is_none());
+
+    let timestamp = &module.scalars["Timestamp"];
+    assert_eq!(timestamp.encode, Some(ScalarEncoding::UnixSeconds));
+}
+
+#[test]
+fn test_scalar_compiled_to_json() {
+    let json = process_zml(SCALAR_MODULE).expect("Scalar module should compile");
+    let scalars = json["scalars"].as_object().expect("Should contain scalars field");
+    assert_eq!(scalars["Money"]["base"], "string");
+    assert_eq!(scalars["Timestamp"]["encode"], "unix_seconds");
+    assert_eq!(json["methods"]["createInvoice"]["params"]["due"]["type"], "Timestamp");
+}
+
+#[test]
+fn test_scalar_encoding_requires_matching_base() {
+    let source = r#"
+module BadModule {
+    scalar Timestamp { base: string, encode: unix_seconds }
+}
+"#;
+    assert!(process_zml(source).is_err(), "unix_seconds on a string base should be rejected");
+}
+
+#[test]
+fn test_scalar_encode_and_decode() {
+    let mut parser = ZMLParserWrapper::new();
+    let module = parser.parse(SCALAR_MODULE).expect("Scalar module should parse");
+    let method = &module.methods["createInvoice"];
+
+    let mut params = HashMap::new();
+    params.insert("amount".to_string(), json!("12.50"));
+    params.insert("due".to_string(), json!("2024-01-01T00:00:00Z"));
+    let encoded = encode_params_zml(&params, &module, method).expect("Params should encode");
+    assert_eq!(encoded["due"], json!(1704067200));
+    assert_eq!(encoded["amount"], json!("12.50"));
+
+    params.insert("amount".to_string(), json!("12.5"));
+    assert!(encode_params_zml(&params, &module, method).is_err(), "Pattern mismatch should fail");
+
+    let decoded = decode_response_zml(&json!(1704067200), method, &module);
+    assert_eq!(decoded, json!("2024-01-01T00:00:00+00:00"));
+}
+
+#[test]
+fn test_scalar_invalid_pattern_is_a_parse_error() {
+    let source = r#"
+module BadModule {
+    scalar Code { base: string, pattern: "^(unclosed$" }
+}
+"#;
+    let err = ZMLParserWrapper::new().parse(source).expect_err("An invalid pattern should be rejected");
+    assert!(err.to_string().contains("invalid pattern"), "unexpected error: {}", err);
+}
+
+#[test]
+fn test_rfc3339_scalar_validates_and_normalizes_datetimes() {
+    let source = r#"
+module EventModule {
+    scalar When { base: string, encode: rfc3339 }
+
+    method createEvent {
+        http_method: POST
+        uri: "/events"
+        params {
+            at: When
+        }
+        response: When
+    }
+}
+"#;
+    let module = ZMLParserWrapper::new().parse(source).expect("Scalar module should parse");
+    let method = &module.methods["createEvent"];
+
+    let mut params = HashMap::new();
+    params.insert("at".to_string(), json!("2024-01-01T08:00:00+08:00"));
+    let encoded = encode_params_zml(&params, &module, method).expect("Datetimes should encode");
+    assert_eq!(encoded["at"], json!("2024-01-01T00:00:00+00:00"));
+
+    params.insert("at".to_string(), json!(1704067200));
+    let encoded = encode_params_zml(&params, &module, method).expect("Unix seconds should encode");
+    assert_eq!(encoded["at"], json!("2024-01-01T00:00:00+00:00"));
+
+    params.insert("at".to_string(), json!("next tuesday"));
+    assert!(encode_params_zml(&params, &module, method).is_err(), "Non-datetimes should fail");
+
+    let decoded = decode_response_zml(&json!("2024-01-01T00:00:00Z"), method, &module);
+    assert_eq!(decoded, json!("2024-01-01T00:00:00+00:00"));
+    let decoded = decode_response_zml(&json!("not a date"), method, &module);
+    assert_eq!(decoded, json!("not a date"));
+}
@@ -0,0 +1,59 @@
+// ZML source map test suite
+use mcp_any_rest::services::dynamic_service::validate_parameters_zml;
+use mcp_any_rest::zml::{process_zml, ZMLParserWrapper};
+use serde_json::json;
+use std::collections::HashMap;
+
+const SOURCE: &str = r#"module UserModule {
+    type User {
+        id: integer
+        profile: object {
+            name: string
+        }
+    }
+
+    method getUser {
+        http_method: GET
+        uri: "/users/{id}"
+        params {
+            id: integer
+        }
+        response: User
+    }
+}
+"#;
+
+#[test]
+fn test_source_map_records_locations() {
+    let mut parser = ZMLParserWrapper::new();
+    let module = parser.parse_with_file(SOURCE, "users.zml").expect("Module should parse");
+
+    let method = module.source_location("methods.getUser").expect("Method location");
+    assert_eq!((method.line, method.column), (9, 5));
+    assert_eq!(method.file.as_deref(), Some("users.zml"));
+
+    let param = module.source_location("methods.getUser.params.id").expect("Param location");
+    assert_eq!((param.line, param.column), (13, 13));
+
+    assert_eq!(module.source_location("types.User.properties.id").unwrap().line, 3);
+    assert!(module.source_location("types.User.properties.name").is_none());
+}
+
+#[test]
+fn test_source_map_compiled_to_json() {
+    let json = process_zml(SOURCE).expect("Module should compile");
+    assert_eq!(json["source_map"]["methods.getUser.params.id"]["line"], 13);
+    assert_eq!(json["source_map"]["types.User"]["line"], 2);
+}
+
+#[test]
+fn test_validation_error_includes_location() {
+    let mut parser = ZMLParserWrapper::new();
+    let module = parser.parse_with_file(SOURCE, "users.zml").expect("Module should parse");
+    let method = &module.methods["getUser"];
+
+    let mut params = HashMap::new();
+    params.insert("id".to_string(), json!("not-a-number"));
+    let err = validate_parameters_zml(&params, &module, method, None).unwrap_err();
+    assert!(err.message.contains("users.zml:13:13"), "unexpected message: {}", err.message);
+}
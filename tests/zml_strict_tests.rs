@@ -0,0 +1,79 @@
+// ZML strict parse mode test suite
+use mcp_any_rest::zml::{ParseOptions, ZMLParserWrapper, ZMLProcessor};
+
+const LOOSE_MODULE: &str = r#"
+module LooseModule {
+    version: "1.0.0"
+    owner: "platform-team"
+
+    resource Users {
+        uri: "/users"
+        cache: true
+    }
+}
+"#;
+
+#[test]
+fn test_default_mode_ignores_unknown_properties() {
+    let mut parser = ZMLParserWrapper::new();
+    let module = parser.parse(LOOSE_MODULE).expect("Lenient mode should ignore unknown properties");
+    assert_eq!(module.version.as_deref(), Some("1.0.0"));
+    assert_eq!(module.resources["Users"].uri, "/users");
+}
+
+#[test]
+fn test_strict_mode_rejects_unknown_module_property() {
+    let mut parser = ZMLParserWrapper::with_options(ParseOptions { strict: true });
+    let err = parser.parse(LOOSE_MODULE).expect_err("Strict mode should reject unknown properties");
+    assert!(err.to_string().contains("owner"), "unexpected error: {}", err);
+}
+
+#[test]
+fn test_strict_mode_rejects_invalid_property_value() {
+    let source = r#"
+module TypedModule {
+    enabled: "yes"
+}
+"#;
+    let mut parser = ZMLParserWrapper::with_options(ParseOptions { strict: true });
+    assert!(parser.parse(source).is_err(), "A string value for `enabled` should be rejected");
+
+    let mut lenient = ZMLParserWrapper::new();
+    assert!(lenient.parse(source).unwrap().enabled.is_none());
+}
+
+#[test]
+fn test_strict_mode_rejects_unknown_resource_property() {
+    let source = r#"
+module ResourceModule {
+    resource Users {
+        uri: "/users"
+        cache: true
+    }
+}
+"#;
+    let mut processor = ZMLProcessor::with_options(ParseOptions { strict: true });
+    let err = processor.process(source).expect_err("Unknown resource property should be rejected");
+    assert!(err.to_string().contains("cache"), "unexpected error: {}", err);
+}
+
+#[test]
+fn test_strict_mode_accepts_known_constructs() {
+    let source = r#"
+module CleanModule {
+    version: "1.0.0"
+    description: "Clean"
+    enabled: true
+    access_level: internal
+    category: "demo"
+
+    method ping {
+        http_method: GET
+        uri: "/ping"
+        response: any
+    }
+}
+"#;
+    let mut parser = ZMLParserWrapper::with_options(ParseOptions { strict: true });
+    assert!(parser.parse(source).is_ok());
+}